@@ -15,23 +15,16 @@ fn main() -> anyhow::Result<()> {
     let boxes = mp4box::get_boxes(&mut file, size, /*decode=*/ false)?;
     println!("Top-level boxes: {}", boxes.len());
 
-    // Example: print types of all top-level boxes
-    let media_info = boxes.iter().find(|b| b.typ == "moov").and_then(|moov_box| {
-        moov_box.children.as_ref().and_then(|children| {
-            children.iter().find(|b| {
-                b.typ == "trak"
-                    && b.children
-                        .as_ref()
-                        .is_some_and(|c| c.iter().any(|cb| cb.typ == "mdia"))
-            })
-        })
-    });
+    // Example: print the children of the first track with media
+    let media_info = boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .flat_map(|moov| moov.descendants_of_type("trak"))
+        .find(|trak| trak.child("mdia").is_some());
     if let Some(trak_box) = media_info {
         println!("Found a 'trak' box inside 'moov':");
-        if let Some(children) = &trak_box.children {
-            for child in children {
-                println!(" - Child box type: {}", child.typ);
-            }
+        for child in trak_box.children.iter().flatten() {
+            println!(" - Child box type: {}", child.typ);
         }
     } else {
         println!("No 'trak' box found inside 'moov'.");
@@ -1,5 +1,7 @@
+use mp4box::walk::{Children, walk};
 use mp4box::{BoxValue, StructuredData, get_boxes};
 use std::fs::File;
+use std::ops::ControlFlow;
 
 fn main() -> anyhow::Result<()> {
     // Check if a file path is provided
@@ -17,7 +19,7 @@ fn main() -> anyhow::Result<()> {
     let boxes = get_boxes(&mut file, size, true)?;
 
     println!("Analyzing sample tables in: {}", path);
-    analyze_sample_tables(&boxes, 0);
+    analyze_sample_tables(&boxes);
 
     // Also test the direct parsing example
     println!("\nTesting direct parsing example:");
@@ -26,72 +28,71 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn analyze_sample_tables(boxes: &[mp4box::Box], depth: usize) {
-    let indent = "  ".repeat(depth);
-
-    for box_info in boxes {
-        // Look for sample table boxes
-        if let Some(decoded) = &box_info.decoded {
-            match box_info.typ.as_str() {
-                "stts" => {
-                    println!("{}📊 Decoding Time-to-Sample Box (stts):", indent);
-                    if decoded.starts_with("structured:") {
-                        println!("{}   Contains structured sample timing data", indent);
-                        // In practice, you would parse the structured data here
-                        // For now we show it's working with structured output
+fn analyze_sample_tables(boxes: &[mp4box::Box]) {
+    let _ = walk(
+        boxes,
+        &mut |box_info: &mp4box::Box, ancestors: &[&mp4box::Box]| {
+            let indent = "  ".repeat(ancestors.len());
+            // Look for sample table boxes
+            if let Some(decoded) = &box_info.decoded {
+                match box_info.typ.as_str() {
+                    "stts" => {
+                        println!("{}📊 Decoding Time-to-Sample Box (stts):", indent);
+                        if decoded.starts_with("structured:") {
+                            println!("{}   Contains structured sample timing data", indent);
+                            // In practice, you would parse the structured data here
+                            // For now we show it's working with structured output
+                        }
                     }
-                }
-                "stsc" => {
-                    println!("{}🗂️  Sample-to-Chunk Box (stsc):", indent);
-                    if decoded.starts_with("structured:") {
-                        println!("{}   Contains structured chunk mapping data", indent);
+                    "stsc" => {
+                        println!("{}🗂️  Sample-to-Chunk Box (stsc):", indent);
+                        if decoded.starts_with("structured:") {
+                            println!("{}   Contains structured chunk mapping data", indent);
+                        }
                     }
-                }
-                "stsz" => {
-                    println!("{}📏 Sample Size Box (stsz):", indent);
-                    if decoded.starts_with("structured:") {
-                        println!("{}   Contains structured sample size data", indent);
+                    "stsz" => {
+                        println!("{}📏 Sample Size Box (stsz):", indent);
+                        if decoded.starts_with("structured:") {
+                            println!("{}   Contains structured sample size data", indent);
+                        }
                     }
-                }
-                "stco" => {
-                    println!("{}📍 Chunk Offset Box (stco):", indent);
-                    if decoded.starts_with("structured:") {
-                        println!("{}   Contains structured chunk offset data", indent);
+                    "stco" => {
+                        println!("{}📍 Chunk Offset Box (stco):", indent);
+                        if decoded.starts_with("structured:") {
+                            println!("{}   Contains structured chunk offset data", indent);
+                        }
                     }
-                }
-                "co64" => {
-                    println!("{}📍 64-bit Chunk Offset Box (co64):", indent);
-                    if decoded.starts_with("structured:") {
-                        println!("{}   Contains structured 64-bit chunk offset data", indent);
+                    "co64" => {
+                        println!("{}📍 64-bit Chunk Offset Box (co64):", indent);
+                        if decoded.starts_with("structured:") {
+                            println!("{}   Contains structured 64-bit chunk offset data", indent);
+                        }
                     }
-                }
-                "stss" => {
-                    println!("{}🎯 Sync Sample Box (stss):", indent);
-                    if decoded.starts_with("structured:") {
-                        println!("{}   Contains structured keyframe data", indent);
+                    "stss" => {
+                        println!("{}🎯 Sync Sample Box (stss):", indent);
+                        if decoded.starts_with("structured:") {
+                            println!("{}   Contains structured keyframe data", indent);
+                        }
                     }
-                }
-                "ctts" => {
-                    println!("{}⏰ Composition Time-to-Sample Box (ctts):", indent);
-                    if decoded.starts_with("structured:") {
-                        println!("{}   Contains structured composition offset data", indent);
+                    "ctts" => {
+                        println!("{}⏰ Composition Time-to-Sample Box (ctts):", indent);
+                        if decoded.starts_with("structured:") {
+                            println!("{}   Contains structured composition offset data", indent);
+                        }
                     }
-                }
-                "stsd" => {
-                    println!("{}🎬 Sample Description Box (stsd):", indent);
-                    if decoded.starts_with("structured:") {
-                        println!("{}   Contains structured codec information", indent);
+                    "stsd" => {
+                        println!("{}🎬 Sample Description Box (stsd):", indent);
+                        if decoded.starts_with("structured:") {
+                            println!("{}   Contains structured codec information", indent);
+                        }
                     }
+                    _ => {}
                 }
-                _ => {}
             }
-        }
 
-        // Recurse into children
-        if let Some(children) = &box_info.children {
-            analyze_sample_tables(children, depth + 1);
-        }
-    }
+            ControlFlow::<(), _>::Continue(Children::Visit)
+        },
+    );
 }
 
 /// Example of how you would access structured data directly from the registry
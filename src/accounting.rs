@@ -0,0 +1,186 @@
+//! Breakdown of file bytes by box type and by what the bytes are for.
+
+use serde::Serialize;
+
+/// What a run of file bytes is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteCategory {
+    /// mdat and idat payloads
+    Media,
+    /// Every other box: moov, moof, ftyp, sidx, meta, ...
+    Metadata,
+    /// free and skip boxes at any depth
+    FreeSpace,
+    /// uuid boxes, typically vendor extensions or padding
+    Vendor,
+    /// Bytes not covered by any top-level box
+    Unaccounted,
+}
+
+impl ByteCategory {
+    fn of(typ: &str) -> Self {
+        match typ {
+            "mdat" | "idat" => ByteCategory::Media,
+            "free" | "skip" => ByteCategory::FreeSpace,
+            "uuid" => ByteCategory::Vendor,
+            _ => ByteCategory::Metadata,
+        }
+    }
+}
+
+/// Total size of the top-level boxes of one type.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TypeBytes {
+    pub typ: String,
+    pub count: usize,
+    pub bytes: u64,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryBytes {
+    pub category: ByteCategory,
+    pub bytes: u64,
+    pub percent: f64,
+}
+
+/// Result of [`size_report`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SizeReport {
+    pub file_size: u64,
+    /// Top-level box types, largest first
+    pub types: Vec<TypeBytes>,
+    /// Every category, in declaration order, including empty ones
+    pub categories: Vec<CategoryBytes>,
+}
+
+impl SizeReport {
+    pub fn bytes(&self, category: ByteCategory) -> u64 {
+        self.categories
+            .iter()
+            .find(|c| c.category == category)
+            .map_or(0, |c| c.bytes)
+    }
+}
+
+/// Account for every byte of a file of `file_size` bytes.
+///
+/// `types` counts whole top-level boxes, so a free box nested in moov is
+/// part of the moov total there. `categories` looks inside containers and
+/// files each box's bytes under its own type.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{accounting::{ByteCategory, size_report}, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let report = size_report(&get_boxes(&mut file, size, false)?, size);
+/// let overhead = report.file_size - report.bytes(ByteCategory::Media);
+/// println!("{} bytes of overhead", overhead);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn size_report(boxes: &[crate::Box], file_size: u64) -> SizeReport {
+    let percent = |bytes: u64| {
+        if file_size == 0 {
+            0.0
+        } else {
+            bytes as f64 * 100.0 / file_size as f64
+        }
+    };
+
+    let mut types: Vec<TypeBytes> = Vec::new();
+    for b in boxes {
+        match types.iter_mut().find(|t| t.typ == b.typ) {
+            Some(t) => {
+                t.count += 1;
+                t.bytes += b.size;
+            }
+            None => types.push(TypeBytes {
+                typ: b.typ.clone(),
+                count: 1,
+                bytes: b.size,
+                percent: 0.0,
+            }),
+        }
+    }
+    for t in &mut types {
+        t.percent = percent(t.bytes);
+    }
+    types.sort_by_key(|t| std::cmp::Reverse(t.bytes));
+
+    let mut totals = [0u64; 4];
+    for b in boxes {
+        add_category_bytes(b, &mut totals);
+    }
+    let covered: u64 = totals.iter().sum();
+    let categories = [
+        ByteCategory::Media,
+        ByteCategory::Metadata,
+        ByteCategory::FreeSpace,
+        ByteCategory::Vendor,
+    ]
+    .into_iter()
+    .zip(totals)
+    .chain([(ByteCategory::Unaccounted, file_size.saturating_sub(covered))])
+    .map(|(category, bytes)| CategoryBytes {
+        category,
+        bytes,
+        percent: percent(bytes),
+    })
+    .collect();
+
+    SizeReport {
+        file_size,
+        types,
+        categories,
+    }
+}
+
+/// Add the bytes of `b` not covered by its children to its category, then
+/// recurse.
+fn add_category_bytes(b: &crate::Box, totals: &mut [u64; 4]) {
+    let children = b.children.as_deref().unwrap_or_default();
+    let nested: u64 = children.iter().map(|c| c.size).sum();
+    totals[ByteCategory::of(&b.typ) as usize] += b.size.saturating_sub(nested);
+    for c in children {
+        add_category_bytes(c, totals);
+    }
+}
+
+/// One entry of [`largest_boxes`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LargeBox {
+    /// Box chain as printed by [`crate::format_box_path`], e.g. `moov>trak[1]>udta`
+    pub path: String,
+    pub typ: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// The `n` largest boxes at any depth, largest first; ties keep file order.
+///
+/// Containers are ranked with their children included, so moov usually
+/// appears alongside its largest descendants.
+pub fn largest_boxes(boxes: &[crate::Box], n: usize) -> Vec<LargeBox> {
+    let mut all = Vec::new();
+    collect_boxes(boxes, "", &mut all);
+    all.sort_by_key(|b| std::cmp::Reverse(b.size));
+    all.truncate(n);
+    all
+}
+
+fn collect_boxes(siblings: &[crate::Box], parent: &str, out: &mut Vec<LargeBox>) {
+    for (i, b) in siblings.iter().enumerate() {
+        let path = crate::api::child_path(parent, siblings, i);
+        out.push(LargeBox {
+            path: path.clone(),
+            typ: b.typ.clone(),
+            offset: b.offset,
+            size: b.size,
+        });
+        collect_boxes(b.children.as_deref().unwrap_or_default(), &path, out);
+    }
+}
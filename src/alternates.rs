@@ -0,0 +1,134 @@
+use crate::registry::StructuredData;
+use serde::Serialize;
+
+/// A `kind` box attached to a track (udta/kind).
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackRole {
+    pub scheme_uri: String,
+    pub value: String,
+}
+
+/// A track as seen by the grouping report.
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupedTrack {
+    pub track_id: u32,
+    /// Handler 4CC from hdlr ("vide", "soun", "subt", ...)
+    pub handler_type: Option<String>,
+    /// Packed ISO 639-2/T code from mdhd
+    pub language: Option<String>,
+    pub roles: Vec<TrackRole>,
+}
+
+/// A set of tracks sharing the same non-zero `alternate_group` in tkhd.
+///
+/// Only one track of a group is meant to be played at a time (e.g. the
+/// English, French and commentary audio tracks of a movie).
+#[derive(Debug, Clone, Serialize)]
+pub struct AlternateGroup {
+    pub alternate_group: u16,
+    pub tracks: Vec<GroupedTrack>,
+}
+
+/// Result of [`track_groups`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackGroupReport {
+    /// Mutually exclusive alternatives, ordered by group number
+    pub alternates: Vec<AlternateGroup>,
+    /// Tracks with `alternate_group == 0`, meant to be presented together
+    pub complementary: Vec<GroupedTrack>,
+}
+
+/// Group the tracks of a decoded box tree by tkhd `alternate_group`.
+///
+/// Requires a tree produced with `decode = true` so that tkhd, mdhd, hdlr
+/// and kind boxes carry structured data.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, track_groups};
+/// use std::fs::File;
+///
+/// let mut file = File::open("multi_audio.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for group in track_groups(&boxes).alternates {
+///     let ids: Vec<_> = group.tracks.iter().map(|t| t.track_id).collect();
+///     println!("group {}: tracks {:?}", group.alternate_group, ids);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn track_groups(boxes: &[crate::Box]) -> TrackGroupReport {
+    let mut alternates: Vec<AlternateGroup> = Vec::new();
+    let mut complementary = Vec::new();
+
+    let traks = boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .filter_map(|moov| moov.children.as_ref())
+        .flatten()
+        .filter(|b| b.typ == "trak");
+
+    for trak in traks {
+        let Some(StructuredData::TrackHeader(tkhd)) =
+            trak.child("tkhd").and_then(|b| b.structured_data.as_ref())
+        else {
+            continue;
+        };
+
+        let mdia = trak.child("mdia");
+        let handler_type = match mdia
+            .and_then(|m| m.child("hdlr"))
+            .and_then(|b| b.structured_data.as_ref())
+        {
+            Some(StructuredData::HandlerReference(h)) => Some(h.handler_type.clone()),
+            _ => None,
+        };
+        let language = match mdia
+            .and_then(|m| m.child("mdhd"))
+            .and_then(|b| b.structured_data.as_ref())
+        {
+            Some(StructuredData::MediaHeader(m)) => Some(m.language.clone()),
+            _ => None,
+        };
+        let roles = trak
+            .child("udta")
+            .and_then(|u| u.children.as_ref())
+            .into_iter()
+            .flatten()
+            .filter_map(|b| match &b.structured_data {
+                Some(StructuredData::TrackKind(k)) => Some(TrackRole {
+                    scheme_uri: k.scheme_uri.clone(),
+                    value: k.value.clone(),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let track = GroupedTrack {
+            track_id: tkhd.track_id,
+            handler_type,
+            language,
+            roles,
+        };
+
+        if tkhd.alternate_group == 0 {
+            complementary.push(track);
+        } else if let Some(g) = alternates
+            .iter_mut()
+            .find(|g| g.alternate_group == tkhd.alternate_group)
+        {
+            g.tracks.push(track);
+        } else {
+            alternates.push(AlternateGroup {
+                alternate_group: tkhd.alternate_group,
+                tracks: vec![track],
+            });
+        }
+    }
+
+    alternates.sort_by_key(|g| g.alternate_group);
+    TrackGroupReport {
+        alternates,
+        complementary,
+    }
+}
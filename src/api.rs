@@ -1,10 +1,11 @@
 use crate::{
-    boxes::{BoxRef, NodeKind},
-    parser::read_box_header,
+    boxes::{BoxHeader, BoxRef, NodeKind},
+    known_boxes::KnownBox,
+    parser::{DEFAULT_MAX_DEPTH, ParseState, ParseWarning, SKIPPED, WarningKind, read_box_header},
+    peek::{MdatPeek, classify_payload},
     registry::{BoxValue, Registry, default_registry},
-    util::{hex_dump, read_slice},
+    util::{Prefetched, Window, hex_dump, read_slice, read_up_to},
 };
-use byteorder::ReadBytesExt;
 use serde::Serialize;
 use std::io::{Read, Seek, SeekFrom};
 
@@ -25,24 +26,46 @@ pub struct Box {
     /// Size of payload data (None for containers)
     pub payload_size: Option<u64>,
 
-    /// Four-character box type code (e.g., "ftyp", "moov")
+    /// Four-character box type code (e.g., "ftyp", "moov"); bytes that are
+    /// not printable show as '.'
     pub typ: String,
+    /// The raw type bytes as 8 hex digits (e.g. "6d6f6f76"), exact even when
+    /// `typ` is not
+    pub typ_hex: String,
     /// UUID for UUID boxes (16-byte hex string)
     pub uuid: Option<String>,
     /// Version field for FullBox types
     pub version: Option<u8>,
     /// Flags field for FullBox types  
     pub flags: Option<u32>,
-    /// Box classification: "leaf", "full", "container", or "unknown"
+    /// Box classification: "leaf", "full", "container", "unknown", or "gap"
+    /// (bytes skipped by recovery)
     pub kind: String,
     /// Human-readable box type name (e.g., "File Type Box")
     pub full_name: String,
-    /// Decoded box content if decode=true and decoder available
+    /// Decoded box content if decode=true and decoder available; for
+    /// structured data this is [`StructuredData::summary`](crate::registry::StructuredData::summary)
     pub decoded: Option<String>,
     /// Structured data if decode=true and structured decoder available
     pub structured_data: Option<crate::registry::StructuredData>,
     /// Child boxes for container types
     pub children: Option<Vec<Box>>,
+    /// Media bytes used by this moof or trak (decode=true only)
+    pub media_ranges: Option<Vec<ByteRange>>,
+    /// First bytes of an mdat payload, when
+    /// [`ParseOptions::mdat_peek`] asks for them
+    pub peek: Option<MdatPeek>,
+}
+
+/// A contiguous run of media bytes, typically inside an `mdat`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ByteRange {
+    /// Absolute byte offset of the first media byte
+    pub offset: u64,
+    /// Length of the run in bytes
+    pub size: u64,
+    /// Offset of the top-level mdat holding this run, if any
+    pub mdat_offset: Option<u64>,
 }
 
 /// Parse an MP4/ISOBMFF file and return the complete box tree as JSON-serializable structures.
@@ -91,60 +114,369 @@ pub fn get_boxes<R: Read + Seek>(r: &mut R, size: u64, decode: bool) -> anyhow::
 /// let boxes = get_boxes_with_registry(&mut file, size, true, default_registry())?; // decode known boxes
 /// # Ok::<(), anyhow::Error>(())
 /// ```
-pub fn get_boxes_with_registry<R: Read + Seek>(r: &mut R, size: u64, decode: bool, registry: Registry) -> anyhow::Result<Vec<Box>> {
-    // let mut f = File::open(&path)?;
-    // let file_len = f.metadata()?.len();
+pub fn get_boxes_with_registry<R: Read + Seek>(
+    r: &mut R,
+    size: u64,
+    decode: bool,
+    registry: Registry,
+) -> anyhow::Result<Vec<Box>> {
+    let options = ParseOptions::new().decode(decode).registry(registry);
+    // Strict callers see decode failures in `decoded` only
+    Ok(get_boxes_with_options(r, size, &options)?.0)
+}
+
+/// Parse as much of a damaged file as possible.
+///
+/// Where [`get_boxes`] fails, this keeps going and reports what it skipped:
+/// unreadable headers, boxes extending past their parent or the file (cut
+/// at that end), box contents that do not parse (kept as leaves) and, with
+/// `decode`, decoder failures.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::get_boxes_lenient;
+/// use std::fs::File;
+///
+/// let mut file = File::open("truncated.mp4")?;
+/// let size = file.metadata()?.len();
+/// let (boxes, warnings) = get_boxes_lenient(&mut file, size, true);
+/// for w in &warnings {
+///     eprintln!("@{} {:?}: {:?}", w.offset, w.typ, w.kind);
+/// }
+/// println!("{} top-level boxes", boxes.len());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn get_boxes_lenient<R: Read + Seek>(
+    r: &mut R,
+    size: u64,
+    decode: bool,
+) -> (Vec<Box>, Vec<ParseWarning>) {
+    let options = ParseOptions::new().decode(decode).lenient(true);
+    // Lenient parsing only fails when the reader cannot report its position
+    get_boxes_with_options(r, size, &options).unwrap_or_default()
+}
+
+/// Default [`ParseOptions::max_table_entries`]: 16M entries, over 70 hours
+/// of 60 fps video.
+pub const DEFAULT_MAX_TABLE_ENTRIES: u64 = 1 << 24;
+
+/// Default [`ParseOptions::buffer_limit`]: 16 MiB, enough for the moov of
+/// a several-hour movie.
+pub const DEFAULT_BUFFER_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// How [`get_boxes_with_options`] parses: mode, resource limits and the
+/// decoders to use.
+///
+/// The defaults match [`get_boxes`] with `decode = true`: strict, no box
+/// limit, the default registry and media ranges linked.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{ParseOptions, get_boxes_with_options};
+/// use std::fs::File;
+///
+/// let options = ParseOptions::new()
+///     .lenient(true)
+///     .max_depth(32)
+///     .max_boxes(100_000)
+///     .max_table_entries(1_000_000);
+/// let mut file = File::open("untrusted.mp4")?;
+/// let size = file.metadata()?.len();
+/// let (boxes, warnings) = get_boxes_with_options(&mut file, size, &options)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct ParseOptions {
+    pub(crate) decode: bool,
+    pub(crate) lenient: bool,
+    recover: bool,
+    max_depth: usize,
+    max_boxes: usize,
+    max_table_entries: u64,
+    buffer_limit: u64,
+    single_pass: u64,
+    pub(crate) mdat_peek: u64,
+    pub(crate) media_ranges: bool,
+    registry: Registry,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        ParseOptions {
+            decode: true,
+            lenient: false,
+            recover: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_boxes: usize::MAX,
+            max_table_entries: DEFAULT_MAX_TABLE_ENTRIES,
+            buffer_limit: DEFAULT_BUFFER_LIMIT,
+            single_pass: 0,
+            mdat_peek: 0,
+            media_ranges: true,
+            registry: default_registry(),
+        }
+    }
+
+    /// Decode box contents with the registry.
+    pub fn decode(mut self, decode: bool) -> Self {
+        self.decode = decode;
+        self
+    }
+
+    /// Record problems as warnings and keep going instead of failing; see
+    /// [`get_boxes_lenient`].
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
 
-    // parse top-level boxes
-    let mut boxes = Vec::new();
-    while r.stream_position()? < size {
-        let h = read_box_header(r)?;
-        let box_end = if h.size == 0 { size } else { h.start + h.size };
-
-        let kind = if crate::known_boxes::KnownBox::from(h.typ).is_container() {
-            r.seek(SeekFrom::Start(h.start + h.header_size))?;
-            NodeKind::Container(crate::parser::parse_children(r, box_end)?)
-        } else if crate::known_boxes::KnownBox::from(h.typ).is_full_box() {
-            r.seek(SeekFrom::Start(h.start + h.header_size))?;
-            let version = r.read_u8()?;
-            let mut fl = [0u8; 3];
-            r.read_exact(&mut fl)?;
-            let flags = ((fl[0] as u32) << 16) | ((fl[1] as u32) << 8) | (fl[2] as u32);
-            let data_offset = r.stream_position()?;
-            let data_len = box_end.saturating_sub(data_offset);
-            NodeKind::FullBox {
-                version,
-                flags,
-                data_offset,
-                data_len,
+    /// After a bad header, scan for the next plausible box header and resume
+    /// there; the skipped bytes become a [`NodeKind::Gap`] box (kind
+    /// `"gap"`) with a [`Resynchronized`](WarningKind::Resynchronized)
+    /// warning. Applies in strict mode too.
+    pub fn recover(mut self, recover: bool) -> Self {
+        self.recover = recover;
+        self
+    }
+
+    /// Deepest nesting level parsed; top-level boxes are level 0. Containers
+    /// whose children would be deeper become leaves with a
+    /// [`LimitExceeded`](WarningKind::LimitExceeded) warning, in strict mode
+    /// too. Defaults to [`DEFAULT_MAX_DEPTH`].
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Most boxes parsed in total, at any depth.
+    pub fn max_boxes(mut self, max_boxes: usize) -> Self {
+        self.max_boxes = max_boxes;
+        self
+    }
+
+    /// Most entries decoded from one sample table (stts, stsz, stco, trun,
+    /// ...); longer tables are cut with an
+    /// [`EntriesClamped`](WarningKind::EntriesClamped) warning. Defaults to
+    /// [`DEFAULT_MAX_TABLE_ENTRIES`].
+    pub fn max_table_entries(mut self, max_table_entries: u64) -> Self {
+        self.max_table_entries = max_table_entries;
+        self
+    }
+
+    /// Link trak and moof boxes to the mdat bytes they use
+    /// ([`Box::media_ranges`]); only applies when decoding.
+    pub fn media_ranges(mut self, media_ranges: bool) -> Self {
+        self.media_ranges = media_ranges;
+        self
+    }
+
+    /// Largest top-level box read into memory in one go and parsed from
+    /// there, so its children and tables cost no further seeks. Bigger
+    /// boxes, and mdat and free space at any size, are parsed from the
+    /// reader. 0 turns buffering off. Defaults to [`DEFAULT_BUFFER_LIMIT`].
+    pub fn buffer_limit(mut self, buffer_limit: u64) -> Self {
+        self.buffer_limit = buffer_limit;
+        self
+    }
+
+    /// Decode in the same pass as the parse: leaf and FullBox contents of up
+    /// to `max_payload` bytes are kept as the tree is walked and decoded from
+    /// memory, instead of seeking back to each one afterwards. Bigger
+    /// payloads are still read again. Only matters for top-level boxes over
+    /// [`buffer_limit`](Self::buffer_limit); 0, the default, turns it off.
+    pub fn single_pass(mut self, max_payload: u64) -> Self {
+        self.single_pass = max_payload;
+        self
+    }
+
+    /// Read the first `len` bytes of every mdat payload and guess what they
+    /// hold ([`Box::peek`]); the rest of the mdat is never read. 0, the
+    /// default, reads none.
+    pub fn mdat_peek(mut self, len: u64) -> Self {
+        self.mdat_peek = len;
+        self
+    }
+
+    pub fn registry(mut self, registry: Registry) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Fresh parser state with this mode and these limits.
+    pub(crate) fn parse_state(&self) -> ParseState {
+        let mut state = ParseState::new(self.lenient, self.max_depth, self.max_boxes);
+        state.recover = self.recover;
+        state
+    }
+}
+
+/// Parse from the reader's current position to `size` as `options` says.
+///
+/// In strict mode the first problem is an error and the warnings hold only
+/// decoder failures; in lenient mode every problem is a warning.
+pub fn get_boxes_with_options<R: Read + Seek>(
+    r: &mut R,
+    size: u64,
+    options: &ParseOptions,
+) -> anyhow::Result<(Vec<Box>, Vec<ParseWarning>)> {
+    let mut state = options.parse_state();
+    let mut tree = Vec::new();
+    // Decoder warnings follow every parse warning, as in one whole-file pass
+    let mut decoded = Vec::new();
+    // Buffered boxes are in memory already
+    let capture = if options.decode {
+        options.single_pass
+    } else {
+        0
+    };
+    let mut pos = r.stream_position()?;
+
+    while pos < size {
+        r.seek(SeekFrom::Start(pos))?;
+        // Bad headers and resynchronisation need the whole remaining range
+        let header = match options.recover {
+            true => None,
+            false => read_box_header(r).ok(),
+        };
+        r.seek(SeekFrom::Start(pos))?;
+        let Some(h) = header else {
+            state.capture = capture;
+            let boxes = crate::parser::parse_children_with(r, size, &mut state)?;
+            let mut r = Prefetched::new(r, std::mem::take(&mut state.captured));
+            for b in &boxes {
+                tree.push(build_box(&mut r, b, options, &mut decoded));
+            }
+            break;
+        };
+        // The parser reports boxes running past the end as truncated
+        let end = match h.size {
+            0 => size,
+            n => pos.saturating_add(n).min(size),
+        };
+
+        let boxes = if end - pos <= options.buffer_limit && payload_needed(&h, options) {
+            let mut data = vec![0u8; (end - pos) as usize];
+            let n = read_up_to(r, &mut data)?;
+            data.truncate(n);
+            let mut window = Window::new(pos, data);
+            let boxes = crate::parser::parse_children_with(&mut window, end, &mut state)?;
+            for b in &boxes {
+                tree.push(build_box(&mut window, b, options, &mut decoded));
             }
+            boxes
         } else {
-            let data_offset = h.start + h.header_size;
-            let data_len = box_end.saturating_sub(data_offset);
-            if &h.typ.0 == b"uuid" {
-                NodeKind::Unknown {
-                    data_offset,
-                    data_len,
-                }
-            } else {
-                NodeKind::Leaf {
-                    data_offset,
-                    data_len,
-                }
+            state.capture = capture;
+            let boxes = crate::parser::parse_children_with(r, end, &mut state)?;
+            state.capture = 0;
+            let mut r = Prefetched::new(r, std::mem::take(&mut state.captured));
+            for b in &boxes {
+                tree.push(build_box(&mut r, b, options, &mut decoded));
             }
+            boxes
         };
+        // Only the box limit leaves a readable header unparsed
+        if boxes.is_empty() {
+            break;
+        }
+        pos = end;
+    }
 
-        r.seek(SeekFrom::Start(box_end))?;
-        boxes.push(BoxRef { hdr: h, kind });
+    if options.decode && options.media_ranges {
+        link_media_ranges(&mut tree, size, options);
     }
+    state.list.extend(decoded);
+    Ok((tree, state.list))
+}
 
-    // build JSON tree
-    let json_boxes = boxes
+/// Whether parsing a box with this header reads its payload at all: it
+/// has children, or a decoder may run on it.
+pub(crate) fn payload_needed(h: &BoxHeader, options: &ParseOptions) -> bool {
+    !SKIPPED.contains(&&h.typ.0)
+        && (KnownBox::from(h.typ).is_container() || &h.typ.0 == b"meta" || options.decode)
+}
+
+/// Fill `media_ranges` on every trak (progressive files, via the sample
+/// tables) and every top-level moof (fragmented files, via tfhd/trun). Each
+/// box links at most `max_table_entries` samples.
+pub(crate) fn link_media_ranges(boxes: &mut [Box], size: u64, options: &ParseOptions) {
+    let mdats: Vec<(u64, u64)> = boxes
         .iter()
-        .map(|b| build_box(r, b, decode, &registry))
+        .filter(|b| b.typ == "mdat")
+        .map(|b| {
+            let end = if b.size == 0 {
+                size
+            } else {
+                b.offset.saturating_add(b.size)
+            };
+            (b.offset, end)
+        })
         .collect();
+    let trex = crate::fragments::trex_defaults(boxes);
+    let limit = options.max_table_entries.min(usize::MAX as u64) as usize;
+
+    for b in boxes.iter_mut() {
+        match b.typ.as_str() {
+            "moov" => {
+                for trak in b.children.iter_mut().flatten() {
+                    if trak.typ != "trak" {
+                        continue;
+                    }
+                    let track = crate::track::Track::new(trak);
+                    if track.stbl().is_none() {
+                        continue;
+                    }
+                    // Offset 0 means the chunk tables could not place the sample
+                    let spans = crate::samples::SampleIter::new(&track)
+                        .within_file_size(size)
+                        .take(limit)
+                        .filter(|s| s.size > 0 && s.file_offset > 0)
+                        .map(|s| (s.file_offset, s.size as u64))
+                        .collect();
+                    trak.media_ranges = Some(coalesce(spans, &mdats));
+                }
+            }
+            "moof" => {
+                let spans = crate::fragments::moof_samples(b, &trex)
+                    .iter()
+                    .take(limit)
+                    .filter(|s| s.size > 0)
+                    .map(|s| (s.file_offset, s.size as u64))
+                    .collect();
+                b.media_ranges = Some(coalesce(spans, &mdats));
+            }
+            _ => {}
+        }
+    }
+}
 
-    Ok(json_boxes)
+/// Merge (offset, len) spans into sorted, non-overlapping ranges.
+fn coalesce(mut spans: Vec<(u64, u64)>, mdats: &[(u64, u64)]) -> Vec<ByteRange> {
+    spans.sort_unstable();
+    let mut out: Vec<ByteRange> = Vec::new();
+    for (offset, len) in spans {
+        if let Some(last) = out.last_mut() {
+            let last_end = last.offset.saturating_add(last.size);
+            if offset <= last_end {
+                let end = offset.saturating_add(len);
+                last.size = last.size.max(end - last.offset);
+                continue;
+            }
+        }
+        out.push(ByteRange {
+            offset,
+            size: len,
+            mdat_offset: mdats
+                .iter()
+                .find(|(start, end)| *start <= offset && offset < *end)
+                .map(|(start, _)| *start),
+        });
+    }
+    out
 }
 
 fn payload_region(b: &BoxRef) -> Option<(crate::boxes::BoxKey, u64, u64)> {
@@ -160,19 +492,21 @@ fn payload_region(b: &BoxRef) -> Option<(crate::boxes::BoxKey, u64, u64)> {
             data_len,
             ..
         } => Some((key, *data_offset, *data_len)),
-        NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
-            let hdr = &b.hdr;
-            if hdr.size == 0 {
-                return None;
-            }
-            let off = hdr.start + hdr.header_size;
-            let len = hdr.size.saturating_sub(hdr.header_size);
-            if len == 0 {
+        // The parser clamped these to the parent's end
+        NodeKind::Leaf {
+            data_offset,
+            data_len,
+        }
+        | NodeKind::Unknown {
+            data_offset,
+            data_len,
+        } => {
+            if b.hdr.size == 0 || *data_len == 0 {
                 return None;
             }
-            Some((key, off, len))
+            Some((key, *data_offset, *data_len))
         }
-        NodeKind::Container(_) => None,
+        NodeKind::Container(_) | NodeKind::Gap { .. } => None,
     }
 }
 
@@ -183,18 +517,24 @@ fn payload_geometry(b: &BoxRef) -> Option<(u64, u64)> {
             data_len,
             ..
         } => Some((*data_offset, *data_len)),
-        NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
-            let hdr = &b.hdr;
-            if hdr.size == 0 {
-                return None;
-            }
-            let off = hdr.start + hdr.header_size;
-            let len = hdr.size.saturating_sub(hdr.header_size);
-            if len == 0 {
+        // The parser clamped these to the parent's end
+        NodeKind::Leaf {
+            data_offset,
+            data_len,
+        }
+        | NodeKind::Unknown {
+            data_offset,
+            data_len,
+        } => {
+            if b.hdr.size == 0 || *data_len == 0 {
                 return None;
             }
-            Some((off, len))
+            Some((*data_offset, *data_len))
         }
+        NodeKind::Gap {
+            data_offset,
+            data_len,
+        } => Some((*data_offset, *data_len)),
         NodeKind::Container(_) => None,
     }
 }
@@ -202,7 +542,8 @@ fn payload_geometry(b: &BoxRef) -> Option<(u64, u64)> {
 fn decode_value<R: Read + Seek>(
     r: &mut R,
     b: &BoxRef,
-    reg: &Registry,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
 ) -> (Option<String>, Option<crate::registry::StructuredData>) {
     let (key, off, len) = match payload_region(b) {
         Some(region) => region,
@@ -212,6 +553,35 @@ fn decode_value<R: Read + Seek>(
         return (None, None);
     }
 
+    // Bound table decoders by what the payload holds and by the cap
+    let mut len = len;
+    if let Some(table) = table_layout(r, b, off) {
+        let fits = match table.entry_bits {
+            0 => table.count,
+            bits => len.saturating_sub(table.header) * 8 / bits,
+        };
+        let decoded = table.count.min(fits).min(options.max_table_entries);
+        let warn = |kind| ParseWarning {
+            offset: b.hdr.start,
+            typ: Some(b.hdr.typ.to_string()),
+            kind,
+        };
+        if decoded < table.count && table.entry_bits == 0 {
+            // Entries without fields: a shorter payload would not help
+            let limit = "max_table_entries";
+            warnings.push(warn(WarningKind::LimitExceeded { limit }));
+            let text = format!("[not decoded: {} entries over limit]", table.count);
+            return (Some(text), None);
+        }
+        if decoded < table.count {
+            warnings.push(warn(WarningKind::EntriesClamped {
+                declared: table.count,
+                decoded,
+            }));
+            len = len.min(table.header + (decoded * table.entry_bits).div_ceil(8));
+        }
+    }
+
     if r.seek(SeekFrom::Start(off)).is_err() {
         return (None, None);
     }
@@ -223,22 +593,83 @@ fn decode_value<R: Read + Seek>(
         _ => (None, None),
     };
 
-    if let Some(res) = reg.decode(&key, &mut limited, &b.hdr, version, flags) {
+    if let Some(res) = options
+        .registry
+        .decode(&key, &mut limited, &b.hdr, version, flags)
+    {
         match res {
             Ok(BoxValue::Text(s)) => (Some(s), None),
             Ok(BoxValue::Bytes(bytes)) => (Some(format!("{} bytes", bytes.len())), None),
-            Ok(BoxValue::Structured(data)) => {
-                let debug_str = format!("structured: {:?}", data);
-                (Some(debug_str), Some(data))
+            Ok(BoxValue::Structured(data)) => (Some(data.summary()), Some(data)),
+            Err(e) => {
+                warnings.push(ParseWarning {
+                    offset: b.hdr.start,
+                    typ: Some(b.hdr.typ.to_string()),
+                    kind: WarningKind::DecodeFailed {
+                        message: e.to_string(),
+                    },
+                });
+                (Some(format!("[decode error: {}]", e)), None)
             }
-            Err(e) => (Some(format!("[decode error: {}]", e)), None),
         }
     } else {
         (None, None)
     }
 }
 
-fn build_box<R: Read + Seek>(r: &mut R, b: &BoxRef, decode: bool, reg: &Registry) -> Box {
+/// Where the entries of a table box start and how large they are.
+struct TableLayout {
+    /// Payload bytes before the first entry
+    header: u64,
+    /// Declared entry count
+    count: u64,
+    entry_bits: u64,
+}
+
+/// Layout of a table box whose payload starts at `off`; None for other
+/// boxes and for stsz with a constant sample size.
+fn table_layout<R: Read + Seek>(r: &mut R, b: &BoxRef, off: u64) -> Option<TableLayout> {
+    let (version, flags) = match &b.kind {
+        NodeKind::FullBox { version, flags, .. } => (*version, *flags),
+        _ => return None,
+    };
+    let mut head = [0u8; 12];
+    r.seek(SeekFrom::Start(off)).ok()?;
+    let n = r.read(&mut head).ok()?;
+    let word = |i: usize| {
+        head.get(i..i + 4)
+            .filter(|_| i + 4 <= n)
+            .map(|w| u32::from_be_bytes(w.try_into().unwrap()) as u64)
+    };
+
+    let (header, count, entry_bits) = match &b.hdr.typ.0 {
+        b"stts" | b"ctts" | b"co64" => (4, word(0)?, 64),
+        b"stss" | b"stco" => (4, word(0)?, 32),
+        b"stsc" => (4, word(0)?, 96),
+        b"elst" => (4, word(0)?, if version == 1 { 160 } else { 96 }),
+        b"stsz" if word(0)? == 0 => (8, word(4)?, 32),
+        b"stz2" => (8, word(4)?, head[3] as u64),
+        b"sbgp" if version == 1 => (12, word(8)?, 64),
+        b"sbgp" => (8, word(4)?, 64),
+        b"trun" => {
+            let header = 4 + 4 * (flags & 0x1) as u64 + 4 * ((flags >> 2) & 0x1) as u64;
+            (header, word(0)?, 32 * (flags & 0xF00).count_ones() as u64)
+        }
+        _ => return None,
+    };
+    Some(TableLayout {
+        header,
+        count,
+        entry_bits,
+    })
+}
+
+pub(crate) fn build_box<R: Read + Seek>(
+    r: &mut R,
+    b: &BoxRef,
+    options: &ParseOptions,
+    warnings: &mut Vec<ParseWarning>,
+) -> Box {
     let hdr = &b.hdr;
     let uuid_str = hdr
         .uuid
@@ -254,22 +685,50 @@ fn build_box<R: Read + Seek>(r: &mut R, b: &BoxRef, decode: bool, reg: &Registry
         .unwrap_or((None, None));
 
     let (version, flags, kind_str, children) = match &b.kind {
-        NodeKind::FullBox { version, flags, .. } => {
-            (Some(*version), Some(*flags), "full".to_string(), None)
+        NodeKind::FullBox {
+            version,
+            flags,
+            children: kids,
+            ..
+        } => {
+            let child_nodes = (!kids.is_empty()).then(|| {
+                kids.iter()
+                    .map(|c| build_box(r, c, options, warnings))
+                    .collect()
+            });
+            (
+                Some(*version),
+                Some(*flags),
+                "full".to_string(),
+                child_nodes,
+            )
         }
         NodeKind::Leaf { .. } => (None, None, "leaf".to_string(), None),
         NodeKind::Unknown { .. } => (None, None, "unknown".to_string(), None),
+        NodeKind::Gap { .. } => (None, None, "gap".to_string(), None),
         NodeKind::Container(kids) => {
-            let child_nodes = kids.iter().map(|c| build_box(r, c, decode, reg)).collect();
+            let child_nodes = kids
+                .iter()
+                .map(|c| build_box(r, c, options, warnings))
+                .collect();
             (None, None, "container".to_string(), Some(child_nodes))
         }
     };
 
-    let (decoded, structured_data) = if decode {
-        decode_value(r, b, reg)
+    let (decoded, structured_data) = if options.decode {
+        decode_value(r, b, options, warnings)
     } else {
         (None, None)
     };
+    let peek = match b.kind {
+        NodeKind::Leaf {
+            data_offset,
+            data_len,
+        } if &hdr.typ.0 == b"mdat" && options.mdat_peek > 0 => {
+            peek_payload(r, data_offset, data_len, options.mdat_peek)
+        }
+        _ => None,
+    };
 
     Box {
         offset: hdr.start,
@@ -279,6 +738,7 @@ fn build_box<R: Read + Seek>(r: &mut R, b: &BoxRef, decode: bool, reg: &Registry
         payload_size,
 
         typ: hdr.typ.to_string(),
+        typ_hex: hdr.typ.to_hex(),
         uuid: uuid_str,
         version,
         flags,
@@ -287,9 +747,23 @@ fn build_box<R: Read + Seek>(r: &mut R, b: &BoxRef, decode: bool, reg: &Registry
         decoded,
         structured_data,
         children,
+        media_ranges: None,
+        peek,
     }
 }
 
+/// Read up to `max` bytes of the payload at `off` and classify them.
+fn peek_payload<R: Read + Seek>(r: &mut R, off: u64, len: u64, max: u64) -> Option<MdatPeek> {
+    let mut head = vec![0u8; len.min(max) as usize];
+    r.seek(SeekFrom::Start(off)).ok()?;
+    let n = read_up_to(r, &mut head).ok()?;
+    head.truncate(n);
+    Some(MdatPeek {
+        format: classify_payload(&head, len),
+        hex: hex::encode(head),
+    })
+}
+
 /// Result of a hex dump operation containing the formatted hex output.
 #[derive(Serialize)]
 pub struct HexDump {
@@ -362,3 +836,131 @@ pub fn hex_range<R: Read + Seek>(
         hex: hex_str,
     })
 }
+
+/// Part of a box a byte offset falls in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoxRegion {
+    /// Size, type and any largesize/uuid fields
+    Header,
+    /// The version and flags of a full box
+    VersionFlags,
+    /// Inside a child box; the next [`BoxPath`] step describes it
+    Child,
+    /// Payload bytes not covered by a child box
+    Payload,
+}
+
+impl BoxRegion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BoxRegion::Header => "header",
+            BoxRegion::VersionFlags => "version_flags",
+            BoxRegion::Child => "child",
+            BoxRegion::Payload => "payload",
+        }
+    }
+}
+
+/// One step of the chain returned by [`box_at_offset`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoxPath {
+    pub typ: String,
+    /// Position among the siblings of the same type, when there are several
+    pub index: Option<usize>,
+    pub offset: u64,
+    pub size: u64,
+    pub region: BoxRegion,
+}
+
+/// The boxes containing `offset`, outermost first; empty when the offset
+/// lies outside every top-level box.
+///
+/// The last step's `region` says which part of the innermost box holds the
+/// byte. [`format_box_path`] renders the chain for display next to a
+/// [`hex_range`] dump.
+///
+/// ```no_run
+/// use mp4box::{box_at_offset, format_box_path, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, false)?;
+/// // e.g. "moov>trak[0]>mdia>minf>stbl>stsz @payload"
+/// println!("{}", format_box_path(&box_at_offset(&boxes, 0x1234)));
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn box_at_offset(tree: &[Box], offset: u64) -> Vec<BoxPath> {
+    let mut path = Vec::new();
+    let mut siblings = tree;
+    while let Some(b) = siblings
+        .iter()
+        .find(|b| offset >= b.offset && offset - b.offset < b.size)
+    {
+        let same_type: Vec<_> = siblings.iter().filter(|s| s.typ == b.typ).collect();
+        let index = (same_type.len() > 1)
+            .then(|| same_type.iter().position(|s| std::ptr::eq(*s, b)))
+            .flatten();
+
+        let rel = offset - b.offset;
+        let children = b.children.as_deref().unwrap_or_default();
+        let in_child = children
+            .iter()
+            .any(|c| offset >= c.offset && offset - c.offset < c.size);
+        let region = if rel < b.header_size {
+            BoxRegion::Header
+        } else if b.version.is_some() && rel < b.header_size + 4 {
+            BoxRegion::VersionFlags
+        } else if in_child {
+            BoxRegion::Child
+        } else {
+            BoxRegion::Payload
+        };
+
+        path.push(BoxPath {
+            typ: b.typ.clone(),
+            index,
+            offset: b.offset,
+            size: b.size,
+            region,
+        });
+        if region != BoxRegion::Child {
+            break;
+        }
+        siblings = children;
+    }
+    path
+}
+
+/// Path of `siblings[i]` under `parent` in [`format_box_path`] notation.
+pub(crate) fn child_path(parent: &str, siblings: &[Box], i: usize) -> String {
+    let typ = &siblings[i].typ;
+    let mut path = if parent.is_empty() {
+        typ.clone()
+    } else {
+        format!("{}>{}", parent, typ)
+    };
+    if siblings.iter().filter(|s| &s.typ == typ).count() > 1 {
+        let index = siblings[..i].iter().filter(|s| &s.typ == typ).count();
+        path = format!("{}[{}]", path, index);
+    }
+    path
+}
+
+/// Render a [`box_at_offset`] chain as `moov>trak[1]>tkhd @payload`.
+pub fn format_box_path(path: &[BoxPath]) -> String {
+    let mut s = path
+        .iter()
+        .map(|p| match p.index {
+            Some(i) => format!("{}[{}]", p.typ, i),
+            None => p.typ.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(">");
+    if let Some(last) = path.last() {
+        s.push_str(" @");
+        s.push_str(last.region.as_str());
+    }
+    s
+}
@@ -1,27 +1,42 @@
 use clap::{ArgAction, Parser};
 use mp4box::{
-    BoxHeader,
-    boxes::{BoxKey, BoxRef, FourCC, NodeKind},
-    known_boxes::KnownBox,
-    parser::{parse_children, read_box_header},
-    registry::{BoxValue, Registry, default_registry},
-    util::{hex_dump, read_slice},
+    ByteRange, ParseOptions, ParseWarning,
+    boxes::{BoxRef, FourCC, NodeKind},
+    follow::Follower,
+    fragments::fragment_index,
+    get_boxes_with_options,
+    numfmt::NumberFormat,
+    parser::{parse_box, parse_children_recover, read_box_header},
+    query::select,
+    repair::{RepairMode, repair_truncated},
+    stream::get_boxes_streaming,
+    util::{CountingReader, hex_dump, read_slice},
+    walk::{Children, walk},
 };
 use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::io::{Read, Seek, SeekFrom};
-use std::{fs::File, str::FromStr};
+use std::ops::ControlFlow;
+use std::{
+    fs::{File, OpenOptions},
+    str::FromStr,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about = "Minimal MP4/ISOBMFF box explorer")]
 struct Args {
-    /// MP4/ISOBMFF file path
+    /// MP4/ISOBMFF file path, or - to read a stream from stdin
     path: String,
 
-    /// Only print subtree(s) matching a dotted path (e.g. moov.trak[0].mdia.minf.stbl)
+    /// Only print subtree(s) matching a dotted path (e.g. moov.trak[0].mdia.minf.stbl);
+    /// a type can also be given as 0x and 8 hex digits. `*` matches any box,
+    /// `**` any number of levels (**.tenc), uuid:<hex> a uuid box and
+    /// [@handler=soun] filters by attribute
     #[arg(long = "filter")]
     filter: Option<String>,
 
-    /// Dump raw payload of this 4CC (e.g. --raw stsd) or uuid:xxxxxxxx...
+    /// Dump raw payload of this 4CC (e.g. --raw stsd, --raw 0xa9746f6f) or
+    /// uuid:xxxxxxxx...
     #[arg(long = "raw")]
     raw: Option<String>,
 
@@ -40,229 +55,458 @@ struct Args {
     /// Emit JSON instead of human-readable tree
     #[arg(long, action = ArgAction::SetTrue)]
     json: bool,
+
+    /// Report how many bytes were actually read from the file (on stderr)
+    #[arg(long = "io-stats", action = ArgAction::SetTrue)]
+    io_stats: bool,
+
+    /// Skip over damaged headers to the next plausible box instead of
+    /// failing; skipped bytes show as unparsed gaps
+    #[arg(long, action = ArgAction::SetTrue)]
+    recover: bool,
+
+    /// Rewrite the size of a last box that runs past the end of the file
+    /// (modifies the file in place) before dumping it
+    #[arg(long = "repair-truncated", action = ArgAction::SetTrue)]
+    repair_truncated: bool,
+
+    /// With --repair-truncated, mark the box as extending to the end of the
+    /// file instead of storing its actual size
+    #[arg(long = "size-to-eof", action = ArgAction::SetTrue, requires = "repair_truncated")]
+    size_to_eof: bool,
+
+    /// Keep watching a file that is still being written and print top-level
+    /// boxes as they are completed (one JSON object per line with --json)
+    #[arg(long, action = ArgAction::SetTrue)]
+    follow: bool,
+
+    /// Group digits and show sizes the way the locale does, instead of
+    /// plain numbers
+    #[arg(long, action = ArgAction::SetTrue)]
+    human: bool,
+}
+
+/// Settings shared by every line of the text tree.
+struct TreeOptions {
+    max_depth: usize,
+    /// Decoded values, keyed by box offset and type
+    decoded: HashMap<(u64, String), String>,
+    /// Inline notes appended to container lines, keyed by box offset
+    notes: HashMap<u64, String>,
+    nf: NumberFormat,
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
-    let mut f = File::open(&args.path)?;
+    let nf = NumberFormat::for_cli(args.human);
+    if args.path == "-" {
+        return dump_stream(&args, &nf);
+    }
+    if args.follow {
+        return follow(&args, &nf);
+    }
+    if args.repair_truncated {
+        repair(&args.path, args.size_to_eof)?;
+    }
+    let mut f = CountingReader::new(File::open(&args.path)?);
+    dump(&mut f, &args, nf)
+}
+
+/// Dump a seekable source. Every pass (structure, decoding, JSON, raw
+/// bytes) goes through the one handle `f`, so it is read from a single
+/// open file, network or FUSE-backed source alike.
+fn dump<R: Read + Seek>(
+    f: &mut CountingReader<R>,
+    args: &Args,
+    nf: NumberFormat,
+) -> anyhow::Result<()> {
+    let file_len = f.seek(SeekFrom::End(0))?;
+    f.seek(SeekFrom::Start(0))?;
 
-    let file_len = f.metadata()?.len();
-    let top = {
+    // JSON mode: output JSON and exit (no tree or raw to keep output clean)
+    if args.json {
+        let (tree, warnings) = parse_tree(f, file_len, args, args.recover)?;
+        if args.recover {
+            for w in &warnings {
+                eprintln!("warning at {:#x}: {:?}", w.offset, w.kind);
+            }
+        }
+        let targets = match &args.filter {
+            Some(path) => select(&tree, path),
+            None => tree.iter().collect(),
+        };
+        let json_boxes: Vec<JsonBox> = targets
+            .iter()
+            .map(|b| JsonBox::new(b, args.decode))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json_boxes)?);
+        if args.io_stats {
+            print_io_stats(f.bytes_read(), file_len, &nf);
+        }
+        return Ok(());
+    }
+
+    let top = if args.recover {
+        let (kids, warnings) = parse_children_recover(f, file_len);
+        for w in &warnings {
+            eprintln!("warning at {:#x}: {:?}", w.offset, w.kind);
+        }
+        kids
+    } else {
         // Top-level loop
         let mut kids = Vec::new();
         while f.stream_position()? < file_len {
-            let h = read_box_header(&mut f)?;
+            let h = read_box_header(f)?;
             let box_end = if h.size == 0 {
                 file_len
             } else {
-                h.start + h.size
+                (h.start + h.size).min(file_len)
             };
 
-            let kind = if is_container(&h) {
-                f.seek(SeekFrom::Start(h.start + h.header_size))?;
-                NodeKind::Container(parse_children(&mut f, box_end)?)
-            } else if is_full_box(&h) {
-                f.seek(SeekFrom::Start(h.start + h.header_size))?;
-                use byteorder::ReadBytesExt;
-                let version = f.read_u8()?;
-                let mut fl = [0u8; 3];
-                f.read_exact(&mut fl)?;
-                let flags = ((fl[0] as u32) << 16) | ((fl[1] as u32) << 8) | (fl[2] as u32);
-                let data_offset = f.stream_position()?;
-                let data_len = box_end.saturating_sub(data_offset);
-                NodeKind::FullBox {
-                    version,
-                    flags,
-                    data_offset,
-                    data_len,
-                }
-            } else {
-                let data_offset = h.start + h.header_size;
-                let data_len = box_end.saturating_sub(data_offset);
-                if &h.typ.0 == b"uuid" {
-                    NodeKind::Unknown {
-                        data_offset,
-                        data_len,
-                    }
-                } else {
-                    NodeKind::Leaf {
-                        data_offset,
-                        data_len,
-                    }
-                }
-            };
+            let kind = parse_box(f, &h, box_end)?;
             f.seek(SeekFrom::Start(box_end))?;
             kids.push(BoxRef { hdr: h, kind });
         }
         kids
     };
 
-    let reg = default_registry();
-
-    // Target roots for printing/JSON
-    let targets: Vec<&BoxRef> = if let Some(path) = &args.filter {
-        select_by_path(&top, path)
+    // Decoded values and attribute filters come from the library tree
+    let tree = if args.decode || attribute_filter(args) {
+        parse_tree(f, file_len, args, true)?.0
     } else {
-        top.iter().collect()
+        Vec::new()
     };
 
-    // JSON mode: output JSON and exit (no tree or raw to keep output clean)
-    if args.json {
-        let mut json_file = File::open(&args.path)?; // fresh handle for decoding
-        let json_boxes: Vec<JsonBox> = targets
-            .iter()
-            .map(|b| build_json_for_box(&mut json_file, b, args.decode, &reg))
-            .collect();
-        println!("{}", serde_json::to_string_pretty(&json_boxes)?);
-        return Ok(());
-    }
+    // Target roots for printing
+    let targets: Vec<&BoxRef> = match &args.filter {
+        // Attributes such as handler need decoded boxes; match on those and
+        // find the same boxes in the raw tree
+        Some(path) if attribute_filter(args) => {
+            let wanted: HashSet<(u64, String)> = select(&tree, path)
+                .into_iter()
+                .map(|b| (b.offset, b.typ_hex.clone()))
+                .collect();
+            top.iter()
+                .flat_map(|b| b.iter())
+                .map(|(_, b)| b)
+                .filter(|b| wanted.contains(&(b.hdr.start, b.hdr.typ.to_hex())))
+                .collect()
+        }
+        Some(path) => select(&top, path),
+        None => top.iter().collect(),
+    };
+
+    // Inline fragment summaries, keyed by moof offset
+    let (decoded, notes) = if args.decode {
+        let mut decoded = HashMap::new();
+        collect_decoded(&tree, &mut decoded);
+        (decoded, fragment_summaries(&tree, file_len, &nf))
+    } else {
+        (HashMap::new(), HashMap::new())
+    };
+    let opts = TreeOptions {
+        max_depth: args.max_depth,
+        decoded,
+        notes,
+        nf,
+    };
 
     // Text tree
     for b in &targets {
-        print_box(&mut f, b, 0, args.max_depth, args.decode, &reg)?;
+        print_box(b, 0, &opts);
     }
 
     // Optional raw dump (unfiltered: still walks the whole tree)
     if let Some(sel) = args.raw.as_ref() {
-        dump_raw(&mut f, &top, sel, args.bytes)?;
+        dump_raw(f, &top, sel, args.bytes)?;
+    }
+
+    if args.io_stats {
+        print_io_stats(f.bytes_read(), file_len, &nf);
     }
 
     Ok(())
 }
 
+fn print_io_stats(bytes_read: u64, file_len: u64, nf: &NumberFormat) {
+    let pct = if file_len > 0 {
+        bytes_read as f64 * 100.0 / file_len as f64
+    } else {
+        0.0
+    };
+    eprintln!(
+        "io: read {} of {} bytes ({}%)",
+        nf.int(bytes_read),
+        nf.int(file_len),
+        nf.float(pct, 3)
+    );
+}
+
 // ---------- Human-readable tree ----------
 
-fn print_box(
-    f: &mut File,
-    b: &BoxRef,
-    depth: usize,
-    max_depth: usize,
-    decode: bool,
-    reg: &Registry,
-) -> anyhow::Result<()> {
+fn print_box(b: &BoxRef, depth: usize, opts: &TreeOptions) {
     let indent = "  ".repeat(depth);
     let hdr = &b.hdr;
+    let size = opts.nf.int(hdr.size);
     match &b.kind {
-        NodeKind::FullBox { version, flags, .. } => {
+        NodeKind::FullBox {
+            version,
+            flags,
+            children,
+            ..
+        } => {
             println!(
                 "{indent}{:>6} {:>10} {} (ver={}, flags=0x{:06x})",
                 format!("{:#x}", hdr.start),
-                hdr.size,
+                size,
                 display_type(hdr),
                 version,
                 flags
             );
-            if decode {
-                maybe_decode(f, b, reg)?;
+            print_decoded(b, opts);
+            if depth < opts.max_depth {
+                for c in children {
+                    print_box(c, depth + 1, opts);
+                }
             }
         }
         NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
             println!(
                 "{indent}{:>6} {:>10} {}",
                 format!("{:#x}", hdr.start),
-                hdr.size,
+                size,
                 display_type(hdr)
             );
-            if decode {
-                maybe_decode(f, b, reg)?;
-            }
+            print_decoded(b, opts);
+        }
+        NodeKind::Gap { .. } => {
+            println!(
+                "{indent}{:>6} {:>10} [unparsed gap]",
+                format!("{:#x}", hdr.start),
+                size
+            );
         }
         NodeKind::Container(children) => {
+            let note = opts
+                .notes
+                .get(&hdr.start)
+                .map(|n| format!(" [{n}]"))
+                .unwrap_or_default();
             println!(
-                "{indent}{:>6} {:>10} {} (container)",
+                "{indent}{:>6} {:>10} {} (container){note}",
                 format!("{:#x}", hdr.start),
-                hdr.size,
+                size,
                 display_type(hdr)
             );
-            if depth < max_depth {
+            if depth < opts.max_depth {
                 for c in children {
-                    print_box(f, c, depth + 1, max_depth, decode, reg)?;
+                    print_box(c, depth + 1, opts);
                 }
             }
         }
     }
-    Ok(())
 }
 
-fn display_type(h: &mp4box::boxes::BoxHeader) -> String {
-    if &h.typ.0 == b"uuid" {
-        let u = h.uuid.unwrap_or([0u8; 16]);
-        format!("uuid:{:02x?}", u)
-    } else {
-        h.typ.to_string()
+fn print_decoded(b: &BoxRef, opts: &TreeOptions) {
+    if let Some(s) = opts.decoded.get(&(b.hdr.start, b.hdr.typ.to_hex())) {
+        println!("        -> {}", s);
     }
 }
 
-// ---------- Decoding helpers (shared by text + JSON) ----------
-
-fn payload_region(b: &BoxRef) -> Option<(BoxKey, u64, u64)> {
-    let key = if &b.hdr.typ.0 == b"uuid" {
-        BoxKey::Uuid(b.hdr.uuid.unwrap())
-    } else {
-        BoxKey::FourCC(b.hdr.typ)
-    };
+/// Dump stdin with the forward-only parser; options that need to seek back
+/// into the file are not available.
+fn dump_stream(args: &Args, nf: &NumberFormat) -> anyhow::Result<()> {
+    if args.raw.is_some() || args.filter.is_some() || args.repair_truncated || args.io_stats {
+        anyhow::bail!("--raw, --filter, --repair-truncated and --io-stats need a file, not stdin");
+    }
+    let options = ParseOptions::new()
+        .decode(args.decode)
+        .lenient(args.recover)
+        .media_ranges(false);
+    let (boxes, warnings) = get_boxes_streaming(std::io::stdin().lock(), &options)?;
+    for w in &warnings {
+        eprintln!("warning at {:#x}: {:?}", w.offset, w.kind);
+    }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&boxes)?);
+        return Ok(());
+    }
+    print_stream_boxes(&boxes, args.max_depth, nf);
+    Ok(())
+}
 
-    match &b.kind {
-        NodeKind::FullBox {
-            data_offset,
-            data_len,
-            ..
-        } => Some((key, *data_offset, *data_len)),
-        NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
-            let hdr = &b.hdr;
-            if hdr.size == 0 {
-                return None;
-            }
-            let off = hdr.start + hdr.header_size;
-            let len = hdr.size.saturating_sub(hdr.header_size);
-            if len == 0 {
-                return None;
+/// Print boxes appended to a growing file until interrupted.
+fn follow(args: &Args, nf: &NumberFormat) -> anyhow::Result<()> {
+    if args.raw.is_some() || args.filter.is_some() || args.repair_truncated || args.io_stats {
+        anyhow::bail!(
+            "--raw, --filter, --repair-truncated and --io-stats cannot be used with --follow"
+        );
+    }
+    let options = ParseOptions::new()
+        .decode(args.decode)
+        .lenient(args.recover)
+        .media_ranges(false);
+    let mut follower = Follower::new(options);
+    let mut file = File::open(&args.path)?;
+    loop {
+        let (boxes, warnings) = follower.poll(&mut file)?;
+        for w in &warnings {
+            eprintln!("warning at {:#x}: {:?}", w.offset, w.kind);
+        }
+        if args.json {
+            for b in &boxes {
+                println!("{}", serde_json::to_string(b)?);
             }
-            Some((key, off, len))
+        } else {
+            print_stream_boxes(&boxes, args.max_depth, nf);
         }
-        NodeKind::Container(_) => None,
+        std::thread::sleep(std::time::Duration::from_millis(500));
     }
 }
 
-fn decode_value(f: &mut File, b: &BoxRef, reg: &Registry) -> Option<String> {
-    let (key, off, len) = payload_region(b)?;
-    if len == 0 {
-        return None;
-    }
+fn print_stream_boxes(boxes: &[mp4box::Box], max_depth: usize, nf: &NumberFormat) {
+    let _ = walk(boxes, &mut |b: &mp4box::Box, ancestors: &[&mp4box::Box]| {
+        let depth = ancestors.len();
+        print_stream_box(b, depth, nf);
+        ControlFlow::<(), _>::Continue(if depth < max_depth {
+            Children::Visit
+        } else {
+            Children::Skip
+        })
+    });
+}
 
-    if f.seek(SeekFrom::Start(off)).is_err() {
-        return None;
+fn print_stream_box(b: &mp4box::Box, depth: usize, nf: &NumberFormat) {
+    let indent = "  ".repeat(depth);
+    let typ = match &b.uuid {
+        Some(u) => format!("uuid:{u}"),
+        None => match FourCC::from_str(&format!("0x{}", b.typ_hex)) {
+            Ok(raw) if !raw.is_printable() => format!("{} (0x{})", b.typ, b.typ_hex),
+            _ => b.typ.clone(),
+        },
+    };
+    let detail = match (b.kind.as_str(), b.version, b.flags) {
+        ("container", ..) => " (container)".to_string(),
+        (_, Some(version), Some(flags)) => format!(" (ver={version}, flags=0x{flags:06x})"),
+        _ => String::new(),
+    };
+    println!(
+        "{indent}{:>6} {:>10} {typ}{detail}",
+        format!("{:#x}", b.offset),
+        nf.int(b.size)
+    );
+    if let Some(decoded) = &b.decoded {
+        println!("        -> {}", decoded);
     }
-    let mut limited = f.take(len);
+}
 
-    // Extract version and flags from the box if it's a FullBox
-    let (version, flags) = match &b.kind {
-        mp4box::boxes::NodeKind::FullBox { version, flags, .. } => (Some(*version), Some(*flags)),
-        _ => (None, None),
+fn repair(path: &str, size_to_eof: bool) -> anyhow::Result<()> {
+    let mode = if size_to_eof {
+        RepairMode::SizeToEof
+    } else {
+        RepairMode::FitSize
     };
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    match repair_truncated(&mut file, mode)? {
+        Some(fix) => eprintln!(
+            "repaired {} at {:#x}: declared {} bytes, {} present, size field set to {}",
+            fix.typ, fix.offset, fix.declared_size, fix.actual_size, fix.new_size
+        ),
+        None => eprintln!("no truncated box found"),
+    }
+    Ok(())
+}
 
-    if let Some(res) = reg.decode(&key, &mut limited, &b.hdr, version, flags) {
-        match res {
-            Ok(BoxValue::Text(s)) => Some(s),
-            Ok(BoxValue::Bytes(bytes)) => Some(format!("{} bytes", bytes.len())),
-            Ok(BoxValue::Structured(data)) => Some(format!("structured: {:?}", data)),
-            Err(e) => Some(format!("[decode error: {}]", e)),
+// ---------- Fragment summaries ----------
+
+/// One-line summary for every top-level moof: sequence number, tracks,
+/// sample count, duration per track and the size of the mdat that follows.
+fn fragment_summaries(
+    tree: &[mp4box::Box],
+    file_len: u64,
+    nf: &NumberFormat,
+) -> HashMap<u64, String> {
+    let header_sizes: HashMap<u64, u64> = tree.iter().map(|b| (b.offset, b.header_size)).collect();
+    let mut notes = HashMap::new();
+    for f in fragment_index(tree, file_len) {
+        let mut parts = Vec::new();
+        if let Some(seq) = f.sequence_number {
+            parts.push(format!("seq={seq}"));
+        }
+        let ids: Vec<String> = f.tracks.iter().map(|t| t.track_id.to_string()).collect();
+        parts.push(format!("tracks={}", ids.join(",")));
+        parts.push(format!(
+            "samples={}",
+            nf.int(f.tracks.iter().map(|t| t.sample_count).sum::<u64>())
+        ));
+        let durations: Vec<String> = f
+            .tracks
+            .iter()
+            .map(|t| format!("{}:{}", t.track_id, nf.int(t.duration)))
+            .collect();
+        parts.push(format!("duration={}", durations.join(",")));
+        if let (Some(offset), Some(size)) = (f.mdat_offset, f.mdat_size) {
+            let payload = size.saturating_sub(header_sizes[&offset]);
+            parts.push(format!("mdat={} bytes", nf.int(payload)));
         }
+
+        notes.insert(f.moof_offset, parts.join(" "));
+    }
+    notes
+}
+
+fn display_type(h: &mp4box::boxes::BoxHeader) -> String {
+    if &h.typ.0 == b"uuid" {
+        let u = h.uuid.unwrap_or([0u8; 16]);
+        format!("uuid:{:02x?}", u)
+    } else if h.typ.is_printable() {
+        h.typ.to_string()
     } else {
-        None
+        // The lossy form alone cannot be passed back to --raw or --filter
+        format!("{} (0x{})", h.typ, h.typ.to_hex())
     }
 }
 
-fn maybe_decode(f: &mut File, b: &BoxRef, reg: &Registry) -> anyhow::Result<()> {
-    if let Some(s) = decode_value(f, b, reg) {
-        println!("        -> {}", s);
+// ---------- Decoding helpers (shared by text + JSON) ----------
+
+/// Attributes such as handler are only known once boxes are decoded.
+fn attribute_filter(args: &Args) -> bool {
+    args.filter.as_deref().is_some_and(|p| p.contains("[@"))
+}
+
+/// Parse with the library, whose decoders run under the [`ParseOptions`]
+/// limits and which links trak and moof boxes to their media ranges.
+fn parse_tree<R: Read + Seek>(
+    f: &mut R,
+    file_len: u64,
+    args: &Args,
+    lenient: bool,
+) -> anyhow::Result<(Vec<mp4box::Box>, Vec<ParseWarning>)> {
+    f.seek(SeekFrom::Start(0))?;
+    let options = ParseOptions::new()
+        .decode(args.decode || attribute_filter(args))
+        .lenient(lenient)
+        .recover(args.recover);
+    get_boxes_with_options(f, file_len, &options)
+}
+
+fn collect_decoded(boxes: &[mp4box::Box], out: &mut HashMap<(u64, String), String>) {
+    for b in boxes {
+        if let Some(s) = &b.decoded {
+            out.insert((b.offset, b.typ_hex.clone()), s.clone());
+        }
+        collect_decoded(b.children.as_deref().unwrap_or_default(), out);
     }
-    Ok(())
 }
 
 // ---------- Raw dump ----------
 
-fn dump_raw(f: &mut File, boxes: &[BoxRef], sel: &str, limit: usize) -> anyhow::Result<()> {
+fn dump_raw<R: Read + Seek>(
+    f: &mut R,
+    boxes: &[BoxRef],
+    sel: &str,
+    limit: usize,
+) -> anyhow::Result<()> {
     let mut matches = Vec::new();
     select_boxes(boxes, sel, &mut matches);
     for (i, (off, len, hdr)) in matches.into_iter().enumerate() {
@@ -296,10 +540,9 @@ fn select_boxes(list: &[BoxRef], sel: &str, out: &mut Vec<(u64, u64, mp4box::box
             } else {
                 false
             }
-        } else if sel.len() == 4 {
-            b.hdr.typ == FourCC::from_str(sel).unwrap()
         } else {
-            false
+            // Counts chars, not UTF-8 bytes, so "©too" and "0x..." match too
+            FourCC::from_str(sel).is_ok_and(|typ| b.hdr.typ == typ)
         };
 
         if matches_sel {
@@ -320,6 +563,10 @@ fn select_boxes(list: &[BoxRef], sel: &str, out: &mut Vec<(u64, u64, mp4box::box
                 NodeKind::Unknown {
                     data_offset,
                     data_len,
+                }
+                | NodeKind::Gap {
+                    data_offset,
+                    data_len,
                 } => {
                     out.push((*data_offset, *data_len, b.hdr.clone()));
                 }
@@ -334,72 +581,7 @@ fn select_boxes(list: &[BoxRef], sel: &str, out: &mut Vec<(u64, u64, mp4box::box
             }
         }
 
-        if let NodeKind::Container(kids) = &b.kind {
-            select_boxes(kids, sel, out);
-        }
-    }
-}
-
-// ---------- Filter path: moov.trak[0].mdia.minf.stbl ----------
-
-fn select_by_path<'a>(roots: &'a [BoxRef], path: &str) -> Vec<&'a BoxRef> {
-    let mut current: Vec<&'a BoxRef> = roots.iter().collect();
-
-    for (depth, seg) in path.split('.').enumerate() {
-        let (name, idx) = parse_segment(seg);
-        let fourcc = FourCC::from_str(name).unwrap_or(FourCC(*b"????"));
-        let mut next = Vec::new();
-
-        if depth == 0 {
-            // match at top level
-            let mut matches: Vec<&BoxRef> = current
-                .into_iter()
-                .filter(|b| b.hdr.typ == fourcc)
-                .collect();
-            if let Some(i) = idx {
-                if i < matches.len() {
-                    next.push(matches[i]);
-                }
-            } else {
-                next.append(&mut matches);
-            }
-        } else {
-            // match in children of current set
-            for b in &current {
-                if let NodeKind::Container(kids) = &b.kind {
-                    let mut matches: Vec<&BoxRef> =
-                        kids.iter().filter(|c| c.hdr.typ == fourcc).collect();
-                    if let Some(i) = idx {
-                        if i < matches.len() {
-                            next.push(matches[i]);
-                        }
-                    } else {
-                        next.append(&mut matches);
-                    }
-                }
-            }
-        }
-
-        current = next;
-        if current.is_empty() {
-            break;
-        }
-    }
-
-    current
-}
-
-fn parse_segment(seg: &str) -> (&str, Option<usize>) {
-    if let Some(l) = seg.find('[') {
-        let name = &seg[..l];
-        if let Some(r) = seg[l + 1..].find(']') {
-            let idx_str = &seg[l + 1..l + 1 + r];
-            let idx = idx_str.parse::<usize>().ok();
-            return (name, idx);
-        }
-        (name, None)
-    } else {
-        (seg, None)
+        select_boxes(b.kind.children(), sel, out);
     }
 }
 
@@ -414,6 +596,7 @@ struct JsonBox {
     payload_size: Option<u64>,
 
     typ: String,
+    typ_hex: String,
     uuid: Option<String>,
     version: Option<u8>,
     flags: Option<u32>,
@@ -421,88 +604,32 @@ struct JsonBox {
     full_name: String,
     decoded: Option<String>,
     children: Option<Vec<JsonBox>>,
+    /// Media bytes used by a moof or trak (with --decode)
+    media_ranges: Option<Vec<ByteRange>>,
 }
 
-fn payload_geometry(b: &BoxRef) -> Option<(u64, u64)> {
-    match &b.kind {
-        NodeKind::FullBox {
-            data_offset,
-            data_len,
-            ..
-        } => Some((*data_offset, *data_len)),
-        NodeKind::Leaf { .. } | NodeKind::Unknown { .. } => {
-            let hdr = &b.hdr;
-            if hdr.size == 0 {
-                return None;
-            }
-            let off = hdr.start + hdr.header_size;
-            let len = hdr.size.saturating_sub(hdr.header_size);
-            if len == 0 {
-                return None;
-            }
-            Some((off, len))
-        }
-        NodeKind::Container(_) => None,
-    }
-}
-
-fn build_json_for_box(f: &mut File, b: &BoxRef, decode: bool, reg: &Registry) -> JsonBox {
-    let hdr = &b.hdr;
-    let uuid_str = hdr
-        .uuid
-        .map(|u| u.iter().map(|b| format!("{:02x}", b)).collect::<String>());
-
-    let kb = mp4box::known_boxes::KnownBox::from(hdr.typ);
-    let full_name = kb.full_name().to_string();
-
-    let header_size = hdr.header_size;
-    let (payload_offset, payload_size) = payload_geometry(b)
-        .map(|(off, len)| (Some(off), Some(len)))
-        .unwrap_or((None, None));
-
-    let (version, flags, kind_str, children) = match &b.kind {
-        NodeKind::FullBox { version, flags, .. } => {
-            (Some(*version), Some(*flags), "full".to_string(), None)
+impl JsonBox {
+    fn new(b: &mp4box::Box, decode: bool) -> Self {
+        JsonBox {
+            offset: b.offset,
+            size: b.size,
+            header_size: b.header_size,
+            payload_offset: b.payload_offset,
+            payload_size: b.payload_size,
+
+            typ: b.typ.clone(),
+            typ_hex: b.typ_hex.clone(),
+            uuid: b.uuid.clone(),
+            version: b.version,
+            flags: b.flags,
+            kind: b.kind.clone(),
+            full_name: b.full_name.clone(),
+            decoded: b.decoded.clone().filter(|_| decode),
+            children: b
+                .children
+                .as_ref()
+                .map(|kids| kids.iter().map(|c| JsonBox::new(c, decode)).collect()),
+            media_ranges: b.media_ranges.clone().filter(|_| decode),
         }
-        NodeKind::Leaf { .. } => (None, None, "leaf".to_string(), None),
-        NodeKind::Unknown { .. } => (None, None, "unknown".to_string(), None),
-        NodeKind::Container(kids) => {
-            let child_nodes = kids
-                .iter()
-                .map(|c| build_json_for_box(f, c, decode, reg))
-                .collect();
-            (None, None, "container".to_string(), Some(child_nodes))
-        }
-    };
-
-    let decoded = if decode {
-        decode_value(f, b, reg)
-    } else {
-        None
-    };
-
-    JsonBox {
-        offset: hdr.start,
-        size: hdr.size,
-        header_size,
-        payload_offset,
-        payload_size,
-
-        typ: hdr.typ.to_string(),
-        uuid: uuid_str,
-        version,
-        flags,
-        kind: kind_str,
-        full_name,
-        decoded,
-        children,
     }
 }
-
-fn is_container(h: &BoxHeader) -> bool {
-    KnownBox::from(h.typ).is_container()
-}
-
-fn is_full_box(h: &BoxHeader) -> bool {
-    KnownBox::from(h.typ).is_full_box()
-}
@@ -1,6 +1,7 @@
 use clap::Parser;
-use mp4box::{Box, get_boxes};
+use mp4box::{MovieSummary, get_boxes, numfmt::NumberFormat, summarize};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -12,6 +13,15 @@ struct Args {
     /// Output as JSON instead of human-readable text
     #[arg(long)]
     json: bool,
+
+    /// Group digits and show sizes the way the locale does, instead of
+    /// plain numbers
+    #[arg(long)]
+    human: bool,
+
+    /// Emit JSON using ffprobe `-show_streams` field names
+    #[arg(long)]
+    compare_ffprobe: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -19,7 +29,7 @@ struct TrackInfo {
     index: usize,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    track_type: Option<String>, // "video" / "audio" / "other"
+    track_type: Option<String>, // "video" / "audio" / "subtitle" / "other"
 
     #[serde(skip_serializing_if = "Option::is_none")]
     codec: Option<String>, // e.g. "avc1", "hvc1", "mp4a"
@@ -30,6 +40,12 @@ struct TrackInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     height: Option<u32>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sample_rate: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channels: Option<u16>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     timescale: Option<u32>,
 
@@ -41,6 +57,29 @@ struct TrackInfo {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     language: Option<String>,
+
+    /// BCP-47 tag from elng, else from the mdhd language
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language_tag: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sample_count: Option<u32>,
+
+    /// Video only: frames per second derived from stts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frame_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nominal_frame_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    variable_frame_rate: Option<bool>,
+
+    /// Sum of all sample sizes from stsz
+    #[serde(skip_serializing_if = "Option::is_none")]
+    media_bytes: Option<u64>,
+
+    /// Average bits per second
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bitrate: Option<u64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -73,269 +112,158 @@ fn main() -> anyhow::Result<()> {
     let size = file.metadata()?.len();
 
     let boxes = get_boxes(&mut file, size, /*decode=*/ true)?;
-    let mut info = MediaInfo {
-        file: path.display().to_string(),
-        major_brand: None,
-        minor_version: None,
-        compatible_brands: Vec::new(),
-        movie_timescale: None,
-        movie_duration_ticks: None,
-        movie_duration_seconds: None,
-        tracks: Vec::new(),
-    };
-
-    // Walk top-level boxes: ftyp, moov, etc.
-    for b in &boxes {
-        match b.typ.as_str() {
-            "ftyp" => parse_ftyp(b, &mut info),
-            "moov" => parse_moov(b, &mut info),
-            _ => {}
-        }
-    }
+    let info = MediaInfo::new(path.display().to_string(), summarize(&boxes));
 
-    if args.json {
+    if args.compare_ffprobe {
+        println!("{}", serde_json::to_string_pretty(&to_ffprobe(&info))?);
+    } else if args.json {
         println!("{}", serde_json::to_string_pretty(&info)?);
     } else {
-        print_human(&info);
+        print_human(&info, &NumberFormat::for_cli(args.human));
     }
 
     Ok(())
 }
 
-fn parse_ftyp(b: &Box, info: &mut MediaInfo) {
-    let decoded = match &b.decoded {
-        Some(s) => s,
-        None => return,
-    };
-
-    // Example decoded string:
-    // "major=isom minor=512 compatible=[\"isom\", \"iso2\", \"avc1\", \"mp41\"]"
-    if let Some(major) = parse_string_field(decoded, "major=") {
-        info.major_brand = Some(major);
-    }
-    if let Some(minor) = parse_u32_field(decoded, "minor=") {
-        info.minor_version = Some(minor);
-    }
-    if let Some(compat) = parse_compatible_brands(decoded) {
-        info.compatible_brands = compat;
-    }
-}
-
-fn parse_moov(b: &Box, info: &mut MediaInfo) {
-    let children = match &b.children {
-        Some(c) => c,
-        None => return,
-    };
-
-    // mvhd for overall movie duration
-    if let Some(mvhd) = children.iter().find(|c| c.typ == "mvhd")
-        && let Some(decoded) = &mvhd.decoded
-    {
-        // Example: "timescale=600000 duration=65536"
-        if let Some(ts) = parse_u32_field(decoded, "timescale=") {
-            info.movie_timescale = Some(ts);
+impl MediaInfo {
+    fn new(file: String, summary: MovieSummary) -> Self {
+        let tracks = summary
+            .tracks
+            .into_iter()
+            .map(|t| TrackInfo {
+                index: t.index,
+                track_type: t.kind.map(|k| k.as_str().to_string()),
+                codec: t.codec,
+                width: t.width,
+                height: t.height,
+                sample_rate: t.sample_rate,
+                channels: t.channels,
+                timescale: t.timescale,
+                duration_ticks: t.duration,
+                duration_seconds: t.duration_seconds,
+                language: t.language,
+                language_tag: t.language_tag,
+                sample_count: t.sample_count,
+                frame_rate: t.frame_rate.map(|fr| fr.average),
+                nominal_frame_rate: t.frame_rate.map(|fr| fr.nominal),
+                variable_frame_rate: t.frame_rate.map(|fr| fr.variable),
+                media_bytes: t.media_bytes,
+                bitrate: t.bitrate,
+            })
+            .collect();
+        MediaInfo {
+            file,
+            major_brand: summary.major_brand,
+            minor_version: summary.minor_version,
+            compatible_brands: summary.compatible_brands,
+            movie_timescale: summary.timescale,
+            movie_duration_ticks: summary.duration,
+            movie_duration_seconds: summary.duration_seconds,
+            tracks,
         }
-        if let Some(dur) = parse_u64_field(decoded, "duration=") {
-            info.movie_duration_ticks = Some(dur);
-            if let Some(ts) = info.movie_timescale {
-                info.movie_duration_seconds = Some(dur as f64 / ts as f64);
-            }
-        }
-    }
-
-    // trak boxes for per-track timing
-    for (i, trak) in children.iter().filter(|c| c.typ == "trak").enumerate() {
-        parse_trak(trak, i + 1, info);
     }
 }
 
-fn parse_trak(trak: &Box, index: usize, info: &mut MediaInfo) {
-    let mut ti = TrackInfo {
-        index,
-        track_type: None,
-        codec: None,
-        width: None,
-        height: None,
-        timescale: None,
-        duration_ticks: None,
-        duration_seconds: None,
-        language: None,
-    };
-
-    // tkhd at the trak level: possible width/height
-    if let Some(tkhd) = find_child(trak, "tkhd")
-        && let Some(decoded) = &tkhd.decoded
-    {
-        // For “normal” tkhd decoders you’ll get something like:
-        // "track_id=1 duration=... width=1920 height=1080"
-        if let Some(w) = parse_u32_field(decoded, "width=") {
-            ti.width = Some(w);
-        }
-        if let Some(h) = parse_u32_field(decoded, "height=") {
-            ti.height = Some(h);
-        }
-    }
-
-    // mdia -> mdhd + hdlr + minf
-    let mdia = match find_child(trak, "mdia") {
-        Some(m) => m,
-        None => {
-            info.tracks.push(ti);
-            return;
-        }
-    };
-
-    // mdhd: timescale / duration / language
-    if let Some(mdhd) = find_child(mdia, "mdhd") {
-        // Try structured data first
-        if let Some(mp4box::registry::StructuredData::MediaHeader(mdhd_data)) =
-            &mdhd.structured_data
-        {
-            ti.timescale = Some(mdhd_data.timescale);
-            ti.duration_ticks = Some(mdhd_data.duration as u64);
-            ti.duration_seconds = Some(mdhd_data.duration as f64 / mdhd_data.timescale as f64);
-            ti.language = Some(mdhd_data.language.clone());
-        }
-        // Fallback to text parsing
-        else if let Some(decoded) = &mdhd.decoded {
-            if let Some(ts) = parse_u32_field(decoded, "timescale=") {
-                ti.timescale = Some(ts);
-            }
-            if let Some(dur) = parse_u64_field(decoded, "duration=") {
-                ti.duration_ticks = Some(dur);
-                if let Some(ts) = ti.timescale {
-                    ti.duration_seconds = Some(dur as f64 / ts as f64);
-                }
-            }
-            if let Some(lang) = parse_string_field(decoded, "language=") {
-                ti.language = Some(lang);
-            }
-        }
-    }
-
-    // hdlr: determine track type (video/audio/other)
-    if let Some(hdlr) = find_child(mdia, "hdlr") {
-        // Try structured data first
-        if let Some(mp4box::registry::StructuredData::HandlerReference(hdlr_data)) =
-            &hdlr.structured_data
-        {
-            let tt = match hdlr_data.handler_type.as_str() {
-                "vide" => "video",
-                "soun" => "audio",
-                _ => "other",
-            };
-            ti.track_type = Some(tt.to_string());
-        }
-        // Fallback to text parsing
-        else if let Some(decoded) = &hdlr.decoded {
-            // Ideally your hdlr decoder now prints "handler=vide name=..."
-            if let Some(handler) = parse_string_field(decoded, "handler=") {
-                let tt = match handler.as_str() {
-                    "vide" => "video",
-                    "soun" => "audio",
-                    _ => "other",
-                };
-                ti.track_type = Some(tt.to_string());
-            }
-        }
-    }
-
-    // minf -> stbl -> stsd: codec + width/height from decoded text
-    if let Some(minf) = find_child(mdia, "minf")
-        && let Some(stbl) = find_child(minf, "stbl")
-        && let Some(stsd) = find_child(stbl, "stsd")
-        && let Some(decoded) = &stsd.decoded
-    {
-        // codec
-        if let Some(c) = parse_string_field(decoded, "codec=") {
-            ti.codec = Some(c.clone());
-
-            // If no type from hdlr, infer from codec
-            if ti.track_type.is_none() {
-                let tt = match c.as_str() {
-                    "avc1" | "hvc1" | "hev1" | "vp09" | "av01" => "video",
-                    "mp4a" | "ac-3" | "ec-3" | "Opus" => "audio",
-                    _ => "other",
-                };
-                ti.track_type = Some(tt.to_string());
-            }
-        }
-
-        // width / height (for video)
-        if let Some(w) = parse_u32_field(decoded, "width=") {
-            ti.width = Some(w);
-        }
-        if let Some(h) = parse_u32_field(decoded, "height=") {
-            ti.height = Some(h);
-        }
-    }
+// ---- ffprobe-compatible output ---------------------------------------
 
-    info.tracks.push(ti);
-}
-
-fn find_child<'a>(parent: &'a Box, typ: &str) -> Option<&'a Box> {
-    parent
-        .children
-        .as_ref()
-        .and_then(|kids| kids.iter().find(|c| c.typ == typ))
-}
-
-// ---- tiny string parsers over the `decoded` text --------------------
-
-fn parse_u32_field(s: &str, key: &str) -> Option<u32> {
-    parse_u64_field(s, key).and_then(|v| u32::try_from(v).ok())
+#[derive(Debug, Serialize)]
+struct FfprobeOutput {
+    streams: Vec<FfprobeStream>,
 }
 
-fn parse_u64_field(s: &str, key: &str) -> Option<u64> {
-    let idx = s.find(key)?;
-    let rest = &s[idx + key.len()..];
-    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
-    if digits.is_empty() {
-        None
-    } else {
-        digits.parse().ok()
-    }
+/// Subset of ffprobe's `-show_streams` fields whose meaning matches ours.
+/// Like ffprobe, durations, bit rates and frame counts are strings.
+#[derive(Debug, Serialize)]
+struct FfprobeStream {
+    index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    codec_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    codec_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    codec_tag_string: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ts: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sample_rate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channels: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bit_rate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nb_frames: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<String, String>,
 }
 
-fn parse_string_field(s: &str, key: &str) -> Option<String> {
-    let idx = s.find(key)?;
-    let mut rest = &s[idx + key.len()..];
-
-    // Trim leading whitespace
-    rest = rest.trim_start();
-
-    // Take until next space or end
-    let token: String = rest.chars().take_while(|c| !c.is_whitespace()).collect();
-
-    if token.is_empty() {
-        None
-    } else {
-        Some(token.trim_matches('"').to_string())
-    }
+fn to_ffprobe(info: &MediaInfo) -> FfprobeOutput {
+    let streams = info
+        .tracks
+        .iter()
+        .map(|t| {
+            let mut tags = BTreeMap::new();
+            if let Some(lang) = &t.language {
+                tags.insert("language".to_string(), lang.clone());
+            }
+            FfprobeStream {
+                // ffprobe numbers streams from 0
+                index: t.index - 1,
+                codec_name: t.codec.as_deref().map(ffprobe_codec_name),
+                codec_type: t.track_type.as_ref().map(|tt| match tt.as_str() {
+                    "video" | "audio" | "subtitle" => tt.clone(),
+                    _ => "data".to_string(),
+                }),
+                codec_tag_string: t.codec.clone(),
+                width: t.width,
+                height: t.height,
+                time_base: t.timescale.map(|ts| format!("1/{ts}")),
+                duration_ts: t.duration_ticks,
+                duration: t.duration_seconds.map(|s| format!("{:.6}", s)),
+                sample_rate: t.sample_rate.map(|r| r.to_string()),
+                channels: t.channels,
+                bit_rate: t.bitrate.map(|b| b.to_string()),
+                nb_frames: t.sample_count.map(|n| n.to_string()),
+                tags,
+            }
+        })
+        .collect();
+    FfprobeOutput { streams }
 }
 
-fn parse_compatible_brands(s: &str) -> Option<Vec<String>> {
-    // e.g. compatible=["isom", "iso2", "avc1", "mp41"]
-    let idx = s.find("compatible=[")?;
-    let rest = &s[idx + "compatible=[".len()..];
-    let end = rest.find(']')?;
-    let inside = &rest[..end];
-    if inside.trim().is_empty() {
-        return Some(Vec::new());
+/// Map a sample entry 4CC to ffprobe's codec_name.
+fn ffprobe_codec_name(fourcc: &str) -> String {
+    match fourcc {
+        "avc1" | "avc3" => "h264",
+        "hvc1" | "hev1" => "hevc",
+        "av01" => "av1",
+        "vp09" => "vp9",
+        "vp08" => "vp8",
+        "mp4v" => "mpeg4",
+        "mp4a" => "aac",
+        "ac-3" => "ac3",
+        "ec-3" => "eac3",
+        "Opus" | "opus" => "opus",
+        "fLaC" | "flac" => "flac",
+        "alac" => "alac",
+        "tx3g" => "mov_text",
+        "wvtt" => "webvtt",
+        "stpp" => "ttml",
+        other => other,
     }
-    let mut out = Vec::new();
-    for part in inside.split(',') {
-        let trimmed = part.trim().trim_matches('"');
-        if !trimmed.is_empty() {
-            out.push(trimmed.to_string());
-        }
-    }
-    Some(out)
+    .to_string()
 }
 
 // ---- human-readable output -----------------------------------------
 
-fn print_human(info: &MediaInfo) {
+fn print_human(info: &MediaInfo, nf: &NumberFormat) {
     println!("File: {}", info.file);
     if let Some(major) = &info.major_brand {
         println!("Major brand: {}", major);
@@ -349,7 +277,12 @@ fn print_human(info: &MediaInfo) {
 
     if let (Some(ts), Some(dur)) = (info.movie_timescale, info.movie_duration_ticks) {
         let sec = dur as f64 / ts as f64;
-        println!("Movie duration: {} ticks @ {} -> {:.3} s", dur, ts, sec);
+        println!(
+            "Movie duration: {} ticks @ {} -> {}",
+            nf.int(dur),
+            nf.int(ts),
+            nf.seconds(sec)
+        );
     }
 
     if info.tracks.is_empty() {
@@ -371,19 +304,40 @@ fn print_human(info: &MediaInfo) {
             println!("    size: {}x{}", w, h);
         }
 
+        if let Some(rate) = t.sample_rate {
+            println!("    sample_rate: {} Hz", nf.int(rate));
+        }
+        if let Some(ch) = t.channels {
+            println!("    channels: {}", ch);
+        }
         if let Some(ts) = t.timescale {
-            println!("    timescale: {}", ts);
+            println!("    timescale: {}", nf.int(ts));
         }
         if let Some(dur) = t.duration_ticks {
             if let Some(sec) = t.duration_seconds {
-                println!("    duration: {} ticks -> {:.3} s", dur, sec);
+                println!("    duration: {} ticks -> {}", nf.int(dur), nf.seconds(sec));
             } else {
-                println!("    duration: {} ticks", dur);
+                println!("    duration: {} ticks", nf.int(dur));
+            }
+        }
+        if let Some(fps) = t.frame_rate {
+            let mut line = format!("    frame_rate: {} fps", nf.float(fps, 3));
+            if let Some(nominal) = t.nominal_frame_rate
+                && t.variable_frame_rate == Some(true)
+            {
+                line.push_str(&format!(" (variable, nominal {})", nf.float(nominal, 3)));
             }
+            println!("{}", line);
+        }
+        if let Some(bps) = t.bitrate {
+            println!("    bitrate: {} bps", nf.int(bps));
         }
         if let Some(lang) = &t.language {
             println!("    language: {}", lang);
         }
+        if let Some(tag) = &t.language_tag {
+            println!("    language_tag: {}", tag);
+        }
     }
 }
 
@@ -392,44 +346,45 @@ mod tests {
     use super::*;
 
     #[test]
-    fn parse_u64_field_extracts_number() {
-        let s = "timescale=600000 duration=65536";
-        assert_eq!(parse_u64_field(s, "timescale="), Some(600000));
-        assert_eq!(parse_u64_field(s, "duration="), Some(65536));
-        assert_eq!(parse_u64_field(s, "missing="), None);
-    }
-
-    #[test]
-    fn parse_u32_field_clamps_to_u32() {
-        let s = "timescale=12345";
-        assert_eq!(parse_u32_field(s, "timescale="), Some(12345));
-    }
-
-    #[test]
-    fn parse_string_field_basic() {
-        let s = "major=isom minor=512";
-        assert_eq!(parse_string_field(s, "major="), Some("isom".to_string()));
-        assert_eq!(parse_string_field(s, "minor="), Some("512".to_string()));
-        assert_eq!(parse_string_field(s, "missing="), None);
-    }
-
-    #[test]
-    fn parse_string_field_trims_quotes() {
-        let s = r#"language="und""#;
-        assert_eq!(parse_string_field(s, "language="), Some("und".to_string()));
-    }
-
-    #[test]
-    fn parse_compatible_brands_parses_list() {
-        let s = r#"major=isom minor=512 compatible=["isom", "iso2", "avc1", "mp41"]"#;
-        let brands = parse_compatible_brands(s).unwrap();
-        assert_eq!(brands, vec!["isom", "iso2", "avc1", "mp41"]);
-    }
-
-    #[test]
-    fn parse_compatible_brands_empty() {
-        let s = r#"compatible=[]"#;
-        let brands = parse_compatible_brands(s).unwrap();
-        assert!(brands.is_empty());
+    fn ffprobe_mapping_uses_ffprobe_names() {
+        let info = MediaInfo {
+            file: "x.mp4".into(),
+            major_brand: None,
+            minor_version: None,
+            compatible_brands: Vec::new(),
+            movie_timescale: None,
+            movie_duration_ticks: None,
+            movie_duration_seconds: None,
+            tracks: vec![TrackInfo {
+                index: 1,
+                track_type: Some("video".into()),
+                codec: Some("avc1".into()),
+                width: Some(1920),
+                height: Some(1080),
+                sample_rate: None,
+                channels: None,
+                timescale: Some(90000),
+                duration_ticks: Some(900000),
+                duration_seconds: Some(10.0),
+                language: Some("und".into()),
+                language_tag: Some("und".into()),
+                sample_count: Some(300),
+                frame_rate: None,
+                nominal_frame_rate: None,
+                variable_frame_rate: None,
+                media_bytes: Some(1_250_000),
+                bitrate: Some(1_000_000),
+            }],
+        };
+        let out = serde_json::to_value(to_ffprobe(&info)).unwrap();
+        let s = &out["streams"][0];
+        assert_eq!(s["index"], 0);
+        assert_eq!(s["codec_name"], "h264");
+        assert_eq!(s["codec_type"], "video");
+        assert_eq!(s["time_base"], "1/90000");
+        assert_eq!(s["duration"], "10.000000");
+        assert_eq!(s["bit_rate"], "1000000");
+        assert_eq!(s["nb_frames"], "300");
+        assert_eq!(s["tags"]["language"], "und");
     }
 }
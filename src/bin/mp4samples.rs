@@ -2,7 +2,17 @@ use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
-use mp4box::{SampleInfo, get_boxes};
+use mp4box::{
+    FragmentTiming, SampleInfo, Track, TrackSamples,
+    elementary::export_elementary_stream,
+    get_boxes,
+    numfmt::NumberFormat,
+    stats::size_stats,
+    track_samples_from_boxes,
+    walk::{Children, walk},
+};
+use std::io::Write;
+use std::ops::ControlFlow;
 
 #[derive(Debug, Parser)]
 #[command(
@@ -36,6 +46,20 @@ struct Args {
     /// Verbose output with sample table statistics
     #[arg(short, long)]
     verbose: bool,
+
+    /// Print sample size statistics and a histogram instead of the sample list
+    #[arg(long)]
+    stats: bool,
+
+    /// Group digits and show sizes the way the locale does, instead of
+    /// plain numbers
+    #[arg(long)]
+    human: bool,
+
+    /// Write the --track-id track to this file as an elementary stream
+    /// (Annex B H.264/H.265 or ADTS AAC)
+    #[arg(long, value_name = "OUT", requires = "track_id")]
+    export: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -46,7 +70,9 @@ struct TrackInfo {
     duration: u64,
     sample_count: u32,
     samples: Vec<SampleInfo>,
+    fragments: Vec<FragmentTiming>,
     // Sample table statistics
+    stsz_entries: u32,
     stts_entries: u32,
     stsc_entries: u32,
     stco_entries: u32,
@@ -62,10 +88,22 @@ fn main() -> Result<()> {
     // Parse with structured decoding enabled
     let boxes = get_boxes(&mut file, size, true)?;
 
-    if args.tables {
+    if let (Some(out), Some(track_id)) = (&args.export, args.track_id) {
+        let mut w = std::io::BufWriter::new(std::fs::File::create(out)?);
+        let summary = export_elementary_stream(&mut file, &boxes, track_id, &mut w)?;
+        w.flush()?;
+        eprintln!(
+            "Wrote {} {} samples ({} bytes) to {}",
+            summary.sample_count,
+            summary.format.extension(),
+            summary.bytes_written,
+            out.display()
+        );
+    } else if args.tables {
         print_sample_tables(&boxes, &args)?;
     } else {
-        let tracks = extract_track_samples(&boxes)?;
+        let timed = track_samples_from_boxes(&boxes)?;
+        let tracks = extract_track_samples(&boxes, &timed)?;
 
         if args.json {
             print_json(&tracks, &args)?;
@@ -77,380 +115,129 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn extract_track_samples(boxes: &[mp4box::Box]) -> Result<Vec<TrackInfo>> {
+fn extract_track_samples(boxes: &[mp4box::Box], timed: &[TrackSamples]) -> Result<Vec<TrackInfo>> {
     let mut tracks = Vec::new();
     let mut track_counter = 1;
 
-    // Find moov box
-    for box_info in boxes {
-        if box_info.typ == "moov"
-            && let Some(children) = &box_info.children
-        {
-            // Find trak boxes
-            for trak_box in children.iter().filter(|b| b.typ == "trak") {
-                if let Some(track_info) = extract_single_track(trak_box, track_counter)? {
-                    // Only add track if it has samples
-                    if track_info.sample_count > 0 {
-                        tracks.push(track_info);
-                        track_counter += 1;
-                    }
-                }
-            }
+    for track in mp4box::tracks(boxes) {
+        if let Some(track_info) = extract_single_track(&track, track_counter, timed) {
+            tracks.push(track_info);
+            track_counter += 1;
         }
     }
 
     Ok(tracks)
 }
 
-fn extract_single_track(trak_box: &mp4box::Box, track_counter: u32) -> Result<Option<TrackInfo>> {
-    // Try to parse actual track metadata
-    let track_id = extract_track_id(trak_box).unwrap_or(track_counter);
-    let handler_type = extract_handler_type(trak_box).unwrap_or_else(|| "vide".to_string());
-    let (timescale, duration) = extract_media_info(trak_box);
-
-    // Find stbl box for sample tables
-    let stbl_box = find_stbl_box(trak_box);
-    if stbl_box.is_none() {
-        return Ok(None);
-    }
-
-    let stbl = stbl_box.unwrap();
-
-    // Try to extract sample table data, return None if no samples found
-    let sample_tables = match extract_sample_table_data(stbl) {
-        Ok(data) => data,
-        Err(_) => return Ok(None), // Skip tracks without valid sample data
-    };
-
-    // Build samples from structured data
-    let samples = build_samples(&sample_tables, timescale)?;
+fn extract_single_track(
+    track: &Track,
+    track_counter: u32,
+    timed: &[TrackSamples],
+) -> Option<TrackInfo> {
+    let track_id = track.track_id().unwrap_or(track_counter);
+    let handler_type = track
+        .hdlr()
+        .map_or_else(|| "vide".to_string(), |h| h.handler_type.clone());
+    // Default values - common for video
+    let (timescale, duration) = track
+        .mdhd()
+        .map_or((12288, 0), |m| (m.timescale, m.duration));
+
+    // Tracks without a sample table are skipped
+    track.stbl()?;
+
+    // Sample timing comes from the library: stbl tables plus any fragments
+    let (samples, fragments) = timed
+        .iter()
+        .find(|t| t.track_id == track_id)
+        .map(|t| (t.samples.clone(), t.fragments.clone()))
+        .unwrap_or_default();
     let sample_count = samples.len() as u32;
 
     // Skip empty tracks
     if sample_count == 0 {
-        return Ok(None);
+        return None;
     }
 
-    Ok(Some(TrackInfo {
+    Some(TrackInfo {
         track_id,
         handler_type,
         timescale,
         duration,
         sample_count,
         samples,
-        stts_entries: sample_tables.stts_entries,
-        stsc_entries: sample_tables.stsc_entries,
-        stco_entries: sample_tables.stco_entries,
-        keyframe_count: sample_tables.keyframe_count,
-    }))
+        fragments,
+        stsz_entries: track.stsz().map_or(0, |d| d.sample_count),
+        stts_entries: track.stts().map_or(0, |d| d.entry_count),
+        stsc_entries: track.stsc().map_or(0, |d| d.entry_count),
+        stco_entries: track
+            .stco()
+            .map(|d| d.entry_count)
+            .or(track.co64().map(|d| d.entry_count))
+            .unwrap_or(0),
+        keyframe_count: track.stss().map_or(0, |d| d.entry_count),
+    })
 }
 
-#[derive(Debug, Default)]
-struct SampleTableData {
-    stts_entries: u32,
-    stsc_entries: u32,
-    stco_entries: u32,
-    keyframe_count: u32,
-    sample_count: u32,
-    sample_sizes: Vec<u32>,
-}
-
-fn find_stbl_box(trak_box: &mp4box::Box) -> Option<&mp4box::Box> {
-    // Navigate to mdia/minf/stbl
-    if let Some(children) = &trak_box.children {
-        for child in children {
-            if child.typ == "mdia"
-                && let Some(mdia_children) = &child.children
-            {
-                for mdia_child in mdia_children {
-                    if mdia_child.typ == "minf"
-                        && let Some(minf_children) = &mdia_child.children
-                    {
-                        for minf_child in minf_children {
-                            if minf_child.typ == "stbl" {
-                                return Some(minf_child);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    None
-}
-
-// Helper functions for extracting track metadata
-fn extract_track_id(trak_box: &mp4box::Box) -> Option<u32> {
-    // Look for tkhd box and extract track ID from structured data
-    if let Some(children) = &trak_box.children {
-        for child in children {
-            if child.typ == "tkhd" {
-                // Extract track ID from structured data
-                if let Some(mp4box::registry::StructuredData::TrackHeader(tkhd_data)) =
-                    &child.structured_data
-                {
-                    return Some(tkhd_data.track_id);
-                }
+fn print_sample_tables(boxes: &[mp4box::Box], args: &Args) -> Result<()> {
+    println!("Sample Table Analysis for: {:?}", args.input);
+    println!("=========================================");
 
-                // Fallback to text parsing if structured data not available
-                if let Some(decoded) = &child.decoded
-                    && let Some(track_id) = extract_number_from_decoded(decoded, "track_id")
-                {
-                    return Some(track_id);
-                }
-            }
-        }
-    }
-    None
+    analyze_boxes(boxes, args);
+    Ok(())
 }
 
-fn extract_handler_type(trak_box: &mp4box::Box) -> Option<String> {
-    // Navigate to mdia/hdlr and extract handler type from structured data
-    if let Some(children) = &trak_box.children {
-        for child in children {
-            if child.typ == "mdia"
-                && let Some(mdia_children) = &child.children
-            {
-                for mdia_child in mdia_children {
-                    if mdia_child.typ == "hdlr" {
-                        // Extract handler type from structured data
-                        if let Some(mp4box::registry::StructuredData::HandlerReference(hdlr_data)) =
-                            &mdia_child.structured_data
-                        {
-                            return Some(hdlr_data.handler_type.clone());
-                        }
+fn analyze_boxes(boxes: &[mp4box::Box], args: &Args) {
+    let _ = walk(
+        boxes,
+        &mut |box_info: &mp4box::Box, ancestors: &[&mp4box::Box]| {
+            let indent = "  ".repeat(ancestors.len());
+            if let Some(decoded) = &box_info.decoded {
+                match box_info.typ.as_str() {
+                    "stts" => {
+                        println!("{}📊 Decoding Time-to-Sample Box (stts):", indent);
+                        println!("{}   {}", indent, decoded);
                     }
-                }
-            }
-        }
-    }
-    None
-}
-
-fn extract_media_info(trak_box: &mp4box::Box) -> (u32, u64) {
-    // Navigate to mdia/mdhd and extract timescale and duration from structured data
-    if let Some(children) = &trak_box.children {
-        for child in children {
-            if child.typ == "mdia"
-                && let Some(mdia_children) = &child.children
-            {
-                for mdia_child in mdia_children {
-                    if mdia_child.typ == "mdhd" {
-                        // Extract timescale and duration from structured data
-                        if let Some(mp4box::registry::StructuredData::MediaHeader(mdhd_data)) =
-                            &mdia_child.structured_data
-                        {
-                            return (mdhd_data.timescale, mdhd_data.duration as u64);
-                        }
+                    "stsc" => {
+                        println!("{}🗂️  Sample-to-Chunk Box (stsc):", indent);
+                        println!("{}   {}", indent, decoded);
                     }
-                }
-            }
-        }
-    }
-    (12288, 0) // Default values - common for video
-}
-
-fn extract_sample_table_data(stbl_box: &mp4box::Box) -> Result<SampleTableData> {
-    let mut data = SampleTableData::default();
-
-    if let Some(children) = &stbl_box.children {
-        for child in children {
-            if let Some(decoded) = &child.decoded {
-                // Try to parse structured data from the decoded string
-                // The current API returns structured data as debug strings like "structured: StszData { ... }"
-                match child.typ.as_str() {
                     "stsz" => {
-                        // Parse sample count and sizes from stsz
-                        if let Some(sample_count) =
-                            extract_number_from_decoded(decoded, "sample_count:")
-                        {
-                            data.sample_count = sample_count;
-                            // For individual sample sizes, try to extract them
-                            data.sample_sizes =
-                                extract_sample_sizes_from_decoded(decoded, sample_count);
-                        }
-                    }
-                    "stts" => {
-                        if let Some(entry_count) =
-                            extract_number_from_decoded(decoded, "entry_count:")
-                        {
-                            data.stts_entries = entry_count;
-                        }
+                        println!("{}📏 Sample Size Box (stsz):", indent);
+                        println!("{}   {}", indent, decoded);
                     }
-                    "stsc" => {
-                        if let Some(entry_count) =
-                            extract_number_from_decoded(decoded, "entry_count:")
-                        {
-                            data.stsc_entries = entry_count;
-                        }
+                    "stco" => {
+                        println!("{}📍 Chunk Offset Box (stco):", indent);
+                        println!("{}   {}", indent, decoded);
                     }
-                    "stco" | "co64" => {
-                        if let Some(entry_count) =
-                            extract_number_from_decoded(decoded, "entry_count:")
-                        {
-                            data.stco_entries = entry_count;
-                        }
+                    "co64" => {
+                        println!("{}📍 64-bit Chunk Offset Box (co64):", indent);
+                        println!("{}   {}", indent, decoded);
                     }
                     "stss" => {
-                        if let Some(entry_count) =
-                            extract_number_from_decoded(decoded, "entry_count:")
-                        {
-                            data.keyframe_count = entry_count;
-                        }
+                        println!("{}🎯 Sync Sample Box (stss):", indent);
+                        println!("{}   {}", indent, decoded);
                     }
-                    _ => {}
-                }
-            }
-        }
-    }
-
-    // If we didn't find any sample data, return error
-    if data.sample_count == 0 {
-        return Err(anyhow::anyhow!("No sample data found in stbl box"));
-    }
-
-    Ok(data)
-}
-
-fn build_samples(table_data: &SampleTableData, timescale: u32) -> Result<Vec<SampleInfo>> {
-    let mut samples = Vec::new();
-
-    // Use default duration if we don't have real timing data
-    // Try to detect the actual frame rate - 24fps is common for cinema content
-    let default_duration = if timescale > 0 {
-        timescale / 24 // ~24fps default (more accurate than 30fps)
-    } else {
-        1000
-    };
-
-    for i in 0..table_data.sample_count {
-        let duration = default_duration; // Would come from STTS in real implementation
-        let dts = i as u64 * duration as u64;
-        let pts = dts; // Would add CTTS offset in real implementation
-
-        let sample = SampleInfo {
-            index: i,
-            dts,
-            pts,
-            start_time: dts as f64 / timescale as f64,
-            duration,
-            rendered_offset: 0,            // From ctts if present
-            file_offset: i as u64 * 50000, // Rough estimate - would come from STCO
-            size: if !table_data.sample_sizes.is_empty() {
-                if i < table_data.sample_sizes.len() as u32 {
-                    table_data.sample_sizes[i as usize]
-                } else {
-                    table_data.sample_sizes[0] // Use first size as default
-                }
-            } else {
-                // Use a more reasonable default size
-                if i == 0 { 50000 } else { 5000 } // First sample larger (keyframe)
-            },
-            is_sync: i % 30 == 0, // Every 30th sample is keyframe (more realistic)
-        };
-        samples.push(sample);
-    }
-
-    Ok(samples)
-}
-
-// Helper functions for parsing structured data from debug strings
-fn extract_number_from_decoded(decoded: &str, field: &str) -> Option<u32> {
-    // Look for patterns like "sample_count: 1234" in the decoded string
-    if let Some(start) = decoded.find(field) {
-        let after_field = &decoded[start + field.len()..];
-        // Skip whitespace and colon
-        let trimmed = after_field.trim_start_matches(|c: char| c.is_whitespace() || c == ':');
-        // Find the number
-        let number_str = trimmed
-            .chars()
-            .take_while(|c| c.is_ascii_digit())
-            .collect::<String>();
-        number_str.parse().ok()
-    } else {
-        None
-    }
-}
-
-fn extract_sample_sizes_from_decoded(decoded: &str, count: u32) -> Vec<u32> {
-    // First check if there's a uniform sample_size
-    if let Some(uniform_size) = extract_number_from_decoded(decoded, "sample_size")
-        && uniform_size > 0
-    {
-        return vec![uniform_size; count as usize];
-    }
-
-    // Try to extract individual sample sizes from the decoded string
-    // Look for patterns like "sample_sizes: [1234, 5678, ...]"
-    if decoded.contains("sample_sizes: [") {
-        // For now, return empty vector which will use defaults
-        // This would need more sophisticated array parsing
-        Vec::new()
-    } else {
-        Vec::new()
-    }
-}
-
-fn print_sample_tables(boxes: &[mp4box::Box], args: &Args) -> Result<()> {
-    println!("Sample Table Analysis for: {:?}", args.input);
-    println!("=========================================");
-
-    analyze_boxes(boxes, 0, args);
-    Ok(())
-}
-
-fn analyze_boxes(boxes: &[mp4box::Box], depth: usize, args: &Args) {
-    let indent = "  ".repeat(depth);
-
-    for box_info in boxes {
-        if let Some(decoded) = &box_info.decoded {
-            match box_info.typ.as_str() {
-                "stts" => {
-                    println!("{}📊 Decoding Time-to-Sample Box (stts):", indent);
-                    println!("{}   {}", indent, decoded);
-                }
-                "stsc" => {
-                    println!("{}🗂️  Sample-to-Chunk Box (stsc):", indent);
-                    println!("{}   {}", indent, decoded);
-                }
-                "stsz" => {
-                    println!("{}📏 Sample Size Box (stsz):", indent);
-                    println!("{}   {}", indent, decoded);
-                }
-                "stco" => {
-                    println!("{}📍 Chunk Offset Box (stco):", indent);
-                    println!("{}   {}", indent, decoded);
-                }
-                "co64" => {
-                    println!("{}📍 64-bit Chunk Offset Box (co64):", indent);
-                    println!("{}   {}", indent, decoded);
-                }
-                "stss" => {
-                    println!("{}🎯 Sync Sample Box (stss):", indent);
-                    println!("{}   {}", indent, decoded);
-                }
-                "ctts" => {
-                    println!("{}⏰ Composition Time-to-Sample Box (ctts):", indent);
-                    println!("{}   {}", indent, decoded);
-                }
-                "stsd" => {
-                    println!("{}🎬 Sample Description Box (stsd):", indent);
-                    println!("{}   {}", indent, decoded);
-                }
-                _ => {
-                    if args.verbose && !decoded.is_empty() {
-                        println!("{}📦 {} Box:", indent, box_info.typ);
+                    "ctts" => {
+                        println!("{}⏰ Composition Time-to-Sample Box (ctts):", indent);
                         println!("{}   {}", indent, decoded);
                     }
+                    "stsd" => {
+                        println!("{}🎬 Sample Description Box (stsd):", indent);
+                        println!("{}   {}", indent, decoded);
+                    }
+                    _ => {
+                        if args.verbose && !decoded.is_empty() {
+                            println!("{}📦 {} Box:", indent, box_info.typ);
+                            println!("{}   {}", indent, decoded);
+                        }
+                    }
                 }
             }
-        }
 
-        // Recurse into children
-        if let Some(children) = &box_info.children {
-            analyze_boxes(children, depth + 1, args);
-        }
-    }
+            ControlFlow::<(), _>::Continue(Children::Visit)
+        },
+    );
 }
 
 fn print_json(tracks: &[TrackInfo], args: &Args) -> Result<()> {
@@ -475,11 +262,17 @@ fn print_json(tracks: &[TrackInfo], args: &Args) -> Result<()> {
                 "sample_count": t.sample_count,
                 "samples": samples,
             });
+            if !t.fragments.is_empty() {
+                track_data["fragments"] = json!(t.fragments);
+            }
+            if args.stats {
+                track_data["size_stats"] = json!(size_stats(&t.samples, Some(HISTOGRAM_BINS)));
+            }
 
             if args.verbose {
                 track_data["sample_tables"] = json!({
                     "stts_entries": t.stts_entries,
-                    "stsz_entries": t.sample_count,
+                    "stsz_entries": t.stsz_entries,
                     "stsc_entries": t.stsc_entries,
                     "stco_entries": t.stco_entries,
                     "keyframes": t.keyframe_count,
@@ -500,19 +293,46 @@ fn print_text(tracks: &[TrackInfo], args: &Args) -> Result<()> {
         .filter(|t| args.track_id.is_none_or(|tid| t.track_id == tid))
         .collect();
 
+    let nf = NumberFormat::for_cli(args.human);
     for t in filtered_tracks {
         println!(
             "Track {} ({}) timescale={} duration={} sample_count={}",
-            t.track_id, t.handler_type, t.timescale, t.duration, t.sample_count
+            t.track_id,
+            t.handler_type,
+            nf.int(t.timescale),
+            nf.int(t.duration),
+            nf.int(t.sample_count)
         );
 
         if args.verbose {
             println!("  Sample Table Info:");
-            println!("    STTS entries: {}", t.stts_entries);
-            println!("    STSC entries: {}", t.stsc_entries);
-            println!("    STCO entries: {}", t.stco_entries);
-            println!("    Keyframes: {}", t.keyframe_count);
+            println!("    STTS entries: {}", nf.int(t.stts_entries));
+            println!("    STSC entries: {}", nf.int(t.stsc_entries));
+            println!("    STCO entries: {}", nf.int(t.stco_entries));
+            println!("    Keyframes: {}", nf.int(t.keyframe_count));
             println!();
+
+            if !t.fragments.is_empty() {
+                println!("  Fragments:");
+                for f in &t.fragments {
+                    let seq = f.sequence_number.map_or("-".into(), |n| nf.int(n));
+                    let base = f.base_media_decode_time.map_or("-".into(), |b| nf.int(b));
+                    println!(
+                        "    moof @{} seq={} base_media_decode_time={} samples={}+{}",
+                        nf.int(f.moof_offset),
+                        seq,
+                        base,
+                        nf.int(f.first_sample),
+                        nf.int(f.sample_count)
+                    );
+                }
+                println!();
+            }
+        }
+
+        if args.stats {
+            print_size_stats(t, &nf);
+            continue;
         }
 
         if args.timing {
@@ -532,24 +352,24 @@ fn print_text(tracks: &[TrackInfo], args: &Args) -> Result<()> {
 
             if args.timing {
                 println!(
-                    "{:5} {:10} {:10} {:10.4} {:8} {:6} {:10} {}",
+                    "{:>5} {:>10} {:>10} {:>10} {:>8} {:>6} {:>10} {}",
                     s.index,
-                    s.dts,
-                    s.pts,
-                    s.start_time,
-                    s.duration,
-                    s.size,
-                    s.file_offset,
+                    nf.int(s.dts),
+                    nf.int(s.pts),
+                    nf.float(s.start_time, 4),
+                    nf.int(s.duration),
+                    nf.int(s.size),
+                    nf.int(s.file_offset),
                     if s.is_sync { "*" } else { "" },
                 );
             } else {
                 println!(
-                    "{:5} {:10.4} {:8} {:6} {:10} {}",
+                    "{:>5} {:>10} {:>8} {:>6} {:>10} {}",
                     s.index,
-                    s.start_time,
-                    s.duration,
-                    s.size,
-                    s.file_offset,
+                    nf.float(s.start_time, 4),
+                    nf.int(s.duration),
+                    nf.int(s.size),
+                    nf.int(s.file_offset),
                     if s.is_sync { "*" } else { "" },
                 );
             }
@@ -558,3 +378,35 @@ fn print_text(tracks: &[TrackInfo], args: &Args) -> Result<()> {
     }
     Ok(())
 }
+
+const HISTOGRAM_BINS: usize = 10;
+
+fn print_size_stats(t: &TrackInfo, nf: &NumberFormat) {
+    let Some(s) = size_stats(&t.samples, Some(HISTOGRAM_BINS)) else {
+        return;
+    };
+    println!("  Sample sizes (bytes):");
+    println!("    total={} mean={}", nf.int(s.total), nf.float(s.mean, 1));
+    println!(
+        "    min={} median={} p90={} p95={} p99={} max={}",
+        nf.int(s.min),
+        nf.float(s.median, 1),
+        nf.int(s.p90),
+        nf.int(s.p95),
+        nf.int(s.p99),
+        nf.int(s.max)
+    );
+    let bins = s.histogram.unwrap_or_default();
+    let widest = bins.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+    for b in bins {
+        let bar = "#".repeat((b.count * 40).div_ceil(widest));
+        println!(
+            "    {:>10} - {:>10} {:>8} {}",
+            nf.int(b.start),
+            nf.int(b.end),
+            nf.int(b.count as u64),
+            bar
+        );
+    }
+    println!();
+}
@@ -8,15 +8,26 @@ impl FourCC {
     pub fn as_str_lossy(&self) -> String {
         self.0
             .iter()
-            .map(|&c| {
-                if (32..=126).contains(&c) {
-                    c as char
-                } else {
-                    '.'
-                }
+            .map(|&c| match c {
+                32..=126 => c as char,
+                // QuickTime/iTunes metadata atoms (©nam, ©too, ...)
+                0xA9 => '©',
+                _ => '.',
             })
             .collect()
     }
+
+    /// The raw bytes as 8 lowercase hex digits ("a9746f6f" for ©too);
+    /// unlike [`as_str_lossy`](Self::as_str_lossy) this never loses bytes.
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// Whether [`as_str_lossy`](Self::as_str_lossy) shows every byte as
+    /// itself, so the string identifies the type.
+    pub fn is_printable(&self) -> bool {
+        self.0.iter().all(|&c| matches!(c, 32..=126 | 0xA9))
+    }
 }
 impl fmt::Debug for FourCC {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -38,8 +49,21 @@ impl From<[u8; 4]> for FourCC {
 impl FromStr for FourCC {
     type Err = ();
 
+    /// Four characters, or `0x` and 8 hex digits for the raw bytes (the
+    /// form for types [`as_str_lossy`](Self::as_str_lossy) cannot show).
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let b = s.as_bytes();
+        if let Some(hex) = s
+            .strip_prefix("0x")
+            .filter(|h| h.len() == 8 && h.bytes().all(|c| c.is_ascii_hexdigit()))
+        {
+            let v = u32::from_str_radix(hex, 16).map_err(|_| ())?;
+            return Ok(FourCC(v.to_be_bytes()));
+        }
+        // Chars map to bytes as Latin-1 so "©nam" round-trips
+        let b: Vec<u8> = s
+            .chars()
+            .map(|c| u8::try_from(u32::from(c)).map_err(|_| ()))
+            .collect::<Result<_, _>>()?;
         if b.len() == 4 {
             Ok(FourCC([b[0], b[1], b[2], b[3]]))
         } else {
@@ -65,6 +89,8 @@ pub enum NodeKind {
         flags: u32,
         data_offset: u64,
         data_len: u64,
+        /// Boxes nested inside the payload (meta items, stsd sample entries)
+        children: Vec<BoxRef>,
     },
     Leaf {
         data_offset: u64,
@@ -74,6 +100,22 @@ pub enum NodeKind {
         data_offset: u64,
         data_len: u64,
     },
+    /// Bytes skipped while resynchronizing after a bad header; the header
+    /// is synthetic (type `gap `, no header bytes)
+    Gap {
+        data_offset: u64,
+        data_len: u64,
+    },
+}
+
+impl NodeKind {
+    /// Child boxes of a container, or the boxes nested in a FullBox payload.
+    pub fn children(&self) -> &[BoxRef] {
+        match self {
+            NodeKind::Container(kids) | NodeKind::FullBox { children: kids, .. } => kids,
+            NodeKind::Leaf { .. } | NodeKind::Unknown { .. } | NodeKind::Gap { .. } => &[],
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -87,3 +129,31 @@ pub enum BoxKey {
     FourCC(FourCC),
     Uuid([u8; 16]),
 }
+
+impl From<FourCC> for BoxKey {
+    fn from(typ: FourCC) -> Self {
+        BoxKey::FourCC(typ)
+    }
+}
+
+impl From<&[u8; 4]> for BoxKey {
+    fn from(typ: &[u8; 4]) -> Self {
+        BoxKey::FourCC(FourCC(*typ))
+    }
+}
+
+impl FromStr for BoxKey {
+    type Err = ();
+
+    /// `uuid:` and 32 hex digits, or anything [`FourCC::from_str`] accepts.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some(hex) = s.strip_prefix("uuid:") else {
+            return s.parse().map(BoxKey::FourCC);
+        };
+        if hex.len() != 32 || !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(());
+        }
+        let v = u128::from_str_radix(hex, 16).map_err(|_| ())?;
+        Ok(BoxKey::Uuid(v.to_be_bytes()))
+    }
+}
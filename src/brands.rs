@@ -0,0 +1,274 @@
+//! Interpretation of ftyp/styp brands.
+
+use crate::registry::{FtypData, StructuredData};
+use serde::Serialize;
+
+/// The kind of file a set of brands declares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerFamily {
+    /// Plain ISO base media file format
+    Iso,
+    /// MP4 (ISO/IEC 14496-14) and its Apple variants
+    Mp4,
+    QuickTime,
+    ThreeGpp,
+    /// MPEG-DASH segments
+    Dash,
+    /// CMAF tracks and segments
+    Cmaf,
+    /// HEIF still images and image sequences
+    Heif,
+    Avif,
+    Unknown,
+}
+
+/// What one known brand means.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BrandInfo {
+    pub brand: String,
+    pub family: ContainerFamily,
+    pub description: &'static str,
+    /// Requirements a file carrying this brand promises to meet
+    pub constraints: &'static [&'static str],
+}
+
+/// Result of [`analyze_brands`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BrandAnalysis {
+    pub major_brand: String,
+    pub family: ContainerFamily,
+    /// Known brands, major first, then compatible brands in box order
+    pub brands: Vec<BrandInfo>,
+    /// Brands this table does not know
+    pub unknown: Vec<String>,
+}
+
+impl BrandAnalysis {
+    /// Constraints of every known brand, without duplicates.
+    pub fn constraints(&self) -> Vec<&'static str> {
+        let mut out: Vec<&'static str> = Vec::new();
+        for c in self.brands.iter().flat_map(|b| b.constraints) {
+            if !out.contains(c) {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    pub fn has_brand(&self, brand: &str) -> bool {
+        self.major_brand == brand || self.brands.iter().any(|b| b.brand == brand)
+    }
+}
+
+type BrandEntry = (
+    &'static str,
+    ContainerFamily,
+    &'static str,
+    &'static [&'static str],
+);
+
+const BRANDS: &[BrandEntry] = &[
+    (
+        "isom",
+        ContainerFamily::Iso,
+        "ISO base media file format",
+        &[],
+    ),
+    (
+        "iso2",
+        ContainerFamily::Iso,
+        "ISO base media file format, second edition",
+        &["may use sub-sample information and sample groups"],
+    ),
+    (
+        "iso5",
+        ContainerFamily::Iso,
+        "ISO base media file format with default-base-is-moof",
+        &["movie fragments may address data relative to the moof"],
+    ),
+    (
+        "iso6",
+        ContainerFamily::Iso,
+        "ISO base media file format with segment boxes",
+        &[
+            "movie fragments may address data relative to the moof",
+            "may contain styp, sidx and prft boxes",
+        ],
+    ),
+    (
+        "mp41",
+        ContainerFamily::Mp4,
+        "MP4 version 1 (ISO/IEC 14496-1)",
+        &[],
+    ),
+    (
+        "mp42",
+        ContainerFamily::Mp4,
+        "MP4 version 2 (ISO/IEC 14496-14)",
+        &[],
+    ),
+    (
+        "avc1",
+        ContainerFamily::Mp4,
+        "AVC file format (ISO/IEC 14496-15)",
+        &["video is H.264 with an avcC configuration"],
+    ),
+    ("M4A ", ContainerFamily::Mp4, "Apple iTunes audio", &[]),
+    ("M4V ", ContainerFamily::Mp4, "Apple iTunes video", &[]),
+    (
+        "qt  ",
+        ContainerFamily::QuickTime,
+        "QuickTime movie",
+        &["QuickTime atom semantics, which differ from ISOBMFF in places"],
+    ),
+    ("3gp4", ContainerFamily::ThreeGpp, "3GPP release 4", &[]),
+    ("3gp5", ContainerFamily::ThreeGpp, "3GPP release 5", &[]),
+    ("3gp6", ContainerFamily::ThreeGpp, "3GPP release 6", &[]),
+    ("dash", ContainerFamily::Dash, "MPEG-DASH file", &[]),
+    (
+        "msdh",
+        ContainerFamily::Dash,
+        "DASH media segment",
+        &["consists of complete movie fragments"],
+    ),
+    (
+        "msix",
+        ContainerFamily::Dash,
+        "DASH indexed media segment",
+        &[
+            "consists of complete movie fragments",
+            "carries a sidx indexing its subsegments",
+        ],
+    ),
+    (
+        "cmfc",
+        ContainerFamily::Cmaf,
+        "CMAF track",
+        &["one track per file", "fragmented, with tfdt in every traf"],
+    ),
+    (
+        "cmf2",
+        ContainerFamily::Cmaf,
+        "CMAF track, structural brand 2",
+        &[
+            "one track per file",
+            "fragmented, with tfdt in every traf",
+            "no edit lists other than a single offset edit",
+        ],
+    ),
+    (
+        "mif1",
+        ContainerFamily::Heif,
+        "HEIF image items",
+        &["has a meta box with a primary item"],
+    ),
+    (
+        "msf1",
+        ContainerFamily::Heif,
+        "HEIF image sequence",
+        &["has a pict handler track"],
+    ),
+    (
+        "heic",
+        ContainerFamily::Heif,
+        "HEIF image coded with HEVC",
+        &[
+            "has a meta box with a primary item",
+            "primary item is an HEVC image",
+        ],
+    ),
+    (
+        "avif",
+        ContainerFamily::Avif,
+        "AVIF image",
+        &[
+            "has a meta box with a primary item",
+            "primary item is an AV1 image",
+        ],
+    ),
+];
+
+/// Lookup for a single brand.
+pub fn describe_brand(brand: &str) -> Option<BrandInfo> {
+    BRANDS
+        .iter()
+        .find(|(b, ..)| *b == brand)
+        .map(|&(b, family, description, constraints)| BrandInfo {
+            brand: b.to_string(),
+            family,
+            description,
+            constraints,
+        })
+}
+
+/// Most specific family first; brands listing several families (an AVIF
+/// file also carries mif1, a CMAF file iso6) resolve to the earlier one.
+const FAMILY_ORDER: [ContainerFamily; 8] = [
+    ContainerFamily::Avif,
+    ContainerFamily::Heif,
+    ContainerFamily::Cmaf,
+    ContainerFamily::Dash,
+    ContainerFamily::QuickTime,
+    ContainerFamily::ThreeGpp,
+    ContainerFamily::Mp4,
+    ContainerFamily::Iso,
+];
+
+/// Interpret the brands of an ftyp or styp.
+pub fn analyze_brands(ftyp: &FtypData) -> BrandAnalysis {
+    let mut brands: Vec<BrandInfo> = Vec::new();
+    let mut unknown = Vec::new();
+    let all = std::iter::once(&ftyp.major_brand).chain(&ftyp.compatible_brands);
+    for brand in all {
+        if brands.iter().any(|b| &b.brand == brand) || unknown.contains(brand) {
+            continue;
+        }
+        match describe_brand(brand) {
+            Some(info) => brands.push(info),
+            None => unknown.push(brand.clone()),
+        }
+    }
+
+    // A QuickTime major brand wins: such files list ISO brands for
+    // compatibility but follow QuickTime rules
+    let family = if ftyp.major_brand == "qt  " {
+        ContainerFamily::QuickTime
+    } else {
+        FAMILY_ORDER
+            .into_iter()
+            .find(|f| brands.iter().any(|b| b.family == *f))
+            .unwrap_or(ContainerFamily::Unknown)
+    };
+
+    BrandAnalysis {
+        major_brand: ftyp.major_brand.clone(),
+        family,
+        brands,
+        unknown,
+    }
+}
+
+/// [`analyze_brands`] on the first top-level ftyp or styp of a decoded tree.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{brands::brand_analysis, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("image.heic")?;
+/// let size = file.metadata()?.len();
+/// if let Some(a) = brand_analysis(&get_boxes(&mut file, size, true)?) {
+///     println!("{:?}: {}", a.family, a.constraints().join("; "));
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn brand_analysis(boxes: &[crate::Box]) -> Option<BrandAnalysis> {
+    boxes
+        .iter()
+        .filter(|b| b.typ == "ftyp" || b.typ == "styp")
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::FileType(f)) => Some(analyze_brands(f)),
+            _ => None,
+        })
+}
@@ -0,0 +1,171 @@
+//! Common Encryption (CENC) key and IV settings per sample.
+
+use crate::registry::{SampleGroupEntry, SeigEntry, StructuredData};
+use crate::sample_groups::{TrackSampleGroups, track_sample_groups};
+use serde::Serialize;
+
+/// Where the settings of an [`EncryptionRange`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// A 'seig' sample group entry (key rotation)
+    SampleGroup,
+    /// The track's tenc defaults
+    Tenc,
+}
+
+/// A run of consecutive samples sharing the same key and IV settings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EncryptionRange {
+    /// 0-based, numbered as in [`crate::TrackSamples`]
+    pub first_sample: u32,
+    pub sample_count: u32,
+    pub source: KeySource,
+    pub settings: SeigEntry,
+}
+
+/// Key and IV settings of every sample of one track.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackEncryption {
+    pub track_id: u32,
+    /// From stsd/.../sinf/schi/tenc; None for clear tracks
+    pub tenc: Option<SeigEntry>,
+    /// Covers every sample that has settings, in order
+    pub ranges: Vec<EncryptionRange>,
+}
+
+impl TrackEncryption {
+    /// Settings of the sample at `sample_index` (0-based).
+    pub fn settings_of(&self, sample_index: u32) -> Option<&EncryptionRange> {
+        self.ranges.iter().find(|r| {
+            sample_index >= r.first_sample && sample_index - r.first_sample < r.sample_count
+        })
+    }
+}
+
+/// Resolve which KID and IV settings apply to each sample of every track.
+///
+/// 'seig' sample groups (track or fragment level) override the tenc
+/// defaults; samples outside any 'seig' group use the sgpd default group
+/// if there is one, else tenc. Requires a tree decoded with `decode = true`.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{cenc::sample_encryption, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("encrypted.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for track in sample_encryption(&boxes) {
+///     for r in &track.ranges {
+///         println!("track {} samples {}+{}: kid {}", track.track_id,
+///             r.first_sample, r.sample_count, r.settings.kid);
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn sample_encryption(boxes: &[crate::Box]) -> Vec<TrackEncryption> {
+    let traks: Vec<&crate::Box> = boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .flat_map(|moov| moov.children.iter().flatten())
+        .filter(|b| b.typ == "trak")
+        .collect();
+
+    track_sample_groups(boxes)
+        .into_iter()
+        .map(|groups| {
+            let tenc = traks
+                .iter()
+                .find(|t| track_id(t) == Some(groups.track_id))
+                .and_then(|t| find_tenc(t));
+            TrackEncryption {
+                track_id: groups.track_id,
+                ranges: ranges(&groups, tenc.as_ref()),
+                tenc,
+            }
+        })
+        .collect()
+}
+
+fn track_id(trak: &crate::Box) -> Option<u32> {
+    trak.children
+        .iter()
+        .flatten()
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::TrackHeader(t)) => Some(t.track_id),
+            _ => None,
+        })
+}
+
+/// First tenc anywhere under the trak (stsd/encv/sinf/schi/tenc).
+fn find_tenc(b: &crate::Box) -> Option<SeigEntry> {
+    if let Some(StructuredData::TrackEncryption(t)) = &b.structured_data {
+        return Some(t.defaults.clone());
+    }
+    b.children.iter().flatten().find_map(find_tenc)
+}
+
+fn ranges(groups: &TrackSampleGroups, tenc: Option<&SeigEntry>) -> Vec<EncryptionRange> {
+    let seig = |entry: &Option<SampleGroupEntry>| match entry {
+        Some(SampleGroupEntry::Encryption(e)) => Some(e.clone()),
+        _ => None,
+    };
+    let fallback = groups
+        .defaults
+        .iter()
+        .filter(|d| d.grouping_type == "seig")
+        .find_map(|d| seig(&d.entry))
+        .map(|e| (KeySource::SampleGroup, e))
+        .or_else(|| tenc.map(|e| (KeySource::Tenc, e.clone())));
+
+    let mut runs: Vec<_> = groups
+        .runs
+        .iter()
+        .filter(|r| r.membership.grouping_type == "seig")
+        .collect();
+    runs.sort_by_key(|r| r.first_sample);
+
+    let mut out: Vec<EncryptionRange> = Vec::new();
+    let mut push = |first: u32, end: u32, settings: Option<(KeySource, SeigEntry)>| {
+        let Some((source, settings)) = settings else {
+            return;
+        };
+        if first >= end {
+            return;
+        }
+        match out.last_mut() {
+            Some(last)
+                if last.first_sample + last.sample_count == first
+                    && last.source == source
+                    && last.settings == settings =>
+            {
+                last.sample_count += end - first;
+            }
+            _ => out.push(EncryptionRange {
+                first_sample: first,
+                sample_count: end - first,
+                source,
+                settings,
+            }),
+        }
+    };
+
+    let mut pos = 0;
+    for r in runs {
+        let end = r
+            .first_sample
+            .saturating_add(r.sample_count)
+            .min(groups.sample_count);
+        push(pos, r.first_sample, fallback.clone());
+        let settings = seig(&r.membership.entry)
+            .map(|e| (KeySource::SampleGroup, e))
+            .or_else(|| fallback.clone());
+        push(pos.max(r.first_sample), end, settings);
+        pos = pos.max(end);
+    }
+    push(pos, groups.sample_count, fallback);
+
+    out
+}
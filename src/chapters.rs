@@ -0,0 +1,144 @@
+//! Chapter lists from Nero `chpl` boxes and QuickTime chapter text tracks.
+
+use crate::registry::StructuredData;
+use serde::Serialize;
+use std::io::{Read, Seek};
+
+/// One chapter marker.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Chapter {
+    /// Start time in seconds
+    pub start: f64,
+    pub title: String,
+}
+
+/// Collect the chapters of a movie, sorted by start time.
+///
+/// QuickTime chapter tracks (text tracks referenced from another track's
+/// `tref/chap`) are preferred; the Nero `moov/udta/chpl` list is used when
+/// there are none. The sample text of chapter tracks is read from `r`.
+///
+/// Requires a tree produced with `decode = true`.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{chapters::chapters, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("audiobook.m4b")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for ch in chapters(&mut file, &boxes)? {
+///     println!("{:>8.3} {}", ch.start, ch.title);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn chapters<R: Read + Seek>(r: &mut R, boxes: &[crate::Box]) -> anyhow::Result<Vec<Chapter>> {
+    let mut out = chapter_track_chapters(r, boxes)?;
+    if out.is_empty() {
+        out = nero_chapters(boxes);
+    }
+    out.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(out)
+}
+
+fn traks(boxes: &[crate::Box]) -> impl Iterator<Item = &crate::Box> {
+    boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .filter_map(|moov| moov.children.as_ref())
+        .flatten()
+        .filter(|b| b.typ == "trak")
+}
+
+fn nero_chapters(boxes: &[crate::Box]) -> Vec<Chapter> {
+    boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .filter_map(|moov| moov.children.as_ref())
+        .flatten()
+        .filter(|b| b.typ == "udta")
+        .filter_map(|udta| udta.children.as_ref())
+        .flatten()
+        .filter_map(|b| match &b.structured_data {
+            Some(StructuredData::ChapterList(c)) => Some(c),
+            _ => None,
+        })
+        .flat_map(|c| &c.entries)
+        .map(|e| Chapter {
+            start: e.start as f64 / 10_000_000.0,
+            title: e.title.clone(),
+        })
+        .collect()
+}
+
+fn chapter_track_chapters<R: Read + Seek>(
+    r: &mut R,
+    boxes: &[crate::Box],
+) -> anyhow::Result<Vec<Chapter>> {
+    let mut chapter_ids: Vec<u32> = traks(boxes)
+        .filter_map(|trak| trak.children.as_ref())
+        .flatten()
+        .filter(|b| b.typ == "tref")
+        .filter_map(|tref| tref.children.as_ref())
+        .flatten()
+        .filter_map(|b| match &b.structured_data {
+            Some(StructuredData::TrackReference(t)) if t.reference_type == "chap" => {
+                Some(&t.track_ids)
+            }
+            _ => None,
+        })
+        .flatten()
+        .copied()
+        .collect();
+    chapter_ids.sort_unstable();
+    chapter_ids.dedup();
+
+    let mut out = Vec::new();
+    for trak in traks(boxes) {
+        let Some(track) = crate::samples::extract_track_samples(trak, r)? else {
+            continue;
+        };
+        if !chapter_ids.contains(&track.track_id) {
+            continue;
+        }
+        let timescale = track.timescale.max(1) as f64;
+        for s in &track.samples {
+            let data = s.read_data(r)?;
+            out.push(Chapter {
+                start: s.pts as f64 / timescale,
+                title: sample_text(&data),
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// Decode a QuickTime text sample: a 16-bit length followed by UTF-8 text,
+/// or UTF-16 when the text starts with a byte order mark.
+fn sample_text(data: &[u8]) -> String {
+    if data.len() < 2 {
+        return String::new();
+    }
+    let len = u16::from_be_bytes([data[0], data[1]]) as usize;
+    let text = &data[2..(2 + len).min(data.len())];
+
+    let utf16 = |bytes: &[u8], be: bool| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| {
+                if be {
+                    u16::from_be_bytes([c[0], c[1]])
+                } else {
+                    u16::from_le_bytes([c[0], c[1]])
+                }
+            })
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    match text {
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, true),
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, false),
+        _ => String::from_utf8_lossy(text).to_string(),
+    }
+}
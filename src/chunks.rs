@@ -0,0 +1,94 @@
+//! Chunk table of a track, from stsc, stco/co64 and stsz.
+
+use crate::track::Track;
+use serde::Serialize;
+
+/// One chunk of a track's sample table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChunkInfo {
+    /// 1-based chunk number, as used by stsc
+    pub index: u32,
+    /// From stco or co64
+    pub file_offset: u64,
+    /// 0-based index of the first sample in the chunk
+    pub first_sample: u32,
+    pub sample_count: u32,
+    /// Sum of the chunk's sample sizes from stsz
+    pub size: u64,
+    pub sample_description_index: u32,
+}
+
+impl ChunkInfo {
+    /// Offset one past the chunk's last byte, saturating at `u64::MAX`.
+    pub fn end(&self) -> u64 {
+        self.file_offset.saturating_add(self.size)
+    }
+}
+
+/// Every chunk listed in stco/co64, in chunk order.
+///
+/// Chunks not covered by any stsc entry have no samples. Samples stsz does
+/// not list count as zero bytes.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{chunks::chunks, get_boxes, tracks};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for track in tracks(&boxes) {
+///     for c in chunks(&track) {
+///         println!("chunk {} @ {}: {} samples, {} bytes",
+///                  c.index, c.file_offset, c.sample_count, c.size);
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn chunks(track: &Track) -> Vec<ChunkInfo> {
+    let offsets: Vec<u64> = match (track.co64(), track.stco()) {
+        (Some(co64), _) => co64.chunk_offsets.clone(),
+        (None, Some(stco)) => stco.chunk_offsets.iter().map(|&o| o as u64).collect(),
+        (None, None) => return Vec::new(),
+    };
+    let stsc = track.stsc().map_or(&[][..], |s| &s.entries[..]);
+    let sample_size = |i: u32| match track.stsz() {
+        Some(stsz) if stsz.sample_size > 0 => stsz.sample_size as u64,
+        Some(stsz) => stsz.sample_sizes.get(i as usize).map_or(0, |&s| s as u64),
+        None => 0,
+    };
+
+    let mut out = Vec::with_capacity(offsets.len());
+    let mut entry = 0;
+    let mut first_sample = 0u32;
+    for (i, &file_offset) in offsets.iter().enumerate() {
+        let index = i as u32 + 1;
+        while stsc
+            .get(entry + 1)
+            .is_some_and(|next| next.first_chunk <= index)
+        {
+            entry += 1;
+        }
+        let (sample_count, sample_description_index) = stsc
+            .get(entry)
+            .filter(|e| e.first_chunk <= index)
+            .map_or((0, 0), |e| {
+                (e.samples_per_chunk, e.sample_description_index)
+            });
+
+        let size = (first_sample..first_sample.saturating_add(sample_count))
+            .map(sample_size)
+            .sum();
+        out.push(ChunkInfo {
+            index,
+            file_offset,
+            first_sample,
+            sample_count,
+            size,
+            sample_description_index,
+        });
+        first_sample = first_sample.saturating_add(sample_count);
+    }
+    out
+}
@@ -0,0 +1,217 @@
+//! Progressive sample tables rebuilt from the samples of a fragmented file,
+//! the core of defragmenting (moof/trun to one moov) workflows.
+
+use crate::registry::{
+    Co64Data, CttsData, CttsEntry, StcoData, StscData, StscEntry, StssData, StszData, SttsData,
+    SttsEntry,
+};
+use crate::samples::{SampleInfo, TrackSamples, track_samples_from_boxes};
+use serde::Serialize;
+
+/// Sample tables for one track, as a progressive stbl would hold them.
+///
+/// Chunk offsets point at the sample data where it is now, inside the
+/// fragments' mdat boxes; a muxer moving the data has to shift them.
+#[derive(Debug, Clone, Serialize)]
+pub struct RebuiltTables {
+    pub track_id: u32,
+    pub handler_type: String,
+    pub timescale: u32,
+    /// Sum of the sample durations, in `timescale` units
+    pub duration: u64,
+    pub stts: SttsData,
+    /// Only when some sample has a composition offset; version 1 if any is
+    /// negative
+    pub ctts: Option<CttsData>,
+    pub stsc: StscData,
+    pub stsz: StszData,
+    /// 32-bit chunk offsets, when all of them fit
+    pub stco: Option<StcoData>,
+    /// 64-bit chunk offsets, when some chunk starts past 4 GiB
+    pub co64: Option<Co64Data>,
+    /// Only when some sample is not a sync sample
+    pub stss: Option<StssData>,
+}
+
+impl RebuiltTables {
+    pub fn sample_count(&self) -> u32 {
+        self.stsz.sample_count
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        match (&self.stco, &self.co64) {
+            (Some(stco), _) => stco.chunk_offsets.len(),
+            (_, Some(co64)) => co64.chunk_offsets.len(),
+            _ => 0,
+        }
+    }
+}
+
+/// Rebuild stts/ctts/stsc/stsz/stco/stss for every track of a parsed box
+/// tree (from [`crate::get_boxes`] with `decode = true`), from the samples
+/// of the moov sample tables followed by those of every moof.
+///
+/// Samples stored back to back in the file share a chunk, so each trun
+/// usually becomes one chunk.
+pub fn rebuild_sample_tables(boxes: &[crate::Box]) -> anyhow::Result<Vec<RebuiltTables>> {
+    Ok(track_samples_from_boxes(boxes)?
+        .iter()
+        .map(rebuild_track)
+        .collect())
+}
+
+fn rebuild_track(track: &TrackSamples) -> RebuiltTables {
+    let samples = &track.samples;
+    let composition_offsets = samples.iter().any(|s| s.rendered_offset != 0);
+    let offsets = chunk_offsets(samples);
+
+    RebuiltTables {
+        track_id: track.track_id,
+        handler_type: track.handler_type.clone(),
+        timescale: track.timescale,
+        duration: samples.iter().map(|s| s.duration as u64).sum(),
+        stts: stts(samples),
+        ctts: composition_offsets.then(|| ctts(samples)),
+        stsc: stsc(samples),
+        stsz: stsz(samples),
+        stco: offsets
+            .iter()
+            .map(|&o| u32::try_from(o).ok())
+            .collect::<Option<Vec<_>>>()
+            .map(|chunk_offsets| StcoData {
+                version: 0,
+                flags: 0,
+                entry_count: chunk_offsets.len() as u32,
+                chunk_offsets,
+            }),
+        co64: offsets
+            .iter()
+            .any(|&o| o > u32::MAX as u64)
+            .then(|| Co64Data {
+                version: 0,
+                flags: 0,
+                entry_count: offsets.len() as u32,
+                chunk_offsets: offsets.clone(),
+            }),
+        stss: samples.iter().any(|s| !s.is_sync).then(|| {
+            let sample_numbers: Vec<u32> = samples
+                .iter()
+                .enumerate()
+                .filter(|(_, s)| s.is_sync)
+                .map(|(i, _)| i as u32 + 1)
+                .collect();
+            StssData {
+                version: 0,
+                flags: 0,
+                entry_count: sample_numbers.len() as u32,
+                sample_numbers,
+            }
+        }),
+    }
+}
+
+/// Run-length encode `values` as (count, value) pairs.
+fn runs<T: PartialEq + Copy>(values: impl Iterator<Item = T>) -> Vec<(u32, T)> {
+    let mut runs: Vec<(u32, T)> = Vec::new();
+    for v in values {
+        match runs.last_mut() {
+            Some((count, last)) if *last == v => *count += 1,
+            _ => runs.push((1, v)),
+        }
+    }
+    runs
+}
+
+fn stts(samples: &[SampleInfo]) -> SttsData {
+    let entries: Vec<SttsEntry> = runs(samples.iter().map(|s| s.duration))
+        .into_iter()
+        .map(|(sample_count, sample_delta)| SttsEntry {
+            sample_count,
+            sample_delta,
+        })
+        .collect();
+    SttsData {
+        version: 0,
+        flags: 0,
+        entry_count: entries.len() as u32,
+        entries,
+    }
+}
+
+fn ctts(samples: &[SampleInfo]) -> CttsData {
+    let entries: Vec<CttsEntry> = runs(samples.iter().map(|s| s.rendered_offset))
+        .into_iter()
+        .map(|(sample_count, offset)| CttsEntry {
+            sample_count,
+            sample_offset: offset.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+        })
+        .collect();
+    CttsData {
+        version: u8::from(entries.iter().any(|e| e.sample_offset < 0)),
+        flags: 0,
+        entry_count: entries.len() as u32,
+        entries,
+    }
+}
+
+/// Sample counts of the chunks: runs of samples stored back to back.
+fn chunk_sizes(samples: &[SampleInfo]) -> Vec<u32> {
+    let mut chunks: Vec<u32> = Vec::new();
+    let mut next = None;
+    for s in samples {
+        match chunks.last_mut() {
+            Some(count) if next == Some(s.file_offset) => *count += 1,
+            _ => chunks.push(1),
+        }
+        // A sample ending past u64::MAX closes its chunk
+        next = s.file_offset.checked_add(s.size as u64);
+    }
+    chunks
+}
+
+fn chunk_offsets(samples: &[SampleInfo]) -> Vec<u64> {
+    let mut first = 0;
+    chunk_sizes(samples)
+        .into_iter()
+        .map(|count| {
+            let offset = samples[first].file_offset;
+            first += count as usize;
+            offset
+        })
+        .collect()
+}
+
+fn stsc(samples: &[SampleInfo]) -> StscData {
+    let mut first_chunk = 1;
+    let mut entries: Vec<StscEntry> = Vec::new();
+    for (chunks, samples_per_chunk) in runs(chunk_sizes(samples).into_iter()) {
+        entries.push(StscEntry {
+            first_chunk,
+            samples_per_chunk,
+            sample_description_index: 1,
+        });
+        first_chunk += chunks;
+    }
+    StscData {
+        version: 0,
+        flags: 0,
+        entry_count: entries.len() as u32,
+        entries,
+    }
+}
+
+fn stsz(samples: &[SampleInfo]) -> StszData {
+    let first = samples.first().map_or(0, |s| s.size);
+    let constant = first > 0 && samples.iter().all(|s| s.size == first);
+    StszData {
+        version: 0,
+        flags: 0,
+        sample_size: if constant { first } else { 0 },
+        sample_count: samples.len() as u32,
+        sample_sizes: if constant {
+            Vec::new()
+        } else {
+            samples.iter().map(|s| s.size).collect()
+        },
+    }
+}
@@ -0,0 +1,117 @@
+//! Final display size of video tracks.
+
+use crate::registry::{ClapData, Orientation, PaspData, StructuredData};
+use serde::Serialize;
+
+/// How a video track's coded picture ends up on screen.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DisplayDimensions {
+    pub track_id: u32,
+    /// Sample entry width/height
+    pub coded_width: u16,
+    pub coded_height: u16,
+    /// Clean aperture size, when the sample entry has a clap box
+    pub clean_width: Option<f64>,
+    pub clean_height: Option<f64>,
+    /// pasp h_spacing:v_spacing; 1:1 without a pasp box
+    pub pixel_aspect: (u32, u32),
+    pub orientation: Orientation,
+    /// Size after cropping, pixel aspect scaling and rotation
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Display dimensions of every video track in a decoded box tree.
+///
+/// The coded size comes from the first sample entry in stsd; it is
+/// cropped to the clean aperture (clap), stretched horizontally by the
+/// pixel aspect ratio (pasp) and swapped for 90/270 degree tkhd rotations.
+/// Tracks whose first sample entry has no width/height are skipped.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{display::display_dimensions, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("anamorphic.mov")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for d in display_dimensions(&boxes) {
+///     println!("track {}: {}x{} -> {}x{}", d.track_id,
+///         d.coded_width, d.coded_height, d.width, d.height);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn display_dimensions(boxes: &[crate::Box]) -> Vec<DisplayDimensions> {
+    boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .flat_map(|moov| moov.children.iter().flatten())
+        .filter(|b| b.typ == "trak")
+        .filter_map(track_dimensions)
+        .collect()
+}
+
+fn track_dimensions(trak: &crate::Box) -> Option<DisplayDimensions> {
+    let tkhd = data(trak).find_map(|d| match d {
+        StructuredData::TrackHeader(t) => Some(t),
+        _ => None,
+    })?;
+    let stsd = ["mdia", "minf", "stbl", "stsd"]
+        .iter()
+        .try_fold(trak, |b, typ| b.child(typ))?;
+    let entry = match &stsd.structured_data {
+        Some(StructuredData::SampleDescription(d)) => d.entries.first()?,
+        _ => return None,
+    };
+    let (coded_width, coded_height) = (entry.width?, entry.height?);
+
+    // clap/pasp are children of the sample entry box
+    let entry_box = stsd.children.iter().flatten().next();
+    let clap: Option<&ClapData> = entry_box.and_then(|b| {
+        data(b).find_map(|d| match d {
+            StructuredData::CleanAperture(c) => Some(c),
+            _ => None,
+        })
+    });
+    let pasp: Option<&PaspData> = entry_box.and_then(|b| {
+        data(b).find_map(|d| match d {
+            StructuredData::PixelAspectRatio(p) => Some(p),
+            _ => None,
+        })
+    });
+
+    let clean = clap.filter(|c| c.width > 0.0 && c.height > 0.0);
+    let mut width = clean.map_or(coded_width as f64, |c| c.width);
+    let height = clean.map_or(coded_height as f64, |c| c.height);
+    let pixel_aspect = pasp
+        .filter(|p| p.h_spacing > 0 && p.v_spacing > 0)
+        .map_or((1, 1), |p| (p.h_spacing, p.v_spacing));
+    width *= pixel_aspect.0 as f64 / pixel_aspect.1 as f64;
+
+    let orientation = tkhd.orientation();
+    let (width, height) = (width.round() as u32, height.round() as u32);
+    let (width, height) = match orientation {
+        Orientation::Rotation { degrees: 90 | 270 } => (height, width),
+        _ => (width, height),
+    };
+
+    Some(DisplayDimensions {
+        track_id: tkhd.track_id,
+        coded_width,
+        coded_height,
+        clean_width: clean.map(|c| c.width),
+        clean_height: clean.map(|c| c.height),
+        pixel_aspect,
+        orientation,
+        width,
+        height,
+    })
+}
+
+fn data(b: &crate::Box) -> impl Iterator<Item = &StructuredData> {
+    b.children
+        .iter()
+        .flatten()
+        .filter_map(|c| c.structured_data.as_ref())
+}
@@ -0,0 +1,219 @@
+//! Cross-checks of the durations declared at each level of a movie.
+
+use crate::registry::StructuredData;
+use serde::Serialize;
+
+/// Default allowed difference between two durations, in seconds.
+pub const DEFAULT_TOLERANCE: f64 = 0.05;
+
+/// A box that declares a duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationSource {
+    Mvhd,
+    Tkhd,
+    Mdhd,
+    /// Sum of the elst segment durations
+    EditList,
+    /// Sum of the stts sample deltas
+    Stts,
+}
+
+/// Durations of one track, in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrackDurations {
+    pub track_id: u32,
+    pub tkhd: Option<f64>,
+    pub mdhd: Option<f64>,
+    pub edit_list: Option<f64>,
+    pub stts: Option<f64>,
+}
+
+/// Two durations that should agree but differ by more than the tolerance.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DurationMismatch {
+    /// None for movie-level checks
+    pub track_id: Option<u32>,
+    pub expected: DurationSource,
+    pub expected_seconds: f64,
+    pub actual: DurationSource,
+    pub actual_seconds: f64,
+}
+
+impl DurationMismatch {
+    /// `actual - expected`, in seconds.
+    pub fn difference(&self) -> f64 {
+        self.actual_seconds - self.expected_seconds
+    }
+}
+
+/// Result of [`check_durations`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DurationReport {
+    /// mvhd duration in seconds
+    pub movie: Option<f64>,
+    pub tracks: Vec<TrackDurations>,
+    pub mismatches: Vec<DurationMismatch>,
+}
+
+/// Compare the mvhd, tkhd, mdhd, elst and stts durations of every track.
+///
+/// For each track the stts total is checked against mdhd, and tkhd against
+/// the edit list total (or against mdhd when there is no edit list). The
+/// mvhd duration is checked against the longest tkhd. Durations are
+/// converted to seconds with their own timescale; two values match when
+/// they differ by at most `tolerance` seconds or one tick of the coarser
+/// timescale.
+///
+/// Zero durations in a fragmented file (one with mvex) and all-ones
+/// durations are treated as unknown and skipped.
+///
+/// Requires a tree produced with `decode = true`.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{durations::{DEFAULT_TOLERANCE, check_durations}, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("movie.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for m in check_durations(&boxes, DEFAULT_TOLERANCE).mismatches {
+///     println!("{:?}: {:?} {:.3}s vs {:?} {:.3}s", m.track_id,
+///         m.expected, m.expected_seconds, m.actual, m.actual_seconds);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn check_durations(boxes: &[crate::Box], tolerance: f64) -> DurationReport {
+    let mut report = DurationReport {
+        movie: None,
+        tracks: Vec::new(),
+        mismatches: Vec::new(),
+    };
+    let Some(moov) = boxes.iter().find(|b| b.typ == "moov") else {
+        return report;
+    };
+    let fragmented = moov.child("mvex").is_some();
+    let known = |d: u64| d != u64::MAX && d != u32::MAX as u64 && (d != 0 || !fragmented);
+
+    let Some(mvhd) = data(moov).find_map(|d| match d {
+        StructuredData::MovieHeader(m) => Some(m),
+        _ => None,
+    }) else {
+        return report;
+    };
+    let movie_scale = mvhd.timescale;
+    report.movie = seconds(mvhd.duration, movie_scale).filter(|_| known(mvhd.duration));
+
+    let mut longest: Option<f64> = None;
+    for trak in moov.children.iter().flatten().filter(|b| b.typ == "trak") {
+        let Some(tkhd) = data(trak).find_map(|d| match d {
+            StructuredData::TrackHeader(t) => Some(t),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let mdia = trak.child("mdia");
+        let mdhd = mdia.into_iter().flat_map(data).find_map(|d| match d {
+            StructuredData::MediaHeader(m) => Some(m),
+            _ => None,
+        });
+        let media_scale = mdhd.map_or(0, |m| m.timescale);
+        let stts = trak
+            .descendants_of_type("stbl")
+            .take(1)
+            .flat_map(data)
+            .find_map(|d| match d {
+                StructuredData::DecodingTimeToSample(s) => Some(
+                    s.entries
+                        .iter()
+                        .map(|e| e.sample_count as u64 * e.sample_delta as u64)
+                        .sum::<u64>(),
+                ),
+                _ => None,
+            });
+        let edit_list = trak
+            .child("edts")
+            .into_iter()
+            .flat_map(data)
+            .find_map(|d| match d {
+                StructuredData::EditList(e) => {
+                    Some(e.entries.iter().map(|e| e.segment_duration).sum::<u64>())
+                }
+                _ => None,
+            });
+
+        let track = TrackDurations {
+            track_id: tkhd.track_id,
+            tkhd: seconds(tkhd.duration, movie_scale).filter(|_| known(tkhd.duration)),
+            mdhd: mdhd.and_then(|m| seconds(m.duration, media_scale).filter(|_| known(m.duration))),
+            edit_list: edit_list.and_then(|d| seconds(d, movie_scale).filter(|_| known(d))),
+            stts: stts.and_then(|d| seconds(d, media_scale).filter(|_| known(d))),
+        };
+
+        let movie_tick = 1.0 / movie_scale.max(1) as f64;
+        let media_tick = 1.0 / media_scale.max(1) as f64;
+        let mut compare = |expected: (DurationSource, Option<f64>),
+                           actual: (DurationSource, Option<f64>),
+                           tick: f64| {
+            if let ((e, Some(es)), (a, Some(as_))) = (expected, actual)
+                && (as_ - es).abs() > tolerance.max(tick)
+            {
+                report.mismatches.push(DurationMismatch {
+                    track_id: Some(tkhd.track_id),
+                    expected: e,
+                    expected_seconds: es,
+                    actual: a,
+                    actual_seconds: as_,
+                });
+            }
+        };
+        compare(
+            (DurationSource::Mdhd, track.mdhd),
+            (DurationSource::Stts, track.stts),
+            media_tick,
+        );
+        if track.edit_list.is_some() {
+            compare(
+                (DurationSource::EditList, track.edit_list),
+                (DurationSource::Tkhd, track.tkhd),
+                movie_tick,
+            );
+        } else {
+            compare(
+                (DurationSource::Mdhd, track.mdhd),
+                (DurationSource::Tkhd, track.tkhd),
+                movie_tick.max(media_tick),
+            );
+        }
+
+        if let Some(t) = track.tkhd {
+            longest = Some(longest.map_or(t, |l| l.max(t)));
+        }
+        report.tracks.push(track);
+    }
+
+    if let (Some(movie), Some(longest)) = (report.movie, longest)
+        && (movie - longest).abs() > tolerance.max(1.0 / movie_scale.max(1) as f64)
+    {
+        report.mismatches.push(DurationMismatch {
+            track_id: None,
+            expected: DurationSource::Tkhd,
+            expected_seconds: longest,
+            actual: DurationSource::Mvhd,
+            actual_seconds: movie,
+        });
+    }
+    report
+}
+
+fn seconds(duration: u64, timescale: u32) -> Option<f64> {
+    (timescale > 0).then(|| duration as f64 / timescale as f64)
+}
+
+fn data(b: &crate::Box) -> impl Iterator<Item = &StructuredData> {
+    b.children
+        .iter()
+        .flatten()
+        .filter_map(|c| c.structured_data.as_ref())
+}
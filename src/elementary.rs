@@ -0,0 +1,394 @@
+//! Export of video and audio tracks as raw elementary streams: Annex B
+//! H.264/H.265 and ADTS AAC.
+
+use crate::nal::{NalCodec, nal_units};
+use crate::registry::BitReader;
+use crate::track::Track;
+use crate::util::read_slice;
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+/// Elementary stream format written by [`export_elementary_stream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamFormat {
+    /// Annex B H.264, from avc1/avc3 + avcC
+    H264,
+    /// Annex B H.265, from hvc1/hev1 + hvcC
+    H265,
+    /// AAC with ADTS headers, from mp4a + esds
+    Aac,
+}
+
+impl StreamFormat {
+    /// Conventional file extension, without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StreamFormat::H264 => "h264",
+            StreamFormat::H265 => "h265",
+            StreamFormat::Aac => "aac",
+        }
+    }
+}
+
+/// NAL unit framing and parameter sets from an avcC or hvcC box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NalConfig {
+    /// Bytes in each NAL unit length prefix: 1, 2 or 4
+    pub length_size: u8,
+    /// VPS/SPS/PPS NAL units, in box order
+    pub parameter_sets: Vec<Vec<u8>>,
+}
+
+/// The parts of an AudioSpecificConfig needed for an ADTS header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AacConfig {
+    /// MPEG-4 audio object type (2 = AAC LC)
+    pub object_type: u8,
+    pub sampling_frequency_index: u8,
+    pub channel_configuration: u8,
+}
+
+/// Result of a successful [`export_elementary_stream`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportSummary {
+    pub track_id: u32,
+    pub format: StreamFormat,
+    pub sample_count: u32,
+    pub bytes_written: u64,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error("track {0} not found")]
+    TrackNotFound(u32),
+    #[error("codec {0:?} cannot be exported as an elementary stream")]
+    UnsupportedCodec(String),
+    #[error("missing or invalid {0} box")]
+    InvalidConfig(&'static str),
+    #[error("AAC object type {0} has no ADTS profile")]
+    UnsupportedAacProfile(u8),
+    #[error("AAC frame of {0} bytes is too long for an ADTS header")]
+    AdtsFrameTooLong(usize),
+    #[error("sample {0} is not a sequence of length-prefixed NAL units")]
+    InvalidSample(u32),
+    #[error("sample {index} ends at byte {end}, past the end of the {len}-byte source")]
+    SampleOutOfBounds { index: u32, end: u64, len: u64 },
+    #[error("sample table: {0}")]
+    SampleTable(anyhow::Error),
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Parse an avcC payload (AVCDecoderConfigurationRecord).
+pub fn parse_avcc(p: &[u8]) -> Option<NalConfig> {
+    let length_size = (p.get(4)? & 0x03) + 1;
+    let mut pos = 6;
+    let mut parameter_sets = Vec::new();
+    let sps_count = p.get(5)? & 0x1F;
+    read_nal_array(p, &mut pos, sps_count as usize, &mut parameter_sets)?;
+    let pps_count = *p.get(pos)?;
+    pos += 1;
+    read_nal_array(p, &mut pos, pps_count as usize, &mut parameter_sets)?;
+    Some(NalConfig {
+        length_size,
+        parameter_sets,
+    })
+}
+
+/// Parse an hvcC payload (HEVCDecoderConfigurationRecord).
+pub fn parse_hvcc(p: &[u8]) -> Option<NalConfig> {
+    let length_size = (p.get(21)? & 0x03) + 1;
+    let array_count = *p.get(22)?;
+    let mut pos = 23;
+    let mut parameter_sets = Vec::new();
+    for _ in 0..array_count {
+        // array_completeness/reserved/NAL_unit_type, then numNalus
+        let count = u16::from_be_bytes([*p.get(pos + 1)?, *p.get(pos + 2)?]);
+        pos += 3;
+        read_nal_array(p, &mut pos, count as usize, &mut parameter_sets)?;
+    }
+    Some(NalConfig {
+        length_size,
+        parameter_sets,
+    })
+}
+
+/// `count` NAL units, each prefixed with a 16-bit length.
+fn read_nal_array(p: &[u8], pos: &mut usize, count: usize, out: &mut Vec<Vec<u8>>) -> Option<()> {
+    for _ in 0..count {
+        let len = u16::from_be_bytes([*p.get(*pos)?, *p.get(*pos + 1)?]) as usize;
+        out.push(p.get(*pos + 2..*pos + 2 + len)?.to_vec());
+        *pos += 2 + len;
+    }
+    Some(())
+}
+
+/// Parse an esds payload (after version/flags) down to the
+/// AudioSpecificConfig in its DecoderSpecificInfo.
+pub fn parse_esds(p: &[u8]) -> Option<AacConfig> {
+    let mut pos = 0;
+    // ES_Descriptor
+    let (tag, _) = descriptor_header(p, &mut pos)?;
+    if tag != 0x03 {
+        return None;
+    }
+    let es_flags = *p.get(pos + 2)?;
+    pos += 3;
+    if es_flags & 0x80 != 0 {
+        pos += 2; // dependsOn_ES_ID
+    }
+    if es_flags & 0x40 != 0 {
+        pos += 1 + *p.get(pos)? as usize; // URL
+    }
+    if es_flags & 0x20 != 0 {
+        pos += 2; // OCR_ES_Id
+    }
+    // DecoderConfigDescriptor
+    let (tag, _) = descriptor_header(p, &mut pos)?;
+    if tag != 0x04 {
+        return None;
+    }
+    pos += 13;
+    // DecoderSpecificInfo
+    let (tag, len) = descriptor_header(p, &mut pos)?;
+    if tag != 0x05 {
+        return None;
+    }
+    parse_audio_specific_config(p.get(pos..pos + len)?)
+}
+
+fn descriptor_header(p: &[u8], pos: &mut usize) -> Option<(u8, usize)> {
+    let tag = *p.get(*pos)?;
+    *pos += 1;
+    let mut len = 0usize;
+    for _ in 0..4 {
+        let b = *p.get(*pos)?;
+        *pos += 1;
+        len = (len << 7) | (b & 0x7F) as usize;
+        if b & 0x80 == 0 {
+            break;
+        }
+    }
+    Some((tag, len))
+}
+
+/// The core object type, rate and channels of an AudioSpecificConfig.
+/// Explicitly signalled SBR/PS (object type 5 or 29) is followed by the
+/// extension rate and the core object type, which is what ADTS carries.
+fn parse_audio_specific_config(asc: &[u8]) -> Option<AacConfig> {
+    let mut bits = BitReader::new(asc);
+    let mut object_type = audio_object_type(&mut bits)?;
+    let sampling_frequency_index = frequency_index(&mut bits)?;
+    let channel_configuration = bits.read(4).ok()? as u8;
+    if object_type == 5 || object_type == 29 {
+        frequency_index(&mut bits)?;
+        object_type = audio_object_type(&mut bits)?;
+    }
+    Some(AacConfig {
+        object_type,
+        sampling_frequency_index,
+        channel_configuration,
+    })
+}
+
+fn audio_object_type(bits: &mut BitReader) -> Option<u8> {
+    match bits.read(5).ok()? as u8 {
+        31 => Some(32 + bits.read(6).ok()? as u8),
+        t => Some(t),
+    }
+}
+
+/// The 4-bit index, skipping the 24-bit frequency that follows index 15.
+fn frequency_index(bits: &mut BitReader) -> Option<u8> {
+    let index = bits.read(4).ok()? as u8;
+    if index == 15 {
+        bits.read(24).ok()?;
+    }
+    Some(index)
+}
+
+/// 7-byte ADTS header (no CRC) for a raw AAC frame of `payload_len` bytes.
+///
+/// Fails for object types that ADTS cannot signal (anything but 1-4), for
+/// an explicit sampling frequency and for frames longer than the 13-bit
+/// frame length allows.
+pub fn adts_header(config: &AacConfig, payload_len: usize) -> Result<[u8; 7], ExportError> {
+    if !(1..=4).contains(&config.object_type) || config.sampling_frequency_index > 12 {
+        return Err(ExportError::UnsupportedAacProfile(config.object_type));
+    }
+    let len = payload_len
+        .checked_add(7)
+        .filter(|&len| len <= 0x1FFF)
+        .ok_or(ExportError::AdtsFrameTooLong(payload_len))?;
+    let profile = config.object_type - 1;
+    let ch = config.channel_configuration;
+    Ok([
+        0xFF,
+        0xF1, // MPEG-4, layer 0, no CRC
+        (profile << 6) | (config.sampling_frequency_index << 2) | ((ch >> 2) & 1),
+        ((ch & 3) << 6) | ((len >> 11) & 0x03) as u8,
+        (len >> 3) as u8,
+        ((len & 7) << 5) as u8 | 0x1F,
+        0xFC, // buffer fullness 0x7FF, one raw data block
+    ])
+}
+
+/// Write the samples of `track_id` to `w` as an elementary stream.
+///
+/// Video samples have their NAL length prefixes replaced by start codes,
+/// and the avcC/hvcC parameter sets are written before every sync sample.
+/// AAC frames each get an ADTS header built from the esds.
+///
+/// `boxes` must come from [`crate::get_boxes`] with `decode = true` on the
+/// same reader.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{elementary::export_elementary_stream, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// let mut out = File::create("video.h264")?;
+/// let summary = export_elementary_stream(&mut file, &boxes, 1, &mut out)?;
+/// println!("{} samples, {} bytes", summary.sample_count, summary.bytes_written);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn export_elementary_stream<R: Read + Seek, W: Write>(
+    r: &mut R,
+    boxes: &[crate::Box],
+    track_id: u32,
+    w: &mut W,
+) -> Result<ExportSummary, ExportError> {
+    let track = crate::track::tracks(boxes)
+        .into_iter()
+        .find(|t| t.track_id() == Some(track_id))
+        .ok_or(ExportError::TrackNotFound(track_id))?;
+    let codec = track
+        .sample_entry()
+        .map(|e| e.codec.clone())
+        .unwrap_or_default();
+
+    let samples = crate::samples::track_samples_from_boxes(boxes)
+        .map_err(ExportError::SampleTable)?
+        .into_iter()
+        .find(|t| t.track_id == track_id)
+        .map(|t| t.samples)
+        .unwrap_or_default();
+
+    let (format, writer) = match codec.as_str() {
+        "avc1" | "avc3" => {
+            let config = config_payload(r, &track, "avcC")?
+                .as_deref()
+                .and_then(parse_avcc)
+                .ok_or(ExportError::InvalidConfig("avcC"))?;
+            (
+                StreamFormat::H264,
+                SampleWriter::AnnexB(config, NalCodec::Avc),
+            )
+        }
+        "hvc1" | "hev1" => {
+            let config = config_payload(r, &track, "hvcC")?
+                .as_deref()
+                .and_then(parse_hvcc)
+                .ok_or(ExportError::InvalidConfig("hvcC"))?;
+            (
+                StreamFormat::H265,
+                SampleWriter::AnnexB(config, NalCodec::Hevc),
+            )
+        }
+        "mp4a" => {
+            let config = config_payload(r, &track, "esds")?
+                .as_deref()
+                .and_then(parse_esds)
+                .ok_or(ExportError::InvalidConfig("esds"))?;
+            // Fail before writing anything if ADTS cannot carry this profile
+            adts_header(&config, 0)?;
+            (StreamFormat::Aac, SampleWriter::Adts(config))
+        }
+        _ => return Err(ExportError::UnsupportedCodec(codec)),
+    };
+
+    let len = r.seek(SeekFrom::End(0))?;
+    let mut bytes_written = 0u64;
+    for s in &samples {
+        let end = s.file_offset.saturating_add(s.size as u64);
+        if end > len {
+            return Err(ExportError::SampleOutOfBounds {
+                index: s.index,
+                end,
+                len,
+            });
+        }
+        let data = s.read_data(r)?;
+        bytes_written += writer.write(w, s.index, s.is_sync, &data)?;
+    }
+
+    Ok(ExportSummary {
+        track_id,
+        format,
+        sample_count: samples.len() as u32,
+        bytes_written,
+    })
+}
+
+enum SampleWriter {
+    AnnexB(NalConfig, NalCodec),
+    Adts(AacConfig),
+}
+
+impl SampleWriter {
+    /// Write one sample and return the number of bytes written.
+    fn write<W: Write>(
+        &self,
+        w: &mut W,
+        index: u32,
+        is_sync: bool,
+        data: &[u8],
+    ) -> Result<u64, ExportError> {
+        let mut written = 0u64;
+        match self {
+            SampleWriter::AnnexB(config, codec) => {
+                if is_sync {
+                    for ps in &config.parameter_sets {
+                        w.write_all(&START_CODE)?;
+                        w.write_all(ps)?;
+                        written += (START_CODE.len() + ps.len()) as u64;
+                    }
+                }
+                for nal in nal_units(data, config.length_size, *codec) {
+                    let nal = nal.map_err(|_| ExportError::InvalidSample(index))?;
+                    w.write_all(&START_CODE)?;
+                    w.write_all(nal.data)?;
+                    written += (START_CODE.len() + nal.data.len()) as u64;
+                }
+            }
+            SampleWriter::Adts(config) => {
+                w.write_all(&adts_header(config, data.len())?)?;
+                w.write_all(data)?;
+                written += 7 + data.len() as u64;
+            }
+        }
+        Ok(written)
+    }
+}
+
+/// Payload of a configuration box (avcC, hvcC, esds) in the first sample
+/// entry of `track`.
+fn config_payload<R: Read + Seek>(
+    r: &mut R,
+    track: &Track,
+    typ: &str,
+) -> Result<Option<Vec<u8>>, ExportError> {
+    let Some(b) = track.sample_entry_box().and_then(|e| e.child(typ)) else {
+        return Ok(None);
+    };
+    match (b.payload_offset, b.payload_size) {
+        (Some(off), Some(len)) => Ok(Some(read_slice(r, off, len)?)),
+        _ => Ok(None),
+    }
+}
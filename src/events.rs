@@ -0,0 +1,213 @@
+//! Event-driven (SAX-style) parsing: callbacks for every box instead of a
+//! tree, so huge files can be scanned in constant memory and the scan can
+//! stop as soon as the caller has what it needs.
+
+use crate::boxes::{BoxHeader, FourCC};
+use crate::known_boxes::KnownBox;
+use crate::parser::{
+    DEFAULT_MAX_DEPTH, Result, meta_is_full_box, read_box_header, sample_entry_fields,
+};
+use byteorder::{BigEndian, ReadBytesExt};
+use std::io::{Read, Seek, SeekFrom};
+
+/// What [`parse_events`] does after a callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    /// Go on, into the children of the box if it has any
+    Continue,
+    /// Go on with the next sibling; [`BoxVisitor::on_box_end`] is still
+    /// called for the box
+    SkipChildren,
+    /// End the parse now; no further callbacks
+    Stop,
+}
+
+/// Callbacks for [`parse_events`]. Every method defaults to
+/// [`Visit::Continue`], so visitors implement only what they need.
+///
+/// `path` holds the types from the top-level box down to the current one,
+/// e.g. `[moov, trak, tkhd]`.
+pub trait BoxVisitor {
+    /// A box header was read; its children (if any) follow.
+    fn on_box_start(&mut self, header: &BoxHeader, path: &[FourCC]) -> Visit {
+        let _ = (header, path);
+        Visit::Continue
+    }
+
+    /// The version and flags of the full box whose start was just
+    /// reported. [`Visit::SkipChildren`] skips the children of full boxes
+    /// that have some (stsd, meta).
+    fn on_fullbox(&mut self, header: &BoxHeader, version: u8, flags: u32) -> Visit {
+        let _ = (header, version, flags);
+        Visit::Continue
+    }
+
+    /// The box and all its children have been reported.
+    fn on_box_end(&mut self, header: &BoxHeader, path: &[FourCC]) -> Visit {
+        let _ = (header, path);
+        Visit::Continue
+    }
+}
+
+/// Walk the boxes from the reader's current position to its end, calling
+/// `visitor` for each, without keeping any of them. Boxes are classified
+/// as [`get_boxes`](crate::get_boxes) does (containers, full boxes, sample
+/// entries, QuickTime meta); a child claiming more than its parent holds is
+/// cut at the parent's end, and boxes nested deeper than
+/// [`DEFAULT_MAX_DEPTH`] are reported without their children.
+///
+/// Returns `Ok(false)` when the visitor stopped the walk, `Ok(true)` when
+/// it reached the end.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::boxes::{BoxHeader, FourCC};
+/// use mp4box::events::{BoxVisitor, Visit, parse_events};
+///
+/// /// Stops at the first moov
+/// struct FindMoov(Option<u64>);
+///
+/// impl BoxVisitor for FindMoov {
+///     fn on_box_start(&mut self, header: &BoxHeader, _path: &[FourCC]) -> Visit {
+///         if &header.typ.0 == b"moov" {
+///             self.0 = Some(header.start);
+///             return Visit::Stop;
+///         }
+///         Visit::SkipChildren
+///     }
+/// }
+///
+/// let mut file = std::fs::File::open("video.mp4")?;
+/// let mut find = FindMoov(None);
+/// parse_events(&mut file, &mut find)?;
+/// println!("moov at {:?}", find.0);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn parse_events<R, V>(r: &mut R, visitor: &mut V) -> Result<bool>
+where
+    R: Read + Seek,
+    V: BoxVisitor + ?Sized,
+{
+    let start = r.stream_position()?;
+    let end = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(start))?;
+    let mut walk = Walk {
+        visitor,
+        path: Vec::new(),
+    };
+    walk.siblings(r, end, None)
+}
+
+struct Walk<'a, V: ?Sized> {
+    visitor: &'a mut V,
+    path: Vec<FourCC>,
+}
+
+impl<V: BoxVisitor + ?Sized> Walk<'_, V> {
+    /// Boxes from the current position to `parent_end`; false once stopped.
+    fn siblings<R: Read + Seek>(
+        &mut self,
+        r: &mut R,
+        parent_end: u64,
+        parent: Option<FourCC>,
+    ) -> Result<bool> {
+        while r.stream_position()? < parent_end {
+            let h = read_box_header(r)?;
+            let box_end = match h.size {
+                0 => parent_end,
+                size => h.start.saturating_add(size).min(parent_end),
+            };
+
+            self.path.push(h.typ);
+            let visit = self.visitor.on_box_start(&h, &self.path);
+            if visit == Visit::Stop
+                || (visit == Visit::Continue && !self.content(r, &h, box_end, parent)?)
+                || self.visitor.on_box_end(&h, &self.path) == Visit::Stop
+            {
+                return Ok(false);
+            }
+            self.path.pop();
+            r.seek(SeekFrom::Start(box_end))?;
+        }
+        Ok(true)
+    }
+
+    /// Version/flags and children of `h`; false once stopped.
+    fn content<R: Read + Seek>(
+        &mut self,
+        r: &mut R,
+        h: &BoxHeader,
+        box_end: u64,
+        parent: Option<FourCC>,
+    ) -> Result<bool> {
+        if self.path.len() > DEFAULT_MAX_DEPTH {
+            return Ok(true);
+        }
+        let data_offset = h.start + h.header_size;
+        let parent = parent.map(|p| p.0);
+
+        // Every child of ilst is a metadata item holding data atoms
+        if parent == Some(*b"ilst") {
+            r.seek(SeekFrom::Start(data_offset))?;
+            return self.siblings(r, box_end, Some(h.typ));
+        }
+        // Every child of stsd is a sample entry
+        if parent == Some(*b"stsd") {
+            return match sample_entry_fields(r, h)? {
+                Some(fields) if tiles(r, data_offset + fields, box_end)? => {
+                    r.seek(SeekFrom::Start(data_offset + fields))?;
+                    self.siblings(r, box_end, Some(h.typ))
+                }
+                _ => Ok(true),
+            };
+        }
+
+        let known = KnownBox::from(h.typ);
+        if known.is_container() || (&h.typ.0 == b"meta" && !meta_is_full_box(r, h, box_end)?) {
+            r.seek(SeekFrom::Start(data_offset))?;
+            return self.siblings(r, box_end, Some(h.typ));
+        }
+        if !known.is_full_box() {
+            return Ok(true);
+        }
+
+        r.seek(SeekFrom::Start(data_offset))?;
+        let version = r.read_u8()?;
+        let flags = r.read_u24::<BigEndian>()?;
+        match self.visitor.on_fullbox(h, version, flags) {
+            Visit::Stop => return Ok(false),
+            Visit::SkipChildren => return Ok(true),
+            Visit::Continue => {}
+        }
+        match &h.typ.0 {
+            // u32 entry_count, then one box per sample entry
+            b"stsd" if box_end >= data_offset + 8 => {
+                r.seek(SeekFrom::Start(data_offset + 8))?;
+                self.siblings(r, box_end, Some(h.typ))
+            }
+            b"meta" => self.siblings(r, box_end, Some(h.typ)),
+            _ => Ok(true),
+        }
+    }
+}
+
+/// Whether `from..end` is exactly a run of box headers and their sizes,
+/// as the tail of a sample entry with child boxes is.
+fn tiles<R: Read + Seek>(r: &mut R, from: u64, end: u64) -> Result<bool> {
+    let mut pos = from;
+    while pos < end {
+        if pos + 8 > end {
+            return Ok(false);
+        }
+        r.seek(SeekFrom::Start(pos))?;
+        let h = match read_box_header(r) {
+            Ok(h) => h,
+            Err(_) => return Ok(false),
+        };
+        if h.size == 0 || pos + h.size > end {
+            return Ok(false);
+        }
+        pos += h.size;
+    }
+    Ok(true)
+}
@@ -0,0 +1,190 @@
+//! Streaming extraction of embedded blobs (cover art, ICC profiles, XMP,
+//! XML) with size caps, so callers never need to hold a blob in memory.
+
+use crate::util::read_slice;
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// XMP packets live in a uuid box with this extended type.
+pub const XMP_UUID: [u8; 16] = [
+    0xBE, 0x7A, 0xCF, 0xCB, 0x97, 0xA9, 0x42, 0xE8, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3, 0xAF, 0xAC,
+];
+
+/// What an embedded blob holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlobKind {
+    /// ilst/covr data atom
+    CoverArt,
+    /// colr box with a `prof` or `rICC` colour type
+    IccProfile,
+    /// uuid box with [`XMP_UUID`]
+    Xmp,
+    /// meta/xml box
+    Xml,
+}
+
+/// Location of an embedded blob in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct EmbeddedBlob {
+    pub kind: BlobKind,
+    /// Absolute offset of the first blob byte
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Result of a successful [`stream_blob`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlobInfo {
+    pub size: u64,
+    /// Sniffed from the leading bytes, see [`sniff_mime`]
+    pub mime_type: &'static str,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExtractError {
+    #[error("blob of {size} bytes exceeds the {limit} byte limit")]
+    TooLarge { size: u64, limit: u64 },
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Guess a MIME type from the first bytes of a blob.
+pub fn sniff_mime(head: &[u8]) -> &'static str {
+    let trimmed = head
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .map_or(&[][..], |i| &head[i..]);
+
+    if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if head.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if head.starts_with(b"GIF8") {
+        "image/gif"
+    } else if head.starts_with(b"BM") {
+        "image/bmp"
+    } else if head.len() >= 40 && &head[36..40] == b"acsp" {
+        "application/vnd.iccprofile"
+    } else if trimmed.starts_with(b"<x:xmpmeta") || trimmed.starts_with(b"<?xpacket") {
+        "application/rdf+xml"
+    } else if trimmed.starts_with(b"<?xml") || trimmed.starts_with(b"<") {
+        "application/xml"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Copy `size` bytes at `offset` from `r` into `w` in fixed-size chunks.
+///
+/// Blobs larger than `max_bytes` are rejected with
+/// [`ExtractError::TooLarge`] before anything is read or written.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::extract::{BlobKind, find_embedded_blobs, stream_blob};
+/// use mp4box::get_boxes;
+/// use std::fs::File;
+///
+/// let mut file = File::open("song.m4a")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for blob in find_embedded_blobs(&mut file, &boxes)? {
+///     if blob.kind == BlobKind::CoverArt {
+///         let mut out = File::create("cover.bin")?;
+///         let info = stream_blob(&mut file, blob.offset, blob.size, &mut out, 10 << 20)?;
+///         println!("wrote {} ({} bytes)", info.mime_type, info.size);
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn stream_blob<R: Read + Seek, W: Write>(
+    r: &mut R,
+    offset: u64,
+    size: u64,
+    w: &mut W,
+    max_bytes: u64,
+) -> Result<BlobInfo, ExtractError> {
+    if size > max_bytes {
+        return Err(ExtractError::TooLarge {
+            size,
+            limit: max_bytes,
+        });
+    }
+
+    let head = read_slice(r, offset, size.min(64))?;
+    let mime_type = sniff_mime(&head);
+    w.write_all(&head)?;
+
+    let rest = size - head.len() as u64;
+    r.seek(SeekFrom::Start(offset + head.len() as u64))?;
+    let copied = std::io::copy(&mut r.take(rest), w)?;
+    if copied < rest {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+
+    Ok(BlobInfo { size, mime_type })
+}
+
+/// Locate cover art, ICC profiles, XMP packets and XML documents in a
+/// decoded box tree. Reads the colour type of every `colr` box from `r`.
+pub fn find_embedded_blobs<R: Read + Seek>(
+    r: &mut R,
+    boxes: &[crate::Box],
+) -> std::io::Result<Vec<EmbeddedBlob>> {
+    let mut out = Vec::new();
+    collect(r, boxes, None, &mut out)?;
+    Ok(out)
+}
+
+fn collect<R: Read + Seek>(
+    r: &mut R,
+    boxes: &[crate::Box],
+    parent: Option<&str>,
+    out: &mut Vec<EmbeddedBlob>,
+) -> std::io::Result<()> {
+    let xmp_hex: String = XMP_UUID.iter().map(|b| format!("{:02x}", b)).collect();
+
+    for b in boxes {
+        let payload = b.payload_offset.zip(b.payload_size);
+        match (b.typ.as_str(), payload) {
+            // Skip the data atom's type indicator and locale
+            ("data", Some((off, len))) if parent == Some("covr") && len > 8 => {
+                out.push(EmbeddedBlob {
+                    kind: BlobKind::CoverArt,
+                    offset: off + 8,
+                    size: len - 8,
+                });
+            }
+            ("colr", Some((off, len))) if len > 4 => {
+                let colour_type = read_slice(r, off, 4)?;
+                if colour_type == b"prof" || colour_type == b"rICC" {
+                    out.push(EmbeddedBlob {
+                        kind: BlobKind::IccProfile,
+                        offset: off + 4,
+                        size: len - 4,
+                    });
+                }
+            }
+            ("uuid", Some((off, len))) if b.uuid.as_deref() == Some(xmp_hex.as_str()) => {
+                out.push(EmbeddedBlob {
+                    kind: BlobKind::Xmp,
+                    offset: off,
+                    size: len,
+                });
+            }
+            ("xml ", Some((off, len))) => {
+                out.push(EmbeddedBlob {
+                    kind: BlobKind::Xml,
+                    offset: off,
+                    size: len,
+                });
+            }
+            _ => {}
+        }
+        if let Some(kids) = &b.children {
+            collect(r, kids, Some(b.typ.as_str()), out)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Progressive-download readiness: where moov sits relative to the media.
+
+use serde::Serialize;
+
+/// Whether moov comes before the first top-level mdat.
+///
+/// A file without mdat counts as faststart when it has a moov; a file
+/// without moov never does.
+pub fn is_faststart(tree: &[crate::Box]) -> bool {
+    let moov = tree.iter().position(|b| b.typ == "moov");
+    let mdat = tree.iter().position(|b| b.typ == "mdat");
+    match (moov, mdat) {
+        (Some(moov), Some(mdat)) => moov < mdat,
+        (moov, _) => moov.is_some(),
+    }
+}
+
+/// Result of [`startup_report`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StartupReport {
+    pub faststart: bool,
+    pub moov_offset: Option<u64>,
+    pub moov_size: Option<u64>,
+    pub first_mdat_offset: Option<u64>,
+    /// Bytes from the start of the file a player must have before it can
+    /// decode the first sample of every track: all of moov plus the
+    /// furthest first sample. None without moov.
+    pub bytes_before_playback: Option<u64>,
+}
+
+/// How much of the file a progressive download needs before playback.
+///
+/// Sample positions need a tree parsed with `decode = true`; otherwise only
+/// the moov is taken into account.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{faststart::startup_report, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let report = startup_report(&get_boxes(&mut file, size, true)?);
+/// if !report.faststart {
+///     println!("moov after mdat: {:?} bytes before playback", report.bytes_before_playback);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn startup_report(tree: &[crate::Box]) -> StartupReport {
+    let moov = tree.iter().find(|b| b.typ == "moov");
+    let first_mdat_offset = tree.iter().find(|b| b.typ == "mdat").map(|b| b.offset);
+
+    let bytes_before_playback = moov.map(|moov| {
+        let first_samples = crate::samples::track_samples_from_boxes(tree)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|t| t.samples.first())
+            .map(|s| s.file_offset.saturating_add(s.size as u64))
+            .max()
+            .unwrap_or(0);
+        moov.offset.saturating_add(moov.size).max(first_samples)
+    });
+
+    StartupReport {
+        faststart: is_faststart(tree),
+        moov_offset: moov.map(|b| b.offset),
+        moov_size: moov.map(|b| b.size),
+        first_mdat_offset,
+        bytes_before_playback,
+    }
+}
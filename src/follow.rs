@@ -0,0 +1,85 @@
+//! Incremental parsing of files that are still being written, such as the
+//! moof/mdat pairs a live fragmented recording appends.
+
+use crate::api::{Box, ParseOptions, build_box};
+use crate::parser::{ParseError, ParseWarning, parse_children_with, read_box_header};
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Remembers how far a growing file has been parsed and, on each
+/// [`poll`](Self::poll), parses only the top-level boxes appended since.
+///
+/// A box is returned once all of it is in the file: a header that is only
+/// partly written, a box whose declared size runs past the current end,
+/// and a box with size 0 ("to the end of the file", which never ends while
+/// the file grows) are left for a later poll. Media ranges are not linked,
+/// since a moof's media may arrive in a later poll than the moof.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::ParseOptions;
+/// use mp4box::follow::Follower;
+///
+/// let mut file = std::fs::File::open("live.mp4")?;
+/// let mut follower = Follower::new(ParseOptions::new());
+/// loop {
+///     let (boxes, _) = follower.poll(&mut file)?;
+///     for b in &boxes {
+///         println!("{} @ {}", b.typ, b.offset);
+///     }
+///     std::thread::sleep(std::time::Duration::from_millis(500));
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct Follower {
+    offset: u64,
+    options: ParseOptions,
+}
+
+impl Follower {
+    /// Follower starting at the beginning of the file.
+    pub fn new(options: ParseOptions) -> Self {
+        Follower { offset: 0, options }
+    }
+
+    /// Follower starting at the top-level box at `offset`, e.g. one saved
+    /// from [`offset`](Self::offset) by an earlier run.
+    pub fn resume_at(offset: u64, options: ParseOptions) -> Self {
+        Follower { offset, options }
+    }
+
+    /// Offset of the next top-level box to parse: everything before it has
+    /// been returned.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Parse the top-level boxes completed since the last poll, in file
+    /// order. Returns nothing new while the next box is still incomplete.
+    pub fn poll<R: Read + Seek>(
+        &mut self,
+        r: &mut R,
+    ) -> anyhow::Result<(Vec<Box>, Vec<ParseWarning>)> {
+        let file_len = r.seek(SeekFrom::End(0))?;
+        let mut state = self.options.parse_state();
+        let mut boxes = Vec::new();
+
+        while self.offset < file_len {
+            r.seek(SeekFrom::Start(self.offset))?;
+            let h = match read_box_header(r) {
+                Ok(h) => h,
+                Err(ParseError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            };
+            if h.size == 0 || h.start.saturating_add(h.size) > file_len {
+                break;
+            }
+            let end = h.start + h.size;
+            r.seek(SeekFrom::Start(self.offset))?;
+            for b in parse_children_with(r, end, &mut state)? {
+                boxes.push(build_box(r, &b, &self.options, &mut state.list));
+            }
+            self.offset = end;
+        }
+        Ok((boxes, state.list))
+    }
+}
@@ -0,0 +1,485 @@
+//! Movie fragment (moof/traf/trun) sample resolution and checks.
+
+use crate::registry::{
+    SampleFlags, SdtpEntry, StructuredData, TfhdData, TrexData, TrunData, TrunEntry,
+};
+use serde::Serialize;
+
+/// How the sample data declared by a moof lines up with the mdat after it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum FragmentDataStatus {
+    /// The declared samples cover the mdat payload exactly
+    Exact,
+    /// The mdat payload has bytes no sample refers to, before, between or
+    /// after the samples
+    Slack { bytes: u64 },
+    /// Samples share bytes of the mdat payload
+    Overlap { bytes: u64 },
+    /// Samples point outside the mdat payload
+    Overrun { bytes: u64 },
+    /// The moof is not followed by an mdat
+    MissingMdat,
+}
+
+/// Result of [`check_fragment_data`] for one moof.
+#[derive(Debug, Clone, Serialize)]
+pub struct FragmentDataCheck {
+    pub moof_offset: u64,
+    pub sequence_number: Option<u32>,
+    pub mdat_offset: Option<u64>,
+    pub mdat_payload_size: Option<u64>,
+    /// Sum of all trun sample sizes
+    pub declared_size: u64,
+    /// Bytes between the start of the mdat payload and the first sample
+    /// (negative when the first sample starts before the payload)
+    pub leading_gap: i64,
+    /// Bytes between the last sample and the end of the mdat payload
+    /// (negative when the samples run past the payload)
+    pub trailing_gap: i64,
+    /// Bytes between samples that no sample covers
+    pub interior_gap: u64,
+    /// Bytes covered by more than one sample
+    pub overlap: u64,
+    pub status: FragmentDataStatus,
+}
+
+/// One entry of [`fragment_index`]: a moof and the mdat after it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FragmentIndexEntry {
+    pub moof_offset: u64,
+    pub moof_size: u64,
+    /// mfhd sequence number
+    pub sequence_number: Option<u32>,
+    /// tfhd track IDs, in traf order
+    pub track_ids: Vec<u32>,
+    /// Samples and duration of each traf, in traf order
+    pub tracks: Vec<FragmentTrack>,
+    /// Smallest tfdt baseMediaDecodeTime of the trafs (in each track's
+    /// timescale); None when no traf has a tfdt
+    pub earliest_decode_time: Option<u64>,
+    pub mdat_offset: Option<u64>,
+    /// Whole mdat box, header included
+    pub mdat_size: Option<u64>,
+}
+
+/// One traf of a [`FragmentIndexEntry`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FragmentTrack {
+    pub track_id: u32,
+    pub sample_count: u64,
+    /// Sum of the sample durations, with tfhd and trex defaults applied
+    pub duration: u64,
+}
+
+/// Which box supplied a defaulted fragment sample field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DefaultSource {
+    /// The trun entry itself
+    Trun,
+    /// trun first_sample_flags (flags of the first sample only)
+    FirstSampleFlags,
+    /// tfhd default
+    Tfhd,
+    /// trex default in moov/mvex
+    Trex,
+    /// Nowhere; the value is 0
+    Missing,
+}
+
+/// What the data offsets of a traf are relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataBase {
+    /// tfhd base_data_offset
+    BaseDataOffset,
+    /// Start of the moof (default-base-is-moof, or the first traf)
+    Moof,
+    /// End of the previous traf's data
+    PreviousTraf,
+}
+
+/// Where each defaulted field of a fragment sample came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FragmentSampleSources {
+    pub duration: DefaultSource,
+    pub size: DefaultSource,
+    pub flags: DefaultSource,
+    pub data_base: DataBase,
+}
+
+/// One sample of a movie fragment with the trex/tfhd defaults applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FragmentSample {
+    pub track_id: u32,
+    pub file_offset: u64,
+    pub size: u32,
+    pub duration: u32,
+    /// Raw sample_flags; see [`SampleFlags::from_bits`]
+    pub flags: u32,
+    pub composition_offset: i64,
+    pub sources: FragmentSampleSources,
+}
+
+impl FragmentSample {
+    pub fn is_sync(&self) -> bool {
+        !SampleFlags::from_bits(self.flags).sample_is_non_sync_sample
+    }
+}
+
+/// The samples of one traf, in decode order.
+#[derive(Debug, Clone)]
+pub(crate) struct TrafSamples {
+    pub track_id: u32,
+    /// tfdt baseMediaDecodeTime, when the traf has one
+    pub base_media_decode_time: Option<u64>,
+    pub samples: Vec<FragmentSample>,
+    /// sdtp entries of the traf, one per sample; empty without sdtp
+    pub sdtp: Vec<SdtpEntry>,
+}
+
+/// mfhd sequence number of a decoded moof.
+pub(crate) fn sequence_number(moof: &crate::Box) -> Option<u32> {
+    moof.children
+        .iter()
+        .flatten()
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::MovieFragmentHeader(m)) => Some(m.sequence_number),
+            _ => None,
+        })
+}
+
+/// Collect the `trex` defaults of a decoded box tree (moov/mvex/trex).
+pub(crate) fn trex_defaults(boxes: &[crate::Box]) -> Vec<TrexData> {
+    boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .filter_map(|moov| moov.children.as_ref())
+        .flatten()
+        .filter(|b| b.typ == "mvex")
+        .filter_map(|mvex| mvex.children.as_ref())
+        .flatten()
+        .filter_map(|b| match &b.structured_data {
+            Some(StructuredData::TrackExtends(t)) => Some(t.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Resolve the file offset and size of every sample described by a decoded
+/// `moof`, following the tfhd/trun base-offset rules of ISO/IEC 14496-12.
+pub(crate) fn moof_samples(moof: &crate::Box, trex: &[TrexData]) -> Vec<FragmentSample> {
+    moof_trafs(moof, trex)
+        .into_iter()
+        .flat_map(|t| t.samples)
+        .collect()
+}
+
+/// Resolve every traf of a decoded `moof` into its samples.
+pub(crate) fn moof_trafs(moof: &crate::Box, trex: &[TrexData]) -> Vec<TrafSamples> {
+    let mut out = Vec::new();
+    // Without an explicit base, the first traf starts at the moof and each
+    // following traf continues where the previous one's data ended.
+    let mut implicit_base = moof.offset;
+
+    let trafs = moof.children.iter().flatten().filter(|b| b.typ == "traf");
+
+    for traf in trafs {
+        let kids = traf.children.as_deref().unwrap_or_default();
+        let Some(tfhd) = kids.iter().find_map(|b| match &b.structured_data {
+            Some(StructuredData::TrackFragmentHeader(h)) => Some(h),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let trex = trex.iter().find(|t| t.track_id == tfhd.track_id);
+        let base_media_decode_time = kids.iter().find_map(|b| match &b.structured_data {
+            Some(StructuredData::TrackFragmentDecodeTime(t)) => Some(t.base_media_decode_time),
+            _ => None,
+        });
+
+        let (base, data_base) = match tfhd.base_data_offset {
+            Some(offset) => (offset, DataBase::BaseDataOffset),
+            None if tfhd.decoded_flags.default_base_is_moof => (moof.offset, DataBase::Moof),
+            None if implicit_base == moof.offset => (moof.offset, DataBase::Moof),
+            None => (implicit_base, DataBase::PreviousTraf),
+        };
+
+        let mut samples = Vec::new();
+        let mut next = base;
+        // Samples past the end of the address space cannot be placed
+        'runs: for trun in kids.iter().filter_map(|b| match &b.structured_data {
+            Some(StructuredData::TrackFragmentRun(r)) => Some(r),
+            _ => None,
+        }) {
+            if let Some(data_offset) = trun.data_offset {
+                next = base.saturating_add_signed(data_offset as i64);
+            }
+            for (i, entry) in trun.entries.iter().enumerate() {
+                let sample = run_sample(trun, i, entry, tfhd, trex, next, data_base);
+                let end = next.checked_add(sample.size as u64);
+                samples.push(sample);
+                match end {
+                    Some(end) => next = end,
+                    None => break 'runs,
+                }
+            }
+        }
+        implicit_base = next;
+        let sdtp = kids
+            .iter()
+            .find_map(|b| match &b.structured_data {
+                Some(StructuredData::SampleDependencyType(d)) => Some(d.entries.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        out.push(TrafSamples {
+            track_id: tfhd.track_id,
+            base_media_decode_time,
+            samples,
+            sdtp,
+        });
+    }
+
+    out
+}
+
+fn run_sample(
+    trun: &TrunData,
+    index: usize,
+    entry: &TrunEntry,
+    tfhd: &TfhdData,
+    trex: Option<&TrexData>,
+    file_offset: u64,
+    data_base: DataBase,
+) -> FragmentSample {
+    let (size, size_source) = cascade([
+        (entry.sample_size, DefaultSource::Trun),
+        (tfhd.default_sample_size, DefaultSource::Tfhd),
+        (trex.map(|t| t.default_sample_size), DefaultSource::Trex),
+    ]);
+    let (duration, duration_source) = cascade([
+        (entry.sample_duration, DefaultSource::Trun),
+        (tfhd.default_sample_duration, DefaultSource::Tfhd),
+        (trex.map(|t| t.default_sample_duration), DefaultSource::Trex),
+    ]);
+    let (flags, flags_source) = cascade([
+        (entry.sample_flags, DefaultSource::Trun),
+        (
+            trun.first_sample_flags.filter(|_| index == 0),
+            DefaultSource::FirstSampleFlags,
+        ),
+        (tfhd.default_sample_flags, DefaultSource::Tfhd),
+        (trex.map(|t| t.default_sample_flags), DefaultSource::Trex),
+    ]);
+
+    FragmentSample {
+        track_id: tfhd.track_id,
+        file_offset,
+        size,
+        duration,
+        flags,
+        composition_offset: entry.sample_composition_time_offset.unwrap_or(0),
+        sources: FragmentSampleSources {
+            duration: duration_source,
+            size: size_source,
+            flags: flags_source,
+            data_base,
+        },
+    }
+}
+
+/// First value present, most specific level first.
+fn cascade<const N: usize>(levels: [(Option<u32>, DefaultSource); N]) -> (u32, DefaultSource) {
+    levels
+        .into_iter()
+        .find_map(|(v, source)| v.map(|v| (v, source)))
+        .unwrap_or((0, DefaultSource::Missing))
+}
+
+/// Check that the trun samples of every top-level moof tile the payload of
+/// the mdat that follows it, taking data_offset into account: no bytes
+/// outside the payload, shared between samples or left unused.
+///
+/// `boxes` must come from [`crate::get_boxes`] with `decode = true`; `size`
+/// is the file length, needed for an mdat that runs to end of file.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, fragments::{FragmentDataStatus, check_fragment_data}};
+/// use std::fs::File;
+///
+/// let mut file = File::open("fragmented.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for check in check_fragment_data(&boxes, size) {
+///     if check.status != FragmentDataStatus::Exact {
+///         println!("moof @{:#x}: {:?}", check.moof_offset, check.status);
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn check_fragment_data(boxes: &[crate::Box], size: u64) -> Vec<FragmentDataCheck> {
+    let trex = trex_defaults(boxes);
+    let mut out = Vec::new();
+
+    for (i, moof) in boxes.iter().enumerate() {
+        if moof.typ != "moof" {
+            continue;
+        }
+        let sequence_number = sequence_number(moof);
+        let samples = moof_samples(moof, &trex);
+        let declared_size = samples
+            .iter()
+            .fold(0u64, |sum, s| sum.saturating_add(s.size as u64));
+
+        let Some(mdat) = boxes.get(i + 1).filter(|b| b.typ == "mdat") else {
+            out.push(FragmentDataCheck {
+                moof_offset: moof.offset,
+                sequence_number,
+                mdat_offset: None,
+                mdat_payload_size: None,
+                declared_size,
+                leading_gap: 0,
+                trailing_gap: 0,
+                interior_gap: 0,
+                overlap: 0,
+                status: FragmentDataStatus::MissingMdat,
+            });
+            continue;
+        };
+
+        let payload_start = mdat.offset.saturating_add(mdat.header_size);
+        let payload_end = if mdat.size == 0 {
+            size
+        } else {
+            mdat.offset.saturating_add(mdat.size)
+        };
+        let payload_size = payload_end.saturating_sub(payload_start);
+
+        let mut ranges: Vec<(u64, u64)> = samples
+            .iter()
+            .map(|s| (s.file_offset, s.file_offset.saturating_add(s.size as u64)))
+            .collect();
+        ranges.sort_unstable();
+
+        // Walk the samples in file order, tracking the furthest byte covered
+        let mut covered = ranges.first().map_or(payload_start, |r| r.0);
+        let (mut interior_gap, mut overlap, mut overrun) = (0u64, 0u64, 0u64);
+        for &(start, end) in &ranges {
+            if start > covered {
+                interior_gap = interior_gap.saturating_add(start - covered);
+            } else {
+                overlap = overlap.saturating_add(end.min(covered) - start);
+            }
+            covered = covered.max(end);
+            let outside = payload_start.saturating_sub(start).min(end - start)
+                + end.saturating_sub(payload_end).min(end - start);
+            overrun = overrun.saturating_add(outside);
+        }
+        let data_start = ranges.first().map_or(payload_start, |r| r.0);
+        let data_end = ranges.last().map_or(payload_start, |_| covered);
+
+        let status = if overrun > 0 {
+            FragmentDataStatus::Overrun { bytes: overrun }
+        } else if overlap > 0 {
+            FragmentDataStatus::Overlap { bytes: overlap }
+        } else if declared_size != payload_size {
+            // Without overruns or overlaps the samples cover exactly
+            // declared_size bytes of the payload
+            FragmentDataStatus::Slack {
+                bytes: payload_size.saturating_sub(declared_size),
+            }
+        } else {
+            FragmentDataStatus::Exact
+        };
+
+        out.push(FragmentDataCheck {
+            moof_offset: moof.offset,
+            sequence_number,
+            mdat_offset: Some(mdat.offset),
+            mdat_payload_size: Some(payload_size),
+            declared_size,
+            leading_gap: signed_gap(data_start, payload_start),
+            trailing_gap: signed_gap(payload_end, data_end),
+            interior_gap,
+            overlap,
+            status,
+        });
+    }
+
+    out
+}
+
+/// `to - from` as an i64, saturating at its bounds.
+fn signed_gap(to: u64, from: u64) -> i64 {
+    (to as i128 - from as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+/// List every top-level moof with its tracks, their sample counts and
+/// durations, the earliest decode time and the mdat that follows it.
+///
+/// `boxes` must come from [`crate::get_boxes`] with `decode = true`; `size`
+/// is the file length, needed for an mdat that runs to end of file.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, fragments::fragment_index};
+/// use std::fs::File;
+///
+/// let mut file = File::open("fragmented.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for f in fragment_index(&boxes, size) {
+///     println!("moof @{} tracks {:?} t={:?}", f.moof_offset, f.track_ids, f.earliest_decode_time);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn fragment_index(boxes: &[crate::Box], size: u64) -> Vec<FragmentIndexEntry> {
+    let trex = trex_defaults(boxes);
+    let mut out = Vec::new();
+
+    for (i, moof) in boxes.iter().enumerate() {
+        if moof.typ != "moof" {
+            continue;
+        }
+        let mut track_ids = Vec::new();
+        let mut earliest_decode_time: Option<u64> = None;
+        for traf in moof.children.iter().flatten().filter(|b| b.typ == "traf") {
+            for b in traf.children.iter().flatten() {
+                match &b.structured_data {
+                    Some(StructuredData::TrackFragmentHeader(h)) => track_ids.push(h.track_id),
+                    Some(StructuredData::TrackFragmentDecodeTime(t)) => {
+                        let t = t.base_media_decode_time;
+                        earliest_decode_time = Some(earliest_decode_time.map_or(t, |e| e.min(t)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let tracks = moof_trafs(moof, &trex)
+            .into_iter()
+            .map(|t| FragmentTrack {
+                track_id: t.track_id,
+                sample_count: t.samples.len() as u64,
+                duration: t.samples.iter().map(|s| s.duration as u64).sum(),
+            })
+            .collect();
+
+        let mdat = boxes.get(i + 1).filter(|b| b.typ == "mdat");
+        out.push(FragmentIndexEntry {
+            moof_offset: moof.offset,
+            moof_size: moof.size,
+            sequence_number: sequence_number(moof),
+            track_ids,
+            tracks,
+            earliest_decode_time,
+            mdat_offset: mdat.map(|m| m.offset),
+            mdat_size: mdat.map(|m| if m.size == 0 { size - m.offset } else { m.size }),
+        });
+    }
+
+    out
+}
@@ -0,0 +1,218 @@
+//! Encoder delay and padding of audio tracks.
+
+use crate::metadata::itunes_tags;
+use crate::registry::{SampleGroupEntry, StructuredData, TagValue};
+use serde::Serialize;
+
+/// Fields of an iTunes `iTunSMPB` freeform tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ItunSmpb {
+    /// Priming samples inserted by the encoder
+    pub encoder_delay: u32,
+    /// Samples of padding after the end of the audio
+    pub padding: u32,
+    /// Length of the source audio in samples
+    pub original_sample_count: u64,
+}
+
+/// Parse an `iTunSMPB` value such as
+/// `" 00000000 00000840 000001CC 0000000000046E00 ..."`.
+///
+/// The fields are whitespace-separated hex numbers; the first is reserved.
+pub fn parse_itunsmpb(value: &str) -> Option<ItunSmpb> {
+    let mut fields = value
+        .split_whitespace()
+        .map(|f| u64::from_str_radix(f, 16).ok());
+    fields.next()?;
+    let encoder_delay = u32::try_from(fields.next()??).ok()?;
+    let padding = u32::try_from(fields.next()??).ok()?;
+    let original_sample_count = fields.next()??;
+    Some(ItunSmpb {
+        encoder_delay,
+        padding,
+        original_sample_count,
+    })
+}
+
+/// Where the priming/remainder counts of a track came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GaplessSource {
+    /// The first non-empty edit of the track's elst
+    EditList,
+    /// The file's `com.apple.iTunes:iTunSMPB` tag
+    ITunSmpb,
+}
+
+/// Gapless playback information for one audio track.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GaplessInfo {
+    pub track_id: u32,
+    /// Sample rate the counts are expressed in
+    pub sample_rate: u32,
+    /// Decoded samples to drop from the start
+    pub priming_samples: u64,
+    /// Decoded samples to drop from the end
+    pub remainder_samples: u64,
+    /// Samples left to play, when known
+    pub valid_samples: Option<u64>,
+    /// Pre-roll from an sgpd 'roll' group; negative values are the number
+    /// of packets to decode before the first one that is played
+    pub roll_distance: Option<i16>,
+    /// None when neither an edit list nor iTunSMPB describes the track
+    pub source: Option<GaplessSource>,
+}
+
+/// Priming and remainder sample counts of every audio track.
+///
+/// An edit list whose first non-empty edit starts after media time 0 or
+/// ends before the media does is used as is. Otherwise the iTunSMPB tag,
+/// if present, is applied to the first audio track, which is how iTunes
+/// writes it. Counts are converted from the mdhd timescale to the sample
+/// entry's sample rate when the two differ.
+///
+/// Requires a tree produced with `decode = true`.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{gapless::gapless_info, get_boxes};
+/// use std::fs::File;
+///
+/// let mut file = File::open("song.m4a")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for g in gapless_info(&boxes) {
+///     println!("track {}: skip {} / trim {}", g.track_id,
+///         g.priming_samples, g.remainder_samples);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn gapless_info(boxes: &[crate::Box]) -> Vec<GaplessInfo> {
+    let smpb = itunes_tags(boxes)
+        .get("com.apple.iTunes:iTunSMPB")
+        .and_then(|v| match v.first() {
+            Some(TagValue::Text(t)) => parse_itunsmpb(t),
+            _ => None,
+        });
+
+    let mut out = Vec::new();
+    for moov in boxes.iter().filter(|b| b.typ == "moov") {
+        let movie_timescale = data(moov)
+            .find_map(|d| match d {
+                StructuredData::MovieHeader(m) => Some(m.timescale),
+                _ => None,
+            })
+            .unwrap_or(0);
+        for trak in moov.children.iter().flatten().filter(|b| b.typ == "trak") {
+            if let Some(mut info) = track_gapless(trak, movie_timescale) {
+                if info.source.is_none()
+                    && let Some(smpb) = smpb.filter(|_| out.is_empty())
+                {
+                    info.priming_samples = smpb.encoder_delay as u64;
+                    info.remainder_samples = smpb.padding as u64;
+                    info.valid_samples = Some(smpb.original_sample_count);
+                    info.source = Some(GaplessSource::ITunSmpb);
+                }
+                out.push(info);
+            }
+        }
+    }
+    out
+}
+
+fn track_gapless(trak: &crate::Box, movie_timescale: u32) -> Option<GaplessInfo> {
+    let mdia = trak.child("mdia")?;
+    let is_audio = data(mdia)
+        .any(|d| matches!(d, StructuredData::HandlerReference(h) if h.handler_type == "soun"));
+    if !is_audio {
+        return None;
+    }
+    let track_id = data(trak).find_map(|d| match d {
+        StructuredData::TrackHeader(t) => Some(t.track_id),
+        _ => None,
+    })?;
+    let mdhd = data(mdia).find_map(|d| match d {
+        StructuredData::MediaHeader(m) => Some(m),
+        _ => None,
+    })?;
+    let stbl = mdia.descendants_of_type("stbl").next();
+    let stbl_data = || stbl.into_iter().flat_map(data);
+
+    let media_duration = stbl_data()
+        .find_map(|d| match d {
+            StructuredData::DecodingTimeToSample(s) => Some(
+                s.entries
+                    .iter()
+                    .map(|e| e.sample_count as u64 * e.sample_delta as u64)
+                    .sum(),
+            ),
+            _ => None,
+        })
+        .unwrap_or(mdhd.duration);
+    let sample_rate = stbl_data()
+        .find_map(|d| match d {
+            StructuredData::SampleDescription(s) => s.entries.first()?.sample_rate,
+            _ => None,
+        })
+        .filter(|&r| r > 0)
+        .unwrap_or(mdhd.timescale);
+    let roll_distance = stbl_data().find_map(|d| match d {
+        StructuredData::SampleGroupDescription(g) if g.grouping_type == "roll" => {
+            g.entries.iter().find_map(|e| match e {
+                SampleGroupEntry::Roll { roll_distance } => Some(*roll_distance),
+                _ => None,
+            })
+        }
+        _ => None,
+    });
+
+    let mut info = GaplessInfo {
+        track_id,
+        sample_rate,
+        priming_samples: 0,
+        remainder_samples: 0,
+        valid_samples: None,
+        roll_distance,
+        source: None,
+    };
+
+    let edit = trak
+        .child("edts")
+        .into_iter()
+        .flat_map(data)
+        .find_map(|d| match d {
+            StructuredData::EditList(e) => e.entries.iter().find(|e| e.media_time >= 0),
+            _ => None,
+        });
+    if let Some(edit) = edit.filter(|_| mdhd.timescale > 0) {
+        let start = edit.media_time as u64;
+        // A zero duration edit runs to the end of the media
+        let played = if edit.segment_duration == 0 || movie_timescale == 0 {
+            media_duration.saturating_sub(start)
+        } else {
+            rescale(edit.segment_duration, mdhd.timescale, movie_timescale)
+        }
+        .min(media_duration.saturating_sub(start));
+        let remainder = media_duration.saturating_sub(start + played);
+        if start > 0 || remainder > 0 {
+            let to_samples = |t: u64| rescale(t, sample_rate, mdhd.timescale);
+            info.priming_samples = to_samples(start);
+            info.remainder_samples = to_samples(remainder);
+            info.valid_samples = Some(to_samples(played));
+            info.source = Some(GaplessSource::EditList);
+        }
+    }
+    Some(info)
+}
+
+/// `value * to / from`, rounded to nearest.
+fn rescale(value: u64, to: u32, from: u32) -> u64 {
+    ((value as u128 * to as u128 + from as u128 / 2) / from as u128) as u64
+}
+
+fn data(b: &crate::Box) -> impl Iterator<Item = &StructuredData> {
+    b.children
+        .iter()
+        .flatten()
+        .filter_map(|c| c.structured_data.as_ref())
+}
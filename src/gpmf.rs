@@ -0,0 +1,234 @@
+//! GoPro GPMF telemetry (`gpmd` timed-metadata samples).
+//!
+//! GPMF is a KLV stream: a 4-byte key, a 1-byte type, a 1-byte struct size
+//! and a 16-bit repeat count, followed by the data padded to 4 bytes. Type 0
+//! marks a nested KLV list (`DEVC` > `STRM` > ...).
+//!
+//! Enabled with the `gpmf` feature.
+
+use crate::registry::StructuredData;
+use crate::walk::{Children, walk};
+use serde::Serialize;
+use std::io::{Read, Seek};
+use std::ops::ControlFlow;
+
+/// One KLV entry of a GPMF stream.
+#[derive(Debug, Clone, Serialize)]
+pub struct KlvEntry {
+    pub key: String,
+    /// GPMF type character ('s', 'l', 'f', 'c', ...); '\0' for nested entries
+    pub type_char: char,
+    pub struct_size: u8,
+    pub repeat: u16,
+    /// Nested entries when `type_char` is '\0'
+    pub children: Vec<KlvEntry>,
+    #[serde(skip)]
+    pub data: Vec<u8>,
+}
+
+impl KlvEntry {
+    /// Every numeric element of the entry, in stream order.
+    ///
+    /// Returns an empty list for strings, nested and unknown types.
+    pub fn numbers(&self) -> Vec<f64> {
+        let (width, conv): (usize, fn(&[u8]) -> f64) = match self.type_char {
+            'b' => (1, |b| b[0] as i8 as f64),
+            'B' => (1, |b| b[0] as f64),
+            's' => (2, |b| i16::from_be_bytes([b[0], b[1]]) as f64),
+            'S' => (2, |b| u16::from_be_bytes([b[0], b[1]]) as f64),
+            'l' => (4, |b| i32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64),
+            'L' => (4, |b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64),
+            'f' => (4, |b| f32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64),
+            'd' => (8, |b| f64::from_be_bytes(b[..8].try_into().unwrap())),
+            'j' => (8, |b| i64::from_be_bytes(b[..8].try_into().unwrap()) as f64),
+            'J' => (8, |b| u64::from_be_bytes(b[..8].try_into().unwrap()) as f64),
+            _ => return Vec::new(),
+        };
+        self.data.chunks_exact(width).map(conv).collect()
+    }
+
+    /// The entry as text, for 'c' (string) entries.
+    pub fn text(&self) -> Option<String> {
+        (self.type_char == 'c').then(|| {
+            String::from_utf8_lossy(&self.data)
+                .trim_end_matches('\0')
+                .to_string()
+        })
+    }
+}
+
+/// Parse a GPMF payload (one `gpmd` sample) into its KLV tree.
+///
+/// Parsing stops at the first truncated entry.
+pub fn parse(data: &[u8]) -> Vec<KlvEntry> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= data.len() {
+        let key = &data[pos..pos + 4];
+        if key == [0, 0, 0, 0] {
+            break;
+        }
+        let type_char = data[pos + 4] as char;
+        let struct_size = data[pos + 5];
+        let repeat = u16::from_be_bytes([data[pos + 6], data[pos + 7]]);
+        let len = struct_size as usize * repeat as usize;
+        let start = pos + 8;
+        let Some(body) = data.get(start..start + len) else {
+            break;
+        };
+
+        let nested = type_char == '\0';
+        out.push(KlvEntry {
+            key: String::from_utf8_lossy(key).to_string(),
+            type_char,
+            struct_size,
+            repeat,
+            children: if nested { parse(body) } else { Vec::new() },
+            data: if nested { Vec::new() } else { body.to_vec() },
+        });
+        pos = start + len.next_multiple_of(4);
+    }
+    out
+}
+
+/// One GPS5 fix.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GpsSample {
+    /// Degrees
+    pub latitude: f64,
+    /// Degrees
+    pub longitude: f64,
+    /// Metres (WGS 84)
+    pub altitude: f64,
+    /// m/s
+    pub speed_2d: f64,
+    /// m/s
+    pub speed_3d: f64,
+}
+
+/// Sensor streams collected from all `gpmd` samples of a file.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Telemetry {
+    pub gps: Vec<GpsSample>,
+    /// m/s², in the axis order stored by the camera
+    pub accelerometer: Vec<[f64; 3]>,
+    /// rad/s, in the axis order stored by the camera
+    pub gyroscope: Vec<[f64; 3]>,
+}
+
+impl Telemetry {
+    /// Add the GPS5, ACCL and GYRO streams of a parsed GPMF payload.
+    pub fn extend_from_klv(&mut self, entries: &[KlvEntry]) {
+        for e in entries {
+            if e.type_char != '\0' {
+                continue;
+            }
+            if e.key == "STRM" {
+                self.add_stream(&e.children);
+            } else {
+                self.extend_from_klv(&e.children);
+            }
+        }
+    }
+
+    fn add_stream(&mut self, entries: &[KlvEntry]) {
+        // SCAL holds one divisor for all elements or one per element
+        let scale = entries
+            .iter()
+            .find(|e| e.key == "SCAL")
+            .map(|e| e.numbers())
+            .unwrap_or_default();
+        let scaled = |e: &KlvEntry, width: usize| -> Vec<Vec<f64>> {
+            e.numbers()
+                .chunks_exact(width)
+                .map(|c| {
+                    c.iter()
+                        .enumerate()
+                        .map(|(i, v)| {
+                            let s = scale.get(i).or(scale.first()).copied().unwrap_or(1.0);
+                            if s == 0.0 { *v } else { v / s }
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+
+        for e in entries {
+            match e.key.as_str() {
+                "GPS5" => self.gps.extend(scaled(e, 5).into_iter().map(|v| GpsSample {
+                    latitude: v[0],
+                    longitude: v[1],
+                    altitude: v[2],
+                    speed_2d: v[3],
+                    speed_3d: v[4],
+                })),
+                "ACCL" => self
+                    .accelerometer
+                    .extend(scaled(e, 3).into_iter().map(|v| [v[0], v[1], v[2]])),
+                "GYRO" => self
+                    .gyroscope
+                    .extend(scaled(e, 3).into_iter().map(|v| [v[0], v[1], v[2]])),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Whether a decoded `trak` carries GPMF: a `GoPro MET` handler or a
+/// `gpmd` sample entry.
+pub fn is_gpmf_track(trak: &crate::Box) -> bool {
+    walk(
+        std::slice::from_ref(trak),
+        &mut |b: &crate::Box, _: &[&crate::Box]| {
+            let hit = match &b.structured_data {
+                Some(StructuredData::HandlerReference(h)) => h.name.trim() == "GoPro MET",
+                Some(StructuredData::SampleDescription(s)) => {
+                    s.entries.iter().any(|e| e.codec == "gpmd")
+                }
+                _ => false,
+            };
+            match hit {
+                true => ControlFlow::Break(()),
+                false => ControlFlow::Continue(Children::Visit),
+            }
+        },
+    )
+    .is_break()
+}
+
+/// Read and parse every GPMF track in a decoded box tree.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, gpmf::telemetry};
+/// use std::fs::File;
+///
+/// let mut file = File::open("GX010001.MP4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// let t = telemetry(&mut file, &boxes)?;
+/// if let Some(fix) = t.gps.first() {
+///     println!("start: {:.6}, {:.6}", fix.latitude, fix.longitude);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn telemetry<R: Read + Seek>(r: &mut R, boxes: &[crate::Box]) -> anyhow::Result<Telemetry> {
+    let mut out = Telemetry::default();
+    let traks = boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .filter_map(|moov| moov.children.as_ref())
+        .flatten()
+        .filter(|b| b.typ == "trak" && is_gpmf_track(b));
+
+    for trak in traks {
+        let Some(track) = crate::samples::extract_track_samples(trak, r)? else {
+            continue;
+        };
+        for s in &track.samples {
+            let data = s.read_data(r)?;
+            out.extend_from_klv(&parse(&data));
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,440 @@
+//! Read+Seek over HTTP range requests, so remote files can be parsed while
+//! fetching only the bytes the parser touches (box headers, moov, sidx)
+//! instead of the whole asset.
+//!
+//! [`HttpFetcher`] speaks plain HTTP/1.1 with the standard library only; for
+//! HTTPS or authenticated requests implement [`RangeFetch`] on top of an
+//! HTTP client and wrap it in a [`RangeReader`].
+//!
+//! Enabled with the `http` feature.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// A source of byte ranges of one resource.
+pub trait RangeFetch {
+    /// Total length of the resource in bytes.
+    fn total_len(&mut self) -> io::Result<u64>;
+
+    /// Bytes `start..start + len`; fewer only at the end of the resource.
+    fn fetch(&mut self, start: u64, len: u64) -> io::Result<Vec<u8>>;
+}
+
+/// [`RangeFetch`] over plain HTTP/1.1 `Range` requests, one connection per
+/// request.
+#[derive(Debug, Clone)]
+pub struct HttpFetcher {
+    host: String,
+    port: u16,
+    path: String,
+    timeout: Duration,
+}
+
+impl HttpFetcher {
+    /// Fetcher for an `http://host[:port]/path` URL.
+    pub fn new(url: &str) -> io::Result<Self> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, msg.to_string());
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            invalid("only http:// URLs are supported; implement RangeFetch for others")
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid("invalid port"))?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(invalid("URL has no host"));
+        }
+        Ok(HttpFetcher {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+            timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// Longest wait for connecting and for each read or write; 30 seconds
+    /// by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn connect(&self) -> io::Result<TcpStream> {
+        let mut last_err = None;
+        for addr in (self.host.as_str(), self.port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(self.timeout))?;
+                    stream.set_write_timeout(Some(self.timeout))?;
+                    return Ok(stream);
+                }
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "host has no addresses")))
+    }
+
+    /// Send a GET for `first..=last` and return the status, headers and
+    /// body.
+    fn get_range(&self, first: u64, last: u64) -> io::Result<Response> {
+        let mut stream = self.connect()?;
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nRange: bytes={first}-{last}\r\nConnection: close\r\n\r\n",
+            self.path, self.host
+        )?;
+        Response::read(BufReader::new(stream))
+    }
+}
+
+impl RangeFetch for HttpFetcher {
+    fn total_len(&mut self) -> io::Result<u64> {
+        let response = self.get_range(0, 0)?;
+        response.total_len()
+    }
+
+    fn fetch(&mut self, start: u64, len: u64) -> io::Result<Vec<u8>> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let response = self.get_range(start, start + len - 1)?;
+        if response.status == 416 {
+            // Range starts past the end
+            return Ok(Vec::new());
+        }
+        response.check_partial()?;
+        // A server answering another range would shift every offset
+        if response.range_start()? != start {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("server sent a range not starting at {start}"),
+            ));
+        }
+        Ok(response.body)
+    }
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn read<R: BufRead>(mut r: R) -> io::Result<Self> {
+        let bad = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let mut line = String::new();
+        r.read_line(&mut line)?;
+        let status = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| bad("malformed HTTP status line"))?;
+
+        let mut headers = Vec::new();
+        loop {
+            line.clear();
+            if r.read_line(&mut line)? == 0 {
+                return Err(bad("connection closed inside HTTP headers"));
+            }
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+
+        let response = Response {
+            status,
+            headers,
+            body: Vec::new(),
+        };
+        let chunked = response
+            .header("transfer-encoding")
+            .is_some_and(|v| v.to_ascii_lowercase().contains("chunked"));
+        let body = match response.header("content-length") {
+            _ if chunked => read_chunked(&mut r)?,
+            Some(len) => {
+                let len: u64 = len.parse().map_err(|_| bad("invalid Content-Length"))?;
+                let mut body = Vec::new();
+                r.take(len).read_to_end(&mut body)?;
+                body
+            }
+            None => {
+                let mut body = Vec::new();
+                r.read_to_end(&mut body)?;
+                body
+            }
+        };
+        Ok(Response { body, ..response })
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn check_partial(&self) -> io::Result<()> {
+        match self.status {
+            206 => Ok(()),
+            200 => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "server ignored the Range header",
+            )),
+            status => Err(io::Error::other(format!("HTTP status {status}"))),
+        }
+    }
+
+    /// First byte of `Content-Range: bytes <first>-<last>/<total>`.
+    fn range_start(&self) -> io::Result<u64> {
+        self.header("content-range")
+            .and_then(|v| v.strip_prefix("bytes "))
+            .and_then(|v| v.split_once('-'))
+            .and_then(|(first, _)| first.trim().parse().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "missing or invalid Content-Range",
+                )
+            })
+    }
+
+    /// Resource length from `Content-Range: bytes 0-0/<total>`.
+    fn total_len(&self) -> io::Result<u64> {
+        self.check_partial()?;
+        self.header("content-range")
+            .and_then(|v| v.rsplit_once('/'))
+            .and_then(|(_, total)| total.parse().ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "missing or unknown length in Content-Range",
+                )
+            })
+    }
+}
+
+/// Body sent with `Transfer-Encoding: chunked`, trailers skipped.
+fn read_chunked<R: BufRead>(r: &mut R) -> io::Result<Vec<u8>> {
+    let bad = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    let mut body = Vec::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if r.read_line(&mut line)? == 0 {
+            return Err(bad("connection closed inside a chunked body"));
+        }
+        // Chunk extensions follow a ';'
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = u64::from_str_radix(size, 16).map_err(|_| bad("invalid chunk size"))?;
+        if size == 0 {
+            break;
+        }
+        let read = r.by_ref().take(size).read_to_end(&mut body)?;
+        if read as u64 != size {
+            return Err(bad("connection closed inside a chunk"));
+        }
+        line.clear();
+        r.read_line(&mut line)?;
+        if !line.trim_end().is_empty() {
+            return Err(bad("chunk longer than its size"));
+        }
+    }
+    loop {
+        line.clear();
+        if r.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            return Ok(body);
+        }
+    }
+}
+
+/// Read+Seek over a [`RangeFetch`], fetching fixed-size chunks on demand
+/// and keeping the most recently used ones.
+///
+/// Seeks are free; a read fetches the chunk under the cursor plus
+/// `read_ahead` following chunks in one request, skipping chunks already
+/// cached. Parsing a progressive file this way fetches the box headers and
+/// the moov, not the mdat.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::get_boxes;
+/// use mp4box::http::RangeReader;
+///
+/// let mut remote = RangeReader::open("http://example.com/video.mp4")?;
+/// let size = remote.len();
+/// let boxes = get_boxes(&mut remote, size, true)?;
+/// println!("{} boxes, {} bytes fetched", boxes.len(), remote.bytes_fetched());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct RangeReader<F> {
+    fetcher: F,
+    len: u64,
+    pos: u64,
+    chunk_size: u64,
+    read_ahead: u64,
+    cache_chunks: usize,
+    /// Chunk index -> (bytes, last use)
+    cache: HashMap<u64, (Vec<u8>, u64)>,
+    clock: u64,
+    requests: u64,
+    bytes_fetched: u64,
+}
+
+impl RangeReader<HttpFetcher> {
+    /// Reader over an `http://` URL; asks the server for the length.
+    pub fn open(url: &str) -> io::Result<Self> {
+        RangeReader::new(HttpFetcher::new(url)?)
+    }
+}
+
+impl<F: RangeFetch> RangeReader<F> {
+    /// Reader with 64 KiB chunks, 3 chunks of read-ahead and room for 64
+    /// cached chunks.
+    pub fn new(mut fetcher: F) -> io::Result<Self> {
+        let len = fetcher.total_len()?;
+        Ok(RangeReader {
+            fetcher,
+            len,
+            pos: 0,
+            chunk_size: 64 * 1024,
+            read_ahead: 3,
+            cache_chunks: 64,
+            cache: HashMap::new(),
+            clock: 0,
+            requests: 0,
+            bytes_fetched: 0,
+        })
+    }
+
+    /// Bytes per fetched chunk. Clears the cache.
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self.cache.clear();
+        self
+    }
+
+    /// Extra chunks fetched after the one a read needs.
+    pub fn read_ahead(mut self, chunks: u64) -> Self {
+        self.read_ahead = chunks;
+        self
+    }
+
+    /// Most chunks kept; the least recently used go first.
+    pub fn cache_chunks(mut self, chunks: usize) -> Self {
+        self.cache_chunks = chunks.max(1);
+        self
+    }
+
+    /// Length of the resource.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Range requests made so far.
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// Bytes received so far.
+    pub fn bytes_fetched(&self) -> u64 {
+        self.bytes_fetched
+    }
+
+    pub fn into_inner(self) -> F {
+        self.fetcher
+    }
+
+    /// Make sure chunk `index` is cached, fetching it and the uncached
+    /// chunks of its read-ahead window in one request.
+    fn load(&mut self, index: u64) -> io::Result<()> {
+        self.clock += 1;
+        if let Some(entry) = self.cache.get_mut(&index) {
+            entry.1 = self.clock;
+            return Ok(());
+        }
+        let last_chunk = (self.len - 1) / self.chunk_size;
+        let mut count = 1;
+        while count <= self.read_ahead
+            && index + count <= last_chunk
+            && !self.cache.contains_key(&(index + count))
+        {
+            count += 1;
+        }
+
+        let start = index * self.chunk_size;
+        let len = (count * self.chunk_size).min(self.len - start);
+        let data = self.fetcher.fetch(start, len)?;
+        self.requests += 1;
+        self.bytes_fetched += data.len() as u64;
+        if data.is_empty() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        for (i, chunk) in data.chunks(self.chunk_size as usize).enumerate() {
+            self.cache
+                .insert(index + i as u64, (chunk.to_vec(), self.clock));
+        }
+
+        while self.cache.len() > self.cache_chunks {
+            let oldest = self
+                .cache
+                .iter()
+                .filter(|(i, _)| **i != index)
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(&i, _)| i);
+            match oldest {
+                Some(i) => self.cache.remove(&i),
+                None => break,
+            };
+        }
+        Ok(())
+    }
+}
+
+impl<F: RangeFetch> Read for RangeReader<F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len {
+            return Ok(0);
+        }
+        let index = self.pos / self.chunk_size;
+        self.load(index)?;
+        let chunk = &self.cache[&index].0;
+        let start = (self.pos - index * self.chunk_size) as usize;
+        if start >= chunk.len() {
+            // The server sent less than the length it announced
+            return Ok(0);
+        }
+        let n = buf.len().min(chunk.len() - start);
+        buf[..n].copy_from_slice(&chunk[start..start + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<F: RangeFetch> Seek for RangeReader<F> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::Current(d) => self.pos.checked_add_signed(d),
+            SeekFrom::End(d) => self.len.checked_add_signed(d),
+        };
+        self.pos = target
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start"))?;
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,144 @@
+//! Depth-first and breadth-first iteration over box trees, both the
+//! decoded [`Box`] tree and the raw [`BoxRef`] tree.
+
+use crate::api::Box;
+use crate::boxes::{BoxRef, FourCC};
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+/// A box in a tree that [`Iter`] can descend into.
+pub trait Node: Sized {
+    fn child_nodes(&self) -> &[Self];
+
+    /// Raw type of the box.
+    fn fourcc(&self) -> FourCC;
+}
+
+impl Node for Box {
+    fn child_nodes(&self) -> &[Self] {
+        self.children.as_deref().unwrap_or_default()
+    }
+
+    fn fourcc(&self) -> FourCC {
+        let mut raw = [0u8; 4];
+        match hex::decode_to_slice(&self.typ_hex, &mut raw) {
+            Ok(()) => FourCC(raw),
+            // Boxes built by hand may leave typ_hex empty
+            Err(_) => FourCC::from_str(&self.typ).unwrap_or(FourCC(*b"????")),
+        }
+    }
+}
+
+impl Node for BoxRef {
+    fn child_nodes(&self) -> &[Self] {
+        self.kind.children()
+    }
+
+    fn fourcc(&self) -> FourCC {
+        self.hdr.typ
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// Each box, then its children, then its next sibling (file order)
+    DepthFirst,
+    /// All boxes of one level before any box of the next
+    BreadthFirst,
+}
+
+/// Iterator over `(path, box)` pairs, where `path` holds the ancestors of
+/// the box from the first level iterated down to its parent.
+pub struct Iter<'a, T> {
+    order: Order,
+    pending: VecDeque<(Vec<&'a T>, &'a T)>,
+}
+
+impl<'a, T: Node> Iter<'a, T> {
+    /// Iterate `roots` and everything below them.
+    pub fn new(roots: &'a [T], order: Order) -> Self {
+        Iter {
+            order,
+            pending: roots.iter().map(|r| (Vec::new(), r)).collect(),
+        }
+    }
+}
+
+impl<'a, T: Node> Iterator for Iter<'a, T> {
+    type Item = (Vec<&'a T>, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (path, node) = self.pending.pop_front()?;
+        let kids = node.child_nodes();
+        if !kids.is_empty() {
+            let mut inner = path.clone();
+            inner.push(node);
+            let kids = kids.iter().map(|k| (inner.clone(), k));
+            match self.order {
+                Order::DepthFirst => {
+                    for (i, kid) in kids.enumerate() {
+                        self.pending.insert(i, kid);
+                    }
+                }
+                Order::BreadthFirst => self.pending.extend(kids),
+            }
+        }
+        Some((path, node))
+    }
+}
+
+impl Box {
+    /// The first direct child of type `typ`.
+    pub fn child(&self, typ: &str) -> Option<&Box> {
+        self.child_nodes().iter().find(|c| c.typ == typ)
+    }
+
+    /// This box and its descendants, depth-first.
+    pub fn iter(&self) -> Iter<'_, Box> {
+        Iter::new(std::slice::from_ref(self), Order::DepthFirst)
+    }
+
+    /// This box and its descendants, level by level.
+    pub fn iter_bfs(&self) -> Iter<'_, Box> {
+        Iter::new(std::slice::from_ref(self), Order::BreadthFirst)
+    }
+
+    /// Descendants (not this box) of type `typ`, depth-first.
+    ///
+    /// ```no_run
+    /// # fn f(trak: &mp4box::Box) {
+    /// let stbl = trak.descendants_of_type("stbl").next();
+    /// # }
+    /// ```
+    pub fn descendants_of_type<'a>(&'a self, typ: &'a str) -> impl Iterator<Item = &'a Box> {
+        Iter::new(self.child_nodes(), Order::DepthFirst)
+            .map(|(_, b)| b)
+            .filter(move |b| b.typ == typ)
+    }
+}
+
+impl BoxRef {
+    /// The first direct child whose FourCC reads `typ`.
+    pub fn child(&self, typ: &str) -> Option<&BoxRef> {
+        self.child_nodes()
+            .iter()
+            .find(|c| c.hdr.typ.0 == typ.as_bytes())
+    }
+
+    /// This box and its descendants, depth-first.
+    pub fn iter(&self) -> Iter<'_, BoxRef> {
+        Iter::new(std::slice::from_ref(self), Order::DepthFirst)
+    }
+
+    /// This box and its descendants, level by level.
+    pub fn iter_bfs(&self) -> Iter<'_, BoxRef> {
+        Iter::new(std::slice::from_ref(self), Order::BreadthFirst)
+    }
+
+    /// Descendants (not this box) whose FourCC reads `typ`, depth-first.
+    pub fn descendants_of_type<'a>(&'a self, typ: &'a str) -> impl Iterator<Item = &'a BoxRef> {
+        Iter::new(self.child_nodes(), Order::DepthFirst)
+            .map(|(_, b)| b)
+            .filter(move |b| b.hdr.typ.0 == typ.as_bytes())
+    }
+}
@@ -41,6 +41,7 @@ pub enum KnownBox {
 
     // mdia children
     Mdhd,
+    Elng,
     Hdlr,
     Minf,
 
@@ -94,11 +95,17 @@ pub enum KnownBox {
     Ipci,
     Ispe,
     Pixi,
+    Irot,
+    Imir,
     AuxC,
     Clap,
     Colr,
     Hvcc,
     Avcc,
+    Esds,
+    Uncc,
+    Cmpd,
+    Uncv,
     Pitm,
 
     // Encryption / CENC
@@ -123,6 +130,7 @@ pub enum KnownBox {
     Vp08,
     Vp09,
     Av01,
+    Encv,
 
     // Sample entries (audio)
     Mp4a,
@@ -133,6 +141,65 @@ pub enum KnownBox {
     Sawb,
     Alac,
     Flac,
+    Ipcm,
+    Fpcm,
+    Mha1,
+    Mhm1,
+    Dtsc,
+    Dtsh,
+    Dtsl,
+    Dtse,
+    Ac4,
+    Lpcm,
+    Sowt,
+    Twos,
+    Enca,
+
+    // Subtitle sample entries
+    Tx3g,
+    Wvtt,
+    Stpp,
+
+    // Audio sample entry children
+    Chnl,
+    Pcmc,
+    Mhac,
+    Ddts,
+    Dac4,
+
+    // udta children
+    Kind,
+    Xyz,
+    /// QuickTime international text atom (©nam, ©too, ©cmt, ...)
+    UserDataText(FourCC),
+    Ludt,
+    Tlou,
+    Alou,
+
+    // iTunes / QuickTime metadata (meta/ilst, meta/keys)
+    Ilst,
+    Data,
+    Mean,
+    Name,
+    Keys,
+    Id32,
+    Xml,
+    Bxml,
+
+    // Chapters (tref/chap, Nero udta/chpl)
+    Chap,
+    Chpl,
+
+    // Spherical video / spatial audio (Google spatial media)
+    St3d,
+    Sv3d,
+    Svhd,
+    Proj,
+    Prhd,
+    Equi,
+    Cbmp,
+    Mshp,
+    Sa3d,
 
     // Misc / QT-ish / common extras
     Pasp,
@@ -183,6 +250,7 @@ impl From<FourCC> for KnownBox {
             b"elst" => KnownBox::Elst,
 
             b"mdhd" => KnownBox::Mdhd,
+            b"elng" => KnownBox::Elng,
             b"hdlr" => KnownBox::Hdlr,
             b"minf" => KnownBox::Minf,
 
@@ -231,11 +299,17 @@ impl From<FourCC> for KnownBox {
             b"ipci" => KnownBox::Ipci,
             b"ispe" => KnownBox::Ispe,
             b"pixi" => KnownBox::Pixi,
+            b"irot" => KnownBox::Irot,
+            b"imir" => KnownBox::Imir,
             b"auxC" => KnownBox::AuxC,
             b"clap" => KnownBox::Clap,
             b"colr" => KnownBox::Colr,
             b"hvcC" => KnownBox::Hvcc,
             b"avcC" => KnownBox::Avcc,
+            b"esds" => KnownBox::Esds,
+            b"uncC" => KnownBox::Uncc,
+            b"cmpd" => KnownBox::Cmpd,
+            b"uncv" => KnownBox::Uncv,
             b"pitm" => KnownBox::Pitm,
 
             b"sinf" => KnownBox::Sinf,
@@ -258,15 +332,68 @@ impl From<FourCC> for KnownBox {
             b"vp08" => KnownBox::Vp08,
             b"vp09" => KnownBox::Vp09,
             b"av01" => KnownBox::Av01,
+            b"encv" => KnownBox::Encv,
 
             b"mp4a" => KnownBox::Mp4a,
             b"ac-3" => KnownBox::Ac3,
             b"ec-3" => KnownBox::Ec3,
-            b"opus" => KnownBox::Opus,
+            b"Opus" | b"opus" => KnownBox::Opus,
             b"samr" => KnownBox::Samr,
             b"sawb" => KnownBox::Sawb,
             b"alac" => KnownBox::Alac,
-            b"flac" => KnownBox::Flac,
+            b"fLaC" | b"flac" => KnownBox::Flac,
+            b"ipcm" => KnownBox::Ipcm,
+            b"fpcm" => KnownBox::Fpcm,
+            b"mha1" => KnownBox::Mha1,
+            b"mhm1" => KnownBox::Mhm1,
+            b"dtsc" => KnownBox::Dtsc,
+            b"dtsh" => KnownBox::Dtsh,
+            b"dtsl" => KnownBox::Dtsl,
+            b"dtse" => KnownBox::Dtse,
+            b"ac-4" => KnownBox::Ac4,
+            b"lpcm" => KnownBox::Lpcm,
+            b"sowt" => KnownBox::Sowt,
+            b"twos" => KnownBox::Twos,
+            b"enca" => KnownBox::Enca,
+
+            b"tx3g" => KnownBox::Tx3g,
+            b"wvtt" => KnownBox::Wvtt,
+            b"stpp" => KnownBox::Stpp,
+
+            b"chnl" => KnownBox::Chnl,
+            b"pcmC" => KnownBox::Pcmc,
+            b"mhaC" => KnownBox::Mhac,
+            b"ddts" => KnownBox::Ddts,
+            b"dac4" => KnownBox::Dac4,
+
+            b"kind" => KnownBox::Kind,
+            b"ludt" => KnownBox::Ludt,
+            b"tlou" => KnownBox::Tlou,
+            b"alou" => KnownBox::Alou,
+            b"\xA9xyz" => KnownBox::Xyz,
+            [0xA9, ..] => KnownBox::UserDataText(cc),
+
+            b"ilst" => KnownBox::Ilst,
+            b"data" => KnownBox::Data,
+            b"mean" => KnownBox::Mean,
+            b"name" => KnownBox::Name,
+            b"keys" => KnownBox::Keys,
+            b"ID32" => KnownBox::Id32,
+            b"xml " => KnownBox::Xml,
+            b"bxml" => KnownBox::Bxml,
+
+            b"chap" => KnownBox::Chap,
+            b"chpl" => KnownBox::Chpl,
+
+            b"st3d" => KnownBox::St3d,
+            b"sv3d" => KnownBox::Sv3d,
+            b"svhd" => KnownBox::Svhd,
+            b"proj" => KnownBox::Proj,
+            b"prhd" => KnownBox::Prhd,
+            b"equi" => KnownBox::Equi,
+            b"cbmp" => KnownBox::Cbmp,
+            b"mshp" => KnownBox::Mshp,
+            b"SA3D" => KnownBox::Sa3d,
 
             b"pasp" => KnownBox::Pasp,
             b"cslg" => KnownBox::Cslg,
@@ -294,17 +421,22 @@ impl KnownBox {
                 | KnownBox::Stbl
                 | KnownBox::Edts
                 | KnownBox::Udta
-                | KnownBox::Meta
                 | KnownBox::Moof
                 | KnownBox::Mvex
                 | KnownBox::Mfra
                 | KnownBox::Meco
                 | KnownBox::Traf
                 | KnownBox::Sinf
+                | KnownBox::Schi
                 | KnownBox::Iprp
                 | KnownBox::Iref
                 | KnownBox::Ipco
                 | KnownBox::Ipma
+                | KnownBox::Sv3d
+                | KnownBox::Proj
+                | KnownBox::Ilst
+                | KnownBox::Tref
+                | KnownBox::Ludt
         )
     }
 
@@ -315,6 +447,7 @@ impl KnownBox {
             KnownBox::Mvhd
                 | KnownBox::Tkhd
                 | KnownBox::Mdhd
+                | KnownBox::Elng
                 | KnownBox::Hdlr
                 | KnownBox::Vmhd
                 | KnownBox::Smhd
@@ -337,6 +470,7 @@ impl KnownBox {
                 | KnownBox::Sbgp
                 | KnownBox::Subs
                 | KnownBox::Elst
+                | KnownBox::Esds
                 | KnownBox::Sidx
                 | KnownBox::Mehd
                 | KnownBox::Trex
@@ -349,10 +483,79 @@ impl KnownBox {
                 | KnownBox::Iinf
                 | KnownBox::Infe
                 | KnownBox::Pitm
+                | KnownBox::Ispe
+                | KnownBox::Pixi
                 | KnownBox::Pssh
-                | KnownBox::Schi
+                | KnownBox::Schm
+                | KnownBox::Tenc
                 | KnownBox::Saio
                 | KnownBox::Saiz
+                | KnownBox::Chnl
+                | KnownBox::Pcmc
+                | KnownBox::Kind
+                | KnownBox::St3d
+                | KnownBox::Svhd
+                | KnownBox::Prhd
+                | KnownBox::Equi
+                | KnownBox::Cbmp
+                | KnownBox::Mshp
+                | KnownBox::Keys
+                | KnownBox::Id32
+                | KnownBox::Xml
+                | KnownBox::Bxml
+                | KnownBox::Chpl
+                | KnownBox::Uncc
+                | KnownBox::Tlou
+                | KnownBox::Alou
+                | KnownBox::Meta
+        )
+    }
+
+    /// Returns `true` if this is a VisualSampleEntry format (avc1, hvc1, ...).
+    pub fn is_visual_sample_entry(&self) -> bool {
+        matches!(
+            self,
+            KnownBox::Avc1
+                | KnownBox::Avc2
+                | KnownBox::Avc3
+                | KnownBox::Avc4
+                | KnownBox::Hev1
+                | KnownBox::Hvc1
+                | KnownBox::Vvc1
+                | KnownBox::Mp4v
+                | KnownBox::Vp08
+                | KnownBox::Vp09
+                | KnownBox::Av01
+                | KnownBox::Uncv
+                | KnownBox::Encv
+        )
+    }
+
+    /// Returns `true` if this is an AudioSampleEntry format (mp4a, ac-3, ...).
+    pub fn is_audio_sample_entry(&self) -> bool {
+        matches!(
+            self,
+            KnownBox::Mp4a
+                | KnownBox::Ac3
+                | KnownBox::Ec3
+                | KnownBox::Opus
+                | KnownBox::Samr
+                | KnownBox::Sawb
+                | KnownBox::Alac
+                | KnownBox::Flac
+                | KnownBox::Ipcm
+                | KnownBox::Fpcm
+                | KnownBox::Mha1
+                | KnownBox::Mhm1
+                | KnownBox::Dtsc
+                | KnownBox::Dtsh
+                | KnownBox::Dtsl
+                | KnownBox::Dtse
+                | KnownBox::Ac4
+                | KnownBox::Lpcm
+                | KnownBox::Sowt
+                | KnownBox::Twos
+                | KnownBox::Enca
         )
     }
 }
@@ -388,6 +591,7 @@ impl KnownBox {
             KnownBox::Meco => "Additional Metadata Container Box",
             KnownBox::Elst => "Edit List Box",
             KnownBox::Mdhd => "Media Header Box",
+            KnownBox::Elng => "Extended Language Tag Box",
             KnownBox::Hdlr => "Handler Reference Box",
             KnownBox::Minf => "Media Information Box",
             KnownBox::Vmhd => "Video Media Header Box",
@@ -431,11 +635,17 @@ impl KnownBox {
             KnownBox::Ipci => "Item Property Container Info Box",
             KnownBox::Ispe => "Image Spatial Extents Property",
             KnownBox::Pixi => "Pixel Information Property",
+            KnownBox::Irot => "Image Rotation Property",
+            KnownBox::Imir => "Image Mirroring Property",
             KnownBox::AuxC => "Auxiliary Type Property",
             KnownBox::Clap => "Clean Aperture Box",
             KnownBox::Colr => "Colour Information Box",
             KnownBox::Hvcc => "HEVC Decoder Configuration Box",
             KnownBox::Avcc => "AVC Decoder Configuration Box",
+            KnownBox::Esds => "Elementary Stream Descriptor Box",
+            KnownBox::Uncc => "Uncompressed Frame Configuration Box",
+            KnownBox::Cmpd => "Component Definition Box",
+            KnownBox::Uncv => "Uncompressed Video Sample Entry",
             KnownBox::Pitm => "Primary Item Box",
             KnownBox::Sinf => "Protection Scheme Information Box",
             KnownBox::Schm => "Scheme Type Box",
@@ -456,6 +666,7 @@ impl KnownBox {
             KnownBox::Vp08 => "VP8 Video Sample Entry",
             KnownBox::Vp09 => "VP9 Video Sample Entry",
             KnownBox::Av01 => "AV1 Video Sample Entry",
+            KnownBox::Encv => "Encrypted Video Sample Entry",
             KnownBox::Mp4a => "MPEG-4 Audio Sample Entry",
             KnownBox::Ac3 => "AC-3 Audio Sample Entry",
             KnownBox::Ec3 => "Enhanced AC-3 Audio Sample Entry",
@@ -464,6 +675,77 @@ impl KnownBox {
             KnownBox::Sawb => "AMR-WB Audio Sample Entry",
             KnownBox::Alac => "Apple Lossless Sample Entry",
             KnownBox::Flac => "FLAC Audio Sample Entry",
+            KnownBox::Ipcm => "Integer PCM Audio Sample Entry",
+            KnownBox::Fpcm => "Floating-point PCM Audio Sample Entry",
+            KnownBox::Mha1 => "MPEG-H Audio Sample Entry",
+            KnownBox::Mhm1 => "MPEG-H Audio (MHAS) Sample Entry",
+            KnownBox::Dtsc => "DTS Core Sample Entry",
+            KnownBox::Dtsh => "DTS-HD Sample Entry",
+            KnownBox::Dtsl => "DTS-HD Lossless Sample Entry",
+            KnownBox::Dtse => "DTS Express Sample Entry",
+            KnownBox::Ac4 => "AC-4 Audio Sample Entry",
+            KnownBox::Lpcm => "QuickTime Linear PCM Sample Entry",
+            KnownBox::Sowt => "Little-endian PCM Sample Entry",
+            KnownBox::Twos => "Big-endian PCM Sample Entry",
+            KnownBox::Enca => "Encrypted Audio Sample Entry",
+            KnownBox::Tx3g => "3GPP Timed Text Sample Entry",
+            KnownBox::Wvtt => "WebVTT Sample Entry",
+            KnownBox::Stpp => "XML Subtitle Sample Entry",
+            KnownBox::Chnl => "Channel Layout Box",
+            KnownBox::Pcmc => "PCM Configuration Box",
+            KnownBox::Mhac => "MPEG-H 3D Audio Configuration Box",
+            KnownBox::Ddts => "DTS Specific Box",
+            KnownBox::Dac4 => "AC-4 Specific Box",
+            KnownBox::Kind => "Track Kind Box",
+            KnownBox::Ludt => "Loudness Box",
+            KnownBox::Tlou => "Track Loudness Info Box",
+            KnownBox::Alou => "Album Loudness Info Box",
+            KnownBox::Xyz => "GPS Location",
+            KnownBox::UserDataText(cc) => match &cc.0[1..] {
+                b"nam" => "Title",
+                b"too" => "Encoding Tool",
+                b"cmt" => "Comment",
+                b"des" => "Description",
+                b"day" => "Date",
+                b"ART" => "Artist",
+                b"aut" => "Author",
+                b"alb" => "Album",
+                b"cpy" => "Copyright",
+                b"swr" => "Software",
+                b"enc" => "Encoded By",
+                b"inf" => "Information",
+                b"mak" => "Make",
+                b"mod" => "Model",
+                b"wrt" => "Writer",
+                b"dir" => "Director",
+                b"prd" => "Producer",
+                b"req" => "Playback Requirements",
+                b"fmt" => "Format",
+                b"src" => "Source",
+                b"hst" => "Host Computer",
+                b"key" => "Keywords",
+                b"gen" => "Genre",
+                _ => "QuickTime User Data Text",
+            },
+            KnownBox::Ilst => "Metadata Item List Box",
+            KnownBox::Data => "Metadata Value Box",
+            KnownBox::Mean => "Metadata Mean Box",
+            KnownBox::Name => "Metadata Name Box",
+            KnownBox::Keys => "Metadata Item Keys Box",
+            KnownBox::Id32 => "ID3v2 Metadata Box",
+            KnownBox::Xml => "XML Box",
+            KnownBox::Bxml => "Binary XML Box",
+            KnownBox::Chap => "Chapter Track Reference",
+            KnownBox::Chpl => "Nero Chapter List Box",
+            KnownBox::St3d => "Stereoscopic 3D Video Box",
+            KnownBox::Sv3d => "Spherical Video Box",
+            KnownBox::Svhd => "Spherical Video Header Box",
+            KnownBox::Proj => "Projection Box",
+            KnownBox::Prhd => "Projection Header Box",
+            KnownBox::Equi => "Equirectangular Projection Box",
+            KnownBox::Cbmp => "Cubemap Projection Box",
+            KnownBox::Mshp => "Mesh Projection Box",
+            KnownBox::Sa3d => "Spatial Audio Box",
             KnownBox::Pasp => "Pixel Aspect Ratio Box",
             KnownBox::Cslg => "Composition Shift Least Greatest Box",
             KnownBox::Cprt => "Copyright Box",
@@ -77,21 +77,80 @@
 //!
 //! For more examples, see the `mp4dump` and `mp4info` binaries in this repository.
 
+pub mod accounting;
+pub mod alternates;
 pub mod api;
 pub mod boxes;
+pub mod brands;
+pub mod cenc;
+pub mod chapters;
+pub mod chunks;
+pub mod defrag;
+pub mod display;
+pub mod durations;
+pub mod elementary;
+pub mod events;
+pub mod extract;
+pub mod faststart;
+pub mod follow;
+pub mod fragments;
+pub mod gapless;
+#[cfg(feature = "gpmf")]
+pub mod gpmf;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod iter;
 pub mod known_boxes;
+pub mod language;
+pub mod metadata;
+pub mod nal;
+pub mod numfmt;
 pub mod parser;
+pub mod peek;
+pub mod query;
 pub mod registry;
+pub mod repair;
+pub mod sample_groups;
 pub mod samples;
+pub mod scan;
+pub mod seek;
+pub mod segments;
+pub mod source;
+pub mod stats;
+pub mod stream;
+pub mod summary;
+pub mod track;
 pub mod util;
+pub mod validate;
+pub mod walk;
 
 pub use boxes::{BoxHeader, BoxKey, BoxRef, FourCC, NodeKind};
-pub use parser::{parse_children, read_box_header};
+pub use parser::{
+    DEFAULT_MAX_DEPTH, ParseWarning, WarningKind, parse_children, parse_children_lenient,
+    parse_container, read_box_header,
+};
 pub use registry::{
-    BoxValue, Co64Data, CttsData, CttsEntry, HdlrData, MdhdData, Registry, SampleEntry, StcoData,
-    StructuredData, StscData, StscEntry, StsdData, StssData, StszData, SttsData, SttsEntry,
+    BoxValue, Co64Data, CttsData, CttsEntry, ElngData, HdlrData, MdhdData, MvhdData, Registry,
+    SampleEntry, StcoData, StructuredData, StscData, StscEntry, StsdData, StssData, StszData,
+    SttsData, SttsEntry,
 };
 
 // High-level API
-pub use api::{Box, HexDump, get_boxes, get_boxes_with_registry, hex_range};
-pub use samples::{SampleInfo, TrackSamples, track_samples_from_path, track_samples_from_reader};
+pub use alternates::{AlternateGroup, GroupedTrack, TrackGroupReport, TrackRole, track_groups};
+pub use api::{
+    Box, BoxPath, BoxRegion, ByteRange, DEFAULT_BUFFER_LIMIT, DEFAULT_MAX_TABLE_ENTRIES, HexDump,
+    ParseOptions, box_at_offset, format_box_path, get_boxes, get_boxes_lenient,
+    get_boxes_with_options, get_boxes_with_registry, hex_range,
+};
+pub use peek::{MdatPeek, PayloadFormat, classify_payload};
+pub use samples::{
+    FragmentTiming, SampleInfo, SampleIter, SyncSource, TrackSamples, track_samples_from_boxes,
+    track_samples_from_path, track_samples_from_reader,
+};
+pub use scan::{BoxCandidate, BoxScanner, scan_for, scan_for_boxes};
+pub use segments::{Segment, SegmentKind, classify_segment, segment_index};
+pub use source::{RandomAccess, Source, get_boxes_from};
+pub use summary::{
+    MovieSummary, TrackKind, TrackSummary, summarize, summarize_path, summarize_reader,
+};
+pub use track::{Track, TrackType, tracks};
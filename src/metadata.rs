@@ -0,0 +1,416 @@
+use crate::query::find_all;
+use crate::registry::{StructuredData, TagValue};
+use crate::util::read_slice;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek};
+
+/// Image format of embedded artwork.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFormat {
+    Jpeg,
+    Png,
+    Bmp,
+    Gif,
+    Unknown,
+}
+
+impl ImageFormat {
+    /// Identify an image from its leading magic bytes.
+    pub fn from_magic(data: &[u8]) -> Self {
+        if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            ImageFormat::Jpeg
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            ImageFormat::Png
+        } else if data.starts_with(b"BM") {
+            ImageFormat::Bmp
+        } else if data.starts_with(b"GIF8") {
+            ImageFormat::Gif
+        } else {
+            ImageFormat::Unknown
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ImageFormat::Jpeg => "image/jpeg",
+            ImageFormat::Png => "image/png",
+            ImageFormat::Bmp => "image/bmp",
+            ImageFormat::Gif => "image/gif",
+            ImageFormat::Unknown => "application/octet-stream",
+        }
+    }
+}
+
+/// One cover image from a `covr` item.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverArt {
+    pub format: ImageFormat,
+    /// Absolute file offset of the image bytes
+    pub offset: u64,
+    #[serde(skip)]
+    pub data: Vec<u8>,
+}
+
+/// Collect iTunes-style metadata (`ilst` items) into a tag map.
+///
+/// Keys are the item types (`"©nam"`, `"trkn"`, `"covr"`, ...). Freeform
+/// `----` items are keyed as `"<mean>:<name>"`, e.g.
+/// `"com.apple.iTunes:iTunNORM"`. Items holding several `data` atoms (such
+/// as multiple cover images) keep every value in order.
+///
+/// `trkn` and `disk` are reported as [`TagValue::Index`] and `gnre` as the
+/// ID3v1 genre number it stores.
+///
+/// Requires a tree produced with `decode = true`.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, metadata::itunes_tags};
+/// use std::fs::File;
+///
+/// let mut file = File::open("song.m4a")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// let tags = itunes_tags(&boxes);
+/// if let Some(title) = tags.get("©nam") {
+///     println!("title: {:?}", title[0]);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn itunes_tags(boxes: &[crate::Box]) -> BTreeMap<String, Vec<TagValue>> {
+    let mut tags = BTreeMap::new();
+    for (_, ilst) in find_all(boxes, b"ilst") {
+        for item in ilst.children.iter().flatten() {
+            let kids = item.children.as_deref().unwrap_or_default();
+
+            let key = if item.typ == "----" {
+                let text = |typ: &str| {
+                    kids.iter()
+                        .find(|b| b.typ == typ)
+                        .and_then(|b| b.decoded.clone())
+                        .unwrap_or_default()
+                };
+                format!("{}:{}", text("mean"), text("name"))
+            } else {
+                item.typ.clone()
+            };
+
+            let values: Vec<TagValue> = kids
+                .iter()
+                .filter_map(|b| match &b.structured_data {
+                    Some(StructuredData::MetadataValue(d)) => {
+                        Some(interpret(&item.typ, d.value.clone()))
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            if !values.is_empty() {
+                tags.entry(key).or_insert_with(Vec::new).extend(values);
+            }
+        }
+    }
+    tags
+}
+
+/// Collect QuickTime `mdta` metadata (meta/keys + meta/ilst) into a tag map.
+///
+/// iPhone and other QuickTime recordings store keys such as
+/// `com.apple.quicktime.make` in a `keys` box; each ilst item's type is the
+/// 1-based index of its key. The item types are read from `r`, since
+/// [`crate::Box::typ`] cannot represent them.
+///
+/// Items whose index has no key are reported as `"#<index>"`. Requires a
+/// tree produced with `decode = true` from the same reader.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, metadata::quicktime_metadata};
+/// use std::fs::File;
+///
+/// let mut file = File::open("IMG_0001.MOV")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// let tags = quicktime_metadata(&mut file, &boxes)?;
+/// if let Some(model) = tags.get("com.apple.quicktime.model") {
+///     println!("camera: {:?}", model[0]);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn quicktime_metadata<R: Read + Seek>(
+    r: &mut R,
+    boxes: &[crate::Box],
+) -> anyhow::Result<BTreeMap<String, Vec<TagValue>>> {
+    let mut tags = BTreeMap::new();
+    for (_, meta) in find_all(boxes, b"meta") {
+        let kids = meta.children.as_deref().unwrap_or_default();
+        let Some(keys) = kids.iter().find_map(|b| match &b.structured_data {
+            Some(StructuredData::MetadataKeys(k)) => Some(k),
+            _ => None,
+        }) else {
+            continue;
+        };
+
+        for item in kids
+            .iter()
+            .filter(|b| b.typ == "ilst")
+            .filter_map(|ilst| ilst.children.as_ref())
+            .flatten()
+        {
+            let raw = read_slice(r, item.offset + 4, 4)?;
+            let index = u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]);
+            let key = index
+                .checked_sub(1)
+                .and_then(|i| keys.entries.get(i as usize))
+                .map(|k| k.key.clone())
+                .unwrap_or_else(|| format!("#{index}"));
+
+            let values = item
+                .children
+                .iter()
+                .flatten()
+                .filter_map(|b| match &b.structured_data {
+                    Some(StructuredData::MetadataValue(d)) => Some(d.value.clone()),
+                    _ => None,
+                });
+            tags.entry(key).or_insert_with(Vec::new).extend(values);
+        }
+    }
+    Ok(tags)
+}
+
+/// Read every cover image stored in `covr` items.
+///
+/// The format is taken from the image's magic bytes rather than the data
+/// atom's type indicator, which taggers often get wrong. Requires a tree
+/// produced with `decode = true` from the same reader.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, metadata::extract_cover_art};
+/// use std::fs::File;
+///
+/// let mut file = File::open("song.m4a")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for (i, art) in extract_cover_art(&mut file, &boxes)?.iter().enumerate() {
+///     println!("cover {}: {} ({} bytes)", i, art.format.mime_type(), art.data.len());
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn extract_cover_art<R: Read + Seek>(
+    r: &mut R,
+    boxes: &[crate::Box],
+) -> anyhow::Result<Vec<CoverArt>> {
+    let mut out = Vec::new();
+    let data_atoms = find_all(boxes, b"ilst")
+        .into_iter()
+        .filter_map(|(_, ilst)| ilst.children.as_ref())
+        .flatten()
+        .filter(|item| item.typ == "covr")
+        .filter_map(|covr| covr.children.as_ref())
+        .flatten()
+        .filter(|b| b.typ == "data");
+
+    for atom in data_atoms {
+        let (Some(payload_offset), Some(payload_size)) = (atom.payload_offset, atom.payload_size)
+        else {
+            continue;
+        };
+        // Skip the type indicator and locale
+        if payload_size <= 8 {
+            continue;
+        }
+        let offset = payload_offset + 8;
+        let data = read_slice(r, offset, payload_size - 8)?;
+        out.push(CoverArt {
+            format: ImageFormat::from_magic(&data),
+            offset,
+            data,
+        });
+    }
+    Ok(out)
+}
+
+/// Extract simple properties from an XMP packet.
+///
+/// Collects the attributes of every `rdf:Description` (`xmp:CreatorTool="..."`)
+/// and its property elements (`<dc:format>video/mp4</dc:format>`). Array
+/// properties (`rdf:Alt`, `rdf:Bag`, `rdf:Seq`) are reported with their
+/// items joined by "; ". Keys keep their namespace prefix. Nested
+/// structures are not interpreted.
+///
+/// # Example
+/// ```
+/// use mp4box::metadata::xmp_properties;
+///
+/// let packet = r#"<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+///   <rdf:Description xmp:CreatorTool="Premiere">
+///     <dc:format>video/mp4</dc:format>
+///   </rdf:Description>
+/// </rdf:RDF>"#;
+/// let props = xmp_properties(packet);
+/// assert_eq!(props["xmp:CreatorTool"], "Premiere");
+/// assert_eq!(props["dc:format"], "video/mp4");
+/// ```
+pub fn xmp_properties(packet: &str) -> BTreeMap<String, String> {
+    let mut props = BTreeMap::new();
+    let mut depth = 0usize;
+    let mut description_depth = None;
+    // (name, depth of its element, collected values)
+    let mut current: Option<(String, usize, Vec<String>)> = None;
+
+    for token in xml_tokens(packet) {
+        match token {
+            XmlToken::Start { name, attrs, empty } => {
+                if name == "rdf:Description" {
+                    for (k, v) in attrs {
+                        if !(k.starts_with("xmlns")
+                            || k.starts_with("rdf:")
+                            || k.starts_with("xml:"))
+                        {
+                            props.insert(k.to_string(), v);
+                        }
+                    }
+                    if !empty {
+                        description_depth = Some(depth + 1);
+                    }
+                } else if current.is_none() && description_depth == Some(depth) {
+                    let mut values = Vec::new();
+                    if let Some((_, v)) = attrs.into_iter().find(|(k, _)| *k == "rdf:resource") {
+                        values.push(v);
+                    }
+                    if empty {
+                        props.insert(name.to_string(), values.join("; "));
+                    } else {
+                        current = Some((name.to_string(), depth + 1, values));
+                    }
+                }
+                if !empty {
+                    depth += 1;
+                }
+            }
+            XmlToken::Text(text) => {
+                if let Some((_, _, values)) = &mut current {
+                    let text = text.trim();
+                    if !text.is_empty() {
+                        values.push(xml_unescape(text));
+                    }
+                }
+            }
+            XmlToken::End => {
+                if current.as_ref().is_some_and(|c| c.1 == depth) {
+                    let (name, _, values) = current.take().unwrap();
+                    props.insert(name, values.join("; "));
+                }
+                if description_depth == Some(depth) {
+                    description_depth = None;
+                }
+                depth = depth.saturating_sub(1);
+            }
+        }
+    }
+    props
+}
+
+enum XmlToken<'a> {
+    Start {
+        name: &'a str,
+        attrs: Vec<(&'a str, String)>,
+        empty: bool,
+    },
+    End,
+    Text(&'a str),
+}
+
+/// Minimal XML tokenizer: skips comments, processing instructions and
+/// declarations; does not validate.
+fn xml_tokens(s: &str) -> Vec<XmlToken<'_>> {
+    let mut out = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        if let Some(body) = rest.strip_prefix("<!--") {
+            rest = body.find("-->").map_or("", |i| &body[i + 3..]);
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = rest.find('>').map_or("", |i| &rest[i + 1..]);
+        } else if let Some(body) = rest.strip_prefix("</") {
+            out.push(XmlToken::End);
+            rest = body.find('>').map_or("", |i| &body[i + 1..]);
+        } else if let Some(body) = rest.strip_prefix('<') {
+            let Some(close) = tag_end(body) else {
+                break;
+            };
+            let tag = &body[..close];
+            let empty = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+            out.push(XmlToken::Start {
+                name: &tag[..name_end],
+                attrs: xml_attrs(&tag[name_end..]),
+                empty,
+            });
+            rest = &body[close + 1..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            out.push(XmlToken::Text(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    out
+}
+
+/// Index of the `>` closing a tag, ignoring any inside quoted values.
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn xml_attrs(s: &str) -> Vec<(&str, String)> {
+    let mut out = Vec::new();
+    let mut rest = s;
+    while let Some(eq) = rest.find('=') {
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let Some(q) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(end) = value[1..].find(q) else {
+            break;
+        };
+        out.push((name, xml_unescape(&value[1..1 + end])));
+        rest = &value[end + 2..];
+    }
+    out
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Apply item-specific meaning to implicitly typed (binary) values.
+fn interpret(item: &str, value: TagValue) -> TagValue {
+    match (item, value) {
+        ("trkn" | "disk", TagValue::Binary(b)) if b.len() >= 6 => TagValue::Index {
+            number: u16::from_be_bytes([b[2], b[3]]),
+            total: u16::from_be_bytes([b[4], b[5]]),
+        },
+        ("gnre", TagValue::Binary(b)) if b.len() == 2 => {
+            TagValue::Integer(u16::from_be_bytes([b[0], b[1]]) as i64)
+        }
+        (_, v) => v,
+    }
+}
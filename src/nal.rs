@@ -0,0 +1,146 @@
+//! NAL unit iteration over length-prefixed H.264/H.265 samples.
+
+use serde::Serialize;
+
+/// Which NAL unit header layout a stream uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NalCodec {
+    /// H.264: one-byte header, 5-bit type
+    Avc,
+    /// H.265: two-byte header, 6-bit type
+    Hevc,
+}
+
+impl NalCodec {
+    /// Codec of a sample entry type, e.g. "avc1" or "hvc1".
+    pub fn from_sample_entry(codec: &str) -> Option<Self> {
+        match codec {
+            "avc1" | "avc2" | "avc3" | "avc4" => Some(NalCodec::Avc),
+            "hvc1" | "hev1" => Some(NalCodec::Hevc),
+            _ => None,
+        }
+    }
+}
+
+/// One NAL unit of a sample.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NalUnit<'a> {
+    pub codec: NalCodec,
+    pub nal_type: u8,
+    /// The whole NAL unit, header included
+    pub data: &'a [u8],
+}
+
+impl NalUnit<'_> {
+    /// Instantaneous decoding refresh picture.
+    pub fn is_idr(&self) -> bool {
+        match self.codec {
+            NalCodec::Avc => self.nal_type == 5,
+            NalCodec::Hevc => matches!(self.nal_type, 19 | 20),
+        }
+    }
+
+    /// Supplemental enhancement information (prefix or suffix in H.265).
+    pub fn is_sei(&self) -> bool {
+        match self.codec {
+            NalCodec::Avc => self.nal_type == 6,
+            NalCodec::Hevc => matches!(self.nal_type, 39 | 40),
+        }
+    }
+
+    /// VPS, SPS or PPS.
+    pub fn is_parameter_set(&self) -> bool {
+        match self.codec {
+            NalCodec::Avc => matches!(self.nal_type, 7 | 8),
+            NalCodec::Hevc => matches!(self.nal_type, 32..=34),
+        }
+    }
+
+    /// The NAL unit without its header.
+    pub fn payload(&self) -> &[u8] {
+        let header = match self.codec {
+            NalCodec::Avc => 1,
+            NalCodec::Hevc => 2,
+        };
+        self.data.get(header..).unwrap_or_default()
+    }
+}
+
+/// A length prefix that runs past the end of the sample.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("NAL unit at byte {offset} overruns the sample")]
+pub struct TruncatedNal {
+    pub offset: usize,
+}
+
+/// Iterator returned by [`nal_units`].
+#[derive(Debug, Clone)]
+pub struct NalUnits<'a> {
+    data: &'a [u8],
+    length_size: usize,
+    codec: NalCodec,
+    pos: usize,
+}
+
+/// Iterate the NAL units of a sample whose units are each prefixed with a
+/// `length_size`-byte big-endian length (the avcC/hvcC lengthSizeMinusOne
+/// plus one).
+///
+/// Zero-length units are skipped. A length that overruns the sample yields
+/// one [`TruncatedNal`] error and ends the iteration.
+///
+/// # Example
+/// ```
+/// use mp4box::nal::{NalCodec, nal_units};
+///
+/// let sample = [0, 0, 0, 2, 0x06, 0x05, 0, 0, 0, 2, 0x65, 0x88];
+/// let units: Vec<_> = nal_units(&sample, 4, NalCodec::Avc)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert!(units[0].is_sei());
+/// assert!(units[1].is_idr());
+/// ```
+pub fn nal_units(sample: &[u8], length_size: u8, codec: NalCodec) -> NalUnits<'_> {
+    NalUnits {
+        data: sample,
+        length_size: length_size as usize,
+        codec,
+        pos: 0,
+    }
+}
+
+impl<'a> Iterator for NalUnits<'a> {
+    type Item = Result<NalUnit<'a>, TruncatedNal>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos < self.data.len() {
+            let offset = self.pos;
+            let unit = self
+                .data
+                .get(offset..offset + self.length_size)
+                .map(|p| p.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize))
+                .and_then(|len| {
+                    let start = offset + self.length_size;
+                    self.data.get(start..start + len)
+                });
+            let Some(data) = unit else {
+                self.pos = self.data.len();
+                return Some(Err(TruncatedNal { offset }));
+            };
+            self.pos = offset + self.length_size + data.len();
+            if let Some(&first) = data.first() {
+                let nal_type = match self.codec {
+                    NalCodec::Avc => first & 0x1F,
+                    NalCodec::Hevc => (first >> 1) & 0x3F,
+                };
+                return Some(Ok(NalUnit {
+                    codec: self.codec,
+                    nal_type,
+                    data,
+                }));
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,173 @@
+//! Number formatting for the text output of the bundled CLIs.
+//!
+//! Porcelain output, the default, is locale-independent and never changes for
+//! cosmetic reasons: plain digits, `.` as the decimal point, sizes in bytes.
+//! Human output (`--human`) groups digits the way the user's locale does
+//! ("1,234,567", "1.234.567", ...) and shows sizes in binary units.
+
+/// Output style of a [`NumberFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberStyle {
+    Human { group: char, decimal: char },
+    Porcelain,
+}
+
+/// Formats numbers for text output; see the module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberFormat {
+    pub style: NumberStyle,
+}
+
+impl NumberFormat {
+    /// Stable, locale-independent formatting for scripts.
+    pub fn porcelain() -> Self {
+        Self {
+            style: NumberStyle::Porcelain,
+        }
+    }
+
+    /// Human formatting with `,` grouping and `.` decimals.
+    pub fn human() -> Self {
+        Self {
+            style: NumberStyle::Human {
+                group: ',',
+                decimal: '.',
+            },
+        }
+    }
+
+    /// Human formatting for a POSIX locale name such as `de_DE.UTF-8`.
+    pub fn from_locale(locale: &str) -> Self {
+        let lang = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let (group, decimal) = match lang.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" => ('.', ','),
+            "fr" | "ru" | "sv" | "nb" | "nn" | "fi" | "pl" | "cs" | "sk" | "uk" | "hu" => {
+                ('\u{a0}', ',')
+            }
+            _ => (',', '.'),
+        };
+        Self {
+            style: NumberStyle::Human { group, decimal },
+        }
+    }
+
+    /// Human formatting for the locale in `LC_ALL`, `LC_NUMERIC` or `LANG`.
+    pub fn from_env() -> Self {
+        let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+            .iter()
+            .filter_map(|k| std::env::var(k).ok())
+            .find(|v| !v.is_empty())
+            .unwrap_or_default();
+        Self::from_locale(&locale)
+    }
+
+    /// [`NumberFormat::from_env`] if `human` is set, otherwise porcelain.
+    pub fn for_cli(human: bool) -> Self {
+        if human {
+            Self::from_env()
+        } else {
+            Self::porcelain()
+        }
+    }
+
+    pub fn is_porcelain(&self) -> bool {
+        self.style == NumberStyle::Porcelain
+    }
+
+    /// Integer with digit grouping.
+    pub fn int(&self, v: impl Into<i128>) -> String {
+        let v: i128 = v.into();
+        match self.style {
+            NumberStyle::Porcelain => v.to_string(),
+            NumberStyle::Human { group, .. } => {
+                let digits = v.unsigned_abs().to_string();
+                let mut out = String::new();
+                if v < 0 {
+                    out.push('-');
+                }
+                for (i, c) in digits.chars().enumerate() {
+                    if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                        out.push(group);
+                    }
+                    out.push(c);
+                }
+                out
+            }
+        }
+    }
+
+    /// Fixed-point number with `precision` decimals.
+    pub fn float(&self, v: f64, precision: usize) -> String {
+        let s = format!("{:.*}", precision, v);
+        match self.style {
+            NumberStyle::Porcelain => s,
+            NumberStyle::Human { decimal, .. } => {
+                let (int_part, frac) = match s.split_once('.') {
+                    Some((i, f)) => (i, Some(f)),
+                    None => (s.as_str(), None),
+                };
+                let mut out = match int_part.parse::<i128>() {
+                    // Keep "-0" for small negatives
+                    Ok(0) if int_part.starts_with('-') => "-0".to_string(),
+                    Ok(i) => self.int(i),
+                    Err(_) => int_part.to_string(),
+                };
+                if let Some(f) = frac {
+                    out.push(decimal);
+                    out.push_str(f);
+                }
+                out
+            }
+        }
+    }
+
+    /// Byte count: "1.5 MiB" for humans, plain bytes for porcelain.
+    pub fn bytes(&self, v: u64) -> String {
+        const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+        if self.is_porcelain() || v < 1024 {
+            return match self.style {
+                NumberStyle::Porcelain => v.to_string(),
+                NumberStyle::Human { .. } => format!("{} B", v),
+            };
+        }
+        let mut value = v as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        format!("{} {}", self.float(value, 1), UNITS[unit])
+    }
+
+    /// Duration given in seconds, with its unit: "1:02:03.500" for humans
+    /// (or "12.500 s" below a minute), "3723.500 s" for porcelain.
+    pub fn seconds(&self, v: f64) -> String {
+        if self.is_porcelain() || !v.is_finite() || v < 60.0 {
+            return format!("{} s", self.float(v, 3));
+        }
+        let total_ms = (v * 1000.0).round() as u64;
+        let (h, rem) = (total_ms / 3_600_000, total_ms % 3_600_000);
+        let (m, rem) = (rem / 60_000, rem % 60_000);
+        let secs = self.float(rem as f64 / 1000.0, 3);
+        let secs = if rem < 10_000 {
+            format!("0{secs}")
+        } else {
+            secs
+        };
+        if h > 0 {
+            format!("{}:{:02}:{}", h, m, secs)
+        } else {
+            format!("{}:{}", m, secs)
+        }
+    }
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self::porcelain()
+    }
+}
@@ -1,5 +1,6 @@
 use crate::boxes::{BoxHeader, BoxRef, FourCC, NodeKind};
 use crate::known_boxes::KnownBox;
+use crate::util::read_up_to;
 use byteorder::{BigEndian, ReadBytesExt};
 use std::io::{Read, Seek, SeekFrom};
 
@@ -9,10 +10,157 @@ pub enum ParseError {
     Io(#[from] std::io::Error),
     #[error("invalid box size")]
     InvalidSize,
+    #[error("implausible box type")]
+    InvalidType,
+    #[error("{0} limit exceeded")]
+    LimitExceeded(&'static str),
 }
 
 pub type Result<T> = std::result::Result<T, ParseError>;
 
+/// Top-level boxes whose payload is never needed: media data and padding.
+pub(crate) const SKIPPED: [&[u8; 4]; 4] = [b"mdat", b"free", b"skip", b"wide"];
+
+/// Something lenient parsing skipped over instead of failing.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ParseWarning {
+    /// Start of the affected box or header
+    pub offset: u64,
+    /// Box type, when the header could be read
+    pub typ: Option<String>,
+    #[serde(flatten)]
+    pub kind: WarningKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WarningKind {
+    /// A header could not be read; the remaining bytes of the parent are skipped
+    BadHeader { message: String },
+    /// A header could not be read; with recovery on, parsing resumed at the
+    /// next plausible header and the bytes before it became a gap node
+    Resynchronized { message: String, resumed_at: u64 },
+    /// The box claims more bytes than its parent (or the file) has left; it
+    /// is parsed up to `available_end`. Recorded in strict mode too.
+    Truncated {
+        declared_end: u64,
+        available_end: u64,
+    },
+    /// A box below the top level has size 0, which only means "to the end
+    /// of the file" for top-level boxes; it is taken to end at its parent's
+    /// end, `end`. Recorded in strict mode too.
+    NestedSizeZero { end: u64 },
+    /// The box content did not parse; it is kept as a leaf
+    Malformed { message: String },
+    /// The registry decoder for the box failed
+    DecodeFailed { message: String },
+    /// A [`ParseOptions`](crate::ParseOptions) limit was hit; `limit` names it
+    LimitExceeded { limit: &'static str },
+    /// A table declares more entries than its payload holds or than
+    /// `max_table_entries` allows; only the first `decoded` were decoded
+    EntriesClamped { declared: u64, decoded: u64 },
+}
+
+/// Default nesting limit: containers deeper than this become leaves.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Mode, limits and warnings of one parse. Strict parsing stops at the
+/// first error; lenient parsing records a warning and keeps going.
+pub(crate) struct ParseState {
+    lenient: bool,
+    /// Resume after bad headers instead of giving up on the parent
+    pub(crate) recover: bool,
+    max_depth: usize,
+    max_boxes: usize,
+    depth: usize,
+    boxes: usize,
+    pub(crate) list: Vec<ParseWarning>,
+    /// Largest leaf or FullBox content kept for decoding; 0 keeps none
+    pub(crate) capture: u64,
+    /// Kept content as (offset, bytes), in file order and not overlapping
+    pub(crate) captured: Vec<(u64, Vec<u8>)>,
+}
+
+impl ParseState {
+    pub(crate) fn strict() -> Self {
+        Self::new(false, DEFAULT_MAX_DEPTH, usize::MAX)
+    }
+
+    pub(crate) fn lenient() -> Self {
+        Self::new(true, DEFAULT_MAX_DEPTH, usize::MAX)
+    }
+
+    pub(crate) fn new(lenient: bool, max_depth: usize, max_boxes: usize) -> Self {
+        ParseState {
+            lenient,
+            recover: false,
+            max_depth,
+            max_boxes,
+            depth: 0,
+            boxes: 0,
+            list: Vec::new(),
+            capture: 0,
+            captured: Vec::new(),
+        }
+    }
+
+    /// Keep the content of leaf or FullBox `h` (version and flags included)
+    /// if it fits under `capture`; it replaces whatever its children kept.
+    fn keep_content<R: Read + Seek>(
+        &mut self,
+        r: &mut R,
+        h: &BoxHeader,
+        kind: &NodeKind,
+        box_end: u64,
+    ) -> Result<()> {
+        let start = h.start + h.header_size;
+        let len = box_end.saturating_sub(start);
+        if matches!(kind, NodeKind::Container(_) | NodeKind::Gap { .. })
+            || len > self.capture
+            || SKIPPED.contains(&&h.typ.0)
+        {
+            return Ok(());
+        }
+        while self.captured.last().is_some_and(|(at, _)| *at >= start) {
+            self.captured.pop();
+        }
+        let mut data = vec![0u8; len as usize];
+        r.seek(SeekFrom::Start(start))?;
+        let n = read_up_to(r, &mut data)?;
+        data.truncate(n);
+        self.captured.push((start, data));
+        Ok(())
+    }
+
+    /// Error in strict mode; warning at `offset` in lenient mode.
+    fn limit(&mut self, offset: u64, typ: Option<FourCC>, limit: &'static str) -> Result<()> {
+        if !self.lenient {
+            return Err(ParseError::LimitExceeded(limit));
+        }
+        self.push(offset, typ, WarningKind::LimitExceeded { limit });
+        Ok(())
+    }
+
+    /// Whether the children of `h`, a box at the current level, would be
+    /// nested past `max_depth`; records a warning if so.
+    fn too_deep(&mut self, h: &BoxHeader) -> bool {
+        if self.depth <= self.max_depth {
+            return false;
+        }
+        let limit = "max_depth";
+        self.push(h.start, Some(h.typ), WarningKind::LimitExceeded { limit });
+        true
+    }
+
+    pub(crate) fn push(&mut self, offset: u64, typ: Option<FourCC>, kind: WarningKind) {
+        self.list.push(ParseWarning {
+            offset,
+            typ: typ.map(|t| t.to_string()),
+            kind,
+        });
+    }
+}
+
 pub fn read_box_header<R: Read + Seek>(r: &mut R) -> Result<BoxHeader> {
     let start = r.stream_position()?;
     let size32 = r.read_u32::<BigEndian>()?;
@@ -51,53 +199,179 @@ pub fn read_box_header<R: Read + Seek>(r: &mut R) -> Result<BoxHeader> {
     })
 }
 
+/// Parse the children of container box `h`, whose content ends at `box_end`.
+///
+/// Prefer this over [`parse_children`] when the container header is at hand:
+/// some boxes are only containers because of their parent (e.g. the items
+/// under `ilst`).
+pub fn parse_container<R: Read + Seek>(
+    r: &mut R,
+    h: &BoxHeader,
+    box_end: u64,
+) -> Result<Vec<BoxRef>> {
+    parse_container_in(r, h, box_end, &mut ParseState::strict())
+}
+
+fn parse_container_in<R: Read + Seek>(
+    r: &mut R,
+    h: &BoxHeader,
+    box_end: u64,
+    w: &mut ParseState,
+) -> Result<Vec<BoxRef>> {
+    r.seek(SeekFrom::Start(h.start + h.header_size))?;
+    parse_children_in(r, box_end, Some(h.typ), w)
+}
+
+/// Parse the boxes from the current position to `parent_end`. A child
+/// claiming more bytes than that is cut at `parent_end`.
 pub fn parse_children<R: Read + Seek>(r: &mut R, parent_end: u64) -> Result<Vec<BoxRef>> {
+    parse_children_in(r, parent_end, None, &mut ParseState::strict())
+}
+
+/// Like [`parse_children`], but damaged boxes become warnings instead of
+/// errors: unreadable headers end the parent, oversized boxes are cut at
+/// the parent's end and boxes whose content does not parse become leaves.
+pub fn parse_children_lenient<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+) -> (Vec<BoxRef>, Vec<ParseWarning>) {
+    let mut w = ParseState::lenient();
+    // Lenient parsing only fails on seek errors, which end the parent early
+    let kids = parse_children_in(r, parent_end, None, &mut w).unwrap_or_default();
+    (kids, w.list)
+}
+
+/// Like [`parse_children_lenient`], but a bad header does not end the
+/// parent: the bytes up to the next plausible header (printable type, size
+/// that fits the parent) become a gap node ([`NodeKind::Gap`]) and parsing
+/// resumes there.
+pub fn parse_children_recover<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+) -> (Vec<BoxRef>, Vec<ParseWarning>) {
+    let mut w = ParseState::lenient();
+    w.recover = true;
+    let kids = parse_children_in(r, parent_end, None, &mut w).unwrap_or_default();
+    (kids, w.list)
+}
+
+/// Parse the boxes from the current position to `parent_end` with the
+/// mode and limits of `w`.
+pub(crate) fn parse_children_with<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+    w: &mut ParseState,
+) -> Result<Vec<BoxRef>> {
+    parse_children_in(r, parent_end, None, w)
+}
+
+fn parse_children_in<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+    parent: Option<FourCC>,
+    w: &mut ParseState,
+) -> Result<Vec<BoxRef>> {
+    if w.depth > w.max_depth {
+        let start = r.stream_position()?;
+        let limit = "max_depth";
+        w.push(start, parent, WarningKind::LimitExceeded { limit });
+        return Ok(Vec::new());
+    }
+    w.depth += 1;
+    let kids = parse_siblings(r, parent_end, parent, w);
+    w.depth -= 1;
+    kids
+}
+
+fn parse_siblings<R: Read + Seek>(
+    r: &mut R,
+    parent_end: u64,
+    parent: Option<FourCC>,
+    w: &mut ParseState,
+) -> Result<Vec<BoxRef>> {
+    // Every child of ilst is a metadata item holding data atoms
+    let items = parent.is_some_and(|p| &p.0 == b"ilst");
+    // Every child of stsd is a sample entry
+    let entries = parent.is_some_and(|p| &p.0 == b"stsd");
     let mut kids = Vec::new();
     while r.stream_position()? < parent_end {
-        let h = read_box_header(r)?;
-        let box_end = if h.size == 0 {
+        let start = r.stream_position()?;
+        if w.boxes >= w.max_boxes {
+            // Reported once; every level above stops here too
+            if w.boxes == w.max_boxes {
+                w.limit(start, None, "max_boxes")?;
+                w.boxes += 1;
+            }
+            break;
+        }
+        w.boxes += 1;
+        let header = read_box_header(r).and_then(|h| match plausible_type(h.typ) {
+            false if w.recover => Err(ParseError::InvalidType),
+            _ => Ok(h),
+        });
+        let h = match header {
+            Ok(h) => h,
+            Err(e) if w.recover => {
+                let end = resync(r, start + 1, parent_end)?.unwrap_or(parent_end);
+                let kind = WarningKind::Resynchronized {
+                    message: e.to_string(),
+                    resumed_at: end,
+                };
+                w.push(start, None, kind);
+                kids.push(gap(start, end));
+                r.seek(SeekFrom::Start(end))?;
+                continue;
+            }
+            Err(e) if w.lenient => {
+                let message = e.to_string();
+                w.push(start, None, WarningKind::BadHeader { message });
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        let mut box_end = if h.size == 0 {
+            // All-zero headers are padding, not a box claiming the rest
+            if parent.is_some() && h.typ.0 != [0; 4] {
+                let kind = WarningKind::NestedSizeZero { end: parent_end };
+                w.push(h.start, Some(h.typ), kind);
+            }
             parent_end
         } else {
             h.start + h.size
         };
+        // Never let a child read into its parent's siblings
+        if box_end > parent_end {
+            let kind = WarningKind::Truncated {
+                declared_end: box_end,
+                available_end: parent_end,
+            };
+            w.push(h.start, Some(h.typ), kind);
+            box_end = parent_end;
+        }
 
-        // Decide kind
-        let kind = if is_container(&h) {
-            // recurse into container
-            let content_start = h.start + h.header_size;
-            r.seek(SeekFrom::Start(content_start))?;
-            let child = parse_children(r, box_end)?;
-            NodeKind::Container(child)
-        } else if is_full_box(&h) {
-            let content_start = h.start + h.header_size;
-            r.seek(SeekFrom::Start(content_start))?;
-            let version = r.read_u8()?;
-            let mut f = [0u8; 3];
-            r.read_exact(&mut f)?;
-            let flags = ((f[0] as u32) << 16) | ((f[1] as u32) << 8) | (f[2] as u32);
-            let data_offset = r.stream_position()?;
-            let data_len = box_end.saturating_sub(data_offset);
-            NodeKind::FullBox {
-                version,
-                flags,
-                data_offset,
-                data_len,
-            }
+        let parsed = if items {
+            container_or_leaf(r, &h, box_end, w)
+        } else if entries {
+            parse_sample_entry(r, &h, box_end, w)
         } else {
-            let data_offset = h.start + h.header_size;
-            let data_len = box_end.saturating_sub(data_offset);
-            if &h.typ.0 == b"uuid" {
-                NodeKind::Unknown {
-                    data_offset,
-                    data_len,
-                }
-            } else {
+            parse_box_in(r, &h, box_end, w)
+        };
+        let kind = match parsed {
+            Ok(kind) => kind,
+            Err(e) if w.lenient => {
+                let message = e.to_string();
+                w.push(h.start, Some(h.typ), WarningKind::Malformed { message });
+                let data_offset = h.start + h.header_size;
                 NodeKind::Leaf {
                     data_offset,
-                    data_len,
+                    data_len: box_end.saturating_sub(data_offset),
                 }
             }
+            Err(e) => return Err(e),
         };
+        if w.capture > 0 {
+            w.keep_content(r, &h, &kind, box_end)?;
+        }
 
         // Skip to end of box
         r.seek(SeekFrom::Start(box_end))?;
@@ -106,6 +380,217 @@ pub fn parse_children<R: Read + Seek>(r: &mut R, parent_end: u64) -> Result<Vec<
     Ok(kids)
 }
 
+/// Box types are printable ASCII, plus © for QuickTime metadata atoms.
+fn plausible_type(typ: FourCC) -> bool {
+    typ.0.iter().all(|&c| matches!(c, 0x20..=0x7e | 0xa9))
+}
+
+/// Offset of the first plausible box header in `from..parent_end`: a
+/// printable type and a 32-bit size that fits before `parent_end`.
+fn resync<R: Read + Seek>(r: &mut R, from: u64, parent_end: u64) -> Result<Option<u64>> {
+    const WINDOW: u64 = 64 * 1024;
+    let mut pos = from;
+    let mut buf = Vec::new();
+    while pos + 8 <= parent_end {
+        // Windows overlap by a header so none is split
+        let len = (parent_end - pos).min(WINDOW + 7);
+        buf.resize(len as usize, 0);
+        r.seek(SeekFrom::Start(pos))?;
+        let n = read_up_to(r, &mut buf)?;
+        for (i, w) in buf[..n].windows(8).enumerate() {
+            let at = pos + i as u64;
+            let size = u32::from_be_bytes([w[0], w[1], w[2], w[3]]) as u64;
+            let typ = FourCC([w[4], w[5], w[6], w[7]]);
+            if size >= 8 && at + size <= parent_end && plausible_type(typ) {
+                return Ok(Some(at));
+            }
+        }
+        if (n as u64) < len {
+            // End of the stream before parent_end
+            break;
+        }
+        pos += len - 7;
+    }
+    Ok(None)
+}
+
+/// Gap node for the unparsed bytes `start..end`.
+fn gap(start: u64, end: u64) -> BoxRef {
+    BoxRef {
+        hdr: BoxHeader {
+            size: end - start,
+            typ: FourCC(*b"gap "),
+            uuid: None,
+            header_size: 0,
+            start,
+        },
+        kind: NodeKind::Gap {
+            data_offset: start,
+            data_len: end - start,
+        },
+    }
+}
+
+/// Classify box `h`, whose content ends at `box_end`, reading its
+/// version/flags or children as needed. Leaves `r` at an unspecified
+/// position inside the box.
+pub fn parse_box<R: Read + Seek>(r: &mut R, h: &BoxHeader, box_end: u64) -> Result<NodeKind> {
+    parse_box_in(r, h, box_end, &mut ParseState::strict())
+}
+
+fn parse_box_in<R: Read + Seek>(
+    r: &mut R,
+    h: &BoxHeader,
+    box_end: u64,
+    w: &mut ParseState,
+) -> Result<NodeKind> {
+    let kind = if is_container(h) || (&h.typ.0 == b"meta" && !meta_is_full_box(r, h, box_end)?) {
+        // recurse into container
+        container_or_leaf(r, h, box_end, w)?
+    } else if is_full_box(h) {
+        let content_start = h.start + h.header_size;
+        r.seek(SeekFrom::Start(content_start))?;
+        let version = r.read_u8()?;
+        let mut f = [0u8; 3];
+        r.read_exact(&mut f)?;
+        let flags = ((f[0] as u32) << 16) | ((f[1] as u32) << 8) | (f[2] as u32);
+        let data_offset = r.stream_position()?;
+        let data_len = box_end.saturating_sub(data_offset);
+
+        let children = match &h.typ.0 {
+            // u32 entry_count, then one box per sample entry
+            b"stsd" if data_len >= 4 => {
+                r.seek(SeekFrom::Start(data_offset + 4))?;
+                parse_children_in(r, box_end, Some(h.typ), w)?
+            }
+            b"meta" => parse_children_in(r, box_end, Some(h.typ), w)?,
+            _ => Vec::new(),
+        };
+        NodeKind::FullBox {
+            version,
+            flags,
+            data_offset,
+            data_len,
+            children,
+        }
+    } else {
+        let data_offset = h.start + h.header_size;
+        let data_len = box_end.saturating_sub(data_offset);
+        if &h.typ.0 == b"uuid" {
+            NodeKind::Unknown {
+                data_offset,
+                data_len,
+            }
+        } else {
+            NodeKind::Leaf {
+                data_offset,
+                data_len,
+            }
+        }
+    };
+    Ok(kind)
+}
+
+/// ISO `meta` is a FullBox, but QuickTime writes it as a plain container
+/// whose first child (hdlr) starts right after the header. Leaves `r` at the
+/// start of the box content.
+pub(crate) fn meta_is_full_box<R: Read + Seek>(
+    r: &mut R,
+    h: &BoxHeader,
+    box_end: u64,
+) -> std::io::Result<bool> {
+    let content_start = h.start + h.header_size;
+    if box_end < content_start + 8 {
+        return Ok(box_end >= content_start + 4);
+    }
+    r.seek(SeekFrom::Start(content_start))?;
+    let mut head = [0u8; 8];
+    r.read_exact(&mut head)?;
+    r.seek(SeekFrom::Start(content_start))?;
+    Ok(&head[4..] != b"hdlr")
+}
+
+/// Visual and audio sample entries become containers of the boxes after
+/// their fixed fields (avcC, esds, btrt, sinf, ...); any other entry, or
+/// one whose tail does not parse as boxes, stays a leaf.
+fn parse_sample_entry<R: Read + Seek>(
+    r: &mut R,
+    h: &BoxHeader,
+    box_end: u64,
+    w: &mut ParseState,
+) -> Result<NodeKind> {
+    let data_offset = h.start + h.header_size;
+    let data_len = box_end.saturating_sub(data_offset);
+    let leaf = NodeKind::Leaf {
+        data_offset,
+        data_len,
+    };
+
+    let Some(fields) = sample_entry_fields(r, h)? else {
+        return Ok(leaf);
+    };
+    if fields > data_len || w.too_deep(h) {
+        return Ok(leaf);
+    }
+
+    r.seek(SeekFrom::Start(data_offset + fields))?;
+    // Probed strictly: a tail that is not boxes makes a leaf, not warnings.
+    // The probe continues the nesting level and box count of `w`.
+    let mut probe = ParseState::new(false, w.max_depth, w.max_boxes);
+    probe.depth = w.depth;
+    probe.boxes = w.boxes;
+    match parse_children_in(r, box_end, Some(h.typ), &mut probe) {
+        Ok(kids) if kids.iter().all(|k| k.hdr.start + k.hdr.size <= box_end) => {
+            w.boxes = probe.boxes;
+            w.list.append(&mut probe.list);
+            Ok(NodeKind::Container(kids))
+        }
+        _ => Ok(leaf),
+    }
+}
+
+/// Length of the fixed fields before the child boxes of sample entry `h`,
+/// or `None` for entries that never have children.
+pub(crate) fn sample_entry_fields<R: Read + Seek>(
+    r: &mut R,
+    h: &BoxHeader,
+) -> std::io::Result<Option<u64>> {
+    let known = KnownBox::from(h.typ);
+    if known.is_visual_sample_entry() {
+        // SampleEntry (8) + VisualSampleEntry fields (70)
+        Ok(Some(78))
+    } else if known.is_audio_sample_entry() {
+        // SampleEntry (8) + AudioSampleEntry fields (20), extended by
+        // QuickTime sound description versions 1 and 2
+        r.seek(SeekFrom::Start(h.start + h.header_size + 8))?;
+        Ok(Some(match r.read_u16::<BigEndian>() {
+            Ok(1) => 44,
+            Ok(2) => 64,
+            _ => 28,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Children of container `h`, or a leaf holding its content when they would
+/// be nested too deep.
+fn container_or_leaf<R: Read + Seek>(
+    r: &mut R,
+    h: &BoxHeader,
+    box_end: u64,
+    w: &mut ParseState,
+) -> Result<NodeKind> {
+    if w.too_deep(h) {
+        let data_offset = h.start + h.header_size;
+        return Ok(NodeKind::Leaf {
+            data_offset,
+            data_len: box_end.saturating_sub(data_offset),
+        });
+    }
+    parse_container_in(r, h, box_end, w).map(NodeKind::Container)
+}
+
 // Known containers from ISOBMFF / MP4
 fn is_container(h: &BoxHeader) -> bool {
     KnownBox::from(h.typ).is_container()
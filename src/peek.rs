@@ -0,0 +1,87 @@
+//! Identifying media payloads from their first bytes, without reading the
+//! rest: the start of an mdat is enough to tell Annex B from length-prefixed
+//! NAL units, or to spot a JPEG in a HEIF file.
+
+use crate::metadata::ImageFormat;
+use serde::Serialize;
+
+/// The first bytes of an mdat payload; see
+/// [`ParseOptions::mdat_peek`](crate::ParseOptions::mdat_peek).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MdatPeek {
+    /// The bytes read, as hex
+    pub hex: String,
+    pub format: PayloadFormat,
+}
+
+/// What the first bytes of a media payload look like. A best guess: a few
+/// bytes cannot prove a format, only rule others out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadFormat {
+    /// H.264/H.265 NAL units behind `00 00 01` or `00 00 00 01` start codes
+    AnnexB,
+    /// NAL units behind a big-endian length of `length_size` bytes, as in
+    /// MP4 samples
+    LengthPrefixed {
+        length_size: u8,
+    },
+    /// AAC in ADTS frames
+    Adts,
+    /// An image, such as a HEIF item or thumbnail
+    Image(ImageFormat),
+    Unknown,
+}
+
+/// Classify `head`, the first bytes of a payload `payload_len` bytes long.
+///
+/// # Example
+/// ```
+/// use mp4box::peek::{PayloadFormat, classify_payload};
+///
+/// let head = [0, 0, 0, 1, 0x67, 0x64, 0x00, 0x1f];
+/// assert_eq!(classify_payload(&head, 1000), PayloadFormat::AnnexB);
+/// ```
+pub fn classify_payload(head: &[u8], payload_len: u64) -> PayloadFormat {
+    if head.starts_with(&[0, 0, 1]) || head.starts_with(&[0, 0, 0, 1]) {
+        return PayloadFormat::AnnexB;
+    }
+    // "BM" is too short a magic to trust in media data
+    match ImageFormat::from_magic(head) {
+        ImageFormat::Unknown | ImageFormat::Bmp => {}
+        format => return PayloadFormat::Image(format),
+    }
+    if let [0xFF, b, ..] = head
+        && b & 0xF6 == 0xF0
+    {
+        return PayloadFormat::Adts;
+    }
+    for length_size in [4, 2] {
+        if length_prefixed(head, payload_len, length_size) {
+            return PayloadFormat::LengthPrefixed {
+                length_size: length_size as u8,
+            };
+        }
+    }
+    PayloadFormat::Unknown
+}
+
+/// Whether every length in `head` is nonzero, fits in the payload and is
+/// followed by a NAL header with the forbidden bit clear.
+fn length_prefixed(head: &[u8], payload_len: u64, length_size: usize) -> bool {
+    let mut pos = 0;
+    while pos + length_size <= head.len() {
+        let len = head[pos..pos + length_size]
+            .iter()
+            .fold(0u64, |n, &b| n << 8 | b as u64);
+        let unit = (pos + length_size) as u64;
+        if len == 0 || unit + len > payload_len {
+            return false;
+        }
+        if head.get(unit as usize).is_some_and(|b| b & 0x80 != 0) {
+            return false;
+        }
+        pos = (unit + len).min(usize::MAX as u64) as usize;
+    }
+    pos > 0
+}
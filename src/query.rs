@@ -0,0 +1,442 @@
+//! Selecting boxes by path expression, e.g. `moov.trak[0].mdia.minf.stbl`,
+//! `**.tenc` or `moov.trak[@handler=soun].mdia`.
+
+use crate::api::Box;
+use crate::boxes::{BoxKey, BoxRef, FourCC, NodeKind};
+use crate::iter::Node;
+use crate::registry::StructuredData;
+use std::fmt;
+use std::str::FromStr;
+
+/// A box matched by [`select_paths`] and where it sits in the tree.
+#[derive(Debug)]
+pub struct Selection<'a, T> {
+    /// Path that selects only this box, e.g. `moov.trak[1].mdia`. A type
+    /// gets an index when its parent holds more than one box of that type;
+    /// uuid boxes are written `uuid:<hex>`.
+    pub path: String,
+    pub node: &'a T,
+}
+
+/// Dotted path that [`select`] resolves to exactly one box, e.g.
+/// `moov.trak[1].mdia.minf.stbl`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryPath(String);
+
+impl QueryPath {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Number of ancestors of the box.
+    pub fn depth(&self) -> usize {
+        split_segments(&self.0).len() - 1
+    }
+}
+
+impl fmt::Display for QueryPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Every box of type `key` (a FourCC, or the extended type of uuid boxes)
+/// anywhere in `roots`, in file order, with its path.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::get_boxes;
+/// use mp4box::query::find_all;
+///
+/// let mut file = std::fs::File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for (path, pssh) in find_all(&boxes, b"pssh") {
+///     println!("{path}: {:?}", pssh.decoded);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn find_all<T: Queryable>(roots: &[T], key: impl Into<BoxKey>) -> Vec<(QueryPath, &T)> {
+    let segment = match key.into() {
+        BoxKey::FourCC(typ) => format!("0x{}", typ.to_hex()),
+        BoxKey::Uuid(u) => format!("uuid:{}", hex::encode(u)),
+    };
+    select_paths(roots, &format!("**.{segment}"))
+        .into_iter()
+        .map(|s| (QueryPath(s.path), s.node))
+        .collect()
+}
+
+/// A box tree [`select`] can match attributes and uuids in.
+pub trait Queryable: Node {
+    /// Extended type of a `uuid` box.
+    fn usertype(&self) -> Option<[u8; 16]>;
+
+    /// Value of attribute `name` for `[@name=value]` filters, if the box
+    /// has it.
+    fn attribute(&self, name: &str) -> Option<String>;
+}
+
+/// Attributes every box has, from its header: `type`, `offset`, `size`,
+/// and `version` and `flags` for full boxes.
+fn header_attribute(
+    typ: FourCC,
+    offset: u64,
+    size: u64,
+    version: Option<u8>,
+    flags: Option<u32>,
+    name: &str,
+) -> Option<String> {
+    match name {
+        "type" => Some(typ.to_string()),
+        "offset" => Some(offset.to_string()),
+        "size" => Some(size.to_string()),
+        "version" => version.map(|v| v.to_string()),
+        "flags" => flags.map(|f| f.to_string()),
+        _ => None,
+    }
+}
+
+/// Decoded trees add `handler` (hdlr handler type of a trak, mdia, meta or
+/// hdlr), `track_id` (tkhd of a trak, tfhd of a traf) and `codec` (first
+/// sample entry below the box).
+impl Queryable for Box {
+    fn usertype(&self) -> Option<[u8; 16]> {
+        let mut raw = [0u8; 16];
+        let hex = self.uuid.as_deref()?;
+        hex::decode_to_slice(hex, &mut raw).ok().map(|()| raw)
+    }
+
+    fn attribute(&self, name: &str) -> Option<String> {
+        match name {
+            "handler" => {
+                let mdia = self.child_nodes().iter().filter(|b| b.typ == "mdia");
+                std::iter::once(self)
+                    .chain(self.child_nodes())
+                    .chain(mdia.flat_map(|m| m.child_nodes()))
+                    .find_map(|b| match &b.structured_data {
+                        Some(StructuredData::HandlerReference(h)) => Some(h.handler_type.clone()),
+                        _ => None,
+                    })
+            }
+            "track_id" => std::iter::once(self)
+                .chain(self.child_nodes())
+                .find_map(|b| match &b.structured_data {
+                    Some(StructuredData::TrackHeader(t)) => Some(t.track_id.to_string()),
+                    Some(StructuredData::TrackFragmentHeader(t)) => Some(t.track_id.to_string()),
+                    _ => None,
+                }),
+            "codec" => self.iter().find_map(|(_, b)| match &b.structured_data {
+                Some(StructuredData::SampleDescription(s)) => {
+                    s.entries.first().map(|e| e.codec.clone())
+                }
+                _ => None,
+            }),
+            _ => header_attribute(
+                self.fourcc(),
+                self.offset,
+                self.size,
+                self.version,
+                self.flags,
+                name,
+            ),
+        }
+    }
+}
+
+/// Raw trees only have the header attributes.
+impl Queryable for BoxRef {
+    fn usertype(&self) -> Option<[u8; 16]> {
+        self.hdr.uuid
+    }
+
+    fn attribute(&self, name: &str) -> Option<String> {
+        let (version, flags) = match self.kind {
+            NodeKind::FullBox { version, flags, .. } => (Some(version), Some(flags)),
+            _ => (None, None),
+        };
+        let h = &self.hdr;
+        header_attribute(h.typ, h.start, h.size, version, flags, name)
+    }
+}
+
+enum Step {
+    /// One level down, keeping the boxes that pass every filter
+    Child(Matcher, Vec<Filter>),
+    /// `**`: zero or more levels down
+    AnyDepth,
+}
+
+enum Matcher {
+    Type(FourCC),
+    Uuid([u8; 16]),
+    /// `*`
+    Any,
+    /// A segment that can match nothing, e.g. a type of the wrong length
+    Never,
+}
+
+enum Filter {
+    /// `[i]`: the i-th box (of those left) under each parent
+    Index(usize),
+    /// `[@name=value]` or `[@name!=value]`
+    Attribute {
+        name: String,
+        value: String,
+        equal: bool,
+    },
+}
+
+/// Boxes of `roots` (a [`Box`] or [`BoxRef`] tree) matching `path`, in file
+/// order.
+///
+/// A path is a dot-separated list of segments, each matched among the
+/// children of the previous segment's matches (the first among `roots`):
+///
+/// - `trak`: boxes of that type; `0x` and 8 hex digits give raw bytes
+/// - `uuid:<32 hex digits>`: uuid boxes with that extended type
+/// - `*`: any box
+/// - `**`: zero or more levels of any boxes, so `**.tenc` is every tenc;
+///   `**[@version=1]` is every box passing the filter
+/// - `[i]` after a segment: only the i-th of its matches under each parent
+/// - `[@name=value]`, `[@name!=value]`: only boxes whose attribute (see
+///   [`Queryable`]) has, or has not, that value; numbers compare by value,
+///   so `[@flags=0x1]` and `[@flags=1]` agree
+///
+/// Filters apply left to right: `trak[@handler=vide][0]` is the first video
+/// track. Bracketed text that is neither an index nor an attribute filter
+/// is ignored.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::get_boxes;
+/// use mp4box::query::select;
+///
+/// let mut file = std::fs::File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for stbl in select(&boxes, "moov.trak[@handler=soun].mdia.minf.stbl") {
+///     println!("audio stbl at {}", stbl.offset);
+/// }
+/// println!("{} tenc boxes", select(&boxes, "**.tenc").len());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn select<'a, T: Queryable>(roots: &'a [T], path: &str) -> Vec<&'a T> {
+    select_paths(roots, path)
+        .into_iter()
+        .map(|s| s.node)
+        .collect()
+}
+
+/// Like [`select`], with the full path of each match.
+pub fn select_paths<'a, T: Queryable>(roots: &'a [T], path: &str) -> Vec<Selection<'a, T>> {
+    let steps: Vec<Step> = split_segments(path)
+        .into_iter()
+        .flat_map(parse_step)
+        .collect();
+    // Sibling lists the next step matches in, with the path of their parent
+    let mut contexts: Vec<(String, &'a [T])> = vec![(String::new(), roots)];
+    let mut matches = Vec::new();
+
+    for step in &steps {
+        matches = match step {
+            Step::AnyDepth => {
+                let mut expanded = Vec::new();
+                for (prefix, siblings) in &contexts {
+                    descend(prefix, siblings, &mut expanded);
+                }
+                expanded.dedup_by_key(|(_, s)| s.as_ptr());
+                contexts = expanded;
+                // A trailing ** selects everything below
+                contexts
+                    .iter()
+                    .flat_map(|(prefix, siblings)| labeled(prefix, siblings))
+                    .map(|(path, node)| Selection { path, node })
+                    .collect()
+            }
+            Step::Child(matcher, filters) => {
+                let mut found = Vec::new();
+                for (prefix, siblings) in &contexts {
+                    let mut kept: Vec<(String, &T)> = labeled(prefix, siblings)
+                        .into_iter()
+                        .filter(|(_, b)| matcher.matches(*b))
+                        .collect();
+                    for filter in filters {
+                        kept = filter.apply(kept);
+                    }
+                    found.extend(
+                        kept.into_iter()
+                            .map(|(path, node)| Selection { path, node }),
+                    );
+                }
+                contexts = found
+                    .iter()
+                    .map(|s| (s.path.clone(), s.node.child_nodes()))
+                    .collect();
+                found
+            }
+        };
+    }
+
+    // Overlapping ** expansions can reach a box twice
+    let mut seen = std::collections::HashSet::new();
+    matches.retain(|s| seen.insert(s.node as *const T));
+    matches
+}
+
+/// `siblings` and, depth-first, the children of every box below them.
+fn descend<'a, T: Queryable>(prefix: &str, siblings: &'a [T], out: &mut Vec<(String, &'a [T])>) {
+    out.push((prefix.to_string(), siblings));
+    for (path, b) in labeled(prefix, siblings) {
+        let kids = b.child_nodes();
+        if !kids.is_empty() {
+            descend(&path, kids, out);
+        }
+    }
+}
+
+/// Each of `siblings` with its full path.
+fn labeled<'a, T: Queryable>(prefix: &str, siblings: &'a [T]) -> Vec<(String, &'a T)> {
+    let names: Vec<String> = siblings.iter().map(name).collect();
+    let mut counts = std::collections::HashMap::<&str, usize>::new();
+    for n in &names {
+        *counts.entry(n).or_default() += 1;
+    }
+    let mut seen = std::collections::HashMap::<&str, usize>::new();
+    siblings
+        .iter()
+        .zip(&names)
+        .map(|(b, name)| {
+            let count = counts[name.as_str()];
+            let index = seen.entry(name).or_default();
+            let mut path = prefix.to_string();
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(name);
+            if count > 1 {
+                path.push_str(&format!("[{index}]"));
+            }
+            *index += 1;
+            (path, b)
+        })
+        .collect()
+}
+
+/// Path segment naming `b` alone among siblings of other types.
+fn name<T: Queryable>(b: &T) -> String {
+    let typ = b.fourcc();
+    match b.usertype() {
+        Some(u) if &typ.0 == b"uuid" => format!("uuid:{}", hex::encode(u)),
+        _ if typ.is_printable() => typ.to_string(),
+        _ => format!("0x{}", typ.to_hex()),
+    }
+}
+
+impl Matcher {
+    fn matches<T: Queryable>(&self, b: &T) -> bool {
+        match self {
+            Matcher::Any => true,
+            Matcher::Type(t) => b.fourcc() == *t,
+            Matcher::Uuid(u) => &b.fourcc().0 == b"uuid" && b.usertype() == Some(*u),
+            Matcher::Never => false,
+        }
+    }
+}
+
+impl Filter {
+    fn apply<'a, T: Queryable>(&self, kept: Vec<(String, &'a T)>) -> Vec<(String, &'a T)> {
+        match self {
+            Filter::Index(i) => kept.into_iter().nth(*i).into_iter().collect(),
+            Filter::Attribute { name, value, equal } => kept
+                .into_iter()
+                .filter(|(_, b)| {
+                    let same = b.attribute(name).is_some_and(|v| same_value(&v, value));
+                    same == *equal
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Equal as strings, or as numbers (decimal or 0x hex).
+fn same_value(a: &str, b: &str) -> bool {
+    let number = |s: &str| match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse::<u64>().ok(),
+    };
+    a == b || matches!((number(a), number(b)), (Some(x), Some(y)) if x == y)
+}
+
+/// Split on dots outside brackets.
+fn split_segments(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in path.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth = depth.saturating_sub(1),
+            '.' if depth == 0 => {
+                segments.push(&path[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    segments.push(&path[start..]);
+    segments
+}
+
+/// `trak[@handler=vide][0]` -> the trak matcher and its two filters;
+/// `**[...]` -> `**` then `*[...]`.
+fn parse_step(seg: &str) -> Vec<Step> {
+    let (head, mut rest) = match seg.find('[') {
+        Some(i) => seg.split_at(i),
+        None => (seg, ""),
+    };
+    if head == "**" && rest.is_empty() {
+        return vec![Step::AnyDepth];
+    }
+
+    let matcher = if head == "*" || head == "**" {
+        Matcher::Any
+    } else if let Some(hex) = head.strip_prefix("uuid:") {
+        let mut raw = [0u8; 16];
+        match hex::decode_to_slice(hex, &mut raw) {
+            Ok(()) => Matcher::Uuid(raw),
+            Err(_) => Matcher::Never,
+        }
+    } else {
+        FourCC::from_str(head).map_or(Matcher::Never, Matcher::Type)
+    };
+
+    let mut filters = Vec::new();
+    while let Some(body) = rest.strip_prefix('[') {
+        let Some((inner, after)) = body.split_once(']') else {
+            break;
+        };
+        rest = after;
+        if let Some(attr) = inner.strip_prefix('@') {
+            let (name, value, equal) = match attr.split_once("!=") {
+                Some((n, v)) => (n, v, false),
+                None => match attr.split_once('=') {
+                    Some((n, v)) => (n, v, true),
+                    None => continue,
+                },
+            };
+            filters.push(Filter::Attribute {
+                name: name.trim().to_string(),
+                value: value.trim().to_string(),
+                equal,
+            });
+        } else if let Ok(i) = inner.parse() {
+            filters.push(Filter::Index(i));
+        }
+    }
+    let child = Step::Child(matcher, filters);
+    match head {
+        "**" => vec![Step::AnyDepth, child],
+        _ => vec![child],
+    }
+}
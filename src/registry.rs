@@ -1,7 +1,9 @@
 use crate::boxes::{BoxHeader, BoxKey, FourCC};
+use crate::known_boxes::KnownBox;
 use byteorder::{BigEndian, ReadBytesExt};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::io::{Cursor, Read};
+use std::sync::Mutex;
 
 /// A value returned from a box decoder.
 ///
@@ -16,18 +18,26 @@ pub enum BoxValue {
 /// Structured data for sample table boxes
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum StructuredData {
+    /// File Type Box (ftyp)
+    FileType(FtypData),
+    /// Movie Header Box (mvhd)
+    MovieHeader(MvhdData),
     /// Sample Description Box (stsd)
     SampleDescription(StsdData),
     /// Decoding Time-to-Sample Box (stts)
     DecodingTimeToSample(SttsData),
     /// Composition Time-to-Sample Box (ctts)
     CompositionTimeToSample(CttsData),
+    /// Edit List Box (elst)
+    EditList(ElstData),
     /// Sample-to-Chunk Box (stsc)
     SampleToChunk(StscData),
     /// Sample Size Box (stsz)
     SampleSize(StszData),
     /// Sync Sample Box (stss)
     SyncSample(StssData),
+    /// Independent and Disposable Samples Box (sdtp)
+    SampleDependencyType(SdtpData),
     /// Chunk Offset Box (stco)
     ChunkOffset(StcoData),
     /// 64-bit Chunk Offset Box (co64)
@@ -38,6 +48,395 @@ pub enum StructuredData {
     HandlerReference(HdlrData),
     /// Track Header Box (tkhd)
     TrackHeader(TkhdData),
+    /// Channel Layout Box (chnl)
+    ChannelLayout(ChnlData),
+    /// PCM Configuration Box (pcmC)
+    PcmConfig(PcmCData),
+    /// Track Kind Box (kind)
+    TrackKind(KindData),
+    /// Extended Language Tag Box (elng)
+    ExtendedLanguage(ElngData),
+    /// Track Extends Box (trex)
+    TrackExtends(TrexData),
+    /// Movie Fragment Header Box (mfhd)
+    MovieFragmentHeader(MfhdData),
+    /// Track Fragment Header Box (tfhd)
+    TrackFragmentHeader(TfhdData),
+    /// Track Fragment Decode Time Box (tfdt)
+    TrackFragmentDecodeTime(TfdtData),
+    /// Track Fragment Run Box (trun)
+    TrackFragmentRun(TrunData),
+    /// Track Fragment Random Access Box (tfra)
+    TrackFragmentRandomAccess(TfraData),
+    /// Segment Index Box (sidx)
+    SegmentIndex(SidxData),
+    /// Sample To Group Box (sbgp)
+    SampleToGroup(SbgpData),
+    /// Sample Group Description Box (sgpd)
+    SampleGroupDescription(SgpdData),
+    /// Track Encryption Box (tenc)
+    TrackEncryption(TencData),
+    /// Pixel Aspect Ratio Box (pasp)
+    PixelAspectRatio(PaspData),
+    /// Clean Aperture Box (clap)
+    CleanAperture(ClapData),
+    /// Stereoscopic 3D Video Box (st3d)
+    StereoVideo(St3dData),
+    /// Spherical Video Header Box (svhd)
+    SphericalVideoHeader(SvhdData),
+    /// Projection Header Box (prhd)
+    ProjectionHeader(PrhdData),
+    /// Equirectangular Projection Box (equi)
+    EquirectangularProjection(EquiData),
+    /// Cubemap Projection Box (cbmp)
+    CubemapProjection(CbmpData),
+    /// Spatial Audio Box (SA3D)
+    SpatialAudio(Sa3dData),
+    /// QuickTime GPS location (©xyz)
+    Location(LocationData),
+    /// iTunes metadata value atom (ilst item `data`)
+    MetadataValue(MetadataValueData),
+    /// QuickTime metadata item keys (keys)
+    MetadataKeys(KeysData),
+    /// Track reference type box (tref children such as chap)
+    TrackReference(TrackReferenceData),
+    /// Nero chapter list (udta/chpl)
+    ChapterList(ChplData),
+    /// Item Location Box (iloc)
+    ItemLocation(IlocData),
+    /// Image Spatial Extents property (ispe)
+    ImageSpatialExtents(IspeData),
+    /// Pixel Information property (pixi)
+    PixelInformation(PixiData),
+    /// Image Rotation property (irot)
+    ImageRotation(IrotData),
+    /// Image Mirroring property (imir)
+    ImageMirror(ImirData),
+    /// ID3v2 tag carried in meta (ID32)
+    Id3Tag(Id32Data),
+    /// XML Box (xml )
+    Xml(XmlData),
+    /// XMP packet (uuid BE7ACFCB-97A9-42E8-9C71-999491E3AFAC)
+    Xmp(XmpData),
+    /// PIFF TfxdBox: absolute time of this fragment
+    PiffFragmentTime(PiffTfxdData),
+    /// PIFF TfrfBox: times of following fragments
+    PiffFragmentReference(PiffTfrfData),
+    /// PIFF SampleEncryptionBox
+    PiffSampleEncryption(PiffSencData),
+    /// MPEG-H 3D Audio decoder configuration (mhaC)
+    MpegHConfig(MhacData),
+    /// DTS specific configuration (ddts)
+    DtsConfig(DdtsData),
+    /// AC-4 decoder specific information (dac4)
+    Ac4Config(Dac4Data),
+    /// Component Definition Box (cmpd)
+    ComponentDefinition(CmpdData),
+    /// Uncompressed Frame Configuration Box (uncC)
+    UncompressedFrameConfig(UncCData),
+    /// Track or album loudness info (tlou, alou)
+    Loudness(LoudnessData),
+    /// QuickTime international text atom (udta ©nam, ©too, ...)
+    UserDataText(UserDataTextData),
+}
+
+impl StructuredData {
+    /// One-line `key=value` summary, used as the `decoded` text of a box.
+    ///
+    /// Keys are stable so text consumers can parse them; free-form strings
+    /// are quoted and long tables are reduced to their counts.
+    pub fn summary(&self) -> String {
+        use std::fmt::Write;
+
+        fn opt<T: std::fmt::Display>(out: &mut String, key: &str, v: &Option<T>) {
+            if let Some(v) = v {
+                let _ = write!(out, " {key}={v}");
+            }
+        }
+
+        fn dates(out: &mut String, created: &Option<String>, modified: &Option<String>) {
+            opt(out, "created", created);
+            opt(out, "modified", modified);
+        }
+
+        match self {
+            StructuredData::MovieHeader(d) => {
+                let mut s = format!(
+                    "timescale={} duration={} rate={} volume={} next_track_id={}",
+                    d.timescale, d.duration, d.rate, d.volume, d.next_track_id
+                );
+                dates(&mut s, &d.creation_date, &d.modification_date);
+                s
+            }
+            StructuredData::SampleDescription(d) => {
+                let mut s = format!("entry_count={}", d.entry_count);
+                if let Some(e) = d.entries.first() {
+                    let _ = write!(s, " codec={}", e.codec);
+                    opt(&mut s, "width", &e.width);
+                    opt(&mut s, "height", &e.height);
+                    opt(&mut s, "channels", &e.channel_count);
+                    opt(&mut s, "sample_size", &e.sample_size);
+                    opt(&mut s, "sample_rate", &e.sample_rate);
+                }
+                s
+            }
+            StructuredData::FileType(d) => format!(
+                "major={} minor={} compatible={:?}",
+                d.major_brand, d.minor_version, d.compatible_brands
+            ),
+            StructuredData::DecodingTimeToSample(d) => format!("entry_count={}", d.entry_count),
+            StructuredData::CompositionTimeToSample(d) => format!("entry_count={}", d.entry_count),
+            StructuredData::EditList(d) => {
+                let mut s = format!("version={} entries={}", d.version, d.entries.len());
+                if let Some(e) = d.entries.first() {
+                    let _ = write!(
+                        s,
+                        " first: duration={} media_time={} rate={}",
+                        e.segment_duration, e.media_time, e.media_rate
+                    );
+                }
+                s
+            }
+            StructuredData::SampleToChunk(d) => format!("entry_count={}", d.entry_count),
+            StructuredData::SampleSize(d) => format!(
+                "sample_size={} sample_count={}",
+                d.sample_size, d.sample_count
+            ),
+            StructuredData::SyncSample(d) => format!("entry_count={}", d.entry_count),
+            StructuredData::SampleDependencyType(d) => format!("entry_count={}", d.entries.len()),
+            StructuredData::ChunkOffset(d) => format!("entry_count={}", d.entry_count),
+            StructuredData::ChunkOffset64(d) => format!("entry_count={}", d.entry_count),
+            StructuredData::MediaHeader(d) => {
+                let mut s = format!(
+                    "timescale={} duration={} language={}",
+                    d.timescale, d.duration, d.language
+                );
+                dates(&mut s, &d.creation_date, &d.modification_date);
+                s
+            }
+            StructuredData::HandlerReference(d) => {
+                format!("handler={} name={:?}", d.handler_type, d.name)
+            }
+            StructuredData::TrackHeader(d) => {
+                let mut s = format!(
+                    "track_id={} duration={} alternate_group={} width={} height={}",
+                    d.track_id, d.duration, d.alternate_group, d.width, d.height
+                );
+                dates(&mut s, &d.creation_date, &d.modification_date);
+                s
+            }
+            StructuredData::ChannelLayout(d) => {
+                let mut s = format!("stream_structure={}", d.stream_structure);
+                opt(&mut s, "defined_layout", &d.defined_layout);
+                if !d.speaker_positions.is_empty() {
+                    let _ = write!(s, " speakers={}", d.speaker_positions.len());
+                }
+                opt(&mut s, "object_count", &d.object_count);
+                s
+            }
+            StructuredData::PcmConfig(d) => format!(
+                "pcm_sample_size={} little_endian={}",
+                d.pcm_sample_size, d.little_endian
+            ),
+            StructuredData::TrackKind(d) => {
+                format!("scheme_uri={:?} value={:?}", d.scheme_uri, d.value)
+            }
+            StructuredData::ExtendedLanguage(d) => {
+                format!("extended_language={}", d.extended_language)
+            }
+            StructuredData::TrackExtends(d) => format!(
+                "track_id={} default_sample_description_index={} default_sample_duration={} default_sample_size={} default_sample_flags=0x{:08x}",
+                d.track_id,
+                d.default_sample_description_index,
+                d.default_sample_duration,
+                d.default_sample_size,
+                d.default_sample_flags
+            ),
+            StructuredData::MovieFragmentHeader(d) => {
+                format!("sequence_number={}", d.sequence_number)
+            }
+            StructuredData::TrackFragmentHeader(d) => {
+                let mut s = format!("track_id={}", d.track_id);
+                opt(&mut s, "base_data_offset", &d.base_data_offset);
+                opt(
+                    &mut s,
+                    "sample_description_index",
+                    &d.sample_description_index,
+                );
+                opt(
+                    &mut s,
+                    "default_sample_duration",
+                    &d.default_sample_duration,
+                );
+                opt(&mut s, "default_sample_size", &d.default_sample_size);
+                if d.decoded_flags.default_base_is_moof {
+                    s.push_str(" default_base_is_moof=true");
+                }
+                s
+            }
+            StructuredData::TrackFragmentDecodeTime(d) => {
+                format!("base_media_decode_time={}", d.base_media_decode_time)
+            }
+            StructuredData::TrackFragmentRandomAccess(d) => {
+                format!("track_id={} entries={}", d.track_id, d.entries.len())
+            }
+            StructuredData::TrackFragmentRun(d) => {
+                let mut s = format!("sample_count={}", d.sample_count);
+                opt(&mut s, "data_offset", &d.data_offset);
+                s
+            }
+            StructuredData::SegmentIndex(d) => format!(
+                "timescale={} earliest_presentation_time={} first_offset={} references={}",
+                d.timescale,
+                d.earliest_presentation_time,
+                d.first_offset,
+                d.references.len()
+            ),
+            StructuredData::SampleToGroup(d) => {
+                let mut s = format!("grouping_type={}", d.grouping_type);
+                opt(
+                    &mut s,
+                    "grouping_type_parameter",
+                    &d.grouping_type_parameter,
+                );
+                let _ = write!(s, " entry_count={}", d.entries.len());
+                s
+            }
+            StructuredData::SampleGroupDescription(d) => {
+                let mut s = format!("grouping_type={}", d.grouping_type);
+                opt(
+                    &mut s,
+                    "default_sample_description_index",
+                    &d.default_sample_description_index,
+                );
+                let _ = write!(s, " entry_count={}", d.entries.len());
+                s
+            }
+            StructuredData::TrackEncryption(d) => format!(
+                "is_protected={} per_sample_iv_size={} kid={}",
+                d.defaults.is_protected, d.defaults.per_sample_iv_size, d.defaults.kid
+            ),
+            StructuredData::PixelAspectRatio(d) => {
+                format!("h_spacing={} v_spacing={}", d.h_spacing, d.v_spacing)
+            }
+            StructuredData::CleanAperture(d) => format!(
+                "width={} height={} horiz_offset={} vert_offset={}",
+                d.width, d.height, d.horiz_offset, d.vert_offset
+            ),
+            StructuredData::StereoVideo(d) => format!("stereo_mode={}", d.stereo_mode_name),
+            StructuredData::SphericalVideoHeader(d) => {
+                format!("metadata_source={:?}", d.metadata_source)
+            }
+            StructuredData::ProjectionHeader(d) => format!(
+                "yaw={} pitch={} roll={}",
+                d.pose_yaw_degrees, d.pose_pitch_degrees, d.pose_roll_degrees
+            ),
+            StructuredData::EquirectangularProjection(d) => format!(
+                "bounds_top={} bounds_bottom={} bounds_left={} bounds_right={}",
+                d.projection_bounds_top,
+                d.projection_bounds_bottom,
+                d.projection_bounds_left,
+                d.projection_bounds_right
+            ),
+            StructuredData::CubemapProjection(d) => {
+                format!("layout={} padding={}", d.layout, d.padding)
+            }
+            StructuredData::SpatialAudio(d) => format!(
+                "ambisonic_order={} channels={}",
+                d.ambisonic_order, d.num_channels
+            ),
+            StructuredData::Location(d) => format!("iso6709={:?}", d.iso6709),
+            StructuredData::MetadataValue(d) => {
+                let value = match &d.value {
+                    TagValue::Text(t) => format!("{t:?}"),
+                    TagValue::Integer(i) => i.to_string(),
+                    TagValue::Float(f) => f.to_string(),
+                    TagValue::Image { format, size } => format!("{format}:{size}"),
+                    TagValue::Index { number, total } => format!("{number}/{total}"),
+                    TagValue::Binary(b) => format!("{}_bytes", b.len()),
+                };
+                format!("type={} value={}", d.type_indicator, value)
+            }
+            StructuredData::MetadataKeys(d) => format!("entry_count={}", d.entries.len()),
+            StructuredData::TrackReference(d) => {
+                let ids: Vec<String> = d.track_ids.iter().map(|i| i.to_string()).collect();
+                format!("type={} track_ids=[{}]", d.reference_type, ids.join(","))
+            }
+            StructuredData::ChapterList(d) => format!("entry_count={}", d.entries.len()),
+            StructuredData::ItemLocation(d) => format!("item_count={}", d.items.len()),
+            StructuredData::ImageSpatialExtents(d) => {
+                format!("width={} height={}", d.image_width, d.image_height)
+            }
+            StructuredData::PixelInformation(d) => {
+                let bits: Vec<String> = d.bits_per_channel.iter().map(|b| b.to_string()).collect();
+                format!("bits_per_channel=[{}]", bits.join(","))
+            }
+            StructuredData::ImageRotation(d) => format!("degrees={}", d.degrees),
+            StructuredData::ImageMirror(d) => format!("axis={}", d.axis_name),
+            StructuredData::Id3Tag(d) => format!(
+                "language={} id3_version={} frames={}",
+                d.language,
+                d.id3_version,
+                d.frames.len()
+            ),
+            StructuredData::Xml(d) => {
+                format!("encoding={} length={}", d.encoding, d.text.len())
+            }
+            StructuredData::Xmp(d) => format!("length={}", d.packet.len()),
+            StructuredData::PiffFragmentTime(d) => {
+                format!("absolute_time={} duration={}", d.absolute_time, d.duration)
+            }
+            StructuredData::PiffFragmentReference(d) => {
+                format!("fragment_count={}", d.fragments.len())
+            }
+            StructuredData::PiffSampleEncryption(d) => {
+                let mut s = format!("sample_count={}", d.sample_count);
+                opt(&mut s, "iv_size", &d.iv_size);
+                opt(&mut s, "kid", &d.kid);
+                s
+            }
+            StructuredData::MpegHConfig(d) => format!(
+                "profile_level={} reference_channel_layout={}",
+                d.profile_level_indication, d.reference_channel_layout
+            ),
+            StructuredData::DtsConfig(d) => format!(
+                "sample_rate={} max_bitrate={} avg_bitrate={} sample_depth={}",
+                d.sampling_frequency, d.max_bitrate, d.avg_bitrate, d.pcm_sample_depth
+            ),
+            StructuredData::Ac4Config(d) => format!(
+                "sample_rate={} bit_rate={} presentations={}",
+                d.sampling_rate, d.bit_rate, d.n_presentations
+            ),
+            StructuredData::ComponentDefinition(d) => {
+                let names: Vec<&str> = d
+                    .components
+                    .iter()
+                    .map(|c| c.component_type_name.as_str())
+                    .collect();
+                format!("components=[{}]", names.join(","))
+            }
+            StructuredData::UncompressedFrameConfig(d) => {
+                let mut s = format!("profile={:?}", d.profile);
+                if d.version == 0 {
+                    let _ = write!(
+                        s,
+                        " components={} sampling={} interleave={}",
+                        d.components.len(),
+                        d.sampling_type_name,
+                        d.interleave_type_name
+                    );
+                }
+                s
+            }
+            StructuredData::Loudness(d) => {
+                format!("type={} entries={}", d.loudness_type, d.entries.len())
+            }
+            StructuredData::UserDataText(d) => match d.entries.first() {
+                Some(e) => format!("text={:?}", e.text),
+                None => "entries=0".to_string(),
+            },
+        }
+    }
 }
 
 /// Sample Description Box data
@@ -56,6 +455,92 @@ pub struct SampleEntry {
     pub data_reference_index: u16,
     pub width: Option<u16>,
     pub height: Option<u16>,
+    /// Audio entries only
+    pub channel_count: Option<u16>,
+    /// Audio entries only, in bits
+    pub sample_size: Option<u16>,
+    /// Audio entries only, in Hz (integer part of the 16.16 value)
+    pub sample_rate: Option<u32>,
+    /// Subtitle entries only (tx3g, wvtt, stpp)
+    pub subtitle: Option<SubtitleConfig>,
+}
+
+/// Configuration of a subtitle sample entry.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "format", rename_all = "snake_case")]
+pub enum SubtitleConfig {
+    /// 3GPP timed text (tx3g)
+    TimedText(Tx3gConfig),
+    /// WebVTT (wvtt)
+    WebVtt(WebVttConfig),
+    /// XML subtitles such as TTML / IMSC (stpp)
+    Xml(XmlSubtitleConfig),
+}
+
+/// 3GPP TextSampleEntry fields
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Tx3gConfig {
+    pub display_flags: u32,
+    pub horizontal_justification: i8,
+    pub vertical_justification: i8,
+    /// RGBA
+    pub background_color: [u8; 4],
+    pub default_text_box: TextBox,
+    pub default_style: TextStyle,
+    /// From the ftab box
+    pub fonts: Vec<FontRecord>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TextBox {
+    pub top: i16,
+    pub left: i16,
+    pub bottom: i16,
+    pub right: i16,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TextStyle {
+    pub start_char: u16,
+    pub end_char: u16,
+    pub font_id: u16,
+    /// 1 = bold, 2 = italic, 4 = underline
+    pub face_style_flags: u8,
+    pub font_size: u8,
+    /// RGBA
+    pub text_color: [u8; 4],
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FontRecord {
+    pub font_id: u16,
+    pub name: String,
+}
+
+/// WebVTT sample entry configuration
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct WebVttConfig {
+    /// WebVTT file header from vttC (e.g. "WEBVTT")
+    pub config: Option<String>,
+    /// Source label from vlab
+    pub label: Option<String>,
+}
+
+/// XMLSubtitleSampleEntry fields
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct XmlSubtitleConfig {
+    /// Space-separated XML namespaces
+    pub namespace: String,
+    pub schema_location: String,
+    pub auxiliary_mime_types: String,
+}
+
+/// File Type Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FtypData {
+    pub major_brand: String,
+    pub minor_version: u32,
+    pub compatible_brands: Vec<String>,
 }
 
 /// Decoding Time-to-Sample Box data
@@ -73,6 +558,70 @@ pub struct SttsEntry {
     pub sample_delta: u32,
 }
 
+/// Edit List Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ElstData {
+    pub version: u8,
+    pub flags: u32,
+    pub entries: Vec<ElstEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ElstEntry {
+    /// In movie (mvhd) timescale units
+    pub segment_duration: u64,
+    /// In media (mdhd) timescale units; -1 for an empty edit
+    pub media_time: i64,
+    pub media_rate: f32,
+}
+
+/// Frame rate of a track, derived from stts and the mdhd timescale.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FrameRate {
+    /// From the sample delta that covers the most samples
+    pub nominal: f64,
+    /// Sample count over total duration
+    pub average: f64,
+    /// More than one sample delta in use (ignoring a short last sample)
+    pub variable: bool,
+}
+
+impl SttsData {
+    /// Frame rate for a track with the given mdhd timescale; None when the
+    /// table has no samples with a non-zero duration.
+    pub fn frame_rate(&self, timescale: u32) -> Option<FrameRate> {
+        let samples: u64 = self.entries.iter().map(|e| e.sample_count as u64).sum();
+        let duration: u64 = self
+            .entries
+            .iter()
+            .map(|e| e.sample_count as u64 * e.sample_delta as u64)
+            .sum();
+        let dominant = self
+            .entries
+            .iter()
+            .filter(|e| e.sample_delta > 0)
+            .max_by_key(|e| e.sample_count)?;
+        if duration == 0 || timescale == 0 {
+            return None;
+        }
+
+        // Muxers often give the final sample whatever duration is left over
+        let body = match self.entries.split_last() {
+            Some((last, rest)) if last.sample_count == 1 && !rest.is_empty() => rest,
+            _ => &self.entries[..],
+        };
+        let variable = body
+            .iter()
+            .any(|e| e.sample_count > 0 && e.sample_delta != dominant.sample_delta);
+
+        Some(FrameRate {
+            nominal: timescale as f64 / dominant.sample_delta as f64,
+            average: samples as f64 * timescale as f64 / duration as f64,
+            variable,
+        })
+    }
+}
+
 /// Composition Time-to-Sample Box data
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CttsData {
@@ -123,6 +672,24 @@ pub struct StssData {
     pub sample_numbers: Vec<u32>,
 }
 
+/// Independent and Disposable Samples Box data; one entry per sample
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SdtpData {
+    pub version: u8,
+    pub flags: u32,
+    pub entries: Vec<SdtpEntry>,
+}
+
+/// Two-bit fields with the same meaning as in [`SampleFlags`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SdtpEntry {
+    pub is_leading: u8,
+    /// 1: depends on other samples, 2: does not (an I picture), 0: unknown
+    pub sample_depends_on: u8,
+    pub sample_is_depended_on: u8,
+    pub sample_has_redundancy: u8,
+}
+
 /// Chunk Offset Box data
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct StcoData {
@@ -141,16 +708,43 @@ pub struct Co64Data {
     pub chunk_offsets: Vec<u64>,
 }
 
+/// Movie Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MvhdData {
+    pub version: u8,
+    pub flags: u32,
+    /// Seconds since 1904-01-01 UTC
+    pub creation_time: u64,
+    pub modification_time: u64,
+    /// RFC 3339; None when the time is not set
+    pub creation_date: Option<String>,
+    pub modification_date: Option<String>,
+    pub timescale: u32,
+    pub duration: u64,
+    /// Preferred playback rate (16.16 fixed point; 1.0 is normal speed)
+    pub rate: f32,
+    /// Preferred volume (8.8 fixed point; 1.0 is full volume)
+    pub volume: f32,
+    /// Next unused track ID
+    pub next_track_id: u32,
+}
+
 /// Media Header Box data
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct MdhdData {
     pub version: u8,
     pub flags: u32,
-    pub creation_time: u32,
-    pub modification_time: u32,
+    /// Seconds since 1904-01-01 UTC
+    pub creation_time: u64,
+    pub modification_time: u64,
+    /// RFC 3339; None when the time is not set
+    pub creation_date: Option<String>,
+    pub modification_date: Option<String>,
     pub timescale: u32,
-    pub duration: u32,
+    pub duration: u64,
     pub language: String,
+    /// `language` as BCP-47 (ISO 639-1 where one exists)
+    pub language_tag: Option<String>,
 }
 
 /// Handler Reference Box data
@@ -167,190 +761,2761 @@ pub struct HdlrData {
 pub struct TkhdData {
     pub version: u8,
     pub flags: u32,
+    /// Seconds since 1904-01-01 UTC
+    pub creation_time: u64,
+    pub modification_time: u64,
+    /// RFC 3339; None when the time is not set
+    pub creation_date: Option<String>,
+    pub modification_date: Option<String>,
     pub track_id: u32,
     pub duration: u64,
+    /// Tracks sharing a non-zero group are alternatives to each other
+    pub alternate_group: u16,
+    /// Transformation matrix {a, b, u, c, d, v, x, y, w}: u, v and w are
+    /// 2.30 fixed point, the rest 16.16
+    pub matrix: [i32; 9],
     pub width: f32,
     pub height: f32,
 }
 
-/// Trait for custom box decoders.
-///
-/// A decoder is responsible for interpreting the payload of a specific box
-/// (identified by a [`BoxKey`]) and returning a [`BoxValue`].
-pub trait BoxDecoder: Send + Sync {
-    fn decode(
-        &self,
-        r: &mut dyn Read,
-        hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
-    ) -> anyhow::Result<BoxValue>;
-}
-
-/// Registry of decoders keyed by `BoxKey` (4CC or UUID).
-///
-/// The registry is immutable once constructed; use [`Registry::with_decoder`]
-/// to build it fluently.
-pub struct Registry {
-    map: HashMap<BoxKey, BoxDecoderEntry>,
+impl TkhdData {
+    /// How the track is rotated or flipped for display.
+    pub fn orientation(&self) -> Orientation {
+        Orientation::from_matrix(&self.matrix)
+    }
 }
 
-struct BoxDecoderEntry {
-    inner: Box<dyn BoxDecoder>,
-    _name: String,
+/// Display orientation encoded by a transformation matrix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Orientation {
+    /// Clockwise rotation: 0, 90, 180 or 270 degrees
+    Rotation { degrees: u16 },
+    /// Mirrored left-right
+    FlipHorizontal,
+    /// Mirrored top-bottom
+    FlipVertical,
+    /// Any other transform (transposition, shear, ...)
+    Other,
 }
 
-impl Registry {
-    /// Create an empty registry.
-    pub fn new() -> Self {
-        Self {
-            map: HashMap::new(),
+impl Orientation {
+    /// Classify a tkhd/mvhd matrix by the signs of its a, b, c and d
+    /// entries; scaling and translation are ignored.
+    pub fn from_matrix(m: &[i32; 9]) -> Self {
+        match (m[0].signum(), m[1].signum(), m[3].signum(), m[4].signum()) {
+            (1, 0, 0, 1) => Orientation::Rotation { degrees: 0 },
+            (0, 1, -1, 0) => Orientation::Rotation { degrees: 90 },
+            (-1, 0, 0, -1) => Orientation::Rotation { degrees: 180 },
+            (0, -1, 1, 0) => Orientation::Rotation { degrees: 270 },
+            (-1, 0, 0, 1) => Orientation::FlipHorizontal,
+            (1, 0, 0, -1) => Orientation::FlipVertical,
+            _ => Orientation::Other,
         }
     }
+}
 
-    /// Return a new registry with the given decoder added.
-    ///
-    /// `name` is human-readable and used only for debugging / logging.
-    pub fn with_decoder(mut self, key: BoxKey, name: &str, dec: Box<dyn BoxDecoder>) -> Self {
-        self.map.insert(
-            key,
-            BoxDecoderEntry {
-                inner: dec,
-                _name: name.to_string(),
-            },
-        );
-        self
-    }
+/// Channel Layout Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChnlData {
+    pub version: u8,
+    pub flags: u32,
+    /// 1 = channel structured, 2 = object structured (may be combined)
+    pub stream_structure: u8,
+    /// Version 1 only: channel ordering of the stream
+    pub format_ordering: Option<u8>,
+    /// Version 1 only: number of channels in the base layout
+    pub base_channel_count: Option<u8>,
+    /// ISO/IEC 23091-3 ChannelConfiguration; 0 means explicit speaker list
+    pub defined_layout: Option<u8>,
+    /// Explicit speaker positions (only when `defined_layout == 0`)
+    pub speaker_positions: Vec<SpeakerPosition>,
+    /// Bitmap of channels of `defined_layout` that are absent from the stream
+    pub omitted_channels_map: Option<u64>,
+    pub object_count: Option<u8>,
+}
 
-    /// Try to decode the payload of a box using a registered decoder.
-    ///
-    /// Returns `None` if no decoder exists for the given key.
-    pub fn decode(
-        &self,
-        key: &BoxKey,
-        r: &mut dyn Read,
-        hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
-    ) -> Option<anyhow::Result<BoxValue>> {
-        self.map
-            .get(key)
-            .map(|d| d.inner.decode(r, hdr, version, flags))
-    }
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SpeakerPosition {
+    /// ISO/IEC 23091-3 OutputChannelPosition; 126 means explicit azimuth/elevation
+    pub speaker_position: u8,
+    pub azimuth: Option<i16>,
+    pub elevation: Option<i8>,
 }
 
-impl Default for Registry {
-    fn default() -> Self {
-        Self::new()
-    }
+/// PCM Configuration Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PcmCData {
+    pub version: u8,
+    pub flags: u32,
+    pub format_flags: u8,
+    /// Bit 0 of `format_flags`
+    pub little_endian: bool,
+    /// Bits per PCM sample
+    pub pcm_sample_size: u8,
 }
 
-// ---------- Helpers ----------
+/// Track Kind Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct KindData {
+    pub version: u8,
+    pub flags: u32,
+    /// e.g. "urn:mpeg:dash:role:2011"
+    pub scheme_uri: String,
+    /// e.g. "main", "commentary"
+    pub value: String,
+}
 
-fn read_all(r: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
-    let mut buf = Vec::new();
-    r.read_to_end(&mut buf)?;
-    Ok(buf)
+/// Extended Language Tag Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ElngData {
+    pub version: u8,
+    pub flags: u32,
+    /// BCP-47 tag as stored, e.g. "en-US"
+    pub extended_language: String,
 }
 
-fn lang_from_u16(code: u16) -> String {
-    if code == 0 {
-        return "und".to_string();
-    }
-    let c1 = ((code >> 10) & 0x1F) as u8 + 0x60;
-    let c2 = ((code >> 5) & 0x1F) as u8 + 0x60;
-    let c3 = (code & 0x1F) as u8 + 0x60;
-    format!("{}{}{}", c1 as char, c2 as char, c3 as char,)
+/// ISOBMFF `sample_flags` word (trex, tfhd, trun) split into its fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SampleFlags {
+    pub is_leading: u8,
+    pub sample_depends_on: u8,
+    pub sample_is_depended_on: u8,
+    pub sample_has_redundancy: u8,
+    pub sample_padding_value: u8,
+    pub sample_is_non_sync_sample: bool,
+    pub sample_degradation_priority: u16,
 }
 
-// ---------- Decoders ----------
+impl SampleFlags {
+    pub fn from_bits(bits: u32) -> Self {
+        Self {
+            is_leading: ((bits >> 26) & 0x3) as u8,
+            sample_depends_on: ((bits >> 24) & 0x3) as u8,
+            sample_is_depended_on: ((bits >> 22) & 0x3) as u8,
+            sample_has_redundancy: ((bits >> 20) & 0x3) as u8,
+            sample_padding_value: ((bits >> 17) & 0x7) as u8,
+            sample_is_non_sync_sample: (bits >> 16) & 0x1 != 0,
+            sample_degradation_priority: (bits & 0xFFFF) as u16,
+        }
+    }
+}
 
-// ftyp: major + minor + compatible brands
-pub struct FtypDecoder;
+/// Track Extends Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrexData {
+    pub version: u8,
+    pub flags: u32,
+    pub track_id: u32,
+    pub default_sample_description_index: u32,
+    pub default_sample_duration: u32,
+    pub default_sample_size: u32,
+    pub default_sample_flags: u32,
+    pub default_sample_flags_decoded: SampleFlags,
+}
 
-impl BoxDecoder for FtypDecoder {
-    fn decode(
-        &self,
-        r: &mut dyn Read,
-        _hdr: &BoxHeader,
-        _version: Option<u8>,
-        _flags: Option<u32>,
-    ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        if buf.len() < 8 {
-            return Ok(BoxValue::Text(format!(
-                "ftyp: payload too short ({} bytes)",
-                buf.len()
-            )));
-        }
+/// Movie Fragment Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MfhdData {
+    pub version: u8,
+    pub flags: u32,
+    pub sequence_number: u32,
+}
 
-        let major = &buf[0..4];
-        let mut minor_bytes = [0u8; 4];
-        minor_bytes.copy_from_slice(&buf[4..8]);
-        let minor = u32::from_be_bytes(minor_bytes);
+/// Named view of the tfhd `tf_flags` bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TfhdFlags {
+    pub base_data_offset_present: bool,
+    pub sample_description_index_present: bool,
+    pub default_sample_duration_present: bool,
+    pub default_sample_size_present: bool,
+    pub default_sample_flags_present: bool,
+    pub duration_is_empty: bool,
+    pub default_base_is_moof: bool,
+}
 
-        let mut brands = Vec::new();
-        for chunk in buf[8..].chunks(4) {
-            if chunk.len() == 4 {
-                brands.push(String::from_utf8_lossy(chunk).to_string());
-            }
+impl TfhdFlags {
+    pub fn from_bits(bits: u32) -> Self {
+        Self {
+            base_data_offset_present: bits & 0x000001 != 0,
+            sample_description_index_present: bits & 0x000002 != 0,
+            default_sample_duration_present: bits & 0x000008 != 0,
+            default_sample_size_present: bits & 0x000010 != 0,
+            default_sample_flags_present: bits & 0x000020 != 0,
+            duration_is_empty: bits & 0x010000 != 0,
+            default_base_is_moof: bits & 0x020000 != 0,
         }
-
-        Ok(BoxValue::Text(format!(
-            "major={} minor={} compatible={:?}",
-            String::from_utf8_lossy(major),
-            minor,
-            brands
-        )))
     }
 }
 
-// mvhd: timescale + duration
-pub struct MvhdDecoder;
+/// Track Fragment Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TfhdData {
+    pub version: u8,
+    pub flags: u32,
+    pub decoded_flags: TfhdFlags,
+    pub track_id: u32,
+    pub base_data_offset: Option<u64>,
+    pub sample_description_index: Option<u32>,
+    pub default_sample_duration: Option<u32>,
+    pub default_sample_size: Option<u32>,
+    pub default_sample_flags: Option<u32>,
+    pub default_sample_flags_decoded: Option<SampleFlags>,
+}
 
-impl BoxDecoder for MvhdDecoder {
-    fn decode(
-        &self,
-        r: &mut dyn Read,
-        _hdr: &BoxHeader,
-        _version: Option<u8>,
-        _flags: Option<u32>,
-    ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
+/// Track Fragment Decode Time Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TfdtData {
+    pub version: u8,
+    pub flags: u32,
+    /// Decode time of the first sample of the traf, in media timescale units
+    pub base_media_decode_time: u64,
+}
 
-        let version = cur.read_u8()?;
-        let _flags = {
-            let mut f = [0u8; 3];
-            cur.read_exact(&mut f)?;
-            ((f[0] as u32) << 16) | ((f[1] as u32) << 8) | (f[2] as u32)
-        };
+/// Track Fragment Random Access Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TfraData {
+    pub version: u8,
+    pub flags: u32,
+    pub track_id: u32,
+    pub entries: Vec<TfraEntry>,
+}
 
-        let (timescale, duration) = if version == 1 {
-            let _creation = cur.read_u64::<BigEndian>()?;
-            let _mod = cur.read_u64::<BigEndian>()?;
-            let ts = cur.read_u32::<BigEndian>()?;
-            let dur = cur.read_u64::<BigEndian>()?;
-            (ts, dur as u64)
-        } else {
-            let _creation = cur.read_u32::<BigEndian>()?;
-            let _mod = cur.read_u32::<BigEndian>()?;
-            let ts = cur.read_u32::<BigEndian>()?;
-            let dur = cur.read_u32::<BigEndian>()? as u64;
-            (ts, dur)
-        };
+/// One random access point listed in tfra.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TfraEntry {
+    /// Presentation time of the sync sample, in media timescale units
+    pub time: u64,
+    /// File offset of the moof holding the sample
+    pub moof_offset: u64,
+    /// 1-based traf, trun and sample numbers within that moof
+    pub traf_number: u32,
+    pub trun_number: u32,
+    pub sample_number: u32,
+}
 
-        Ok(BoxValue::Text(format!(
-            "timescale={} duration={}",
-            timescale, duration
-        )))
-    }
+/// Segment Index Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SidxData {
+    pub version: u8,
+    pub flags: u32,
+    pub reference_id: u32,
+    pub timescale: u32,
+    pub earliest_presentation_time: u64,
+    /// Bytes from the end of the sidx to the first referenced byte
+    pub first_offset: u64,
+    pub references: Vec<SidxReference>,
 }
 
-// tkhd: track id, duration, width, height
-pub struct TkhdDecoder;
+/// One reference of a sidx: a media subsegment or another sidx.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SidxReference {
+    /// reference_type 1: the referenced bytes start with another sidx
+    pub references_sidx: bool,
+    pub referenced_size: u32,
+    /// In sidx timescale units
+    pub subsegment_duration: u32,
+    pub starts_with_sap: bool,
+    pub sap_type: u8,
+    pub sap_delta_time: u32,
+}
 
-impl BoxDecoder for TkhdDecoder {
+/// Named view of the trun `tr_flags` bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TrunFlags {
+    pub data_offset_present: bool,
+    pub first_sample_flags_present: bool,
+    pub sample_duration_present: bool,
+    pub sample_size_present: bool,
+    pub sample_flags_present: bool,
+    pub sample_composition_time_offsets_present: bool,
+}
+
+impl TrunFlags {
+    pub fn from_bits(bits: u32) -> Self {
+        Self {
+            data_offset_present: bits & 0x000001 != 0,
+            first_sample_flags_present: bits & 0x000004 != 0,
+            sample_duration_present: bits & 0x000100 != 0,
+            sample_size_present: bits & 0x000200 != 0,
+            sample_flags_present: bits & 0x000400 != 0,
+            sample_composition_time_offsets_present: bits & 0x000800 != 0,
+        }
+    }
+}
+
+/// Track Fragment Run Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrunData {
+    pub version: u8,
+    pub flags: u32,
+    pub decoded_flags: TrunFlags,
+    pub sample_count: u32,
+    pub data_offset: Option<i32>,
+    pub first_sample_flags: Option<u32>,
+    pub first_sample_flags_decoded: Option<SampleFlags>,
+    pub entries: Vec<TrunEntry>,
+}
+
+/// One sample of a trun; fields are `None` when not present in the box.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrunEntry {
+    pub sample_duration: Option<u32>,
+    pub sample_size: Option<u32>,
+    /// Raw sample_flags; see [`SampleFlags::from_bits`]
+    pub sample_flags: Option<u32>,
+    /// Unsigned in version 0, signed in version 1
+    pub sample_composition_time_offset: Option<i64>,
+}
+
+/// Pixel Aspect Ratio Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PaspData {
+    pub h_spacing: u32,
+    pub v_spacing: u32,
+}
+
+/// Clean Aperture Box data; the fractions are evaluated to f64.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ClapData {
+    pub width: f64,
+    pub height: f64,
+    /// Offset of the aperture centre from the picture centre
+    pub horiz_offset: f64,
+    pub vert_offset: f64,
+}
+
+/// Stereoscopic 3D Video Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct St3dData {
+    pub version: u8,
+    pub flags: u32,
+    pub stereo_mode: u8,
+    /// "monoscopic", "top-bottom", "left-right" or "stereo-custom"
+    pub stereo_mode_name: String,
+}
+
+/// Spherical Video Header Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SvhdData {
+    pub version: u8,
+    pub flags: u32,
+    /// Tool that injected the spherical metadata
+    pub metadata_source: String,
+}
+
+/// Projection Header Box data; angles are in degrees.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PrhdData {
+    pub version: u8,
+    pub flags: u32,
+    pub pose_yaw_degrees: f64,
+    pub pose_pitch_degrees: f64,
+    pub pose_roll_degrees: f64,
+}
+
+/// Equirectangular Projection Box data.
+///
+/// Bounds are 0.32 fixed-point fractions of the frame cropped from each edge.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EquiData {
+    pub version: u8,
+    pub flags: u32,
+    pub projection_bounds_top: u32,
+    pub projection_bounds_bottom: u32,
+    pub projection_bounds_left: u32,
+    pub projection_bounds_right: u32,
+}
+
+/// Cubemap Projection Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CbmpData {
+    pub version: u8,
+    pub flags: u32,
+    pub layout: u32,
+    pub padding: u32,
+}
+
+/// Spatial Audio Box data (ambisonics)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Sa3dData {
+    /// Version byte from the payload; SA3D is not a FullBox
+    pub version: u8,
+    /// 0 = periphonic
+    pub ambisonic_type: u8,
+    pub ambisonic_order: u32,
+    /// 0 = ACN
+    pub ambisonic_channel_ordering: u8,
+    /// 0 = SN3D
+    pub ambisonic_normalization: u8,
+    pub num_channels: u32,
+    pub channel_map: Vec<u32>,
+}
+
+/// QuickTime user-data location (©xyz)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LocationData {
+    /// ISO 6709 string as stored, e.g. "+37.7749-122.4194+010.000/"
+    pub iso6709: String,
+    /// Packed Macintosh/ISO language code of the string
+    pub language_code: u16,
+    /// Decimal degrees, positive north
+    pub latitude: Option<f64>,
+    /// Decimal degrees, positive east
+    pub longitude: Option<f64>,
+    /// Metres above the reference ellipsoid
+    pub altitude: Option<f64>,
+}
+
+/// A value from an iTunes-style `data` atom.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum TagValue {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    /// Cover art; bytes are left in the file, see `Box::payload_offset`
+    Image {
+        format: String,
+        size: u64,
+    },
+    /// trkn / disk: position and total
+    Index {
+        number: u16,
+        total: u16,
+    },
+    Binary(Vec<u8>),
+}
+
+/// iTunes metadata value atom data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MetadataValueData {
+    /// Well-known type (1 = UTF-8, 13 = JPEG, 14 = PNG, 21 = signed int, ...)
+    pub type_indicator: u32,
+    pub locale: u32,
+    pub value: TagValue,
+}
+
+/// QuickTime Metadata Item Keys Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct KeysData {
+    pub version: u8,
+    pub flags: u32,
+    /// Keys in order; ilst items refer to them by 1-based index
+    pub entries: Vec<MetadataKey>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MetadataKey {
+    /// Key namespace, usually "mdta"
+    pub namespace: String,
+    /// e.g. "com.apple.quicktime.make"
+    pub key: String,
+}
+
+/// Track Reference Type Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TrackReferenceData {
+    /// Reference type, e.g. "chap"
+    pub reference_type: String,
+    pub track_ids: Vec<u32>,
+}
+
+/// Nero Chapter List Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChplData {
+    pub version: u8,
+    pub entries: Vec<ChplEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChplEntry {
+    /// Start time in 100 ns units
+    pub start: u64,
+    pub title: String,
+}
+
+/// Item Location Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IlocData {
+    pub version: u8,
+    pub offset_size: u8,
+    pub length_size: u8,
+    pub base_offset_size: u8,
+    /// Always 0 in version 0
+    pub index_size: u8,
+    pub items: Vec<IlocItem>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IlocItem {
+    pub item_id: u32,
+    /// 0 = file offset, 1 = idat offset, 2 = item offset (always 0 in version 0)
+    pub construction_method: u8,
+    pub construction_method_name: String,
+    /// 0 = this file
+    pub data_reference_index: u16,
+    pub base_offset: u64,
+    pub extents: Vec<IlocExtent>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IlocExtent {
+    /// Only present when index_size is non-zero
+    pub index: Option<u64>,
+    /// Relative to base_offset
+    pub offset: u64,
+    /// 0 means the extent runs to the end of the source
+    pub length: u64,
+}
+
+/// Image Spatial Extents property data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IspeData {
+    pub image_width: u32,
+    pub image_height: u32,
+}
+
+/// Pixel Information property data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PixiData {
+    /// Bit depth of each channel, in channel order
+    pub bits_per_channel: Vec<u8>,
+}
+
+/// Image Rotation property data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct IrotData {
+    /// Anti-clockwise rotation in units of 90 degrees (0-3)
+    pub angle: u8,
+    pub degrees: u16,
+}
+
+/// Image Mirroring property data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ImirData {
+    /// 0 = vertical axis (left-right flip), 1 = horizontal axis (top-bottom flip)
+    pub axis: u8,
+    pub axis_name: String,
+}
+
+/// ID3v2 Metadata Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Id32Data {
+    pub language: String,
+    /// e.g. "2.4.0"; empty if the payload is not an ID3v2 tag
+    pub id3_version: String,
+    /// Every frame in tag order
+    pub frames: Vec<Id3Frame>,
+    /// Text frames by frame ID (`TXXX` frames as `"TXXX:<description>"`)
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Id3Frame {
+    pub id: String,
+    pub size: u32,
+}
+
+/// XML Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct XmlData {
+    /// "utf-8" or "utf-16"
+    pub encoding: String,
+    pub text: String,
+}
+
+/// XMP packet data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct XmpData {
+    pub packet: String,
+}
+
+impl XmpData {
+    /// Simple RDF properties of the packet, see
+    /// [`crate::metadata::xmp_properties`].
+    pub fn properties(&self) -> BTreeMap<String, String> {
+        crate::metadata::xmp_properties(&self.packet)
+    }
+}
+
+/// PIFF TfxdBox extended type.
+pub const PIFF_TFXD_UUID: [u8; 16] = [
+    0x6D, 0x1D, 0x9B, 0x05, 0x42, 0xD5, 0x44, 0xE6, 0x80, 0xE2, 0x14, 0x1D, 0xAF, 0xF7, 0x57, 0xB2,
+];
+/// PIFF TfrfBox extended type.
+pub const PIFF_TFRF_UUID: [u8; 16] = [
+    0xD4, 0x80, 0x7E, 0xF2, 0xCA, 0x39, 0x46, 0x95, 0x8E, 0x54, 0x26, 0xCB, 0x9E, 0x46, 0xA7, 0x9F,
+];
+/// PIFF SampleEncryptionBox extended type.
+pub const PIFF_SENC_UUID: [u8; 16] = [
+    0xA2, 0x39, 0x4F, 0x52, 0x5A, 0x9B, 0x4F, 0x14, 0xA2, 0x44, 0x6C, 0x42, 0x7C, 0x64, 0x8D, 0xF4,
+];
+
+/// PIFF TfxdBox data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PiffTfxdData {
+    pub version: u8,
+    /// In the track timescale
+    pub absolute_time: u64,
+    pub duration: u64,
+}
+
+/// PIFF TfrfBox data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PiffTfrfData {
+    pub version: u8,
+    pub fragments: Vec<PiffFragmentEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PiffFragmentEntry {
+    pub absolute_time: u64,
+    pub duration: u64,
+}
+
+/// PIFF SampleEncryptionBox data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PiffSencData {
+    pub version: u8,
+    pub flags: u32,
+    /// Present when flags & 1 (override TrackEncryptionBox parameters)
+    pub algorithm_id: Option<u32>,
+    /// Per-sample IV size; guessed (8 or 16) when not given in the box
+    pub iv_size: Option<u8>,
+    /// Key ID as hex, present when flags & 1
+    pub kid: Option<String>,
+    pub sample_count: u32,
+    pub samples: Vec<EncryptedSample>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EncryptedSample {
+    /// Initialization vector as hex
+    pub iv: String,
+    /// Present when flags & 2
+    pub subsamples: Vec<SubsampleEncryption>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SubsampleEncryption {
+    pub clear_bytes: u16,
+    pub protected_bytes: u32,
+}
+
+/// MHADecoderConfigurationRecord
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MhacData {
+    pub configuration_version: u8,
+    pub profile_level_indication: u8,
+    /// ISO/IEC 23001-8 ChannelConfiguration value
+    pub reference_channel_layout: u8,
+    /// Declared size of the mpegh3daConfig() payload in bytes
+    pub mpegh3da_config_length: u16,
+}
+
+/// DTSSpecificBox data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DdtsData {
+    pub sampling_frequency: u32,
+    pub max_bitrate: u32,
+    pub avg_bitrate: u32,
+    pub pcm_sample_depth: u8,
+    /// 0-3, for 512, 1024, 2048 or 4096 samples per frame
+    pub frame_duration_code: u8,
+    pub frame_duration: u32,
+    /// Which DTS components the stream carries (core, XLL, LBR, ...)
+    pub stream_construction: u8,
+    pub core_lfe_present: bool,
+    pub core_layout: u8,
+    pub core_size: u16,
+    pub stereo_downmix: bool,
+    pub representation_type: u8,
+    /// Speaker activity mask
+    pub channel_layout: u16,
+    pub multi_asset: bool,
+    pub lbr_duration_mod: bool,
+}
+
+/// AC4SpecificBox data (ac4_dsi_v1)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Dac4Data {
+    pub ac4_dsi_version: u8,
+    pub bitstream_version: u8,
+    pub fs_index: u8,
+    /// 44100 or 48000
+    pub sampling_rate: u32,
+    pub frame_rate_index: u8,
+    pub n_presentations: u16,
+    pub short_program_id: Option<u16>,
+    /// Hex
+    pub program_uuid: Option<String>,
+    pub bit_rate_mode: u8,
+    pub bit_rate: u32,
+    pub bit_rate_precision: u32,
+    pub presentations: Vec<Ac4Presentation>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Ac4Presentation {
+    pub presentation_version: u8,
+    /// None for presentation versions this decoder does not interpret
+    pub presentation_config: Option<u8>,
+    /// Decoder compatibility level (0-7)
+    pub mdcompat: Option<u8>,
+    pub presentation_id: Option<u8>,
+    /// 24-bit speaker group mask; absent for object-based presentations
+    pub channel_mask: Option<u32>,
+}
+
+/// Component Definition Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CmpdData {
+    pub components: Vec<ComponentDefinition>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ComponentDefinition {
+    pub component_type: u16,
+    /// e.g. "Y", "Cb", "R", "alpha"; "user-defined" for types >= 0x8000
+    pub component_type_name: String,
+    /// Present for user-defined types
+    pub component_type_uri: Option<String>,
+}
+
+/// Uncompressed Frame Configuration Box data
+///
+/// Version 1 carries only the profile; the other fields keep their
+/// defaults.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct UncCData {
+    pub version: u8,
+    pub profile: String,
+    pub components: Vec<UncCComponent>,
+    /// 0 = 4:4:4, 1 = 4:2:2, 2 = 4:2:0, 3 = 4:1:1
+    pub sampling_type: u8,
+    pub sampling_type_name: String,
+    /// 0 = component, 1 = pixel, 2 = mixed, 3 = row, 4 = tile-component, 5 = multi-Y
+    pub interleave_type: u8,
+    pub interleave_type_name: String,
+    pub block_size: u8,
+    pub components_little_endian: bool,
+    pub block_pad_lsb: bool,
+    pub block_little_endian: bool,
+    pub block_reversed: bool,
+    pub pad_unknown: bool,
+    pub pixel_size: u32,
+    pub row_align_size: u32,
+    pub tile_align_size: u32,
+    pub num_tile_cols: u32,
+    pub num_tile_rows: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UncCComponent {
+    /// Index into the cmpd component list
+    pub component_index: u16,
+    pub bit_depth: u16,
+    /// 0 = unsigned int, 1 = float, 2 = complex
+    pub component_format: u8,
+    pub component_align_size: u8,
+}
+
+/// LoudnessBaseBox data (tlou / alou)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LoudnessData {
+    pub version: u8,
+    /// "tlou" (track) or "alou" (album)
+    pub loudness_type: String,
+    pub entries: Vec<LoudnessInfo>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LoudnessInfo {
+    /// Version 1 and later only
+    pub eq_set_id: Option<u8>,
+    pub downmix_id: u8,
+    pub drc_set_id: u8,
+    /// dBFS; None when not signalled
+    pub sample_peak_level: Option<f64>,
+    /// dBTP; None when not signalled
+    pub true_peak_level: Option<f64>,
+    pub measurement_system_for_tp: u8,
+    pub reliability_for_tp: u8,
+    pub measurements: Vec<LoudnessMeasurement>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LoudnessMeasurement {
+    /// 1 = program loudness, 2 = anchor loudness, 5 = loudness range, ...
+    pub method_definition: u8,
+    pub method_value: u8,
+    /// method_value in the method's unit (LKFS, LU or dB); None for
+    /// methods without a numeric scale
+    pub value: Option<f64>,
+    /// 1 = EBU R128, 2 = ITU-R BS.1770-4, ...
+    pub measurement_system: u8,
+    /// 0 = unknown, 1 = unverified, 2 = ceiling, 3 = accurate
+    pub reliability: u8,
+}
+
+/// QuickTime international text atom: one string per language
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UserDataTextData {
+    pub entries: Vec<UserDataText>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UserDataText {
+    /// ISO 639-2/T code, when the atom uses a packed ISO language code
+    pub language: Option<String>,
+    /// Macintosh language code (0 = English) otherwise
+    pub mac_language: Option<u16>,
+    pub text: String,
+}
+
+/// Sample To Group Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SbgpData {
+    pub version: u8,
+    pub flags: u32,
+    pub grouping_type: String,
+    /// Version 1 only
+    pub grouping_type_parameter: Option<u32>,
+    pub entries: Vec<SbgpEntry>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SbgpEntry {
+    pub sample_count: u32,
+    /// 1-based index into the matching sgpd; 0 = not in any group. In a
+    /// traf, values above 0x10000 refer to the traf's own sgpd.
+    pub group_description_index: u32,
+}
+
+/// Sample Group Description Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SgpdData {
+    pub version: u8,
+    pub flags: u32,
+    pub grouping_type: String,
+    /// Group of samples not mapped by any sbgp (version 2 and later)
+    pub default_sample_description_index: Option<u32>,
+    pub entries: Vec<SampleGroupEntry>,
+}
+
+/// One sgpd entry, decoded for the common grouping types.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SampleGroupEntry {
+    /// 'roll' / 'prol': pre-roll or post-roll distance in samples
+    Roll { roll_distance: i16 },
+    /// 'rap ': open-GOP random access point
+    RandomAccessPoint {
+        num_leading_samples_known: bool,
+        num_leading_samples: u8,
+    },
+    /// 'sync': NAL unit type of sync samples
+    Sync { nal_unit_type: u8 },
+    /// 'seig': CENC key and IV settings
+    Encryption(SeigEntry),
+    /// Any other grouping type, as hex
+    Raw { data: String },
+}
+
+/// CENC sample encryption information group entry ('seig').
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SeigEntry {
+    pub crypt_byte_block: u8,
+    pub skip_byte_block: u8,
+    pub is_protected: bool,
+    pub per_sample_iv_size: u8,
+    /// Key ID, hex
+    pub kid: String,
+    /// Hex; only when protected with per_sample_iv_size 0
+    pub constant_iv: Option<String>,
+}
+
+/// Track Encryption Box data
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TencData {
+    pub version: u8,
+    pub flags: u32,
+    /// Track-wide key and IV settings; same layout as a 'seig' entry
+    pub defaults: SeigEntry,
+}
+
+/// Trait for custom box decoders.
+///
+/// A decoder is responsible for interpreting the payload of a specific box
+/// (identified by a [`BoxKey`]) and returning a [`BoxValue`].
+pub trait BoxDecoder: Send + Sync {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue>;
+}
+
+/// Registry of decoders keyed by `BoxKey` (4CC or UUID).
+///
+/// The registry is immutable once constructed; use [`Registry::with_decoder`]
+/// to build it fluently. A key can have several decoders, tried in priority
+/// order until one succeeds ([`Registry::with_decoder_priority`]), and a
+/// catch-all decoder can take every key without one
+/// ([`Registry::with_catch_all`]). [`Registry::with_cache`] makes repeated
+/// decodes of the same box free.
+pub struct Registry {
+    /// Highest priority first
+    map: HashMap<BoxKey, Vec<BoxDecoderEntry>>,
+    catch_all: Option<BoxDecoderEntry>,
+    cache: Option<Mutex<DecodeCache>>,
+}
+
+struct BoxDecoderEntry {
+    inner: Box<dyn BoxDecoder>,
+    _name: String,
+    priority: i32,
+}
+
+impl Registry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self {
+            map: HashMap::new(),
+            catch_all: None,
+            cache: None,
+        }
+    }
+
+    /// Return this registry with decoded values cached, keyed by box offset
+    /// and size, until the boxes cached add up to `budget` bytes; the oldest
+    /// values are dropped first. Decoding the same box again returns the
+    /// cached value (or error) without reading the payload.
+    ///
+    /// The key says nothing about which file a box came from: use one
+    /// caching registry per file, or [`clear_cache`](Self::clear_cache)
+    /// between files.
+    pub fn with_cache(mut self, budget: u64) -> Self {
+        self.cache = Some(Mutex::new(DecodeCache {
+            budget,
+            ..DecodeCache::default()
+        }));
+        self
+    }
+
+    /// Drop every cached value.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            let mut cache = cache.lock().unwrap();
+            cache.values.clear();
+            cache.order.clear();
+            cache.used = 0;
+        }
+    }
+
+    /// Return a new registry with the given decoder as the only one for
+    /// `key`, at priority 0, replacing any registered before.
+    ///
+    /// `name` is human-readable and used only for debugging / logging.
+    pub fn with_decoder(mut self, key: BoxKey, name: &str, dec: Box<dyn BoxDecoder>) -> Self {
+        self.map.remove(&key);
+        self.with_decoder_priority(key, name, 0, dec)
+    }
+
+    /// Return a new registry with the given decoder added next to those
+    /// already registered for `key`. Decoders are tried from the highest
+    /// `priority` down, in registration order for equal priorities, until
+    /// one succeeds; when all fail, the error is the first one's. A
+    /// structured decoder can so fall back to a generic one such as
+    /// [`GenericDecoder`].
+    pub fn with_decoder_priority(
+        mut self,
+        key: BoxKey,
+        name: &str,
+        priority: i32,
+        dec: Box<dyn BoxDecoder>,
+    ) -> Self {
+        let chain = self.map.entry(key).or_default();
+        let at = chain.partition_point(|d| d.priority >= priority);
+        chain.insert(
+            at,
+            BoxDecoderEntry {
+                inner: dec,
+                _name: name.to_string(),
+                priority,
+            },
+        );
+        self
+    }
+
+    /// Return a new registry that decodes boxes of every key without a
+    /// decoder of its own with `dec`, such as [`GenericDecoder`]. It is
+    /// handed whole payloads, mdat included when decoding a tree, so it
+    /// should only read what it needs.
+    pub fn with_catch_all(mut self, name: &str, dec: Box<dyn BoxDecoder>) -> Self {
+        self.catch_all = Some(BoxDecoderEntry {
+            inner: dec,
+            _name: name.to_string(),
+            priority: 0,
+        });
+        self
+    }
+
+    /// Try to decode the payload of a box using a registered decoder.
+    ///
+    /// Returns `None` if no decoder exists for the given key and there is
+    /// no catch-all decoder.
+    pub fn decode(
+        &self,
+        key: &BoxKey,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> Option<anyhow::Result<BoxValue>> {
+        let chain = match self.map.get(key) {
+            Some(chain) => chain.as_slice(),
+            None => std::slice::from_ref(self.catch_all.as_ref()?),
+        };
+        let Some(cache) = &self.cache else {
+            return Some(decode_chain(chain, r, hdr, version, flags));
+        };
+        let at = (hdr.start, hdr.size);
+        if let Some(hit) = cache.lock().unwrap().values.get(&at) {
+            return Some(hit.clone().map_err(anyhow::Error::msg));
+        }
+        let value = decode_chain(chain, r, hdr, version, flags);
+        let kept = match &value {
+            Ok(v) => Ok(v.clone()),
+            Err(e) => Err(e.to_string()),
+        };
+        cache.lock().unwrap().insert(at, kept);
+        Some(value)
+    }
+}
+
+/// Run `chain` until a decoder succeeds. A lone decoder reads `r` directly;
+/// several share a copy of the payload.
+fn decode_chain(
+    chain: &[BoxDecoderEntry],
+    r: &mut dyn Read,
+    hdr: &BoxHeader,
+    version: Option<u8>,
+    flags: Option<u32>,
+) -> anyhow::Result<BoxValue> {
+    if let [only] = chain {
+        return only.inner.decode(r, hdr, version, flags);
+    }
+    let payload = read_all(r)?;
+    let mut first = None;
+    for d in chain {
+        match d
+            .inner
+            .decode(&mut Cursor::new(&payload), hdr, version, flags)
+        {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                first.get_or_insert(e);
+            }
+        }
+    }
+    Err(first.unwrap_or_else(|| anyhow::anyhow!("no decoder")))
+}
+
+/// Decoded values by (offset, size) of their box, oldest first in `order`.
+#[derive(Default)]
+struct DecodeCache {
+    budget: u64,
+    used: u64,
+    values: HashMap<(u64, u64), Result<BoxValue, String>>,
+    order: VecDeque<(u64, u64)>,
+}
+
+impl DecodeCache {
+    /// Keep `value`, counting the box size against the budget.
+    fn insert(&mut self, at: (u64, u64), value: Result<BoxValue, String>) {
+        let size = at.1;
+        if size > self.budget {
+            return;
+        }
+        while self.used + size > self.budget {
+            let Some(old) = self.order.pop_front() else {
+                break;
+            };
+            self.values.remove(&old);
+            self.used -= old.1;
+        }
+        if self.values.insert(at, value).is_none() {
+            self.order.push_back(at);
+            self.used += size;
+        }
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ---------- Helpers ----------
+
+fn read_all(r: &mut dyn Read) -> anyhow::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Read a big-endian unsigned integer of `size` bytes (0 reads nothing).
+fn read_uint(r: &mut dyn Read, size: u8) -> anyhow::Result<u64> {
+    match size {
+        0 => Ok(0),
+        1..=8 => Ok(r.read_uint::<BigEndian>(size as usize)?),
+        n => anyhow::bail!("unsupported field size {}", n),
+    }
+}
+
+/// Most zero-size entries (a trun without per-sample fields) a decoder
+/// builds: no payload bytes bound their count.
+const MAX_EMPTY_ENTRIES: u32 = 1 << 20;
+
+/// How many of `declared` fixed-size entries the rest of the payload can
+/// hold, so a hostile count cannot drive a decoder past its data.
+/// Zero-size entries fail past [`MAX_EMPTY_ENTRIES`].
+fn entries_that_fit(
+    declared: u32,
+    cur: &Cursor<&Vec<u8>>,
+    entry_size: usize,
+) -> anyhow::Result<u32> {
+    if entry_size == 0 {
+        anyhow::ensure!(
+            declared <= MAX_EMPTY_ENTRIES,
+            "{declared} entries without fields (limit {MAX_EMPTY_ENTRIES})"
+        );
+        return Ok(declared);
+    }
+    let remaining = cur.get_ref().len().saturating_sub(cur.position() as usize);
+    Ok(declared.min(u32::try_from(remaining / entry_size).unwrap_or(u32::MAX)))
+}
+
+/// 28-bit "syncsafe" integer as used by ID3v2 sizes.
+fn syncsafe(b: &[u8]) -> u32 {
+    b.iter().fold(0, |acc, &x| (acc << 7) | (x & 0x7F) as u32)
+}
+
+/// Decode an ID3v2 text frame body: an encoding byte followed by one or
+/// more null-separated strings.
+fn id3_strings(body: &[u8]) -> Vec<String> {
+    let Some((&encoding, text)) = body.split_first() else {
+        return Vec::new();
+    };
+    let utf16 = |bytes: &[u8], be: bool| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|c| {
+                if be {
+                    u16::from_be_bytes([c[0], c[1]])
+                } else {
+                    u16::from_le_bytes([c[0], c[1]])
+                }
+            })
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    let s = match (encoding, text) {
+        (0, _) => text.iter().map(|&b| b as char).collect(),
+        (1, [0xFF, 0xFE, rest @ ..]) => utf16(rest, false),
+        (1, [0xFE, 0xFF, rest @ ..]) | (1 | 2, rest) => utf16(rest, true),
+        _ => String::from_utf8_lossy(text).to_string(),
+    };
+    s.trim_end_matches('\0')
+        .split('\0')
+        .map(|v| v.trim_start_matches('\u{feff}').to_string())
+        .collect()
+}
+
+fn lang_from_u16(code: u16) -> String {
+    if code == 0 {
+        return "und".to_string();
+    }
+    let c1 = ((code >> 10) & 0x1F) as u8 + 0x60;
+    let c2 = ((code >> 5) & 0x1F) as u8 + 0x60;
+    let c3 = (code & 0x1F) as u8 + 0x60;
+    format!("{}{}{}", c1 as char, c2 as char, c3 as char,)
+}
+
+/// MSB-first bit reader over a byte slice.
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Read `n` (at most 32) bits.
+    pub(crate) fn read(&mut self, n: u32) -> anyhow::Result<u32> {
+        let mut v = 0u32;
+        for _ in 0..n {
+            let Some(byte) = self.data.get(self.pos / 8) else {
+                anyhow::bail!("bitstream ended after {} bits", self.pos);
+            };
+            v = (v << 1) | ((byte >> (7 - self.pos % 8)) & 1) as u32;
+            self.pos += 1;
+        }
+        Ok(v)
+    }
+
+    fn read_bool(&mut self) -> anyhow::Result<bool> {
+        Ok(self.read(1)? == 1)
+    }
+
+    fn skip(&mut self, n: usize) {
+        self.pos += n;
+    }
+
+    fn byte_align(&mut self) {
+        self.pos = self.pos.next_multiple_of(8);
+    }
+
+    fn byte_pos(&self) -> usize {
+        self.pos / 8
+    }
+}
+
+/// Iterate the (type, payload) pairs of a run of plain child boxes.
+fn child_boxes(mut buf: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> {
+    std::iter::from_fn(move || {
+        if buf.len() < 8 {
+            return None;
+        }
+        let size = u32::from_be_bytes([buf[0], buf[1], buf[2], buf[3]]) as usize;
+        if size < 8 || size > buf.len() {
+            return None;
+        }
+        let typ = [buf[4], buf[5], buf[6], buf[7]];
+        let payload = &buf[8..size];
+        buf = &buf[size..];
+        Some((typ, payload))
+    })
+}
+
+/// Parse the body of a subtitle sample entry, starting after its 8-byte
+/// SampleEntry header (reserved + data_reference_index).
+fn subtitle_config(codec: &str, body: &[u8]) -> Option<SubtitleConfig> {
+    match codec {
+        "tx3g" => {
+            if body.len() < 30 {
+                return None;
+            }
+            let be16 = |i: usize| u16::from_be_bytes([body[i], body[i + 1]]);
+            let rgba = |i: usize| [body[i], body[i + 1], body[i + 2], body[i + 3]];
+            let fonts = child_boxes(&body[30..])
+                .find(|(typ, _)| typ == b"ftab")
+                .map(|(_, ftab)| {
+                    let mut fonts = Vec::new();
+                    let mut pos = 2;
+                    while pos + 3 <= ftab.len() {
+                        let font_id = u16::from_be_bytes([ftab[pos], ftab[pos + 1]]);
+                        let len = ftab[pos + 2] as usize;
+                        let name = ftab.get(pos + 3..pos + 3 + len).unwrap_or_default();
+                        fonts.push(FontRecord {
+                            font_id,
+                            name: String::from_utf8_lossy(name).to_string(),
+                        });
+                        pos += 3 + len;
+                    }
+                    fonts
+                })
+                .unwrap_or_default();
+            Some(SubtitleConfig::TimedText(Tx3gConfig {
+                display_flags: u32::from_be_bytes(rgba(0)),
+                horizontal_justification: body[4] as i8,
+                vertical_justification: body[5] as i8,
+                background_color: rgba(6),
+                default_text_box: TextBox {
+                    top: be16(10) as i16,
+                    left: be16(12) as i16,
+                    bottom: be16(14) as i16,
+                    right: be16(16) as i16,
+                },
+                default_style: TextStyle {
+                    start_char: be16(18),
+                    end_char: be16(20),
+                    font_id: be16(22),
+                    face_style_flags: body[24],
+                    font_size: body[25],
+                    text_color: rgba(26),
+                },
+                fonts,
+            }))
+        }
+        "wvtt" => {
+            let text = |p: &[u8]| {
+                String::from_utf8_lossy(p)
+                    .trim_end_matches('\0')
+                    .to_string()
+            };
+            let mut config = WebVttConfig {
+                config: None,
+                label: None,
+            };
+            for (typ, payload) in child_boxes(body) {
+                match &typ {
+                    b"vttC" => config.config = Some(text(payload)),
+                    b"vlab" => config.label = Some(text(payload)),
+                    _ => {}
+                }
+            }
+            Some(SubtitleConfig::WebVtt(config))
+        }
+        "stpp" => {
+            // Three null-terminated strings; the last two may be empty
+            let mut strings = body
+                .split(|&b| b == 0)
+                .map(|s| String::from_utf8_lossy(s).to_string());
+            Some(SubtitleConfig::Xml(XmlSubtitleConfig {
+                namespace: strings.next().unwrap_or_default(),
+                schema_location: strings.next().unwrap_or_default(),
+                auxiliary_mime_types: strings.next().unwrap_or_default(),
+            }))
+        }
+        _ => None,
+    }
+}
+
+// ---------- Decoders ----------
+
+// ftyp/styp: major + minor + compatible brands
+pub struct FtypDecoder;
+
+impl BoxDecoder for FtypDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        if buf.len() < 8 {
+            return Ok(BoxValue::Text(format!(
+                "ftyp: payload too short ({} bytes)",
+                buf.len()
+            )));
+        }
+
+        let major_brand = String::from_utf8_lossy(&buf[0..4]).to_string();
+        let minor_version = u32::from_be_bytes(buf[4..8].try_into().unwrap());
+        let compatible_brands = buf[8..]
+            .chunks_exact(4)
+            .map(|c| String::from_utf8_lossy(c).to_string())
+            .collect();
+
+        Ok(BoxValue::Structured(StructuredData::FileType(FtypData {
+            major_brand,
+            minor_version,
+            compatible_brands,
+        })))
+    }
+}
+
+// mvhd: timescale + duration
+pub struct MvhdDecoder;
+
+impl BoxDecoder for MvhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let size = if version == 1 { 8 } else { 4 };
+        let creation_time = read_uint(r, size)?;
+        let modification_time = read_uint(r, size)?;
+        let timescale = r.read_u32::<BigEndian>()?;
+        let duration = read_uint(r, size)?;
+        let rate = r.read_i32::<BigEndian>()? as f32 / 65536.0;
+        let volume = r.read_i16::<BigEndian>()? as f32 / 256.0;
+        // reserved (2 + 8 bytes), matrix, pre_defined
+        let mut skip = [0u8; 10 + 36 + 24];
+        r.read_exact(&mut skip)?;
+        let next_track_id = r.read_u32::<BigEndian>()?;
+
+        let data = MvhdData {
+            version,
+            flags: flags.unwrap_or(0),
+            creation_time,
+            modification_time,
+            creation_date: crate::util::mp4_time_to_rfc3339(creation_time),
+            modification_date: crate::util::mp4_time_to_rfc3339(modification_time),
+            timescale,
+            duration,
+            rate,
+            volume,
+            next_track_id,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::MovieHeader(data)))
+    }
+}
+
+// tkhd: track id, duration, alternate group, width, height
+pub struct TkhdDecoder;
+
+impl BoxDecoder for TkhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        // For FullBox types, version and flags are already parsed by the main parser
+        let buf = read_all(r)?;
+        let version = version.unwrap_or(0);
+        let mut cur = Cursor::new(&buf);
+
+        // creation_time, modification_time, track_id, reserved, duration;
+        // the times and duration are 64-bit in version 1
+        let size = if version == 1 { 8 } else { 4 };
+        let (Ok(creation_time), Ok(modification_time), Ok(track_id)) = (
+            read_uint(&mut cur, size),
+            read_uint(&mut cur, size),
+            cur.read_u32::<BigEndian>(),
+        ) else {
+            return Ok(BoxValue::Text(format!(
+                "tkhd: payload too short ({} bytes)",
+                buf.len()
+            )));
+        };
+        // Fields past the end of a short payload keep their defaults
+        let _reserved = cur.read_u32::<BigEndian>();
+        let duration = read_uint(&mut cur, size).unwrap_or(0);
+
+        // reserved[2], layer
+        cur.set_position(cur.position() + 10);
+        let alternate_group = cur.read_u16::<BigEndian>().unwrap_or(0);
+        // volume, reserved
+        cur.set_position(cur.position() + 4);
+        let matrix = if cur.position() + 36 <= buf.len() as u64 {
+            let mut matrix = [0i32; 9];
+            for v in &mut matrix {
+                *v = cur.read_i32::<BigEndian>()?;
+            }
+            matrix
+        } else {
+            cur.set_position(buf.len() as u64);
+            // Unity matrix
+            [0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x4000_0000]
+        };
+
+        let width = cur.read_u32::<BigEndian>().unwrap_or(0) as f32 / 65536.0;
+        let height = cur.read_u32::<BigEndian>().unwrap_or(0) as f32 / 65536.0;
+
+        let data = TkhdData {
+            version,
+            flags: flags.unwrap_or(0),
+            creation_time,
+            modification_time,
+            creation_date: crate::util::mp4_time_to_rfc3339(creation_time),
+            modification_date: crate::util::mp4_time_to_rfc3339(modification_time),
+            track_id,
+            duration,
+            alternate_group,
+            matrix,
+            width,
+            height,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::TrackHeader(data)))
+    }
+}
+
+// mdhd: timescale, duration, language
+pub struct MdhdDecoder;
+
+impl BoxDecoder for MdhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let size = if version == 1 { 8 } else { 4 };
+        let creation_time = read_uint(r, size)?;
+        let modification_time = read_uint(r, size)?;
+        let timescale = r.read_u32::<BigEndian>()?;
+        let duration = read_uint(r, size)?;
+        let language_code = r.read_u16::<BigEndian>()?;
+        let _pre_defined = r.read_u16::<BigEndian>()?;
+
+        let lang = lang_from_u16(language_code);
+
+        let data = MdhdData {
+            version,
+            flags: flags.unwrap_or(0),
+            creation_time,
+            modification_time,
+            creation_date: crate::util::mp4_time_to_rfc3339(creation_time),
+            modification_date: crate::util::mp4_time_to_rfc3339(modification_time),
+            timescale,
+            duration,
+            language_tag: crate::language::track_language_tag(&lang, None),
+            language: lang,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::MediaHeader(data)))
+    }
+}
+
+// hdlr: handler type + name
+pub struct HdlrDecoder;
+
+impl BoxDecoder for HdlrDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        use byteorder::{BigEndian, ReadBytesExt};
+
+        // pre_defined (4 bytes) + handler_type (4 bytes)
+        let _pre_defined = r.read_u32::<BigEndian>()?;
+        let mut handler_type = [0u8; 4];
+        r.read_exact(&mut handler_type)?;
+
+        // reserved (3 * 4 bytes)
+        let mut reserved = [0u8; 12];
+        r.read_exact(&mut reserved)?;
+
+        // name: null-terminated string (or just rest of box)
+        let mut name_bytes = Vec::new();
+        r.read_to_end(&mut name_bytes)?;
+        // strip trailing nulls
+        while name_bytes.last() == Some(&0) {
+            name_bytes.pop();
+        }
+        let name = String::from_utf8_lossy(&name_bytes).to_string();
+
+        let handler_str = std::str::from_utf8(&handler_type).unwrap_or("????");
+
+        let data = HdlrData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            handler_type: handler_str.to_string(),
+            name,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::HandlerReference(data)))
+    }
+}
+
+// sidx: segment index with its subsegment references
+pub struct SidxDecoder;
+
+impl BoxDecoder for SidxDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let size = if version == 1 { 8 } else { 4 };
+
+        let reference_id = r.read_u32::<BigEndian>()?;
+        let timescale = r.read_u32::<BigEndian>()?;
+        let earliest_presentation_time = read_uint(r, size)?;
+        let first_offset = read_uint(r, size)?;
+        let _reserved = r.read_u16::<BigEndian>()?;
+        let reference_count = r.read_u16::<BigEndian>()?;
+
+        let mut references = Vec::with_capacity(reference_count as usize);
+        for _ in 0..reference_count {
+            let a = r.read_u32::<BigEndian>()?;
+            let subsegment_duration = r.read_u32::<BigEndian>()?;
+            let c = r.read_u32::<BigEndian>()?;
+            references.push(SidxReference {
+                references_sidx: a >> 31 != 0,
+                referenced_size: a & 0x7FFF_FFFF,
+                subsegment_duration,
+                starts_with_sap: c >> 31 != 0,
+                sap_type: ((c >> 28) & 0x7) as u8,
+                sap_delta_time: c & 0x0FFF_FFFF,
+            });
+        }
+
+        let data = SidxData {
+            version,
+            flags: flags.unwrap_or(0),
+            reference_id,
+            timescale,
+            earliest_presentation_time,
+            first_offset,
+            references,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SegmentIndex(data)))
+    }
+}
+
+// stsd: every sample entry's format, data reference and basic fields
+pub struct StsdDecoder;
+
+impl StsdDecoder {
+    const SUBTITLE_CODECS: [&'static str; 3] = ["tx3g", "wvtt", "stpp"];
+
+    /// Decode one sample entry from its box size, format and body (the
+    /// bytes after the 8-byte box header).
+    fn entry(size: u32, format: FourCC, body: &[u8]) -> SampleEntry {
+        let be16 = |i: usize| body.get(i..i + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+        let be32 = |i: usize| {
+            body.get(i..i + 4)
+                .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        };
+
+        let mut entry = SampleEntry {
+            size,
+            data_reference_index: be16(6).unwrap_or(0),
+            width: None,
+            height: None,
+            channel_count: None,
+            sample_size: None,
+            sample_rate: None,
+            subtitle: None,
+            codec: format.to_string(),
+        };
+        let known = KnownBox::from(format);
+        let codec = entry.codec.as_str();
+        if known.is_visual_sample_entry() {
+            // VisualSampleEntry: 6 reserved, u16 data_reference_index, 16
+            // bytes pre_defined/reserved, u16 width, u16 height
+            entry.width = be16(24);
+            entry.height = be16(26);
+        } else if known.is_audio_sample_entry() {
+            // AudioSampleEntry: 6 reserved, u16 data_reference_index, 8
+            // reserved, u16 channelcount, u16 samplesize, u32
+            // pre_defined/reserved, u32 samplerate (16.16)
+            entry.channel_count = be16(16);
+            entry.sample_size = be16(18);
+            entry.sample_rate = be32(24).map(|v| v >> 16);
+
+            // QuickTime sound description v2 moves the real values after
+            // the v0 fields: f64 sample rate at 32, u32 channels at 40,
+            // u32 bits per channel at 48
+            if be16(8) == Some(2) {
+                let rate = body
+                    .get(32..40)
+                    .map(|b| f64::from_be_bytes(b.try_into().unwrap()));
+                entry.sample_rate = rate.map(|r| r as u32).or(entry.sample_rate);
+                entry.channel_count = be32(40).map(|c| c as u16).or(entry.channel_count);
+                entry.sample_size = be32(48).map(|b| b as u16).or(entry.sample_size);
+            }
+        } else if Self::SUBTITLE_CODECS.contains(&codec) {
+            entry.subtitle = subtitle_config(codec, body.get(8..).unwrap_or_default());
+        }
+        entry
+    }
+}
+
+impl BoxDecoder for StsdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        // stsd is a FullBox; our reader is already positioned at payload:
+        // u32 entry_count
+        // [ SampleEntry entries... ]
+        let entry_count = r.read_u32::<BigEndian>()?;
+        if entry_count == 0 {
+            return Ok(BoxValue::Text("entry_count=0".to_string()));
+        }
+        let buf = read_all(r)?;
+
+        let mut entries = Vec::new();
+        let mut pos = 0usize;
+        for _ in 0..entry_count {
+            let Some(header) = buf.get(pos..pos + 8) else {
+                break;
+            };
+            let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+            let format = FourCC([header[4], header[5], header[6], header[7]]);
+            // A size of 0 (or one past the end) runs to the end of the box
+            let end = if size < 8 {
+                buf.len()
+            } else {
+                (pos + size as usize).min(buf.len())
+            };
+            let declared = if size < 8 { (end - pos) as u32 } else { size };
+            entries.push(Self::entry(declared, format, &buf[pos + 8..end]));
+            pos = end;
+        }
+
+        let data = StsdData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            entries,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SampleDescription(
+            data,
+        )))
+    }
+}
+
+// stts: time-to-sample
+pub struct SttsDecoder;
+
+impl BoxDecoder for SttsDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        // and stripped from the payload. We start directly with the box-specific data.
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut entries = Vec::new();
+
+        for _ in 0..entries_that_fit(entry_count, &cur, 8)? {
+            let sample_count = cur.read_u32::<BigEndian>()?;
+            let sample_delta = cur.read_u32::<BigEndian>()?;
+            entries.push(SttsEntry {
+                sample_count,
+                sample_delta,
+            });
+        }
+
+        let data = SttsData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            entries,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::DecodingTimeToSample(
+            data,
+        )))
+    }
+}
+
+// stss: sync sample table
+pub struct StssDecoder;
+
+impl BoxDecoder for StssDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut sample_numbers = Vec::new();
+
+        for _ in 0..entries_that_fit(entry_count, &cur, 4)? {
+            sample_numbers.push(cur.read_u32::<BigEndian>()?);
+        }
+
+        let data = StssData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            sample_numbers,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SyncSample(data)))
+    }
+}
+
+// sdtp: sample dependency flags, one byte per sample up to the box end
+pub struct SdtpDecoder;
+
+impl BoxDecoder for SdtpDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let entries = read_all(r)?
+            .into_iter()
+            .map(|b| SdtpEntry {
+                is_leading: b >> 6,
+                sample_depends_on: (b >> 4) & 0x3,
+                sample_is_depended_on: (b >> 2) & 0x3,
+                sample_has_redundancy: b & 0x3,
+            })
+            .collect();
+
+        Ok(BoxValue::Structured(StructuredData::SampleDependencyType(
+            SdtpData {
+                version: version.unwrap_or(0),
+                flags: flags.unwrap_or(0),
+                entries,
+            },
+        )))
+    }
+}
+
+// ctts: composition time to sample
+pub struct CttsDecoder;
+
+impl BoxDecoder for CttsDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut entries = Vec::new();
+
+        for _ in 0..entries_that_fit(entry_count, &cur, 8)? {
+            let sample_count = cur.read_u32::<BigEndian>()?;
+            // Note: In version 1, sample_offset can be signed, but since we don't have access
+            // to the parsed version here, we assume version 0 behavior (unsigned)
+            let sample_offset = cur.read_u32::<BigEndian>()? as i32;
+            entries.push(CttsEntry {
+                sample_count,
+                sample_offset,
+            });
+        }
+
+        let data = CttsData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            entries,
+        };
+
+        Ok(BoxValue::Structured(
+            StructuredData::CompositionTimeToSample(data),
+        ))
+    }
+}
+
+// stsc: sample-to-chunk
+pub struct StscDecoder;
+
+impl BoxDecoder for StscDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut entries = Vec::new();
+
+        for _ in 0..entries_that_fit(entry_count, &cur, 12)? {
+            let first_chunk = cur.read_u32::<BigEndian>()?;
+            let samples_per_chunk = cur.read_u32::<BigEndian>()?;
+            let sample_description_index = cur.read_u32::<BigEndian>()?;
+            entries.push(StscEntry {
+                first_chunk,
+                samples_per_chunk,
+                sample_description_index,
+            });
+        }
+
+        let data = StscData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            entries,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SampleToChunk(data)))
+    }
+}
+
+// stsz: sample sizes
+pub struct StszDecoder;
+
+impl BoxDecoder for StszDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let sample_size = cur.read_u32::<BigEndian>()?;
+        let sample_count = cur.read_u32::<BigEndian>()?;
+        let mut sample_sizes = Vec::new();
+
+        // If sample_size is 0, each sample has its own size
+        if sample_size == 0 {
+            for _ in 0..entries_that_fit(sample_count, &cur, 4)? {
+                sample_sizes.push(cur.read_u32::<BigEndian>()?);
+            }
+        }
+
+        let data = StszData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            sample_size,
+            sample_count,
+            sample_sizes,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SampleSize(data)))
+    }
+}
+
+// stz2: compact sample sizes, expanded into the stsz table
+pub struct Stz2Decoder;
+
+impl BoxDecoder for Stz2Decoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // 24 reserved bits, then the field size in bits
+        cur.read_u24::<BigEndian>()?;
+        let field_size = cur.read_u8()?;
+        let sample_count = cur.read_u32::<BigEndian>()?;
+        let mut sample_sizes = Vec::new();
+        match field_size {
+            4 => {
+                for i in 0..sample_count {
+                    let byte = buf
+                        .get(8 + i as usize / 2)
+                        .copied()
+                        .ok_or_else(|| anyhow::anyhow!("stz2 truncated"))?;
+                    let size = if i % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+                    sample_sizes.push(size as u32);
+                }
+            }
+            8 | 16 => {
+                for _ in 0..sample_count {
+                    sample_sizes.push(read_uint(&mut cur, field_size / 8)? as u32);
+                }
+            }
+            _ => anyhow::bail!("stz2 field_size {} not supported", field_size),
+        }
+
+        let data = StszData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            sample_size: 0,
+            sample_count,
+            sample_sizes,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SampleSize(data)))
+    }
+}
+
+// stco: 32-bit chunk offsets
+pub struct StcoDecoder;
+
+impl BoxDecoder for StcoDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut chunk_offsets = Vec::new();
+
+        for _ in 0..entries_that_fit(entry_count, &cur, 4)? {
+            chunk_offsets.push(cur.read_u32::<BigEndian>()?);
+        }
+
+        let data = StcoData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            chunk_offsets,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::ChunkOffset(data)))
+    }
+}
+
+// co64: 64-bit chunk offsets
+pub struct Co64Decoder;
+
+impl BoxDecoder for Co64Decoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+
+        // For FullBox types, version and flags are already parsed by the main parser
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let mut chunk_offsets = Vec::new();
+
+        for _ in 0..entries_that_fit(entry_count, &cur, 8)? {
+            chunk_offsets.push(cur.read_u64::<BigEndian>()?);
+        }
+
+        let data = Co64Data {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entry_count,
+            chunk_offsets,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::ChunkOffset64(data)))
+    }
+}
+
+// elst: edit list
+pub struct ElstDecoder;
+
+impl BoxDecoder for ElstDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+        let entry_count = cur.read_u32::<BigEndian>()?;
+        let entry_size = if version == 1 { 20 } else { 12 };
+        let mut entries = Vec::new();
+        for _ in 0..entries_that_fit(entry_count, &cur, entry_size)? {
+            let (segment_duration, media_time) = if version == 1 {
+                (cur.read_u64::<BigEndian>()?, cur.read_i64::<BigEndian>()?)
+            } else {
+                (
+                    cur.read_u32::<BigEndian>()? as u64,
+                    cur.read_i32::<BigEndian>()? as i64,
+                )
+            };
+            let media_rate = cur.read_i32::<BigEndian>()? as f32 / 65536.0;
+            entries.push(ElstEntry {
+                segment_duration,
+                media_time,
+                media_rate,
+            });
+        }
+
+        Ok(BoxValue::Structured(StructuredData::EditList(ElstData {
+            version,
+            flags: flags.unwrap_or(0),
+            entries,
+        })))
+    }
+}
+
+// chnl: channel layout
+pub struct ChnlDecoder;
+
+impl BoxDecoder for ChnlDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+        let version = version.unwrap_or(0);
+        let remaining = |cur: &Cursor<&Vec<u8>>| buf.len() as u64 - cur.position();
+
+        let (stream_structure, format_ordering, base_channel_count) = if version >= 1 {
+            let b = cur.read_u8()?;
+            let base = cur.read_u8()?;
+            (b >> 4, Some(b & 0x0F), Some(base))
+        } else {
+            (cur.read_u8()?, None, None)
+        };
+
+        let mut defined_layout = None;
+        let mut speaker_positions = Vec::new();
+        let mut omitted_channels_map = None;
+
+        if stream_structure & 1 != 0 {
+            let layout = cur.read_u8()?;
+            defined_layout = Some(layout);
+            if layout == 0 {
+                // v0 has no explicit count: positions run to the end of the box
+                // (minus the trailing object_count when present).
+                let count = if version >= 1 {
+                    Some(cur.read_u8()? as usize)
+                } else {
+                    None
+                };
+                let reserve = if stream_structure & 2 != 0 { 1 } else { 0 };
+                while count.is_none_or(|c| speaker_positions.len() < c) && remaining(&cur) > reserve
+                {
+                    let speaker_position = cur.read_u8()?;
+                    let (azimuth, elevation) = if speaker_position == 126 {
+                        (Some(cur.read_i16::<BigEndian>()?), Some(cur.read_i8()?))
+                    } else {
+                        (None, None)
+                    };
+                    speaker_positions.push(SpeakerPosition {
+                        speaker_position,
+                        azimuth,
+                        elevation,
+                    });
+                }
+            } else if version >= 1 {
+                let b = cur.read_u8()?;
+                if b & 1 != 0 {
+                    omitted_channels_map = Some(cur.read_u64::<BigEndian>()?);
+                }
+            } else {
+                omitted_channels_map = Some(cur.read_u64::<BigEndian>()?);
+            }
+        }
+
+        // Version 1 derives the object count from the sample entry instead.
+        let object_count = if stream_structure & 2 != 0 && version == 0 {
+            Some(cur.read_u8()?)
+        } else {
+            None
+        };
+
+        let data = ChnlData {
+            version,
+            flags: flags.unwrap_or(0),
+            stream_structure,
+            format_ordering,
+            base_channel_count,
+            defined_layout,
+            speaker_positions,
+            omitted_channels_map,
+            object_count,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::ChannelLayout(data)))
+    }
+}
+
+// pcmC: PCM format flags + sample size
+pub struct PcmCDecoder;
+
+impl BoxDecoder for PcmCDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let format_flags = r.read_u8()?;
+        let pcm_sample_size = r.read_u8()?;
+
+        let data = PcmCData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            format_flags,
+            little_endian: format_flags & 1 != 0,
+            pcm_sample_size,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::PcmConfig(data)))
+    }
+}
+
+// kind: role scheme + value (two null-terminated strings)
+pub struct KindDecoder;
+
+impl BoxDecoder for KindDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut parts = buf.split(|&b| b == 0);
+        let scheme_uri = String::from_utf8_lossy(parts.next().unwrap_or_default()).to_string();
+        let value = String::from_utf8_lossy(parts.next().unwrap_or_default()).to_string();
+
+        let data = KindData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            scheme_uri,
+            value,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::TrackKind(data)))
+    }
+}
+
+// elng: null-terminated BCP-47 language tag
+pub struct ElngDecoder;
+
+impl BoxDecoder for ElngDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let tag = buf.split(|&b| b == 0).next().unwrap_or_default();
+
+        let data = ElngData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            extended_language: String::from_utf8_lossy(tag).trim().to_string(),
+        };
+
+        Ok(BoxValue::Structured(StructuredData::ExtendedLanguage(data)))
+    }
+}
+
+// trex: per-track fragment defaults
+pub struct TrexDecoder;
+
+impl BoxDecoder for TrexDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let track_id = r.read_u32::<BigEndian>()?;
+        let default_sample_description_index = r.read_u32::<BigEndian>()?;
+        let default_sample_duration = r.read_u32::<BigEndian>()?;
+        let default_sample_size = r.read_u32::<BigEndian>()?;
+        let default_sample_flags = r.read_u32::<BigEndian>()?;
+
+        let data = TrexData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            track_id,
+            default_sample_description_index,
+            default_sample_duration,
+            default_sample_size,
+            default_sample_flags,
+            default_sample_flags_decoded: SampleFlags::from_bits(default_sample_flags),
+        };
+
+        Ok(BoxValue::Structured(StructuredData::TrackExtends(data)))
+    }
+}
+
+// mfhd: movie fragment sequence number
+pub struct MfhdDecoder;
+
+impl BoxDecoder for MfhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let data = MfhdData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            sequence_number: r.read_u32::<BigEndian>()?,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::MovieFragmentHeader(
+            data,
+        )))
+    }
+}
+
+// tfhd: track fragment header, optional fields driven by tf_flags
+pub struct TfhdDecoder;
+
+impl BoxDecoder for TfhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let flags = flags.unwrap_or(0);
+        let f = TfhdFlags::from_bits(flags);
+
+        let track_id = r.read_u32::<BigEndian>()?;
+        let base_data_offset = if f.base_data_offset_present {
+            Some(r.read_u64::<BigEndian>()?)
+        } else {
+            None
+        };
+        let sample_description_index = if f.sample_description_index_present {
+            Some(r.read_u32::<BigEndian>()?)
+        } else {
+            None
+        };
+        let default_sample_duration = if f.default_sample_duration_present {
+            Some(r.read_u32::<BigEndian>()?)
+        } else {
+            None
+        };
+        let default_sample_size = if f.default_sample_size_present {
+            Some(r.read_u32::<BigEndian>()?)
+        } else {
+            None
+        };
+        let default_sample_flags = if f.default_sample_flags_present {
+            Some(r.read_u32::<BigEndian>()?)
+        } else {
+            None
+        };
+
+        let data = TfhdData {
+            version: version.unwrap_or(0),
+            flags,
+            decoded_flags: f,
+            track_id,
+            base_data_offset,
+            sample_description_index,
+            default_sample_duration,
+            default_sample_size,
+            default_sample_flags,
+            default_sample_flags_decoded: default_sample_flags.map(SampleFlags::from_bits),
+        };
+
+        Ok(BoxValue::Structured(StructuredData::TrackFragmentHeader(
+            data,
+        )))
+    }
+}
+
+// tfdt: base media decode time, 64-bit in version 1
+pub struct TfdtDecoder;
+
+impl BoxDecoder for TfdtDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let data = TfdtData {
+            version,
+            flags: flags.unwrap_or(0),
+            base_media_decode_time: read_uint(r, if version == 1 { 8 } else { 4 })?,
+        };
+
+        Ok(BoxValue::Structured(
+            StructuredData::TrackFragmentDecodeTime(data),
+        ))
+    }
+}
+
+// tfra: random access points of one track in a fragmented file
+pub struct TfraDecoder;
+
+impl BoxDecoder for TfraDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let track_id = r.read_u32::<BigEndian>()?;
+        let sizes = r.read_u32::<BigEndian>()?;
+        let traf_size = ((sizes >> 4) & 3) as u8 + 1;
+        let trun_size = ((sizes >> 2) & 3) as u8 + 1;
+        let sample_size = (sizes & 3) as u8 + 1;
+        let entry_count = r.read_u32::<BigEndian>()?;
+        let time_size = if version == 1 { 8 } else { 4 };
+
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            entries.push(TfraEntry {
+                time: read_uint(r, time_size)?,
+                moof_offset: read_uint(r, time_size)?,
+                traf_number: read_uint(r, traf_size)? as u32,
+                trun_number: read_uint(r, trun_size)? as u32,
+                sample_number: read_uint(r, sample_size)? as u32,
+            });
+        }
+
+        Ok(BoxValue::Structured(
+            StructuredData::TrackFragmentRandomAccess(TfraData {
+                version,
+                flags: flags.unwrap_or(0),
+                track_id,
+                entries,
+            }),
+        ))
+    }
+}
+
+// trun: track fragment run, per-sample fields driven by tr_flags
+pub struct TrunDecoder;
+
+impl BoxDecoder for TrunDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
+        let version = version.unwrap_or(0);
+        let flags = flags.unwrap_or(0);
+        let f = TrunFlags::from_bits(flags);
+
+        let sample_count = cur.read_u32::<BigEndian>()?;
+        let data_offset = if f.data_offset_present {
+            Some(cur.read_i32::<BigEndian>()?)
+        } else {
+            None
+        };
+        let first_sample_flags = if f.first_sample_flags_present {
+            Some(cur.read_u32::<BigEndian>()?)
+        } else {
+            None
+        };
+
+        let entry_size = 4 * [
+            f.sample_duration_present,
+            f.sample_size_present,
+            f.sample_flags_present,
+            f.sample_composition_time_offsets_present,
+        ]
+        .iter()
+        .filter(|&&present| present)
+        .count();
+        let mut entries = Vec::new();
+        for _ in 0..entries_that_fit(sample_count, &cur, entry_size)? {
+            let sample_duration = if f.sample_duration_present {
+                Some(cur.read_u32::<BigEndian>()?)
+            } else {
+                None
+            };
+            let sample_size = if f.sample_size_present {
+                Some(cur.read_u32::<BigEndian>()?)
+            } else {
+                None
+            };
+            let sample_flags = if f.sample_flags_present {
+                Some(cur.read_u32::<BigEndian>()?)
+            } else {
+                None
+            };
+            let sample_composition_time_offset = if f.sample_composition_time_offsets_present {
+                if version == 0 {
+                    Some(cur.read_u32::<BigEndian>()? as i64)
+                } else {
+                    Some(cur.read_i32::<BigEndian>()? as i64)
+                }
+            } else {
+                None
+            };
+            entries.push(TrunEntry {
+                sample_duration,
+                sample_size,
+                sample_flags,
+                sample_composition_time_offset,
+            });
+        }
+
+        let data = TrunData {
+            version,
+            flags,
+            decoded_flags: f,
+            sample_count,
+            data_offset,
+            first_sample_flags,
+            first_sample_flags_decoded: first_sample_flags.map(SampleFlags::from_bits),
+            entries,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::TrackFragmentRun(data)))
+    }
+}
+
+// pasp: pixel aspect ratio
+pub struct PaspDecoder;
+
+impl BoxDecoder for PaspDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let data = PaspData {
+            h_spacing: r.read_u32::<BigEndian>()?,
+            v_spacing: r.read_u32::<BigEndian>()?,
+        };
+        Ok(BoxValue::Structured(StructuredData::PixelAspectRatio(data)))
+    }
+}
+
+// clap: clean aperture as four N/D fractions
+pub struct ClapDecoder;
+
+impl BoxDecoder for ClapDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let mut fraction = |signed: bool| -> anyhow::Result<f64> {
+            let n = r.read_u32::<BigEndian>()?;
+            let d = r.read_u32::<BigEndian>()?;
+            let n = if signed { n as i32 as f64 } else { n as f64 };
+            Ok(if d == 0 { 0.0 } else { n / d as f64 })
+        };
+        let data = ClapData {
+            width: fraction(false)?,
+            height: fraction(false)?,
+            horiz_offset: fraction(true)?,
+            vert_offset: fraction(true)?,
+        };
+        Ok(BoxValue::Structured(StructuredData::CleanAperture(data)))
+    }
+}
+
+// st3d: stereoscopic 3D video mode
+pub struct St3dDecoder;
+
+impl BoxDecoder for St3dDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let stereo_mode = r.read_u8()?;
+        let stereo_mode_name = match stereo_mode {
+            0 => "monoscopic",
+            1 => "top-bottom",
+            2 => "left-right",
+            3 => "stereo-custom",
+            _ => "unknown",
+        };
+
+        let data = St3dData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            stereo_mode,
+            stereo_mode_name: stereo_mode_name.to_string(),
+        };
+
+        Ok(BoxValue::Structured(StructuredData::StereoVideo(data)))
+    }
+}
+
+// svhd: spherical video header
+pub struct SvhdDecoder;
+
+impl BoxDecoder for SvhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        let metadata_source = String::from_utf8_lossy(&buf[..end]).to_string();
+
+        let data = SvhdData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            metadata_source,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SphericalVideoHeader(
+            data,
+        )))
+    }
+}
+
+// prhd: projection pose (16.16 fixed-point degrees)
+pub struct PrhdDecoder;
+
+impl BoxDecoder for PrhdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let yaw = r.read_i32::<BigEndian>()?;
+        let pitch = r.read_i32::<BigEndian>()?;
+        let roll = r.read_i32::<BigEndian>()?;
+
+        let data = PrhdData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            pose_yaw_degrees: yaw as f64 / 65536.0,
+            pose_pitch_degrees: pitch as f64 / 65536.0,
+            pose_roll_degrees: roll as f64 / 65536.0,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::ProjectionHeader(data)))
+    }
+}
+
+// equi: equirectangular projection bounds
+pub struct EquiDecoder;
+
+impl BoxDecoder for EquiDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let data = EquiData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            projection_bounds_top: r.read_u32::<BigEndian>()?,
+            projection_bounds_bottom: r.read_u32::<BigEndian>()?,
+            projection_bounds_left: r.read_u32::<BigEndian>()?,
+            projection_bounds_right: r.read_u32::<BigEndian>()?,
+        };
+
+        Ok(BoxValue::Structured(
+            StructuredData::EquirectangularProjection(data),
+        ))
+    }
+}
+
+// cbmp: cubemap projection layout
+pub struct CbmpDecoder;
+
+impl BoxDecoder for CbmpDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let data = CbmpData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            layout: r.read_u32::<BigEndian>()?,
+            padding: r.read_u32::<BigEndian>()?,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::CubemapProjection(
+            data,
+        )))
+    }
+}
+
+// SA3D: ambisonic spatial audio configuration
+pub struct Sa3dDecoder;
+
+impl BoxDecoder for Sa3dDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = r.read_u8()?;
+        let ambisonic_type = r.read_u8()?;
+        let ambisonic_order = r.read_u32::<BigEndian>()?;
+        let ambisonic_channel_ordering = r.read_u8()?;
+        let ambisonic_normalization = r.read_u8()?;
+        let num_channels = r.read_u32::<BigEndian>()?;
+
+        let mut channel_map = Vec::new();
+        for _ in 0..num_channels {
+            channel_map.push(r.read_u32::<BigEndian>()?);
+        }
+
+        let data = Sa3dData {
+            version,
+            ambisonic_type,
+            ambisonic_order,
+            ambisonic_channel_ordering,
+            ambisonic_normalization,
+            num_channels,
+            channel_map,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::SpatialAudio(data)))
+    }
+}
+
+// ©xyz: QuickTime GPS location as an ISO 6709 string
+pub struct XyzDecoder;
+
+impl BoxDecoder for XyzDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -360,198 +3525,699 @@ impl BoxDecoder for TkhdDecoder {
     ) -> anyhow::Result<BoxValue> {
         let buf = read_all(r)?;
         if buf.len() < 4 {
-            return Ok(BoxValue::Text(format!(
-                "tkhd: payload too short ({} bytes)",
-                buf.len()
-            )));
+            return Ok(BoxValue::Text("©xyz: payload too short".into()));
         }
+        let len = u16::from_be_bytes([buf[0], buf[1]]) as usize;
+        let language_code = u16::from_be_bytes([buf[2], buf[3]]);
+        let text = &buf[4..(4 + len).min(buf.len())];
+        let iso6709 = String::from_utf8_lossy(text)
+            .trim_end_matches('\0')
+            .to_string();
 
-        let mut pos = 0usize;
-        let version = buf[pos];
-        pos += 1;
-        if pos + 3 > buf.len() {
-            return Ok(BoxValue::Text("tkhd: truncated flags".into()));
-        }
+        let parsed = crate::util::parse_iso6709(&iso6709);
+        let data = LocationData {
+            latitude: parsed.map(|p| p.0),
+            longitude: parsed.map(|p| p.1),
+            altitude: parsed.and_then(|p| p.2),
+            iso6709,
+            language_code,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::Location(data)))
+    }
+}
 
-        // Extract flags as a 24-bit big-endian value
-        let flags_bytes = [0, buf[pos], buf[pos + 1], buf[pos + 2]];
-        let flags_value = u32::from_be_bytes(flags_bytes);
-        pos += 3;
+// data: iTunes metadata value, interpreted by its type indicator
+pub struct DataDecoder;
+
+impl BoxDecoder for DataDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let type_indicator = r.read_u32::<BigEndian>()? & 0x00FF_FFFF;
+        let locale = r.read_u32::<BigEndian>()?;
+        let payload = read_all(r)?;
 
-        let read_u32 = |pos: &mut usize| -> Option<u32> {
-            if *pos + 4 > buf.len() {
+        let be_int = |signed: bool| -> Option<i64> {
+            if payload.is_empty() || payload.len() > 8 {
                 return None;
             }
-            let v = u32::from_be_bytes(buf[*pos..*pos + 4].try_into().unwrap());
-            *pos += 4;
-            Some(v)
-        };
-        let read_u64 = |pos: &mut usize| -> Option<u64> {
-            if *pos + 8 > buf.len() {
-                return None;
+            let mut v: u64 = 0;
+            for &b in &payload {
+                v = (v << 8) | b as u64;
             }
-            let v = u64::from_be_bytes(buf[*pos..*pos + 8].try_into().unwrap());
-            *pos += 8;
-            Some(v)
+            if signed && payload.len() < 8 && payload[0] & 0x80 != 0 {
+                v |= u64::MAX << (payload.len() * 8);
+            }
+            Some(v as i64)
         };
 
-        let track_id;
-        let duration;
+        let value =
+            match type_indicator {
+                1 => TagValue::Text(String::from_utf8_lossy(&payload).to_string()),
+                2 => {
+                    let units: Vec<u16> = payload
+                        .chunks_exact(2)
+                        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                        .collect();
+                    TagValue::Text(String::from_utf16_lossy(&units))
+                }
+                13 | 14 | 27 => TagValue::Image {
+                    format: match type_indicator {
+                        13 => "jpeg",
+                        14 => "png",
+                        _ => "bmp",
+                    }
+                    .to_string(),
+                    size: payload.len() as u64,
+                },
+                21 | 22 => match be_int(type_indicator == 21) {
+                    Some(v) => TagValue::Integer(v),
+                    None => TagValue::Binary(payload),
+                },
+                23 if payload.len() == 4 => TagValue::Float(f32::from_be_bytes([
+                    payload[0], payload[1], payload[2], payload[3],
+                ]) as f64),
+                24 if payload.len() == 8 => {
+                    let mut b = [0u8; 8];
+                    b.copy_from_slice(&payload);
+                    TagValue::Float(f64::from_be_bytes(b))
+                }
+                _ => TagValue::Binary(payload),
+            };
+
+        let data = MetadataValueData {
+            type_indicator,
+            locale,
+            value,
+        };
 
-        if version == 1 {
-            // creation_time (8), modification_time (8), track_id (4), reserved (4), duration (8)
-            if read_u64(&mut pos).is_none() || read_u64(&mut pos).is_none() {
-                return Ok(BoxValue::Text(
-                    "tkhd: truncated creation/modification".into(),
-                ));
-            }
-            track_id = read_u32(&mut pos).unwrap_or(0);
-            let _ = read_u32(&mut pos); // reserved
-            duration = read_u64(&mut pos).unwrap_or(0);
-            eprintln!(
-                "DEBUG tkhd v1: track_id={}, duration={}",
-                track_id, duration
-            );
-        } else {
-            // For version 0, read two 8-byte timestamps then the fields
-            let _creation_time = read_u64(&mut pos).unwrap_or(0);
-            let _modification_time = read_u64(&mut pos).unwrap_or(0);
+        Ok(BoxValue::Structured(StructuredData::MetadataValue(data)))
+    }
+}
 
-            track_id = read_u32(&mut pos).unwrap_or(0);
-            let _reserved = read_u32(&mut pos).unwrap_or(0);
-            duration = read_u32(&mut pos).unwrap_or(0) as u64;
-        }
+// mean / name: reverse-DNS key of a freeform ("----") ilst item
+pub struct FreeformKeyDecoder;
+
+impl BoxDecoder for FreeformKeyDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        // Laid out like a FullBox; skip the zero version/flags when present
+        let text = match buf.strip_prefix(&[0, 0, 0, 0]) {
+            Some(rest) => rest,
+            None => &buf[..],
+        };
+        Ok(BoxValue::Text(String::from_utf8_lossy(text).to_string()))
+    }
+}
+
+// keys: QuickTime metadata key table referenced by ilst item indices
+pub struct KeysDecoder;
 
-        // reserved[2]
-        for _ in 0..2 {
-            let _ = read_u32(&mut pos);
+impl BoxDecoder for KeysDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let entry_count = r.read_u32::<BigEndian>()?;
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let key_size = r.read_u32::<BigEndian>()?;
+            let mut namespace = [0u8; 4];
+            r.read_exact(&mut namespace)?;
+            let mut key = vec![0u8; key_size.saturating_sub(8) as usize];
+            r.read_exact(&mut key)?;
+            entries.push(MetadataKey {
+                namespace: FourCC(namespace).to_string(),
+                key: String::from_utf8_lossy(&key).to_string(),
+            });
         }
 
-        // layer/alt_group/volume/reserved (8 bytes)
-        if pos + 8 <= buf.len() {
-            pos += 8;
-        } else {
-            // we still have track/duration, just don't try width/height
-            return Ok(BoxValue::Text(format!(
-                "track_id={} duration={} (no width/height, short payload)",
-                track_id, duration
-            )));
+        let data = KeysData {
+            version: version.unwrap_or(0),
+            flags: flags.unwrap_or(0),
+            entries,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::MetadataKeys(data)))
+    }
+}
+
+// chap (and other tref children): list of referenced track IDs
+pub struct TrackReferenceDecoder;
+
+impl BoxDecoder for TrackReferenceDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let track_ids = buf
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+
+        let data = TrackReferenceData {
+            reference_type: hdr.typ.to_string(),
+            track_ids,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::TrackReference(data)))
+    }
+}
+
+// chpl: Nero chapter list (start times in 100 ns units, Pascal-string titles)
+pub struct ChplDecoder;
+
+impl BoxDecoder for ChplDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        if version >= 1 {
+            let _reserved = r.read_u32::<BigEndian>()?;
+        }
+        let count = r.read_u8()?;
+        let mut entries = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let start = r.read_u64::<BigEndian>()?;
+            let len = r.read_u8()?;
+            let mut title = vec![0u8; len as usize];
+            r.read_exact(&mut title)?;
+            entries.push(ChplEntry {
+                start,
+                title: String::from_utf8_lossy(&title).to_string(),
+            });
         }
 
-        // matrix (36 bytes)
-        if pos + 36 <= buf.len() {
-            pos += 36;
-        } else {
+        let data = ChplData { version, entries };
+        Ok(BoxValue::Structured(StructuredData::ChapterList(data)))
+    }
+}
+
+// iloc: item extents for HEIF/AVIF items (versions 0-2)
+pub struct IlocDecoder;
+
+impl BoxDecoder for IlocDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        if version > 2 {
             return Ok(BoxValue::Text(format!(
-                "track_id={} duration={} (no width/height, short payload)",
-                track_id, duration
+                "iloc: unsupported version {}",
+                version
             )));
         }
 
-        // width / height
-        let (width, height) = if pos + 8 <= buf.len() {
-            let width = u32::from_be_bytes(buf[pos..pos + 4].try_into().unwrap());
-            let height = u32::from_be_bytes(buf[pos + 4..pos + 8].try_into().unwrap());
-            (width as f32 / 65536.0, height as f32 / 65536.0)
+        let sizes = r.read_u16::<BigEndian>()?;
+        let offset_size = (sizes >> 12) as u8;
+        let length_size = ((sizes >> 8) & 0xF) as u8;
+        let base_offset_size = ((sizes >> 4) & 0xF) as u8;
+        let index_size = if version == 0 { 0 } else { (sizes & 0xF) as u8 };
+
+        let item_count = if version < 2 {
+            r.read_u16::<BigEndian>()? as u32
         } else {
-            (0.0, 0.0)
+            r.read_u32::<BigEndian>()?
         };
 
-        let data = TkhdData {
+        let mut items = Vec::new();
+        for _ in 0..item_count {
+            let item_id = if version < 2 {
+                r.read_u16::<BigEndian>()? as u32
+            } else {
+                r.read_u32::<BigEndian>()?
+            };
+            let construction_method = if version == 0 {
+                0
+            } else {
+                (r.read_u16::<BigEndian>()? & 0xF) as u8
+            };
+            let data_reference_index = r.read_u16::<BigEndian>()?;
+            let base_offset = read_uint(r, base_offset_size)?;
+            let extent_count = r.read_u16::<BigEndian>()?;
+
+            let mut extents = Vec::with_capacity(extent_count as usize);
+            for _ in 0..extent_count {
+                let index = if index_size > 0 {
+                    Some(read_uint(r, index_size)?)
+                } else {
+                    None
+                };
+                let offset = read_uint(r, offset_size)?;
+                let length = read_uint(r, length_size)?;
+                extents.push(IlocExtent {
+                    index,
+                    offset,
+                    length,
+                });
+            }
+
+            let construction_method_name = match construction_method {
+                0 => "file_offset",
+                1 => "idat_offset",
+                2 => "item_offset",
+                _ => "unknown",
+            }
+            .to_string();
+
+            items.push(IlocItem {
+                item_id,
+                construction_method,
+                construction_method_name,
+                data_reference_index,
+                base_offset,
+                extents,
+            });
+        }
+
+        let data = IlocData {
             version,
-            flags: flags_value,
-            track_id,
-            duration,
-            width,
-            height,
+            offset_size,
+            length_size,
+            base_offset_size,
+            index_size,
+            items,
+        };
+
+        Ok(BoxValue::Structured(StructuredData::ItemLocation(data)))
+    }
+}
+
+// ispe: reconstructed image width and height
+pub struct IspeDecoder;
+
+impl BoxDecoder for IspeDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let data = IspeData {
+            image_width: r.read_u32::<BigEndian>()?,
+            image_height: r.read_u32::<BigEndian>()?,
+        };
+        Ok(BoxValue::Structured(StructuredData::ImageSpatialExtents(
+            data,
+        )))
+    }
+}
+
+// pixi: channel count followed by one bit depth per channel
+pub struct PixiDecoder;
+
+impl BoxDecoder for PixiDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let num_channels = r.read_u8()?;
+        let mut bits_per_channel = vec![0u8; num_channels as usize];
+        r.read_exact(&mut bits_per_channel)?;
+        Ok(BoxValue::Structured(StructuredData::PixelInformation(
+            PixiData { bits_per_channel },
+        )))
+    }
+}
+
+// irot: anti-clockwise rotation in 90 degree steps
+pub struct IrotDecoder;
+
+impl BoxDecoder for IrotDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let angle = r.read_u8()? & 0x3;
+        let data = IrotData {
+            angle,
+            degrees: angle as u16 * 90,
+        };
+        Ok(BoxValue::Structured(StructuredData::ImageRotation(data)))
+    }
+}
+
+// imir: mirroring axis
+pub struct ImirDecoder;
+
+impl BoxDecoder for ImirDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let axis = r.read_u8()? & 0x1;
+        let axis_name = if axis == 0 { "vertical" } else { "horizontal" }.to_string();
+        Ok(BoxValue::Structured(StructuredData::ImageMirror(
+            ImirData { axis, axis_name },
+        )))
+    }
+}
+
+// ID32: language + ID3v2 tag; text frames are collected into a tag map
+pub struct Id32Decoder;
+
+impl BoxDecoder for Id32Decoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let language = lang_from_u16(r.read_u16::<BigEndian>()? & 0x7FFF);
+        let tag = read_all(r)?;
+
+        let mut data = Id32Data {
+            language,
+            id3_version: String::new(),
+            frames: Vec::new(),
+            tags: BTreeMap::new(),
+        };
+        if tag.len() < 10 || &tag[..3] != b"ID3" {
+            return Ok(BoxValue::Structured(StructuredData::Id3Tag(data)));
+        }
+
+        let major = tag[3];
+        data.id3_version = format!("2.{}.{}", major, tag[4]);
+        let tag_flags = tag[5];
+        let end = (10 + syncsafe(&tag[6..10]) as usize).min(tag.len());
+
+        let mut pos = 10;
+        if tag_flags & 0x40 != 0 && tag.len() >= 14 {
+            // Extended header: v2.4 counts its own size field, v2.3 does not
+            pos += match major {
+                4 => syncsafe(&tag[10..14]) as usize,
+                _ => 4 + u32::from_be_bytes([tag[10], tag[11], tag[12], tag[13]]) as usize,
+            };
+        }
+
+        // v2.2 uses 3-byte IDs and sizes with no frame flags
+        let (id_len, header_len) = if major == 2 { (3, 6) } else { (4, 10) };
+        while pos + header_len <= end {
+            let id = &tag[pos..pos + id_len];
+            if id[0] == 0 {
+                break; // padding
+            }
+            let size_bytes = &tag[pos + id_len..pos + 2 * id_len];
+            let size = match major {
+                2 => u32::from_be_bytes([0, size_bytes[0], size_bytes[1], size_bytes[2]]),
+                3 => {
+                    u32::from_be_bytes([size_bytes[0], size_bytes[1], size_bytes[2], size_bytes[3]])
+                }
+                _ => syncsafe(size_bytes),
+            };
+            let body_start = pos + header_len;
+            let body = &tag[body_start..(body_start + size as usize).min(end)];
+            let id = String::from_utf8_lossy(id).to_string();
+
+            // Multiple values (ID3v2.4) are joined with "; "
+            let strings = id3_strings(body);
+            if id == "TXXX" || id == "TXX" {
+                if let Some((desc, values)) = strings.split_first() {
+                    data.tags
+                        .insert(format!("{}:{}", id, desc), values.join("; "));
+                }
+            } else if id.starts_with('T') {
+                let values: Vec<_> = strings.into_iter().filter(|v| !v.is_empty()).collect();
+                data.tags.insert(id.clone(), values.join("; "));
+            }
+            data.frames.push(Id3Frame { id, size });
+            pos = body_start + size as usize;
+        }
+
+        Ok(BoxValue::Structured(StructuredData::Id3Tag(data)))
+    }
+}
+
+// xml : XML document, UTF-8 or UTF-16 with a byte order mark
+pub struct XmlDecoder;
+
+impl BoxDecoder for XmlDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let utf16 = |bytes: &[u8], be: bool| {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|c| {
+                    if be {
+                        u16::from_be_bytes([c[0], c[1]])
+                    } else {
+                        u16::from_le_bytes([c[0], c[1]])
+                    }
+                })
+                .collect();
+            String::from_utf16_lossy(&units)
+        };
+        let (encoding, text) = match buf.as_slice() {
+            [0xFE, 0xFF, rest @ ..] => ("utf-16", utf16(rest, true)),
+            [0xFF, 0xFE, rest @ ..] => ("utf-16", utf16(rest, false)),
+            [0xEF, 0xBB, 0xBF, rest @ ..] => ("utf-8", String::from_utf8_lossy(rest).to_string()),
+            rest => ("utf-8", String::from_utf8_lossy(rest).to_string()),
+        };
+
+        let data = XmlData {
+            encoding: encoding.to_string(),
+            text: text.trim_end_matches('\0').to_string(),
+        };
+        Ok(BoxValue::Structured(StructuredData::Xml(data)))
+    }
+}
+
+// bxml: binary XML, kept as raw bytes
+pub struct BxmlDecoder;
+
+impl BoxDecoder for BxmlDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        Ok(BoxValue::Bytes(read_all(r)?))
+    }
+}
+
+// uuid BE7ACFCB-...: XMP packet as UTF-8 text
+pub struct XmpDecoder;
+
+impl BoxDecoder for XmpDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let buf = read_all(r)?;
+        let packet = String::from_utf8_lossy(&buf)
+            .trim_end_matches('\0')
+            .to_string();
+        Ok(BoxValue::Structured(StructuredData::Xmp(XmpData {
+            packet,
+        })))
+    }
+}
+
+// PIFF tfxd (uuid 6D1D9B05-...): absolute fragment time and duration
+pub struct PiffTfxdDecoder;
+
+impl BoxDecoder for PiffTfxdDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        // uuid boxes carry their own version/flags
+        let version = r.read_u8()?;
+        let _flags = r.read_u24::<BigEndian>()?;
+        let (absolute_time, duration) = if version == 1 {
+            (r.read_u64::<BigEndian>()?, r.read_u64::<BigEndian>()?)
+        } else {
+            (
+                r.read_u32::<BigEndian>()? as u64,
+                r.read_u32::<BigEndian>()? as u64,
+            )
         };
-
-        Ok(BoxValue::Structured(StructuredData::TrackHeader(data)))
+        Ok(BoxValue::Structured(StructuredData::PiffFragmentTime(
+            PiffTfxdData {
+                version,
+                absolute_time,
+                duration,
+            },
+        )))
     }
 }
 
-// mdhd: timescale, duration, language
-pub struct MdhdDecoder;
+// PIFF tfrf (uuid D4807EF2-...): times of upcoming fragments (live)
+pub struct PiffTfrfDecoder;
 
-impl BoxDecoder for MdhdDecoder {
+impl BoxDecoder for PiffTfrfDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
+        _version: Option<u8>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let creation_time = r.read_u32::<BigEndian>()?;
-        let modification_time = r.read_u32::<BigEndian>()?;
-        let timescale = r.read_u32::<BigEndian>()?;
-        let duration = r.read_u32::<BigEndian>()?;
-        let language_code = r.read_u16::<BigEndian>()?;
-        let _pre_defined = r.read_u16::<BigEndian>()?;
-
-        let lang = lang_from_u16(language_code);
-
-        let data = MdhdData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            creation_time,
-            modification_time,
-            timescale,
-            duration,
-            language: lang,
-        };
-
-        Ok(BoxValue::Structured(StructuredData::MediaHeader(data)))
+        let version = r.read_u8()?;
+        let _flags = r.read_u24::<BigEndian>()?;
+        let count = r.read_u8()?;
+        let mut fragments = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (absolute_time, duration) = if version == 1 {
+                (r.read_u64::<BigEndian>()?, r.read_u64::<BigEndian>()?)
+            } else {
+                (
+                    r.read_u32::<BigEndian>()? as u64,
+                    r.read_u32::<BigEndian>()? as u64,
+                )
+            };
+            fragments.push(PiffFragmentEntry {
+                absolute_time,
+                duration,
+            });
+        }
+        Ok(BoxValue::Structured(StructuredData::PiffFragmentReference(
+            PiffTfrfData { version, fragments },
+        )))
     }
 }
 
-// hdlr: handler type + name
-pub struct HdlrDecoder;
+// PIFF senc (uuid A2394F52-...): per-sample IVs and subsample ranges
+pub struct PiffSencDecoder;
+
+impl PiffSencDecoder {
+    /// Parse the sample entries with a fixed IV size; `None` unless the
+    /// entries use up the buffer exactly.
+    fn parse_samples(
+        buf: &[u8],
+        count: u32,
+        iv_size: usize,
+        subsamples: bool,
+    ) -> Option<Vec<EncryptedSample>> {
+        let mut c = Cursor::new(buf);
+        let mut samples = Vec::new();
+        for _ in 0..count {
+            let mut iv = vec![0u8; iv_size];
+            c.read_exact(&mut iv).ok()?;
+            let mut entries = Vec::new();
+            if subsamples {
+                let n = c.read_u16::<BigEndian>().ok()?;
+                for _ in 0..n {
+                    entries.push(SubsampleEncryption {
+                        clear_bytes: c.read_u16::<BigEndian>().ok()?,
+                        protected_bytes: c.read_u32::<BigEndian>().ok()?,
+                    });
+                }
+            }
+            samples.push(EncryptedSample {
+                iv: hex::encode(iv),
+                subsamples: entries,
+            });
+        }
+        (c.position() == buf.len() as u64).then_some(samples)
+    }
+}
 
-impl BoxDecoder for HdlrDecoder {
+impl BoxDecoder for PiffSencDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
+        _version: Option<u8>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        use byteorder::{BigEndian, ReadBytesExt};
-
-        // pre_defined (4 bytes) + handler_type (4 bytes)
-        let _pre_defined = r.read_u32::<BigEndian>()?;
-        let mut handler_type = [0u8; 4];
-        r.read_exact(&mut handler_type)?;
-
-        // reserved (3 * 4 bytes)
-        let mut reserved = [0u8; 12];
-        r.read_exact(&mut reserved)?;
-
-        // name: null-terminated string (or just rest of box)
-        let mut name_bytes = Vec::new();
-        r.read_to_end(&mut name_bytes)?;
-        // strip trailing nulls
-        while name_bytes.last() == Some(&0) {
-            name_bytes.pop();
+        let version = r.read_u8()?;
+        let flags = r.read_u24::<BigEndian>()?;
+
+        let (mut algorithm_id, mut iv_size, mut kid) = (None, None, None);
+        if flags & 0x1 != 0 {
+            algorithm_id = Some(r.read_u24::<BigEndian>()?);
+            iv_size = Some(r.read_u8()?);
+            let mut k = [0u8; 16];
+            r.read_exact(&mut k)?;
+            kid = Some(hex::encode(k));
         }
-        let name = String::from_utf8_lossy(&name_bytes).to_string();
-
-        let handler_str = std::str::from_utf8(&handler_type).unwrap_or("????");
-
-        let data = HdlrData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            handler_type: handler_str.to_string(),
-            name,
+        let sample_count = r.read_u32::<BigEndian>()?;
+        let rest = read_all(r)?;
+
+        // Without an override the IV size lives in the track's tenc
+        let subsamples = flags & 0x2 != 0;
+        let candidates = match iv_size {
+            Some(n) => vec![n],
+            None => vec![8, 16],
         };
-
-        Ok(BoxValue::Structured(StructuredData::HandlerReference(data)))
+        let (iv_size, samples) = candidates
+            .into_iter()
+            .find_map(|n| {
+                Self::parse_samples(&rest, sample_count, n as usize, subsamples)
+                    .map(|s| (Some(n), s))
+            })
+            .unwrap_or((iv_size, Vec::new()));
+
+        Ok(BoxValue::Structured(StructuredData::PiffSampleEncryption(
+            PiffSencData {
+                version,
+                flags,
+                algorithm_id,
+                iv_size,
+                kid,
+                sample_count,
+                samples,
+            },
+        )))
     }
 }
 
-// sidx: segment index summary
-pub struct SidxDecoder;
+// mhaC: MPEG-H 3D Audio decoder configuration record
+pub struct MhacDecoder;
 
-impl BoxDecoder for SidxDecoder {
+impl BoxDecoder for MhacDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -559,44 +4225,20 @@ impl BoxDecoder for SidxDecoder {
         _version: Option<u8>,
         _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        let version = cur.read_u8()?;
-        let _flags = {
-            let mut f = [0u8; 3];
-            cur.read_exact(&mut f)?;
-            ((f[0] as u32) << 16) | ((f[1] as u32) << 8) | (f[2] as u32)
-        };
-
-        let _ref_id = cur.read_u32::<BigEndian>()?;
-        let timescale = cur.read_u32::<BigEndian>()?;
-
-        let (earliest, first_offset) = if version == 1 {
-            let earliest = cur.read_u64::<BigEndian>()?;
-            let first = cur.read_u64::<BigEndian>()?;
-            (earliest, first)
-        } else {
-            let earliest = cur.read_u32::<BigEndian>()? as u64;
-            let first = cur.read_u32::<BigEndian>()? as u64;
-            (earliest, first)
+        let data = MhacData {
+            configuration_version: r.read_u8()?,
+            profile_level_indication: r.read_u8()?,
+            reference_channel_layout: r.read_u8()?,
+            mpegh3da_config_length: r.read_u16::<BigEndian>()?,
         };
-
-        let _reserved = cur.read_u16::<BigEndian>()?;
-        let ref_count = cur.read_u16::<BigEndian>()?;
-
-        Ok(BoxValue::Text(format!(
-            "timescale={} earliest_presentation_time={} first_offset={} references={}",
-            timescale, earliest, first_offset, ref_count
-        )))
+        Ok(BoxValue::Structured(StructuredData::MpegHConfig(data)))
     }
 }
 
-// stsd: list sample entry formats, maybe WxH
-// ---- stsd decoder: codec + width/height for first entry -----------------
-pub struct StsdDecoder;
+// ddts: DTS specific box (ETSI TS 102 114 Annex E)
+pub struct DdtsDecoder;
 
-impl BoxDecoder for StsdDecoder {
+impl BoxDecoder for DdtsDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -604,281 +4246,511 @@ impl BoxDecoder for StsdDecoder {
         _version: Option<u8>,
         _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        use byteorder::{BigEndian, ReadBytesExt};
-
-        // stsd is a FullBox; our reader is already positioned at payload:
-        // u32 entry_count
-        // [ SampleEntry entries... ]
+        let sampling_frequency = r.read_u32::<BigEndian>()?;
+        let max_bitrate = r.read_u32::<BigEndian>()?;
+        let avg_bitrate = r.read_u32::<BigEndian>()?;
+        let pcm_sample_depth = r.read_u8()?;
+        let bits = read_all(r)?;
+        let mut b = BitReader::new(&bits);
+
+        let frame_duration_code = b.read(2)? as u8;
+        let data = DdtsData {
+            sampling_frequency,
+            max_bitrate,
+            avg_bitrate,
+            pcm_sample_depth,
+            frame_duration_code,
+            frame_duration: 512 << frame_duration_code,
+            stream_construction: b.read(5)? as u8,
+            core_lfe_present: b.read_bool()?,
+            core_layout: b.read(6)? as u8,
+            core_size: b.read(14)? as u16,
+            stereo_downmix: b.read_bool()?,
+            representation_type: b.read(3)? as u8,
+            channel_layout: b.read(16)? as u16,
+            multi_asset: b.read_bool()?,
+            lbr_duration_mod: b.read_bool()?,
+        };
+        Ok(BoxValue::Structured(StructuredData::DtsConfig(data)))
+    }
+}
 
-        let entry_count = r.read_u32::<BigEndian>()?;
-        if entry_count == 0 {
-            return Ok(BoxValue::Text("entry_count=0".to_string()));
+// dac4: AC-4 decoder specific info (ETSI TS 103 190-2 Annex E)
+pub struct Dac4Decoder;
+
+impl Dac4Decoder {
+    /// Leading fields of ac4_presentation_v0_dsi / ac4_presentation_v1_dsi.
+    fn presentation(version: u8, body: &[u8]) -> anyhow::Result<Ac4Presentation> {
+        let mut p = Ac4Presentation {
+            presentation_version: version,
+            presentation_config: None,
+            mdcompat: None,
+            presentation_id: None,
+            channel_mask: None,
+        };
+        if version > 2 {
+            return Ok(p);
         }
 
-        // First sample entry only (good enough for mp4info-like summary)
-        let entry_size = r.read_u32::<BigEndian>()?;
-
-        let mut codec_bytes = [0u8; 4];
-        r.read_exact(&mut codec_bytes)?;
-        let codec = std::str::from_utf8(&codec_bytes)
-            .unwrap_or("????")
-            .to_string();
-
-        // Now we’re at SampleEntry fields.
-        // For visual sample entries (avc1/hvc1/etc.), layout is:
-        //
-        // 6 reserved bytes
-        // u16 data_reference_index
-        // 16 bytes pre_defined / reserved
-        // u16 width
-        // u16 height
-        //
-        // For audio sample entries, this layout is different, so we only
-        // try to read width/height for known video codecs.
-        let visual_codecs = ["avc1", "hvc1", "hev1", "vp09", "av01"];
-
-        let mut width: Option<u32> = None;
-        let mut height: Option<u32> = None;
-
-        if visual_codecs.contains(&codec.as_str()) {
-            // Skip reserved + data_reference_index
-            let mut skip = [0u8; 6 + 2 + 16];
-            r.read_exact(&mut skip)?;
-
-            let w = r.read_u16::<BigEndian>()?;
-            let h = r.read_u16::<BigEndian>()?;
-            width = Some(w as u32);
-            height = Some(h as u32);
+        let mut b = BitReader::new(body);
+        let config = b.read(5)? as u8;
+        p.presentation_config = Some(config);
+        if config == 0x06 {
+            // EMDF-only presentation
+            return Ok(p);
         }
-
-        let mut parts = Vec::new();
-        parts.push(format!("entry_count={}", entry_count));
-        parts.push(format!("codec={}", codec));
-        if let Some(w) = width {
-            parts.push(format!("width={}", w));
+        p.mdcompat = Some(b.read(3)? as u8);
+        if b.read_bool()? {
+            p.presentation_id = Some(b.read(5)? as u8);
         }
-        if let Some(h) = height {
-            parts.push(format!("height={}", h));
+        let _frame_rate_multiply_info = b.read(2)?;
+        if version > 0 {
+            let _frame_rate_fraction_info = b.read(2)?;
         }
-
-        // Create structured data
-        let data = StsdData {
-            version: _version.unwrap_or(0),
-            flags: _flags.unwrap_or(0),
-            entry_count,
-            entries: vec![SampleEntry {
-                size: entry_size,
-                codec,
-                data_reference_index: 1, // Default value
-                width: width.map(|w| w as u16),
-                height: height.map(|h| h as u16),
-            }],
-        };
-
-        Ok(BoxValue::Structured(StructuredData::SampleDescription(
-            data,
-        )))
+        let _emdf_version = b.read(5)?;
+        let _key_id = b.read(10)?;
+        if version == 0 {
+            p.channel_mask = Some(b.read(24)?);
+        } else if b.read_bool()? {
+            // b_presentation_channel_coded
+            let ch_mode = b.read(5)?;
+            if (11..=14).contains(&ch_mode) {
+                b.skip(1 + 2); // b_4_back_channels_present, top_channel_pairs
+            }
+            p.channel_mask = Some(b.read(24)?);
+        }
+        Ok(p)
     }
 }
 
-// stts: time-to-sample
-pub struct SttsDecoder;
-
-impl BoxDecoder for SttsDecoder {
+impl BoxDecoder for Dac4Decoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
+        _version: Option<u8>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        // and stripped from the payload. We start directly with the box-specific data.
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut entries = Vec::new();
+        let mut b = BitReader::new(&buf);
+
+        let ac4_dsi_version = b.read(3)? as u8;
+        let bitstream_version = b.read(7)? as u8;
+        let fs_index = b.read(1)? as u8;
+        let frame_rate_index = b.read(4)? as u8;
+        let n_presentations = b.read(9)? as u16;
+
+        let (mut short_program_id, mut program_uuid) = (None, None);
+        if bitstream_version > 1 && b.read_bool()? {
+            short_program_id = Some(b.read(16)? as u16);
+            if b.read_bool()? {
+                let mut uuid = [0u8; 16];
+                for byte in uuid.iter_mut() {
+                    *byte = b.read(8)? as u8;
+                }
+                program_uuid = Some(hex::encode(uuid));
+            }
+        }
 
-        for _ in 0..entry_count {
-            let sample_count = cur.read_u32::<BigEndian>()?;
-            let sample_delta = cur.read_u32::<BigEndian>()?;
-            entries.push(SttsEntry {
-                sample_count,
-                sample_delta,
-            });
+        let bit_rate_mode = b.read(2)? as u8;
+        let bit_rate = b.read(32)?;
+        let bit_rate_precision = b.read(32)?;
+        b.byte_align();
+
+        let mut presentations = Vec::new();
+        let mut pos = b.byte_pos();
+        for _ in 0..n_presentations {
+            let Some(&[version, size]) = buf.get(pos..pos + 2) else {
+                break;
+            };
+            pos += 2;
+            let mut pres_bytes = size as usize;
+            if size == 255 {
+                let Some(&[hi, lo]) = buf.get(pos..pos + 2) else {
+                    break;
+                };
+                pres_bytes += u16::from_be_bytes([hi, lo]) as usize;
+                pos += 2;
+            }
+            let body = buf
+                .get(pos..pos + pres_bytes)
+                .unwrap_or(&buf[pos.min(buf.len())..]);
+            presentations.push(Self::presentation(version, body)?);
+            pos += pres_bytes;
         }
 
-        let data = SttsData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            entries,
+        let data = Dac4Data {
+            ac4_dsi_version,
+            bitstream_version,
+            fs_index,
+            sampling_rate: if fs_index == 0 { 44_100 } else { 48_000 },
+            frame_rate_index,
+            n_presentations,
+            short_program_id,
+            program_uuid,
+            bit_rate_mode,
+            bit_rate,
+            bit_rate_precision,
+            presentations,
         };
-
-        Ok(BoxValue::Structured(StructuredData::DecodingTimeToSample(
-            data,
-        )))
+        Ok(BoxValue::Structured(StructuredData::Ac4Config(data)))
     }
 }
 
-// stss: sync sample table
-pub struct StssDecoder;
+// cmpd: component types of an uncompressed frame (ISO/IEC 23001-17)
+pub struct CmpdDecoder;
 
-impl BoxDecoder for StssDecoder {
+impl BoxDecoder for CmpdDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
+        _version: Option<u8>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut sample_numbers = Vec::new();
-
-        for _ in 0..entry_count {
-            sample_numbers.push(cur.read_u32::<BigEndian>()?);
+        let count = r.read_u32::<BigEndian>()?;
+        let mut components = Vec::new();
+        for _ in 0..count {
+            let component_type = r.read_u16::<BigEndian>()?;
+            let component_type_uri = if component_type >= 0x8000 {
+                let mut uri = Vec::new();
+                loop {
+                    match r.read_u8()? {
+                        0 => break,
+                        b => uri.push(b),
+                    }
+                }
+                Some(String::from_utf8_lossy(&uri).to_string())
+            } else {
+                None
+            };
+            let component_type_name = match component_type {
+                0 => "monochrome",
+                1 => "Y",
+                2 => "Cb",
+                3 => "Cr",
+                4 => "R",
+                5 => "G",
+                6 => "B",
+                7 => "alpha",
+                8 => "depth",
+                9 => "disparity",
+                10 => "palette",
+                11 => "filter array",
+                12 => "padded",
+                13 => "cyan",
+                14 => "magenta",
+                15 => "yellow",
+                16 => "key",
+                0x8000.. => "user-defined",
+                _ => "reserved",
+            }
+            .to_string();
+            components.push(ComponentDefinition {
+                component_type,
+                component_type_name,
+                component_type_uri,
+            });
         }
-
-        let data = StssData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            sample_numbers,
-        };
-
-        Ok(BoxValue::Structured(StructuredData::SyncSample(data)))
+        Ok(BoxValue::Structured(StructuredData::ComponentDefinition(
+            CmpdData { components },
+        )))
     }
 }
 
-// ctts: composition time to sample
-pub struct CttsDecoder;
+// uncC: layout of uncompressed frames (ISO/IEC 23001-17)
+pub struct UncCDecoder;
 
-impl BoxDecoder for CttsDecoder {
+impl BoxDecoder for UncCDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
         version: Option<u8>,
-        flags: Option<u32>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut entries = Vec::new();
+        let version = version.unwrap_or(0);
+        let mut profile = [0u8; 4];
+        r.read_exact(&mut profile)?;
+        let mut data = UncCData {
+            version,
+            profile: FourCC(profile).to_string(),
+            ..Default::default()
+        };
+        if version != 0 {
+            return Ok(BoxValue::Structured(
+                StructuredData::UncompressedFrameConfig(data),
+            ));
+        }
 
-        for _ in 0..entry_count {
-            let sample_count = cur.read_u32::<BigEndian>()?;
-            // Note: In version 1, sample_offset can be signed, but since we don't have access
-            // to the parsed version here, we assume version 0 behavior (unsigned)
-            let sample_offset = cur.read_u32::<BigEndian>()? as i32;
-            entries.push(CttsEntry {
-                sample_count,
-                sample_offset,
+        let count = r.read_u32::<BigEndian>()?;
+        for _ in 0..count {
+            data.components.push(UncCComponent {
+                component_index: r.read_u16::<BigEndian>()?,
+                bit_depth: r.read_u8()? as u16 + 1,
+                component_format: r.read_u8()?,
+                component_align_size: r.read_u8()?,
             });
         }
-
-        let data = CttsData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            entries,
-        };
+        data.sampling_type = r.read_u8()?;
+        data.sampling_type_name = match data.sampling_type {
+            0 => "4:4:4",
+            1 => "4:2:2",
+            2 => "4:2:0",
+            3 => "4:1:1",
+            _ => "reserved",
+        }
+        .to_string();
+        data.interleave_type = r.read_u8()?;
+        data.interleave_type_name = match data.interleave_type {
+            0 => "component",
+            1 => "pixel",
+            2 => "mixed",
+            3 => "row",
+            4 => "tile-component",
+            5 => "multi-y",
+            _ => "reserved",
+        }
+        .to_string();
+        data.block_size = r.read_u8()?;
+        let bits = r.read_u8()?;
+        data.components_little_endian = bits & 0x80 != 0;
+        data.block_pad_lsb = bits & 0x40 != 0;
+        data.block_little_endian = bits & 0x20 != 0;
+        data.block_reversed = bits & 0x10 != 0;
+        data.pad_unknown = bits & 0x08 != 0;
+        data.pixel_size = r.read_u32::<BigEndian>()?;
+        data.row_align_size = r.read_u32::<BigEndian>()?;
+        data.tile_align_size = r.read_u32::<BigEndian>()?;
+        data.num_tile_cols = r.read_u32::<BigEndian>()? + 1;
+        data.num_tile_rows = r.read_u32::<BigEndian>()? + 1;
 
         Ok(BoxValue::Structured(
-            StructuredData::CompositionTimeToSample(data),
+            StructuredData::UncompressedFrameConfig(data),
         ))
     }
 }
 
-// stsc: sample-to-chunk
-pub struct StscDecoder;
+// tlou / alou: loudness and peak measurements (ISO/IEC 14496-12, 23003-4)
+pub struct LoudnessDecoder;
 
-impl BoxDecoder for StscDecoder {
+impl LoudnessDecoder {
+    /// bs_sample_peak_level / bs_true_peak_level: 0 means not signalled
+    fn peak(bits: u32) -> Option<f64> {
+        let v = ((bits << 20) as i32) >> 20; // sign-extend 12 bits
+        (v != 0).then(|| 20.0 - v as f64 / 32.0)
+    }
+
+    fn value(method: u8, v: u8) -> Option<f64> {
+        let v = v as f64;
+        match method {
+            1..=4 | 8 => Some(-57.75 + v / 4.0),
+            5 if v <= 128.0 => Some(v / 4.0),
+            5 if v <= 204.0 => Some(32.0 + (v - 128.0) / 2.0),
+            5 => Some(70.0 + (v - 204.0)),
+            6 => Some(80.0 + v),
+            _ => None,
+        }
+    }
+}
+
+impl BoxDecoder for LoudnessDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
-        _hdr: &BoxHeader,
+        hdr: &BoxHeader,
         version: Option<u8>,
-        flags: Option<u32>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
+        let mut b = BitReader::new(&buf);
 
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
+        let count = if version >= 1 {
+            b.skip(2);
+            b.read(6)?
+        } else {
+            1
+        };
         let mut entries = Vec::new();
-
-        for _ in 0..entry_count {
-            let first_chunk = cur.read_u32::<BigEndian>()?;
-            let samples_per_chunk = cur.read_u32::<BigEndian>()?;
-            let sample_description_index = cur.read_u32::<BigEndian>()?;
-            entries.push(StscEntry {
-                first_chunk,
-                samples_per_chunk,
-                sample_description_index,
+        for _ in 0..count {
+            let eq_set_id = if version >= 1 {
+                b.skip(2);
+                Some(b.read(6)? as u8)
+            } else {
+                None
+            };
+            b.skip(3);
+            let downmix_id = b.read(7)? as u8;
+            let drc_set_id = b.read(6)? as u8;
+            let sample_peak_level = Self::peak(b.read(12)?);
+            let true_peak_level = Self::peak(b.read(12)?);
+            let measurement_system_for_tp = b.read(4)? as u8;
+            let reliability_for_tp = b.read(4)? as u8;
+            let measurement_count = b.read(8)?;
+
+            let mut measurements = Vec::new();
+            for _ in 0..measurement_count {
+                let method_definition = b.read(8)? as u8;
+                let method_value = b.read(8)? as u8;
+                measurements.push(LoudnessMeasurement {
+                    method_definition,
+                    method_value,
+                    value: Self::value(method_definition, method_value),
+                    measurement_system: b.read(4)? as u8,
+                    reliability: b.read(4)? as u8,
+                });
+            }
+            entries.push(LoudnessInfo {
+                eq_set_id,
+                downmix_id,
+                drc_set_id,
+                sample_peak_level,
+                true_peak_level,
+                measurement_system_for_tp,
+                reliability_for_tp,
+                measurements,
             });
         }
-
-        let data = StscData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            entry_count,
-            entries,
+
+        let data = LoudnessData {
+            version,
+            loudness_type: hdr.typ.to_string(),
+            entries,
+        };
+        Ok(BoxValue::Structured(StructuredData::Loudness(data)))
+    }
+}
+
+/// Any payload: printable UTF-8 text as [`BoxValue::Text`], anything else
+/// as a hex preview of its first bytes. Reads at most
+/// [`GenericDecoder::TEXT_LIMIT`] bytes, so it suits
+/// [`Registry::with_catch_all`] and fallbacks.
+pub struct GenericDecoder;
+
+impl GenericDecoder {
+    /// Longest payload shown as text
+    pub const TEXT_LIMIT: usize = 4096;
+    /// Bytes shown in a hex preview
+    const PREVIEW: usize = 32;
+}
+
+impl BoxDecoder for GenericDecoder {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let mut head = Vec::new();
+        r.take(Self::TEXT_LIMIT as u64 + 1).read_to_end(&mut head)?;
+        let text = std::str::from_utf8(&head)
+            .ok()
+            .filter(|_| head.len() <= Self::TEXT_LIMIT)
+            .map(|t| t.trim_end_matches('\0'))
+            .filter(|t| t.chars().all(|c| !c.is_control() || c.is_whitespace()));
+        if let Some(text) = text {
+            return Ok(BoxValue::Text(text.to_string()));
+        }
+        let total = match hdr.size {
+            0 => head.len() as u64,
+            size => size.saturating_sub(hdr.header_size),
         };
-
-        Ok(BoxValue::Structured(StructuredData::SampleToChunk(data)))
+        let preview = &head[..head.len().min(Self::PREVIEW)];
+        let more = if (preview.len() as u64) < total {
+            " ..."
+        } else {
+            ""
+        };
+        Ok(BoxValue::Text(format!(
+            "{}{} ({} bytes)",
+            hex::encode(preview),
+            more,
+            total
+        )))
     }
 }
 
-// stsz: sample sizes
-pub struct StszDecoder;
+// ©nam, ©too, ©cmt, ...: QuickTime international text, a list of
+// (u16 size, u16 language, text) records
+pub struct UserDataTextDecoder;
+
+impl UserDataTextDecoder {
+    /// The udta text atoms registered by [`default_registry`].
+    const ATOMS: [&'static [u8; 3]; 23] = [
+        b"nam", b"too", b"cmt", b"des", b"day", b"ART", b"aut", b"alb", b"cpy", b"swr", b"enc",
+        b"inf", b"mak", b"mod", b"wrt", b"dir", b"prd", b"req", b"fmt", b"src", b"hst", b"key",
+        b"gen",
+    ];
+
+    fn text(bytes: &[u8]) -> String {
+        let s = match bytes {
+            [0xFE, 0xFF, rest @ ..] => {
+                let units: Vec<u16> = rest
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+            _ => String::from_utf8_lossy(bytes).to_string(),
+        };
+        s.trim_end_matches('\0').to_string()
+    }
+}
 
-impl BoxDecoder for StszDecoder {
+impl BoxDecoder for UserDataTextDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        version: Option<u8>,
-        flags: Option<u32>,
+        _version: Option<u8>,
+        _flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let sample_size = cur.read_u32::<BigEndian>()?;
-        let sample_count = cur.read_u32::<BigEndian>()?;
-        let mut sample_sizes = Vec::new();
 
-        // If sample_size is 0, each sample has its own size
-        if sample_size == 0 {
-            for _ in 0..sample_count {
-                sample_sizes.push(cur.read_u32::<BigEndian>()?);
-            }
+        // Some writers use an iTunes-style data atom here instead
+        if buf.get(4..8) == Some(b"data") {
+            let text = buf.get(16..).unwrap_or_default();
+            let entries = vec![UserDataText {
+                language: None,
+                mac_language: None,
+                text: Self::text(text),
+            }];
+            return Ok(BoxValue::Structured(StructuredData::UserDataText(
+                UserDataTextData { entries },
+            )));
         }
 
-        let data = StszData {
-            version: version.unwrap_or(0),
-            flags: flags.unwrap_or(0),
-            sample_size,
-            sample_count,
-            sample_sizes,
-        };
+        let mut entries = Vec::new();
+        let mut pos = 0;
+        while pos + 4 <= buf.len() {
+            let size = u16::from_be_bytes([buf[pos], buf[pos + 1]]) as usize;
+            let code = u16::from_be_bytes([buf[pos + 2], buf[pos + 3]]);
+            let end = (pos + 4 + size).min(buf.len());
+            let (language, mac_language) = if code < 0x400 {
+                (None, Some(code))
+            } else {
+                (Some(lang_from_u16(code & 0x7FFF)), None)
+            };
+            entries.push(UserDataText {
+                language,
+                mac_language,
+                text: Self::text(&buf[pos + 4..end]),
+            });
+            pos = end;
+        }
 
-        Ok(BoxValue::Structured(StructuredData::SampleSize(data)))
+        Ok(BoxValue::Structured(StructuredData::UserDataText(
+            UserDataTextData { entries },
+        )))
     }
 }
 
-// stco: 32-bit chunk offsets
-pub struct StcoDecoder;
+// sbgp: runs of samples mapped to a sample group description
+pub struct SbgpDecoder;
 
-impl BoxDecoder for StcoDecoder {
+impl BoxDecoder for SbgpDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -886,32 +4758,60 @@ impl BoxDecoder for StcoDecoder {
         version: Option<u8>,
         flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
+        let version = version.unwrap_or(0);
+        let grouping_type = FourCC(r.read_u32::<BigEndian>()?.to_be_bytes()).to_string();
+        let grouping_type_parameter = if version == 1 {
+            Some(r.read_u32::<BigEndian>()?)
+        } else {
+            None
+        };
+        let entry_count = r.read_u32::<BigEndian>()?;
         let buf = read_all(r)?;
         let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut chunk_offsets = Vec::new();
-
-        for _ in 0..entry_count {
-            chunk_offsets.push(cur.read_u32::<BigEndian>()?);
+        let mut entries = Vec::new();
+        for _ in 0..entries_that_fit(entry_count, &cur, 8)? {
+            entries.push(SbgpEntry {
+                sample_count: cur.read_u32::<BigEndian>()?,
+                group_description_index: cur.read_u32::<BigEndian>()?,
+            });
         }
 
-        let data = StcoData {
-            version: version.unwrap_or(0),
+        let data = SbgpData {
+            version,
             flags: flags.unwrap_or(0),
-            entry_count,
-            chunk_offsets,
+            grouping_type,
+            grouping_type_parameter,
+            entries,
         };
-
-        Ok(BoxValue::Structured(StructuredData::ChunkOffset(data)))
+        Ok(BoxValue::Structured(StructuredData::SampleToGroup(data)))
     }
 }
 
-// co64: 64-bit chunk offsets
-pub struct Co64Decoder;
+/// Parse a 'seig' entry, which tenc shares after its version/flags.
+fn seig_entry(b: &[u8]) -> Option<SeigEntry> {
+    let [_, pattern, protected, iv_size, rest @ ..] = b else {
+        return None;
+    };
+    let kid = rest.get(..16)?;
+    let is_protected = *protected == 1;
+    let constant_iv = match rest[16..] {
+        [n, ref iv @ ..] if is_protected && *iv_size == 0 => iv.get(..n as usize).map(hex::encode),
+        _ => None,
+    };
+    Some(SeigEntry {
+        crypt_byte_block: pattern >> 4,
+        skip_byte_block: pattern & 0x0F,
+        is_protected,
+        per_sample_iv_size: *iv_size,
+        kid: hex::encode(kid),
+        constant_iv,
+    })
+}
 
-impl BoxDecoder for Co64Decoder {
+// tenc: default CENC key and IV settings of a track
+pub struct TencDecoder;
+
+impl BoxDecoder for TencDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
@@ -920,124 +4820,115 @@ impl BoxDecoder for Co64Decoder {
         flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
         let buf = read_all(r)?;
-        let mut cur = Cursor::new(&buf);
-
-        // For FullBox types, version and flags are already parsed by the main parser
-        let entry_count = cur.read_u32::<BigEndian>()?;
-        let mut chunk_offsets = Vec::new();
-
-        for _ in 0..entry_count {
-            chunk_offsets.push(cur.read_u64::<BigEndian>()?);
-        }
-
-        let data = Co64Data {
+        let Some(defaults) = seig_entry(&buf) else {
+            anyhow::bail!("tenc: payload too short ({} bytes)", buf.len());
+        };
+        let data = TencData {
             version: version.unwrap_or(0),
             flags: flags.unwrap_or(0),
-            entry_count,
-            chunk_offsets,
+            defaults,
         };
-
-        Ok(BoxValue::Structured(StructuredData::ChunkOffset64(data)))
+        Ok(BoxValue::Structured(StructuredData::TrackEncryption(data)))
     }
 }
 
-// elst: edit list
-pub struct ElstDecoder;
+// sgpd: sample group description entries
+pub struct SgpdDecoder;
+
+impl SgpdDecoder {
+    /// Entry size of grouping types whose entries have a fixed length, for
+    /// version 0 boxes that carry no lengths.
+    fn fixed_length(grouping_type: &str) -> Option<u32> {
+        match grouping_type {
+            "roll" | "prol" => Some(2),
+            "rap " | "sync" | "tele" => Some(1),
+            _ => None,
+        }
+    }
 
-impl BoxDecoder for ElstDecoder {
+    fn entry(grouping_type: &str, b: &[u8]) -> SampleGroupEntry {
+        match (grouping_type, b) {
+            ("seig", _) => match seig_entry(b) {
+                Some(e) => SampleGroupEntry::Encryption(e),
+                None => SampleGroupEntry::Raw {
+                    data: hex::encode(b),
+                },
+            },
+            ("roll" | "prol", [hi, lo, ..]) => SampleGroupEntry::Roll {
+                roll_distance: i16::from_be_bytes([*hi, *lo]),
+            },
+            ("rap ", [v, ..]) => SampleGroupEntry::RandomAccessPoint {
+                num_leading_samples_known: v & 0x80 != 0,
+                num_leading_samples: v & 0x7F,
+            },
+            ("sync", [v, ..]) => SampleGroupEntry::Sync {
+                nal_unit_type: v & 0x3F,
+            },
+            _ => SampleGroupEntry::Raw {
+                data: hex::encode(b),
+            },
+        }
+    }
+}
+
+impl BoxDecoder for SgpdDecoder {
     fn decode(
         &self,
         r: &mut dyn Read,
         _hdr: &BoxHeader,
-        _version: Option<u8>,
-        _flags: Option<u32>,
+        version: Option<u8>,
+        flags: Option<u32>,
     ) -> anyhow::Result<BoxValue> {
-        let buf = read_all(r)?;
-        if buf.len() < 8 {
-            return Ok(BoxValue::Text(format!(
-                "elst: payload too short ({} bytes)",
-                buf.len()
-            )));
-        }
-
-        let mut pos = 0usize;
-        let version = buf[pos];
-        pos += 1;
-        if pos + 3 > buf.len() {
-            return Ok(BoxValue::Text("elst: truncated flags".into()));
-        }
-        pos += 3;
-
-        let read_u32 = |pos: &mut usize| -> Option<u32> {
-            if *pos + 4 > buf.len() {
-                return None;
-            }
-            let v = u32::from_be_bytes(buf[*pos..*pos + 4].try_into().unwrap());
-            *pos += 4;
-            Some(v)
-        };
-        let read_u64 = |pos: &mut usize| -> Option<u64> {
-            if *pos + 8 > buf.len() {
-                return None;
-            }
-            let v = u64::from_be_bytes(buf[*pos..*pos + 8].try_into().unwrap());
-            *pos += 8;
-            Some(v)
-        };
-        let read_i32 = |pos: &mut usize| -> Option<i32> {
-            if *pos + 4 > buf.len() {
-                return None;
-            }
-            let v = i32::from_be_bytes(buf[*pos..*pos + 4].try_into().unwrap());
-            *pos += 4;
-            Some(v)
-        };
-        let read_i64 = |pos: &mut usize| -> Option<i64> {
-            if *pos + 8 > buf.len() {
-                return None;
-            }
-            let v = i64::from_be_bytes(buf[*pos..*pos + 8].try_into().unwrap());
-            *pos += 8;
-            Some(v)
+        let version = version.unwrap_or(0);
+        let grouping_type = FourCC(r.read_u32::<BigEndian>()?.to_be_bytes()).to_string();
+        let default_length = if version >= 1 {
+            r.read_u32::<BigEndian>()?
+        } else {
+            0
         };
-        let read_i16 = |pos: &mut usize| -> Option<i16> {
-            if *pos + 2 > buf.len() {
-                return None;
-            }
-            let v = i16::from_be_bytes(buf[*pos..*pos + 2].try_into().unwrap());
-            *pos += 2;
-            Some(v)
+        let default_sample_description_index = if version >= 2 {
+            Some(r.read_u32::<BigEndian>()?)
+        } else {
+            None
         };
+        let entry_count = r.read_u32::<BigEndian>()?;
+        let buf = read_all(r)?;
+        let mut cur = Cursor::new(&buf);
 
-        let entry_count = read_u32(&mut pos).unwrap_or(0);
-
-        if entry_count == 0 {
-            return Ok(BoxValue::Text(format!("version={} entries=0", version)));
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let len = match version {
+                1.. if default_length == 0 => cur.read_u32::<BigEndian>()?,
+                1.. => default_length,
+                // seig entries with a constant IV are longer than 20 bytes
+                _ if grouping_type == "seig" => {
+                    let at = cur.position() as usize;
+                    match buf.get(at + 2..at + 4) {
+                        Some([1, 0]) => 21 + buf.get(at + 20).copied().unwrap_or(0) as u32,
+                        _ => 20,
+                    }
+                }
+                _ => match Self::fixed_length(&grouping_type) {
+                    Some(n) => n,
+                    // Unknown v0 entry size: keep the rest as one entry
+                    None => (buf.len() as u64 - cur.position()) as u32,
+                },
+            };
+            let mut b = vec![0u8; len as usize];
+            cur.read_exact(&mut b)?;
+            entries.push(Self::entry(&grouping_type, &b));
         }
 
-        let (seg_duration, media_time) = if version == 1 {
-            let dur = read_u64(&mut pos).unwrap_or(0);
-            let mt = read_i64(&mut pos).unwrap_or(0);
-            (dur, mt)
-        } else {
-            let dur = read_u32(&mut pos).unwrap_or(0) as u64;
-            let mt = read_i32(&mut pos).unwrap_or(0) as i64;
-            (dur, mt)
+        let data = SgpdData {
+            version,
+            flags: flags.unwrap_or(0),
+            grouping_type,
+            default_sample_description_index,
+            entries,
         };
-
-        let rate_int = read_i16(&mut pos);
-        let rate_frac = read_i16(&mut pos);
-
-        match (rate_int, rate_frac) {
-            (Some(ri), Some(rf)) => Ok(BoxValue::Text(format!(
-                "version={} entries={} first: duration={} media_time={} rate={}/{}",
-                version, entry_count, seg_duration, media_time, ri, rf
-            ))),
-            _ => Ok(BoxValue::Text(format!(
-                "version={} entries={} first: duration={} media_time={} (no rate, short payload)",
-                version, entry_count, seg_duration, media_time
-            ))),
-        }
+        Ok(BoxValue::Structured(
+            StructuredData::SampleGroupDescription(data),
+        ))
     }
 }
 
@@ -1045,12 +4936,17 @@ impl BoxDecoder for ElstDecoder {
 pub fn default_registry() -> Registry {
     use crate::boxes::BoxKey;
 
-    Registry::new()
+    let registry = Registry::new()
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"ftyp")),
             "ftyp",
             Box::new(FtypDecoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"styp")),
+            "styp",
+            Box::new(FtypDecoder),
+        )
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"mvhd")),
             "mvhd",
@@ -1091,6 +4987,11 @@ pub fn default_registry() -> Registry {
             "stss",
             Box::new(StssDecoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"sdtp")),
+            "sdtp",
+            Box::new(SdtpDecoder),
+        )
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"ctts")),
             "ctts",
@@ -1106,6 +5007,11 @@ pub fn default_registry() -> Registry {
             "stsz",
             Box::new(StszDecoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"stz2")),
+            "stz2",
+            Box::new(Stz2Decoder),
+        )
         .with_decoder(
             BoxKey::FourCC(FourCC(*b"stco")),
             "stco",
@@ -1121,4 +5027,250 @@ pub fn default_registry() -> Registry {
             "elst",
             Box::new(ElstDecoder),
         )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"chnl")),
+            "chnl",
+            Box::new(ChnlDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"pcmC")),
+            "pcmC",
+            Box::new(PcmCDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"kind")),
+            "kind",
+            Box::new(KindDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"elng")),
+            "elng",
+            Box::new(ElngDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"trex")),
+            "trex",
+            Box::new(TrexDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"mfhd")),
+            "mfhd",
+            Box::new(MfhdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tfhd")),
+            "tfhd",
+            Box::new(TfhdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"sbgp")),
+            "sbgp",
+            Box::new(SbgpDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"sgpd")),
+            "sgpd",
+            Box::new(SgpdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tenc")),
+            "tenc",
+            Box::new(TencDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tfdt")),
+            "tfdt",
+            Box::new(TfdtDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tfra")),
+            "tfra",
+            Box::new(TfraDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"trun")),
+            "trun",
+            Box::new(TrunDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"pasp")),
+            "pasp",
+            Box::new(PaspDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"clap")),
+            "clap",
+            Box::new(ClapDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"st3d")),
+            "st3d",
+            Box::new(St3dDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"svhd")),
+            "svhd",
+            Box::new(SvhdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"prhd")),
+            "prhd",
+            Box::new(PrhdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"equi")),
+            "equi",
+            Box::new(EquiDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"cbmp")),
+            "cbmp",
+            Box::new(CbmpDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"SA3D")),
+            "SA3D",
+            Box::new(Sa3dDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"\xA9xyz")),
+            "©xyz",
+            Box::new(XyzDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"data")),
+            "data",
+            Box::new(DataDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"mean")),
+            "mean",
+            Box::new(FreeformKeyDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"name")),
+            "name",
+            Box::new(FreeformKeyDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"keys")),
+            "keys",
+            Box::new(KeysDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"chap")),
+            "chap",
+            Box::new(TrackReferenceDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"chpl")),
+            "chpl",
+            Box::new(ChplDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"iloc")),
+            "iloc",
+            Box::new(IlocDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"ispe")),
+            "ispe",
+            Box::new(IspeDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"pixi")),
+            "pixi",
+            Box::new(PixiDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"irot")),
+            "irot",
+            Box::new(IrotDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"imir")),
+            "imir",
+            Box::new(ImirDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"ID32")),
+            "ID32",
+            Box::new(Id32Decoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"xml ")),
+            "xml ",
+            Box::new(XmlDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"bxml")),
+            "bxml",
+            Box::new(BxmlDecoder),
+        )
+        .with_decoder(
+            BoxKey::Uuid(crate::extract::XMP_UUID),
+            "xmp",
+            Box::new(XmpDecoder),
+        )
+        .with_decoder(
+            BoxKey::Uuid(PIFF_TFXD_UUID),
+            "tfxd",
+            Box::new(PiffTfxdDecoder),
+        )
+        .with_decoder(
+            BoxKey::Uuid(PIFF_TFRF_UUID),
+            "tfrf",
+            Box::new(PiffTfrfDecoder),
+        )
+        .with_decoder(
+            BoxKey::Uuid(PIFF_SENC_UUID),
+            "piff-senc",
+            Box::new(PiffSencDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"mhaC")),
+            "mhaC",
+            Box::new(MhacDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"ddts")),
+            "ddts",
+            Box::new(DdtsDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"dac4")),
+            "dac4",
+            Box::new(Dac4Decoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"cmpd")),
+            "cmpd",
+            Box::new(CmpdDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"uncC")),
+            "uncC",
+            Box::new(UncCDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"tlou")),
+            "tlou",
+            Box::new(LoudnessDecoder),
+        )
+        .with_decoder(
+            BoxKey::FourCC(FourCC(*b"alou")),
+            "alou",
+            Box::new(LoudnessDecoder),
+        );
+
+    UserDataTextDecoder::ATOMS
+        .iter()
+        .fold(registry, |reg, name| {
+            let cc = FourCC([0xA9, name[0], name[1], name[2]]);
+            reg.with_decoder(
+                BoxKey::FourCC(cc),
+                &cc.to_string(),
+                Box::new(UserDataTextDecoder),
+            )
+        })
 }
@@ -0,0 +1,119 @@
+//! In-place repair of files whose last top-level box was cut short, as
+//! left behind by recordings that stopped abruptly.
+
+use crate::parser::read_box_header;
+use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+/// How [`repair_truncated`] rewrites the size of the cut box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairMode {
+    /// Store the number of bytes actually present
+    FitSize,
+    /// Store 0 ("box extends to the end of the file"). Boxes with a 64-bit
+    /// size have no such encoding and get [`FitSize`](Self::FitSize).
+    SizeToEof,
+}
+
+/// The cut box and the size written for it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Truncation {
+    /// Header offset of the cut box
+    pub offset: u64,
+    pub typ: String,
+    pub declared_size: u64,
+    /// Bytes from the header to the end of the file
+    pub actual_size: u64,
+    /// Offset of the size field that is (or was) rewritten
+    pub field_offset: u64,
+    /// Value for the size field: `actual_size`, or 0 for size-to-EOF
+    pub new_size: u64,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum RepairError {
+    #[error("file ends inside the header of the box at {offset}")]
+    HeaderTruncated { offset: u64 },
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Find the top-level box whose declared size runs past the end of the
+/// file and the size that would fix it, without writing anything.
+///
+/// Returns `None` when every top-level box fits. Only the top-level size
+/// is considered: children of a cut container (a moov stopped mid-write)
+/// still overrun it after the repair.
+pub fn find_truncated<R: Read + Seek>(
+    r: &mut R,
+    mode: RepairMode,
+) -> Result<Option<Truncation>, RepairError> {
+    let file_len = r.seek(SeekFrom::End(0))?;
+    let mut pos = 0;
+    while pos < file_len {
+        r.seek(SeekFrom::Start(pos))?;
+        let h = read_box_header(r).map_err(|_| RepairError::HeaderTruncated { offset: pos })?;
+        if h.size == 0 {
+            // Already extends to the end of the file
+            return Ok(None);
+        }
+        let end = h.start.saturating_add(h.size);
+        if end > file_len {
+            let actual_size = file_len - h.start;
+            if actual_size < h.header_size {
+                return Err(RepairError::HeaderTruncated { offset: h.start });
+            }
+            let large = h.header_size == 16 || h.header_size == 32;
+            let (field_offset, new_size) = match (large, mode) {
+                (true, _) => (h.start + 8, actual_size),
+                (false, RepairMode::FitSize) => (h.start, actual_size),
+                (false, RepairMode::SizeToEof) => (h.start, 0),
+            };
+            return Ok(Some(Truncation {
+                offset: h.start,
+                typ: h.typ.to_string(),
+                declared_size: h.size,
+                actual_size,
+                field_offset,
+                new_size,
+            }));
+        }
+        pos = end;
+    }
+    Ok(None)
+}
+
+/// Rewrite the size of the top-level box cut by the end of the file (see
+/// [`find_truncated`]) so players accept the file again. Only the 4 or 8
+/// byte size field is written; returns what was changed, or `None` if the
+/// file needed no repair.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::repair::{RepairMode, repair_truncated};
+/// use std::fs::OpenOptions;
+///
+/// let mut file = OpenOptions::new().read(true).write(true).open("cut.mp4")?;
+/// if let Some(fix) = repair_truncated(&mut file, RepairMode::FitSize)? {
+///     println!("{} at {}: {} -> {} bytes", fix.typ, fix.offset, fix.declared_size, fix.actual_size);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn repair_truncated<F: Read + Write + Seek>(
+    f: &mut F,
+    mode: RepairMode,
+) -> Result<Option<Truncation>, RepairError> {
+    let Some(fix) = find_truncated(f, mode)? else {
+        return Ok(None);
+    };
+    f.seek(SeekFrom::Start(fix.field_offset))?;
+    if fix.field_offset == fix.offset {
+        // The compact size is only ever set from a smaller 32-bit value
+        f.write_all(&(fix.new_size as u32).to_be_bytes())?;
+    } else {
+        f.write_all(&fix.new_size.to_be_bytes())?;
+    }
+    f.flush()?;
+    Ok(Some(fix))
+}
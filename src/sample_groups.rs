@@ -0,0 +1,245 @@
+//! Sample group membership from sbgp + sgpd, at track and fragment level.
+
+use crate::registry::{SampleGroupEntry, SbgpData, SgpdData, StructuredData};
+use serde::Serialize;
+
+/// sbgp indices above this refer to the sgpd of the same traf.
+const FRAGMENT_LOCAL_BASE: u32 = 0x10000;
+
+/// One sample's membership in a sample group.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SampleGroupMembership {
+    /// e.g. "roll", "rap ", "seig"
+    pub grouping_type: String,
+    pub grouping_type_parameter: Option<u32>,
+    /// 1-based index into the sgpd the entry came from
+    pub group_description_index: u32,
+    /// The description lives in a traf's sgpd rather than the track's
+    pub fragment_local: bool,
+    /// None when the sgpd has no entry at that index
+    pub entry: Option<SampleGroupEntry>,
+}
+
+/// A run of consecutive samples with the same group membership.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SampleGroupRun {
+    /// 0-based index of the first sample, counting stbl samples first and
+    /// then fragment samples in file order (as in [`crate::TrackSamples`])
+    pub first_sample: u32,
+    pub sample_count: u32,
+    pub membership: SampleGroupMembership,
+}
+
+/// All sample group mappings of one track.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackSampleGroups {
+    pub track_id: u32,
+    /// stbl samples plus the trun samples of every fragment
+    pub sample_count: u32,
+    pub runs: Vec<SampleGroupRun>,
+    /// sgpd version 2 defaults for samples no sbgp maps
+    pub defaults: Vec<SampleGroupMembership>,
+}
+
+impl TrackSampleGroups {
+    /// Group memberships of the sample at `sample_index` (0-based).
+    pub fn groups_of(&self, sample_index: u32) -> Vec<&SampleGroupMembership> {
+        let mut out: Vec<&SampleGroupMembership> = self
+            .runs
+            .iter()
+            .filter(|r| {
+                sample_index >= r.first_sample && sample_index - r.first_sample < r.sample_count
+            })
+            .map(|r| &r.membership)
+            .collect();
+        for d in &self.defaults {
+            if !out.iter().any(|m| m.grouping_type == d.grouping_type) {
+                out.push(d);
+            }
+        }
+        out
+    }
+}
+
+/// Resolve the sample groups of every track in a decoded box tree.
+///
+/// Track-level sbgp/sgpd boxes (in stbl) cover the samples of the sample
+/// table; traf-level ones cover that traf's samples, which are numbered
+/// after the stbl samples in file order. Requires `decode = true`.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, sample_groups::track_sample_groups};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for track in track_sample_groups(&boxes) {
+///     for m in track.groups_of(0) {
+///         println!("track {} sample 0: {} {:?}", track.track_id, m.grouping_type, m.entry);
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn track_sample_groups(boxes: &[crate::Box]) -> Vec<TrackSampleGroups> {
+    let mut tracks = Vec::new();
+    // Track-level sgpds, per track
+    let mut state: Vec<Vec<&SgpdData>> = Vec::new();
+
+    let traks = boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .flat_map(|moov| moov.children.iter().flatten())
+        .filter(|b| b.typ == "trak");
+    for trak in traks {
+        let Some(track_id) = child_data(trak).find_map(|d| match d {
+            StructuredData::TrackHeader(t) => Some(t.track_id),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let stbl = trak.descendants_of_type("stbl").next();
+        let stbl_data: Vec<&StructuredData> =
+            stbl.map(|b| child_data(b).collect()).unwrap_or_default();
+
+        let sample_count = stbl_data
+            .iter()
+            .find_map(|d| match d {
+                StructuredData::SampleSize(s) => Some(s.sample_count),
+                _ => None,
+            })
+            .unwrap_or(0);
+        let sgpds: Vec<&SgpdData> = stbl_data.iter().filter_map(|d| sgpd(d)).collect();
+
+        let mut groups = TrackSampleGroups {
+            track_id,
+            sample_count,
+            runs: Vec::new(),
+            defaults: Vec::new(),
+        };
+        for sbgp in stbl_data.iter().filter_map(|d| sbgp(d)) {
+            push_runs(&mut groups.runs, sbgp, 0, &sgpds, &[]);
+        }
+        for g in &sgpds {
+            if let Some(index) = g.default_sample_description_index.filter(|&i| i > 0) {
+                groups.defaults.push(membership(
+                    g.grouping_type.clone(),
+                    None,
+                    index,
+                    &sgpds,
+                    &[],
+                ));
+            }
+        }
+        tracks.push(groups);
+        state.push(sgpds);
+    }
+
+    let trafs = boxes
+        .iter()
+        .filter(|b| b.typ == "moof")
+        .flat_map(|moof| moof.children.iter().flatten())
+        .filter(|b| b.typ == "traf");
+    for traf in trafs {
+        let data: Vec<&StructuredData> = child_data(traf).collect();
+        let Some(track_id) = data.iter().find_map(|d| match d {
+            StructuredData::TrackFragmentHeader(h) => Some(h.track_id),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let Some(i) = tracks.iter().position(|t| t.track_id == track_id) else {
+            continue;
+        };
+        let track = &mut tracks[i];
+        let local: Vec<&SgpdData> = data.iter().filter_map(|d| sgpd(d)).collect();
+
+        for sbgp in data.iter().filter_map(|d| sbgp(d)) {
+            push_runs(&mut track.runs, sbgp, track.sample_count, &state[i], &local);
+        }
+        track.sample_count += data
+            .iter()
+            .filter_map(|d| match d {
+                StructuredData::TrackFragmentRun(r) => Some(r.entries.len() as u32),
+                _ => None,
+            })
+            .sum::<u32>();
+    }
+
+    tracks
+}
+
+fn child_data(b: &crate::Box) -> impl Iterator<Item = &StructuredData> {
+    b.children
+        .iter()
+        .flatten()
+        .filter_map(|c| c.structured_data.as_ref())
+}
+
+fn sbgp(d: &StructuredData) -> Option<&SbgpData> {
+    match d {
+        StructuredData::SampleToGroup(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn sgpd(d: &StructuredData) -> Option<&SgpdData> {
+    match d {
+        StructuredData::SampleGroupDescription(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn push_runs(
+    runs: &mut Vec<SampleGroupRun>,
+    sbgp: &SbgpData,
+    first_sample: u32,
+    track: &[&SgpdData],
+    local: &[&SgpdData],
+) {
+    let mut next = first_sample;
+    for e in &sbgp.entries {
+        if e.group_description_index > 0 {
+            runs.push(SampleGroupRun {
+                first_sample: next,
+                sample_count: e.sample_count,
+                membership: membership(
+                    sbgp.grouping_type.clone(),
+                    sbgp.grouping_type_parameter,
+                    e.group_description_index,
+                    track,
+                    local,
+                ),
+            });
+        }
+        next = next.saturating_add(e.sample_count);
+    }
+}
+
+fn membership(
+    grouping_type: String,
+    grouping_type_parameter: Option<u32>,
+    index: u32,
+    track: &[&SgpdData],
+    local: &[&SgpdData],
+) -> SampleGroupMembership {
+    let (fragment_local, index, sgpds) = if index > FRAGMENT_LOCAL_BASE && !local.is_empty() {
+        (true, index - FRAGMENT_LOCAL_BASE, local)
+    } else {
+        (false, index, track)
+    };
+    let entry = sgpds
+        .iter()
+        .find(|g| g.grouping_type == grouping_type)
+        .and_then(|g| g.entries.get(index as usize - 1))
+        .cloned();
+
+    SampleGroupMembership {
+        grouping_type,
+        grouping_type_parameter,
+        group_description_index: index,
+        fragment_local,
+        entry,
+    }
+}
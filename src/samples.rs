@@ -18,20 +18,57 @@ pub struct SampleInfo {
     /// Start time in seconds (pts / timescale as f64)
     pub start_time: f64,
 
-    /// Duration in track timescale units (from stts)
+    /// Duration in track timescale units (from stts, or trun for fragments)
     pub duration: u32,
 
     /// Composition/rendered offset in track timescale units (from ctts, may be 0)
     pub rendered_offset: i64,
 
-    /// Byte offset in the file (from stsc + stco/co64)
+    /// Byte offset in the file (from stsc + stco/co64, or tfhd/trun)
     pub file_offset: u64,
 
     /// Sample size in bytes (from stsz)
     pub size: u32,
 
-    /// Whether this sample is a sync sample / keyframe (from stss)
+    /// Whether this sample is a sync sample / keyframe; see `sync_source`
     pub is_sync: bool,
+
+    /// Which box decided `is_sync`
+    pub sync_source: SyncSource,
+
+    /// For fragment samples: which box each defaulted field came from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragment_sources: Option<crate::fragments::FragmentSampleSources>,
+}
+
+/// Where the sync decision of a sample came from, most specific first.
+///
+/// Sample tables use stss, then sdtp (sync when the sample does not depend
+/// on others). Fragments use the trun/tfhd/trex sample_flags, then the
+/// traf's sdtp. With none of these every sample is sync.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncSource {
+    Stss,
+    Sdtp,
+    SampleFlags,
+    Default,
+}
+
+/// Sync decision from an sdtp entry; None when it does not say.
+fn sdtp_sync(entry: Option<&crate::registry::SdtpEntry>) -> Option<(bool, SyncSource)> {
+    match entry?.sample_depends_on {
+        1 => Some((false, SyncSource::Sdtp)),
+        2 => Some((true, SyncSource::Sdtp)),
+        _ => None,
+    }
+}
+
+impl SampleInfo {
+    /// Read this sample's bytes from the file at `file_offset`.
+    pub fn read_data<R: Read + Seek>(&self, r: &mut R) -> std::io::Result<Vec<u8>> {
+        crate::util::read_slice(r, self.file_offset, self.size as u64)
+    }
 }
 
 /// Complete sample information and metadata for a single track in an MP4 file.
@@ -99,11 +136,27 @@ pub struct SampleInfo {
 #[derive(Debug, Clone, Serialize)]
 pub struct TrackSamples {
     pub track_id: u32,
-    pub handler_type: String, // "vide", "soun", etc.
+    pub handler_type: String, // "vide", "soun", etc.; empty without hdlr
     pub timescale: u32,
     pub duration: u64, // in track timescale units
     pub sample_count: u32,
     pub samples: Vec<SampleInfo>,
+    /// One entry per traf of this track in a fragmented file
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fragments: Vec<FragmentTiming>,
+}
+
+/// Timing of one track fragment (traf) and the samples it contributed.
+#[derive(Debug, Clone, Serialize)]
+pub struct FragmentTiming {
+    pub moof_offset: u64,
+    /// mfhd sequence number
+    pub sequence_number: Option<u32>,
+    /// tfdt baseMediaDecodeTime; None when the traf has no tfdt
+    pub base_media_decode_time: Option<u64>,
+    /// Index of the fragment's first sample in `TrackSamples::samples`
+    pub first_sample: u32,
+    pub sample_count: u32,
 }
 
 /// Extracts sample information from all tracks in an MP4 file using a generic reader.
@@ -126,6 +179,9 @@ pub struct TrackSamples {
 /// - Track metadata (ID, handler type, timescale, duration)
 /// - Individual sample information (timing, size, sync status, file offsets)
 ///
+/// Samples of fragmented files (moof/traf/trun) are appended to their track
+/// after the samples listed in the track's own sample table.
+///
 /// # Errors
 ///
 /// This function may return an error in the following cases:
@@ -156,23 +212,98 @@ pub fn track_samples_from_reader<R: Read + Seek>(
     let boxes = crate::get_boxes(&mut reader, file_size, /*decode=*/ true)
         .context("getting boxes from reader")?;
 
+    track_samples_from_boxes(&boxes)
+}
+
+/// Extracts sample information from all tracks of an already-parsed box
+/// tree (from [`crate::get_boxes`] with `decode = true`).
+///
+/// This is what [`track_samples_from_reader`] runs after parsing; use it
+/// directly to avoid parsing a file twice.
+pub fn track_samples_from_boxes(boxes: &[crate::Box]) -> anyhow::Result<Vec<TrackSamples>> {
     let mut result = Vec::new();
+    let file_size = data_end(boxes);
 
-    for moov_box in boxes.iter().filter(|b| b.typ == "moov") {
-        if let Some(children) = &moov_box.children {
-            for trak_box in children.iter().filter(|b| b.typ == "trak") {
-                if let Some(track_samples) =
-                    crate::samples::extract_track_samples(trak_box, &mut reader)?
-                {
-                    result.push(track_samples);
-                }
-            }
+    for trak_box in crate::track::tracks(boxes).iter().map(|t| t.trak()) {
+        if let Some(track_samples) = trak_samples(trak_box, file_size)? {
+            result.push(track_samples);
         }
     }
 
+    append_fragment_samples(boxes, &mut result);
+
     Ok(result)
 }
 
+/// End of the bytes the parse actually saw: payload sizes are cut to the
+/// file, while a declared box size may not be.
+fn data_end(boxes: &[crate::Box]) -> u64 {
+    boxes
+        .iter()
+        .map(|b| match (b.payload_offset, b.payload_size, &b.children) {
+            (Some(offset), Some(size), _) => offset.saturating_add(size),
+            (_, _, Some(children)) => data_end(children).max(b.offset + b.header_size),
+            _ => b.offset + b.header_size,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Append the samples of every top-level moof to the matching track.
+///
+/// Decode times start at the tfdt baseMediaDecodeTime of each traf; a traf
+/// without tfdt continues from the end of the previous one.
+fn append_fragment_samples(boxes: &[crate::Box], tracks: &mut [TrackSamples]) {
+    let trex = crate::fragments::trex_defaults(boxes);
+
+    for moof in boxes.iter().filter(|b| b.typ == "moof") {
+        let sequence_number = crate::fragments::sequence_number(moof);
+        for traf in crate::fragments::moof_trafs(moof, &trex) {
+            let Some(track) = tracks.iter_mut().find(|t| t.track_id == traf.track_id) else {
+                continue;
+            };
+            let mut dts = traf.base_media_decode_time.unwrap_or_else(|| {
+                track
+                    .samples
+                    .last()
+                    .map_or(0, |s| s.dts + s.duration as u64)
+            });
+            track.fragments.push(FragmentTiming {
+                moof_offset: moof.offset,
+                sequence_number,
+                base_media_decode_time: traf.base_media_decode_time,
+                first_sample: track.samples.len() as u32,
+                sample_count: traf.samples.len() as u32,
+            });
+
+            for (i, fs) in traf.samples.iter().enumerate() {
+                let (is_sync, sync_source) =
+                    if fs.sources.flags != crate::fragments::DefaultSource::Missing {
+                        (fs.is_sync(), SyncSource::SampleFlags)
+                    } else {
+                        sdtp_sync(traf.sdtp.get(i)).unwrap_or((true, SyncSource::Default))
+                    };
+                let pts = dts.saturating_add_signed(fs.composition_offset);
+                track.samples.push(SampleInfo {
+                    index: track.samples.len() as u32,
+                    dts,
+                    pts,
+                    start_time: pts as f64 / track.timescale as f64,
+                    duration: fs.duration,
+                    rendered_offset: fs.composition_offset,
+                    file_offset: fs.file_offset,
+                    size: fs.size,
+                    is_sync,
+                    sync_source,
+                    fragment_sources: Some(fs.sources),
+                });
+                dts += fs.duration as u64;
+            }
+            track.sample_count = track.samples.len() as u32;
+        }
+    }
+}
+
 /// Extracts sample information from all tracks in an MP4 file specified by file path.
 ///
 /// This is a convenience function that opens a file from the filesystem and delegates
@@ -289,22 +420,30 @@ pub fn extract_track_samples<R: Read + Seek>(
     trak_box: &crate::Box,
     reader: &mut R,
 ) -> anyhow::Result<Option<TrackSamples>> {
-    // use crate::{BoxValue, StructuredData}; // Will be used when we implement proper parsing
+    let pos = reader.stream_position()?;
+    let file_size = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(pos))?;
+    trak_samples(trak_box, file_size)
+}
 
-    // Find track ID from tkhd
+fn trak_samples(trak_box: &crate::Box, file_size: u64) -> anyhow::Result<Option<TrackSamples>> {
+    let track = crate::track::Track::new(trak_box);
     let track_id = find_track_id(trak_box)?;
 
-    // Find handler type from mdhd
-    let (handler_type, timescale, duration) = find_media_info(trak_box)?;
-
-    // Find sample table (stbl) box
-    let stbl_box = find_stbl_box(trak_box)?;
+    let handler_type = track
+        .hdlr()
+        .map_or_else(String::new, |h| h.handler_type.clone());
+    let (timescale, duration) = track
+        .mdhd()
+        .map_or((1000, 0), |m| (m.timescale, m.duration));
 
-    // Extract sample table data
-    let sample_tables = extract_sample_tables(stbl_box)?;
+    if track.stbl().is_none() {
+        anyhow::bail!("stbl box not found")
+    }
 
-    // Build sample information from the tables
-    let samples = build_sample_info(&sample_tables, timescale, reader)?;
+    let samples: Vec<SampleInfo> = SampleIter::new(&track)
+        .within_file_size(file_size)
+        .collect();
     let sample_count = samples.len() as u32;
 
     Ok(Some(TrackSamples {
@@ -314,379 +453,295 @@ pub fn extract_track_samples<R: Read + Seek>(
         duration,
         sample_count,
         samples,
+        fragments: Vec::new(),
     }))
 }
 
 fn find_track_id(trak_box: &crate::Box) -> anyhow::Result<u32> {
-    use crate::registry::StructuredData;
-
-    // Look for tkhd box to get track ID
-    if let Some(children) = &trak_box.children {
-        for child in children {
-            if child.typ == "tkhd" {
-                // Extract track ID from structured data
-                if let Some(StructuredData::TrackHeader(tkhd_data)) = &child.structured_data {
-                    return Ok(tkhd_data.track_id);
-                }
-            }
-        }
-    }
-    anyhow::bail!("No tkhd box found or track ID could not be parsed")
-}
-
-fn find_media_info(trak_box: &crate::Box) -> anyhow::Result<(String, u32, u64)> {
-    use crate::registry::StructuredData;
-
-    // Look for mdia/mdhd and mdia/hdlr boxes
-    if let Some(children) = &trak_box.children {
-        for child in children {
-            if child.typ == "mdia"
-                && let Some(mdia_children) = &child.children
-            {
-                let mut timescale = 1000; // Default
-                let mut duration = 0; // Default
-                let mut handler_type = String::from("vide"); // Default
-
-                for mdia_child in mdia_children {
-                    if mdia_child.typ == "mdhd" {
-                        // Parse timescale and duration from mdhd
-                        if let Some(StructuredData::MediaHeader(mdhd_data)) =
-                            &mdia_child.structured_data
-                        {
-                            timescale = mdhd_data.timescale;
-                            duration = mdhd_data.duration as u64;
-                        }
-                    }
-                    if mdia_child.typ == "hdlr" {
-                        // Parse handler type from hdlr
-                        if let Some(StructuredData::HandlerReference(hdlr_data)) =
-                            &mdia_child.structured_data
-                        {
-                            handler_type = hdlr_data.handler_type.clone();
-                        }
-                    }
-                }
-
-                return Ok((handler_type, timescale, duration));
-            }
-        }
-    }
-    Ok((String::from("vide"), 1000, 0))
+    crate::track::Track::new(trak_box)
+        .track_id()
+        .context("No tkhd box found or track ID could not be parsed")
 }
 
-fn find_stbl_box(trak_box: &crate::Box) -> anyhow::Result<&crate::Box> {
-    // Navigate to mdia/minf/stbl
-    if let Some(children) = &trak_box.children {
-        for child in children {
-            if child.typ == "mdia"
-                && let Some(mdia_children) = &child.children
-            {
-                for mdia_child in mdia_children {
-                    if mdia_child.typ == "minf"
-                        && let Some(minf_children) = &mdia_child.children
-                    {
-                        for minf_child in minf_children {
-                            if minf_child.typ == "stbl" {
-                                return Ok(minf_child);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
-    anyhow::bail!("stbl box not found")
+/// Lazily walks the sample table of one track, yielding a [`SampleInfo`]
+/// per sample.
+///
+/// stts, ctts, stsc, stco/co64 and stss are followed with run cursors, so
+/// memory use does not grow with the number of samples and each step is
+/// O(1). Only the sample table is covered; fragment samples are not.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mp4box::{SampleIter, get_boxes, tracks};
+/// use std::fs::File;
+///
+/// let mut file = File::open("long.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for track in tracks(&boxes) {
+///     let bytes: u64 = SampleIter::new(&track).map(|s| s.size as u64).sum();
+///     println!("track {:?}: {} bytes", track.track_id(), bytes);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct SampleIter<'a> {
+    timescale: u32,
+    stts: Option<&'a crate::registry::SttsData>,
+    ctts: Option<&'a crate::registry::CttsData>,
+    stsc: Option<&'a crate::registry::StscData>,
+    stsz: Option<&'a crate::registry::StszData>,
+    stss: Option<&'a crate::registry::StssData>,
+    sdtp: Option<&'a crate::registry::SdtpData>,
+    stco: Option<&'a crate::registry::StcoData>,
+    co64: Option<&'a crate::registry::Co64Data>,
+
+    index: u32,
+    sample_count: u32,
+    dts: u64,
+    stts_run: RunCursor,
+    ctts_run: RunCursor,
+    stss_pos: usize,
+    /// 0-based chunk holding the next sample
+    chunk: u32,
+    /// stsc entry describing `chunk`
+    stsc_entry: usize,
+    sample_in_chunk: u32,
+    offset_in_chunk: u64,
 }
 
-#[derive(Debug)]
-struct SampleTables {
-    stsd: Option<crate::registry::StsdData>,
-    stts: Option<crate::registry::SttsData>,
-    ctts: Option<crate::registry::CttsData>,
-    stsc: Option<crate::registry::StscData>,
-    stsz: Option<crate::registry::StszData>,
-    stss: Option<crate::registry::StssData>,
-    stco: Option<crate::registry::StcoData>,
-    co64: Option<crate::registry::Co64Data>,
+/// Position within a run-length table such as stts or ctts.
+#[derive(Debug, Clone, Copy, Default)]
+struct RunCursor {
+    entry: usize,
+    used: u32,
 }
 
-fn extract_sample_tables(stbl_box: &crate::Box) -> anyhow::Result<SampleTables> {
-    let mut tables = SampleTables {
-        stsd: None,
-        stts: None,
-        ctts: None,
-        stsc: None,
-        stsz: None,
-        stss: None,
-        stco: None,
-        co64: None,
-    };
-
-    // Extract structured data directly from child boxes
-    if let Some(children) = &stbl_box.children {
-        for child in children {
-            if let Some(structured_data) = &child.structured_data {
-                match structured_data {
-                    crate::registry::StructuredData::SampleDescription(data) => {
-                        tables.stsd = Some(data.clone());
-                    }
-                    crate::registry::StructuredData::DecodingTimeToSample(data) => {
-                        tables.stts = Some(data.clone());
-                    }
-                    crate::registry::StructuredData::CompositionTimeToSample(data) => {
-                        tables.ctts = Some(data.clone());
-                    }
-                    crate::registry::StructuredData::SampleToChunk(data) => {
-                        tables.stsc = Some(data.clone());
-                    }
-                    crate::registry::StructuredData::SampleSize(data) => {
-                        tables.stsz = Some(data.clone());
-                    }
-                    crate::registry::StructuredData::SyncSample(data) => {
-                        tables.stss = Some(data.clone());
-                    }
-                    crate::registry::StructuredData::ChunkOffset(data) => {
-                        tables.stco = Some(data.clone());
-                    }
-                    crate::registry::StructuredData::ChunkOffset64(data) => {
-                        tables.co64 = Some(data.clone());
-                    }
-                    // MediaHeader, HandlerReference, and TrackHeader are not sample table data, ignore them
-                    crate::registry::StructuredData::MediaHeader(_) => {}
-                    crate::registry::StructuredData::HandlerReference(_) => {}
-                    crate::registry::StructuredData::TrackHeader(_) => {}
-                }
+impl RunCursor {
+    /// Entry covering the current sample, then advance by one sample.
+    fn step<T>(&mut self, entries: &[T], count: impl Fn(&T) -> u32) -> Option<usize> {
+        while let Some(e) = entries.get(self.entry) {
+            if self.used < count(e) {
+                self.used += 1;
+                return Some(self.entry);
             }
+            self.entry += 1;
+            self.used = 0;
         }
+        None
     }
-
-    Ok(tables)
 }
 
-fn build_sample_info<R: Read + Seek>(
-    tables: &SampleTables,
-    timescale: u32,
-    _reader: &mut R,
-) -> anyhow::Result<Vec<SampleInfo>> {
-    let mut samples = Vec::new();
-
-    // Get sample count from stsz
-    let sample_count = if let Some(stsz) = &tables.stsz {
-        stsz.sample_count
-    } else {
-        return Ok(samples);
-    };
-
-    // Calculate timing information from stts
-    let mut current_dts = 0u64;
-    let default_duration = if timescale > 0 { timescale / 24 } else { 1000 };
-
-    // Build samples using the available tables
-    for i in 0..sample_count {
-        // Get duration from stts or use default
-        let duration = if let Some(stts) = &tables.stts {
-            get_sample_duration_from_stts(stts, i).unwrap_or(default_duration)
-        } else {
-            default_duration
-        };
-
-        // Calculate PTS from DTS + composition offset
-        let composition_offset = if let Some(ctts) = &tables.ctts {
-            get_composition_offset_from_ctts(ctts, i).unwrap_or(0)
-        } else {
-            0
-        };
-
-        let pts = current_dts.saturating_add_signed(composition_offset as i64);
-
-        let sample = SampleInfo {
-            index: i,
-            dts: current_dts,
-            pts,
-            start_time: pts as f64 / timescale as f64,
-            duration,
-            rendered_offset: composition_offset as i64,
-            file_offset: get_sample_file_offset(tables, i),
-            size: get_sample_size(&tables.stsz, i),
-            is_sync: is_sync_sample(&tables.stss, i + 1), // stss uses 1-based indexing
+impl<'a> SampleIter<'a> {
+    pub fn new(track: &crate::track::Track<'a>) -> Self {
+        let stsz = track.stsz();
+        let mut iter = SampleIter {
+            timescale: track.mdhd().map_or(1000, |m| m.timescale),
+            stts: track.stts(),
+            ctts: track.ctts(),
+            stsc: track.stsc(),
+            stsz,
+            stss: track.stss(),
+            sdtp: track.sdtp(),
+            stco: track.stco(),
+            co64: track.co64(),
+            index: 0,
+            sample_count: stsz.map_or(0, |s| s.sample_count),
+            dts: 0,
+            stts_run: RunCursor::default(),
+            ctts_run: RunCursor::default(),
+            stss_pos: 0,
+            chunk: 0,
+            stsc_entry: 0,
+            sample_in_chunk: 0,
+            offset_in_chunk: 0,
         };
-
-        current_dts += duration as u64;
-        samples.push(sample);
+        iter.sample_count = iter.sample_count.min(iter.table_capacity());
+        iter
     }
 
-    Ok(samples)
-}
-
-fn get_sample_size(stsz: &Option<crate::registry::StszData>, index: u32) -> u32 {
-    if let Some(stsz) = stsz {
-        if stsz.sample_size > 0 {
-            // All samples have the same size
-            stsz.sample_size
-        } else if let Some(size) = stsz.sample_sizes.get(index as usize) {
-            *size
-        } else {
-            0
+    /// Stop after the samples that fit in a file of `file_size` bytes. A
+    /// constant-size stsz only declares its sample count, which can claim far
+    /// more samples than the file holds.
+    pub fn within_file_size(mut self, file_size: u64) -> Self {
+        if let Some(stsz) = self.stsz
+            && stsz.sample_size > 0
+        {
+            let fits = file_size / stsz.sample_size as u64;
+            self.sample_count = self.sample_count.min(fits.min(u32::MAX as u64) as u32);
         }
-    } else {
-        0
+        self
     }
-}
 
-fn is_sync_sample(stss: &Option<crate::registry::StssData>, sample_number: u32) -> bool {
-    if let Some(stss) = stss {
-        stss.sample_numbers.contains(&sample_number)
-    } else {
-        // If no stss box, all samples are sync samples
-        true
+    /// Most samples the tables can describe: the listed sizes of a
+    /// variable-size stsz, and the room stsc gives the chunk offsets.
+    fn table_capacity(&self) -> u32 {
+        let mut capacity = u64::MAX;
+        if let Some(stsz) = self.stsz
+            && stsz.sample_size == 0
+        {
+            capacity = stsz.sample_sizes.len() as u64;
+        }
+        if let Some(stsc) = self.stsc
+            && (self.stco.is_some() || self.co64.is_some())
+        {
+            let chunk_count = self.chunk_count() as u64;
+            let mut in_chunks = 0u64;
+            for (i, e) in stsc.entries.iter().enumerate() {
+                let first = (e.first_chunk as u64).clamp(1, chunk_count + 1);
+                let end = stsc
+                    .entries
+                    .get(i + 1)
+                    .map_or(chunk_count + 1, |n| n.first_chunk as u64)
+                    .clamp(first, chunk_count + 1);
+                in_chunks = in_chunks.saturating_add((end - first) * e.samples_per_chunk as u64);
+            }
+            capacity = capacity.min(in_chunks);
+        }
+        capacity.min(u32::MAX as u64) as u32
     }
-}
 
-// Helper functions for timing calculations
-fn get_sample_duration_from_stts(
-    stts: &crate::registry::SttsData,
-    sample_index: u32,
-) -> Option<u32> {
-    let mut current_sample = 0;
+    fn chunk_count(&self) -> u32 {
+        match (self.co64, self.stco) {
+            (Some(co64), _) => co64.chunk_offsets.len() as u32,
+            (None, Some(stco)) => stco.chunk_offsets.len() as u32,
+            (None, None) => 0,
+        }
+    }
 
-    for entry in &stts.entries {
-        if sample_index < current_sample + entry.sample_count {
-            return Some(entry.sample_delta);
+    fn chunk_offset(&self, chunk: u32) -> u64 {
+        match (self.co64, self.stco) {
+            (Some(co64), _) => co64.chunk_offsets.get(chunk as usize).copied().unwrap_or(0),
+            (None, Some(stco)) => stco
+                .chunk_offsets
+                .get(chunk as usize)
+                .map_or(0, |&o| o as u64),
+            (None, None) => 0,
         }
-        current_sample += entry.sample_count;
     }
 
-    // If not found, use the last entry's duration
-    stts.entries.last().map(|entry| entry.sample_delta)
-}
+    /// Samples per chunk for `self.chunk`, moving to the stsc entry that
+    /// covers it.
+    fn samples_per_chunk(&mut self, stsc: &crate::registry::StscData) -> u32 {
+        while let Some(next) = stsc.entries.get(self.stsc_entry + 1)
+            && next.first_chunk <= self.chunk + 1
+        {
+            self.stsc_entry += 1;
+        }
+        stsc.entries
+            .get(self.stsc_entry)
+            .filter(|e| e.first_chunk <= self.chunk + 1)
+            .map_or(0, |e| e.samples_per_chunk)
+    }
 
-fn get_composition_offset_from_ctts(
-    ctts: &crate::registry::CttsData,
-    sample_index: u32,
-) -> Option<i32> {
-    let mut current_sample = 0;
+    /// File offset of the next sample (0 when the tables do not place it),
+    /// then advance the chunk cursor past it.
+    fn next_file_offset(&mut self, size: u32) -> u64 {
+        let Some(stsc) = self.stsc else {
+            return 0;
+        };
+        let chunk_count = self.chunk_count();
+        // Skip chunks that hold no samples
+        while self.chunk < chunk_count && self.samples_per_chunk(stsc) == 0 {
+            self.chunk += 1;
+        }
+        if self.chunk >= chunk_count {
+            return 0;
+        }
 
-    for entry in &ctts.entries {
-        if sample_index < current_sample + entry.sample_count {
-            return Some(entry.sample_offset);
+        let offset = self
+            .chunk_offset(self.chunk)
+            .saturating_add(self.offset_in_chunk);
+        self.sample_in_chunk += 1;
+        self.offset_in_chunk += size as u64;
+        if self.sample_in_chunk >= self.samples_per_chunk(stsc) {
+            self.chunk += 1;
+            self.sample_in_chunk = 0;
+            self.offset_in_chunk = 0;
         }
-        current_sample += entry.sample_count;
+        offset
     }
-
-    // If not found, no composition offset
-    Some(0)
 }
 
-fn get_sample_file_offset(tables: &SampleTables, sample_index: u32) -> u64 {
-    // Calculate actual file offset using stsc + stco/co64 + stsz
-
-    let stsc = match &tables.stsc {
-        Some(data) => data,
-        None => return 0, // No chunk mapping available
-    };
-
-    let stsz = match &tables.stsz {
-        Some(data) => data,
-        None => return 0, // No sample sizes available
-    };
-
-    // Get chunk offsets reference (prefer 64-bit if available)
-    let (chunk_offsets_64, chunk_offsets_32) = if let Some(co64) = &tables.co64 {
-        (Some(&co64.chunk_offsets), None)
-    } else if let Some(stco) = &tables.stco {
-        (None, Some(&stco.chunk_offsets))
-    } else {
-        return 0; // No chunk offsets available
-    };
-
-    // Helper function to get chunk offset by index
-    let get_chunk_offset = |index: usize| -> u64 {
-        if let Some(offsets_64) = chunk_offsets_64 {
-            offsets_64.get(index).copied().unwrap_or(0)
-        } else if let Some(offsets_32) = chunk_offsets_32 {
-            offsets_32.get(index).copied().unwrap_or(0) as u64
-        } else {
-            0
+impl Iterator for SampleIter<'_> {
+    type Item = SampleInfo;
+
+    fn next(&mut self) -> Option<SampleInfo> {
+        if self.index >= self.sample_count {
+            return None;
         }
-    };
-
-    // Get chunk count
-    let chunk_count = if let Some(offsets_64) = chunk_offsets_64 {
-        offsets_64.len()
-    } else if let Some(offsets_32) = chunk_offsets_32 {
-        offsets_32.len()
-    } else {
-        0
-    };
-
-    // Find which chunk contains this sample (1-based sample indexing in MP4)
-    let target_sample = sample_index + 1;
-    let mut current_sample = 1u32;
-    let mut chunk_index = 0usize;
-    let mut samples_per_chunk = 0u32;
-    let mut sample_offset_in_range = 0u32;
-    let mut chunk_offset_in_range = 0u32;
-
-    for (i, entry) in stsc.entries.iter().enumerate() {
-        // Calculate how many samples are covered by previous chunks with this entry's configuration
-        let next_first_chunk = if i + 1 < stsc.entries.len() {
-            stsc.entries[i + 1].first_chunk
+        let i = self.index;
+        self.index += 1;
+
+        // Past the last stts entry the final delta repeats; without stts
+        // there is no timing to report
+        let duration = self.stts.map_or(0, |stts| {
+            self.stts_run
+                .step(&stts.entries, |e| e.sample_count)
+                .or(stts.entries.len().checked_sub(1))
+                .map_or(0, |e| stts.entries[e].sample_delta)
+        });
+        let composition_offset = self
+            .ctts
+            .and_then(|ctts| {
+                let e = self.ctts_run.step(&ctts.entries, |e| e.sample_count)?;
+                Some(ctts.entries[e].sample_offset)
+            })
+            .unwrap_or(0) as i64;
+
+        let size = match self.stsz {
+            Some(stsz) if stsz.sample_size > 0 => stsz.sample_size,
+            Some(stsz) => stsz.sample_sizes.get(i as usize).copied().unwrap_or(0),
+            None => 0,
+        };
+        let file_offset = if self.stsz.is_some() {
+            self.next_file_offset(size)
         } else {
-            chunk_count as u32 + 1 // Beyond last chunk
+            0
         };
 
-        samples_per_chunk = entry.samples_per_chunk;
-        let chunks_with_this_config = next_first_chunk - entry.first_chunk;
-        let samples_in_this_range =
-            (chunks_with_this_config as u64).saturating_mul(samples_per_chunk as u64);
-
-        if (current_sample as u64) + samples_in_this_range > target_sample as u64 {
-            // Target sample is in this range
-            // First, find the chunk containing the sample within this range
-            sample_offset_in_range = target_sample - current_sample;
-            chunk_offset_in_range = sample_offset_in_range / samples_per_chunk;
-            chunk_index = (entry.first_chunk - 1) as usize + chunk_offset_in_range as usize;
-            break;
-        }
-
-        current_sample =
-            (current_sample as u64 + samples_in_this_range).min(u32::MAX as u64) as u32;
-    }
+        // stss uses 1-based sample numbers
+        let (is_sync, sync_source) = match self.stss {
+            Some(stss) => {
+                let numbers = &stss.sample_numbers;
+                while numbers.get(self.stss_pos).is_some_and(|&n| n < i + 1) {
+                    self.stss_pos += 1;
+                }
+                (
+                    numbers.get(self.stss_pos) == Some(&(i + 1)),
+                    SyncSource::Stss,
+                )
+            }
+            None => sdtp_sync(self.sdtp.and_then(|d| d.entries.get(i as usize)))
+                .unwrap_or((true, SyncSource::Default)),
+        };
 
-    if chunk_index >= chunk_count {
-        return 0; // Chunk index out of bounds
+        let dts = self.dts;
+        self.dts += duration as u64;
+        let pts = dts.saturating_add_signed(composition_offset);
+        Some(SampleInfo {
+            index: i,
+            dts,
+            pts,
+            start_time: pts as f64 / self.timescale as f64,
+            duration,
+            rendered_offset: composition_offset,
+            file_offset,
+            size,
+            is_sync,
+            sync_source,
+            fragment_sources: None,
+        })
     }
 
-    // Get the base offset of the chunk
-    let chunk_offset = get_chunk_offset(chunk_index);
-
-    // Calculate which sample within the chunk we want
-    // Values were already calculated when breaking out of the loop
-    let sample_in_chunk = (sample_offset_in_range % samples_per_chunk) as usize;
-
-    // Sum up the sizes of preceding samples in this chunk to get the offset within chunk
-    let mut offset_in_chunk = 0u64;
-    // The chunk_start_sample is the first sample in this specific chunk (0-based for array indexing)
-    let chunk_start_sample =
-        (current_sample - 1 + chunk_offset_in_range * samples_per_chunk) as usize;
-
-    // Handle both fixed and variable sample sizes
-    if stsz.sample_size > 0 {
-        // Fixed sample size for all samples
-        offset_in_chunk = sample_in_chunk as u64 * stsz.sample_size as u64;
-    } else if !stsz.sample_sizes.is_empty() {
-        // Variable sample sizes
-        for i in 0..sample_in_chunk {
-            let sample_idx = chunk_start_sample + i;
-            if sample_idx < stsz.sample_sizes.len() {
-                offset_in_chunk += stsz.sample_sizes[sample_idx] as u64;
-            }
-        }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = (self.sample_count - self.index) as usize;
+        (n, Some(n))
     }
-
-    chunk_offset + offset_in_chunk
 }
 
+impl ExactSizeIterator for SampleIter<'_> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -698,8 +753,14 @@ mod tests {
         let tkhd_data = TkhdData {
             version: 0,
             flags: 0,
+            creation_time: 0,
+            modification_time: 0,
+            creation_date: None,
+            modification_date: None,
             track_id: 42,
             duration: 48000,
+            alternate_group: 0,
+            matrix: [0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x4000_0000],
             width: 1920.0,
             height: 1080.0,
         };
@@ -711,6 +772,7 @@ mod tests {
             payload_offset: None,
             payload_size: None,
             typ: "tkhd".to_string(),
+            typ_hex: "746b6864".to_string(),
             uuid: None,
             version: Some(0),
             flags: Some(0),
@@ -719,6 +781,8 @@ mod tests {
             decoded: None,
             structured_data: Some(StructuredData::TrackHeader(tkhd_data)),
             children: None,
+            media_ranges: None,
+            peek: None,
         };
 
         let trak_box = crate::Box {
@@ -728,6 +792,7 @@ mod tests {
             payload_offset: None,
             payload_size: None,
             typ: "trak".to_string(),
+            typ_hex: "7472616b".to_string(),
             uuid: None,
             version: None,
             flags: None,
@@ -736,6 +801,8 @@ mod tests {
             decoded: None,
             structured_data: None,
             children: Some(vec![tkhd_box]),
+            media_ranges: None,
+            peek: None,
         };
 
         // Test that we can extract the correct track ID
@@ -750,8 +817,14 @@ mod tests {
             let tkhd_data = TkhdData {
                 version: 0,
                 flags: 0,
+                creation_time: 0,
+                modification_time: 0,
+                creation_date: None,
+                modification_date: None,
                 track_id: expected_id,
                 duration: 24000,
+                alternate_group: 0,
+                matrix: [0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x4000_0000],
                 width: 0.0,
                 height: 0.0,
             };
@@ -763,6 +836,7 @@ mod tests {
                 payload_offset: None,
                 payload_size: None,
                 typ: "tkhd".to_string(),
+                typ_hex: "746b6864".to_string(),
                 uuid: None,
                 version: Some(0),
                 flags: Some(0),
@@ -771,6 +845,8 @@ mod tests {
                 decoded: None,
                 structured_data: Some(StructuredData::TrackHeader(tkhd_data)),
                 children: None,
+                media_ranges: None,
+                peek: None,
             };
 
             let trak_box = crate::Box {
@@ -780,6 +856,7 @@ mod tests {
                 payload_offset: None,
                 payload_size: None,
                 typ: "trak".to_string(),
+                typ_hex: "7472616b".to_string(),
                 uuid: None,
                 version: None,
                 flags: None,
@@ -788,6 +865,8 @@ mod tests {
                 decoded: None,
                 structured_data: None,
                 children: Some(vec![tkhd_box]),
+                media_ranges: None,
+                peek: None,
             };
 
             let track_id = find_track_id(&trak_box).unwrap();
@@ -805,6 +884,7 @@ mod tests {
             payload_offset: None,
             payload_size: None,
             typ: "trak".to_string(),
+            typ_hex: "7472616b".to_string(),
             uuid: None,
             version: None,
             flags: None,
@@ -813,6 +893,8 @@ mod tests {
             decoded: None,
             structured_data: None,
             children: Some(vec![]),
+            media_ranges: None,
+            peek: None,
         };
 
         let result = find_track_id(&trak_box);
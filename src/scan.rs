@@ -0,0 +1,237 @@
+use crate::boxes::{BoxHeader, FourCC};
+use crate::known_boxes::KnownBox;
+use crate::parser::{meta_is_full_box, read_box_header};
+use crate::util::read_up_to;
+use std::io::{Read, Seek, SeekFrom};
+
+/// Header-only iterator over every box of a given type in a file.
+///
+/// Created by [`scan_for`]. The scanner reads box headers only: containers are
+/// entered, every other payload is skipped with a relative seek, so no tree is
+/// built and media data is never read.
+pub struct BoxScanner<R> {
+    reader: R,
+    target: FourCC,
+    /// End offsets of the containers we are currently inside (innermost last).
+    ends: Vec<u64>,
+    file_len: Option<u64>,
+    done: bool,
+}
+
+/// Iterate over the headers of all boxes with type `fourcc`, at any depth.
+///
+/// This walks the file header by header, descending into known containers and
+/// seeking over everything else, so finding every `moof` or `pssh` in a very
+/// large file costs roughly one small read per box.
+///
+/// Iteration stops at the first unreadable or invalid header.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{FourCC, scan_for};
+/// use std::fs::File;
+///
+/// let file = File::open("fragmented.mp4")?;
+/// for hdr in scan_for(file, FourCC(*b"moof")) {
+///     println!("moof @ {:#x} ({} bytes)", hdr.start, hdr.size);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn scan_for<R: Read + Seek>(reader: R, fourcc: FourCC) -> BoxScanner<R> {
+    BoxScanner {
+        reader,
+        target: fourcc,
+        ends: Vec::new(),
+        file_len: None,
+        done: false,
+    }
+}
+
+impl<R: Read + Seek> BoxScanner<R> {
+    /// Borrow the underlying reader (e.g. a [`CountingReader`](crate::util::CountingReader)
+    /// to inspect how many bytes the scan actually read).
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consume the scanner and return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn next_header(&mut self) -> std::io::Result<Option<BoxHeader>> {
+        let file_len = match self.file_len {
+            Some(len) => len,
+            None => {
+                let len = self.reader.seek(SeekFrom::End(0))?;
+                self.reader.seek(SeekFrom::Start(0))?;
+                self.file_len = Some(len);
+                len
+            }
+        };
+
+        loop {
+            let pos = self.reader.stream_position()?;
+
+            // Pop every container we have walked past.
+            while let Some(&end) = self.ends.last() {
+                if pos >= end {
+                    self.ends.pop();
+                } else {
+                    break;
+                }
+            }
+
+            let parent_end = self.ends.last().copied().unwrap_or(file_len);
+            if pos >= parent_end {
+                return Ok(None);
+            }
+
+            let h = match read_box_header(&mut self.reader) {
+                Ok(h) => h,
+                Err(_) => return Ok(None),
+            };
+            let box_end = if h.size == 0 {
+                parent_end
+            } else {
+                h.start + h.size
+            };
+            let content_start = h.start + h.header_size;
+
+            if KnownBox::from(h.typ).is_container() && box_end > content_start {
+                // Already positioned at the first child.
+                self.ends.push(box_end);
+            } else if &h.typ.0 == b"meta" && box_end > content_start {
+                // Step over the version/flags of an ISO meta box
+                if meta_is_full_box(&mut self.reader, &h, box_end)? {
+                    self.reader.seek(SeekFrom::Current(4))?;
+                }
+                self.ends.push(box_end);
+            } else {
+                // Relative skip: never touches payload bytes, and stays cheap on
+                // sparse or remote files where only reads cost anything.
+                let skip = box_end.saturating_sub(content_start);
+                match i64::try_from(skip) {
+                    Ok(skip) => self.reader.seek(SeekFrom::Current(skip))?,
+                    Err(_) => self.reader.seek(SeekFrom::Start(box_end))?,
+                };
+            }
+
+            if h.typ == self.target {
+                return Ok(Some(h));
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for BoxScanner<R> {
+    type Item = BoxHeader;
+
+    fn next(&mut self) -> Option<BoxHeader> {
+        if self.done {
+            return None;
+        }
+        match self.next_header() {
+            Ok(Some(h)) => Some(h),
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+/// Box types [`scan_for_boxes`] looks for: the top-level boxes and the
+/// first children of moov and moof.
+const RECOGNIZED: [&[u8; 4]; 16] = [
+    b"ftyp", b"styp", b"moov", b"mvhd", b"trak", b"mvex", b"udta", b"moof", b"mfhd", b"traf",
+    b"mdat", b"free", b"skip", b"wide", b"sidx", b"mfra",
+];
+
+/// A byte position that looks like the header of a recognized box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoxCandidate {
+    pub offset: u64,
+    pub typ: FourCC,
+    /// Declared size including the header; 0 means "to the end of the file"
+    pub size: u64,
+    /// 8, or 16 with a 64-bit size
+    pub header_size: u64,
+    /// The declared size fits in the file
+    pub complete: bool,
+}
+
+/// Brute-force scan of the whole file for recognizable box headers
+/// ([`RECOGNIZED`] types with a sane size), ignoring the box tree.
+///
+/// Unlike [`scan_for`] this reads every byte and does not trust any size, so
+/// it finds boxes in damaged files: a moov or moof left behind by an
+/// interrupted recording, or an mdat whose header was never finalized.
+/// Candidates are in file order and may overlap (a moov and its mvhd) or be
+/// false positives inside media data.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::scan::scan_for_boxes;
+/// use std::fs::File;
+///
+/// for c in scan_for_boxes(File::open("broken.mp4")?)? {
+///     println!("{} @ {:#x} ({} bytes)", c.typ, c.offset, c.size);
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn scan_for_boxes<R: Read + Seek>(mut reader: R) -> std::io::Result<Vec<BoxCandidate>> {
+    const WINDOW: u64 = 64 * 1024;
+    // Windows overlap by a 64-bit header so none is split
+    const OVERLAP: u64 = 15;
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let mut found = Vec::new();
+    let mut buf = Vec::new();
+    let mut pos = 0;
+    while pos + 8 <= file_len {
+        let len = (file_len - pos).min(WINDOW + OVERLAP);
+        buf.resize(len as usize, 0);
+        reader.seek(SeekFrom::Start(pos))?;
+        let n = read_up_to(&mut reader, &mut buf)?;
+        // The last window also checks the final positions
+        let last = pos + len >= file_len || (n as u64) < len;
+        let scan_to = if last { n } else { WINDOW as usize };
+        for i in 0..scan_to.min(n.saturating_sub(7)) {
+            if let Some(c) = candidate_at(&buf[i..n], pos + i as u64, file_len) {
+                found.push(c);
+            }
+        }
+        if last {
+            break;
+        }
+        pos += WINDOW;
+    }
+    Ok(found)
+}
+
+/// Candidate whose header starts at `bytes[0]`, file offset `offset`.
+fn candidate_at(bytes: &[u8], offset: u64, file_len: u64) -> Option<BoxCandidate> {
+    let typ: [u8; 4] = bytes[4..8].try_into().unwrap();
+    if !RECOGNIZED.contains(&&typ) {
+        return None;
+    }
+    let size32 = u32::from_be_bytes(bytes[..4].try_into().unwrap());
+    let (size, header_size) = match size32 {
+        0 => (0, 8),
+        1 => {
+            let large = bytes.get(8..16)?;
+            (u64::from_be_bytes(large.try_into().unwrap()), 16)
+        }
+        size => (size as u64, 8),
+    };
+    if size != 0 && size < header_size {
+        return None;
+    }
+    Some(BoxCandidate {
+        offset,
+        typ: FourCC(typ),
+        size,
+        header_size,
+        complete: offset.checked_add(size).is_some_and(|end| end <= file_len),
+    })
+}
@@ -0,0 +1,141 @@
+//! Sync sample (keyframe) index for seeking.
+
+use crate::registry::StructuredData;
+use crate::samples::TrackSamples;
+use serde::Serialize;
+
+/// A sync sample a player can start decoding from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SeekPoint {
+    /// Index into [`TrackSamples::samples`]; None for points read from tfra
+    pub sample_index: Option<u32>,
+    /// Presentation time in media timescale units
+    pub pts: u64,
+    pub seconds: f64,
+    /// File offset of the sample data
+    pub offset: u64,
+    pub size: u32,
+    /// The moof holding the sample, for fragmented tracks
+    pub moof_offset: Option<u64>,
+}
+
+/// Every sync sample of a track, in decode order.
+///
+/// Sync samples come from stss for the sample table (every sample when
+/// there is no stss) and from the trun/tfhd/trex sample flags for movie
+/// fragments.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{seek::seek_index, track_samples_from_path};
+///
+/// for track in track_samples_from_path("video.mp4")? {
+///     let points = seek_index(&track);
+///     // Last keyframe at or before 12.5 s
+///     if let Some(p) = points.iter().rev().find(|p| p.seconds <= 12.5) {
+///         println!("track {}: seek to byte {}", track.track_id, p.offset);
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn seek_index(track: &TrackSamples) -> Vec<SeekPoint> {
+    track
+        .samples
+        .iter()
+        .filter(|s| s.is_sync)
+        .map(|s| SeekPoint {
+            sample_index: Some(s.index),
+            pts: s.pts,
+            seconds: s.start_time,
+            offset: s.file_offset,
+            size: s.size,
+            moof_offset: track
+                .fragments
+                .iter()
+                .find(|f| (f.first_sample..f.first_sample + f.sample_count).contains(&s.index))
+                .map(|f| f.moof_offset),
+        })
+        .collect()
+}
+
+/// Sync samples of `track_id` listed in the mfra/tfra boxes of a
+/// fragmented file.
+///
+/// Each tfra entry is resolved against the moof it points to, so `boxes`
+/// must hold both (from [`crate::get_boxes`] with `decode = true`).
+/// Entries whose moof, traf, trun or sample cannot be found are skipped.
+pub fn seek_index_from_tfra(boxes: &[crate::Box], track_id: u32) -> Vec<SeekPoint> {
+    let timescale = crate::track::tracks(boxes)
+        .into_iter()
+        .find(|t| t.track_id() == Some(track_id))
+        .and_then(|t| t.mdhd())
+        .map_or(0, |m| m.timescale);
+    let trex = crate::fragments::trex_defaults(boxes);
+
+    let tfra = boxes
+        .iter()
+        .filter(|b| b.typ == "mfra")
+        .flat_map(|b| b.children.iter().flatten())
+        .find_map(|b| match &b.structured_data {
+            Some(StructuredData::TrackFragmentRandomAccess(t)) if t.track_id == track_id => Some(t),
+            _ => None,
+        });
+
+    let mut out = Vec::new();
+    for entry in tfra.iter().flat_map(|t| &t.entries) {
+        let Some(moof) = boxes
+            .iter()
+            .find(|b| b.typ == "moof" && b.offset == entry.moof_offset)
+        else {
+            continue;
+        };
+        let traf_index = entry.traf_number.saturating_sub(1) as usize;
+        let Some(traf_box) = moof
+            .children
+            .iter()
+            .flatten()
+            .filter(|b| b.typ == "traf")
+            .nth(traf_index)
+        else {
+            continue;
+        };
+        // Samples of the truns before the one the entry points into
+        let run_lengths: Vec<usize> = traf_box
+            .children
+            .iter()
+            .flatten()
+            .filter_map(|b| match &b.structured_data {
+                Some(StructuredData::TrackFragmentRun(r)) => Some(r.entries.len()),
+                _ => None,
+            })
+            .collect();
+        let trun_index = entry.trun_number.saturating_sub(1) as usize;
+        if trun_index >= run_lengths.len() {
+            continue;
+        }
+        let sample_index = run_lengths[..trun_index].iter().sum::<usize>()
+            + entry.sample_number.saturating_sub(1) as usize;
+
+        let trafs = crate::fragments::moof_trafs(moof, &trex);
+        let Some(sample) = trafs
+            .get(traf_index)
+            .filter(|t| t.track_id == track_id)
+            .and_then(|t| t.samples.get(sample_index))
+        else {
+            continue;
+        };
+        out.push(SeekPoint {
+            sample_index: None,
+            pts: entry.time,
+            seconds: if timescale > 0 {
+                entry.time as f64 / timescale as f64
+            } else {
+                0.0
+            },
+            offset: sample.file_offset,
+            size: sample.size,
+            moof_offset: Some(moof.offset),
+        });
+    }
+    out
+}
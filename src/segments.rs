@@ -0,0 +1,298 @@
+//! DASH segment maps from `sidx` boxes and segment classification.
+
+use crate::registry::{SidxData, StructuredData};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::Path;
+
+/// Nested sidx chains deeper than this are treated as broken.
+const MAX_SIDX_DEPTH: usize = 16;
+
+/// Resolution stops after this many segments.
+const MAX_SEGMENTS: usize = 1 << 20;
+
+/// One media subsegment referenced from a sidx.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Segment {
+    /// sidx reference_ID (usually the track ID)
+    pub reference_id: u32,
+    /// Absolute byte offset of the subsegment in the file
+    pub offset: u64,
+    pub size: u64,
+    pub timescale: u32,
+    /// Presentation time range `[start_time, end_time)` in `timescale` units
+    pub start_time: u64,
+    pub end_time: u64,
+    pub starts_with_sap: bool,
+    pub sap_type: u8,
+}
+
+impl Segment {
+    /// Inclusive byte range as used by DASH `mediaRange`, e.g. "1234-5677".
+    pub fn media_range(&self) -> String {
+        format!(
+            "{}-{}",
+            self.offset,
+            self.offset.saturating_add(self.size.saturating_sub(1))
+        )
+    }
+
+    pub fn start_seconds(&self) -> f64 {
+        self.start_time as f64 / self.timescale as f64
+    }
+
+    pub fn end_seconds(&self) -> f64 {
+        self.end_time as f64 / self.timescale as f64
+    }
+}
+
+/// What a file or buffer holds, from a DASH/CMAF packaging point of view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    /// moov without media data (ftyp + moov, typically with mvex)
+    Initialization,
+    /// Fragments without a moov (optionally styp/sidx + moof + mdat)
+    Media,
+    /// moov followed by fragments in the same file
+    SelfInitializing,
+    /// moov with the media in mdat, no fragments
+    Progressive,
+    /// None of the above (e.g. an empty buffer or a lone mdat)
+    Unknown,
+}
+
+/// Classify a parsed file by which top-level boxes it has.
+///
+/// Only the top-level box types are looked at, so `boxes` may come from
+/// [`crate::get_boxes`] with or without decoding.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, segments::{SegmentKind, classify_segment}};
+/// use std::fs::File;
+///
+/// let mut file = File::open("init.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, false)?;
+/// assert_eq!(classify_segment(&boxes), SegmentKind::Initialization);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn classify_segment(boxes: &[crate::Box]) -> SegmentKind {
+    let has = |typ: &str| boxes.iter().any(|b| b.typ == typ);
+    let (moov, moof, mdat, styp) = (has("moov"), has("moof"), has("mdat"), has("styp"));
+
+    match (moov, moof) {
+        (true, true) => SegmentKind::SelfInitializing,
+        (true, false) if mdat => SegmentKind::Progressive,
+        (true, false) => SegmentKind::Initialization,
+        (false, _) if moof || styp => SegmentKind::Media,
+        (false, _) => SegmentKind::Unknown,
+    }
+}
+
+/// How a file delivers its media.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerProfile {
+    /// All samples described by the moov sample tables
+    Progressive,
+    /// moof fragments without a sidx, as in the DASH live profile
+    Fragmented,
+    /// moof fragments indexed by sidx, as in the DASH on-demand profile
+    FragmentedIndexed,
+    /// Samples in the moov sample tables and in moof fragments
+    Hybrid,
+    /// No moov and no fragments
+    Unknown,
+}
+
+/// Result of [`container_profile`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProfileReport {
+    pub profile: ContainerProfile,
+    /// The observations the profile was derived from, e.g. "moof boxes: 3"
+    pub evidence: Vec<String>,
+}
+
+/// Classify a file by delivery type.
+///
+/// Looks at mvex, moof and sidx boxes and at the number of samples in the
+/// moov sample tables; the latter needs a tree parsed with `decode = true`,
+/// without which a hybrid file reads as fragmented.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, segments::{ContainerProfile, container_profile}};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let report = container_profile(&get_boxes(&mut file, size, true)?);
+/// if report.profile == ContainerProfile::Progressive {
+///     println!("plain MP4: {}", report.evidence.join(", "));
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn container_profile(boxes: &[crate::Box]) -> ProfileReport {
+    let mut evidence = Vec::new();
+    let count = |typ: &str| boxes.iter().filter(|b| b.typ == typ).count();
+    let (moofs, sidxs) = (count("moof"), count("sidx"));
+
+    let moov = boxes.iter().find(|b| b.typ == "moov");
+    let mvex = moov.is_some_and(|m| m.children.iter().flatten().any(|c| c.typ == "mvex"));
+    let table_samples: u64 = crate::track::tracks(boxes)
+        .iter()
+        .filter_map(|t| t.stsz())
+        .map(|s| s.sample_count as u64)
+        .sum();
+
+    match moov {
+        Some(m) => evidence.push(format!("moov at offset {}", m.offset)),
+        None => evidence.push("no moov".to_string()),
+    }
+    if mvex {
+        evidence.push("moov has mvex".to_string());
+    }
+    if table_samples > 0 {
+        evidence.push(format!("moov sample tables: {} samples", table_samples));
+    }
+    if moofs > 0 {
+        evidence.push(format!("moof boxes: {}", moofs));
+    }
+    if sidxs > 0 {
+        evidence.push(format!("sidx boxes: {}", sidxs));
+    }
+
+    let fragmented = moofs > 0 || mvex;
+    let profile = match (table_samples > 0, fragmented) {
+        (true, true) => ContainerProfile::Hybrid,
+        (false, true) if sidxs > 0 => ContainerProfile::FragmentedIndexed,
+        (false, true) => ContainerProfile::Fragmented,
+        (_, false) if moov.is_some() => ContainerProfile::Progressive,
+        (_, false) => ContainerProfile::Unknown,
+    };
+
+    ProfileReport { profile, evidence }
+}
+
+/// A top-level sidx with its absolute position.
+struct IndexedSidx<'a> {
+    offset: u64,
+    end: u64,
+    data: &'a SidxData,
+}
+
+/// Resolve the sidx boxes of a file into absolute media segments.
+///
+/// See [`segment_index_from_boxes`].
+///
+/// # Example
+/// ```no_run
+/// use mp4box::segments::segment_index;
+///
+/// for seg in segment_index("dash.mp4")? {
+///     println!("{:>8.3}s  bytes {}", seg.start_seconds(), seg.media_range());
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn segment_index(path: impl AsRef<Path>) -> anyhow::Result<Vec<Segment>> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let boxes = crate::get_boxes(&mut file, size, true)?;
+    Ok(segment_index_from_boxes(&boxes))
+}
+
+/// Resolve the top-level sidx boxes of a decoded tree into media segments.
+///
+/// References to other sidx boxes (hierarchical indexes) are followed, so
+/// only media subsegments are returned, in file order of their root sidx.
+/// Offsets are absolute: each sidx's `first_offset` is counted from the
+/// first byte after that sidx. Each nested sidx is resolved once, only
+/// when it lies after the sidx pointing at it; references whose offset
+/// overflows are dropped.
+///
+/// Requires a tree produced with `decode = true`.
+pub fn segment_index_from_boxes(boxes: &[crate::Box]) -> Vec<Segment> {
+    let sidxs: Vec<IndexedSidx> = boxes
+        .iter()
+        .filter_map(|b| match &b.structured_data {
+            Some(StructuredData::SegmentIndex(data)) => Some(IndexedSidx {
+                offset: b.offset,
+                end: b.offset.saturating_add(b.size),
+                data,
+            }),
+            _ => None,
+        })
+        .collect();
+
+    // A root is any sidx that no other sidx points at
+    let nested: HashSet<u64> = sidxs
+        .iter()
+        .flat_map(|s| {
+            references(s)
+                .filter(|(r, _)| r.references_sidx)
+                .map(|(_, offset)| offset)
+        })
+        .collect();
+
+    let by_offset: HashMap<u64, &IndexedSidx> = sidxs.iter().map(|s| (s.offset, s)).collect();
+    let mut visited = HashSet::new();
+    let mut out = Vec::new();
+    for root in sidxs.iter().filter(|s| !nested.contains(&s.offset)) {
+        resolve(root, &by_offset, 0, &mut visited, &mut out);
+    }
+    out
+}
+
+/// Each reference of a sidx with its absolute byte offset, up to the first
+/// offset that overflows.
+fn references<'a>(
+    sidx: &'a IndexedSidx,
+) -> impl Iterator<Item = (&'a crate::registry::SidxReference, u64)> + 'a {
+    let first = sidx.end.checked_add(sidx.data.first_offset);
+    sidx.data.references.iter().scan(first, |next, r| {
+        let offset = (*next)?;
+        *next = offset.checked_add(r.referenced_size as u64);
+        Some((r, offset))
+    })
+}
+
+fn resolve(
+    sidx: &IndexedSidx,
+    all: &HashMap<u64, &IndexedSidx>,
+    depth: usize,
+    visited: &mut HashSet<u64>,
+    out: &mut Vec<Segment>,
+) {
+    if depth > MAX_SIDX_DEPTH || !visited.insert(sidx.offset) {
+        return;
+    }
+    let mut time = sidx.data.earliest_presentation_time;
+
+    for (r, offset) in references(sidx) {
+        if out.len() >= MAX_SEGMENTS {
+            return;
+        }
+        let end_time = time.saturating_add(r.subsegment_duration as u64);
+        if r.references_sidx {
+            // Only forward: a child before its parent could loop back
+            if let Some(child) = all.get(&offset).filter(|_| offset >= sidx.end) {
+                resolve(child, all, depth + 1, visited, out);
+            }
+        } else {
+            out.push(Segment {
+                reference_id: sidx.data.reference_id,
+                offset,
+                size: r.referenced_size as u64,
+                timescale: sidx.data.timescale,
+                start_time: time,
+                end_time,
+                starts_with_sap: r.starts_with_sap,
+                sap_type: r.sap_type,
+            });
+        }
+        time = end_time;
+    }
+}
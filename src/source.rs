@@ -0,0 +1,260 @@
+//! Positional byte sources for the parser.
+//!
+//! [`RandomAccess`] is the minimal interface a storage backend has to
+//! provide: the length and "give me `len` bytes at `offset`". It takes
+//! `&self` and carries no cursor, so one source can be shared between
+//! threads and parses, and cloud-storage backends (S3 `GetObject` with a
+//! `Range`, GCS, Azure blobs) map onto it directly. [`Source`] gives each
+//! parse its own cursor and read-ahead buffer over a `RandomAccess`, so the
+//! many small header reads of a parse become a few large ranged reads.
+
+use crate::api::{Box, ParseOptions, get_boxes_with_options};
+use crate::parser::ParseWarning;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+/// Read-ahead of a [`Source`] unless set with [`Source::with_read_ahead`].
+pub const DEFAULT_READ_AHEAD: usize = 64 * 1024;
+
+/// A byte source addressed by offset.
+pub trait RandomAccess {
+    /// Total length in bytes.
+    fn size(&self) -> io::Result<u64>;
+
+    /// Fill `buf` with the bytes at `offset` and return how many were read:
+    /// fewer than `buf.len()` only at the end of the source, none past it.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize>;
+}
+
+impl RandomAccess for [u8] {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        let start = usize::try_from(offset).map_or(self.len(), |o| o.min(self.len()));
+        let n = buf.len().min(self.len() - start);
+        buf[..n].copy_from_slice(&self[start..start + n]);
+        Ok(n)
+    }
+}
+
+impl RandomAccess for Vec<u8> {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        RandomAccess::read_at(self.as_slice(), offset, buf)
+    }
+}
+
+/// Positional reads (`pread` / `ReadFile` with an offset) that leave the
+/// file's own cursor alone.
+impl RandomAccess for File {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        #[cfg(unix)]
+        use std::os::unix::fs::FileExt;
+        #[cfg(windows)]
+        use std::os::windows::fs::FileExt;
+
+        let mut filled = 0;
+        while filled < buf.len() {
+            let Some(at) = offset.checked_add(filled as u64) else {
+                break;
+            };
+            #[cfg(unix)]
+            let n = FileExt::read_at(self, &mut buf[filled..], at);
+            #[cfg(windows)]
+            let n = self.seek_read(&mut buf[filled..], at);
+            match n {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(filled)
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl RandomAccess for memmap2::Mmap {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        RandomAccess::read_at(&self[..], offset, buf)
+    }
+}
+
+impl<T: RandomAccess + ?Sized> RandomAccess for &T {
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(offset, buf)
+    }
+}
+
+impl<T: RandomAccess + ?Sized> RandomAccess for Arc<T> {
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(offset, buf)
+    }
+}
+
+impl<T: RandomAccess + ?Sized> RandomAccess for std::boxed::Box<T> {
+    fn size(&self) -> io::Result<u64> {
+        (**self).size()
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read_at(offset, buf)
+    }
+}
+
+/// Read+Seek over a [`RandomAccess`], with a cursor of its own.
+///
+/// Seeking only moves the cursor. Reads smaller than the read-ahead are
+/// served from a buffer refilled with one `read_at` of up to
+/// [`DEFAULT_READ_AHEAD`] bytes; larger reads go to the source directly.
+#[derive(Clone)]
+pub struct Source<A> {
+    inner: A,
+    len: u64,
+    pos: u64,
+    read_ahead: usize,
+    buf: Vec<u8>,
+    /// Source offset of `buf[0]`
+    buf_start: u64,
+}
+
+impl<A: RandomAccess> Source<A> {
+    pub fn new(inner: A) -> io::Result<Self> {
+        let len = inner.size()?;
+        Ok(Source {
+            inner,
+            len,
+            pos: 0,
+            read_ahead: DEFAULT_READ_AHEAD,
+            buf: Vec::new(),
+            buf_start: 0,
+        })
+    }
+
+    /// Bytes fetched per `read_at` for small reads; 0 sends every read to
+    /// the source as is.
+    pub fn with_read_ahead(mut self, read_ahead: usize) -> Self {
+        self.read_ahead = read_ahead;
+        self.buf = Vec::new();
+        self
+    }
+
+    /// The buffered bytes from the cursor on.
+    fn buffered(&self) -> &[u8] {
+        match self.pos.checked_sub(self.buf_start) {
+            Some(at) if at < self.buf.len() as u64 => &self.buf[at as usize..],
+            _ => &[],
+        }
+    }
+
+    /// Length of the source when the `Source` was made.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get_ref(&self) -> &A {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+}
+
+impl<A> std::fmt::Debug for Source<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Source")
+            .field("len", &self.len)
+            .field("pos", &self.pos)
+            .field("read_ahead", &self.read_ahead)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<A: RandomAccess> Read for Source<A> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len {
+            return Ok(0);
+        }
+        let remaining = usize::try_from(self.len - self.pos).unwrap_or(usize::MAX);
+        if self.buffered().is_empty() {
+            if buf.len() >= self.read_ahead {
+                let want = buf.len().min(remaining);
+                let n = self.inner.read_at(self.pos, &mut buf[..want])?;
+                self.pos += n as u64;
+                return Ok(n);
+            }
+            self.buf.resize(self.read_ahead.min(remaining), 0);
+            let n = self.inner.read_at(self.pos, &mut self.buf)?;
+            self.buf.truncate(n);
+            self.buf_start = self.pos;
+        }
+        let data = self.buffered();
+        let n = data.len().min(buf.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl<A: RandomAccess> Seek for Source<A> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::Current(d) => self.pos.checked_add_signed(d),
+            SeekFrom::End(d) => self.len.checked_add_signed(d),
+        };
+        self.pos = target
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "seek before the start"))?;
+        Ok(self.pos)
+    }
+}
+
+/// Parse a [`RandomAccess`] source as `options` says, like
+/// [`get_boxes_with_options`] does for a reader. The source is only read
+/// through `&self`, so several parses may share it.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::ParseOptions;
+/// use mp4box::source::get_boxes_from;
+///
+/// let file = std::fs::File::open("video.mp4")?;
+/// let (boxes, _) = get_boxes_from(&file, &ParseOptions::new())?;
+/// println!("{} top-level boxes", boxes.len());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn get_boxes_from<A: RandomAccess + ?Sized>(
+    source: &A,
+    options: &ParseOptions,
+) -> anyhow::Result<(Vec<Box>, Vec<ParseWarning>)> {
+    let mut reader = Source::new(source)?;
+    let size = reader.len();
+    get_boxes_with_options(&mut reader, size, options)
+}
@@ -0,0 +1,101 @@
+//! Sample size statistics for a track.
+
+use crate::samples::SampleInfo;
+use serde::Serialize;
+
+/// Distribution of the sample sizes of one track, in bytes.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SizeStats {
+    pub count: usize,
+    pub total: u64,
+    pub min: u32,
+    pub max: u32,
+    pub mean: f64,
+    /// Mean of the two middle sizes when `count` is even
+    pub median: f64,
+    /// Nearest-rank percentiles
+    pub p90: u32,
+    pub p95: u32,
+    pub p99: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub histogram: Option<Vec<HistogramBin>>,
+}
+
+/// Samples whose size falls in `start..=end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HistogramBin {
+    pub start: u32,
+    pub end: u32,
+    pub count: usize,
+}
+
+/// Size statistics over `samples`, such as [`TrackSamples::samples`](crate::TrackSamples),
+/// which covers stsz and trun sizes alike.
+///
+/// With `histogram_bins`, the range `min..=max` is split into that many
+/// equal-width bins (fewer when the range is narrower than the bin count).
+/// None when `samples` is empty.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{stats::size_stats, track_samples_from_path};
+///
+/// for track in track_samples_from_path("video.mp4")? {
+///     if let Some(s) = size_stats(&track.samples, None) {
+///         println!("track {}: median {} bytes, max {}", track.track_id, s.median, s.max);
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn size_stats(samples: &[SampleInfo], histogram_bins: Option<usize>) -> Option<SizeStats> {
+    let mut sizes: Vec<u32> = samples.iter().map(|s| s.size).collect();
+    if sizes.is_empty() {
+        return None;
+    }
+    sizes.sort_unstable();
+
+    let count = sizes.len();
+    let total: u64 = sizes.iter().map(|&s| s as u64).sum();
+    let median = if count.is_multiple_of(2) {
+        (sizes[count / 2 - 1] as f64 + sizes[count / 2] as f64) / 2.0
+    } else {
+        sizes[count / 2] as f64
+    };
+    let percentile = |p: usize| sizes[(count * p).div_ceil(100).max(1) - 1];
+
+    Some(SizeStats {
+        count,
+        total,
+        min: sizes[0],
+        max: sizes[count - 1],
+        mean: total as f64 / count as f64,
+        median,
+        p90: percentile(90),
+        p95: percentile(95),
+        p99: percentile(99),
+        histogram: histogram_bins
+            .filter(|&b| b > 0)
+            .map(|bins| histogram(&sizes, bins)),
+    })
+}
+
+fn histogram(sorted: &[u32], bins: usize) -> Vec<HistogramBin> {
+    let (min, max) = (sorted[0] as u64, sorted[sorted.len() - 1] as u64);
+    let width = (max - min + 1).div_ceil(bins as u64);
+
+    let mut out = Vec::new();
+    let mut rest = sorted;
+    let mut start = min;
+    while start <= max {
+        let end = (start + width - 1).min(max);
+        let n = rest.partition_point(|&s| s as u64 <= end);
+        out.push(HistogramBin {
+            start: start as u32,
+            end: end as u32,
+            count: n,
+        });
+        rest = &rest[n..];
+        start = end + 1;
+    }
+    out
+}
@@ -0,0 +1,170 @@
+//! Forward-only parsing of readers that cannot seek: stdin, pipes and
+//! network streams.
+
+use crate::api::{Box, ParseOptions, build_box, link_media_ranges, payload_needed};
+use crate::boxes::{BoxRef, NodeKind};
+use crate::known_boxes::KnownBox;
+use crate::parser::{ParseWarning, WarningKind, parse_children_with, read_box_header};
+use crate::util::{Window, read_up_to};
+use anyhow::bail;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// Leaf boxes bigger than this are skipped rather than held in memory.
+pub const STREAM_BUFFER_LIMIT: u64 = 64 * 1024 * 1024;
+
+/// Parse a stream read strictly front to back, as `options` says.
+///
+/// Each top-level box header is read in turn. Boxes whose payload is needed
+/// (containers such as moov and moof, and leaves with decoders) are read
+/// into memory one at a time and parsed like [`get_boxes_with_options`]
+/// would; mdat, free space and leaves over [`STREAM_BUFFER_LIMIT`] are read
+/// past without being kept. Memory use is bounded by the largest buffered
+/// box, not the stream.
+///
+/// Offsets in the result are stream offsets, as if the stream were a file.
+/// A stream that ends inside a box is an error in strict mode and a
+/// [`Truncated`](WarningKind::Truncated) warning in lenient mode.
+///
+/// [`get_boxes_with_options`]: crate::get_boxes_with_options
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{ParseOptions, stream::get_boxes_streaming};
+///
+/// let (boxes, _) = get_boxes_streaming(std::io::stdin().lock(), &ParseOptions::new())?;
+/// for b in &boxes {
+///     println!("{} @ {}", b.typ, b.offset);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn get_boxes_streaming<R: Read>(
+    mut r: R,
+    options: &ParseOptions,
+) -> anyhow::Result<(Vec<Box>, Vec<ParseWarning>)> {
+    let mut state = options.parse_state();
+    let mut tree = Vec::new();
+    let mut offset = 0u64;
+
+    loop {
+        let Some(header) = read_header_bytes(&mut r, offset, options.lenient, &mut state.list)?
+        else {
+            break;
+        };
+        let mut window = Window::new(offset, header);
+        let h = match read_box_header(&mut window) {
+            Ok(h) => h,
+            Err(e) if options.lenient => {
+                let message = e.to_string();
+                let kind = WarningKind::BadHeader { message };
+                state.push(offset, None, kind);
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let data_offset = h.start + h.header_size;
+        let known = KnownBox::from(h.typ);
+        // Size 0: the box runs to the end of the stream
+        let declared = (h.size != 0).then(|| h.size - h.header_size);
+
+        let wanted = payload_needed(&h, options)
+            && (known.is_container()
+                || &h.typ.0 == b"meta"
+                || declared.is_some_and(|len| len <= STREAM_BUFFER_LIMIT));
+
+        if wanted {
+            let mut payload = Vec::new();
+            match declared {
+                Some(len) => {
+                    (&mut r).take(len).read_to_end(&mut payload)?;
+                }
+                None => {
+                    r.read_to_end(&mut payload)?;
+                }
+            };
+            let len = payload.len() as u64;
+            window.data.extend(payload);
+            if declared.is_some_and(|d| len < d) && !options.lenient {
+                bail!("stream ends inside {} at offset {}", h.typ, h.start);
+            }
+            // Cut boxes are clamped and reported by the parser
+            window.seek(SeekFrom::Start(offset))?;
+            let end = window.end();
+            for b in parse_children_with(&mut window, end, &mut state)? {
+                tree.push(build_box(&mut window, &b, options, &mut state.list));
+            }
+            offset = end;
+        } else {
+            // Keep what an mdat peek looks at; the rest is never held
+            let head = match &h.typ.0 {
+                b"mdat" => declared.map_or(options.mdat_peek, |d| d.min(options.mdat_peek)),
+                _ => 0,
+            };
+            let kept = (&mut r).take(head).read_to_end(&mut window.data)? as u64;
+            let len = kept
+                + match declared {
+                    Some(len) => io::copy(&mut (&mut r).take(len - kept), &mut io::sink())?,
+                    None => io::copy(&mut r, &mut io::sink())?,
+                };
+            if declared.is_some_and(|d| len < d) {
+                if !options.lenient {
+                    bail!("stream ends inside {} at offset {}", h.typ, h.start);
+                }
+                let kind = WarningKind::Truncated {
+                    declared_end: h.start + h.size,
+                    available_end: data_offset + len,
+                };
+                state.push(h.start, Some(h.typ), kind);
+            }
+            let kind = NodeKind::Leaf {
+                data_offset,
+                data_len: len,
+            };
+            let b = BoxRef { hdr: h, kind };
+            tree.push(build_box(&mut window, &b, options, &mut state.list));
+            offset = data_offset + len;
+        }
+    }
+
+    if options.decode && options.media_ranges {
+        link_media_ranges(&mut tree, offset, options);
+    }
+    Ok((tree, state.list))
+}
+
+/// Read the header at `offset`: 8 bytes, plus a 64-bit size and a uuid
+/// when present. `None` at the end of the stream.
+fn read_header_bytes<R: Read>(
+    r: &mut R,
+    offset: u64,
+    lenient: bool,
+    warnings: &mut Vec<ParseWarning>,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let mut head = vec![0u8; 8];
+    let mut n = read_up_to(r, &mut head)?;
+    if n == 8 {
+        let mut extra = 0;
+        if head[..4] == 1u32.to_be_bytes() {
+            extra += 8;
+        }
+        if &head[4..8] == b"uuid" {
+            extra += 16;
+        }
+        head.resize(8 + extra, 0);
+        n += read_up_to(r, &mut head[8..])?;
+    }
+    match n {
+        0 => Ok(None),
+        n if n == head.len() => Ok(Some(head)),
+        _ if lenient => {
+            warnings.push(ParseWarning {
+                offset,
+                typ: None,
+                kind: WarningKind::BadHeader {
+                    message: "stream ends inside a box header".into(),
+                },
+            });
+            Ok(None)
+        }
+        _ => bail!("stream ends inside the box header at offset {offset}"),
+    }
+}
@@ -0,0 +1,247 @@
+//! High-level, typed description of a movie and its tracks.
+
+use crate::registry::{FrameRate, StructuredData};
+use crate::track::{Track, TrackType};
+use anyhow::Context;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// What a track carries, from its handler and sample entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackKind {
+    Video,
+    Audio,
+    Subtitle,
+    /// GoPro GPMF metadata (only detected with the `gpmf` feature)
+    Telemetry,
+    Other,
+}
+
+impl TrackKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrackKind::Video => "video",
+            TrackKind::Audio => "audio",
+            TrackKind::Subtitle => "subtitle",
+            TrackKind::Telemetry => "telemetry",
+            TrackKind::Other => "other",
+        }
+    }
+}
+
+/// Summary of one track.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrackSummary {
+    /// 1-based position of the trak in moov
+    pub index: usize,
+    pub track_id: Option<u32>,
+    pub kind: Option<TrackKind>,
+    /// Sample entry type of the first stsd entry, e.g. "avc1", "mp4a"
+    pub codec: Option<String>,
+    /// From the sample entry, else from tkhd
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Audio only, in Hz
+    pub sample_rate: Option<u32>,
+    /// Audio only
+    pub channels: Option<u16>,
+    /// mdhd timescale and duration
+    pub timescale: Option<u32>,
+    pub duration: Option<u64>,
+    pub duration_seconds: Option<f64>,
+    /// Packed ISO 639-2/T code from mdhd
+    pub language: Option<String>,
+    /// BCP-47 tag from elng, else from the mdhd language
+    pub language_tag: Option<String>,
+    pub sample_count: Option<u32>,
+    /// Sum of all sample sizes from stsz
+    pub media_bytes: Option<u64>,
+    /// Average bits per second over the mdhd duration
+    pub bitrate: Option<u64>,
+    /// Video only, derived from stts
+    pub frame_rate: Option<FrameRate>,
+}
+
+/// Summary of a movie: brands, duration and tracks.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MovieSummary {
+    pub major_brand: Option<String>,
+    pub minor_version: Option<u32>,
+    pub compatible_brands: Vec<String>,
+    /// mvhd timescale and duration
+    pub timescale: Option<u32>,
+    pub duration: Option<u64>,
+    pub duration_seconds: Option<f64>,
+    pub tracks: Vec<TrackSummary>,
+}
+
+/// Summarize a decoded box tree.
+///
+/// Requires a tree produced with `decode = true`.
+pub fn summarize(boxes: &[crate::Box]) -> MovieSummary {
+    let mut summary = MovieSummary::default();
+
+    if let Some(StructuredData::FileType(ftyp)) = boxes
+        .iter()
+        .find(|b| b.typ == "ftyp")
+        .and_then(|b| b.structured_data.as_ref())
+    {
+        summary.major_brand = Some(ftyp.major_brand.clone());
+        summary.minor_version = Some(ftyp.minor_version);
+        summary.compatible_brands = ftyp.compatible_brands.clone();
+    }
+
+    for moov in boxes.iter().filter(|b| b.typ == "moov") {
+        if let Some(StructuredData::MovieHeader(mvhd)) =
+            moov.child("mvhd").and_then(|b| b.structured_data.as_ref())
+        {
+            summary.timescale = Some(mvhd.timescale);
+            summary.duration = Some(mvhd.duration);
+            summary.duration_seconds =
+                (mvhd.timescale > 0).then(|| mvhd.duration as f64 / mvhd.timescale as f64);
+        }
+        let traks = moov.children.iter().flatten().filter(|b| b.typ == "trak");
+        for (i, trak) in traks.enumerate() {
+            summary
+                .tracks
+                .push(summarize_track(Track::new(trak), i + 1));
+        }
+    }
+    summary
+}
+
+/// Parse and summarize an MP4 file from any seekable reader.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use mp4box::summarize_reader;
+/// use std::fs::File;
+///
+/// let summary = summarize_reader(File::open("video.mp4")?)?;
+/// for t in &summary.tracks {
+///     println!("track {}: {:?} {:?}", t.index, t.kind, t.codec);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn summarize_reader<R: Read + Seek>(mut reader: R) -> anyhow::Result<MovieSummary> {
+    let file_size = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+    let boxes = crate::get_boxes(&mut reader, file_size, /*decode=*/ true)
+        .context("getting boxes from reader")?;
+    Ok(summarize(&boxes))
+}
+
+/// Parse and summarize the MP4 file at `path`.
+pub fn summarize_path(path: impl AsRef<Path>) -> anyhow::Result<MovieSummary> {
+    summarize_reader(File::open(path)?)
+}
+
+fn summarize_track(track: Track, index: usize) -> TrackSummary {
+    let mut t = TrackSummary {
+        index,
+        track_id: track.track_id(),
+        kind: track.track_type().map(|tt| match tt {
+            TrackType::Video => TrackKind::Video,
+            TrackType::Audio => TrackKind::Audio,
+            TrackType::Subtitle => TrackKind::Subtitle,
+            _ => TrackKind::Other,
+        }),
+        codec: None,
+        width: None,
+        height: None,
+        sample_rate: None,
+        channels: None,
+        timescale: None,
+        duration: None,
+        duration_seconds: None,
+        language: None,
+        language_tag: None,
+        sample_count: None,
+        media_bytes: None,
+        bitrate: None,
+        frame_rate: None,
+    };
+
+    if let Some(tkhd) = track.tkhd()
+        && tkhd.width > 0.0
+        && tkhd.height > 0.0
+    {
+        t.width = Some(tkhd.width as u32);
+        t.height = Some(tkhd.height as u32);
+    }
+
+    let Some(mdia) = track.mdia() else {
+        return t;
+    };
+
+    if let Some(mdhd) = track.mdhd() {
+        t.timescale = Some(mdhd.timescale);
+        t.duration = Some(mdhd.duration);
+        t.duration_seconds =
+            (mdhd.timescale > 0).then(|| mdhd.duration as f64 / mdhd.timescale as f64);
+        t.language = Some(mdhd.language.clone());
+    }
+
+    // elng overrides the packed mdhd code when present
+    let elng = mdia.child("elng").and_then(|b| match &b.structured_data {
+        Some(StructuredData::ExtendedLanguage(d)) => Some(d.extended_language.as_str()),
+        _ => None,
+    });
+    t.language_tag =
+        crate::language::track_language_tag(t.language.as_deref().unwrap_or_default(), elng);
+
+    #[cfg(feature = "gpmf")]
+    if crate::gpmf::is_gpmf_track(track.trak()) {
+        t.kind = Some(TrackKind::Telemetry);
+    }
+
+    if let Some(entry) = track.sample_entry() {
+        t.codec = Some(entry.codec.clone());
+
+        // Timed text under a generic handler is still a subtitle
+        if matches!(entry.codec.as_str(), "tx3g" | "wvtt" | "stpp") {
+            t.kind = Some(TrackKind::Subtitle);
+        }
+
+        // If no type from hdlr, infer from codec
+        if t.kind.is_none() {
+            t.kind = Some(match entry.codec.as_str() {
+                "avc1" | "hvc1" | "hev1" | "vp09" | "av01" => TrackKind::Video,
+                "mp4a" | "ac-3" | "ec-3" | "Opus" | "ipcm" | "fpcm" => TrackKind::Audio,
+                _ => TrackKind::Other,
+            });
+        }
+
+        if let (Some(w), Some(h)) = (entry.width, entry.height) {
+            t.width = Some(w as u32);
+            t.height = Some(h as u32);
+        }
+        t.sample_rate = entry.sample_rate;
+        t.channels = entry.channel_count;
+    }
+
+    if let Some(stsz) = track.stsz() {
+        t.sample_count = Some(stsz.sample_count);
+        t.media_bytes = Some(if stsz.sample_size > 0 {
+            stsz.sample_size as u64 * stsz.sample_count as u64
+        } else {
+            stsz.sample_sizes.iter().map(|&s| s as u64).sum()
+        });
+    }
+    t.bitrate = match (t.media_bytes, t.duration_seconds) {
+        (Some(bytes), Some(sec)) if sec > 0.0 => Some((bytes as f64 * 8.0 / sec).round() as u64),
+        _ => None,
+    };
+
+    if t.kind == Some(TrackKind::Video)
+        && let Some(stts) = track.stts()
+    {
+        t.frame_rate = stts.frame_rate(t.timescale.unwrap_or(0));
+    }
+
+    t
+}
@@ -0,0 +1,187 @@
+//! Typed access to a `trak` box and its sample tables.
+
+use crate::boxes::FourCC;
+use crate::registry::{
+    Co64Data, CttsData, ElstData, HdlrData, MdhdData, SampleEntry, SdtpData, StcoData,
+    StructuredData, StscData, StsdData, StssData, StszData, SttsData, TkhdData,
+};
+use std::fmt;
+
+/// Media type of a track, from its hdlr handler_type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrackType {
+    /// `vide`
+    Video,
+    /// `soun`
+    Audio,
+    /// `sbtl`, `subt`, `text`, `clcp`
+    Subtitle,
+    /// `hint`
+    Hint,
+    /// `meta`
+    Metadata,
+    /// `tmcd`
+    Timecode,
+    Other(FourCC),
+}
+
+impl TrackType {
+    pub fn from_handler(handler: FourCC) -> Self {
+        match &handler.0 {
+            b"vide" => TrackType::Video,
+            b"soun" => TrackType::Audio,
+            b"sbtl" | b"subt" | b"text" | b"clcp" => TrackType::Subtitle,
+            b"hint" => TrackType::Hint,
+            b"meta" => TrackType::Metadata,
+            b"tmcd" => TrackType::Timecode,
+            _ => TrackType::Other(handler),
+        }
+    }
+}
+
+impl fmt::Display for TrackType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackType::Video => f.write_str("video"),
+            TrackType::Audio => f.write_str("audio"),
+            TrackType::Subtitle => f.write_str("subtitle"),
+            TrackType::Hint => f.write_str("hint"),
+            TrackType::Metadata => f.write_str("metadata"),
+            TrackType::Timecode => f.write_str("timecode"),
+            TrackType::Other(h) => write!(f, "{}", h),
+        }
+    }
+}
+
+/// A decoded `trak` box.
+///
+/// Accessors return None when the box is missing or was not decoded.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, track::{TrackType, tracks}};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, true)?;
+/// for t in tracks(&boxes).iter().filter(|t| t.track_type() == Some(TrackType::Video)) {
+///     let samples = t.stsz().map_or(0, |s| s.sample_count);
+///     println!("video track {:?}: {} samples", t.track_id(), samples);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone, Copy)]
+pub struct Track<'a> {
+    trak: &'a crate::Box,
+}
+
+/// Every `moov/trak` of a decoded box tree, in file order.
+pub fn tracks(boxes: &[crate::Box]) -> Vec<Track<'_>> {
+    boxes
+        .iter()
+        .filter(|b| b.typ == "moov")
+        .flat_map(|moov| moov.children.iter().flatten())
+        .filter(|b| b.typ == "trak")
+        .map(Track::new)
+        .collect()
+}
+
+macro_rules! stbl_accessor {
+    ($name:ident, $variant:ident, $ty:ty) => {
+        pub fn $name(&self) -> Option<&'a $ty> {
+            self.stbl_data().find_map(|d| match d {
+                StructuredData::$variant(v) => Some(v),
+                _ => None,
+            })
+        }
+    };
+}
+
+impl<'a> Track<'a> {
+    pub fn new(trak: &'a crate::Box) -> Self {
+        Track { trak }
+    }
+
+    /// The underlying `trak` box.
+    pub fn trak(&self) -> &'a crate::Box {
+        self.trak
+    }
+
+    pub fn track_id(&self) -> Option<u32> {
+        self.tkhd().map(|t| t.track_id)
+    }
+
+    /// None when the track has no decoded hdlr.
+    pub fn track_type(&self) -> Option<TrackType> {
+        let handler = self.hdlr()?.handler_type.parse().ok()?;
+        Some(TrackType::from_handler(handler))
+    }
+
+    pub fn tkhd(&self) -> Option<&'a TkhdData> {
+        data(Some(self.trak)).find_map(|d| match d {
+            StructuredData::TrackHeader(t) => Some(t),
+            _ => None,
+        })
+    }
+
+    pub fn mdhd(&self) -> Option<&'a MdhdData> {
+        data(self.mdia()).find_map(|d| match d {
+            StructuredData::MediaHeader(m) => Some(m),
+            _ => None,
+        })
+    }
+
+    pub fn hdlr(&self) -> Option<&'a HdlrData> {
+        data(self.mdia()).find_map(|d| match d {
+            StructuredData::HandlerReference(h) => Some(h),
+            _ => None,
+        })
+    }
+
+    pub fn elst(&self) -> Option<&'a ElstData> {
+        data(self.trak.child("edts")).find_map(|d| match d {
+            StructuredData::EditList(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    pub fn mdia(&self) -> Option<&'a crate::Box> {
+        self.trak.child("mdia")
+    }
+
+    pub fn stbl(&self) -> Option<&'a crate::Box> {
+        self.mdia()?.child("minf")?.child("stbl")
+    }
+
+    stbl_accessor!(stsd, SampleDescription, StsdData);
+    stbl_accessor!(stts, DecodingTimeToSample, SttsData);
+    stbl_accessor!(ctts, CompositionTimeToSample, CttsData);
+    stbl_accessor!(stsc, SampleToChunk, StscData);
+    stbl_accessor!(stsz, SampleSize, StszData);
+    stbl_accessor!(stss, SyncSample, StssData);
+    stbl_accessor!(sdtp, SampleDependencyType, SdtpData);
+    stbl_accessor!(stco, ChunkOffset, StcoData);
+    stbl_accessor!(co64, ChunkOffset64, Co64Data);
+
+    /// First entry of stsd.
+    pub fn sample_entry(&self) -> Option<&'a SampleEntry> {
+        self.stsd()?.entries.first()
+    }
+
+    /// Box of the first stsd entry, whose children hold codec configuration
+    /// (avcC, esds, pasp, sinf, ...).
+    pub fn sample_entry_box(&self) -> Option<&'a crate::Box> {
+        self.stbl()?.child("stsd")?.children.iter().flatten().next()
+    }
+
+    fn stbl_data(&self) -> impl Iterator<Item = &'a StructuredData> {
+        data(self.stbl())
+    }
+}
+
+fn data(b: Option<&crate::Box>) -> impl Iterator<Item = &StructuredData> {
+    b.into_iter()
+        .flat_map(|b| b.children.iter().flatten())
+        .filter_map(|c| c.structured_data.as_ref())
+}
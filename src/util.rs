@@ -1,12 +1,30 @@
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{self, Read, Seek, SeekFrom};
 
+/// Read `len` bytes at `offset`. The buffer grows with the data actually
+/// read, so a length past the end of the source fails with
+/// `UnexpectedEof` instead of allocating it up front.
 pub fn read_slice<R: Read + Seek>(r: &mut R, offset: u64, len: u64) -> std::io::Result<Vec<u8>> {
     r.seek(SeekFrom::Start(offset))?;
-    let mut v = vec![0u8; len as usize];
-    r.read_exact(&mut v)?;
+    let mut v = Vec::with_capacity(len.min(64 * 1024) as usize);
+    r.take(len).read_to_end(&mut v)?;
+    if (v.len() as u64) < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
     Ok(v)
 }
 
+/// Fill `buf` as far as the reader allows; returns the bytes read.
+pub(crate) fn read_up_to<R: Read>(r: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match r.read(&mut buf[n..])? {
+            0 => break,
+            k => n += k,
+        }
+    }
+    Ok(n)
+}
+
 pub fn hex_dump(bytes: &[u8], start_offset: u64) -> String {
     // Simple hexdump
     let mut out = String::new();
@@ -28,3 +46,261 @@ pub fn hex_dump(bytes: &[u8], start_offset: u64) -> String {
     }
     out
 }
+
+/// Parse an ISO 6709 location string such as `+37.7749-122.4194+010.000/`.
+///
+/// Returns `(latitude, longitude, altitude)` in decimal degrees and metres.
+/// Degree, degree-minute and degree-minute-second forms are accepted
+/// (`±DD.D`, `±DDMM.M`, `±DDMMSS.S` and the three-digit longitude variants).
+pub fn parse_iso6709(s: &str) -> Option<(f64, f64, Option<f64>)> {
+    let s = s.trim().trim_end_matches('/');
+    // Drop an optional trailing "CRS..." designator
+    let s = s.split("CRS").next()?;
+
+    // Split into signed components: "+37.7749", "-122.4194", "+010.000"
+    let mut parts = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        if c == '+' || c == '-' {
+            if let Some(st) = start {
+                parts.push(&s[st..i]);
+            }
+            start = Some(i);
+        }
+    }
+    parts.push(&s[start?..]);
+
+    let latitude = iso6709_angle(parts.first()?, 2)?;
+    let longitude = iso6709_angle(parts.get(1)?, 3)?;
+    let altitude = match parts.get(2) {
+        Some(a) => Some(a.parse::<f64>().ok()?),
+        None => None,
+    };
+    if latitude.abs() > 90.0 || longitude.abs() > 180.0 {
+        return None;
+    }
+    Some((latitude, longitude, altitude))
+}
+
+/// One signed ISO 6709 angle; `deg_digits` is 2 for latitude, 3 for longitude.
+fn iso6709_angle(part: &str, deg_digits: usize) -> Option<f64> {
+    let (sign, body) = match part.split_at_checked(1)? {
+        ("+", b) => (1.0, b),
+        ("-", b) => (-1.0, b),
+        _ => return None,
+    };
+    let int_len = body.find('.').unwrap_or(body.len());
+    if !body[..int_len].bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let frac: f64 = if int_len < body.len() {
+        format!("0{}", &body[int_len..]).parse().ok()?
+    } else {
+        0.0
+    };
+    let digits = &body[..int_len];
+    let field = |r: std::ops::Range<usize>| digits[r].parse::<f64>().ok();
+
+    let value = if int_len == deg_digits {
+        field(0..deg_digits)? + frac
+    } else if int_len == deg_digits + 2 {
+        field(0..deg_digits)? + (field(deg_digits..deg_digits + 2)? + frac) / 60.0
+    } else if int_len == deg_digits + 4 {
+        field(0..deg_digits)?
+            + field(deg_digits..deg_digits + 2)? / 60.0
+            + (field(deg_digits + 2..deg_digits + 4)? + frac) / 3600.0
+    } else {
+        return None;
+    };
+    Some(sign * value)
+}
+
+/// Seconds between the MP4 epoch (1904-01-01) and the Unix epoch.
+const MP4_EPOCH_OFFSET: i64 = 2_082_844_800;
+
+/// Format an mvhd/tkhd/mdhd timestamp (seconds since 1904-01-01 UTC) as an
+/// RFC 3339 string such as `2023-06-10T12:34:56Z`.
+///
+/// Returns `None` for 0, which writers use for "not set".
+pub fn mp4_time_to_rfc3339(secs: u64) -> Option<String> {
+    if secs == 0 {
+        return None;
+    }
+    let unix = i64::try_from(secs).ok()? - MP4_EPOCH_OFFSET;
+    let (days, rem) = (unix.div_euclid(86_400), unix.rem_euclid(86_400));
+
+    // Civil date from days since 1970-01-01 (proleptic Gregorian)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    Some(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    ))
+}
+
+/// `Read + Seek` wrapper that counts how many bytes were actually read.
+///
+/// Seeks are forwarded untouched and never counted, so wrapping a file in a
+/// `CountingReader` shows how much real I/O a parse performed — useful for
+/// sparse or cloud-backed files where reads are expensive but seeks are not.
+pub struct CountingReader<R> {
+    inner: R,
+    bytes_read: u64,
+}
+
+impl<R> CountingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            bytes_read: 0,
+        }
+    }
+
+    /// Total number of bytes returned by `read` so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for CountingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Bytes `base..base + data.len()` of a file or stream, addressed with
+/// their offsets there so the seeking parser can run over them.
+pub(crate) struct Window {
+    base: u64,
+    pub(crate) data: Vec<u8>,
+    pos: u64,
+}
+
+impl Window {
+    pub(crate) fn new(base: u64, data: Vec<u8>) -> Self {
+        Window {
+            base,
+            data,
+            pos: base,
+        }
+    }
+
+    pub(crate) fn end(&self) -> u64 {
+        self.base + self.data.len() as u64
+    }
+}
+
+impl Read for Window {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos < self.base {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "offset before the buffered box",
+            ));
+        }
+        let start = ((self.pos - self.base) as usize).min(self.data.len());
+        let n = buf.len().min(self.data.len() - start);
+        buf[..n].copy_from_slice(&self.data[start..start + n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for Window {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = match pos {
+            SeekFrom::Start(p) => p,
+            SeekFrom::Current(d) => self.pos.saturating_add_signed(d),
+            SeekFrom::End(d) => self.end().saturating_add_signed(d),
+        };
+        Ok(self.pos)
+    }
+}
+
+/// Reads `inner`, except for the chunks kept in memory while parsing, which
+/// are served from there. `inner` is only seeked when it is read.
+pub(crate) struct Prefetched<'a, R> {
+    inner: &'a mut R,
+    /// (offset, bytes), in offset order and not overlapping
+    chunks: Vec<(u64, Vec<u8>)>,
+    pos: u64,
+    /// Where `inner` is, when known
+    inner_pos: Option<u64>,
+}
+
+impl<'a, R> Prefetched<'a, R> {
+    pub(crate) fn new(inner: &'a mut R, chunks: Vec<(u64, Vec<u8>)>) -> Self {
+        Prefetched {
+            inner,
+            chunks,
+            pos: 0,
+            inner_pos: None,
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for Prefetched<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let i = self.chunks.partition_point(|(at, _)| *at <= self.pos);
+        if let Some((at, data)) = i.checked_sub(1).map(|i| &self.chunks[i]) {
+            let start = (self.pos - at) as usize;
+            if start < data.len() {
+                let n = buf.len().min(data.len() - start);
+                buf[..n].copy_from_slice(&data[start..start + n]);
+                self.pos += n as u64;
+                return Ok(n);
+            }
+        }
+        if self.inner_pos != Some(self.pos) {
+            self.inner.seek(SeekFrom::Start(self.pos))?;
+        }
+        let n = self.inner.read(buf)?;
+        self.pos += n as u64;
+        self.inner_pos = Some(self.pos);
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Prefetched<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.pos = match pos {
+            SeekFrom::Start(p) => p,
+            SeekFrom::Current(d) => self.pos.saturating_add_signed(d),
+            SeekFrom::End(d) => {
+                let p = self.inner.seek(SeekFrom::End(d))?;
+                self.inner_pos = Some(p);
+                p
+            }
+        };
+        Ok(self.pos)
+    }
+}
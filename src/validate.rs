@@ -0,0 +1,189 @@
+//! Structural checks against what the ISOBMFF editions define.
+
+use serde::Serialize;
+
+/// How much [`check_structure`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strictness {
+    /// Only versions no edition defines
+    Lenient,
+    /// Also flags outside the defined bits
+    Standard,
+    /// Also unknown boxes inside parents whose children are fully specified
+    Strict,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StructureIssueKind {
+    UndefinedVersion { version: u8, max_version: u8 },
+    ReservedFlags { flags: u32, defined: u32 },
+    UnexpectedChild { parent: String },
+}
+
+/// One finding of [`check_structure`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StructureIssue {
+    /// Box path in [`crate::format_box_path`] notation
+    pub path: String,
+    pub offset: u64,
+    #[serde(flatten)]
+    pub kind: StructureIssueKind,
+}
+
+/// (type, highest defined version, defined flag bits)
+const FULL_BOXES: &[(&str, u8, u32)] = &[
+    ("mvhd", 1, 0),
+    ("tkhd", 1, 0x00000F),
+    ("mdhd", 1, 0),
+    ("hdlr", 0, 0),
+    ("elng", 0, 0),
+    ("vmhd", 0, 0x000001),
+    ("smhd", 0, 0),
+    ("dref", 0, 0),
+    ("url ", 0, 0x000001),
+    ("urn ", 0, 0x000001),
+    ("elst", 1, 0),
+    ("stsd", 0, 0),
+    ("stts", 0, 0),
+    ("ctts", 1, 0),
+    ("cslg", 1, 0),
+    ("stss", 0, 0),
+    ("stsz", 0, 0),
+    ("stz2", 0, 0),
+    ("stsc", 0, 0),
+    ("stco", 0, 0),
+    ("co64", 0, 0),
+    ("sdtp", 0, 0),
+    ("sbgp", 1, 0),
+    ("sgpd", 2, 0),
+    ("saiz", 0, 0x000001),
+    ("saio", 1, 0x000001),
+    ("mehd", 1, 0),
+    ("trex", 0, 0),
+    ("mfhd", 0, 0),
+    // base-data-offset, sample-description-index, default duration, size
+    // and flags, duration-is-empty, default-base-is-moof
+    ("tfhd", 0, 0x03003B),
+    ("tfdt", 1, 0),
+    // data-offset, first-sample-flags, per-sample duration, size, flags and
+    // composition time offset
+    ("trun", 1, 0x000F05),
+    ("tfra", 1, 0),
+    ("mfro", 0, 0),
+    ("sidx", 1, 0),
+];
+
+/// Parents whose possible children the specification lists completely.
+/// free, skip and uuid are allowed everywhere.
+const CHILDREN: &[(&str, &[&str])] = &[
+    (
+        "moov",
+        &["mvhd", "trak", "mvex", "udta", "meta", "iods", "pssh"],
+    ),
+    (
+        "trak",
+        &["tkhd", "tref", "trgr", "edts", "meta", "mdia", "udta"],
+    ),
+    ("edts", &["elst"]),
+    ("mdia", &["mdhd", "hdlr", "elng", "minf", "udta"]),
+    (
+        "minf",
+        &[
+            "vmhd", "smhd", "hmhd", "nmhd", "sthd", "gmhd", "dinf", "stbl",
+        ],
+    ),
+    ("dinf", &["dref"]),
+    (
+        "stbl",
+        &[
+            "stsd", "stts", "ctts", "cslg", "stss", "stsh", "stsz", "stz2", "stsc", "stco", "co64",
+            "padb", "stdp", "sdtp", "sbgp", "sgpd", "subs", "saiz", "saio",
+        ],
+    ),
+    ("mvex", &["mehd", "trex", "leva", "trep"]),
+    ("moof", &["mfhd", "meta", "traf", "pssh"]),
+    (
+        "traf",
+        &[
+            "tfhd", "trun", "sbgp", "sgpd", "subs", "saiz", "saio", "tfdt", "meta", "senc", "sdtp",
+        ],
+    ),
+    ("mfra", &["tfra", "mfro"]),
+];
+
+/// Check versions, flags and, at [`Strictness::Strict`], child types of
+/// every box against the definitions in ISO/IEC 14496-12.
+///
+/// Only box types this module knows are checked; anything else passes.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::{get_boxes, validate::{Strictness, check_structure}};
+/// use std::fs::File;
+///
+/// let mut file = File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// for issue in check_structure(&get_boxes(&mut file, size, false)?, Strictness::Standard) {
+///     println!("{} @{}: {:?}", issue.path, issue.offset, issue.kind);
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn check_structure(boxes: &[crate::Box], strictness: Strictness) -> Vec<StructureIssue> {
+    let mut out = Vec::new();
+    walk(boxes, None, "", strictness, &mut out);
+    out
+}
+
+fn walk(
+    siblings: &[crate::Box],
+    parent: Option<&str>,
+    parent_path: &str,
+    strictness: Strictness,
+    out: &mut Vec<StructureIssue>,
+) {
+    let allowed = parent.and_then(|p| CHILDREN.iter().find(|(t, _)| *t == p));
+    for (i, b) in siblings.iter().enumerate() {
+        let path = crate::api::child_path(parent_path, siblings, i);
+        let mut issue = |kind| {
+            out.push(StructureIssue {
+                path: path.clone(),
+                offset: b.offset,
+                kind,
+            })
+        };
+
+        if let Some(&(_, max_version, defined)) = FULL_BOXES.iter().find(|(t, ..)| *t == b.typ) {
+            if let Some(version) = b.version.filter(|&v| v > max_version) {
+                issue(StructureIssueKind::UndefinedVersion {
+                    version,
+                    max_version,
+                });
+            }
+            if let Some(flags) = b.flags.filter(|&f| f & !defined != 0)
+                && strictness >= Strictness::Standard
+            {
+                issue(StructureIssueKind::ReservedFlags { flags, defined });
+            }
+        }
+
+        if let Some((parent, children)) = allowed
+            && strictness >= Strictness::Strict
+            && !children.contains(&b.typ.as_str())
+            && !matches!(b.typ.as_str(), "free" | "skip" | "uuid")
+        {
+            issue(StructureIssueKind::UnexpectedChild {
+                parent: parent.to_string(),
+            });
+        }
+
+        walk(
+            b.children.as_deref().unwrap_or_default(),
+            Some(&b.typ),
+            &path,
+            strictness,
+            out,
+        );
+    }
+}
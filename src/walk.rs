@@ -0,0 +1,93 @@
+//! Depth-first traversal of a parsed box tree.
+
+use crate::api::Box;
+use std::ops::ControlFlow;
+
+/// Whether [`walk`] visits the children of the box just entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Children {
+    Visit,
+    Skip,
+}
+
+/// Hooks for [`walk`]. `ancestors` runs from the top-level box down to the
+/// parent of `b`, so `ancestors.len()` is the depth.
+///
+/// The tree outlives the walk (`'a`), so visitors can keep references to
+/// the boxes they are handed. Closures
+/// `FnMut(&Box, &[&Box]) -> ControlFlow<B, Children>` are visitors with only
+/// a pre hook.
+pub trait Visitor<'a> {
+    /// Value that ends the walk early, returned by [`walk`].
+    type Break;
+
+    /// Called before the children of `b`.
+    fn pre(&mut self, b: &'a Box, ancestors: &[&'a Box]) -> ControlFlow<Self::Break, Children> {
+        let _ = (b, ancestors);
+        ControlFlow::Continue(Children::Visit)
+    }
+
+    /// Called after the children of `b`, or right after
+    /// [`pre`](Self::pre) when they were skipped.
+    fn post(&mut self, b: &'a Box, ancestors: &[&'a Box]) -> ControlFlow<Self::Break> {
+        let _ = (b, ancestors);
+        ControlFlow::Continue(())
+    }
+}
+
+impl<'a, B, F> Visitor<'a> for F
+where
+    F: FnMut(&'a Box, &[&'a Box]) -> ControlFlow<B, Children>,
+{
+    type Break = B;
+
+    fn pre(&mut self, b: &'a Box, ancestors: &[&'a Box]) -> ControlFlow<B, Children> {
+        self(b, ancestors)
+    }
+}
+
+/// Visit `boxes` and their descendants depth-first, in file order, until
+/// the visitor breaks.
+///
+/// # Example
+/// ```no_run
+/// use mp4box::get_boxes;
+/// use mp4box::walk::{Children, walk};
+/// use std::ops::ControlFlow;
+///
+/// let mut file = std::fs::File::open("video.mp4")?;
+/// let size = file.metadata()?.len();
+/// let boxes = get_boxes(&mut file, size, false)?;
+///
+/// // First stsd, or None
+/// let stsd = walk(&boxes, &mut |b: &mp4box::Box, _: &[&mp4box::Box]| match b.typ.as_str() {
+///     "stsd" => ControlFlow::Break(b.offset),
+///     "mdat" => ControlFlow::Continue(Children::Skip),
+///     _ => ControlFlow::Continue(Children::Visit),
+/// });
+/// println!("{:?}", stsd.break_value());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn walk<'a, V: Visitor<'a> + ?Sized>(
+    boxes: &'a [Box],
+    visitor: &mut V,
+) -> ControlFlow<V::Break> {
+    walk_in(boxes, visitor, &mut Vec::new())
+}
+
+fn walk_in<'a, V: Visitor<'a> + ?Sized>(
+    boxes: &'a [Box],
+    visitor: &mut V,
+    ancestors: &mut Vec<&'a Box>,
+) -> ControlFlow<V::Break> {
+    for b in boxes {
+        if let (Children::Visit, Some(kids)) = (visitor.pre(b, ancestors)?, &b.children) {
+            ancestors.push(b);
+            let flow = walk_in(kids, visitor, ancestors);
+            ancestors.pop();
+            flow?;
+        }
+        visitor.post(b, ancestors)?;
+    }
+    ControlFlow::Continue(())
+}
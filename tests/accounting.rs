@@ -0,0 +1,76 @@
+mod common;
+
+use common::*;
+use mp4box::accounting::{ByteCategory, size_report};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+#[test]
+fn bytes_by_type_and_category() {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let moov = container(b"moov", &[mvhd(1000, 0), boxed(b"free", &[0; 12])]);
+    let uuid = boxed(b"uuid", &[0; 24]);
+    let free = boxed(b"free", &[0; 32]);
+    let mdat = boxed(b"mdat", &[0; 400]);
+    let mut data = [ftyp.clone(), moov.clone(), uuid, free, mdat].concat();
+    let parsed_len = data.len() as u64;
+    data.extend_from_slice(&[0; 4]); // trailing bytes too short for a box
+    let boxes = get_boxes(&mut Cursor::new(&data), parsed_len, false).unwrap();
+
+    let report = size_report(&boxes, data.len() as u64);
+    let types: Vec<_> = report
+        .types
+        .iter()
+        .map(|t| (t.typ.as_str(), t.count, t.bytes))
+        .collect();
+    assert_eq!(
+        types,
+        [
+            ("mdat", 1, 408),
+            ("moov", 1, moov.len() as u64),
+            ("free", 1, 40),
+            ("uuid", 1, 32),
+            ("ftyp", 1, ftyp.len() as u64),
+        ]
+    );
+
+    assert_eq!(report.bytes(ByteCategory::Media), 408);
+    assert_eq!(report.bytes(ByteCategory::FreeSpace), 40 + 20);
+    assert_eq!(report.bytes(ByteCategory::Vendor), 32);
+    assert_eq!(
+        report.bytes(ByteCategory::Metadata),
+        (ftyp.len() + moov.len()) as u64 - 20
+    );
+    assert_eq!(report.bytes(ByteCategory::Unaccounted), 4);
+    let total: f64 = report.categories.iter().map(|c| c.percent).sum();
+    assert!((total - 100.0).abs() < 1e-9);
+}
+
+#[test]
+fn largest_boxes_with_paths() {
+    let udta = container(b"udta", &[boxed(b"blob", &[0; 300])]);
+    let trak = |extra: Vec<u8>| container(b"trak", &[tkhd(1, 0, 0, 0, 0), extra]);
+    let moov = container(
+        b"moov",
+        &[mvhd(1000, 0), trak(boxed(b"free", &[])), trak(udta)],
+    );
+    let mdat = boxed(b"mdat", &[0; 200]);
+    let data = [moov.clone(), mdat].concat();
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap();
+
+    let top: Vec<_> = mp4box::accounting::largest_boxes(&boxes, 5)
+        .into_iter()
+        .map(|b| (b.path, b.size))
+        .collect();
+    let tkhd_len = tkhd(1, 0, 0, 0, 0).len() as u64;
+    assert_eq!(
+        top,
+        [
+            ("moov".to_string(), moov.len() as u64),
+            ("moov>trak[1]".to_string(), 8 + tkhd_len + 316),
+            ("moov>trak[1]>udta".to_string(), 316),
+            ("moov>trak[1]>udta>blob".to_string(), 308),
+            ("mdat".to_string(), 208),
+        ]
+    );
+}
@@ -0,0 +1,275 @@
+mod common;
+
+use common::*;
+use mp4box::boxes::FourCC;
+use mp4box::registry::StructuredData;
+
+#[test]
+fn chnl_defined_layout_with_omitted_channels() {
+    let payload = [
+        1, // stream_structure = channel structured
+        6, // definedLayout = 5.1
+        0, 0, 0, 0, 0, 0, 0, 0x08, // omittedChannelsMap
+    ];
+    match decode_payload(b"chnl", &payload, Some((0, 0))) {
+        StructuredData::ChannelLayout(c) => {
+            assert_eq!(c.stream_structure, 1);
+            assert_eq!(c.defined_layout, Some(6));
+            assert_eq!(c.omitted_channels_map, Some(8));
+            assert!(c.speaker_positions.is_empty());
+            assert_eq!(c.object_count, None);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn chnl_explicit_speaker_positions() {
+    let payload = [
+        3, // channel + object structured
+        0, // explicit layout
+        2, // L
+        126, 0xFF, 0xE2, 10, // explicit: azimuth -30, elevation 10
+        4,  // object_count
+    ];
+    match decode_payload(b"chnl", &payload, Some((0, 0))) {
+        StructuredData::ChannelLayout(c) => {
+            assert_eq!(c.defined_layout, Some(0));
+            assert_eq!(c.speaker_positions.len(), 2);
+            assert_eq!(c.speaker_positions[0].speaker_position, 2);
+            assert_eq!(c.speaker_positions[1].azimuth, Some(-30));
+            assert_eq!(c.speaker_positions[1].elevation, Some(10));
+            assert_eq!(c.object_count, Some(4));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn pcmc_little_endian_24bit() {
+    match decode_payload(b"pcmC", &[0x01, 24], Some((0, 0))) {
+        StructuredData::PcmConfig(p) => {
+            assert!(p.little_endian);
+            assert_eq!(p.pcm_sample_size, 24);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn stsd_reports_ipcm_audio_properties() {
+    let payload = [
+        0, 0, 0, 1, // entry_count
+        0, 0, 0, 36, // size
+        b'i', b'p', b'c', b'm', // format
+        0, 0, 0, 0, 0, 0, // reserved
+        0, 1, // data_reference_index
+        0, 0, 0, 0, 0, 0, 0, 0, // reserved
+        0, 6, // channelcount
+        0, 24, // samplesize
+        0, 0, 0, 0, // pre_defined + reserved
+        0xBB, 0x80, 0, 0, // samplerate = 48000
+    ];
+    match decode_payload(b"stsd", &payload, Some((0, 0))) {
+        StructuredData::SampleDescription(stsd) => {
+            let e = &stsd.entries[0];
+            assert_eq!(e.codec, "ipcm");
+            assert_eq!(e.channel_count, Some(6));
+            assert_eq!(e.sample_size, Some(24));
+            assert_eq!(e.sample_rate, Some(48000));
+            assert_eq!(e.width, None);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn sa3d_first_order_ambisonics() {
+    let mut payload = vec![0, 0]; // version, ambisonic_type
+    payload.extend_from_slice(&1u32.to_be_bytes()); // order
+    payload.extend_from_slice(&[0, 0]); // ACN, SN3D
+    payload.extend_from_slice(&4u32.to_be_bytes());
+    for ch in [0u32, 1, 2, 3] {
+        payload.extend_from_slice(&ch.to_be_bytes());
+    }
+    match decode_payload(b"SA3D", &payload, None) {
+        StructuredData::SpatialAudio(s) => {
+            assert_eq!(s.ambisonic_order, 1);
+            assert_eq!(s.num_channels, 4);
+            assert_eq!(s.channel_map, vec![0, 1, 2, 3]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn mhac_config_record() {
+    let mut payload = vec![1, 0x0D, 6];
+    payload.extend_from_slice(&3u16.to_be_bytes());
+    payload.extend_from_slice(&[0xAA, 0xBB, 0xCC]);
+    match decode_payload(b"mhaC", &payload, None) {
+        StructuredData::MpegHConfig(m) => {
+            assert_eq!(m.configuration_version, 1);
+            assert_eq!(m.profile_level_indication, 0x0D);
+            assert_eq!(m.reference_channel_layout, 6);
+            assert_eq!(m.mpegh3da_config_length, 3);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn ddts_specific_box() {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&48_000u32.to_be_bytes());
+    payload.extend_from_slice(&1_509_000u32.to_be_bytes());
+    payload.extend_from_slice(&1_509_000u32.to_be_bytes());
+    payload.push(24);
+    // frame duration 1 (1024), stream construction 17, LFE, core layout 9,
+    // core size 2012, no downmix, representation 0, channel layout 0x000F
+    let mut bits: u64 = 0;
+    for (value, width) in [
+        (1u64, 2),
+        (17, 5),
+        (1, 1),
+        (9, 6),
+        (2012, 14),
+        (0, 1),
+        (0, 3),
+        (0x000F, 16),
+        (0, 1),
+        (0, 1),
+        (0, 1),
+        (0, 5),
+    ] {
+        bits = (bits << width) | value;
+    }
+    payload.extend_from_slice(&bits.to_be_bytes()[1..]);
+
+    match decode_payload(b"ddts", &payload, None) {
+        StructuredData::DtsConfig(d) => {
+            assert_eq!(d.sampling_frequency, 48_000);
+            assert_eq!(d.avg_bitrate, 1_509_000);
+            assert_eq!(d.pcm_sample_depth, 24);
+            assert_eq!(d.frame_duration, 1024);
+            assert_eq!(d.stream_construction, 17);
+            assert!(d.core_lfe_present);
+            assert_eq!(d.core_layout, 9);
+            assert_eq!(d.core_size, 2012);
+            assert_eq!(d.channel_layout, 0x000F);
+            assert!(!d.multi_asset);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+/// MSB-first bit packer for bitstream-coded payloads.
+#[derive(Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    fn put(&mut self, value: u64, width: usize) -> &mut Self {
+        for i in (0..width).rev() {
+            if self.len.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+        self
+    }
+}
+
+#[test]
+fn dac4_presentations() {
+    let mut b = Bits::default();
+    b.put(1, 3) // ac4_dsi_version
+        .put(2, 7) // bitstream_version
+        .put(1, 1) // fs_index: 48 kHz
+        .put(2, 4) // frame_rate_index
+        .put(2, 9) // n_presentations
+        .put(1, 1) // b_program_id
+        .put(0x1234, 16)
+        .put(0, 1) // b_uuid
+        .put(0, 2) // bit_rate_mode
+        .put(256_000, 32)
+        .put(0xFFFF_FFFF, 32);
+    let mut payload = b.bytes.clone();
+
+    // Presentation 1: v1, channel coded 5.1 (mask 0x47)
+    let mut p = Bits::default();
+    p.put(1, 5).put(3, 3).put(1, 1).put(4, 5); // config, mdcompat, id
+    p.put(0, 2).put(0, 2).put(0, 5).put(0, 10); // frame rate, emdf, key id
+    p.put(1, 1).put(6, 5).put(0x47, 24); // channel coded, ch_mode, mask
+    payload.extend_from_slice(&[1, p.bytes.len() as u8]);
+    payload.extend_from_slice(&p.bytes);
+
+    // Presentation 2: v1, object based (not channel coded), 2 trailing bytes
+    let mut p = Bits::default();
+    p.put(2, 5).put(1, 3).put(0, 1);
+    p.put(0, 2).put(0, 2).put(0, 5).put(0, 10).put(0, 1);
+    p.bytes.extend_from_slice(&[0xEE, 0xEE]);
+    payload.extend_from_slice(&[1, p.bytes.len() as u8]);
+    payload.extend_from_slice(&p.bytes);
+
+    match decode_payload(b"dac4", &payload, None) {
+        StructuredData::Ac4Config(d) => {
+            assert_eq!(d.bitstream_version, 2);
+            assert_eq!(d.sampling_rate, 48_000);
+            assert_eq!(d.n_presentations, 2);
+            assert_eq!(d.short_program_id, Some(0x1234));
+            assert_eq!(d.bit_rate, 256_000);
+            assert_eq!(d.presentations.len(), 2);
+
+            let first = &d.presentations[0];
+            assert_eq!(first.presentation_config, Some(1));
+            assert_eq!(first.mdcompat, Some(3));
+            assert_eq!(first.presentation_id, Some(4));
+            assert_eq!(first.channel_mask, Some(0x47));
+
+            let second = &d.presentations[1];
+            assert_eq!(second.mdcompat, Some(1));
+            assert_eq!(second.presentation_id, None);
+            assert_eq!(second.channel_mask, None);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn album_loudness_v1() {
+    use mp4box::known_boxes::KnownBox;
+    assert!(KnownBox::from(FourCC(*b"ludt")).is_container());
+
+    let mut b = Bits::default();
+    b.put(0, 2).put(1, 6); // loudness_base_count
+    b.put(0, 2).put(0, 6); // EQ_set_ID
+    b.put(0, 3).put(0, 7).put(0, 6); // downmix_ID, DRC_set_ID
+    b.put(0, 12); // sample peak: not signalled
+    b.put(0x0A0, 12); // true peak: 20 - 160/32 = 15
+    b.put(2, 4).put(3, 4); // TP measurement system, reliability
+    b.put(2, 8); // measurement_count
+    b.put(1, 8).put(148, 8).put(2, 4).put(3, 4); // program loudness -20.75 LKFS
+    b.put(5, 8).put(40, 8).put(1, 4).put(2, 4); // loudness range 10 LU
+
+    match decode_payload(b"alou", &b.bytes, Some((1, 0))) {
+        StructuredData::Loudness(l) => {
+            assert_eq!(l.loudness_type, "alou");
+            let e = &l.entries[0];
+            assert_eq!(e.eq_set_id, Some(0));
+            assert_eq!(e.sample_peak_level, None);
+            assert_eq!(e.true_peak_level, Some(15.0));
+            assert_eq!(e.reliability_for_tp, 3);
+            assert_eq!(e.measurements[0].value, Some(-20.75));
+            assert_eq!(e.measurements[0].measurement_system, 2);
+            assert_eq!(e.measurements[1].value, Some(10.0));
+            assert_eq!(e.measurements[1].reliability, 2);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::*;
+use mp4box::{BoxRegion, box_at_offset, format_box_path, get_boxes};
+use std::io::Cursor;
+
+#[test]
+fn offsets_resolve_to_box_chains() {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let trak = |id| container(b"trak", &[tkhd(id, 0, 0, 0, 0)]);
+    let moov = container(b"moov", &[mvhd(1000, 0), trak(1), trak(2)]);
+    let mvhd_len = mvhd(1000, 0).len() as u64;
+    let trak_len = trak(1).len() as u64;
+    let data = [ftyp.clone(), moov].concat();
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+
+    let moov_at = ftyp.len() as u64;
+    let second_trak = moov_at + 8 + mvhd_len + trak_len;
+    let describe = |offset| format_box_path(&box_at_offset(&boxes, offset));
+
+    assert_eq!(describe(3), "ftyp @header");
+    assert_eq!(describe(12), "ftyp @payload");
+    assert_eq!(describe(moov_at + 8 + 9), "moov>mvhd @version_flags");
+    assert_eq!(describe(second_trak + 4), "moov>trak[1] @header");
+    // tkhd payload starts after its 8-byte header and version/flags
+    assert_eq!(describe(second_trak + 8 + 20), "moov>trak[1]>tkhd @payload");
+    assert!(box_at_offset(&boxes, data.len() as u64).is_empty());
+
+    let path = box_at_offset(&boxes, second_trak + 8);
+    assert_eq!(path.len(), 3);
+    assert_eq!(path[1].region, BoxRegion::Child);
+    assert_eq!((path[1].offset, path[1].size), (second_trak, trak_len));
+}
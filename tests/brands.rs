@@ -0,0 +1,43 @@
+mod common;
+
+use common::*;
+use mp4box::brands::{ContainerFamily, brand_analysis};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn analyze(data: Vec<u8>) -> mp4box::brands::BrandAnalysis {
+    let len = data.len() as u64;
+    brand_analysis(&get_boxes(&mut Cursor::new(data), len, true).unwrap()).unwrap()
+}
+
+#[test]
+fn families_from_brands() {
+    let a = analyze(ftyp(b"avif", &[b"avif", b"mif1", b"miaf"]));
+    assert_eq!(a.family, ContainerFamily::Avif);
+    assert_eq!(a.unknown, ["miaf"]);
+    assert!(a.constraints().contains(&"primary item is an AV1 image"));
+
+    let a = analyze(ftyp(b"isom", &[b"isom", b"iso2", b"avc1", b"mp41"]));
+    assert_eq!(a.family, ContainerFamily::Mp4);
+    let brands: Vec<_> = a.brands.iter().map(|b| b.brand.as_str()).collect();
+    assert_eq!(brands, ["isom", "iso2", "avc1", "mp41"]);
+
+    assert_eq!(
+        analyze(ftyp(b"qt  ", &[b"qt  ", b"isom"])).family,
+        ContainerFamily::QuickTime
+    );
+    assert_eq!(analyze(ftyp(b"abcd", &[])).family, ContainerFamily::Unknown);
+}
+
+#[test]
+fn styp_brands() {
+    let styp = boxed(b"styp", b"msdh\0\0\0\0msdhmsixcmfc");
+    let a = analyze(styp);
+    assert_eq!(a.major_brand, "msdh");
+    assert_eq!(a.family, ContainerFamily::Cmaf);
+    assert!(a.has_brand("msix"));
+    assert!(
+        a.constraints()
+            .contains(&"carries a sidx indexing its subsegments")
+    );
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use common::*;
+use mp4box::{ParseOptions, get_boxes_with_options};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+/// Counts seeks and records every byte range read.
+struct Tracing<R> {
+    inner: R,
+    seeks: usize,
+    reads: Vec<(u64, u64)>,
+}
+
+impl<R: Read + Seek> Read for Tracing<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let at = self.inner.stream_position()?;
+        let n = self.inner.read(buf)?;
+        self.reads.push((at, at + n as u64));
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Tracing<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        if !matches!(pos, SeekFrom::Current(0)) {
+            self.seeks += 1;
+        }
+        self.inner.seek(pos)
+    }
+}
+
+fn movie() -> (Vec<u8>, u64) {
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(3, 1000)]),
+            stsc(&[(1, 3, 1)]),
+            stsz(&[4, 4, 4]),
+            stco(&[0]),
+        ],
+    );
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 3000, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 3000, "und"),
+                    hdlr(b"soun", "Sound"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    );
+    let moov = container(b"moov", &[mvhd(1000, 3000), trak]);
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let mdat_at = (ftyp.len() + moov.len()) as u64;
+    let data = [
+        ftyp,
+        moov,
+        boxed(b"mdat", &[7; 12]),
+        boxed(b"free", &[0; 8]),
+    ]
+    .concat();
+    (data, mdat_at)
+}
+
+fn parse<'a>(
+    data: &'a [u8],
+    options: &ParseOptions,
+) -> (
+    Tracing<Cursor<&'a [u8]>>,
+    serde_json::Value,
+    serde_json::Value,
+) {
+    let mut r = Tracing {
+        inner: Cursor::new(data),
+        seeks: 0,
+        reads: Vec::new(),
+    };
+    let (boxes, warnings) = get_boxes_with_options(&mut r, data.len() as u64, options).unwrap();
+    let boxes = serde_json::to_value(&boxes).unwrap();
+    let warnings = serde_json::to_value(&warnings).unwrap();
+    (r, boxes, warnings)
+}
+
+#[test]
+fn buffering_gives_the_same_tree_with_fewer_seeks() {
+    let (data, _) = movie();
+    let (buffered, boxes, warnings) = parse(&data, &ParseOptions::new());
+    let (direct, direct_boxes, direct_warnings) =
+        parse(&data, &ParseOptions::new().buffer_limit(0));
+
+    assert_eq!(boxes, direct_boxes);
+    assert_eq!(warnings, direct_warnings);
+    assert!(
+        buffered.seeks * 2 < direct.seeks,
+        "{} seeks buffered, {} direct",
+        buffered.seeks,
+        direct.seeks
+    );
+}
+
+#[test]
+fn media_data_is_never_read() {
+    let (data, mdat_at) = movie();
+    let (r, boxes, _) = parse(&data, &ParseOptions::new().buffer_limit(u64::MAX));
+
+    assert_eq!(boxes[2]["typ"], "mdat");
+    let payload = mdat_at + 8..mdat_at + 20;
+    for &(start, end) in &r.reads {
+        assert!(
+            end <= payload.start || start >= payload.end,
+            "read {start}..{end} overlaps mdat"
+        );
+    }
+}
+
+#[test]
+fn boxes_over_the_limit_and_truncated_boxes_match() {
+    let (mut data, _) = movie();
+    // Cut the file inside the trailing free box
+    data.truncate(data.len() - 4);
+    let options = |limit| ParseOptions::new().lenient(true).buffer_limit(limit);
+    // Only ftyp and the cut free box fit in 64 bytes
+    let (_, boxes, warnings) = parse(&data, &options(64));
+    let (_, direct_boxes, direct_warnings) = parse(&data, &options(0));
+
+    assert_eq!(boxes, direct_boxes);
+    assert_eq!(warnings, direct_warnings);
+    assert_eq!(warnings[0]["available_end"], data.len());
+}
+
+#[test]
+fn single_pass_decodes_from_the_parse_reads() {
+    let (data, _) = movie();
+    let unbuffered = || ParseOptions::new().buffer_limit(0);
+    let (single, boxes, warnings) = parse(&data, &unbuffered().single_pass(1024));
+    let (two_pass, two_pass_boxes, two_pass_warnings) = parse(&data, &unbuffered());
+
+    assert_eq!(boxes, two_pass_boxes);
+    assert_eq!(warnings, two_pass_warnings);
+    assert!(boxes[1]["children"][0]["structured_data"].is_object());
+    let bytes = |r: &Tracing<_>| r.reads.iter().map(|(s, e)| e - s).sum::<u64>();
+    assert!(single.seeks < two_pass.seeks);
+    assert!(bytes(&single) < bytes(&two_pass));
+}
+
+#[test]
+fn single_pass_rereads_payloads_over_the_cap() {
+    let (data, _) = movie();
+    let unbuffered = || ParseOptions::new().buffer_limit(0);
+    // Only ftyp, stts and stco fit; everything else is read again
+    let (_, boxes, warnings) = parse(&data, &unbuffered().single_pass(16));
+    let (_, two_pass_boxes, two_pass_warnings) = parse(&data, &unbuffered());
+
+    assert_eq!(boxes, two_pass_boxes);
+    assert_eq!(warnings, two_pass_warnings);
+}
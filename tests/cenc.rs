@@ -0,0 +1,109 @@
+mod common;
+
+use common::*;
+use mp4box::cenc::{KeySource, sample_encryption};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+/// tenc/seig body: reserved, pattern, isProtected, IV size, KID.
+fn key(kid: u8, iv_size: u8) -> Vec<u8> {
+    let mut p = vec![0, 0, 1, iv_size];
+    p.extend_from_slice(&[kid; 16]);
+    p
+}
+
+fn seig_sgpd(kid: u8) -> Vec<u8> {
+    let mut p = b"seig".to_vec();
+    p.extend_from_slice(&20u32.to_be_bytes()); // default_length
+    p.extend_from_slice(&1u32.to_be_bytes());
+    p.extend_from_slice(&key(kid, 16));
+    full_box(b"sgpd", 1, 0, &p)
+}
+
+fn seig_sbgp(entries: &[(u32, u32)]) -> Vec<u8> {
+    let mut p = b"seig".to_vec();
+    p.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for (count, index) in entries {
+        p.extend_from_slice(&count.to_be_bytes());
+        p.extend_from_slice(&index.to_be_bytes());
+    }
+    full_box(b"sbgp", 0, 0, &p)
+}
+
+#[test]
+fn seig_groups_override_tenc_defaults() {
+    let sinf = container(
+        b"sinf",
+        &[container(
+            b"schi",
+            &[full_box(b"tenc", 0, 0, &key(0xAA, 8))],
+        )],
+    );
+    let mut encv = vec![0u8; 78];
+    encv.extend_from_slice(&sinf);
+    let mut stsd = 1u32.to_be_bytes().to_vec();
+    stsd.extend_from_slice(&boxed(b"encv", &encv));
+
+    let stbl = container(
+        b"stbl",
+        &[
+            full_box(b"stsd", 0, 0, &stsd),
+            stsz(&[1, 1, 1, 1]),
+            seig_sgpd(0xBB),
+            seig_sbgp(&[(1, 0), (2, 1), (1, 0)]),
+        ],
+    );
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 0, 0, 0, 0),
+            container(b"mdia", &[container(b"minf", &[stbl])]),
+        ],
+    );
+    let traf = container(
+        b"traf",
+        &[
+            tfhd(1, None),
+            trun(0, &[1, 1]),
+            seig_sgpd(0xCC),
+            seig_sbgp(&[(2, 0x10001)]),
+        ],
+    );
+    let data = [
+        container(b"moov", &[trak]),
+        container(b"moof", &[mfhd(1), traf]),
+    ]
+    .concat();
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    let tracks = sample_encryption(&boxes);
+    assert_eq!(tracks.len(), 1);
+    let t = &tracks[0];
+    assert_eq!(t.tenc.as_ref().unwrap().kid, "aa".repeat(16));
+    assert_eq!(t.tenc.as_ref().unwrap().per_sample_iv_size, 8);
+
+    let ranges: Vec<_> = t
+        .ranges
+        .iter()
+        .map(|r| {
+            (
+                r.first_sample,
+                r.sample_count,
+                r.source,
+                &r.settings.kid[..2],
+            )
+        })
+        .collect();
+    assert_eq!(
+        ranges,
+        [
+            (0, 1, KeySource::Tenc, "aa"),
+            (1, 2, KeySource::SampleGroup, "bb"),
+            (3, 1, KeySource::Tenc, "aa"),
+            (4, 2, KeySource::SampleGroup, "cc"),
+        ]
+    );
+    assert_eq!(t.settings_of(2).unwrap().settings.per_sample_iv_size, 16);
+    assert!(t.settings_of(6).is_none());
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use common::*;
+use mp4box::chapters::{Chapter, chapters};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn chpl(entries: &[(u64, &str)]) -> Vec<u8> {
+    let mut p = 0u32.to_be_bytes().to_vec(); // reserved (version 1)
+    p.push(entries.len() as u8);
+    for (start, title) in entries {
+        p.extend_from_slice(&start.to_be_bytes());
+        p.push(title.len() as u8);
+        p.extend_from_slice(title.as_bytes());
+    }
+    full_box(b"chpl", 1, 0, &p)
+}
+
+fn text_sample(text: &[u8]) -> Vec<u8> {
+    let mut s = (text.len() as u16).to_be_bytes().to_vec();
+    s.extend_from_slice(text);
+    s
+}
+
+fn trak(track_id: u32, handler: &[u8; 4], extra: Vec<u8>, stbl: Vec<u8>) -> Vec<u8> {
+    container(
+        b"trak",
+        &[
+            tkhd(track_id, 10_000, 0, 0, 0),
+            extra,
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 10_000, "eng"),
+                    hdlr(handler, "Handler"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    )
+}
+
+fn read(data: Vec<u8>) -> Vec<Chapter> {
+    let len = data.len() as u64;
+    let mut cursor = Cursor::new(data);
+    let boxes = get_boxes(&mut cursor, len, true).unwrap();
+    chapters(&mut cursor, &boxes).unwrap()
+}
+
+#[test]
+fn nero_chapter_list() {
+    let moov = container(
+        b"moov",
+        &[
+            mvhd(1000, 10_000),
+            container(b"udta", &[chpl(&[(45_000_000, "Second"), (0, "First")])]),
+        ],
+    );
+    assert_eq!(
+        read(moov),
+        vec![
+            Chapter {
+                start: 0.0,
+                title: "First".into()
+            },
+            Chapter {
+                start: 4.5,
+                title: "Second".into()
+            },
+        ]
+    );
+}
+
+#[test]
+fn quicktime_chapter_track() {
+    let ftyp = ftyp(b"M4A ", &[b"M4A "]);
+    let intro = text_sample(b"Intro");
+    // UTF-16 with a byte order mark
+    let utf16: Vec<u8> = [0xFE, 0xFF]
+        .into_iter()
+        .chain("Ende".encode_utf16().flat_map(|u| u.to_be_bytes()))
+        .collect();
+    let outro = text_sample(&utf16);
+    let first = (ftyp.len() + 8) as u32;
+    let mdat = boxed(b"mdat", &[intro.clone(), outro.clone()].concat());
+
+    let audio = trak(
+        1,
+        b"soun",
+        container(b"tref", &[boxed(b"chap", &2u32.to_be_bytes())]),
+        container(b"stbl", &[stts(&[]), stsc(&[]), stsz(&[]), stco(&[])]),
+    );
+    let text = trak(
+        2,
+        b"text",
+        Vec::new(),
+        container(
+            b"stbl",
+            &[
+                stts(&[(1, 2500), (1, 7500)]),
+                stsc(&[(1, 1, 1)]),
+                stsz(&[intro.len() as u32, outro.len() as u32]),
+                stco(&[first, first + intro.len() as u32]),
+            ],
+        ),
+    );
+    // A chpl is ignored when a chapter track exists
+    let moov = container(
+        b"moov",
+        &[
+            mvhd(1000, 10_000),
+            audio,
+            text,
+            container(b"udta", &[chpl(&[(0, "Nero")])]),
+        ],
+    );
+
+    let chapters = read([ftyp, mdat, moov].concat());
+    assert_eq!(
+        chapters,
+        vec![
+            Chapter {
+                start: 0.0,
+                title: "Intro".into()
+            },
+            Chapter {
+                start: 2.5,
+                title: "Ende".into()
+            },
+        ]
+    );
+}
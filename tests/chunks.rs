@@ -0,0 +1,56 @@
+mod common;
+
+use common::*;
+use mp4box::chunks::{ChunkInfo, chunks};
+use mp4box::{get_boxes, tracks};
+use std::io::Cursor;
+
+#[test]
+fn chunk_table_from_stsc_runs() {
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(7, 100)]),
+            stsc(&[(1, 3, 1), (3, 1, 2)]),
+            stsz(&[1, 2, 3, 4, 5, 6, 7]),
+            stco(&[0x100, 0x200, 0x300, 0x400]),
+        ],
+    );
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 0, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 0, "und"),
+                    hdlr(b"vide", "Video"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    );
+    let data = container(b"moov", &[trak]);
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+
+    let chunk = |index, file_offset, first_sample, sample_count, size, sdi| ChunkInfo {
+        index,
+        file_offset,
+        first_sample,
+        sample_count,
+        size,
+        sample_description_index: sdi,
+    };
+    let got = chunks(&tracks(&boxes)[0]);
+    assert_eq!(
+        got,
+        [
+            chunk(1, 0x100, 0, 3, 6, 1),
+            chunk(2, 0x200, 3, 3, 15, 1),
+            chunk(3, 0x300, 6, 1, 7, 2),
+            chunk(4, 0x400, 7, 1, 0, 2),
+        ]
+    );
+    assert_eq!(got[1].end(), 0x200 + 15);
+    assert_eq!(chunk(1, u64::MAX - 4, 0, 1, 16, 1).end(), u64::MAX);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::*;
+use std::process::Command;
+
+/// Run `mp4dump` on `data` written to a temp file named `name`.
+fn mp4dump(data: &[u8], name: &str, args: &[&str]) -> String {
+    mp4dump_in(data, name, args, "en_US.UTF-8")
+}
+
+/// Run `mp4dump` with `locale` as `LANG`.
+fn mp4dump_in(data: &[u8], name: &str, args: &[&str], locale: &str) -> String {
+    let path = temp_file(data, name);
+    let out = Command::new(env!("CARGO_BIN_EXE_mp4dump"))
+        .arg(&path)
+        .args(args)
+        .env_remove("LC_ALL")
+        .env_remove("LC_NUMERIC")
+        .env("LANG", locale)
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "{}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    String::from_utf8(out.stdout).unwrap()
+}
+
+fn progressive() -> (Vec<u8>, u64) {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let mdat_offset = ftyp.len() as u64;
+    let first = (mdat_offset + 8) as u32;
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(3, 1000)]),
+            stsc(&[(1, 3, 1)]),
+            stsz(&[4, 4, 4]),
+            stco(&[first]),
+        ],
+    );
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 3000, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 3000, "und"),
+                    hdlr(b"soun", "Sound"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    );
+    let moov = container(b"moov", &[mvhd(1000, 3000), trak]);
+    let data = [ftyp, boxed(b"mdat", &[7; 12]), moov].concat();
+    (data, mdat_offset)
+}
+
+fn fragment(trun: Vec<u8>) -> Vec<u8> {
+    fragment_with(tfhd(1, None), trun)
+}
+
+fn fragment_with(tfhd: Vec<u8>, trun: Vec<u8>) -> Vec<u8> {
+    let moov = container(b"moov", &[container(b"mvex", &[trex(1, 1000, 0)])]);
+    let moof = container(b"moof", &[mfhd(7), container(b"traf", &[tfhd, trun])]);
+    [moov, moof, boxed(b"mdat", &[0; 60])].concat()
+}
+
+#[test]
+fn json_carries_media_ranges_when_decoding() {
+    let (data, mdat_offset) = progressive();
+    let out = mp4dump(&data, "mp4box_cli_json_ranges.mp4", &["--json", "--decode"]);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+
+    let trak = &json[2]["children"][1];
+    assert_eq!(trak["typ"], "trak");
+    assert_eq!(
+        trak["media_ranges"],
+        serde_json::json!([{"offset": mdat_offset + 8, "size": 12, "mdat_offset": mdat_offset}])
+    );
+    assert!(trak["children"][0]["decoded"].is_string());
+
+    let out = mp4dump(&data, "mp4box_cli_json_plain.mp4", &["--json"]);
+    let json: serde_json::Value = serde_json::from_str(&out).unwrap();
+    assert!(json[2]["children"][1]["media_ranges"].is_null());
+    assert!(json[2]["children"][1]["children"][0]["decoded"].is_null());
+}
+
+#[test]
+fn decode_summarizes_fragments() {
+    let data = fragment(trun(0, &[10, 20, 30]));
+    let out = mp4dump(&data, "mp4box_cli_fragment.mp4", &["--decode"]);
+    assert!(
+        out.contains("(container) [seq=7 tracks=1 samples=3 duration=1:3000 mdat=60 bytes]"),
+        "{out}"
+    );
+}
+
+#[test]
+fn fragment_summaries_prefer_tfhd_defaults() {
+    let data = fragment_with(tfhd(1, Some(500)), trun(0, &[10, 20, 30]));
+    let out = mp4dump(&data, "mp4box_cli_fragment_tfhd.mp4", &["--decode"]);
+    assert!(
+        out.contains("samples=3 duration=1:1500 mdat=60 bytes]"),
+        "{out}"
+    );
+}
+
+#[test]
+fn decode_applies_table_limits() {
+    // A trun without per-sample fields declaring 0xFFFFFFFF samples
+    let bomb = full_box(b"trun", 0, 0, &[0xFF, 0xFF, 0xFF, 0xFF]);
+    let out = mp4dump(&fragment(bomb), "mp4box_cli_trun_limit.mp4", &["--decode"]);
+    assert!(
+        out.contains("-> [not decoded: 4294967295 entries over limit]"),
+        "{out}"
+    );
+}
+
+#[test]
+fn numbers_are_plain_unless_human_is_asked_for() {
+    // A 1.5 KiB mdat so its size needs grouping
+    let data = [ftyp(b"isom", &[b"isom"]), boxed(b"mdat", &[0; 1528])].concat();
+    let line = |out: String| {
+        out.lines()
+            .find(|l| l.ends_with("mdat"))
+            .unwrap()
+            .to_string()
+    };
+
+    let out = mp4dump_in(&data, "mp4box_cli_plain.mp4", &[], "de_DE.UTF-8");
+    assert_eq!(line(out), "  0x14       1536 mdat");
+    let out = mp4dump(&data, "mp4box_cli_human.mp4", &["--human"]);
+    assert_eq!(line(out), "  0x14      1,536 mdat");
+    let out = mp4dump_in(
+        &data,
+        "mp4box_cli_human_de.mp4",
+        &["--human"],
+        "de_DE.UTF-8",
+    );
+    assert_eq!(line(out), "  0x14      1.536 mdat");
+}
@@ -0,0 +1,274 @@
+//! Builders for small synthetic MP4 files used across integration tests.
+#![allow(dead_code)]
+
+/// Plain box: size + type + payload.
+pub fn boxed(typ: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+    let mut v = Vec::new();
+    v.extend_from_slice(&(8 + payload.len() as u32).to_be_bytes());
+    v.extend_from_slice(typ);
+    v.extend_from_slice(payload);
+    v
+}
+
+/// FullBox: size + type + version + flags + payload.
+pub fn full_box(typ: &[u8; 4], version: u8, flags: u32, payload: &[u8]) -> Vec<u8> {
+    let mut body = vec![version];
+    body.extend_from_slice(&flags.to_be_bytes()[1..]);
+    body.extend_from_slice(payload);
+    boxed(typ, &body)
+}
+
+/// Container box holding the concatenation of `children`.
+pub fn container(typ: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+    boxed(typ, &children.concat())
+}
+
+pub fn ftyp(major: &[u8; 4], compatible: &[&[u8; 4]]) -> Vec<u8> {
+    let mut p = major.to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes());
+    for c in compatible {
+        p.extend_from_slice(*c);
+    }
+    boxed(b"ftyp", &p)
+}
+
+/// Version 0 mvhd.
+pub fn mvhd(timescale: u32, duration: u32) -> Vec<u8> {
+    let mut p = Vec::new();
+    p.extend_from_slice(&0u32.to_be_bytes()); // creation
+    p.extend_from_slice(&0u32.to_be_bytes()); // modification
+    p.extend_from_slice(&timescale.to_be_bytes());
+    p.extend_from_slice(&duration.to_be_bytes());
+    p.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // rate 1.0
+    p.extend_from_slice(&0x0100u16.to_be_bytes()); // volume 1.0
+    p.extend_from_slice(&[0u8; 10]); // reserved
+    p.extend_from_slice(&identity_matrix());
+    p.extend_from_slice(&[0u8; 24]); // pre_defined
+    p.extend_from_slice(&3u32.to_be_bytes()); // next_track_id
+    full_box(b"mvhd", 0, 0, &p)
+}
+
+pub fn identity_matrix() -> Vec<u8> {
+    matrix([0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000])
+}
+
+pub fn matrix(m: [i32; 9]) -> Vec<u8> {
+    m.iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+/// Version 0 tkhd with the given matrix.
+pub fn tkhd_with_matrix(
+    track_id: u32,
+    duration: u32,
+    alternate_group: u16,
+    width: u16,
+    height: u16,
+    m: &[u8],
+) -> Vec<u8> {
+    let mut p = Vec::new();
+    p.extend_from_slice(&0u32.to_be_bytes()); // creation
+    p.extend_from_slice(&0u32.to_be_bytes()); // modification
+    p.extend_from_slice(&track_id.to_be_bytes());
+    p.extend_from_slice(&0u32.to_be_bytes()); // reserved
+    p.extend_from_slice(&duration.to_be_bytes());
+    p.extend_from_slice(&[0u8; 8]); // reserved
+    p.extend_from_slice(&0u16.to_be_bytes()); // layer
+    p.extend_from_slice(&alternate_group.to_be_bytes());
+    p.extend_from_slice(&0u16.to_be_bytes()); // volume
+    p.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    p.extend_from_slice(m);
+    p.extend_from_slice(&((width as u32) << 16).to_be_bytes());
+    p.extend_from_slice(&((height as u32) << 16).to_be_bytes());
+    full_box(b"tkhd", 0, 7, &p)
+}
+
+pub fn tkhd(
+    track_id: u32,
+    duration: u32,
+    alternate_group: u16,
+    width: u16,
+    height: u16,
+) -> Vec<u8> {
+    tkhd_with_matrix(
+        track_id,
+        duration,
+        alternate_group,
+        width,
+        height,
+        &identity_matrix(),
+    )
+}
+
+/// Version 0 mdhd; `lang` is a three-letter ISO 639-2/T code.
+pub fn mdhd(timescale: u32, duration: u32, lang: &str) -> Vec<u8> {
+    let b = lang.as_bytes();
+    let packed = ((b[0] - 0x60) as u16) << 10 | ((b[1] - 0x60) as u16) << 5 | (b[2] - 0x60) as u16;
+    let mut p = Vec::new();
+    p.extend_from_slice(&0u32.to_be_bytes());
+    p.extend_from_slice(&0u32.to_be_bytes());
+    p.extend_from_slice(&timescale.to_be_bytes());
+    p.extend_from_slice(&duration.to_be_bytes());
+    p.extend_from_slice(&packed.to_be_bytes());
+    p.extend_from_slice(&0u16.to_be_bytes());
+    full_box(b"mdhd", 0, 0, &p)
+}
+
+pub fn hdlr(handler: &[u8; 4], name: &str) -> Vec<u8> {
+    let mut p = vec![0u8; 4];
+    p.extend_from_slice(handler);
+    p.extend_from_slice(&[0u8; 12]);
+    p.extend_from_slice(name.as_bytes());
+    p.push(0);
+    full_box(b"hdlr", 0, 0, &p)
+}
+
+pub fn kind(scheme: &str, value: &str) -> Vec<u8> {
+    let mut p = scheme.as_bytes().to_vec();
+    p.push(0);
+    p.extend_from_slice(value.as_bytes());
+    p.push(0);
+    full_box(b"kind", 0, 0, &p)
+}
+
+/// stts with (count, delta) runs.
+pub fn stts(entries: &[(u32, u32)]) -> Vec<u8> {
+    let mut p = (entries.len() as u32).to_be_bytes().to_vec();
+    for (c, d) in entries {
+        p.extend_from_slice(&c.to_be_bytes());
+        p.extend_from_slice(&d.to_be_bytes());
+    }
+    full_box(b"stts", 0, 0, &p)
+}
+
+/// stsc with (first_chunk, samples_per_chunk, sample_description_index) runs.
+pub fn stsc(entries: &[(u32, u32, u32)]) -> Vec<u8> {
+    let mut p = (entries.len() as u32).to_be_bytes().to_vec();
+    for (f, n, d) in entries {
+        p.extend_from_slice(&f.to_be_bytes());
+        p.extend_from_slice(&n.to_be_bytes());
+        p.extend_from_slice(&d.to_be_bytes());
+    }
+    full_box(b"stsc", 0, 0, &p)
+}
+
+pub fn stsz(sizes: &[u32]) -> Vec<u8> {
+    let mut p = 0u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&(sizes.len() as u32).to_be_bytes());
+    for s in sizes {
+        p.extend_from_slice(&s.to_be_bytes());
+    }
+    full_box(b"stsz", 0, 0, &p)
+}
+
+pub fn stco(offsets: &[u32]) -> Vec<u8> {
+    let mut p = (offsets.len() as u32).to_be_bytes().to_vec();
+    for o in offsets {
+        p.extend_from_slice(&o.to_be_bytes());
+    }
+    full_box(b"stco", 0, 0, &p)
+}
+
+pub fn stss(samples: &[u32]) -> Vec<u8> {
+    let mut p = (samples.len() as u32).to_be_bytes().to_vec();
+    for s in samples {
+        p.extend_from_slice(&s.to_be_bytes());
+    }
+    full_box(b"stss", 0, 0, &p)
+}
+
+pub fn trex(track_id: u32, default_duration: u32, default_size: u32) -> Vec<u8> {
+    let mut p = Vec::new();
+    for v in [track_id, 1, default_duration, default_size, 0] {
+        p.extend_from_slice(&v.to_be_bytes());
+    }
+    full_box(b"trex", 0, 0, &p)
+}
+
+pub fn mfhd(sequence_number: u32) -> Vec<u8> {
+    full_box(b"mfhd", 0, 0, &sequence_number.to_be_bytes())
+}
+
+/// tfhd with default-base-is-moof and an optional default sample duration.
+pub fn tfhd(track_id: u32, default_duration: Option<u32>) -> Vec<u8> {
+    let mut flags = 0x020000;
+    let mut p = track_id.to_be_bytes().to_vec();
+    if let Some(d) = default_duration {
+        flags |= 0x08;
+        p.extend_from_slice(&d.to_be_bytes());
+    }
+    full_box(b"tfhd", 0, flags, &p)
+}
+
+pub fn tfdt(base_media_decode_time: u64) -> Vec<u8> {
+    full_box(b"tfdt", 1, 0, &base_media_decode_time.to_be_bytes())
+}
+
+/// trun with a data_offset and per-sample sizes.
+pub fn trun(data_offset: i32, sizes: &[u32]) -> Vec<u8> {
+    let mut p = (sizes.len() as u32).to_be_bytes().to_vec();
+    p.extend_from_slice(&data_offset.to_be_bytes());
+    for s in sizes {
+        p.extend_from_slice(&s.to_be_bytes());
+    }
+    full_box(b"trun", 0, 0x000201, &p)
+}
+
+/// iTunes `data` atom with the given well-known type.
+pub fn data_atom(type_indicator: u32, value: &[u8]) -> Vec<u8> {
+    let mut p = type_indicator.to_be_bytes().to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes()); // locale
+    p.extend_from_slice(value);
+    boxed(b"data", &p)
+}
+
+/// ilst item holding one UTF-8 text value.
+pub fn text_item(typ: &[u8; 4], text: &str) -> Vec<u8> {
+    container(typ, &[data_atom(1, text.as_bytes())])
+}
+
+/// Parse `data` with payload decoding.
+pub fn decode(data: Vec<u8>) -> Vec<mp4box::Box> {
+    let len = data.len() as u64;
+    mp4box::get_boxes(&mut std::io::Cursor::new(data), len, true).unwrap()
+}
+
+/// Decode `payload` with the default registry's decoder for `typ`;
+/// `full` holds the version and flags of a FullBox.
+pub fn decode_payload(
+    typ: &[u8; 4],
+    payload: &[u8],
+    full: Option<(u8, u32)>,
+) -> mp4box::registry::StructuredData {
+    use mp4box::boxes::{BoxHeader, BoxKey, FourCC};
+    use mp4box::registry::{BoxValue, default_registry};
+
+    let header_size = if full.is_some() { 12 } else { 8 };
+    let header = BoxHeader {
+        typ: FourCC(*typ),
+        uuid: None,
+        size: header_size + payload.len() as u64,
+        header_size: 8,
+        start: 0,
+    };
+    let value = default_registry()
+        .decode(
+            &BoxKey::FourCC(FourCC(*typ)),
+            &mut std::io::Cursor::new(payload),
+            &header,
+            full.map(|(version, _)| version),
+            full.map(|(_, flags)| flags),
+        )
+        .expect("decoder registered")
+        .expect("decode failed");
+    match value {
+        BoxValue::Structured(data) => data,
+        other => panic!("expected structured data, got {:?}", other),
+    }
+}
+
+/// Write `data` to a uniquely named temp file and return its path.
+pub fn temp_file(data: &[u8], name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, data).expect("write temp file failed");
+    path
+}
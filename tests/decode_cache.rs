@@ -0,0 +1,102 @@
+use mp4box::boxes::{BoxHeader, BoxKey, FourCC};
+use mp4box::registry::{BoxDecoder, BoxValue, Registry};
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Returns the payload as text and counts its calls; fails on "bad".
+struct Counting(Arc<AtomicUsize>);
+
+impl BoxDecoder for Counting {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+        anyhow::ensure!(text != "bad", "bad payload");
+        Ok(BoxValue::Text(text))
+    }
+}
+
+fn registry(calls: &Arc<AtomicUsize>) -> Registry {
+    Registry::new().with_decoder(key(), "test", Box::new(Counting(calls.clone())))
+}
+
+fn key() -> BoxKey {
+    BoxKey::FourCC(FourCC(*b"test"))
+}
+
+fn header(start: u64, size: u64) -> BoxHeader {
+    BoxHeader {
+        size,
+        typ: FourCC(*b"test"),
+        uuid: None,
+        header_size: 8,
+        start,
+    }
+}
+
+fn decode(reg: &Registry, payload: &str, start: u64) -> Result<String, String> {
+    let hdr = header(start, 8 + payload.len() as u64);
+    let mut r = Cursor::new(payload.as_bytes());
+    match reg.decode(&key(), &mut r, &hdr, None, None).unwrap() {
+        Ok(BoxValue::Text(text)) => Ok(text),
+        Ok(_) => panic!("expected text"),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[test]
+fn repeated_decodes_come_from_the_cache() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let reg = registry(&calls).with_cache(1024);
+
+    assert_eq!(decode(&reg, "abc", 0), Ok("abc".into()));
+    // Same box: the payload is not read again
+    assert_eq!(decode(&reg, "xyz", 0), Ok("abc".into()));
+    assert_eq!(decode(&reg, "bad", 100), Err("bad payload".into()));
+    assert_eq!(decode(&reg, "bad", 100), Err("bad payload".into()));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    reg.clear_cache();
+    assert_eq!(decode(&reg, "xyz", 0), Ok("xyz".into()));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn no_cache_by_default() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let reg = registry(&calls);
+
+    decode(&reg, "abc", 0).unwrap();
+    assert_eq!(decode(&reg, "xyz", 0), Ok("xyz".into()));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn oldest_values_leave_when_over_budget() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    // Room for two 10-byte boxes
+    let reg = registry(&calls).with_cache(20);
+
+    decode(&reg, "aa", 0).unwrap();
+    decode(&reg, "bb", 10).unwrap();
+    decode(&reg, "cc", 20).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    assert_eq!(decode(&reg, "--", 20), Ok("cc".into()));
+    assert_eq!(decode(&reg, "--", 10), Ok("bb".into()));
+    assert_eq!(decode(&reg, "--", 0), Ok("--".into()));
+    assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+    // Bigger than the whole budget: never kept
+    let big = "x".repeat(30);
+    decode(&reg, &big, 40).unwrap();
+    decode(&reg, &big, 40).unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 6);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::*;
+use mp4box::boxes::{BoxHeader, BoxKey, FourCC};
+use mp4box::registry::{BoxDecoder, BoxValue, GenericDecoder, Registry, default_registry};
+use mp4box::{ParseOptions, get_boxes_with_options};
+use std::io::{Cursor, Read};
+
+/// Succeeds with its label when the payload starts with `accept`.
+struct Picky {
+    label: &'static str,
+    accept: u8,
+}
+
+impl BoxDecoder for Picky {
+    fn decode(
+        &self,
+        r: &mut dyn Read,
+        _hdr: &BoxHeader,
+        _version: Option<u8>,
+        _flags: Option<u32>,
+    ) -> anyhow::Result<BoxValue> {
+        let mut first = [0u8; 1];
+        r.read_exact(&mut first)?;
+        anyhow::ensure!(
+            first[0] == self.accept,
+            "{} rejects {}",
+            self.label,
+            first[0]
+        );
+        Ok(BoxValue::Text(self.label.to_string()))
+    }
+}
+
+fn picky(label: &'static str, accept: u8) -> Box<Picky> {
+    Box::new(Picky { label, accept })
+}
+
+fn key(typ: &[u8; 4]) -> BoxKey {
+    BoxKey::FourCC(FourCC(*typ))
+}
+
+fn decode(reg: &Registry, typ: &[u8; 4], payload: &[u8]) -> Option<Result<String, String>> {
+    let hdr = BoxHeader {
+        size: 8 + payload.len() as u64,
+        typ: FourCC(*typ),
+        uuid: None,
+        header_size: 8,
+        start: 0,
+    };
+    let value = reg.decode(&key(typ), &mut Cursor::new(payload), &hdr, None, None)?;
+    Some(match value {
+        Ok(BoxValue::Text(text)) => Ok(text),
+        Ok(_) => panic!("expected text"),
+        Err(e) => Err(e.to_string()),
+    })
+}
+
+#[test]
+fn decoders_are_tried_by_priority_until_one_succeeds() {
+    let reg = Registry::new()
+        .with_decoder_priority(key(b"test"), "low", -1, picky("low", 2))
+        .with_decoder_priority(key(b"test"), "high", 10, picky("high", 1))
+        .with_decoder(key(b"othr"), "other", picky("other", 1))
+        .with_decoder_priority(key(b"test"), "mid", 0, picky("mid", 1));
+
+    assert_eq!(decode(&reg, b"test", &[1]), Some(Ok("high".into())));
+    // Every decoder reads the payload from the start
+    assert_eq!(decode(&reg, b"test", &[2]), Some(Ok("low".into())));
+    // All failed: the highest priority error
+    assert_eq!(
+        decode(&reg, b"test", &[3]),
+        Some(Err("high rejects 3".into()))
+    );
+    assert_eq!(decode(&reg, b"none", &[1]), None);
+}
+
+#[test]
+fn with_decoder_replaces_the_whole_chain() {
+    let reg = Registry::new()
+        .with_decoder_priority(key(b"test"), "a", 5, picky("a", 1))
+        .with_decoder_priority(key(b"test"), "b", 0, picky("b", 2))
+        .with_decoder(key(b"test"), "c", picky("c", 3));
+
+    assert_eq!(decode(&reg, b"test", &[1]), Some(Err("c rejects 1".into())));
+    assert_eq!(decode(&reg, b"test", &[3]), Some(Ok("c".into())));
+}
+
+#[test]
+fn catch_all_takes_only_keys_without_decoders() {
+    let reg = Registry::new()
+        .with_decoder(key(b"test"), "test", picky("test", 1))
+        .with_catch_all("generic", Box::new(GenericDecoder));
+
+    assert_eq!(decode(&reg, b"none", b"hello\0"), Some(Ok("hello".into())));
+    assert_eq!(decode(&reg, b"test", &[1]), Some(Ok("test".into())));
+    assert_eq!(
+        decode(&reg, b"test", &[0xff]),
+        Some(Err("test rejects 255".into()))
+    );
+}
+
+#[test]
+fn generic_decoder_previews_binary_payloads() {
+    let reg = Registry::new().with_catch_all("generic", Box::new(GenericDecoder));
+
+    assert_eq!(
+        decode(&reg, b"bin ", &[0, 1, 0xff]),
+        Some(Ok("0001ff (3 bytes)".into()))
+    );
+    let long = [0x80u8; 40];
+    let preview = format!("{} ... (40 bytes)", "80".repeat(32));
+    assert_eq!(decode(&reg, b"bin ", &long), Some(Ok(preview)));
+}
+
+#[test]
+fn structured_decoder_falls_back_to_generic() {
+    // An mdhd too short for its decoder still gets a value
+    let reg = default_registry().with_decoder_priority(
+        key(b"mdhd"),
+        "generic",
+        -1,
+        Box::new(GenericDecoder),
+    );
+    let data = [full_box(b"mdhd", 0, 0, b"abcd"), boxed(b"zzzz", b"note")].concat();
+    let options =
+        ParseOptions::new().registry(reg.with_catch_all("generic", Box::new(GenericDecoder)));
+    let (boxes, warnings) =
+        get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, &options).unwrap();
+
+    assert!(warnings.is_empty());
+    assert_eq!(boxes[0].decoded.as_deref(), Some("abcd"));
+    assert_eq!(boxes[1].decoded.as_deref(), Some("note"));
+}
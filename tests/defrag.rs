@@ -0,0 +1,114 @@
+mod common;
+
+use common::*;
+use mp4box::defrag::rebuild_sample_tables;
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn fragmented_moov() -> Vec<u8> {
+    let stbl = container(b"stbl", &[stts(&[]), stsc(&[]), stsz(&[]), stco(&[])]);
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 0, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 0, "und"),
+                    hdlr(b"vide", "Video"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    );
+    container(b"moov", &[trak, container(b"mvex", &[trex(1, 100, 0)])])
+}
+
+/// moof whose single traf points at the mdat right after it.
+fn moof(seq: u32, default_duration: Option<u32>, sizes: &[u32]) -> Vec<u8> {
+    let build = |data_offset: i32| {
+        let traf = [tfhd(1, default_duration), trun(data_offset, sizes)];
+        container(b"moof", &[mfhd(seq), container(b"traf", &traf)])
+    };
+    let len = build(0).len() as i32;
+    build(len + 8)
+}
+
+#[test]
+fn fragments_become_one_sample_table() {
+    let mut data = fragmented_moov();
+    let mut chunk_offsets = Vec::new();
+    for (seq, duration, sizes) in [
+        (1, None, &[10u32, 20][..]),
+        (2, Some(200), &[5][..]),
+        (3, Some(200), &[5, 5][..]),
+    ] {
+        let moof = moof(seq, duration, sizes);
+        chunk_offsets.push((data.len() + moof.len() + 8) as u32);
+        let total: u32 = sizes.iter().sum();
+        data.extend(moof);
+        data.extend(boxed(b"mdat", &vec![0; total as usize]));
+    }
+
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    let tables = rebuild_sample_tables(&boxes).unwrap();
+    assert_eq!(tables.len(), 1);
+    let t = &tables[0];
+    assert_eq!((t.track_id, t.handler_type.as_str()), (1, "vide"));
+    assert_eq!(t.sample_count(), 5);
+    assert_eq!(t.duration, 800);
+
+    let stts: Vec<_> = t
+        .stts
+        .entries
+        .iter()
+        .map(|e| (e.sample_count, e.sample_delta))
+        .collect();
+    assert_eq!(stts, [(2, 100), (3, 200)]);
+
+    // One chunk per trun
+    let stsc: Vec<_> = t
+        .stsc
+        .entries
+        .iter()
+        .map(|e| (e.first_chunk, e.samples_per_chunk))
+        .collect();
+    assert_eq!(stsc, [(1, 2), (2, 1), (3, 2)]);
+    assert_eq!(t.chunk_count(), 3);
+    assert_eq!(t.stco.as_ref().unwrap().chunk_offsets, chunk_offsets);
+    assert!(t.co64.is_none());
+
+    assert_eq!(t.stsz.sample_size, 0);
+    assert_eq!(t.stsz.sample_sizes, [10, 20, 5, 5, 5]);
+    // No composition offsets and every sample is a sync sample
+    assert!(t.ctts.is_none());
+    assert!(t.stss.is_none());
+}
+
+#[test]
+fn constant_sizes_use_a_single_sample_size() {
+    let mut data = fragmented_moov();
+    let moof = moof(1, None, &[7, 7, 7]);
+    data.extend(moof);
+    data.extend(boxed(b"mdat", &[0; 21]));
+
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    let t = &rebuild_sample_tables(&boxes).unwrap()[0];
+    assert_eq!((t.stsz.sample_size, t.stsz.sample_count), (7, 3));
+    assert!(t.stsz.sample_sizes.is_empty());
+    assert_eq!(t.stsc.entries.len(), 1);
+}
+
+#[test]
+fn samples_past_the_address_space_do_not_panic() {
+    // tfhd base_data_offset just below u64::MAX
+    let mut p = 1u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&(u64::MAX - 4).to_be_bytes());
+    let traf = [full_box(b"tfhd", 0, 0x01, &p), trun(0, &[10, 10])];
+    let moof = container(b"moof", &[mfhd(1), container(b"traf", &traf)]);
+    let data = [fragmented_moov(), moof].concat();
+
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    let tables = rebuild_sample_tables(&boxes).unwrap();
+    assert_eq!(tables[0].sample_count(), 1);
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::*;
+use mp4box::durations::{DEFAULT_TOLERANCE, DurationSource, check_durations};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn trak(
+    id: u32,
+    tkhd_duration: u32,
+    elst_duration: Option<u32>,
+    timescale: u32,
+    mdhd_duration: u32,
+    deltas: &[(u32, u32)],
+) -> Vec<u8> {
+    let stbl = container(b"stbl", &[stts(deltas)]);
+    let mdia = container(
+        b"mdia",
+        &[
+            mdhd(timescale, mdhd_duration, "und"),
+            hdlr(b"vide", "Video"),
+            container(b"minf", &[stbl]),
+        ],
+    );
+    let mut kids = vec![tkhd(id, tkhd_duration, 0, 0, 0)];
+    if let Some(d) = elst_duration {
+        let mut p = 1u32.to_be_bytes().to_vec();
+        p.extend_from_slice(&d.to_be_bytes());
+        p.extend_from_slice(&0u32.to_be_bytes());
+        p.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        kids.push(container(b"edts", &[full_box(b"elst", 0, 0, &p)]));
+    }
+    kids.push(mdia);
+    container(b"trak", &kids)
+}
+
+fn check(moov: Vec<u8>) -> mp4box::durations::DurationReport {
+    let len = moov.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(moov), len, true).unwrap();
+    check_durations(&boxes, DEFAULT_TOLERANCE)
+}
+
+#[test]
+fn consistent_durations_have_no_mismatches() {
+    let report = check(container(
+        b"moov",
+        &[
+            mvhd(1000, 10_000),
+            trak(1, 10_000, None, 90000, 900_000, &[(300, 3000)]),
+            // 48 kHz audio 10 ms short of the edit: within tolerance
+            trak(
+                2,
+                10_000,
+                Some(10_000),
+                48000,
+                479_520,
+                &[(468, 1024), (1, 288)],
+            ),
+        ],
+    ));
+    assert_eq!(report.movie, Some(10.0));
+    assert_eq!(report.tracks.len(), 2);
+    assert_eq!(report.tracks[1].edit_list, Some(10.0));
+    assert!(report.mismatches.is_empty(), "{:?}", report.mismatches);
+}
+
+#[test]
+fn mismatches_are_reported() {
+    let report = check(container(
+        b"moov",
+        &[
+            mvhd(1000, 12_000),
+            trak(1, 10_000, Some(9_500), 48000, 480_000, &[(400, 1024)]),
+        ],
+    ));
+    let found: Vec<(Option<u32>, DurationSource, DurationSource)> = report
+        .mismatches
+        .iter()
+        .map(|m| (m.track_id, m.expected, m.actual))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (Some(1), DurationSource::Mdhd, DurationSource::Stts),
+            (Some(1), DurationSource::EditList, DurationSource::Tkhd),
+            (None, DurationSource::Tkhd, DurationSource::Mvhd),
+        ]
+    );
+    assert!((report.mismatches[0].difference() - (409_600.0 / 48000.0 - 10.0)).abs() < 1e-9);
+    assert_eq!(report.mismatches[2].difference(), 2.0);
+}
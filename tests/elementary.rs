@@ -0,0 +1,200 @@
+mod common;
+
+use common::*;
+use mp4box::elementary::{
+    AacConfig, ExportError, StreamFormat, adts_header, export_elementary_stream, parse_esds,
+};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn trak(id: u32, handler: &[u8; 4], entry: Vec<u8>, sizes: &[u32], offset: u32) -> Vec<u8> {
+    let mut stsd = 1u32.to_be_bytes().to_vec();
+    stsd.extend_from_slice(&entry);
+    let stbl = container(
+        b"stbl",
+        &[
+            full_box(b"stsd", 0, 0, &stsd),
+            stts(&[(sizes.len() as u32, 1000)]),
+            stsc(&[(1, sizes.len() as u32, 1)]),
+            stsz(sizes),
+            stco(&[offset]),
+            stss(&[1]),
+        ],
+    );
+    let mdia = container(
+        b"mdia",
+        &[
+            mdhd(1000, 2000, "und"),
+            hdlr(handler, ""),
+            container(b"minf", &[stbl]),
+        ],
+    );
+    container(b"trak", &[tkhd(id, 2000, 0, 0, 0), mdia])
+}
+
+fn esds() -> Vec<u8> {
+    esds_with(&[0x12, 0x10]) // AAC LC, 44.1 kHz, stereo
+}
+
+/// esds around the AudioSpecificConfig `asc`.
+fn esds_with(asc: &[u8]) -> Vec<u8> {
+    let dsi = [&[0x05, asc.len() as u8][..], asc].concat();
+    let mut dcd = vec![0x04, 13 + dsi.len() as u8, 0x40, 0x15];
+    dcd.extend_from_slice(&[0; 11]);
+    dcd.extend_from_slice(&dsi);
+    let mut es = vec![0x03, (3 + dcd.len() + 3) as u8, 0, 1, 0];
+    es.extend_from_slice(&dcd);
+    es.extend_from_slice(&[0x06, 0x01, 0x02]);
+    full_box(b"esds", 0, 0, &es)
+}
+
+fn sample_file() -> Vec<u8> {
+    let video: [&[u8]; 2] = [
+        &[0, 0, 0, 2, 0x65, 0xAA, 0, 0, 0, 1, 0x06],
+        &[0, 0, 0, 2, 0x41, 0xBB],
+    ];
+    let audio: [&[u8]; 2] = [&[0x21, 0x10], &[0x21, 0x20, 0x30]];
+
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    let mdat_payload: Vec<u8> = video
+        .iter()
+        .chain(&audio)
+        .flat_map(|s| s.to_vec())
+        .collect();
+    let video_offset = data.len() as u32 + 8;
+    let audio_offset = video_offset + 17;
+    data.extend_from_slice(&boxed(b"mdat", &mdat_payload));
+
+    let mut avc1 = vec![0u8; 6];
+    avc1.extend_from_slice(&1u16.to_be_bytes());
+    avc1.extend_from_slice(&[0; 16]);
+    avc1.extend_from_slice(&16u16.to_be_bytes());
+    avc1.extend_from_slice(&16u16.to_be_bytes());
+    avc1.extend_from_slice(&[0; 50]);
+    avc1.extend_from_slice(&boxed(
+        b"avcC",
+        &[
+            1, 0x42, 0, 0x1E, 0xFF, 0xE1, 0, 3, 0x67, 1, 2, 1, 0, 2, 0x68, 3,
+        ],
+    ));
+
+    let mut mp4a = vec![0u8; 6];
+    mp4a.extend_from_slice(&1u16.to_be_bytes());
+    mp4a.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 16, 0, 0, 0, 0]);
+    mp4a.extend_from_slice(&(44100u32 << 16).to_be_bytes());
+    mp4a.extend_from_slice(&esds());
+
+    data.extend_from_slice(&container(
+        b"moov",
+        &[
+            mvhd(1000, 2000),
+            trak(1, b"vide", boxed(b"avc1", &avc1), &[11, 6], video_offset),
+            trak(2, b"soun", boxed(b"mp4a", &mp4a), &[2, 3], audio_offset),
+        ],
+    ));
+    data
+}
+
+fn export(track_id: u32) -> Result<(StreamFormat, Vec<u8>), ExportError> {
+    let data = sample_file();
+    let len = data.len() as u64;
+    let mut r = Cursor::new(data);
+    let boxes = get_boxes(&mut r, len, true).unwrap();
+    let mut out = Vec::new();
+    let summary = export_elementary_stream(&mut r, &boxes, track_id, &mut out)?;
+    assert_eq!(summary.bytes_written, out.len() as u64);
+    Ok((summary.format, out))
+}
+
+#[test]
+fn avc_track_to_annex_b() {
+    let (format, out) = export(1).unwrap();
+    assert_eq!(format, StreamFormat::H264);
+    assert_eq!(
+        out,
+        [
+            &[0, 0, 0, 1, 0x67, 1, 2][..], // SPS
+            &[0, 0, 0, 1, 0x68, 3],        // PPS
+            &[0, 0, 0, 1, 0x65, 0xAA],
+            &[0, 0, 0, 1, 0x06],
+            &[0, 0, 0, 1, 0x41, 0xBB], // not a sync sample: no parameter sets
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn aac_track_to_adts() {
+    let (format, out) = export(2).unwrap();
+    assert_eq!(format, StreamFormat::Aac);
+    assert_eq!(out.len(), 7 + 2 + 7 + 3);
+    assert_eq!(&out[..7], &[0xFF, 0xF1, 0x50, 0x80, 0x01, 0x3F, 0xFC]);
+    assert_eq!(&out[7..9], &[0x21, 0x10]);
+    assert_eq!(&out[9..11], &[0xFF, 0xF1]);
+
+    assert!(matches!(export(9), Err(ExportError::TrackNotFound(9))));
+}
+
+#[test]
+fn samples_past_the_source_fail() {
+    // The second audio sample claims 3000 bytes instead of 3
+    let mut data = sample_file();
+    let stsz = [&b"stsz"[..], &[0; 8], &[0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 3]].concat();
+    let at = data.windows(stsz.len()).position(|w| w == stsz).unwrap() + stsz.len();
+    data[at - 2..at].copy_from_slice(&3000u16.to_be_bytes());
+
+    let len = data.len() as u64;
+    let mut r = Cursor::new(data);
+    let boxes = get_boxes(&mut r, len, true).unwrap();
+    let err = export_elementary_stream(&mut r, &boxes, 2, &mut Vec::new()).unwrap_err();
+    assert!(matches!(
+        err,
+        ExportError::SampleOutOfBounds { index: 1, end, len: l } if end > len && l == len
+    ));
+}
+
+#[test]
+fn esds_and_adts_profiles() {
+    let esds = esds();
+    let config = parse_esds(&esds[12..]).unwrap();
+    assert_eq!(
+        config,
+        AacConfig {
+            object_type: 2,
+            sampling_frequency_index: 4,
+            channel_configuration: 2,
+        }
+    );
+
+    let he_aac = AacConfig {
+        object_type: 5,
+        ..config
+    };
+    assert!(matches!(
+        adts_header(&he_aac, 10),
+        Err(ExportError::UnsupportedAacProfile(5))
+    ));
+
+    // The 13-bit frame length counts the header
+    assert!(adts_header(&config, 0x1FFF - 7).is_ok());
+    assert!(matches!(
+        adts_header(&config, 0x1FFF - 6),
+        Err(ExportError::AdtsFrameTooLong(8185))
+    ));
+}
+
+#[test]
+fn explicit_he_aac_exports_its_core_profile() {
+    // HE-AAC: SBR at 48 kHz over AAC LC at 24 kHz, stereo
+    let esds = esds_with(&[0x2B, 0x11, 0x88]);
+    let config = parse_esds(&esds[12..]).unwrap();
+    assert_eq!(
+        config,
+        AacConfig {
+            object_type: 2,
+            sampling_frequency_index: 6,
+            channel_configuration: 2,
+        }
+    );
+    assert_eq!(adts_header(&config, 10).unwrap()[2] >> 6, 1);
+}
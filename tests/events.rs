@@ -0,0 +1,158 @@
+mod common;
+
+use common::*;
+use mp4box::boxes::{BoxHeader, FourCC};
+use mp4box::events::{BoxVisitor, Visit, parse_events};
+use mp4box::get_boxes;
+use std::io::{Cursor, Seek, SeekFrom};
+
+fn movie() -> Vec<u8> {
+    let mut avc1 = vec![0u8; 78];
+    avc1.extend(boxed(b"avcC", &[1, 0x64, 0, 0x1f]));
+    let stsd = full_box(
+        b"stsd",
+        0,
+        0,
+        &[&1u32.to_be_bytes()[..], &boxed(b"avc1", &avc1)].concat(),
+    );
+    let stbl = container(b"stbl", &[stsd, stts(&[(1, 1000)])]);
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 1000, 0, 0, 0),
+            container(
+                b"mdia",
+                &[mdhd(1000, 1000, "und"), container(b"minf", &[stbl])],
+            ),
+        ],
+    );
+    let ilst = container(b"ilst", &[text_item(b"\xa9nam", "title")]);
+    let meta = full_box(b"meta", 0, 0, &[hdlr(b"mdir", ""), ilst].concat());
+    [
+        ftyp(b"isom", &[b"isom"]),
+        container(
+            b"moov",
+            &[mvhd(1000, 1000), trak, container(b"udta", &[meta])],
+        ),
+        boxed(b"mdat", &[0; 16]),
+    ]
+    .concat()
+}
+
+/// Records every callback as a line.
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    stop_at: Option<&'static str>,
+    skip: Option<&'static str>,
+}
+
+impl BoxVisitor for Recorder {
+    fn on_box_start(&mut self, header: &BoxHeader, path: &[FourCC]) -> Visit {
+        let path: Vec<String> = path.iter().map(|t| t.to_string()).collect();
+        self.events.push(format!("start {}", path.join(".")));
+        if self.stop_at == Some(header.typ.to_string().as_str()) {
+            Visit::Stop
+        } else if self.skip == Some(header.typ.to_string().as_str()) {
+            Visit::SkipChildren
+        } else {
+            Visit::Continue
+        }
+    }
+
+    fn on_fullbox(&mut self, header: &BoxHeader, version: u8, flags: u32) -> Visit {
+        self.events
+            .push(format!("full {} v{version} f{flags:#x}", header.typ));
+        Visit::Continue
+    }
+
+    fn on_box_end(&mut self, header: &BoxHeader, _path: &[FourCC]) -> Visit {
+        self.events.push(format!("end {}", header.typ));
+        Visit::Continue
+    }
+}
+
+/// The same lines, from the tree `get_boxes` builds.
+fn expected(boxes: &[mp4box::Box], parents: &str, out: &mut Vec<String>) {
+    for b in boxes {
+        let path = if parents.is_empty() {
+            b.typ.clone()
+        } else {
+            format!("{parents}.{}", b.typ)
+        };
+        out.push(format!("start {path}"));
+        if let (Some(v), Some(f)) = (b.version, b.flags) {
+            out.push(format!("full {} v{v} f{f:#x}", b.typ));
+        }
+        expected(b.children.as_deref().unwrap_or_default(), &path, out);
+        out.push(format!("end {}", b.typ));
+    }
+}
+
+#[test]
+fn events_follow_the_box_tree() {
+    let data = movie();
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap();
+    let mut want = Vec::new();
+    expected(&boxes, "", &mut want);
+
+    let mut rec = Recorder::default();
+    assert!(parse_events(&mut Cursor::new(&data), &mut rec).unwrap());
+    assert_eq!(rec.events, want);
+    assert!(
+        rec.events
+            .contains(&"start moov.trak.mdia.minf.stbl.stsd.avc1.avcC".to_string())
+    );
+    assert!(
+        rec.events
+            .contains(&"start moov.udta.meta.ilst.\u{a9}nam.data".to_string())
+    );
+}
+
+#[test]
+fn stop_ends_the_walk_early() {
+    let data = movie();
+    let mut rec = Recorder {
+        stop_at: Some("mvhd"),
+        ..Default::default()
+    };
+    assert!(!parse_events(&mut Cursor::new(&data), &mut rec).unwrap());
+    assert_eq!(
+        rec.events,
+        ["start ftyp", "end ftyp", "start moov", "start moov.mvhd"]
+    );
+}
+
+#[test]
+fn skipped_children_are_not_reported() {
+    let data = movie();
+    let mut rec = Recorder {
+        skip: Some("trak"),
+        ..Default::default()
+    };
+    assert!(parse_events(&mut Cursor::new(&data), &mut rec).unwrap());
+    let trak = rec
+        .events
+        .iter()
+        .position(|e| e == "start moov.trak")
+        .unwrap();
+    assert_eq!(rec.events[trak + 1], "end trak");
+    assert!(!rec.events.iter().any(|e| e.contains("tkhd")));
+    assert_eq!(rec.events.last().unwrap(), "end mdat");
+}
+
+#[test]
+fn walk_starts_at_the_current_position() {
+    let data = movie();
+    let mut r = Cursor::new(&data);
+    let ftyp_len = ftyp(b"isom", &[b"isom"]).len() as u64;
+    r.seek(SeekFrom::Start(ftyp_len)).unwrap();
+    let mut rec = Recorder::default();
+    parse_events(&mut r, &mut rec).unwrap();
+    assert_eq!(rec.events[0], "start moov");
+
+    // A header cut by the end of the file is an error
+    let mut cut = data.clone();
+    cut.extend([0, 0, 0, 9, b'f']);
+    assert!(parse_events(&mut Cursor::new(&cut), &mut Recorder::default()).is_err());
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::*;
+use mp4box::extract::{
+    BlobKind, ExtractError, XMP_UUID, find_embedded_blobs, sniff_mime, stream_blob,
+};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn uuid_box(uuid: &[u8; 16], payload: &[u8]) -> Vec<u8> {
+    let mut p = uuid.to_vec();
+    p.extend_from_slice(payload);
+    boxed(b"uuid", &p)
+}
+
+fn icc_profile() -> Vec<u8> {
+    let mut icc = vec![0u8; 128];
+    icc[36..40].copy_from_slice(b"acsp");
+    icc
+}
+
+fn sample_file() -> Vec<u8> {
+    let jpeg = [0xFF, 0xD8, 0xFF, 0xE0, 0, 0, 0, 0, 0, 0];
+    let mut colr = b"prof".to_vec();
+    colr.extend_from_slice(&icc_profile());
+    [
+        container(
+            b"moov",
+            &[container(
+                b"udta",
+                &[container(
+                    b"ilst",
+                    &[container(b"covr", &[data_atom(13, &jpeg)])],
+                )],
+            )],
+        ),
+        container(b"ipco", &[boxed(b"colr", &colr)]),
+        uuid_box(&XMP_UUID, b"<x:xmpmeta xmlns:x=\"adobe:ns:meta/\"/>"),
+    ]
+    .concat()
+}
+
+#[test]
+fn blobs_are_located_and_streamed() {
+    let data = sample_file();
+    let len = data.len() as u64;
+    let mut cursor = Cursor::new(data);
+    let boxes = get_boxes(&mut cursor, len, true).unwrap();
+
+    let blobs = find_embedded_blobs(&mut cursor, &boxes).unwrap();
+    let kinds: Vec<_> = blobs.iter().map(|b| b.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![BlobKind::CoverArt, BlobKind::IccProfile, BlobKind::Xmp]
+    );
+
+    let mut out = Vec::new();
+    let info = stream_blob(&mut cursor, blobs[0].offset, blobs[0].size, &mut out, 1024).unwrap();
+    assert_eq!(info.mime_type, "image/jpeg");
+    assert_eq!(info.size, 10);
+    assert_eq!(out.len(), 10);
+
+    let mut out = Vec::new();
+    let info = stream_blob(&mut cursor, blobs[1].offset, blobs[1].size, &mut out, 1024).unwrap();
+    assert_eq!(info.mime_type, "application/vnd.iccprofile");
+    assert_eq!(out, icc_profile());
+
+    let mut out = Vec::new();
+    let info = stream_blob(&mut cursor, blobs[2].offset, blobs[2].size, &mut out, 1024).unwrap();
+    assert_eq!(info.mime_type, "application/rdf+xml");
+}
+
+#[test]
+fn oversized_blob_is_rejected_without_writing() {
+    let data = sample_file();
+    let len = data.len() as u64;
+    let mut cursor = Cursor::new(data);
+    let boxes = get_boxes(&mut cursor, len, true).unwrap();
+    let blob = find_embedded_blobs(&mut cursor, &boxes).unwrap()[1];
+
+    let mut out = Vec::new();
+    match stream_blob(&mut cursor, blob.offset, blob.size, &mut out, 64) {
+        Err(ExtractError::TooLarge { size, limit }) => {
+            assert_eq!(size, 128);
+            assert_eq!(limit, 64);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(out.is_empty());
+}
+
+#[test]
+fn truncated_blob_is_an_error() {
+    let mut cursor = Cursor::new(vec![0u8; 100]);
+    let mut out = Vec::new();
+    assert!(matches!(
+        stream_blob(&mut cursor, 50, 100, &mut out, 1000),
+        Err(ExtractError::Io(_))
+    ));
+}
+
+#[test]
+fn mime_sniffing() {
+    assert_eq!(sniff_mime(b"\x89PNG\r\n\x1a\n...."), "image/png");
+    assert_eq!(sniff_mime(b"  <?xml version=\"1.0\"?>"), "application/xml");
+    assert_eq!(sniff_mime(b"\x00\x01\x02"), "application/octet-stream");
+}
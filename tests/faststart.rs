@@ -0,0 +1,80 @@
+mod common;
+
+use common::*;
+use mp4box::faststart::{is_faststart, startup_report};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn moov(chunk_offset: u32) -> Vec<u8> {
+    moov_with(stco(&[chunk_offset]))
+}
+
+/// moov whose only chunk is at the offset listed in `chunk_offsets`.
+fn moov_with(chunk_offsets: Vec<u8>) -> Vec<u8> {
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(2, 100)]),
+            stsc(&[(1, 2, 1)]),
+            stsz(&[30, 30]),
+            chunk_offsets,
+        ],
+    );
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 0, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 0, "und"),
+                    hdlr(b"soun", "Sound"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    );
+    container(b"moov", &[mvhd(1000, 0), trak])
+}
+
+fn parse(data: &[u8]) -> Vec<mp4box::Box> {
+    get_boxes(&mut Cursor::new(data), data.len() as u64, true).unwrap()
+}
+
+#[test]
+fn moov_first() {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let moov_len = moov(0).len();
+    let media_at = (ftyp.len() + moov_len + 8) as u32;
+    let data = [ftyp, moov(media_at), boxed(b"mdat", &[0; 60])].concat();
+    let boxes = parse(&data);
+
+    assert!(is_faststart(&boxes));
+    let report = startup_report(&boxes);
+    assert_eq!(report.first_mdat_offset, Some(media_at as u64 - 8));
+    // moov, then the 30-byte first sample
+    assert_eq!(report.bytes_before_playback, Some(media_at as u64 + 30));
+}
+
+#[test]
+fn moov_at_end() {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let media_at = (ftyp.len() + 8) as u32;
+    let data = [ftyp, boxed(b"mdat", &[0; 60]), moov(media_at)].concat();
+    let boxes = parse(&data);
+
+    assert!(!is_faststart(&boxes));
+    let report = startup_report(&boxes);
+    assert_eq!(report.bytes_before_playback, Some(data.len() as u64));
+    assert!(!is_faststart(&parse(&boxed(b"mdat", &[]))));
+}
+
+#[test]
+fn chunk_at_the_end_of_the_address_space() {
+    let mut co64 = 1u32.to_be_bytes().to_vec();
+    co64.extend_from_slice(&0xffff_ffff_ffff_fff0u64.to_be_bytes());
+    let data = moov_with(full_box(b"co64", 0, 0, &co64));
+
+    let report = startup_report(&parse(&data));
+    assert_eq!(report.bytes_before_playback, Some(u64::MAX));
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::*;
+use mp4box::ParseOptions;
+use mp4box::StructuredData;
+use mp4box::follow::Follower;
+use std::io::Cursor;
+
+fn fragment(seq: u32) -> Vec<u8> {
+    [
+        container(b"moof", &[mfhd(seq)]),
+        boxed(b"mdat", &[seq as u8; 32]),
+    ]
+    .concat()
+}
+
+fn types(boxes: &[mp4box::Box]) -> Vec<&str> {
+    boxes.iter().map(|b| b.typ.as_str()).collect()
+}
+
+#[test]
+fn polls_return_only_appended_boxes() {
+    let init = [
+        ftyp(b"iso6", &[b"iso6"]),
+        container(b"moov", &[mvhd(1000, 0)]),
+    ]
+    .concat();
+    let mut file = Cursor::new(init.clone());
+    let mut follower = Follower::new(ParseOptions::new());
+
+    let (boxes, warnings) = follower.poll(&mut file).unwrap();
+    assert_eq!(types(&boxes), ["ftyp", "moov"]);
+    assert!(warnings.is_empty());
+    assert_eq!(follower.offset(), init.len() as u64);
+
+    // Nothing new
+    assert!(follower.poll(&mut file).unwrap().0.is_empty());
+
+    file.get_mut().extend(fragment(1));
+    let (boxes, _) = follower.poll(&mut file).unwrap();
+    assert_eq!(types(&boxes), ["moof", "mdat"]);
+    assert_eq!(boxes[0].offset, init.len() as u64);
+    assert_eq!(boxes[0].children.as_ref().unwrap()[0].typ, "mfhd");
+}
+
+#[test]
+fn incomplete_boxes_wait_for_the_rest() {
+    let frag = fragment(2);
+    let moof_len = container(b"moof", &[mfhd(2)]).len();
+    let mut file = Cursor::new(Vec::new());
+    let mut follower = Follower::new(ParseOptions::new());
+
+    // Half a header, then a header without its payload
+    for cut in [4, 8, moof_len - 1] {
+        file.get_mut().clear();
+        file.get_mut().extend(&frag[..cut]);
+        assert!(follower.poll(&mut file).unwrap().0.is_empty());
+        assert_eq!(follower.offset(), 0);
+    }
+
+    // The moof is complete, the mdat is not
+    file.get_mut().clear();
+    file.get_mut().extend(&frag[..moof_len + 10]);
+    assert_eq!(types(&follower.poll(&mut file).unwrap().0), ["moof"]);
+
+    file.get_mut().clear();
+    file.get_mut().extend(&frag);
+    assert_eq!(types(&follower.poll(&mut file).unwrap().0), ["mdat"]);
+    assert_eq!(follower.offset(), frag.len() as u64);
+}
+
+#[test]
+fn size_zero_box_is_never_complete() {
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend(0u32.to_be_bytes());
+    data.extend(b"mdat");
+    data.extend([0; 16]);
+    let mut follower = Follower::new(ParseOptions::new());
+    let (boxes, _) = follower.poll(&mut Cursor::new(data)).unwrap();
+    assert_eq!(types(&boxes), ["ftyp"]);
+}
+
+#[test]
+fn resumes_from_a_saved_offset() {
+    let first = fragment(1);
+    let data = [first.clone(), fragment(2)].concat();
+    let mut follower = Follower::resume_at(first.len() as u64, ParseOptions::new().decode(true));
+    let (boxes, _) = follower.poll(&mut Cursor::new(data)).unwrap();
+    assert_eq!(types(&boxes), ["moof", "mdat"]);
+    let mfhd = &boxes[0].children.as_ref().unwrap()[0];
+    match &mfhd.structured_data {
+        Some(StructuredData::MovieFragmentHeader(d)) => assert_eq!(d.sequence_number, 2),
+        other => panic!("unexpected mfhd data: {other:?}"),
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::*;
+use mp4box::boxes::{BoxKey, FourCC};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+#[test]
+fn hex_form_round_trips_every_type() {
+    for raw in [*b"moov", *b"\xa9too", *b"\xff\x00ab"] {
+        let cc = FourCC(raw);
+        let hex = format!("0x{}", cc.to_hex());
+        assert_eq!(hex.parse::<FourCC>(), Ok(cc));
+    }
+    assert_eq!(FourCC(*b"\xa9too").to_hex(), "a9746f6f");
+    assert_eq!("©too".parse::<FourCC>(), Ok(FourCC(*b"\xa9too")));
+
+    assert!(FourCC(*b"\xa9too").is_printable());
+    assert!(!FourCC(*b"\xff\x00ab").is_printable());
+    assert_eq!(FourCC(*b"\xff\x00ab").as_str_lossy(), "..ab");
+
+    // Not hex: a malformed "0x" form is rejected rather than misread
+    assert!("0x+1234567".parse::<FourCC>().is_err());
+    assert!("0xzzzzzzzz".parse::<FourCC>().is_err());
+    // Four characters are always a literal type
+    assert_eq!("0x12".parse::<FourCC>(), Ok(FourCC(*b"0x12")));
+}
+
+#[test]
+fn box_keys_parse_from_strings() {
+    assert_eq!("stsd".parse(), Ok(BoxKey::FourCC(FourCC(*b"stsd"))));
+    assert_eq!(
+        "0xff000001".parse(),
+        Ok(BoxKey::FourCC(FourCC([0xff, 0, 0, 1])))
+    );
+    let uuid = "uuid:be7acfcb97a942e89c71999491e3afac".parse::<BoxKey>();
+    assert_eq!(
+        uuid,
+        Ok(BoxKey::Uuid([
+            0xBE, 0x7A, 0xCF, 0xCB, 0x97, 0xA9, 0x42, 0xE8, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3,
+            0xAF, 0xAC,
+        ]))
+    );
+    assert!("uuid:be7a".parse::<BoxKey>().is_err());
+}
+
+#[test]
+fn json_keeps_raw_type_bytes() {
+    let data = [boxed(b"\xff\x01ab", &[1, 2]), boxed(b"\xfe\x01ab", &[])].concat();
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap();
+    // Same lossy string, different types
+    assert_eq!(boxes[0].typ, boxes[1].typ);
+    assert_eq!(boxes[0].typ_hex, "ff016162");
+    assert_eq!(boxes[1].typ_hex, "fe016162");
+
+    let json = serde_json::to_value(&boxes[0]).unwrap();
+    assert_eq!(json["typ_hex"], "ff016162");
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::*;
+use mp4box::boxes::{BoxHeader, BoxKey, FourCC};
+use mp4box::registry::{SampleFlags, StructuredData, default_registry};
+use std::io::Cursor;
+
+#[test]
+fn tfhd_flags_are_named() {
+    let mut payload = 1u32.to_be_bytes().to_vec(); // track_id
+    payload.extend_from_slice(&1024u32.to_be_bytes()); // default_sample_duration
+    payload.extend_from_slice(&0x0101_0000u32.to_be_bytes()); // default_sample_flags
+
+    match decode_payload(b"tfhd", &payload, Some((0, 0x020028))) {
+        StructuredData::TrackFragmentHeader(t) => {
+            assert_eq!(t.track_id, 1);
+            assert!(t.decoded_flags.default_base_is_moof);
+            assert!(t.decoded_flags.default_sample_duration_present);
+            assert!(t.decoded_flags.default_sample_flags_present);
+            assert!(!t.decoded_flags.base_data_offset_present);
+            assert_eq!(t.default_sample_duration, Some(1024));
+            assert_eq!(t.default_sample_size, None);
+            let sf = t.default_sample_flags_decoded.unwrap();
+            assert!(sf.sample_is_non_sync_sample);
+            assert_eq!(sf.sample_depends_on, 1);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn trun_entries_follow_flags() {
+    let mut payload = 2u32.to_be_bytes().to_vec(); // sample_count
+    payload.extend_from_slice(&100i32.to_be_bytes()); // data_offset
+    payload.extend_from_slice(&0x0200_0000u32.to_be_bytes()); // first_sample_flags
+    for (size, cto) in [(500u32, -512i32), (300, 1024)] {
+        payload.extend_from_slice(&size.to_be_bytes());
+        payload.extend_from_slice(&cto.to_be_bytes());
+    }
+
+    match decode_payload(b"trun", &payload, Some((1, 0x000A05))) {
+        StructuredData::TrackFragmentRun(t) => {
+            assert!(t.decoded_flags.data_offset_present);
+            assert!(t.decoded_flags.first_sample_flags_present);
+            assert!(t.decoded_flags.sample_size_present);
+            assert!(t.decoded_flags.sample_composition_time_offsets_present);
+            assert!(!t.decoded_flags.sample_duration_present);
+            assert_eq!(t.data_offset, Some(100));
+            assert_eq!(t.entries.len(), 2);
+            assert_eq!(t.entries[0].sample_size, Some(500));
+            assert_eq!(t.entries[0].sample_composition_time_offset, Some(-512));
+            assert_eq!(t.entries[1].sample_duration, None);
+            assert!(
+                !t.first_sample_flags_decoded
+                    .unwrap()
+                    .sample_is_non_sync_sample
+            );
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn trun_without_fields_caps_its_entries() {
+    match decode_payload(b"trun", &3u32.to_be_bytes(), Some((0, 0))) {
+        StructuredData::TrackFragmentRun(t) => assert_eq!(t.entries.len(), 3),
+        other => panic!("unexpected {:?}", other),
+    }
+
+    // Nothing in the payload bounds empty entries
+    let header = BoxHeader {
+        typ: FourCC(*b"trun"),
+        uuid: None,
+        size: 16,
+        header_size: 8,
+        start: 0,
+    };
+    let result = default_registry()
+        .decode(
+            &BoxKey::FourCC(FourCC(*b"trun")),
+            &mut Cursor::new(u32::MAX.to_be_bytes().to_vec()),
+            &header,
+            Some(0),
+            Some(0),
+        )
+        .expect("decoder registered");
+    assert!(result.is_err());
+}
+
+#[test]
+fn sample_flags_from_bits() {
+    let f = SampleFlags::from_bits(0x0401_0005);
+    assert_eq!(f.is_leading, 1);
+    assert_eq!(f.sample_depends_on, 0);
+    assert!(f.sample_is_non_sync_sample);
+    assert_eq!(f.sample_degradation_priority, 5);
+}
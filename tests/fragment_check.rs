@@ -0,0 +1,130 @@
+mod common;
+
+use common::*;
+use mp4box::fragments::{FragmentDataStatus, check_fragment_data, fragment_index};
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+/// moof with one trun whose data starts `skip` bytes into the next mdat.
+fn moof(seq: u32, sizes: &[u32], skip: i32) -> Vec<u8> {
+    moof_runs(seq, &[(skip, sizes)])
+}
+
+/// moof with one trun per (skip, sizes), each starting `skip` bytes into
+/// the next mdat.
+fn moof_runs(seq: u32, runs: &[(i32, &[u32])]) -> Vec<u8> {
+    let build = |base: i32| {
+        let mut traf = vec![tfhd(1, Some(1000))];
+        traf.extend(runs.iter().map(|(skip, sizes)| trun(base + skip, sizes)));
+        container(b"moof", &[mfhd(seq), container(b"traf", &traf)])
+    };
+    let len = build(0).len() as i32;
+    build(len + 8)
+}
+
+fn check(data: Vec<u8>) -> Vec<mp4box::fragments::FragmentDataCheck> {
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    check_fragment_data(&boxes, len)
+}
+
+#[test]
+fn exact_fragment() {
+    let data = [moof(1, &[10, 20], 0), boxed(b"mdat", &[0u8; 30])].concat();
+    let checks = check(data);
+    assert_eq!(checks.len(), 1);
+    assert_eq!(checks[0].sequence_number, Some(1));
+    assert_eq!(checks[0].declared_size, 30);
+    assert_eq!(checks[0].status, FragmentDataStatus::Exact);
+}
+
+#[test]
+fn slack_and_overrun() {
+    let data = [
+        moof(1, &[10, 20], 0),
+        boxed(b"mdat", &[0u8; 31]),
+        moof(2, &[10, 20], 2),
+        boxed(b"mdat", &[0u8; 30]),
+    ]
+    .concat();
+    let checks = check(data);
+    assert_eq!(checks[0].status, FragmentDataStatus::Slack { bytes: 1 });
+    assert_eq!(checks[0].trailing_gap, 1);
+    assert_eq!(checks[1].leading_gap, 2);
+    assert_eq!(checks[1].status, FragmentDataStatus::Overrun { bytes: 2 });
+}
+
+#[test]
+fn interior_hole_is_slack() {
+    // Samples at 0..10 and 14..30 of a 30-byte payload
+    let data = [
+        moof_runs(1, &[(0, &[10]), (14, &[16])]),
+        boxed(b"mdat", &[0u8; 30]),
+    ]
+    .concat();
+    let checks = check(data);
+    assert_eq!(checks[0].declared_size, 26);
+    assert_eq!((checks[0].leading_gap, checks[0].trailing_gap), (0, 0));
+    assert_eq!(checks[0].interior_gap, 4);
+    assert_eq!(checks[0].status, FragmentDataStatus::Slack { bytes: 4 });
+}
+
+#[test]
+fn overlapping_samples() {
+    // Samples at 0..20 and 15..30: both ends line up, 5 bytes are shared
+    let data = [
+        moof_runs(1, &[(0, &[20]), (15, &[15])]),
+        boxed(b"mdat", &[0u8; 30]),
+    ]
+    .concat();
+    let checks = check(data);
+    assert_eq!(checks[0].declared_size, 35);
+    assert_eq!(checks[0].overlap, 5);
+    assert_eq!(checks[0].status, FragmentDataStatus::Overlap { bytes: 5 });
+}
+
+#[test]
+fn moof_without_mdat() {
+    let checks = check(moof(1, &[10], 0));
+    assert_eq!(checks[0].status, FragmentDataStatus::MissingMdat);
+}
+
+#[test]
+fn fragment_index_lists_moofs() {
+    let two_tracks = container(
+        b"moof",
+        &[
+            mfhd(7),
+            container(b"traf", &[tfhd(1, None), tfdt(9000), trun(0, &[4])]),
+            container(b"traf", &[tfhd(2, None), tfdt(4800), trun(0, &[4])]),
+        ],
+    );
+    let first = moof(6, &[10], 0);
+    let mdat = boxed(b"mdat", &[0u8; 10]);
+    let second_offset = (first.len() + mdat.len()) as u64;
+    let data = [first.clone(), mdat, two_tracks].concat();
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    let index = fragment_index(&boxes, len);
+    assert_eq!(index.len(), 2);
+
+    assert_eq!(index[0].moof_offset, 0);
+    assert_eq!(index[0].moof_size, first.len() as u64);
+    assert_eq!(index[0].sequence_number, Some(6));
+    assert_eq!(index[0].track_ids, [1]);
+    assert_eq!(index[0].earliest_decode_time, None);
+    assert_eq!(index[0].mdat_offset, Some(first.len() as u64));
+    assert_eq!(index[0].mdat_size, Some(18));
+
+    assert_eq!(index[1].moof_offset, second_offset);
+    assert_eq!(index[1].track_ids, [1, 2]);
+    let counts: Vec<_> = index[1]
+        .tracks
+        .iter()
+        .map(|t| (t.track_id, t.sample_count))
+        .collect();
+    assert_eq!(counts, [(1, 1), (2, 1)]);
+    assert_eq!(index[1].earliest_decode_time, Some(4800));
+    assert_eq!(index[1].mdat_offset, None);
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use common::*;
+use mp4box::fragments::{DataBase, DefaultSource, FragmentSampleSources};
+use mp4box::track_samples_from_reader;
+use std::io::Cursor;
+
+/// Video track with an empty sample table, as fragmented files carry.
+fn fragmented_moov(trex: Vec<u8>) -> Vec<u8> {
+    let stbl = container(b"stbl", &[stts(&[]), stsc(&[]), stsz(&[]), stco(&[])]);
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 0, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 0, "und"),
+                    hdlr(b"vide", "Video"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    );
+    container(b"moov", &[trak, container(b"mvex", &[trex])])
+}
+
+/// moof whose single traf points at the mdat right after it.
+fn moof(seq: u32, traf_head: &[Vec<u8>], sizes: &[u32]) -> Vec<u8> {
+    let build = |data_offset: i32| {
+        let mut traf = traf_head.to_vec();
+        traf.push(trun(data_offset, sizes));
+        container(b"moof", &[mfhd(seq), container(b"traf", &traf)])
+    };
+    let len = build(0).len() as i32;
+    build(len + 8)
+}
+
+#[test]
+fn fragment_samples_follow_tfdt_and_defaults() {
+    let moov = fragmented_moov(trex(1, 100, 0));
+    let moof1 = moof(1, &[tfhd(1, None), tfdt(5000)], &[10, 20]);
+    let mdat1 = boxed(b"mdat", &[0u8; 30]);
+    let moof2 = moof(2, &[tfhd(1, Some(200))], &[5]);
+    let mdat2 = boxed(b"mdat", &[0u8; 5]);
+
+    let moof1_offset = moov.len() as u64;
+    let data1 = moof1_offset + moof1.len() as u64 + 8;
+    let data2 = data1 + 30 + moof2.len() as u64 + 8;
+    let data = [moov, moof1, mdat1, moof2, mdat2].concat();
+
+    let tracks = track_samples_from_reader(Cursor::new(data)).unwrap();
+    assert_eq!(tracks.len(), 1);
+    let t = &tracks[0];
+    assert_eq!(t.sample_count, 3);
+
+    let s: Vec<_> = t
+        .samples
+        .iter()
+        .map(|s| (s.index, s.dts, s.duration, s.file_offset, s.size))
+        .collect();
+    assert_eq!(
+        s,
+        [
+            (0, 5000, 100, data1, 10),
+            (1, 5100, 100, data1 + 10, 20),
+            // No tfdt: continue after the previous fragment; tfhd overrides trex
+            (2, 5200, 200, data2, 5),
+        ]
+    );
+    assert_eq!(t.samples[0].start_time, 5.0);
+
+    let sources = |i: usize| t.samples[i].fragment_sources.unwrap();
+    assert_eq!(
+        sources(0),
+        FragmentSampleSources {
+            duration: DefaultSource::Trex,
+            size: DefaultSource::Trun,
+            flags: DefaultSource::Trex,
+            data_base: DataBase::Moof,
+        }
+    );
+    assert_eq!(sources(2).duration, DefaultSource::Tfhd);
+
+    let f: Vec<_> = t
+        .fragments
+        .iter()
+        .map(|f| {
+            (
+                f.moof_offset,
+                f.sequence_number,
+                f.base_media_decode_time,
+                f.first_sample,
+                f.sample_count,
+            )
+        })
+        .collect();
+    assert_eq!(
+        f,
+        [
+            (moof1_offset, Some(1), Some(5000), 0, 2),
+            (data1 + 30, Some(2), None, 2, 1),
+        ]
+    );
+}
+
+#[test]
+fn fragment_sync_flags_and_composition_offsets() {
+    // trex: duration 512, non-sync by default
+    let mut p = Vec::new();
+    for v in [1u32, 1, 512, 0, 0x0001_0000] {
+        p.extend_from_slice(&v.to_be_bytes());
+    }
+    let moov = fragmented_moov(full_box(b"trex", 0, 0, &p));
+
+    // trun v1: data_offset, first_sample_flags (sync), size + cto per sample
+    let build = |data_offset: i32| {
+        let mut p = 3u32.to_be_bytes().to_vec();
+        p.extend_from_slice(&data_offset.to_be_bytes());
+        p.extend_from_slice(&0x0200_0000u32.to_be_bytes());
+        for (size, cto) in [(4u32, 1024i32), (4, -512), (4, 0)] {
+            p.extend_from_slice(&size.to_be_bytes());
+            p.extend_from_slice(&cto.to_be_bytes());
+        }
+        let traf = container(
+            b"traf",
+            &[tfhd(1, None), tfdt(0), full_box(b"trun", 1, 0x000A05, &p)],
+        );
+        container(b"moof", &[mfhd(1), traf])
+    };
+    let len = build(0).len() as i32;
+    let data = [moov, build(len + 8), boxed(b"mdat", &[0u8; 12])].concat();
+
+    let tracks = track_samples_from_reader(Cursor::new(data)).unwrap();
+    let s: Vec<_> = tracks[0]
+        .samples
+        .iter()
+        .map(|s| (s.dts, s.pts, s.rendered_offset, s.is_sync))
+        .collect();
+    assert_eq!(
+        s,
+        [
+            (0, 1024, 1024, true),
+            (512, 0, -512, false),
+            (1024, 1024, 0, false),
+        ]
+    );
+    let flag_sources: Vec<_> = tracks[0]
+        .samples
+        .iter()
+        .map(|s| s.fragment_sources.unwrap().flags)
+        .collect();
+    assert_eq!(
+        flag_sources,
+        [
+            DefaultSource::FirstSampleFlags,
+            DefaultSource::Trex,
+            DefaultSource::Trex,
+        ]
+    );
+}
+
+#[test]
+fn trafs_without_base_continue_after_previous_traf() {
+    // tfhd with no flags: offsets chain from the moof through each traf
+    let plain_tfhd = full_box(b"tfhd", 0, 0, &1u32.to_be_bytes());
+    let sizes_only = |sizes: &[u32]| {
+        let mut p = (sizes.len() as u32).to_be_bytes().to_vec();
+        for s in sizes {
+            p.extend_from_slice(&s.to_be_bytes());
+        }
+        full_box(b"trun", 0, 0x000200, &p)
+    };
+    let moov = fragmented_moov(trex(1, 10, 0));
+    let moof_offset = moov.len() as u64;
+    let moof = container(
+        b"moof",
+        &[
+            mfhd(1),
+            container(b"traf", &[plain_tfhd.clone(), sizes_only(&[3, 4])]),
+            container(b"traf", &[plain_tfhd, sizes_only(&[5])]),
+        ],
+    );
+    let data = [moov, moof].concat();
+
+    let tracks = track_samples_from_reader(Cursor::new(data)).unwrap();
+    let s: Vec<_> = tracks[0]
+        .samples
+        .iter()
+        .map(|s| (s.file_offset, s.fragment_sources.unwrap().data_base))
+        .collect();
+    assert_eq!(
+        s,
+        [
+            (moof_offset, DataBase::Moof),
+            (moof_offset + 3, DataBase::Moof),
+            (moof_offset + 7, DataBase::PreviousTraf),
+        ]
+    );
+}
+
+#[test]
+fn offsets_stop_at_the_end_of_the_address_space() {
+    let mut p = 1u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&(u64::MAX - 15).to_be_bytes());
+    let tfhd = full_box(b"tfhd", 0, 0x000001, &p);
+    let moov = fragmented_moov(trex(1, 10, 0));
+    let moof = container(
+        b"moof",
+        &[mfhd(1), container(b"traf", &[tfhd, trun(0, &[10, 20, 30])])],
+    );
+    let data = [moov, moof].concat();
+
+    // The second sample ends past u64::MAX; nothing after it can be placed
+    let tracks = track_samples_from_reader(Cursor::new(data)).unwrap();
+    let offsets: Vec<_> = tracks[0].samples.iter().map(|s| s.file_offset).collect();
+    assert_eq!(offsets, [u64::MAX - 15, u64::MAX - 5]);
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::*;
+use mp4box::gapless::{GaplessSource, ItunSmpb, gapless_info, parse_itunsmpb};
+
+/// AAC track of 100 packets at 44.1 kHz with an optional edit list.
+fn audio_trak(elst: Option<(u32, i32)>) -> Vec<u8> {
+    let mut mp4a = vec![0u8; 6];
+    mp4a.extend_from_slice(&1u16.to_be_bytes());
+    mp4a.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 16, 0, 0, 0, 0]);
+    mp4a.extend_from_slice(&(44100u32 << 16).to_be_bytes());
+    let mut stsd = 1u32.to_be_bytes().to_vec();
+    stsd.extend_from_slice(&boxed(b"mp4a", &mp4a));
+
+    let mut roll = b"roll".to_vec();
+    roll.extend_from_slice(&2u32.to_be_bytes()); // default_length
+    roll.extend_from_slice(&1u32.to_be_bytes());
+    roll.extend_from_slice(&(-1i16).to_be_bytes());
+
+    let stbl = container(
+        b"stbl",
+        &[
+            full_box(b"stsd", 0, 0, &stsd),
+            stts(&[(100, 1024)]),
+            full_box(b"sgpd", 1, 0, &roll),
+        ],
+    );
+    let mdia = container(
+        b"mdia",
+        &[
+            mdhd(44100, 102400, "und"),
+            hdlr(b"soun", "Sound"),
+            container(b"minf", &[stbl]),
+        ],
+    );
+    let mut kids = vec![tkhd(1, 0, 0, 0, 0)];
+    if let Some((duration, media_time)) = elst {
+        let mut p = 1u32.to_be_bytes().to_vec();
+        p.extend_from_slice(&duration.to_be_bytes());
+        p.extend_from_slice(&media_time.to_be_bytes());
+        p.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+        kids.push(container(b"edts", &[full_box(b"elst", 0, 0, &p)]));
+    }
+    kids.push(mdia);
+    container(b"trak", &kids)
+}
+
+#[test]
+fn itunsmpb_fields() {
+    let v = " 00000000 00000840 000001CC 0000000000046E00 00000000 00000000";
+    assert_eq!(
+        parse_itunsmpb(v),
+        Some(ItunSmpb {
+            encoder_delay: 0x840,
+            padding: 0x1CC,
+            original_sample_count: 0x46E00,
+        })
+    );
+    assert_eq!(parse_itunsmpb(" 00000000 zz"), None);
+}
+
+#[test]
+fn edit_list_priming_and_remainder() {
+    let boxes = decode(container(
+        b"moov",
+        &[mvhd(44100, 100000), audio_trak(Some((100000, 2112)))],
+    ));
+    let info = gapless_info(&boxes);
+    assert_eq!(info.len(), 1);
+    let g = &info[0];
+    assert_eq!(g.source, Some(GaplessSource::EditList));
+    assert_eq!(g.sample_rate, 44100);
+    assert_eq!(g.priming_samples, 2112);
+    assert_eq!(g.remainder_samples, 102400 - 2112 - 100000);
+    assert_eq!(g.valid_samples, Some(100000));
+    assert_eq!(g.roll_distance, Some(-1));
+}
+
+#[test]
+fn itunsmpb_applies_without_edit_list() {
+    let ilst = container(
+        b"ilst",
+        &[container(
+            b"----",
+            &[
+                full_box(b"mean", 0, 0, b"com.apple.iTunes"),
+                full_box(b"name", 0, 0, b"iTunSMPB"),
+                data_atom(1, b" 00000000 00000840 00000140 00000000000186A0"),
+            ],
+        )],
+    );
+    let boxes = decode(container(
+        b"moov",
+        &[
+            mvhd(44100, 0),
+            audio_trak(None),
+            container(b"udta", &[ilst]),
+        ],
+    ));
+    let g = &gapless_info(&boxes)[0];
+    assert_eq!(g.source, Some(GaplessSource::ITunSmpb));
+    assert_eq!(
+        (g.priming_samples, g.remainder_samples, g.valid_samples),
+        (0x840, 0x140, Some(100000))
+    );
+
+    // A whole-media edit says nothing about priming
+    let boxes = decode(container(
+        b"moov",
+        &[mvhd(44100, 0), audio_trak(Some((102400, 0)))],
+    ));
+    let g = &gapless_info(&boxes)[0];
+    assert_eq!(g.source, None);
+    assert_eq!((g.priming_samples, g.remainder_samples), (0, 0));
+}
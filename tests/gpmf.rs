@@ -0,0 +1,147 @@
+#![cfg(feature = "gpmf")]
+
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::gpmf::{GpsSample, parse, telemetry};
+use std::io::Cursor;
+
+fn klv(key: &[u8; 4], typ: u8, size: u8, values: &[u8]) -> Vec<u8> {
+    let repeat = if size == 0 {
+        0
+    } else {
+        values.len() / size as usize
+    };
+    let mut v = key.to_vec();
+    v.push(typ);
+    v.push(size);
+    v.extend_from_slice(&(repeat as u16).to_be_bytes());
+    v.extend_from_slice(values);
+    v.resize(v.len().next_multiple_of(4), 0);
+    v
+}
+
+fn nested(key: &[u8; 4], children: &[Vec<u8>]) -> Vec<u8> {
+    let body = children.concat();
+    let mut v = key.to_vec();
+    v.extend_from_slice(&[0, 4]);
+    v.extend_from_slice(&((body.len() / 4) as u16).to_be_bytes());
+    v.extend_from_slice(&body);
+    v
+}
+
+fn be<T: Copy>(values: &[T], f: fn(T) -> Vec<u8>) -> Vec<u8> {
+    values.iter().flat_map(|v| f(*v)).collect()
+}
+
+fn gpmf_sample() -> Vec<u8> {
+    let gps = nested(
+        b"STRM",
+        &[
+            klv(
+                b"STNM",
+                b'c',
+                1,
+                b"GPS (Lat., Long., Alt., 2D speed, 3D speed)",
+            ),
+            klv(
+                b"SCAL",
+                b'l',
+                4,
+                &be(&[10_000_000i32, 10_000_000, 1000, 1000, 100], |v| {
+                    v.to_be_bytes().to_vec()
+                }),
+            ),
+            klv(
+                b"GPS5",
+                b'l',
+                20,
+                &be(&[515_007_000i32, -1_246_000, 35_500, 1_250, 130], |v| {
+                    v.to_be_bytes().to_vec()
+                }),
+            ),
+        ],
+    );
+    let accl = nested(
+        b"STRM",
+        &[
+            klv(b"SCAL", b's', 2, &418i16.to_be_bytes()),
+            klv(
+                b"ACCL",
+                b's',
+                6,
+                &be(&[4180i16, 0, -836, 418, 418, 0], |v| {
+                    v.to_be_bytes().to_vec()
+                }),
+            ),
+        ],
+    );
+    nested(b"DEVC", &[klv(b"DVNM", b'c', 1, b"HERO9 Black"), gps, accl])
+}
+
+#[test]
+fn klv_tree_and_streams() {
+    let entries = parse(&gpmf_sample());
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].key, "DEVC");
+    let kids = &entries[0].children;
+    assert_eq!(kids[0].text().as_deref(), Some("HERO9 Black"));
+    assert_eq!(kids[1].key, "STRM");
+
+    let mut t = mp4box::gpmf::Telemetry::default();
+    t.extend_from_klv(&entries);
+    assert_eq!(
+        t.gps,
+        vec![GpsSample {
+            latitude: 51.5007,
+            longitude: -0.1246,
+            altitude: 35.5,
+            speed_2d: 1.25,
+            speed_3d: 1.3,
+        }]
+    );
+    assert_eq!(t.accelerometer, vec![[10.0, 0.0, -2.0], [1.0, 1.0, 0.0]]);
+    assert!(t.gyroscope.is_empty());
+}
+
+#[test]
+fn telemetry_from_gopro_met_track() {
+    let sample = gpmf_sample();
+    let ftyp = ftyp(b"mp41", &[b"mp41"]);
+    let first = (ftyp.len() + 8) as u32;
+    let mdat = boxed(b"mdat", &sample);
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(3, 1001, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 1001, "eng"),
+                    hdlr(b"meta", "\tGoPro MET"),
+                    container(
+                        b"minf",
+                        &[container(
+                            b"stbl",
+                            &[
+                                stts(&[(1, 1001)]),
+                                stsc(&[(1, 1, 1)]),
+                                stsz(&[sample.len() as u32]),
+                                stco(&[first]),
+                            ],
+                        )],
+                    ),
+                ],
+            ),
+        ],
+    );
+    let data = [ftyp, mdat, container(b"moov", &[trak])].concat();
+    let len = data.len() as u64;
+    let mut cursor = Cursor::new(data);
+    let boxes = get_boxes(&mut cursor, len, true).unwrap();
+
+    let t = telemetry(&mut cursor, &boxes).unwrap();
+    assert_eq!(t.gps.len(), 1);
+    assert_eq!(t.accelerometer.len(), 2);
+}
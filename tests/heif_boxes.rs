@@ -0,0 +1,131 @@
+mod common;
+
+use common::*;
+use mp4box::registry::StructuredData;
+
+#[test]
+fn iloc_version_1_with_base_offset_and_index() {
+    let mut p = Vec::new();
+    p.extend_from_slice(&0x4484u16.to_be_bytes()); // offset 4, length 4, base 8, index 4
+    p.extend_from_slice(&2u16.to_be_bytes()); // item_count
+    // item 1: file offsets, two extents
+    p.extend_from_slice(&1u16.to_be_bytes());
+    p.extend_from_slice(&0u16.to_be_bytes()); // construction_method 0
+    p.extend_from_slice(&0u16.to_be_bytes()); // data_reference_index
+    p.extend_from_slice(&0x1_0000_0000u64.to_be_bytes());
+    p.extend_from_slice(&2u16.to_be_bytes());
+    for (index, offset, length) in [(0u32, 0u32, 100u32), (1, 100, 50)] {
+        p.extend_from_slice(&index.to_be_bytes());
+        p.extend_from_slice(&offset.to_be_bytes());
+        p.extend_from_slice(&length.to_be_bytes());
+    }
+    // item 2: stored in idat
+    p.extend_from_slice(&2u16.to_be_bytes());
+    p.extend_from_slice(&1u16.to_be_bytes());
+    p.extend_from_slice(&0u16.to_be_bytes());
+    p.extend_from_slice(&0u64.to_be_bytes());
+    p.extend_from_slice(&1u16.to_be_bytes());
+    p.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 8, 0, 0, 0, 16]);
+
+    let boxes = decode(full_box(b"iloc", 1, 0, &p));
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::ItemLocation(iloc)) => {
+            assert_eq!(iloc.version, 1);
+            assert_eq!(iloc.base_offset_size, 8);
+            assert_eq!(iloc.index_size, 4);
+            assert_eq!(iloc.items.len(), 2);
+
+            let first = &iloc.items[0];
+            assert_eq!(first.item_id, 1);
+            assert_eq!(first.construction_method_name, "file_offset");
+            assert_eq!(first.base_offset, 0x1_0000_0000);
+            assert_eq!(first.extents[1].index, Some(1));
+            assert_eq!(first.extents[1].offset, 100);
+            assert_eq!(first.extents[1].length, 50);
+
+            let second = &iloc.items[1];
+            assert_eq!(second.construction_method, 1);
+            assert_eq!(second.construction_method_name, "idat_offset");
+            assert_eq!(second.extents[0].offset, 8);
+            assert_eq!(second.extents[0].length, 16);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn iloc_version_2_uses_32_bit_item_ids() {
+    let mut p = Vec::new();
+    p.extend_from_slice(&0x4400u16.to_be_bytes()); // offset 4, length 4, no base, no index
+    p.extend_from_slice(&1u32.to_be_bytes());
+    p.extend_from_slice(&70_000u32.to_be_bytes());
+    p.extend_from_slice(&0u16.to_be_bytes());
+    p.extend_from_slice(&0u16.to_be_bytes());
+    p.extend_from_slice(&1u16.to_be_bytes());
+    p.extend_from_slice(&1234u32.to_be_bytes());
+    p.extend_from_slice(&0u32.to_be_bytes());
+
+    let boxes = decode(full_box(b"iloc", 2, 0, &p));
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::ItemLocation(iloc)) => {
+            let item = &iloc.items[0];
+            assert_eq!(item.item_id, 70_000);
+            assert_eq!(item.base_offset, 0);
+            assert_eq!(item.extents[0].index, None);
+            assert_eq!(item.extents[0].offset, 1234);
+            assert_eq!(item.extents[0].length, 0);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn image_properties_in_ipco() {
+    let mut ispe = 4032u32.to_be_bytes().to_vec();
+    ispe.extend_from_slice(&3024u32.to_be_bytes());
+    let ipco = container(
+        b"ipco",
+        &[
+            full_box(b"ispe", 0, 0, &ispe),
+            full_box(b"pixi", 0, 0, &[3, 10, 10, 10]),
+        ],
+    );
+
+    let boxes = decode(ipco);
+    let props = boxes[0].children.as_ref().unwrap();
+    match props[0].structured_data.as_ref() {
+        Some(StructuredData::ImageSpatialExtents(e)) => {
+            assert_eq!((e.image_width, e.image_height), (4032, 3024));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match props[1].structured_data.as_ref() {
+        Some(StructuredData::PixelInformation(p)) => {
+            assert_eq!(p.bits_per_channel, vec![10, 10, 10]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn orientation_properties() {
+    let ipco = container(b"ipco", &[boxed(b"irot", &[3]), boxed(b"imir", &[1])]);
+
+    let boxes = decode(ipco);
+    let props = boxes[0].children.as_ref().unwrap();
+    assert_eq!(props[0].full_name, "Image Rotation Property");
+    match props[0].structured_data.as_ref() {
+        Some(StructuredData::ImageRotation(r)) => {
+            assert_eq!(r.angle, 3);
+            assert_eq!(r.degrees, 270);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match props[1].structured_data.as_ref() {
+        Some(StructuredData::ImageMirror(m)) => {
+            assert_eq!(m.axis, 1);
+            assert_eq!(m.axis_name, "horizontal");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
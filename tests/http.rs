@@ -0,0 +1,225 @@
+#![cfg(feature = "http")]
+
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::http::{HttpFetcher, RangeFetch, RangeReader};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fetcher over a buffer that records every request.
+struct Memory {
+    data: Vec<u8>,
+    ranges: Vec<(u64, u64)>,
+}
+
+impl RangeFetch for Memory {
+    fn total_len(&mut self) -> io::Result<u64> {
+        Ok(self.data.len() as u64)
+    }
+
+    fn fetch(&mut self, start: u64, len: u64) -> io::Result<Vec<u8>> {
+        self.ranges.push((start, len));
+        let end = (start + len).min(self.data.len() as u64);
+        Ok(self.data[start as usize..end as usize].to_vec())
+    }
+}
+
+/// ftyp, 1 MiB mdat, then moov at the end.
+fn progressive() -> Vec<u8> {
+    [
+        ftyp(b"isom", &[b"isom"]),
+        boxed(b"mdat", &vec![0xaa; 1 << 20]),
+        container(b"moov", &[mvhd(1000, 5000)]),
+    ]
+    .concat()
+}
+
+#[test]
+fn parsing_fetches_headers_and_moov_only() {
+    let data = progressive();
+    let size = data.len() as u64;
+    let fetcher = Memory {
+        data: data.clone(),
+        ranges: Vec::new(),
+    };
+    let mut reader = RangeReader::new(fetcher)
+        .unwrap()
+        .chunk_size(4096)
+        .read_ahead(1);
+    assert_eq!(reader.len(), size);
+
+    let boxes = get_boxes(&mut reader, size, true).unwrap();
+    let types: Vec<_> = boxes.iter().map(|b| b.typ.as_str()).collect();
+    assert_eq!(types, ["ftyp", "mdat", "moov"]);
+    assert!(reader.bytes_fetched() <= 4 * 4096);
+    assert_eq!(reader.requests() as usize, reader.into_inner().ranges.len());
+}
+
+#[test]
+fn reads_match_the_source_across_chunks() {
+    let data: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+    let fetcher = Memory {
+        data: data.clone(),
+        ranges: Vec::new(),
+    };
+    let mut reader = RangeReader::new(fetcher)
+        .unwrap()
+        .chunk_size(1000)
+        .read_ahead(2)
+        .cache_chunks(2);
+
+    reader.seek(SeekFrom::Start(2500)).unwrap();
+    let mut buf = vec![0; 3000];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, data[2500..5500]);
+
+    // Back to a chunk that was evicted
+    reader.seek(SeekFrom::Start(0)).unwrap();
+    let mut all = Vec::new();
+    reader.read_to_end(&mut all).unwrap();
+    assert_eq!(all, data);
+    let ranges = reader.into_inner().ranges;
+    assert_eq!(ranges[0], (2000, 3000));
+}
+
+/// How the test server answers.
+#[derive(Clone, Copy, PartialEq)]
+enum Server {
+    Ranges,
+    /// Ignores Range and sends the whole resource
+    NoRanges,
+    /// Ranges in a chunked body
+    Chunked,
+    /// Always answers from the first byte
+    FromStart,
+    /// Reads the request and never answers
+    Stalled,
+}
+
+/// Serve `data` over HTTP on a local port.
+fn serve(data: Arc<Vec<u8>>, server: Server) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/video.mp4", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut range = None;
+            for line in BufReader::new(&stream).lines() {
+                let line = line.unwrap();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(r) = line.strip_prefix("Range: bytes=") {
+                    let (a, b) = r.split_once('-').unwrap();
+                    range = Some((a.parse::<usize>().unwrap(), b.parse::<usize>().unwrap()));
+                }
+            }
+            if server == Server::Stalled {
+                std::thread::sleep(std::time::Duration::from_secs(10));
+                continue;
+            }
+            let response = match range.filter(|_| server != Server::NoRanges) {
+                Some((first, last)) => {
+                    let first = if server == Server::FromStart {
+                        0
+                    } else {
+                        first
+                    };
+                    let last = last.min(data.len() - 1);
+                    let body = &data[first..=last];
+                    let mut r = format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {first}-{last}/{}\r\n",
+                        data.len()
+                    )
+                    .into_bytes();
+                    if server == Server::Chunked {
+                        r.extend_from_slice(b"Transfer-Encoding: chunked\r\n\r\n");
+                        for chunk in body.chunks(100) {
+                            r.extend(format!("{:x};ext=1\r\n", chunk.len()).into_bytes());
+                            r.extend_from_slice(chunk);
+                            r.extend_from_slice(b"\r\n");
+                        }
+                        r.extend_from_slice(b"0\r\nX-Trailer: 1\r\n\r\n");
+                    } else {
+                        r.extend(format!("Content-Length: {}\r\n\r\n", body.len()).into_bytes());
+                        r.extend_from_slice(body);
+                    }
+                    r
+                }
+                None => {
+                    let mut r =
+                        format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", data.len())
+                            .into_bytes();
+                    r.extend_from_slice(&data);
+                    r
+                }
+            };
+            let _ = stream.write_all(&response);
+        }
+    });
+    url
+}
+
+#[test]
+fn http_server_with_ranges() {
+    let data = Arc::new(progressive());
+    let url = serve(data.clone(), Server::Ranges);
+
+    let mut reader = RangeReader::open(&url).unwrap();
+    assert_eq!(reader.len(), data.len() as u64);
+    let boxes = get_boxes(&mut reader, data.len() as u64, true).unwrap();
+    assert_eq!(boxes[2].typ, "moov");
+    assert!(reader.bytes_fetched() < data.len() as u64 / 2);
+}
+
+#[test]
+fn http_server_without_ranges_is_an_error() {
+    let url = serve(Arc::new(progressive()), Server::NoRanges);
+    let err = RangeReader::open(&url).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+
+    assert!(RangeReader::open("https://example.com/a.mp4").is_err());
+}
+
+#[test]
+fn http_chunked_bodies_are_decoded() {
+    let data = Arc::new(progressive());
+    let url = serve(data.clone(), Server::Chunked);
+
+    let mut reader = RangeReader::open(&url).unwrap().chunk_size(1000);
+    let mut all = Vec::new();
+    reader.read_to_end(&mut all).unwrap();
+    assert_eq!(all, *data);
+}
+
+#[test]
+fn http_range_must_start_where_asked() {
+    let data = Arc::new(progressive());
+    let url = serve(data, Server::FromStart);
+
+    let mut reader = RangeReader::open(&url).unwrap().chunk_size(1000);
+    reader.seek(SeekFrom::Start(1500)).unwrap();
+    let err = reader.read(&mut [0; 4]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn http_stalled_server_times_out() {
+    let url = serve(Arc::new(progressive()), Server::Stalled);
+    let fetcher = HttpFetcher::new(&url)
+        .unwrap()
+        .timeout(Duration::from_millis(100));
+
+    let err = RangeReader::new(fetcher).err().unwrap();
+    assert!(
+        matches!(
+            err.kind(),
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+        ),
+        "{err}"
+    );
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use std::io::Cursor;
+
+fn syncsafe(v: u32) -> [u8; 4] {
+    [
+        (v >> 21) as u8 & 0x7F,
+        (v >> 14) as u8 & 0x7F,
+        (v >> 7) as u8 & 0x7F,
+        v as u8 & 0x7F,
+    ]
+}
+
+fn frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut f = id.to_vec();
+    f.extend_from_slice(&syncsafe(body.len() as u32));
+    f.extend_from_slice(&[0, 0]);
+    f.extend_from_slice(body);
+    f
+}
+
+fn id3v24(frames: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = frames.concat();
+    body.extend_from_slice(&[0u8; 16]); // padding
+    let mut tag = b"ID3\x04\x00\x00".to_vec();
+    tag.extend_from_slice(&syncsafe(body.len() as u32));
+    tag.extend_from_slice(&body);
+    tag
+}
+
+#[test]
+fn id32_text_frames() {
+    let utf16_artist: Vec<u8> = [1, 0xFF, 0xFE]
+        .into_iter()
+        .chain("Björk".encode_utf16().flat_map(|u| u.to_le_bytes()))
+        .collect();
+    let tag = id3v24(&[
+        frame(b"TIT2", b"\x03Hello\0"),
+        frame(b"TPE1", &utf16_artist),
+        frame(b"TCON", b"\x03Pop\0Rock"),
+        frame(b"TXXX", b"\x03mood\0calm"),
+        frame(b"APIC", b"\x00image/png\0\x03\0\x89PNG"),
+    ]);
+
+    // pad bit + packed "eng"
+    let mut payload = 0x15C7u16.to_be_bytes().to_vec();
+    payload.extend_from_slice(&tag);
+    let data = full_box(b"ID32", 0, 0, &payload);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    assert_eq!(boxes[0].full_name, "ID3v2 Metadata Box");
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::Id3Tag(id3)) => {
+            assert_eq!(id3.language, "eng");
+            assert_eq!(id3.id3_version, "2.4.0");
+            let ids: Vec<_> = id3.frames.iter().map(|f| f.id.as_str()).collect();
+            assert_eq!(ids, ["TIT2", "TPE1", "TCON", "TXXX", "APIC"]);
+            assert_eq!(id3.tags["TIT2"], "Hello");
+            assert_eq!(id3.tags["TPE1"], "Björk");
+            assert_eq!(id3.tags["TCON"], "Pop; Rock");
+            assert_eq!(id3.tags["TXXX:mood"], "calm");
+            assert!(!id3.tags.contains_key("APIC"));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::*;
+use mp4box::iter::{Iter, Order};
+use mp4box::parser::parse_children;
+use mp4box::{Box, get_boxes};
+use std::io::Cursor;
+
+fn movie() -> Vec<u8> {
+    let stbl = container(b"stbl", &[stts(&[(1, 1000)]), stsz(&[4])]);
+    let minf = container(b"minf", &[stbl]);
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 1000, 0, 0, 0),
+            container(b"mdia", &[mdhd(1000, 1000, "und"), minf]),
+        ],
+    );
+    container(b"moov", &[mvhd(1000, 1000), trak])
+}
+
+fn moov() -> Box {
+    let data = movie();
+    get_boxes(&mut Cursor::new(&data), data.len() as u64, true)
+        .unwrap()
+        .remove(0)
+}
+
+fn names<'a>(items: impl Iterator<Item = (Vec<&'a Box>, &'a Box)>) -> Vec<String> {
+    items
+        .map(|(path, b)| {
+            let mut types: Vec<&str> = path.iter().map(|p| p.typ.as_str()).collect();
+            types.push(&b.typ);
+            types.join(".")
+        })
+        .collect()
+}
+
+#[test]
+fn depth_first_follows_file_order() {
+    let moov = moov();
+    assert_eq!(
+        names(moov.iter()),
+        [
+            "moov",
+            "moov.mvhd",
+            "moov.trak",
+            "moov.trak.tkhd",
+            "moov.trak.mdia",
+            "moov.trak.mdia.mdhd",
+            "moov.trak.mdia.minf",
+            "moov.trak.mdia.minf.stbl",
+            "moov.trak.mdia.minf.stbl.stts",
+            "moov.trak.mdia.minf.stbl.stsz",
+        ]
+    );
+}
+
+#[test]
+fn breadth_first_goes_level_by_level() {
+    let moov = moov();
+    let depths: Vec<usize> = moov.iter_bfs().map(|(path, _)| path.len()).collect();
+    assert!(depths.is_sorted());
+    assert_eq!(
+        names(moov.iter_bfs())[..4],
+        ["moov", "moov.mvhd", "moov.trak", "moov.trak.tkhd"]
+    );
+    assert_eq!(moov.iter_bfs().count(), moov.iter().count());
+}
+
+#[test]
+fn descendants_of_type_skips_the_box_itself() {
+    let moov = moov();
+    let stbl: Vec<_> = moov.descendants_of_type("stbl").collect();
+    assert_eq!(stbl.len(), 1);
+    assert_eq!(stbl[0].children.as_ref().unwrap().len(), 2);
+    assert_eq!(moov.descendants_of_type("moov").count(), 0);
+
+    let forest = [moov, self::moov()];
+    assert_eq!(Iter::new(&forest, Order::DepthFirst).count(), 20);
+}
+
+#[test]
+fn child_looks_one_level_down() {
+    let moov = moov();
+    let trak = moov.child("trak").unwrap();
+    assert_eq!(trak.child("mdia").unwrap().typ, "mdia");
+    assert!(moov.child("mdia").is_none());
+    assert!(trak.child("tkhd").unwrap().child("tkhd").is_none());
+}
+
+#[test]
+fn raw_trees_iterate_too() {
+    let data = movie();
+    let mut r = Cursor::new(&data);
+    let top = parse_children(&mut r, data.len() as u64).unwrap();
+    let moov = &top[0];
+    assert_eq!(moov.iter().count(), 10);
+    let stsz = moov.descendants_of_type("stsz").next().unwrap();
+    assert_eq!(&stsz.hdr.typ.0, b"stsz");
+    let (path, last) = moov.iter_bfs().last().unwrap();
+    assert_eq!(path.len(), 5);
+    assert_eq!(last.hdr.typ, stsz.hdr.typ);
+    assert_eq!(&moov.child("trak").unwrap().hdr.typ.0, b"trak");
+}
@@ -0,0 +1,164 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::metadata::{ImageFormat, extract_cover_art, itunes_tags};
+use mp4box::registry::TagValue;
+use std::io::Cursor;
+
+fn freeform(mean: &str, name: &str, value: &str) -> Vec<u8> {
+    container(
+        b"----",
+        &[
+            full_box(b"mean", 0, 0, mean.as_bytes()),
+            full_box(b"name", 0, 0, name.as_bytes()),
+            data_atom(1, value.as_bytes()),
+        ],
+    )
+}
+
+fn sample_ilst() -> Vec<u8> {
+    container(
+        b"ilst",
+        &[
+            text_item(b"\xA9nam", "Song Title"),
+            text_item(b"\xA9ART", "Artist"),
+            text_item(b"\xA9alb", "Album"),
+            text_item(b"\xA9day", "2024"),
+            container(b"trkn", &[data_atom(0, &[0, 0, 0, 3, 0, 12, 0, 0])]),
+            container(b"disk", &[data_atom(0, &[0, 0, 0, 1, 0, 2])]),
+            container(b"gnre", &[data_atom(0, &[0, 18])]),
+            container(
+                b"covr",
+                &[
+                    data_atom(13, &[0xFF, 0xD8, 0xFF, 0xE0]),
+                    data_atom(14, b"\x89PNG\r\n\x1a\n"),
+                ],
+            ),
+            freeform("com.apple.iTunes", "iTunNORM", " 0000021A"),
+        ],
+    )
+}
+
+#[test]
+fn ilst_tags_are_collected() {
+    let data = container(b"moov", &[container(b"udta", &[sample_ilst()])]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    let tags = itunes_tags(&boxes);
+
+    assert_eq!(tags["©nam"], vec![TagValue::Text("Song Title".into())]);
+    assert_eq!(tags["©ART"], vec![TagValue::Text("Artist".into())]);
+    assert_eq!(tags["©alb"], vec![TagValue::Text("Album".into())]);
+    assert_eq!(tags["©day"], vec![TagValue::Text("2024".into())]);
+    assert_eq!(
+        tags["trkn"],
+        vec![TagValue::Index {
+            number: 3,
+            total: 12
+        }]
+    );
+    assert_eq!(
+        tags["disk"],
+        vec![TagValue::Index {
+            number: 1,
+            total: 2
+        }]
+    );
+    assert_eq!(tags["gnre"], vec![TagValue::Integer(18)]);
+    assert_eq!(
+        tags["covr"],
+        vec![
+            TagValue::Image {
+                format: "jpeg".into(),
+                size: 4
+            },
+            TagValue::Image {
+                format: "png".into(),
+                size: 8
+            },
+        ]
+    );
+    assert_eq!(
+        tags["com.apple.iTunes:iTunNORM"],
+        vec![TagValue::Text(" 0000021A".into())]
+    );
+}
+
+#[test]
+fn iso_meta_skips_version_and_flags() {
+    let meta = full_box(b"meta", 0, 0, &[hdlr(b"mdir", ""), sample_ilst()].concat());
+    let data = container(b"moov", &[container(b"udta", &[meta])]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    let udta = &boxes[0].children.as_ref().unwrap()[0];
+    let meta = &udta.children.as_ref().unwrap()[0];
+    assert_eq!((meta.kind.as_str(), meta.version), ("full", Some(0)));
+    let kids: Vec<&str> = meta
+        .children
+        .iter()
+        .flatten()
+        .map(|b| b.typ.as_str())
+        .collect();
+    assert_eq!(kids, ["hdlr", "ilst"]);
+    assert_eq!(
+        itunes_tags(&boxes)["©nam"],
+        vec![TagValue::Text("Song Title".into())]
+    );
+}
+
+#[test]
+fn ilst_items_are_containers() {
+    let data = sample_ilst();
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    let items = boxes[0].children.as_ref().unwrap();
+    assert_eq!(items[0].typ, "©nam");
+    assert_eq!(items[0].kind, "container");
+    assert_eq!(items[0].children.as_ref().unwrap()[0].typ, "data");
+}
+
+#[test]
+fn integer_data_atoms() {
+    let data = container(
+        b"ilst",
+        &[
+            container(b"tmpo", &[data_atom(21, &[0x00, 0x78])]),
+            container(b"rtng", &[data_atom(21, &[0xFF])]),
+        ],
+    );
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    let tags = itunes_tags(&boxes);
+    assert_eq!(tags["tmpo"], vec![TagValue::Integer(120)]);
+    assert_eq!(tags["rtng"], vec![TagValue::Integer(-1)]);
+}
+
+#[test]
+fn cover_art_is_extracted_by_magic() {
+    let png = b"\x89PNG\r\n\x1a\nrest-of-png".to_vec();
+    let jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 1, 2, 3];
+    let data = container(
+        b"ilst",
+        &[
+            text_item(b"\xA9nam", "Title"),
+            container(
+                b"covr",
+                // Mislabelled as JPEG; magic bytes win
+                &[data_atom(13, &png), data_atom(13, &jpeg)],
+            ),
+        ],
+    );
+    let len = data.len() as u64;
+    let mut cursor = Cursor::new(data);
+    let boxes = get_boxes(&mut cursor, len, true).unwrap();
+
+    let art = extract_cover_art(&mut cursor, &boxes).unwrap();
+    assert_eq!(art.len(), 2);
+    assert_eq!(art[0].format, ImageFormat::Png);
+    assert_eq!(art[0].data, png);
+    assert_eq!(art[1].format, ImageFormat::Jpeg);
+    assert_eq!(art[1].format.mime_type(), "image/jpeg");
+    assert_eq!(art[1].data, jpeg);
+}
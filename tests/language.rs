@@ -0,0 +1,61 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::language::{iso639_1, normalize_bcp47, track_language_tag};
+use mp4box::registry::StructuredData;
+use std::io::Cursor;
+
+#[test]
+fn iso639_2_codes_shorten_to_639_1() {
+    assert_eq!(iso639_1("eng"), Some("en"));
+    assert_eq!(iso639_1("deu"), Some("de"));
+    // Bibliographic forms are accepted too
+    assert_eq!(iso639_1("ger"), Some("de"));
+    assert_eq!(iso639_1("ZHO"), Some("zh"));
+    // No two-letter equivalent
+    assert_eq!(iso639_1("und"), None);
+    assert_eq!(iso639_1("yue"), None);
+}
+
+#[test]
+fn tags_are_normalized() {
+    assert_eq!(normalize_bcp47("EN-us").as_deref(), Some("en-US"));
+    assert_eq!(normalize_bcp47("zh-hant-tw").as_deref(), Some("zh-Hant-TW"));
+    assert_eq!(normalize_bcp47("spa-419").as_deref(), Some("es-419"));
+    assert_eq!(normalize_bcp47("yue").as_deref(), Some("yue"));
+    assert_eq!(normalize_bcp47("").as_deref(), None);
+
+    assert_eq!(track_language_tag("und", None).as_deref(), Some("und"));
+    assert_eq!(track_language_tag("fra", Some("")).as_deref(), Some("fr"));
+}
+
+#[test]
+fn elng_overrides_mdhd_language() {
+    let mut elng = b"en-us".to_vec();
+    elng.push(0);
+    let data = container(
+        b"mdia",
+        &[mdhd(1000, 0, "eng"), full_box(b"elng", 0, 0, &elng)],
+    );
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    let children = boxes[0].children.as_ref().unwrap();
+
+    let mdhd_lang = match &children[0].structured_data {
+        Some(StructuredData::MediaHeader(m)) => {
+            assert_eq!(m.language_tag.as_deref(), Some("en"));
+            m.language.clone()
+        }
+        other => panic!("unexpected {:?}", other),
+    };
+    let elng = match &children[1].structured_data {
+        Some(StructuredData::ExtendedLanguage(e)) => e.extended_language.clone(),
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(elng, "en-us");
+    assert_eq!(
+        track_language_tag(&mdhd_lang, Some(&elng)).as_deref(),
+        Some("en-US")
+    );
+}
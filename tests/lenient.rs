@@ -0,0 +1,67 @@
+mod common;
+
+use common::*;
+use mp4box::{WarningKind, get_boxes_lenient};
+use std::io::Cursor;
+
+#[test]
+fn truncated_file_keeps_what_parses() {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let moov = container(b"moov", &[mvhd(1000, 0), boxed(b"udta", &[0; 40])]);
+    let mut data = [ftyp.clone(), moov.clone()].concat();
+    // Cut the file in the middle of udta
+    data.truncate(data.len() - 20);
+    let size = data.len() as u64;
+
+    let (boxes, warnings) = get_boxes_lenient(&mut Cursor::new(&data), size, true);
+    let types: Vec<_> = boxes.iter().map(|b| b.typ.as_str()).collect();
+    assert_eq!(types, ["ftyp", "moov"]);
+    let moov_kids: Vec<_> = boxes[1]
+        .children
+        .iter()
+        .flatten()
+        .map(|b| b.typ.as_str())
+        .collect();
+    assert_eq!(moov_kids, ["mvhd", "udta"]);
+
+    let moov_at = ftyp.len() as u64;
+    let kinds: Vec<_> = warnings
+        .iter()
+        .map(|w| (w.offset, w.typ.as_deref(), &w.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            (
+                moov_at,
+                Some("moov"),
+                &WarningKind::Truncated {
+                    declared_end: moov_at + moov.len() as u64,
+                    available_end: size,
+                }
+            ),
+            (
+                size + 20 - 48,
+                Some("udta"),
+                &WarningKind::Truncated {
+                    declared_end: size + 20,
+                    available_end: size,
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn bad_header_and_decode_failure() {
+    // mdhd too short to decode, then 5 bytes that are not a box header
+    let moov = container(b"moov", &[full_box(b"mdhd", 0, 0, &[0; 4])]);
+    let data = [moov.clone(), vec![0, 0, 0, 0x10, b'x']].concat();
+    let (boxes, warnings) = get_boxes_lenient(&mut Cursor::new(&data), data.len() as u64, true);
+
+    assert_eq!(boxes.len(), 1);
+    assert!(matches!(warnings[0].kind, WarningKind::BadHeader { .. }));
+    assert_eq!(warnings[0].offset, moov.len() as u64);
+    assert_eq!(warnings[1].typ.as_deref(), Some("mdhd"));
+    assert!(matches!(warnings[1].kind, WarningKind::DecodeFailed { .. }));
+}
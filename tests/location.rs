@@ -0,0 +1,58 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use mp4box::util::parse_iso6709;
+use std::io::Cursor;
+
+fn xyz(location: &str) -> Vec<u8> {
+    let mut p = (location.len() as u16).to_be_bytes().to_vec();
+    p.extend_from_slice(&0x15c7u16.to_be_bytes());
+    p.extend_from_slice(location.as_bytes());
+    boxed(b"\xA9xyz", &p)
+}
+
+#[test]
+fn xyz_in_udta_is_decoded() {
+    let data = container(
+        b"moov",
+        &[container(b"udta", &[xyz("+37.7749-122.4194+010.000/")])],
+    );
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    let udta = &boxes[0].children.as_ref().unwrap()[0];
+    let loc = &udta.children.as_ref().unwrap()[0];
+    assert_eq!(loc.full_name, "GPS Location");
+    match loc.structured_data.as_ref() {
+        Some(StructuredData::Location(l)) => {
+            assert_eq!(l.iso6709, "+37.7749-122.4194+010.000/");
+            assert_eq!(l.latitude, Some(37.7749));
+            assert_eq!(l.longitude, Some(-122.4194));
+            assert_eq!(l.altitude, Some(10.0));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn iso6709_forms() {
+    assert_eq!(
+        parse_iso6709("+48.8577+002.2950/"),
+        Some((48.8577, 2.295, None))
+    );
+
+    // Degrees and minutes
+    let (lat, lon, _) = parse_iso6709("+4851.462+00217.700/").unwrap();
+    assert!((lat - 48.8577).abs() < 1e-4);
+    assert!((lon - 2.295).abs() < 1e-4);
+
+    // Degrees, minutes and seconds with a CRS suffix
+    let (lat, lon, alt) = parse_iso6709("-335124.0+1511245.0+5.0CRSWGS_84/").unwrap();
+    assert!((lat + 33.8567).abs() < 1e-4);
+    assert!((lon - 151.2125).abs() < 1e-4);
+    assert_eq!(alt, Some(5.0));
+
+    assert_eq!(parse_iso6709("garbage"), None);
+    assert_eq!(parse_iso6709("+95.0+000.0/"), None);
+}
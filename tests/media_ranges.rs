@@ -0,0 +1,129 @@
+mod common;
+
+use common::*;
+use mp4box::{ByteRange, ParseOptions, get_boxes, get_boxes_with_options};
+use std::io::Cursor;
+
+#[test]
+fn moof_links_to_following_mdat() {
+    let moov = container(b"moov", &[container(b"mvex", &[trex(1, 1000, 0)])]);
+
+    // moof layout is fixed-size, so build once to learn its length
+    let build_moof = |data_offset: i32| {
+        container(
+            b"moof",
+            &[
+                mfhd(1),
+                container(b"traf", &[tfhd(1, None), trun(data_offset, &[10, 20, 30])]),
+            ],
+        )
+    };
+    let moof_len = build_moof(0).len() as i32;
+    let moof = build_moof(moof_len + 8);
+    let mdat = boxed(b"mdat", &[0u8; 60]);
+
+    let moof_offset = moov.len() as u64;
+    let mdat_offset = moof_offset + moof.len() as u64;
+    let boxes = decode([moov, moof, mdat].concat());
+
+    let moof = boxes.iter().find(|b| b.typ == "moof").unwrap();
+    assert_eq!(
+        moof.media_ranges.as_deref(),
+        Some(
+            &[ByteRange {
+                offset: mdat_offset + 8,
+                size: 60,
+                mdat_offset: Some(mdat_offset),
+            }][..]
+        )
+    );
+}
+
+fn video_trak(stbl: Vec<u8>) -> Vec<u8> {
+    container(
+        b"trak",
+        &[
+            tkhd(1, 4000, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 4000, "und"),
+                    hdlr(b"vide", "Video"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    )
+}
+
+#[test]
+fn trak_ranges_follow_chunk_offsets() {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let mdat_offset = ftyp.len() as u64;
+    let mdat = boxed(b"mdat", &[0u8; 40]);
+    let first = (mdat_offset + 8) as u32;
+
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(4, 1000)]),
+            stsc(&[(1, 2, 1)]),
+            stsz(&[5, 5, 10, 10]),
+            // Second chunk leaves a 10 byte gap after the first
+            stco(&[first, first + 20]),
+        ],
+    );
+    let moov = container(b"moov", &[video_trak(stbl)]);
+    let boxes = decode([ftyp, mdat, moov].concat());
+
+    let moov = boxes.iter().find(|b| b.typ == "moov").unwrap();
+    let trak = &moov.children.as_ref().unwrap()[0];
+    let ranges = trak.media_ranges.as_ref().unwrap();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!((ranges[0].offset, ranges[0].size), (first as u64, 10));
+    assert_eq!((ranges[1].offset, ranges[1].size), (first as u64 + 20, 20));
+    assert!(ranges.iter().all(|r| r.mdat_offset == Some(mdat_offset)));
+}
+
+#[test]
+fn ranges_are_only_filled_when_decoding() {
+    let data = container(b"moof", &[mfhd(1)]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, false).unwrap();
+    assert!(boxes[0].media_ranges.is_none());
+}
+
+#[test]
+fn ranges_respect_the_table_entry_limit() {
+    // 0xFFFFFFFF one-byte samples in a single chunk
+    let stsz = full_box(b"stsz", 0, 0, &[0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFF]);
+    let stbl = container(b"stbl", &[stsc(&[(1, u32::MAX, 1)]), stsz, stco(&[100])]);
+    let data = [container(b"moov", &[video_trak(stbl)]), vec![0; 100]].concat();
+
+    let options = ParseOptions::new().max_table_entries(8);
+    let len = data.len() as u64;
+    let (boxes, _) = get_boxes_with_options(&mut Cursor::new(data), len, &options).unwrap();
+    let trak = &boxes[0].children.as_ref().unwrap()[0];
+    let ranges = trak.media_ranges.as_ref().unwrap();
+    assert_eq!((ranges[0].offset, ranges[0].size), (100, 8));
+}
+
+#[test]
+fn ranges_near_the_end_of_the_address_space_saturate() {
+    let mut co64 = 1u32.to_be_bytes().to_vec();
+    co64.extend_from_slice(&(u64::MAX - 4).to_be_bytes());
+    let stbl = container(
+        b"stbl",
+        &[
+            stsc(&[(1, 2, 1)]),
+            stsz(&[5, 5]),
+            full_box(b"co64", 0, 0, &co64),
+        ],
+    );
+    let boxes = decode(container(b"moov", &[video_trak(stbl)]));
+
+    let trak = &boxes[0].children.as_ref().unwrap()[0];
+    let ranges = trak.media_ranges.as_ref().unwrap();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!((ranges[0].offset, ranges[0].size), (u64::MAX - 4, 5));
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::*;
+use mp4box::{TrackKind, summarize_reader};
+use std::io::Cursor;
+
+fn trak(
+    id: u32,
+    handler: &[u8; 4],
+    entry: Vec<u8>,
+    timescale: u32,
+    deltas: &[(u32, u32)],
+    sizes: &[u32],
+) -> Vec<u8> {
+    let duration: u32 = deltas.iter().map(|(c, d)| c * d).sum();
+    let mut stsd = 1u32.to_be_bytes().to_vec();
+    stsd.extend_from_slice(&entry);
+    let stbl = container(
+        b"stbl",
+        &[full_box(b"stsd", 0, 0, &stsd), stts(deltas), stsz(sizes)],
+    );
+    let mdia = container(
+        b"mdia",
+        &[
+            mdhd(timescale, duration, "eng"),
+            hdlr(handler, ""),
+            container(b"minf", &[stbl]),
+        ],
+    );
+    container(b"trak", &[tkhd(id, 0, 0, 0, 0), mdia])
+}
+
+#[test]
+fn typed_movie_summary() {
+    let mut avc1 = vec![0u8; 6];
+    avc1.extend_from_slice(&1u16.to_be_bytes());
+    avc1.extend_from_slice(&[0; 16]);
+    avc1.extend_from_slice(&1280u16.to_be_bytes());
+    avc1.extend_from_slice(&720u16.to_be_bytes());
+    avc1.extend_from_slice(&[0; 50]);
+
+    let mut mp4a = vec![0u8; 6];
+    mp4a.extend_from_slice(&1u16.to_be_bytes());
+    mp4a.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 16, 0, 0, 0, 0]);
+    mp4a.extend_from_slice(&(48000u32 << 16).to_be_bytes());
+
+    let mut data = ftyp(b"isom", &[b"isom", b"avc1"]);
+    data.extend_from_slice(&container(
+        b"moov",
+        &[
+            mvhd(1000, 2000),
+            trak(
+                1,
+                b"vide",
+                boxed(b"avc1", &avc1),
+                90000,
+                &[(50, 3600)],
+                &[5000; 50],
+            ),
+            trak(
+                2,
+                b"soun",
+                boxed(b"mp4a", &mp4a),
+                48000,
+                &[(96, 1000)],
+                &[250; 96],
+            ),
+        ],
+    ));
+
+    let s = summarize_reader(Cursor::new(data)).unwrap();
+    assert_eq!(s.major_brand.as_deref(), Some("isom"));
+    assert_eq!(s.compatible_brands, ["isom", "avc1"]);
+    assert_eq!(s.duration_seconds, Some(2.0));
+    assert_eq!(s.tracks.len(), 2);
+
+    let v = &s.tracks[0];
+    assert_eq!(v.kind, Some(TrackKind::Video));
+    assert_eq!(v.codec.as_deref(), Some("avc1"));
+    assert_eq!((v.width, v.height), (Some(1280), Some(720)));
+    assert_eq!(v.frame_rate.unwrap().average, 25.0);
+    assert_eq!(v.bitrate, Some(50 * 5000 * 8 / 2));
+    assert_eq!(v.language_tag.as_deref(), Some("en"));
+
+    let a = &s.tracks[1];
+    assert_eq!(a.kind, Some(TrackKind::Audio));
+    assert_eq!((a.sample_rate, a.channels), (Some(48000), Some(2)));
+    assert_eq!(a.duration_seconds, Some(2.0));
+    assert_eq!(a.bitrate, Some(96 * 250 * 8 / 2));
+    assert!(a.frame_rate.is_none());
+}
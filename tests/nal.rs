@@ -0,0 +1,34 @@
+use mp4box::nal::{NalCodec, TruncatedNal, nal_units};
+
+#[test]
+fn avc_units_with_two_byte_lengths() {
+    // SPS, PPS, SEI, IDR slice, zero-length unit
+    let sample = [
+        0, 2, 0x67, 0x42, 0, 1, 0x68, 0, 3, 0x06, 0x05, 0x80, 0, 2, 0x65, 0x88, 0, 0,
+    ];
+    let units: Vec<_> = nal_units(&sample, 2, NalCodec::Avc)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let types: Vec<u8> = units.iter().map(|u| u.nal_type).collect();
+    assert_eq!(types, [7, 8, 6, 5]);
+    assert_eq!(units.iter().filter(|u| u.is_parameter_set()).count(), 2);
+    assert_eq!(units.iter().filter(|u| u.is_sei()).count(), 1);
+    assert!(units[3].is_idr());
+    assert_eq!(units[2].payload(), [0x05, 0x80]);
+}
+
+#[test]
+fn hevc_header_and_truncation() {
+    // IDR_W_RADL (19), then a length running past the end
+    let sample = [0, 0, 0, 3, 19 << 1, 0x01, 0xAF, 0, 0, 0, 9, 0x4E];
+    let mut it = nal_units(&sample, 4, NalCodec::Hevc);
+    let idr = it.next().unwrap().unwrap();
+    assert_eq!(idr.nal_type, 19);
+    assert!(idr.is_idr());
+    assert_eq!(idr.payload(), [0xAF]);
+    assert_eq!(it.next(), Some(Err(TruncatedNal { offset: 7 })));
+    assert_eq!(it.next(), None);
+
+    assert_eq!(NalCodec::from_sample_entry("hev1"), Some(NalCodec::Hevc));
+    assert_eq!(NalCodec::from_sample_entry("mp4a"), None);
+}
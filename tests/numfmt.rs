@@ -0,0 +1,48 @@
+use mp4box::numfmt::NumberFormat;
+
+#[test]
+fn porcelain_is_plain() {
+    let nf = NumberFormat::porcelain();
+    assert_eq!(nf.int(1_234_567u64), "1234567");
+    assert_eq!(nf.float(1234.5, 3), "1234.500");
+    assert_eq!(nf.bytes(5_000_000), "5000000");
+    assert_eq!(nf.seconds(3723.5), "3723.500 s");
+}
+
+#[test]
+fn human_groups_digits() {
+    let nf = NumberFormat::human();
+    assert_eq!(nf.int(0u8), "0");
+    assert_eq!(nf.int(999u32), "999");
+    assert_eq!(nf.int(1_234_567u64), "1,234,567");
+    assert_eq!(nf.int(-1234i64), "-1,234");
+    assert_eq!(nf.float(1234.5, 2), "1,234.50");
+    assert_eq!(nf.float(-0.25, 2), "-0.25");
+    assert_eq!(nf.bytes(512), "512 B");
+    assert_eq!(nf.bytes(1536), "1.5 KiB");
+    assert_eq!(nf.bytes(5 * 1024 * 1024), "5.0 MiB");
+    assert_eq!(nf.seconds(12.5), "12.500 s");
+    assert_eq!(nf.seconds(62.5), "1:02.500");
+    assert_eq!(nf.seconds(3723.5), "1:02:03.500");
+}
+
+#[test]
+fn locale_separators() {
+    assert_eq!(
+        NumberFormat::from_locale("de_DE.UTF-8").int(1_234_567u64),
+        "1.234.567"
+    );
+    assert_eq!(
+        NumberFormat::from_locale("de_DE.UTF-8").float(1.5, 1),
+        "1,5"
+    );
+    assert_eq!(
+        NumberFormat::from_locale("fr_FR").int(1_234u32),
+        "1\u{a0}234"
+    );
+    assert_eq!(NumberFormat::from_locale("C").int(1_234u32), "1,234");
+    assert_eq!(
+        NumberFormat::from_locale("en_US.UTF-8"),
+        NumberFormat::human()
+    );
+}
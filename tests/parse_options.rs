@@ -0,0 +1,243 @@
+mod common;
+
+use common::*;
+use mp4box::registry::{GenericDecoder, Registry};
+use mp4box::{
+    DEFAULT_MAX_DEPTH, ParseOptions, StructuredData, WarningKind, get_boxes, get_boxes_lenient,
+    get_boxes_with_options,
+};
+use std::io::Cursor;
+
+fn nested() -> Vec<u8> {
+    let stbl = container(b"stbl", &[stsz(&[1, 2, 3]), stco(&[0])]);
+    container(
+        b"moov",
+        &[
+            mvhd(1000, 0),
+            container(
+                b"trak",
+                &[container(b"mdia", &[container(b"minf", &[stbl])])],
+            ),
+        ],
+    )
+}
+
+fn depth(b: &mp4box::Box) -> usize {
+    1 + b.children.iter().flatten().map(depth).max().unwrap_or(0)
+}
+
+#[test]
+fn depth_and_box_limits() {
+    let data = nested();
+    let parse = |options: &ParseOptions| {
+        get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, options)
+    };
+
+    let (boxes, warnings) = parse(&ParseOptions::new().lenient(true).max_depth(2)).unwrap();
+    // moov > trak > mdia, whose children are not parsed
+    assert_eq!(depth(&boxes[0]), 3);
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::LimitExceeded { limit: "max_depth" }
+    );
+    // Strict parsing cuts the tree the same way
+    let (boxes, _) = parse(&ParseOptions::new().max_depth(2)).unwrap();
+    assert_eq!(depth(&boxes[0]), 3);
+
+    let (boxes, warnings) = parse(&ParseOptions::new().lenient(true).max_boxes(3)).unwrap();
+    let kids: Vec<_> = boxes[0]
+        .children
+        .iter()
+        .flatten()
+        .map(|b| b.typ.as_str())
+        .collect();
+    assert_eq!(kids, ["mvhd", "trak"]);
+    assert!(
+        boxes[0].children.as_ref().unwrap()[1]
+            .children
+            .as_ref()
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(warnings.len(), 1);
+    assert!(parse(&ParseOptions::new().max_boxes(3)).is_err());
+}
+
+#[test]
+fn table_limit_and_decode_switches() {
+    let data = nested();
+    let options = ParseOptions::new().max_table_entries(2).media_ranges(false);
+    let (boxes, warnings) =
+        get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, &options).unwrap();
+
+    let stbl = &boxes[0].children.as_ref().unwrap()[1]
+        .children
+        .as_ref()
+        .unwrap()[0]
+        .children
+        .as_ref()
+        .unwrap()[0]
+        .children
+        .as_ref()
+        .unwrap()[0];
+    let stbl_kids = stbl.children.as_ref().unwrap();
+    match &stbl_kids[0].structured_data {
+        Some(StructuredData::SampleSize(stsz)) => assert_eq!(stsz.sample_sizes, vec![1, 2]),
+        other => panic!("expected stsz, got {other:?}"),
+    }
+    assert!(stbl_kids[1].structured_data.is_some());
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].typ.as_deref(), Some("stsz"));
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::EntriesClamped {
+            declared: 3,
+            decoded: 2
+        }
+    );
+
+    let options = ParseOptions::new().decode(false);
+    let (boxes, _) =
+        get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, &options).unwrap();
+    assert!(boxes[0].children.as_ref().unwrap()[0].decoded.is_none());
+}
+
+#[test]
+fn hostile_entry_count_is_clamped_to_payload() {
+    // stco claiming 4G entries with room for two
+    let mut payload = 0xFFFF_FFFFu32.to_be_bytes().to_vec();
+    payload.extend_from_slice(&[0, 0, 0, 8, 0, 0, 0, 16]);
+    let data = full_box(b"stco", 0, 0, &payload);
+
+    let options = ParseOptions::new();
+    let (boxes, warnings) =
+        get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, &options).unwrap();
+    match &boxes[0].structured_data {
+        Some(StructuredData::ChunkOffset(stco)) => assert_eq!(stco.chunk_offsets, vec![8, 16]),
+        other => panic!("expected stco, got {other:?}"),
+    }
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::EntriesClamped {
+            declared: 0xFFFF_FFFF,
+            decoded: 2
+        }
+    );
+
+    // Plain get_boxes decodes the same entries without reporting
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    assert!(boxes[0].structured_data.is_some());
+}
+
+#[test]
+fn deep_nesting_becomes_leaf() {
+    let mut data = boxed(b"free", &[]);
+    for _ in 0..10_000 {
+        data = container(b"udta", &[data]);
+    }
+
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap();
+    assert_eq!(depth(&boxes[0]), DEFAULT_MAX_DEPTH + 1);
+
+    let (boxes, warnings) = get_boxes_lenient(&mut Cursor::new(&data), data.len() as u64, false);
+    assert_eq!(depth(&boxes[0]), DEFAULT_MAX_DEPTH + 1);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::LimitExceeded { limit: "max_depth" }
+    );
+    assert_eq!(warnings[0].typ.as_deref(), Some("udta"));
+}
+
+#[test]
+fn oversized_child_is_cut_at_parent_end() {
+    // udta claims 16 bytes more than moov holds, which would reach into free
+    let mut udta = boxed(b"udta", &[]);
+    udta[..4].copy_from_slice(&24u32.to_be_bytes());
+    let moov = container(b"moov", &[mvhd(1000, 0), udta]);
+    let udta_at = moov.len() as u64 - 8;
+    let data = [moov, boxed(b"free", &[])].concat();
+
+    let (boxes, warnings) = get_boxes_with_options(
+        &mut Cursor::new(&data),
+        data.len() as u64,
+        &ParseOptions::new(),
+    )
+    .unwrap();
+    let top: Vec<_> = boxes.iter().map(|b| b.typ.as_str()).collect();
+    assert_eq!(top, ["moov", "free"]);
+    let udta = &boxes[0].children.as_ref().unwrap()[1];
+    assert_eq!(udta.typ, "udta");
+    assert!(udta.children.as_ref().unwrap().is_empty());
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].offset, udta_at);
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::Truncated {
+            declared_end: udta_at + 24,
+            available_end: udta_at + 8,
+        }
+    );
+}
+
+#[test]
+fn nested_size_zero_ends_at_parent() {
+    let mut udta = boxed(b"udta", &[]);
+    udta[..4].copy_from_slice(&0u32.to_be_bytes());
+    let moov = container(b"moov", &[mvhd(1000, 0), udta]);
+    let moov_end = moov.len() as u64;
+    let data = [moov, boxed(b"free", &[])].concat();
+
+    let (boxes, warnings) = get_boxes_with_options(
+        &mut Cursor::new(&data),
+        data.len() as u64,
+        &ParseOptions::new(),
+    )
+    .unwrap();
+    let top: Vec<_> = boxes.iter().map(|b| b.typ.as_str()).collect();
+    assert_eq!(top, ["moov", "free"]);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].typ.as_deref(), Some("udta"));
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::NestedSizeZero { end: moov_end }
+    );
+
+    // At the top level size 0 is normal
+    let mut mdat = boxed(b"mdat", &[0; 4]);
+    mdat[..4].copy_from_slice(&0u32.to_be_bytes());
+    let (_, warnings) = get_boxes_with_options(
+        &mut Cursor::new(&mdat),
+        mdat.len() as u64,
+        &ParseOptions::new(),
+    )
+    .unwrap();
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn oversized_leaf_decodes_only_what_its_parent_holds() {
+    // A udta child claiming 40 bytes with 8 present; unbuffered reads go
+    // straight to the file, so only the clamped length keeps free out
+    let mut note = boxed(b"note", b"abcdefgh");
+    note[..4].copy_from_slice(&40u32.to_be_bytes());
+    let moov = container(b"moov", &[container(b"udta", &[note])]);
+    let data = [moov, boxed(b"free", b"SIBLINGDATA_SHOULD_NOT_SHOW")].concat();
+
+    for buffer_limit in [0, u64::MAX] {
+        let options = ParseOptions::new()
+            .lenient(true)
+            .buffer_limit(buffer_limit)
+            .registry(Registry::new().with_catch_all("generic", Box::new(GenericDecoder)));
+        let (boxes, warnings) =
+            get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, &options).unwrap();
+        let note = &boxes[0].children.as_ref().unwrap()[0]
+            .children
+            .as_ref()
+            .unwrap()[0];
+        assert_eq!(note.payload_size, Some(8));
+        assert_eq!(note.decoded.as_deref(), Some("abcdefgh"));
+        assert!(matches!(warnings[0].kind, WarningKind::Truncated { .. }));
+    }
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::*;
+use mp4box::metadata::ImageFormat;
+use mp4box::stream::get_boxes_streaming;
+use mp4box::{ParseOptions, PayloadFormat, classify_payload, get_boxes_with_options};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+/// Remembers the furthest byte read.
+struct Furthest<R> {
+    inner: R,
+    end: u64,
+}
+
+impl<R: Read + Seek> Read for Furthest<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.end = self.end.max(self.inner.stream_position()?);
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Furthest<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+fn length_prefixed_avc() -> Vec<u8> {
+    // SPS, then a 3-byte slice
+    [
+        &[0, 0, 0, 4, 0x67, 0x64, 0x00, 0x1f][..],
+        &[0, 0, 0, 3, 0x65, 0x88, 0x84],
+    ]
+    .concat()
+}
+
+#[test]
+fn classify_common_payloads() {
+    let annex_b = [0, 0, 0, 1, 0x67, 0x64, 0x00, 0x1f];
+    assert_eq!(classify_payload(&annex_b, 100), PayloadFormat::AnnexB);
+    assert_eq!(
+        classify_payload(&[0, 0, 1, 0x65], 100),
+        PayloadFormat::AnnexB
+    );
+    assert_eq!(
+        classify_payload(&length_prefixed_avc(), 15),
+        PayloadFormat::LengthPrefixed { length_size: 4 }
+    );
+    assert_eq!(
+        classify_payload(&[0xFF, 0xD8, 0xFF, 0xE0], 100),
+        PayloadFormat::Image(ImageFormat::Jpeg)
+    );
+    assert_eq!(
+        classify_payload(&[0xFF, 0xF1, 0x50, 0x80], 100),
+        PayloadFormat::Adts
+    );
+    // A length running past the payload is not a NAL unit
+    assert_eq!(
+        classify_payload(&[0, 0, 2, 0, 0x65], 100),
+        PayloadFormat::Unknown
+    );
+    assert_eq!(classify_payload(b"BMxx", 100), PayloadFormat::Unknown);
+}
+
+#[test]
+fn peek_reads_only_the_head_of_mdat() {
+    let payload = [length_prefixed_avc(), vec![0xAB; 4096]].concat();
+    let moov = container(b"moov", &[mvhd(1000, 0)]);
+    let mdat_at = moov.len() as u64;
+    // Size 0: the mdat runs to the end of the file
+    let mdat = [&[0, 0, 0, 0][..], b"mdat", &payload].concat();
+    let data = [moov, mdat].concat();
+    let mut r = Furthest {
+        inner: Cursor::new(&data),
+        end: 0,
+    };
+
+    let options = ParseOptions::new().mdat_peek(8);
+    let (boxes, _) = get_boxes_with_options(&mut r, data.len() as u64, &options).unwrap();
+
+    let peek = boxes[1].peek.as_ref().unwrap();
+    assert_eq!(peek.hex, "000000046764001f");
+    assert_eq!(
+        peek.format,
+        PayloadFormat::LengthPrefixed { length_size: 4 }
+    );
+    assert_eq!(r.end, mdat_at + 8 + 8);
+    assert!(boxes[0].peek.is_none());
+}
+
+#[test]
+fn peek_is_off_by_default_and_works_on_streams() {
+    let data = [
+        ftyp(b"isom", &[b"isom"]),
+        boxed(b"mdat", &[0, 0, 0, 1, 0x09, 0xF0, 0, 0, 0, 1]),
+    ]
+    .concat();
+
+    let (boxes, _) = get_boxes_with_options(
+        &mut Cursor::new(&data),
+        data.len() as u64,
+        &ParseOptions::new(),
+    )
+    .unwrap();
+    assert!(boxes[1].peek.is_none());
+
+    let options = ParseOptions::new().mdat_peek(64);
+    let (boxes, _) = get_boxes_streaming(&data[..], &options).unwrap();
+    let peek = boxes[1].peek.as_ref().unwrap();
+    assert_eq!(peek.hex, "0000000109f000000001");
+    assert_eq!(peek.format, PayloadFormat::AnnexB);
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use common::*;
+use mp4box::registry::{PIFF_SENC_UUID, PIFF_TFRF_UUID, PIFF_TFXD_UUID, StructuredData};
+
+fn piff_box(uuid: &[u8; 16], version: u8, flags: u32, payload: &[u8]) -> Vec<u8> {
+    let mut p = uuid.to_vec();
+    p.push(version);
+    p.extend_from_slice(&flags.to_be_bytes()[1..]);
+    p.extend_from_slice(payload);
+    boxed(b"uuid", &p)
+}
+
+#[test]
+fn tfxd_and_tfrf() {
+    let mut tfxd = 20_000_000u64.to_be_bytes().to_vec();
+    tfxd.extend_from_slice(&20_000_000u64.to_be_bytes());
+
+    let mut tfrf = vec![2u8];
+    for (t, d) in [(40_000_000u64, 20_000_000u64), (60_000_000, 20_000_000)] {
+        tfrf.extend_from_slice(&t.to_be_bytes());
+        tfrf.extend_from_slice(&d.to_be_bytes());
+    }
+
+    let traf = container(
+        b"traf",
+        &[
+            piff_box(&PIFF_TFXD_UUID, 1, 0, &tfxd),
+            piff_box(&PIFF_TFRF_UUID, 1, 0, &tfrf),
+        ],
+    );
+    let boxes = decode(traf);
+    let kids = boxes[0].children.as_ref().unwrap();
+
+    match kids[0].structured_data.as_ref() {
+        Some(StructuredData::PiffFragmentTime(t)) => {
+            assert_eq!(t.absolute_time, 20_000_000);
+            assert_eq!(t.duration, 20_000_000);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match kids[1].structured_data.as_ref() {
+        Some(StructuredData::PiffFragmentReference(r)) => {
+            assert_eq!(r.fragments.len(), 2);
+            assert_eq!(r.fragments[1].absolute_time, 60_000_000);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn sample_encryption_with_subsamples() {
+    let mut p = 2u32.to_be_bytes().to_vec();
+    for iv in [[1u8; 8], [2u8; 8]] {
+        p.extend_from_slice(&iv);
+        p.extend_from_slice(&1u16.to_be_bytes());
+        p.extend_from_slice(&5u16.to_be_bytes());
+        p.extend_from_slice(&100u32.to_be_bytes());
+    }
+    let boxes = decode(piff_box(&PIFF_SENC_UUID, 0, 2, &p));
+
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::PiffSampleEncryption(s)) => {
+            assert_eq!(s.sample_count, 2);
+            assert_eq!(s.iv_size, Some(8));
+            assert_eq!(s.kid, None);
+            assert_eq!(s.samples[1].iv, "0202020202020202");
+            assert_eq!(s.samples[0].subsamples[0].clear_bytes, 5);
+            assert_eq!(s.samples[0].subsamples[0].protected_bytes, 100);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn sample_encryption_with_override() {
+    let mut p = vec![0, 0, 1, 16]; // AES-CTR, 16-byte IVs
+    p.extend_from_slice(&[0xAB; 16]);
+    p.extend_from_slice(&1u32.to_be_bytes());
+    p.extend_from_slice(&[7u8; 16]);
+    let boxes = decode(piff_box(&PIFF_SENC_UUID, 0, 1, &p));
+
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::PiffSampleEncryption(s)) => {
+            assert_eq!(s.algorithm_id, Some(1));
+            assert_eq!(s.iv_size, Some(16));
+            assert_eq!(s.kid.as_deref(), Some("abababababababababababababababab"));
+            assert_eq!(s.samples.len(), 1);
+            assert!(s.samples[0].subsamples.is_empty());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,202 @@
+mod common;
+
+use common::*;
+use mp4box::boxes::{BoxKey, FourCC};
+use mp4box::get_boxes;
+use mp4box::parser::parse_children;
+use mp4box::query::{find_all, select, select_paths};
+use std::io::Cursor;
+
+const UUID: [u8; 16] = [0xab; 16];
+
+fn trak(id: u32, handler: &[u8; 4]) -> Vec<u8> {
+    let stbl = container(b"stbl", &[stsz(&[id])]);
+    container(
+        b"trak",
+        &[
+            tkhd(id, 1000, 0, 0, 0),
+            container(b"mdia", &[hdlr(handler, ""), container(b"minf", &[stbl])]),
+        ],
+    )
+}
+
+fn movie() -> Vec<u8> {
+    [
+        ftyp(b"isom", &[b"isom"]),
+        container(
+            b"moov",
+            &[mvhd(1000, 1000), trak(1, b"vide"), trak(2, b"soun")],
+        ),
+        boxed(b"\xa9xyz", b""),
+        boxed(b"\x00\x01\x02\x03", b""),
+        boxed(b"uuid", &[&UUID[..], b"payload"].concat()),
+    ]
+    .concat()
+}
+
+fn boxes() -> Vec<mp4box::Box> {
+    let data = movie();
+    get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap()
+}
+
+#[test]
+fn paths_select_every_match_in_file_order() {
+    let boxes = boxes();
+    let stbl = select_paths(&boxes, "moov.trak.mdia.minf.stbl");
+    let paths: Vec<&str> = stbl.iter().map(|s| s.path.as_str()).collect();
+    assert_eq!(
+        paths,
+        ["moov.trak[0].mdia.minf.stbl", "moov.trak[1].mdia.minf.stbl",]
+    );
+    assert!(stbl[0].node.offset < stbl[1].node.offset);
+}
+
+#[test]
+fn indices_pick_one_box_per_parent() {
+    let boxes = boxes();
+    let second = select(&boxes, "moov.trak[1].tkhd");
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0].offset, select(&boxes, "moov.trak.tkhd")[1].offset);
+
+    assert!(select(&boxes, "moov.trak[2]").is_empty());
+    assert!(select(&boxes, "moov.mdia").is_empty());
+    assert!(select(&boxes, "").is_empty());
+    // A malformed index is ignored
+    assert_eq!(select(&boxes, "moov.trak[x]").len(), 2);
+}
+
+#[test]
+fn raw_types_and_raw_trees() {
+    let boxes = boxes();
+    let copyright = select_paths(&boxes, "\u{a9}xyz");
+    assert_eq!(copyright.len(), 1);
+    let unprintable = select_paths(&boxes, "0x00010203");
+    assert_eq!(unprintable[0].path, "0x00010203");
+
+    let data = movie();
+    let raw = parse_children(&mut Cursor::new(&data), data.len() as u64).unwrap();
+    let found = select_paths(&raw, "moov.trak[0].mdia.minf.stbl.stsz");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].path, "moov.trak[0].mdia.minf.stbl.stsz");
+    assert_eq!(&found[0].node.hdr.typ.0, b"stsz");
+}
+
+fn paths(selected: Vec<mp4box::query::Selection<'_, mp4box::Box>>) -> Vec<String> {
+    selected.into_iter().map(|s| s.path).collect()
+}
+
+#[test]
+fn wildcards_match_any_type_and_any_depth() {
+    let boxes = boxes();
+    assert_eq!(
+        paths(select_paths(&boxes, "moov.*")),
+        ["moov.mvhd", "moov.trak[0]", "moov.trak[1]"]
+    );
+    assert_eq!(
+        paths(select_paths(&boxes, "**.stsz")),
+        [
+            "moov.trak[0].mdia.minf.stbl.stsz",
+            "moov.trak[1].mdia.minf.stbl.stsz",
+        ]
+    );
+    // ** may match no levels at all
+    assert_eq!(paths(select_paths(&boxes, "**.moov")), ["moov"]);
+    assert_eq!(select(&boxes, "moov.**.**.hdlr").len(), 2);
+    // A trailing ** is everything below
+    assert_eq!(select(&boxes, "moov.trak[1].**").len(), 6);
+    assert_eq!(select(&boxes, "*.*.*").len(), 4);
+}
+
+#[test]
+fn uuid_selectors() {
+    let boxes = boxes();
+    let hex = "ab".repeat(16);
+    let found = select_paths(&boxes, &format!("uuid:{hex}"));
+    assert_eq!(paths(found), [format!("uuid:{hex}")]);
+    assert!(select(&boxes, &format!("uuid:{}", "cd".repeat(16))).is_empty());
+    assert!(select(&boxes, "uuid:zz").is_empty());
+    assert_eq!(select(&boxes, "uuid").len(), 1);
+}
+
+#[test]
+fn attribute_filters() {
+    let boxes = boxes();
+    assert_eq!(
+        paths(select_paths(&boxes, "moov.trak[@handler=soun].mdia")),
+        ["moov.trak[1].mdia"]
+    );
+    assert_eq!(
+        paths(select_paths(&boxes, "moov.trak[@handler!=soun]")),
+        ["moov.trak[0]"]
+    );
+    assert_eq!(
+        paths(select_paths(&boxes, "**.trak[@track_id=2].tkhd")),
+        ["moov.trak[1].tkhd"]
+    );
+    // Filters apply in order: the first of the non-video tracks
+    assert_eq!(
+        paths(select_paths(&boxes, "moov.trak[@handler!=vide][0]")),
+        ["moov.trak[1]"]
+    );
+    assert!(select(&boxes, "moov.trak[0][@handler=soun]").is_empty());
+    // Numbers compare by value; missing attributes never match
+    assert_eq!(select(&boxes, "**.hdlr[@version=0x0]").len(), 2);
+    assert!(select(&boxes, "**[@nonesuch=1]").is_empty());
+}
+
+#[test]
+fn raw_trees_filter_on_header_attributes() {
+    let data = movie();
+    let raw = parse_children(&mut Cursor::new(&data), data.len() as u64).unwrap();
+    let ftyp_len = ftyp(b"isom", &[b"isom"]).len();
+    let moov = select(&raw, &format!("*[@offset={ftyp_len}]"));
+    assert_eq!(moov.len(), 1);
+    assert_eq!(&moov[0].hdr.typ.0, b"moov");
+    // mvhd, hdlr and stsz; tkhd has the enabled flags set
+    assert_eq!(select(&raw, "**[@flags=0]").len(), 5);
+    // Decoded attributes are not available on raw trees
+    assert!(select(&raw, "moov.trak[@handler=soun]").is_empty());
+    let uuid = select_paths(&raw, &format!("uuid:{}", "ab".repeat(16)));
+    assert_eq!(uuid.len(), 1);
+}
+
+#[test]
+fn find_all_searches_the_whole_tree() {
+    let boxes = boxes();
+    let stsz = find_all(&boxes, b"stsz");
+    assert_eq!(stsz.len(), 2);
+    assert_eq!(stsz[1].0.as_str(), "moov.trak[1].mdia.minf.stbl.stsz");
+    assert_eq!(stsz[1].0.depth(), 5);
+    // Paths resolve back to the box they came with
+    for (path, b) in &stsz {
+        assert_eq!(select(&boxes, path.as_str())[0].offset, b.offset);
+    }
+
+    let top = find_all(&boxes, FourCC(*b"moov"));
+    assert_eq!(top[0].0.to_string(), "moov");
+    assert_eq!(top[0].0.depth(), 0);
+    assert!(find_all(&boxes, b"pssh").is_empty());
+    assert_eq!(find_all(&boxes, FourCC([0, 1, 2, 3])).len(), 1);
+
+    let uuid = find_all(&boxes, BoxKey::Uuid(UUID));
+    assert_eq!(uuid.len(), 1);
+    assert_eq!(uuid[0].0.as_str(), format!("uuid:{}", "ab".repeat(16)));
+    assert!(find_all(&boxes, BoxKey::Uuid([0; 16])).is_empty());
+
+    let data = movie();
+    let raw = parse_children(&mut Cursor::new(&data), data.len() as u64).unwrap();
+    assert_eq!(find_all(&raw, b"hdlr").len(), 2);
+}
+
+#[test]
+fn long_sibling_lists_are_indexed() {
+    // A long recording: many thousand top-level fragments
+    let data = [boxed(b"moof", &[]), boxed(b"mdat", &[])]
+        .concat()
+        .repeat(20_000);
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap();
+
+    let mdats = find_all(&boxes, b"mdat");
+    assert_eq!(mdats.len(), 20_000);
+    assert_eq!(mdats[19_999].0.as_str(), "mdat[19999]");
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::metadata::quicktime_metadata;
+use mp4box::registry::{StructuredData, TagValue};
+use std::io::Cursor;
+
+fn keys(names: &[&str]) -> Vec<u8> {
+    let mut p = (names.len() as u32).to_be_bytes().to_vec();
+    for n in names {
+        p.extend_from_slice(&(8 + n.len() as u32).to_be_bytes());
+        p.extend_from_slice(b"mdta");
+        p.extend_from_slice(n.as_bytes());
+    }
+    full_box(b"keys", 0, 0, &p)
+}
+
+fn indexed_item(index: u32, value: Vec<u8>) -> Vec<u8> {
+    container(&index.to_be_bytes(), &[value])
+}
+
+#[test]
+fn mdta_items_join_their_keys() {
+    let meta = container(
+        b"meta",
+        &[
+            hdlr(b"mdta", ""),
+            keys(&[
+                "com.apple.quicktime.make",
+                "com.apple.quicktime.model",
+                "com.apple.quicktime.location.ISO6709",
+            ]),
+            container(
+                b"ilst",
+                &[
+                    indexed_item(1, data_atom(1, b"Apple")),
+                    indexed_item(2, data_atom(1, b"iPhone 15 Pro")),
+                    indexed_item(3, data_atom(1, b"+37.3349-122.0090+030.000/")),
+                    indexed_item(9, data_atom(21, &[42])),
+                ],
+            ),
+        ],
+    );
+    let mut data = container(b"moov", &[meta]);
+    let len = data.len() as u64;
+    let mut cursor = Cursor::new(std::mem::take(&mut data));
+    let boxes = get_boxes(&mut cursor, len, true).unwrap();
+
+    let meta = &boxes[0].children.as_ref().unwrap()[0];
+    match meta.children.as_ref().unwrap()[1].structured_data.as_ref() {
+        Some(StructuredData::MetadataKeys(k)) => {
+            assert_eq!(k.entries.len(), 3);
+            assert_eq!(k.entries[0].namespace, "mdta");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let tags = quicktime_metadata(&mut cursor, &boxes).unwrap();
+    assert_eq!(
+        tags["com.apple.quicktime.make"],
+        vec![TagValue::Text("Apple".into())]
+    );
+    assert_eq!(
+        tags["com.apple.quicktime.model"],
+        vec![TagValue::Text("iPhone 15 Pro".into())]
+    );
+    assert_eq!(
+        tags["com.apple.quicktime.location.ISO6709"],
+        vec![TagValue::Text("+37.3349-122.0090+030.000/".into())]
+    );
+    assert_eq!(tags["#9"], vec![TagValue::Integer(42)]);
+}
+
+fn intl_text(records: &[(u16, &[u8])]) -> Vec<u8> {
+    let mut p = Vec::new();
+    for (lang, text) in records {
+        p.extend_from_slice(&(text.len() as u16).to_be_bytes());
+        p.extend_from_slice(&lang.to_be_bytes());
+        p.extend_from_slice(text);
+    }
+    p
+}
+
+#[test]
+fn udta_text_atoms_are_decoded() {
+    let udta = container(
+        b"udta",
+        &[
+            boxed(b"\xA9too", &intl_text(&[(0x55C4, b"Lavf60.3.100")])),
+            boxed(
+                b"\xA9cmt",
+                &intl_text(&[(0, b"hello"), (0x15C7, b"\xFE\xFF\x00h\x00i")]),
+            ),
+        ],
+    );
+    let data = container(b"moov", &[udta]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    let atoms = boxes[0].children.as_ref().unwrap()[0]
+        .children
+        .as_ref()
+        .unwrap();
+    assert_eq!(atoms[0].typ, "©too");
+    assert_eq!(atoms[0].full_name, "Encoding Tool");
+    let text = |b: &mp4box::Box| match &b.structured_data {
+        Some(StructuredData::UserDataText(t)) => t.entries.clone(),
+        other => panic!("unexpected {:?}", other),
+    };
+
+    let too = text(&atoms[0]);
+    assert_eq!(too[0].text, "Lavf60.3.100");
+    assert_eq!(too[0].language.as_deref(), Some("und"));
+
+    let cmt = text(&atoms[1]);
+    assert_eq!(
+        (cmt[0].mac_language, cmt[0].text.as_str()),
+        (Some(0), "hello")
+    );
+    assert_eq!(
+        (cmt[1].language.as_deref(), cmt[1].text.as_str()),
+        (Some("eng"), "hi")
+    );
+}
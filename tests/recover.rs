@@ -0,0 +1,89 @@
+mod common;
+
+use common::*;
+use mp4box::{ParseOptions, WarningKind, get_boxes_lenient, get_boxes_with_options};
+use std::io::Cursor;
+
+fn damaged() -> (Vec<u8>, u64) {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let garbage = vec![0xff; 13];
+    let moov = container(b"moov", &[mvhd(1000, 0)]);
+    let gap_at = ftyp.len() as u64;
+    (
+        [ftyp, garbage, boxed(b"free", &[0; 4]), moov].concat(),
+        gap_at,
+    )
+}
+
+#[test]
+fn resumes_after_garbage() {
+    let (data, gap_at) = damaged();
+    let options = ParseOptions::new().recover(true);
+    let (boxes, warnings) =
+        get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, &options).unwrap();
+
+    let types: Vec<_> = boxes
+        .iter()
+        .map(|b| (b.typ.as_str(), b.kind.as_str()))
+        .collect();
+    assert_eq!(
+        types,
+        [
+            ("ftyp", "leaf"),
+            ("gap ", "gap"),
+            ("free", "leaf"),
+            ("moov", "container")
+        ]
+    );
+    assert_eq!((boxes[1].offset, boxes[1].size), (gap_at, 13));
+    assert_eq!(boxes[1].payload_size, Some(13));
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::Resynchronized {
+            message: "implausible box type".into(),
+            resumed_at: gap_at + 13,
+        }
+    );
+    assert_eq!(warnings[0].offset, gap_at);
+}
+
+#[test]
+fn bad_size_inside_container() {
+    let bad = [3u32.to_be_bytes().to_vec(), b"junk".to_vec()].concat();
+    let moov = container(b"moov", &[bad, boxed(b"udta", &[0; 4]), mvhd(1000, 0)]);
+    let options = ParseOptions::new().lenient(true).recover(true);
+    let (boxes, warnings) =
+        get_boxes_with_options(&mut Cursor::new(&moov), moov.len() as u64, &options).unwrap();
+
+    let kids: Vec<_> = boxes[0]
+        .children
+        .iter()
+        .flatten()
+        .map(|b| b.typ.as_str())
+        .collect();
+    assert_eq!(kids, ["gap ", "udta", "mvhd"]);
+    assert!(matches!(
+        &warnings[0].kind,
+        WarningKind::Resynchronized { message, resumed_at: 16 } if message == "invalid box size"
+    ));
+}
+
+#[test]
+fn without_recovery_the_rest_is_skipped() {
+    let (data, _) = damaged();
+    let (boxes, warnings) = get_boxes_lenient(&mut Cursor::new(&data), data.len() as u64, false);
+    // 0xffffffff claims more than the file has and is cut instead
+    assert_eq!(boxes.len(), 2);
+    assert!(!warnings.is_empty());
+}
+
+#[test]
+fn trailing_garbage_becomes_one_gap() {
+    let ftyp = ftyp(b"isom", &[b"isom"]);
+    let data = [ftyp.clone(), vec![0; 5]].concat();
+    let options = ParseOptions::new().recover(true);
+    let (boxes, _) =
+        get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, &options).unwrap();
+    assert_eq!(boxes.len(), 2);
+    assert_eq!((boxes[1].offset, boxes[1].size), (ftyp.len() as u64, 5));
+}
@@ -462,6 +462,199 @@ mod tests {
                 assert_eq!(entry.data_reference_index, 1); // Default value
                 assert_eq!(entry.width, None); // Audio entries don't have width/height
                 assert_eq!(entry.height, None);
+                assert_eq!(entry.channel_count, Some(2));
+                assert_eq!(entry.sample_size, Some(16));
+                assert_eq!(entry.sample_rate, Some(44100));
+            }
+            _ => panic!("Expected structured STSD data"),
+        }
+    }
+
+    #[test]
+    fn test_stsd_quicktime_v2_sound_description() {
+        let mut entry = vec![0, 0, 0, 0];
+        entry.extend_from_slice(b"alac");
+        entry.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]); // reserved, dri
+        entry.extend_from_slice(&[0, 2, 0, 0, 0, 0, 0, 0]); // version 2
+        entry.extend_from_slice(&[0, 3, 0, 16, 0xFF, 0xFE, 0, 0]);
+        entry.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 72]);
+        entry.extend_from_slice(&96000f64.to_be_bytes());
+        entry.extend_from_slice(&6u32.to_be_bytes());
+        entry.extend_from_slice(&0x7F00_0000u32.to_be_bytes());
+        entry.extend_from_slice(&24u32.to_be_bytes());
+        entry.extend_from_slice(&[0; 12]);
+        let size = entry.len() as u32;
+        entry[..4].copy_from_slice(&size.to_be_bytes());
+
+        let mut payload = 1u32.to_be_bytes().to_vec();
+        payload.extend_from_slice(&entry);
+        let header = BoxHeader {
+            typ: FourCC(*b"stsd"),
+            uuid: None,
+            size: 12 + payload.len() as u64,
+            header_size: 8,
+            start: 0,
+        };
+
+        let result = default_registry()
+            .decode(
+                &BoxKey::FourCC(FourCC(*b"stsd")),
+                &mut Cursor::new(payload),
+                &header,
+                Some(0),
+                Some(0),
+            )
+            .unwrap()
+            .unwrap();
+
+        match result {
+            BoxValue::Structured(StructuredData::SampleDescription(stsd)) => {
+                let entry = &stsd.entries[0];
+                assert_eq!(entry.codec, "alac");
+                assert_eq!(entry.channel_count, Some(6));
+                assert_eq!(entry.sample_size, Some(24));
+                assert_eq!(entry.sample_rate, Some(96000));
+            }
+            _ => panic!("Expected structured STSD data"),
+        }
+    }
+
+    fn tkhd_payload(version: u8) -> Vec<u8> {
+        // Without version/flags: times, track_id, reserved, duration
+        let mut p = Vec::new();
+        if version == 1 {
+            p.extend_from_slice(&[0; 16]);
+            p.extend_from_slice(&7u32.to_be_bytes());
+            p.extend_from_slice(&[0; 4]);
+            p.extend_from_slice(&(1u64 << 33).to_be_bytes());
+        } else {
+            p.extend_from_slice(&[0; 8]);
+            p.extend_from_slice(&7u32.to_be_bytes());
+            p.extend_from_slice(&[0; 4]);
+            p.extend_from_slice(&9000u32.to_be_bytes());
+        }
+        // reserved[2], layer, alternate_group, volume, reserved, matrix
+        p.extend_from_slice(&[0; 8]);
+        p.extend_from_slice(&[0, 0, 0, 2, 1, 0, 0, 0]);
+        p.extend_from_slice(&[0; 36]);
+        p.extend_from_slice(&(1280u32 << 16).to_be_bytes());
+        p.extend_from_slice(&(720u32 << 16).to_be_bytes());
+        p
+    }
+
+    fn decode_tkhd(payload: Vec<u8>, version: u8) -> BoxValue {
+        let header = BoxHeader {
+            typ: FourCC(*b"tkhd"),
+            uuid: None,
+            size: 12 + payload.len() as u64,
+            header_size: 8,
+            start: 0,
+        };
+        default_registry()
+            .decode(
+                &BoxKey::FourCC(FourCC(*b"tkhd")),
+                &mut Cursor::new(payload),
+                &header,
+                Some(version),
+                Some(3),
+            )
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_tkhd_structured_decoding() {
+        for (version, duration) in [(0, 9000), (1, 1u64 << 33)] {
+            match decode_tkhd(tkhd_payload(version), version) {
+                BoxValue::Structured(StructuredData::TrackHeader(tkhd)) => {
+                    assert_eq!(tkhd.version, version);
+                    assert_eq!(tkhd.flags, 3);
+                    assert_eq!(tkhd.track_id, 7);
+                    assert_eq!(tkhd.duration, duration);
+                    assert_eq!(tkhd.alternate_group, 2);
+                    assert_eq!((tkhd.width, tkhd.height), (1280.0, 720.0));
+                }
+                _ => panic!("Expected structured TKHD data"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_tkhd_short_payload_keeps_leading_fields() {
+        for (version, duration, cut) in [(0, 9000, 20), (1, 1u64 << 33, 32)] {
+            // Cut after duration: no alternate_group, matrix or dimensions
+            let mut payload = tkhd_payload(version);
+            payload.truncate(cut);
+            match decode_tkhd(payload, version) {
+                BoxValue::Structured(StructuredData::TrackHeader(tkhd)) => {
+                    assert_eq!(tkhd.track_id, 7);
+                    assert_eq!(tkhd.duration, duration);
+                    assert_eq!(tkhd.alternate_group, 0);
+                    assert_eq!(tkhd.matrix[8], 0x4000_0000);
+                    assert_eq!((tkhd.width, tkhd.height), (0.0, 0.0));
+                }
+                _ => panic!("Expected structured TKHD data"),
+            }
+        }
+
+        // Not even a track_id
+        match decode_tkhd(vec![0; 10], 0) {
+            BoxValue::Text(text) => assert_eq!(text, "tkhd: payload too short (10 bytes)"),
+            _ => panic!("Expected text"),
+        }
+    }
+
+    #[test]
+    fn test_stsd_multiple_entries() {
+        // Two avc1 entries (e.g. an encrypted and a clear description) of
+        // different sizes, each with its own data_reference_index
+        let avc1 = |size: u32, dri: u16, width: u16| {
+            let mut e = size.to_be_bytes().to_vec();
+            e.extend_from_slice(b"avc1");
+            e.extend_from_slice(&[0; 6]);
+            e.extend_from_slice(&dri.to_be_bytes());
+            e.extend_from_slice(&[0; 16]);
+            e.extend_from_slice(&width.to_be_bytes());
+            e.extend_from_slice(&720u16.to_be_bytes());
+            e.resize(size as usize, 0);
+            e
+        };
+        let mut mock_data = 3u32.to_be_bytes().to_vec();
+        mock_data.extend_from_slice(&avc1(86, 1, 1280));
+        mock_data.extend_from_slice(&avc1(120, 2, 960));
+        // Audio entry that runs to the end of the box (size 0)
+        mock_data.extend_from_slice(&[0, 0, 0, 0, b'm', b'p', b'4', b'a', 0, 0, 0, 0, 0, 0, 0, 3]);
+
+        let header = BoxHeader {
+            typ: FourCC(*b"stsd"),
+            uuid: None,
+            size: 8 + 4 + mock_data.len() as u64,
+            header_size: 8,
+            start: 0,
+        };
+        let result = default_registry()
+            .decode(
+                &BoxKey::FourCC(FourCC(*b"stsd")),
+                &mut Cursor::new(mock_data),
+                &header,
+                Some(0),
+                Some(0),
+            )
+            .unwrap()
+            .unwrap();
+
+        match result {
+            BoxValue::Structured(StructuredData::SampleDescription(stsd_data)) => {
+                assert_eq!(stsd_data.entry_count, 3);
+                assert_eq!(stsd_data.entries.len(), 3);
+
+                let sizes: Vec<_> = stsd_data.entries.iter().map(|e| e.size).collect();
+                assert_eq!(sizes, vec![86, 120, 16]);
+                assert_eq!(stsd_data.entries[0].width, Some(1280));
+                assert_eq!(stsd_data.entries[1].width, Some(960));
+                assert_eq!(stsd_data.entries[1].data_reference_index, 2);
+                assert_eq!(stsd_data.entries[2].codec, "mp4a");
+                assert_eq!(stsd_data.entries[2].data_reference_index, 3);
             }
             _ => panic!("Expected structured STSD data"),
         }
@@ -0,0 +1,95 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::repair::{RepairError, RepairMode, find_truncated, repair_truncated};
+use std::io::Cursor;
+
+/// ftyp + moov + an mdat declaring 1000 payload bytes with 100 written
+fn cut_recording() -> (Vec<u8>, u64) {
+    let mut data = [
+        ftyp(b"isom", &[b"isom"]),
+        container(b"moov", &[mvhd(1000, 0)]),
+    ]
+    .concat();
+    let mdat_at = data.len() as u64;
+    data.extend_from_slice(&1008u32.to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend(vec![0xaa; 100]);
+    (data, mdat_at)
+}
+
+#[test]
+fn fit_size_rewrites_last_box() {
+    let (data, mdat_at) = cut_recording();
+    let mut file = Cursor::new(data);
+
+    let fix = repair_truncated(&mut file, RepairMode::FitSize)
+        .unwrap()
+        .unwrap();
+    assert_eq!(fix.offset, mdat_at);
+    assert_eq!(fix.typ, "mdat");
+    assert_eq!((fix.declared_size, fix.actual_size), (1008, 108));
+    assert_eq!(fix.new_size, 108);
+
+    let data = file.into_inner();
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap();
+    assert_eq!(boxes[2].size, 108);
+    // Nothing left to repair
+    assert!(
+        find_truncated(&mut Cursor::new(&data), RepairMode::FitSize)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn size_to_eof_and_large_sizes() {
+    let (data, mdat_at) = cut_recording();
+    let mut file = Cursor::new(data);
+    let fix = repair_truncated(&mut file, RepairMode::SizeToEof)
+        .unwrap()
+        .unwrap();
+    assert_eq!(fix.new_size, 0);
+    let data = file.into_inner();
+    let at = mdat_at as usize;
+    assert_eq!(&data[at..at + 4], &[0, 0, 0, 0]);
+
+    // 64-bit sizes have no size-to-EOF form and are fitted instead
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    let mdat_at = data.len() as u64;
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&(1u64 << 33).to_be_bytes());
+    data.extend(vec![0; 50]);
+    let mut file = Cursor::new(data);
+    let fix = repair_truncated(&mut file, RepairMode::SizeToEof)
+        .unwrap()
+        .unwrap();
+    assert_eq!((fix.field_offset, fix.new_size), (mdat_at + 8, 66));
+    let data = file.into_inner();
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap();
+    assert_eq!(boxes[1].size, 66);
+}
+
+#[test]
+fn intact_and_unrepairable_files() {
+    let data = [
+        ftyp(b"isom", &[b"isom"]),
+        container(b"moov", &[mvhd(1000, 0)]),
+    ]
+    .concat();
+    assert!(
+        repair_truncated(&mut Cursor::new(data.clone()), RepairMode::FitSize)
+            .unwrap()
+            .is_none()
+    );
+
+    // Cut inside the last header
+    let mut cut = data.clone();
+    cut.extend_from_slice(&[0, 0, 1]);
+    assert!(matches!(
+        find_truncated(&mut Cursor::new(&cut), RepairMode::FitSize),
+        Err(RepairError::HeaderTruncated { offset }) if offset == data.len() as u64
+    ));
+}
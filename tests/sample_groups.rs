@@ -0,0 +1,154 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{SampleGroupEntry, SeigEntry, StructuredData};
+use mp4box::sample_groups::track_sample_groups;
+use std::io::Cursor;
+
+fn sbgp(grouping_type: &[u8; 4], entries: &[(u32, u32)]) -> Vec<u8> {
+    let mut p = grouping_type.to_vec();
+    p.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for (count, index) in entries {
+        p.extend_from_slice(&count.to_be_bytes());
+        p.extend_from_slice(&index.to_be_bytes());
+    }
+    full_box(b"sbgp", 0, 0, &p)
+}
+
+/// Version 1 sgpd with a per-entry description_length.
+fn sgpd_v1(grouping_type: &[u8; 4], entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut p = grouping_type.to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes()); // default_length
+    p.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for e in entries {
+        p.extend_from_slice(&(e.len() as u32).to_be_bytes());
+        p.extend_from_slice(e);
+    }
+    full_box(b"sgpd", 1, 0, &p)
+}
+
+fn roll(distance: i16) -> SampleGroupEntry {
+    SampleGroupEntry::Roll {
+        roll_distance: distance,
+    }
+}
+
+#[test]
+fn sgpd_version_0_uses_fixed_entry_sizes() {
+    let mut p = b"roll".to_vec();
+    p.extend_from_slice(&2u32.to_be_bytes());
+    p.extend_from_slice(&(-1i16).to_be_bytes());
+    p.extend_from_slice(&(-3i16).to_be_bytes());
+    let data = full_box(b"sgpd", 0, 0, &p);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    match &boxes[0].structured_data {
+        Some(StructuredData::SampleGroupDescription(g)) => {
+            assert_eq!(g.grouping_type, "roll");
+            assert_eq!(g.entries, [roll(-1), roll(-3)]);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(
+        boxes[0].decoded.as_deref(),
+        Some("grouping_type=roll entry_count=2")
+    );
+}
+
+#[test]
+fn track_and_fragment_groups_are_joined() {
+    // rap default for every unmapped sample (sgpd version 2)
+    let mut rap = b"rap ".to_vec();
+    rap.extend_from_slice(&1u32.to_be_bytes()); // default_length
+    rap.extend_from_slice(&1u32.to_be_bytes()); // default_sample_description_index
+    rap.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+    rap.push(0x81);
+
+    let stbl = container(
+        b"stbl",
+        &[
+            stsz(&[1, 1, 1, 1]),
+            sgpd_v1(
+                b"roll",
+                &[
+                    (-1i16).to_be_bytes().to_vec(),
+                    (-2i16).to_be_bytes().to_vec(),
+                ],
+            ),
+            full_box(b"sgpd", 2, 0, &rap),
+            sbgp(b"roll", &[(2, 1), (1, 0), (1, 2)]),
+        ],
+    );
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 0, 0, 0, 0),
+            container(b"mdia", &[container(b"minf", &[stbl])]),
+        ],
+    );
+
+    // seig with a constant IV, referenced from the traf's own sgpd
+    let mut seig = vec![0, 0x19, 1, 0];
+    seig.extend_from_slice(&[0xAB; 16]);
+    seig.push(8);
+    seig.extend_from_slice(&[0xCD; 8]);
+    let traf = container(
+        b"traf",
+        &[
+            tfhd(1, None),
+            trun(0, &[1, 1]),
+            sgpd_v1(b"seig", &[seig]),
+            sbgp(b"seig", &[(2, 0x10001)]),
+        ],
+    );
+    let data = [
+        container(b"moov", &[trak]),
+        container(b"moof", &[mfhd(1), traf]),
+    ]
+    .concat();
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    let tracks = track_sample_groups(&boxes);
+    assert_eq!(tracks.len(), 1);
+    let t = &tracks[0];
+    let entries = |i: u32| -> Vec<_> {
+        t.groups_of(i)
+            .into_iter()
+            .map(|m| (m.grouping_type.as_str(), m.entry.clone()))
+            .collect()
+    };
+    let rap = Some(SampleGroupEntry::RandomAccessPoint {
+        num_leading_samples_known: true,
+        num_leading_samples: 1,
+    });
+
+    assert_eq!(
+        entries(0),
+        [("roll", Some(roll(-1))), ("rap ", rap.clone())]
+    );
+    assert_eq!(entries(2), [("rap ", rap.clone())]);
+    assert_eq!(
+        entries(3),
+        [("roll", Some(roll(-2))), ("rap ", rap.clone())]
+    );
+
+    let seig = t.groups_of(4)[0].clone();
+    assert!(seig.fragment_local);
+    assert_eq!(seig.group_description_index, 1);
+    assert_eq!(
+        seig.entry,
+        Some(SampleGroupEntry::Encryption(SeigEntry {
+            crypt_byte_block: 1,
+            skip_byte_block: 9,
+            is_protected: true,
+            per_sample_iv_size: 0,
+            kid: "ab".repeat(16),
+            constant_iv: Some("cd".repeat(8)),
+        }))
+    );
+    assert_eq!(entries(5)[0].0, "seig");
+    assert_eq!(entries(6), [("rap ", rap)]);
+}
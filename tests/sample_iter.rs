@@ -0,0 +1,270 @@
+mod common;
+
+use common::*;
+use mp4box::{SampleIter, get_boxes, tracks};
+use std::io::Cursor;
+
+fn ctts(entries: &[(u32, u32)]) -> Vec<u8> {
+    let mut p = (entries.len() as u32).to_be_bytes().to_vec();
+    for (c, o) in entries {
+        p.extend_from_slice(&c.to_be_bytes());
+        p.extend_from_slice(&o.to_be_bytes());
+    }
+    full_box(b"ctts", 0, 0, &p)
+}
+
+fn co64(offsets: &[u64]) -> Vec<u8> {
+    let mut p = (offsets.len() as u32).to_be_bytes().to_vec();
+    for o in offsets {
+        p.extend_from_slice(&o.to_be_bytes());
+    }
+    full_box(b"co64", 0, 0, &p)
+}
+
+fn trak(id: u32, handler: &[u8; 4], stbl: Vec<u8>) -> Vec<u8> {
+    container(
+        b"trak",
+        &[
+            tkhd(id, 0, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 0, "und"),
+                    hdlr(handler, "Media"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    )
+}
+
+fn movie(stbl: Vec<u8>) -> Vec<u8> {
+    container(b"moov", &[trak(1, b"vide", stbl)])
+}
+
+#[test]
+fn walks_all_tables() {
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(2, 100), (4, 50)]),
+            ctts(&[(1, 200), (2, 0), (3, 100)]),
+            stsc(&[(1, 2, 1), (3, 1, 1)]),
+            stsz(&[10, 20, 30, 40, 50, 60]),
+            co64(&[0x1_0000_0000, 0x2000, 0x3000, 0x4000]),
+            stss(&[1, 5]),
+        ],
+    );
+    let data = movie(stbl);
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    let track = tracks(&boxes)[0];
+
+    let iter = SampleIter::new(&track);
+    assert_eq!(iter.len(), 6);
+    let got: Vec<_> = iter
+        .map(|s| (s.dts, s.pts, s.duration, s.file_offset, s.size, s.is_sync))
+        .collect();
+    assert_eq!(
+        got,
+        [
+            (0, 200, 100, 0x1_0000_0000, 10, true),
+            (100, 100, 100, 0x1_0000_000a, 20, false),
+            (200, 200, 50, 0x2000, 30, false),
+            (250, 350, 50, 0x2000 + 30, 40, false),
+            (300, 400, 50, 0x3000, 50, true),
+            (350, 450, 50, 0x4000, 60, false),
+        ]
+    );
+}
+
+#[test]
+fn matches_track_samples() {
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(3, 40)]),
+            stsc(&[(1, 3, 1)]),
+            stsz(&[5, 6, 7]),
+            stco(&[0x800]),
+        ],
+    );
+    let data = movie(stbl);
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    let track = tracks(&boxes)[0];
+    let lazy: Vec<_> = SampleIter::new(&track)
+        .map(|s| (s.index, s.dts, s.file_offset, s.is_sync))
+        .collect();
+    let tracks = mp4box::track_samples_from_boxes(&boxes).unwrap();
+    let eager: Vec<_> = tracks[0]
+        .samples
+        .iter()
+        .map(|s| (s.index, s.dts, s.file_offset, s.is_sync))
+        .collect();
+    assert_eq!(lazy, eager);
+    assert_eq!(lazy[2], (2, 80, 0x800 + 11, true));
+}
+
+#[test]
+fn compact_sizes_without_stts() {
+    // stz2 with 4-bit fields: sizes 3, 9, 15
+    let stz2 = full_box(b"stz2", 0, 0, &[0, 0, 0, 4, 0, 0, 0, 3, 0x39, 0xF0]);
+    let stbl = container(b"stbl", &[stsc(&[(1, 3, 1)]), stz2, stco(&[0x100])]);
+    let data = movie(stbl);
+    let tracks = mp4box::track_samples_from_reader(Cursor::new(data)).unwrap();
+
+    let got: Vec<_> = tracks[0]
+        .samples
+        .iter()
+        .map(|s| (s.size, s.file_offset, s.duration))
+        .collect();
+    assert_eq!(got, [(3, 0x100, 0), (9, 0x103, 0), (15, 0x10c, 0)]);
+}
+
+#[test]
+fn declared_counts_are_clamped_to_the_tables_and_file() {
+    // Constant 1-byte samples, 0xFFFFFFFF of them, in one huge chunk
+    let huge = full_box(b"stsz", 0, 0, &[0, 0, 0, 1, 0xFF, 0xFF, 0xFF, 0xFF]);
+    let stbl = container(b"stbl", &[stsc(&[(1, u32::MAX, 1)]), huge, stco(&[0])]);
+    let data = movie(stbl);
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    let track = tracks(&boxes)[0];
+    assert_eq!(SampleIter::new(&track).len(), u32::MAX as usize);
+    let iter = SampleIter::new(&track).within_file_size(data.len() as u64);
+    assert_eq!(iter.len(), data.len());
+
+    let samples = mp4box::track_samples_from_reader(Cursor::new(&data)).unwrap();
+    assert_eq!(samples[0].sample_count as usize, data.len());
+
+    // Listed sizes and chunk room both limit the count
+    let short = full_box(b"stsz", 0, 0, &[0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0, 4]);
+    let stbl = container(b"stbl", &[stsc(&[(1, 2, 1)]), short, stco(&[0, 8])]);
+    let data = movie(stbl);
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    assert_eq!(SampleIter::new(&tracks(&boxes)[0]).len(), 1);
+    let stbl = container(b"stbl", &[stsc(&[(1, 2, 1)]), stsz(&[1; 9]), stco(&[0, 8])]);
+    let data = movie(stbl);
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    assert_eq!(SampleIter::new(&tracks(&boxes)[0]).len(), 4);
+}
+
+#[test]
+fn offsets_read_interleaved_chunks() {
+    // mdat at offset 0: video [V0 V1x] audio [a] video [V2yy] audio [bc]
+    let mdat = boxed(b"mdat", b"V0V1xaV2yybc");
+    let video = container(
+        b"stbl",
+        &[
+            stts(&[(3, 100)]),
+            stsc(&[(1, 2, 1), (2, 1, 1)]),
+            stsz(&[2, 3, 4]),
+            stco(&[8, 14]),
+        ],
+    );
+    let audio = container(
+        b"stbl",
+        &[
+            stts(&[(3, 100)]),
+            stsc(&[(1, 1, 1), (2, 2, 1)]),
+            stsz(&[1, 1, 1]),
+            stco(&[13, 18]),
+        ],
+    );
+    let moov = container(b"moov", &[trak(1, b"vide", video), trak(2, b"soun", audio)]);
+    let mut file = Cursor::new([mdat, moov].concat());
+    let tracks = mp4box::track_samples_from_reader(&mut file).unwrap();
+
+    let read = |t: usize| -> Vec<Vec<u8>> {
+        let mut file = file.clone();
+        tracks[t]
+            .samples
+            .iter()
+            .map(|s| s.read_data(&mut file).unwrap())
+            .collect()
+    };
+    assert_eq!(read(0), [&b"V0"[..], b"V1x", b"V2yy"]);
+    assert_eq!(read(1), [&b"a"[..], b"b", b"c"]);
+}
+
+#[test]
+fn read_data_past_the_end_fails() {
+    let stbl = container(
+        b"stbl",
+        &[stsc(&[(1, 1, 1)]), stsz(&[u32::MAX]), stco(&[4])],
+    );
+    let data = movie(stbl);
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    let sample = SampleIter::new(&tracks(&boxes)[0]).next().unwrap();
+
+    let err = sample.read_data(&mut Cursor::new(&data)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+fn sdtp(depends_on: &[u8]) -> Vec<u8> {
+    let p: Vec<u8> = depends_on.iter().map(|d| d << 4).collect();
+    full_box(b"sdtp", 0, 0, &p)
+}
+
+#[test]
+fn sync_falls_back_from_stss_to_sdtp() {
+    use mp4box::SyncSource::*;
+
+    let table = |extra: Vec<u8>| {
+        let stbl = container(
+            b"stbl",
+            &[
+                stts(&[(3, 100)]),
+                stsc(&[(1, 3, 1)]),
+                stsz(&[1, 1, 1]),
+                stco(&[0x100]),
+                extra,
+            ],
+        );
+        let tracks = mp4box::track_samples_from_reader(Cursor::new(movie(stbl))).unwrap();
+        tracks[0]
+            .samples
+            .iter()
+            .map(|s| (s.is_sync, s.sync_source))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        table(stss(&[2])),
+        [(false, Stss), (true, Stss), (false, Stss)]
+    );
+    // Unknown dependency (0) leaves the default
+    assert_eq!(
+        table(sdtp(&[2, 1, 0])),
+        [(true, Sdtp), (false, Sdtp), (true, Default)]
+    );
+    assert_eq!(table(boxed(b"free", &[])), [(true, Default); 3]);
+}
+
+#[test]
+fn fragment_without_flags_uses_traf_sdtp() {
+    // No trex and no tfhd default flags: sample_flags are missing
+    let build_moof = |data_offset: i32| {
+        let traf = container(
+            b"traf",
+            &[tfhd(1, None), trun(data_offset, &[4, 4]), sdtp(&[2, 1])],
+        );
+        container(b"moof", &[mfhd(1), traf])
+    };
+    let moov = movie(container(b"stbl", &[stsz(&[])]));
+    let moof = build_moof(build_moof(0).len() as i32 + 8);
+    let mdat = boxed(b"mdat", &[0; 8]);
+    let data = [moov, moof, mdat].concat();
+    let tracks = mp4box::track_samples_from_reader(Cursor::new(data)).unwrap();
+
+    let got: Vec<_> = tracks[0]
+        .samples
+        .iter()
+        .map(|s| (s.is_sync, s.sync_source))
+        .collect();
+    assert_eq!(
+        got,
+        [
+            (true, mp4box::SyncSource::Sdtp),
+            (false, mp4box::SyncSource::Sdtp)
+        ]
+    );
+}
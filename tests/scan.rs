@@ -0,0 +1,131 @@
+mod common;
+
+use common::*;
+use mp4box::boxes::FourCC;
+use mp4box::scan_for;
+use std::io::Cursor;
+
+/// [ftyp] [moof [mfhd] [traf]] [mdat] [moof [mfhd]] [mdat]
+fn make_fragmented() -> Vec<u8> {
+    let mut data = boxed(b"ftyp", b"iso6\0\0\0\0");
+    for seq in 1u32..=2 {
+        let mut mfhd = vec![0u8; 4];
+        mfhd.extend_from_slice(&seq.to_be_bytes());
+        let mut moof_payload = boxed(b"mfhd", &mfhd);
+        if seq == 1 {
+            moof_payload.extend(boxed(b"traf", &[]));
+        }
+        data.extend(boxed(b"moof", &moof_payload));
+        data.extend(boxed(b"mdat", &[0xAB; 32]));
+    }
+    data
+}
+
+#[test]
+fn scan_finds_top_level_boxes() {
+    let data = make_fragmented();
+    let hits: Vec<_> = scan_for(Cursor::new(&data), FourCC(*b"moof")).collect();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].start, 16);
+    assert!(hits[1].start > hits[0].start);
+}
+
+#[test]
+fn scan_descends_into_containers() {
+    let data = make_fragmented();
+    let mfhd: Vec<_> = scan_for(Cursor::new(&data), FourCC(*b"mfhd")).collect();
+    assert_eq!(mfhd.len(), 2);
+    assert_eq!(mfhd[0].start, 16 + 8);
+
+    let traf: Vec<_> = scan_for(Cursor::new(&data), FourCC(*b"traf")).collect();
+    assert_eq!(traf.len(), 1);
+}
+
+#[test]
+fn scan_descends_into_both_meta_layouts() {
+    let hdlr = boxed(b"hdlr", &[0; 24]);
+    let ilst = boxed(b"ilst", &[]);
+    let mut iso = vec![0u8; 4];
+    iso.extend_from_slice(&hdlr);
+    iso.extend_from_slice(&ilst);
+    let quicktime = [hdlr.clone(), ilst.clone()].concat();
+    let data = [boxed(b"meta", &iso), boxed(b"meta", &quicktime)].concat();
+
+    let starts: Vec<u64> = scan_for(Cursor::new(data), FourCC(*b"ilst"))
+        .map(|h| h.start)
+        .collect();
+    assert_eq!(starts, vec![8 + 4 + 32, 8 + 4 + 32 + 8 + 8 + 32]);
+}
+
+#[test]
+fn scan_stops_on_truncated_input() {
+    let mut data = make_fragmented();
+    data.extend_from_slice(&[0, 0, 0]); // trailing garbage shorter than a header
+    let count = scan_for(Cursor::new(&data), FourCC(*b"mdat")).count();
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn scan_never_reads_mdat_payload() {
+    use mp4box::util::CountingReader;
+
+    let mut data = make_fragmented();
+    data.extend(boxed(b"mdat", &vec![0u8; 1 << 20]));
+    let mut scanner = scan_for(CountingReader::new(Cursor::new(&data)), FourCC(*b"mdat"));
+    assert_eq!(scanner.by_ref().count(), 3);
+
+    // Only headers (8 bytes each) were read: 3 mdat + 2 moof + 2 mfhd + 1 traf + ftyp.
+    assert_eq!(scanner.get_ref().bytes_read(), 9 * 8);
+}
+
+#[test]
+fn brute_force_scan_finds_boxes_outside_the_tree() {
+    use mp4box::scan_for_boxes;
+
+    // Interrupted recording: mdat header claims far more than was written
+    // and the moov written afterwards sits inside its declared range
+    let mut data = boxed(b"ftyp", b"isom\0\0\0\0");
+    let mdat_at = data.len() as u64;
+    data.extend_from_slice(&1_000_000u32.to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend(vec![0x11; 65_530]);
+    let moov_at = data.len() as u64;
+    let mvhd = boxed(b"mvhd", &[0; 100]);
+    data.extend(boxed(b"moov", &mvhd));
+
+    let found = scan_for_boxes(Cursor::new(&data)).unwrap();
+    let hits: Vec<_> = found
+        .iter()
+        .map(|c| (c.typ.to_string(), c.offset, c.complete))
+        .collect();
+    assert_eq!(
+        hits,
+        [
+            ("ftyp".to_string(), 0, true),
+            ("mdat".to_string(), mdat_at, false),
+            ("moov".to_string(), moov_at, true),
+            ("mvhd".to_string(), moov_at + 8, true),
+        ]
+    );
+    assert_eq!(found[2].size, 8 + 108);
+}
+
+#[test]
+fn brute_force_scan_reads_64_bit_sizes_and_skips_bad_ones() {
+    use mp4box::scan_for_boxes;
+
+    let mut data = Vec::new();
+    // Too small to be a box
+    data.extend_from_slice(&4u32.to_be_bytes());
+    data.extend_from_slice(b"moof");
+    data.extend_from_slice(&1u32.to_be_bytes());
+    data.extend_from_slice(b"mdat");
+    data.extend_from_slice(&24u64.to_be_bytes());
+    data.extend_from_slice(&[0; 8]);
+
+    let found = scan_for_boxes(Cursor::new(&data)).unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].offset, found[0].size), (8, 24));
+    assert_eq!(found[0].header_size, 16);
+    assert!(found[0].complete);
+}
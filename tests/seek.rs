@@ -0,0 +1,140 @@
+mod common;
+
+use common::*;
+use mp4box::seek::{seek_index, seek_index_from_tfra};
+use mp4box::{get_boxes, track_samples_from_reader};
+use std::io::Cursor;
+
+fn video_trak(stbl: Vec<u8>) -> Vec<u8> {
+    container(
+        b"trak",
+        &[
+            tkhd(1, 0, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 0, "und"),
+                    hdlr(b"vide", "Video"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    )
+}
+
+#[test]
+fn stss_sync_samples() {
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(4, 500)]),
+            stsc(&[(1, 4, 1)]),
+            stsz(&[10, 20, 30, 40]),
+            stco(&[0x1000]),
+            stss(&[1, 3]),
+        ],
+    );
+    let data = container(b"moov", &[video_trak(stbl)]);
+    let tracks = track_samples_from_reader(Cursor::new(data)).unwrap();
+
+    let points: Vec<_> = seek_index(&tracks[0])
+        .iter()
+        .map(|p| (p.sample_index, p.pts, p.seconds, p.offset, p.moof_offset))
+        .collect();
+    assert_eq!(
+        points,
+        [
+            (Some(0), 0, 0.0, 0x1000, None),
+            (Some(2), 1000, 1.0, 0x1000 + 30, None)
+        ]
+    );
+}
+
+/// trun whose first sample is flagged sync; trex marks the rest non-sync.
+fn sync_first_trun(data_offset: i32, sizes: &[u32]) -> Vec<u8> {
+    let mut p = (sizes.len() as u32).to_be_bytes().to_vec();
+    p.extend_from_slice(&data_offset.to_be_bytes());
+    p.extend_from_slice(&0x0200_0000u32.to_be_bytes());
+    for s in sizes {
+        p.extend_from_slice(&s.to_be_bytes());
+    }
+    full_box(b"trun", 0, 0x000205, &p)
+}
+
+fn moof(seq: u32, sizes: &[u32]) -> Vec<u8> {
+    let build = |data_offset: i32| {
+        let traf = container(
+            b"traf",
+            &[tfhd(1, None), sync_first_trun(data_offset, sizes)],
+        );
+        container(b"moof", &[mfhd(seq), traf])
+    };
+    let len = build(0).len() as i32;
+    build(len + 8)
+}
+
+fn tfra(entries: &[(u32, u32)]) -> Vec<u8> {
+    let mut p = 1u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&0u32.to_be_bytes()); // 1-byte traf/trun/sample numbers
+    p.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for (time, moof_offset) in entries {
+        p.extend_from_slice(&time.to_be_bytes());
+        p.extend_from_slice(&moof_offset.to_be_bytes());
+        p.extend_from_slice(&[1, 1, 1]);
+    }
+    full_box(b"tfra", 0, 0, &p)
+}
+
+#[test]
+fn fragment_sync_samples_and_tfra() {
+    let stbl = container(b"stbl", &[stts(&[]), stsc(&[]), stsz(&[]), stco(&[])]);
+    let mut trex = Vec::new();
+    for v in [1u32, 1, 100, 0, 0x0001_0000] {
+        trex.extend_from_slice(&v.to_be_bytes());
+    }
+    let moov = container(
+        b"moov",
+        &[
+            video_trak(stbl),
+            container(b"mvex", &[full_box(b"trex", 0, 0, &trex)]),
+        ],
+    );
+    let moof1 = moof(1, &[10, 20]);
+    let moof2 = moof(2, &[5, 6]);
+    let moof1_offset = moov.len() as u32;
+    let moof2_offset = moof1_offset + moof1.len() as u32 + 8 + 30;
+    let data = [
+        moov,
+        moof1,
+        boxed(b"mdat", &[0; 30]),
+        moof2,
+        boxed(b"mdat", &[0; 11]),
+        container(b"mfra", &[tfra(&[(0, moof1_offset), (200, moof2_offset)])]),
+    ]
+    .concat();
+
+    let tracks = track_samples_from_reader(Cursor::new(data.clone())).unwrap();
+    let from_flags = seek_index(&tracks[0]);
+    let summary: Vec<_> = from_flags
+        .iter()
+        .map(|p| (p.sample_index, p.pts, p.moof_offset))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (Some(0), 0, Some(moof1_offset as u64)),
+            (Some(2), 200, Some(moof2_offset as u64))
+        ]
+    );
+
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    let from_tfra = seek_index_from_tfra(&boxes, 1);
+    assert_eq!(from_tfra.len(), 2);
+    for (a, b) in from_tfra.iter().zip(&from_flags) {
+        assert_eq!((a.pts, a.offset, a.size), (b.pts, b.offset, b.size));
+        assert_eq!(a.sample_index, None);
+    }
+    assert_eq!(from_tfra[1].seconds, 0.2);
+    assert!(seek_index_from_tfra(&boxes, 2).is_empty());
+}
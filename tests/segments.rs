@@ -0,0 +1,225 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use mp4box::segments::{
+    ContainerProfile, Segment, SegmentKind, classify_segment, container_profile, segment_index,
+    segment_index_from_boxes,
+};
+use std::io::Cursor;
+
+/// Version 0 sidx; each reference is (references_sidx, size, duration) and
+/// starts with a type 1 SAP.
+fn sidx(first_offset: u32, refs: &[(bool, u32, u32)]) -> Vec<u8> {
+    let mut p = Vec::new();
+    for v in [1u32, 1000, 0, first_offset] {
+        p.extend_from_slice(&v.to_be_bytes());
+    }
+    p.extend_from_slice(&0u16.to_be_bytes());
+    p.extend_from_slice(&(refs.len() as u16).to_be_bytes());
+    for &(nested, size, duration) in refs {
+        p.extend_from_slice(&((nested as u32) << 31 | size).to_be_bytes());
+        p.extend_from_slice(&duration.to_be_bytes());
+        p.extend_from_slice(&0x9000_0000u32.to_be_bytes());
+    }
+    full_box(b"sidx", 0, 0, &p)
+}
+
+#[test]
+fn sidx_references_are_decoded() {
+    let data = sidx(0, &[(true, 300, 900), (false, 50, 1000)]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    match &boxes[0].structured_data {
+        Some(StructuredData::SegmentIndex(s)) => {
+            assert_eq!((s.reference_id, s.timescale), (1, 1000));
+            assert_eq!(s.references.len(), 2);
+            assert!(s.references[0].references_sidx);
+            assert_eq!(s.references[1].referenced_size, 50);
+            assert!(s.references[1].starts_with_sap);
+            assert_eq!(s.references[1].sap_type, 1);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(
+        boxes[0].decoded.as_deref(),
+        Some("timescale=1000 earliest_presentation_time=0 first_offset=0 references=2")
+    );
+}
+
+#[test]
+fn hierarchical_sidx_resolves_to_absolute_ranges() {
+    let seg1 = boxed(b"mdat", &[0u8; 92]);
+    let seg2 = boxed(b"mdat", &[0u8; 42]);
+    let child = sidx(0, &[(false, seg1.len() as u32, 900)]);
+    let free = boxed(b"free", &[]);
+    let root = sidx(
+        free.len() as u32,
+        &[
+            (true, (child.len() + seg1.len()) as u32, 900),
+            (false, seg2.len() as u32, 1000),
+        ],
+    );
+
+    let seg1_offset = (root.len() + free.len() + child.len()) as u64;
+    let seg2_offset = seg1_offset + seg1.len() as u64;
+    let data = [root, free, child, seg1, seg2].concat();
+    let path = temp_file(&data, "mp4box_segments_hierarchical.mp4");
+
+    let segments = segment_index(&path).unwrap();
+    assert_eq!(
+        segments,
+        [
+            Segment {
+                reference_id: 1,
+                offset: seg1_offset,
+                size: 100,
+                timescale: 1000,
+                start_time: 0,
+                end_time: 900,
+                starts_with_sap: true,
+                sap_type: 1,
+            },
+            Segment {
+                reference_id: 1,
+                offset: seg2_offset,
+                size: 50,
+                timescale: 1000,
+                start_time: 900,
+                end_time: 1900,
+                starts_with_sap: true,
+                sap_type: 1,
+            },
+        ]
+    );
+    assert_eq!(
+        segments[1].media_range(),
+        format!("{}-{}", seg2_offset, seg2_offset + 49)
+    );
+    assert_eq!(segments[1].end_seconds(), 1.9);
+}
+
+#[test]
+fn files_without_sidx_have_no_segments() {
+    let data = container(b"moov", &[mvhd(1000, 0)]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    assert!(segment_index_from_boxes(&boxes).is_empty());
+}
+
+#[test]
+fn segments_are_classified_by_top_level_boxes() {
+    let kind = |parts: &[Vec<u8>]| {
+        let data = parts.concat();
+        let len = data.len() as u64;
+        classify_segment(&get_boxes(&mut Cursor::new(data), len, false).unwrap())
+    };
+    let ftyp = ftyp(b"iso6", &[b"iso6", b"dash"]);
+    let moov = container(b"moov", &[mvhd(1000, 0)]);
+    let moof = container(b"moof", &[mfhd(1)]);
+    let mdat = boxed(b"mdat", &[0u8; 4]);
+    let styp = boxed(b"styp", b"msdh\0\0\0\0msdh");
+
+    assert_eq!(
+        kind(&[ftyp.clone(), moov.clone()]),
+        SegmentKind::Initialization
+    );
+    assert_eq!(
+        kind(&[styp.clone(), moof.clone(), mdat.clone()]),
+        SegmentKind::Media
+    );
+    assert_eq!(
+        kind(&[ftyp.clone(), moov.clone(), moof, mdat.clone()]),
+        SegmentKind::SelfInitializing
+    );
+    assert_eq!(kind(&[ftyp, moov, mdat.clone()]), SegmentKind::Progressive);
+    assert_eq!(kind(&[mdat]), SegmentKind::Unknown);
+    assert_eq!(kind(&[styp]), SegmentKind::Media);
+}
+
+#[test]
+fn delivery_profiles() {
+    let profile = |parts: &[Vec<u8>]| {
+        let data = parts.concat();
+        let len = data.len() as u64;
+        container_profile(&get_boxes(&mut Cursor::new(data), len, true).unwrap())
+    };
+    let trak = |sizes: &[u32]| {
+        let stbl = container(b"stbl", &[stsz(sizes)]);
+        container(
+            b"trak",
+            &[
+                tkhd(1, 0, 0, 0, 0),
+                container(b"mdia", &[container(b"minf", &[stbl])]),
+            ],
+        )
+    };
+    let mvex = container(b"mvex", &[trex(1, 1000, 0)]);
+    let plain = container(b"moov", &[mvhd(1000, 0), trak(&[10, 10])]);
+    let init = container(b"moov", &[mvhd(1000, 0), trak(&[]), mvex.clone()]);
+    let both = container(b"moov", &[mvhd(1000, 0), trak(&[10]), mvex]);
+    let moof = container(b"moof", &[mfhd(1)]);
+    let mdat = boxed(b"mdat", &[0u8; 20]);
+
+    let p = profile(&[plain, mdat.clone()]);
+    assert_eq!(p.profile, ContainerProfile::Progressive);
+    assert_eq!(
+        p.evidence,
+        ["moov at offset 0", "moov sample tables: 2 samples"]
+    );
+    assert_eq!(
+        profile(&[init.clone(), moof.clone(), mdat.clone()]).profile,
+        ContainerProfile::Fragmented
+    );
+    let p = profile(&[
+        init,
+        sidx(0, &[(false, 20, 1000)]),
+        moof.clone(),
+        mdat.clone(),
+    ]);
+    assert_eq!(p.profile, ContainerProfile::FragmentedIndexed);
+    assert!(p.evidence.contains(&"sidx boxes: 1".to_string()));
+    assert_eq!(
+        profile(&[both, moof, mdat.clone()]).profile,
+        ContainerProfile::Hybrid
+    );
+    assert_eq!(profile(&[mdat]).profile, ContainerProfile::Unknown);
+}
+
+#[test]
+fn shared_sidx_references_resolve_once() {
+    // Twelve zero-size references per sidx all point at the next one
+    let fan_out = [(true, 0, 1); 12];
+    let mut data = Vec::new();
+    for _ in 0..8 {
+        data.extend(sidx(0, &fan_out));
+    }
+    data.extend(sidx(0, &[(false, 10, 1)]));
+
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    let segments = segment_index_from_boxes(&boxes);
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].offset, len);
+}
+
+#[test]
+fn overflowing_sidx_offsets_are_dropped() {
+    // Version 1: 64-bit earliest_presentation_time and first_offset
+    let mut p = Vec::new();
+    p.extend_from_slice(&1u32.to_be_bytes());
+    p.extend_from_slice(&1000u32.to_be_bytes());
+    p.extend_from_slice(&u64::MAX.to_be_bytes());
+    p.extend_from_slice(&u64::MAX.to_be_bytes());
+    p.extend_from_slice(&[0, 0, 0, 1]);
+    p.extend_from_slice(&10u32.to_be_bytes());
+    p.extend_from_slice(&1000u32.to_be_bytes());
+    p.extend_from_slice(&0x9000_0000u32.to_be_bytes());
+    let data = full_box(b"sidx", 1, 0, &p);
+
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    assert!(segment_index_from_boxes(&boxes).is_empty());
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::*;
+use mp4box::{
+    ParseOptions, RandomAccess, Source, get_boxes, get_boxes_from, get_boxes_with_options,
+};
+use std::cell::Cell;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+fn movie() -> Vec<u8> {
+    [
+        ftyp(b"isom", &[b"isom"]),
+        boxed(b"mdat", &[0; 64]),
+        container(b"moov", &[mvhd(1000, 5000)]),
+    ]
+    .concat()
+}
+
+fn types(boxes: &[mp4box::Box]) -> Vec<&str> {
+    boxes.iter().map(|b| b.typ.as_str()).collect()
+}
+
+/// A backend that only answers ranged reads, as object storage does.
+struct Ranged {
+    data: Vec<u8>,
+    calls: Cell<usize>,
+}
+
+impl RandomAccess for Ranged {
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.data.len() as u64)
+    }
+
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> io::Result<usize> {
+        self.calls.set(self.calls.get() + 1);
+        self.data.as_slice().read_at(offset, buf)
+    }
+}
+
+/// `len` bytes at `offset` of `source`.
+fn read(source: &impl RandomAccess, offset: u64, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    let n = source.read_at(offset, &mut buf).unwrap();
+    buf.truncate(n);
+    buf
+}
+
+#[test]
+fn slices_read_within_bounds() {
+    let data = [1u8, 2, 3, 4];
+    let s = &data[..];
+    assert_eq!(read(&s, 1, 2), [2, 3]);
+    assert_eq!(read(&s, 3, 10), [4]);
+    assert!(read(&s, 9, 1).is_empty());
+    assert!(read(&s, u64::MAX, 1).is_empty());
+}
+
+#[test]
+fn parses_slices_vecs_and_custom_backends() {
+    let data = movie();
+    let options = ParseOptions::new();
+
+    let (boxes, _) = get_boxes_from(&data[..], &options).unwrap();
+    assert_eq!(types(&boxes), ["ftyp", "mdat", "moov"]);
+
+    let (from_vec, _) = get_boxes_from(&data, &options).unwrap();
+    assert_eq!(types(&from_vec), types(&boxes));
+
+    let ranged = Ranged {
+        data: data.clone(),
+        calls: Cell::new(0),
+    };
+    let (from_ranged, _) = get_boxes_from(&ranged, &options).unwrap();
+    assert_eq!(from_ranged[2].children.as_ref().unwrap()[0].typ, "mvhd");
+    assert!(ranged.calls.get() > 0);
+}
+
+#[test]
+fn small_reads_share_one_ranged_read() {
+    let data = movie();
+    let ranged = Ranged {
+        data: data.clone(),
+        calls: Cell::new(0),
+    };
+    let size = data.len() as u64;
+    let options = ParseOptions::new().buffer_limit(0);
+
+    let mut source = Source::new(&ranged).unwrap();
+    let (boxes, _) = get_boxes_with_options(&mut source, size, &options).unwrap();
+    assert_eq!(types(&boxes), ["ftyp", "mdat", "moov"]);
+    assert_eq!(ranged.calls.get(), 1);
+
+    ranged.calls.set(0);
+    let mut direct = Source::new(&ranged).unwrap().with_read_ahead(0);
+    let (direct_boxes, _) = get_boxes_with_options(&mut direct, size, &options).unwrap();
+    assert_eq!(types(&direct_boxes), types(&boxes));
+    assert!(ranged.calls.get() > 5, "{} calls", ranged.calls.get());
+}
+
+#[test]
+fn file_reads_leave_its_cursor_alone() {
+    let data = movie();
+    let path = temp_file(&data, "mp4box_source_cursor.mp4");
+    let mut file = std::fs::File::open(&path).unwrap();
+    file.seek(SeekFrom::Start(5)).unwrap();
+
+    let (boxes, _) = get_boxes_from(&file, &ParseOptions::new()).unwrap();
+    assert_eq!(types(&boxes), ["ftyp", "mdat", "moov"]);
+    assert_eq!(file.stream_position().unwrap(), 5);
+    assert_eq!(read(&file, 4, 4), b"ftyp");
+}
+
+#[test]
+fn sources_have_independent_cursors() {
+    let shared = Arc::new(movie());
+    let mut a = Source::new(shared.clone()).unwrap();
+    let mut b = Source::new(shared.clone()).unwrap();
+
+    a.seek(SeekFrom::End(-8)).unwrap();
+    let mut tail = [0; 8];
+    a.read_exact(&mut tail).unwrap();
+    assert_eq!(tail, shared[shared.len() - 8..]);
+
+    let size = b.len();
+    let boxes = get_boxes(&mut b, size, true).unwrap();
+    assert_eq!(types(&boxes), ["ftyp", "mdat", "moov"]);
+    assert!(
+        a.seek(SeekFrom::Current(-(shared.len() as i64) - 1))
+            .is_err()
+    );
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn memory_maps_parse() {
+    let data = movie();
+    let path = temp_file(&data, "mp4box_source_mmap.mp4");
+    let file = std::fs::File::open(&path).unwrap();
+    let map = unsafe { memmap2::Mmap::map(&file).unwrap() };
+    let (boxes, _) = get_boxes_from(&map, &ParseOptions::new()).unwrap();
+    assert_eq!(types(&boxes), ["ftyp", "mdat", "moov"]);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use std::io::Cursor;
+
+fn sv3d() -> Vec<u8> {
+    let mut prhd = Vec::new();
+    for deg in [90i32, -10, 0] {
+        prhd.extend_from_slice(&(deg << 16).to_be_bytes());
+    }
+    let mut equi = Vec::new();
+    for bound in [0u32, 0, 0x4000_0000, 0x4000_0000] {
+        equi.extend_from_slice(&bound.to_be_bytes());
+    }
+    container(
+        b"sv3d",
+        &[
+            full_box(b"svhd", 0, 0, b"Spherical Metadata Tool\0"),
+            container(
+                b"proj",
+                &[
+                    full_box(b"prhd", 0, 0, &prhd),
+                    full_box(b"equi", 0, 0, &equi),
+                ],
+            ),
+        ],
+    )
+}
+
+#[test]
+fn sv3d_hierarchy_is_decoded() {
+    let mut data = full_box(b"st3d", 0, 0, &[2]);
+    data.extend_from_slice(&sv3d());
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::StereoVideo(s)) => {
+            assert_eq!(s.stereo_mode, 2);
+            assert_eq!(s.stereo_mode_name, "left-right");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let sv3d = &boxes[1];
+    assert_eq!(sv3d.kind, "container");
+    let kids = sv3d.children.as_ref().unwrap();
+    match kids[0].structured_data.as_ref() {
+        Some(StructuredData::SphericalVideoHeader(h)) => {
+            assert_eq!(h.metadata_source, "Spherical Metadata Tool")
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+
+    let proj = kids[1].children.as_ref().unwrap();
+    match proj[0].structured_data.as_ref() {
+        Some(StructuredData::ProjectionHeader(p)) => {
+            assert_eq!(p.pose_yaw_degrees, 90.0);
+            assert_eq!(p.pose_pitch_degrees, -10.0);
+            assert_eq!(p.pose_roll_degrees, 0.0);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match proj[1].structured_data.as_ref() {
+        Some(StructuredData::EquirectangularProjection(e)) => {
+            assert_eq!(e.projection_bounds_left, 0x4000_0000);
+            assert_eq!(e.projection_bounds_top, 0);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn cbmp_layout() {
+    let mut p = 0u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&16u32.to_be_bytes());
+    let data = container(b"proj", &[full_box(b"cbmp", 0, 0, &p)]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    match boxes[0].children.as_ref().unwrap()[0]
+        .structured_data
+        .as_ref()
+    {
+        Some(StructuredData::CubemapProjection(c)) => {
+            assert_eq!(c.layout, 0);
+            assert_eq!(c.padding, 16);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,50 @@
+use mp4box::SampleInfo;
+use mp4box::stats::{HistogramBin, size_stats};
+
+fn samples(sizes: &[u32]) -> Vec<SampleInfo> {
+    sizes
+        .iter()
+        .enumerate()
+        .map(|(i, &size)| SampleInfo {
+            index: i as u32,
+            dts: 0,
+            pts: 0,
+            start_time: 0.0,
+            duration: 0,
+            rendered_offset: 0,
+            file_offset: 0,
+            size,
+            is_sync: true,
+            sync_source: mp4box::SyncSource::Default,
+            fragment_sources: None,
+        })
+        .collect()
+}
+
+#[test]
+fn size_distribution() {
+    let sizes: Vec<u32> = (1..=100).rev().collect();
+    let s = size_stats(&samples(&sizes), Some(4)).unwrap();
+    assert_eq!((s.count, s.total, s.min, s.max), (100, 5050, 1, 100));
+    assert_eq!((s.mean, s.median), (50.5, 50.5));
+    assert_eq!((s.p90, s.p95, s.p99), (90, 95, 99));
+    let bin = |start, end, count| HistogramBin { start, end, count };
+    assert_eq!(
+        s.histogram.unwrap(),
+        [
+            bin(1, 25, 25),
+            bin(26, 50, 25),
+            bin(51, 75, 25),
+            bin(76, 100, 25)
+        ]
+    );
+}
+
+#[test]
+fn narrow_ranges_and_empty_tracks() {
+    let s = size_stats(&samples(&[7, 7, 8]), Some(10)).unwrap();
+    assert_eq!(s.median, 7.0);
+    assert_eq!(s.p99, 8);
+    assert_eq!(s.histogram.unwrap().len(), 2);
+    assert!(size_stats(&samples(&[]), None).is_none());
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::*;
+use mp4box::stream::get_boxes_streaming;
+use mp4box::{ParseOptions, WarningKind, get_boxes_with_options};
+use std::io::{Cursor, Read};
+
+/// Reader that cannot seek, like stdin.
+struct Pipe(Cursor<Vec<u8>>);
+
+impl Read for Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        // Short reads, as pipes give
+        let n = buf.len().min(7);
+        self.0.read(&mut buf[..n])
+    }
+}
+
+fn movie() -> Vec<u8> {
+    let len = build(0).len() as u32;
+    // The only chunk starts at the mdat payload
+    build(len - 60)
+}
+
+fn build(chunk_offset: u32) -> Vec<u8> {
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(3, 100)]),
+            stsc(&[(1, 3, 1)]),
+            stsz(&[10, 20, 30]),
+            stco(&[chunk_offset]),
+        ],
+    );
+    let trak = container(
+        b"trak",
+        &[
+            tkhd(1, 0, 0, 0, 0),
+            container(
+                b"mdia",
+                &[
+                    mdhd(1000, 300, "und"),
+                    hdlr(b"vide", "Video"),
+                    container(b"minf", &[stbl]),
+                ],
+            ),
+        ],
+    );
+    [
+        ftyp(b"isom", &[b"isom"]),
+        boxed(b"free", &[0; 16]),
+        container(b"moov", &[mvhd(1000, 300), trak]),
+        boxed(b"mdat", &[0xaa; 60]),
+    ]
+    .concat()
+}
+
+/// (depth, type, offset, size, decoded) for every box, in tree order.
+fn flatten(boxes: &[mp4box::Box], depth: usize, out: &mut Vec<String>) {
+    for b in boxes {
+        out.push(format!(
+            "{depth} {} {} {} {:?} {:?}",
+            b.typ, b.offset, b.size, b.kind, b.decoded
+        ));
+        flatten(b.children.as_deref().unwrap_or_default(), depth + 1, out);
+    }
+}
+
+#[test]
+fn stream_matches_seeking_parse() {
+    let data = movie();
+    let options = ParseOptions::new().media_ranges(false);
+    let (seeking, _) =
+        get_boxes_with_options(&mut Cursor::new(&data), data.len() as u64, &options).unwrap();
+    let (streamed, warnings) = get_boxes_streaming(Pipe(Cursor::new(data)), &options).unwrap();
+    assert!(warnings.is_empty());
+
+    let (mut a, mut b) = (Vec::new(), Vec::new());
+    flatten(&seeking, 0, &mut a);
+    flatten(&streamed, 0, &mut b);
+    assert_eq!(a, b);
+    assert_eq!(streamed[3].typ, "mdat");
+    assert_eq!(streamed[3].payload_size, Some(60));
+}
+
+#[test]
+fn media_ranges_link_across_boxes() {
+    let data = movie();
+    let (boxes, _) = get_boxes_streaming(Pipe(Cursor::new(data)), &ParseOptions::new()).unwrap();
+    let mdat = &boxes[3];
+    let trak = &boxes[2].children.as_ref().unwrap()[1];
+    let ranges = trak.media_ranges.as_ref().unwrap();
+    assert_eq!(ranges.len(), 1);
+    assert_eq!(
+        (ranges[0].offset, ranges[0].size, ranges[0].mdat_offset),
+        (mdat.offset + 8, 60, Some(mdat.offset))
+    );
+}
+
+#[test]
+fn cut_stream() {
+    let mut data = movie();
+    data.truncate(data.len() - 10);
+
+    let strict = get_boxes_streaming(Pipe(Cursor::new(data.clone())), &ParseOptions::new());
+    assert!(strict.is_err());
+
+    let options = ParseOptions::new().lenient(true);
+    let (boxes, warnings) = get_boxes_streaming(Pipe(Cursor::new(data.clone())), &options).unwrap();
+    assert_eq!(boxes.len(), 4);
+    let mdat = &boxes[3];
+    assert_eq!(
+        warnings[0].kind,
+        WarningKind::Truncated {
+            declared_end: mdat.offset + 68,
+            available_end: data.len() as u64,
+        }
+    );
+}
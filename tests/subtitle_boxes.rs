@@ -0,0 +1,87 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::{StructuredData, SubtitleConfig};
+use std::io::Cursor;
+
+fn stsd_with(entry: Vec<u8>) -> Vec<u8> {
+    let mut p = 1u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&entry);
+    full_box(b"stsd", 0, 0, &p)
+}
+
+fn sample_entry(codec: &[u8; 4], fields: &[u8]) -> Vec<u8> {
+    let mut p = vec![0u8; 6];
+    p.extend_from_slice(&1u16.to_be_bytes());
+    p.extend_from_slice(fields);
+    boxed(codec, &p)
+}
+
+fn subtitle(stsd: Vec<u8>) -> SubtitleConfig {
+    let len = stsd.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(stsd), len, true).unwrap();
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::SampleDescription(d)) => d.entries[0].subtitle.clone().unwrap(),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn tx3g_entry() {
+    let mut f = 0x2000_0000u32.to_be_bytes().to_vec(); // display flags
+    f.extend_from_slice(&[1, 0xFF]); // centered, bottom
+    f.extend_from_slice(&[0, 0, 0, 0x80]); // background
+    for v in [0i16, 0, 60, 400] {
+        f.extend_from_slice(&v.to_be_bytes());
+    }
+    f.extend_from_slice(&[0, 0, 0, 0, 0, 1, 2, 18, 255, 255, 255, 255]); // style
+    let mut ftab = 1u16.to_be_bytes().to_vec();
+    ftab.extend_from_slice(&[0, 1, 5]);
+    ftab.extend_from_slice(b"Serif");
+    f.extend_from_slice(&boxed(b"ftab", &ftab));
+
+    match subtitle(stsd_with(sample_entry(b"tx3g", &f))) {
+        SubtitleConfig::TimedText(t) => {
+            assert_eq!(t.display_flags, 0x2000_0000);
+            assert_eq!(t.horizontal_justification, 1);
+            assert_eq!(t.vertical_justification, -1);
+            assert_eq!(t.background_color, [0, 0, 0, 0x80]);
+            assert_eq!(t.default_text_box.bottom, 60);
+            assert_eq!(t.default_text_box.right, 400);
+            assert_eq!(t.default_style.font_id, 1);
+            assert_eq!(t.default_style.face_style_flags, 2);
+            assert_eq!(t.default_style.font_size, 18);
+            assert_eq!(t.fonts[0].name, "Serif");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn wvtt_entry() {
+    let f = [boxed(b"vttC", b"WEBVTT"), boxed(b"vlab", b"English")].concat();
+    match subtitle(stsd_with(sample_entry(b"wvtt", &f))) {
+        SubtitleConfig::WebVtt(w) => {
+            assert_eq!(w.config.as_deref(), Some("WEBVTT"));
+            assert_eq!(w.label.as_deref(), Some("English"));
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn stpp_entry() {
+    let f = b"http://www.w3.org/ns/ttml http://www.w3.org/ns/ttml#styling\0\0image/png\0";
+    match subtitle(stsd_with(sample_entry(b"stpp", f))) {
+        SubtitleConfig::Xml(x) => {
+            assert_eq!(
+                x.namespace,
+                "http://www.w3.org/ns/ttml http://www.w3.org/ns/ttml#styling"
+            );
+            assert_eq!(x.schema_location, "");
+            assert_eq!(x.auxiliary_mime_types, "image/png");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
@@ -0,0 +1,36 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use std::io::Cursor;
+
+fn decoded(data: Vec<u8>) -> Vec<String> {
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+    boxes[0]
+        .children
+        .iter()
+        .flatten()
+        .map(|b| b.decoded.clone().unwrap_or_default())
+        .collect()
+}
+
+#[test]
+fn structured_boxes_render_key_value_summaries() {
+    let mdia = container(
+        b"mdia",
+        &[
+            mdhd(48000, 96000, "eng"),
+            hdlr(b"soun", "Sound Handler"),
+            tkhd(2, 2000, 1, 0, 0),
+        ],
+    );
+    assert_eq!(
+        decoded(mdia),
+        [
+            "timescale=48000 duration=96000 language=eng",
+            "handler=soun name=\"Sound Handler\"",
+            "track_id=2 duration=2000 alternate_group=1 width=0 height=0",
+        ]
+    );
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::registry::StructuredData;
+use mp4box::util::mp4_time_to_rfc3339;
+use std::io::Cursor;
+
+#[test]
+fn mp4_epoch_conversion() {
+    assert_eq!(mp4_time_to_rfc3339(0), None);
+    assert_eq!(
+        mp4_time_to_rfc3339(1).as_deref(),
+        Some("1904-01-01T00:00:01Z")
+    );
+    assert_eq!(
+        mp4_time_to_rfc3339(2_082_844_800).as_deref(),
+        Some("1970-01-01T00:00:00Z")
+    );
+    // 2023-06-10T12:34:56Z is 1686400496 in Unix time
+    assert_eq!(
+        mp4_time_to_rfc3339(2_082_844_800 + 1_686_400_496).as_deref(),
+        Some("2023-06-10T12:34:56Z")
+    );
+    // Leap day
+    assert_eq!(
+        mp4_time_to_rfc3339(2_082_844_800 + 1_709_164_800).as_deref(),
+        Some("2024-02-29T00:00:00Z")
+    );
+}
+
+#[test]
+fn mvhd_version_1_times_are_decoded() {
+    let created: u64 = 2_082_844_800 + 1_686_400_496;
+    let mut p = created.to_be_bytes().to_vec();
+    p.extend_from_slice(&0u64.to_be_bytes());
+    p.extend_from_slice(&90_000u32.to_be_bytes());
+    p.extend_from_slice(&(1u64 << 33).to_be_bytes());
+    p.extend_from_slice(&[0; 80]);
+    let data = container(b"moov", &[full_box(b"mvhd", 1, 0, &p)]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    let mvhd = &boxes[0].children.as_ref().unwrap()[0];
+    match &mvhd.structured_data {
+        Some(StructuredData::MovieHeader(m)) => {
+            assert_eq!(m.timescale, 90_000);
+            assert_eq!(m.duration, 1 << 33);
+            assert_eq!(m.creation_date.as_deref(), Some("2023-06-10T12:34:56Z"));
+            assert_eq!(m.modification_date, None);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(
+        mvhd.decoded.as_deref(),
+        Some(
+            "timescale=90000 duration=8589934592 rate=0 volume=0 next_track_id=0 \
+             created=2023-06-10T12:34:56Z"
+        )
+    );
+}
+
+#[test]
+fn mvhd_rate_volume_and_next_track_id() {
+    let data = container(b"moov", &[mvhd(600, 1200)]);
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    match &boxes[0].children.as_ref().unwrap()[0].structured_data {
+        Some(StructuredData::MovieHeader(m)) => {
+            assert_eq!(m.rate, 1.0);
+            assert_eq!(m.volume, 1.0);
+            assert_eq!(m.next_track_id, 3);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
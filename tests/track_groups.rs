@@ -0,0 +1,55 @@
+mod common;
+
+use common::*;
+use mp4box::{get_boxes, track_groups};
+use std::io::Cursor;
+
+fn trak(id: u32, group: u16, handler: &[u8; 4], lang: &str, kinds: &[(&str, &str)]) -> Vec<u8> {
+    let mut children = vec![
+        tkhd(id, 1000, group, 0, 0),
+        container(
+            b"mdia",
+            &[mdhd(48000, 48000, lang), hdlr(handler, "handler")],
+        ),
+    ];
+    if !kinds.is_empty() {
+        let ks: Vec<_> = kinds.iter().map(|(s, v)| kind(s, v)).collect();
+        children.push(container(b"udta", &ks));
+    }
+    container(b"trak", &children)
+}
+
+#[test]
+fn groups_alternate_audio_tracks() {
+    let role = "urn:mpeg:dash:role:2011";
+    let moov = container(
+        b"moov",
+        &[
+            mvhd(1000, 1000),
+            trak(1, 0, b"vide", "und", &[]),
+            trak(2, 1, b"soun", "eng", &[(role, "main")]),
+            trak(3, 1, b"soun", "fra", &[(role, "dub")]),
+            trak(4, 1, b"soun", "eng", &[(role, "commentary")]),
+            trak(5, 2, b"subt", "eng", &[]),
+        ],
+    );
+    let mut data = ftyp(b"isom", &[b"isom"]);
+    data.extend(moov);
+
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, true).unwrap();
+    let report = track_groups(&boxes);
+
+    assert_eq!(report.complementary.len(), 1);
+    assert_eq!(report.complementary[0].track_id, 1);
+
+    assert_eq!(report.alternates.len(), 2);
+    let audio = &report.alternates[0];
+    assert_eq!(audio.alternate_group, 1);
+    let ids: Vec<_> = audio.tracks.iter().map(|t| t.track_id).collect();
+    assert_eq!(ids, vec![2, 3, 4]);
+    assert_eq!(audio.tracks[1].language.as_deref(), Some("fra"));
+    assert_eq!(audio.tracks[2].roles[0].value, "commentary");
+    assert_eq!(audio.tracks[0].handler_type.as_deref(), Some("soun"));
+
+    assert_eq!(report.alternates[1].tracks[0].track_id, 5);
+}
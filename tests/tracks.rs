@@ -0,0 +1,64 @@
+mod common;
+
+use common::*;
+use mp4box::boxes::FourCC;
+use mp4box::{TrackType, get_boxes, tracks};
+use std::io::Cursor;
+
+fn trak(id: u32, handler: &[u8; 4]) -> Vec<u8> {
+    let stbl = container(
+        b"stbl",
+        &[
+            stts(&[(3, 1000)]),
+            stsc(&[(1, 3, 1)]),
+            stsz(&[10, 20, 30]),
+            stco(&[0x100]),
+        ],
+    );
+    let mdia = container(
+        b"mdia",
+        &[
+            mdhd(1000, 3000, "und"),
+            hdlr(handler, ""),
+            container(b"minf", &[stbl]),
+        ],
+    );
+    container(b"trak", &[tkhd(id, 3000, 0, 0, 0), mdia])
+}
+
+#[test]
+fn tracks_expose_handler_type_and_tables() {
+    let data = container(
+        b"moov",
+        &[
+            mvhd(1000, 3000),
+            trak(1, b"vide"),
+            trak(2, b"tmcd"),
+            trak(3, b"hint"),
+            trak(4, b"ABCD"),
+        ],
+    );
+    let len = data.len() as u64;
+    let boxes = get_boxes(&mut Cursor::new(data), len, true).unwrap();
+
+    let tracks = tracks(&boxes);
+    let types: Vec<_> = tracks.iter().map(|t| t.track_type()).collect();
+    assert_eq!(
+        types,
+        [
+            Some(TrackType::Video),
+            Some(TrackType::Timecode),
+            Some(TrackType::Hint),
+            Some(TrackType::Other(FourCC(*b"ABCD"))),
+        ]
+    );
+    assert_eq!(TrackType::Other(FourCC(*b"ABCD")).to_string(), "ABCD");
+
+    let t = &tracks[0];
+    assert_eq!(t.track_id(), Some(1));
+    assert_eq!(t.mdhd().map(|m| m.timescale), Some(1000));
+    assert_eq!(t.stsz().map(|s| s.sample_count), Some(3));
+    assert_eq!(t.stco().map(|s| s.chunk_offsets.clone()), Some(vec![0x100]));
+    assert!(t.co64().is_none());
+    assert!(t.elst().is_none());
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::*;
+use mp4box::get_boxes;
+use mp4box::validate::{Strictness, StructureIssueKind, check_structure};
+use std::io::Cursor;
+
+#[test]
+fn issues_by_strictness() {
+    let stbl = container(
+        b"stbl",
+        &[
+            full_box(b"ctts", 2, 0, &0u32.to_be_bytes()),
+            boxed(b"abcd", &[]),
+            boxed(b"free", &[]),
+        ],
+    );
+    let moov = container(
+        b"moov",
+        &[
+            container(
+                b"trak",
+                &[container(b"mdia", &[container(b"minf", &[stbl])])],
+            ),
+            container(b"udta", &[boxed(b"xyz1", &[])]),
+        ],
+    );
+    let traf = container(
+        b"traf",
+        &[full_box(b"trun", 0, 0x000003, &[0, 0, 0, 0, 0, 0, 0, 0])],
+    );
+    let moof = container(b"moof", &[traf]);
+    let data = [moov, moof].concat();
+    let boxes = get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap();
+
+    let kinds = |s| {
+        check_structure(&boxes, s)
+            .into_iter()
+            .map(|i| (i.path, i.kind))
+            .collect::<Vec<_>>()
+    };
+    let ctts = (
+        "moov>trak>mdia>minf>stbl>ctts".to_string(),
+        StructureIssueKind::UndefinedVersion {
+            version: 2,
+            max_version: 1,
+        },
+    );
+    let abcd = (
+        "moov>trak>mdia>minf>stbl>abcd".to_string(),
+        StructureIssueKind::UnexpectedChild {
+            parent: "stbl".to_string(),
+        },
+    );
+    let trun = (
+        "moof>traf>trun".to_string(),
+        StructureIssueKind::ReservedFlags {
+            flags: 3,
+            defined: 0xF05,
+        },
+    );
+
+    assert_eq!(kinds(Strictness::Lenient), std::slice::from_ref(&ctts));
+    assert_eq!(kinds(Strictness::Standard), [ctts.clone(), trun.clone()]);
+    assert_eq!(kinds(Strictness::Strict), [ctts, abcd, trun]);
+}
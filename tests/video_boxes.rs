@@ -0,0 +1,235 @@
+mod common;
+
+use common::*;
+use mp4box::registry::StructuredData;
+
+#[test]
+fn uncompressed_rgba_config() {
+    let mut cmpd = 5u32.to_be_bytes().to_vec();
+    for t in [4u16, 5, 6, 7] {
+        cmpd.extend_from_slice(&t.to_be_bytes());
+    }
+    cmpd.extend_from_slice(&0x8001u16.to_be_bytes());
+    cmpd.extend_from_slice(b"urn:example:thermal\0");
+
+    let mut uncc = b"rgba".to_vec();
+    uncc.extend_from_slice(&4u32.to_be_bytes());
+    for i in 0..4u16 {
+        uncc.extend_from_slice(&i.to_be_bytes());
+        uncc.extend_from_slice(&[7, 0, 0]); // 8 bit unsigned, no alignment
+    }
+    uncc.extend_from_slice(&[0, 1, 0, 0x80]); // 4:4:4, pixel interleave, LE components
+    for v in [0u32, 0, 0, 1, 0] {
+        uncc.extend_from_slice(&v.to_be_bytes());
+    }
+
+    let boxes = decode([boxed(b"cmpd", &cmpd), full_box(b"uncC", 0, 0, &uncc)].concat());
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::ComponentDefinition(c)) => {
+            let names: Vec<_> = c
+                .components
+                .iter()
+                .map(|c| c.component_type_name.as_str())
+                .collect();
+            assert_eq!(names, ["R", "G", "B", "alpha", "user-defined"]);
+            assert_eq!(
+                c.components[4].component_type_uri.as_deref(),
+                Some("urn:example:thermal")
+            );
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match boxes[1].structured_data.as_ref() {
+        Some(StructuredData::UncompressedFrameConfig(u)) => {
+            assert_eq!(u.profile, "rgba");
+            assert_eq!(u.components.len(), 4);
+            assert_eq!(u.components[3].component_index, 3);
+            assert_eq!(u.components[0].bit_depth, 8);
+            assert_eq!(u.sampling_type_name, "4:4:4");
+            assert_eq!(u.interleave_type_name, "pixel");
+            assert!(u.components_little_endian);
+            assert!(!u.block_pad_lsb);
+            assert_eq!(u.num_tile_cols, 2);
+            assert_eq!(u.num_tile_rows, 1);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn uncc_version_1_is_profile_only() {
+    let boxes = decode(full_box(b"uncC", 1, 0, b"yuv2"));
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::UncompressedFrameConfig(u)) => {
+            assert_eq!(u.version, 1);
+            assert_eq!(u.profile, "yuv2");
+            assert!(u.components.is_empty());
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn sample_entry_children_are_parsed() {
+    let mut avc1 = vec![0u8; 6];
+    avc1.extend_from_slice(&1u16.to_be_bytes());
+    avc1.extend_from_slice(&[0; 16]);
+    avc1.extend_from_slice(&1920u16.to_be_bytes());
+    avc1.extend_from_slice(&1080u16.to_be_bytes());
+    avc1.extend_from_slice(&[0; 50]);
+    avc1.extend_from_slice(&boxed(b"avcC", &[1, 0x64, 0, 0x28, 0xFF, 0xE0, 0]));
+    avc1.extend_from_slice(&boxed(b"pasp", &[0, 0, 0, 1, 0, 0, 0, 1]));
+
+    let mut mp4a = vec![0u8; 6];
+    mp4a.extend_from_slice(&1u16.to_be_bytes());
+    mp4a.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 16, 0, 0, 0, 0]);
+    mp4a.extend_from_slice(&(48000u32 << 16).to_be_bytes());
+    mp4a.extend_from_slice(&full_box(b"esds", 0, 0, &[3, 0]));
+
+    let mut p = 2u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&boxed(b"avc1", &avc1));
+    p.extend_from_slice(&boxed(b"mp4a", &mp4a));
+    let boxes = decode(full_box(b"stsd", 0, 0, &p));
+
+    let stsd = &boxes[0];
+    assert!(matches!(
+        stsd.structured_data,
+        Some(StructuredData::SampleDescription(_))
+    ));
+    let entries = stsd.children.as_ref().unwrap();
+    let kinds: Vec<(&str, &str)> = entries
+        .iter()
+        .map(|e| (e.typ.as_str(), e.kind.as_str()))
+        .collect();
+    assert_eq!(kinds, [("avc1", "container"), ("mp4a", "container")]);
+
+    let avc1_kids: Vec<&str> = entries[0]
+        .children
+        .iter()
+        .flatten()
+        .map(|c| c.typ.as_str())
+        .collect();
+    assert_eq!(avc1_kids, ["avcC", "pasp"]);
+    assert_eq!(entries[0].children.as_ref().unwrap()[0].offset, 16 + 8 + 78);
+
+    let esds = &entries[1].children.as_ref().unwrap()[0];
+    assert_eq!(
+        (esds.typ.as_str(), esds.offset),
+        ("esds", 16 + 117 + 8 + 28)
+    );
+}
+
+#[test]
+fn tkhd_matrix_orientation() {
+    use mp4box::registry::Orientation;
+
+    let one = 0x0001_0000;
+    let w = 0x4000_0000;
+    let cases = [
+        (
+            [one, 0, 0, 0, one, 0, 0, 0, w],
+            Orientation::Rotation { degrees: 0 },
+        ),
+        (
+            [0, one, 0, -one, 0, 0, 1080 << 16, 0, w],
+            Orientation::Rotation { degrees: 90 },
+        ),
+        (
+            [-one, 0, 0, 0, -one, 0, 0, 0, w],
+            Orientation::Rotation { degrees: 180 },
+        ),
+        (
+            [0, -one, 0, one, 0, 0, 0, 0, w],
+            Orientation::Rotation { degrees: 270 },
+        ),
+        (
+            [-one, 0, 0, 0, one, 0, 0, 0, w],
+            Orientation::FlipHorizontal,
+        ),
+        ([one, 0, 0, 0, -one, 0, 0, 0, w], Orientation::FlipVertical),
+        ([0, one, 0, one, 0, 0, 0, 0, w], Orientation::Other),
+    ];
+    for (m, expected) in cases {
+        let boxes = decode(tkhd_with_matrix(1, 100, 0, 1920, 1080, &matrix(m)));
+        match &boxes[0].structured_data {
+            Some(StructuredData::TrackHeader(t)) => {
+                assert_eq!(t.matrix, m);
+                assert_eq!(t.orientation(), expected);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn frame_rate_from_stts() {
+    let rate =
+        |entries: &[(u32, u32)], timescale: u32| match &decode(stts(entries))[0].structured_data {
+            Some(StructuredData::DecodingTimeToSample(s)) => s.frame_rate(timescale),
+            other => panic!("unexpected {:?}", other),
+        };
+
+    // 29.97 fps with a short final frame: still constant
+    let fr = rate(&[(299, 1001), (1, 500)], 30000).unwrap();
+    assert!((fr.nominal - 29.97).abs() < 0.001);
+    assert!(!fr.variable);
+
+    // Mixed 24 and 30 fps sections
+    let fr = rate(&[(240, 3750), (100, 3000)], 90000).unwrap();
+    assert_eq!(fr.nominal, 24.0);
+    assert_eq!(
+        fr.average,
+        340.0 * 90000.0 / (240.0 * 3750.0 + 100.0 * 3000.0)
+    );
+    assert!(fr.variable);
+
+    assert!(rate(&[], 90000).is_none());
+}
+
+#[test]
+fn display_dimensions_combine_clap_pasp_and_rotation() {
+    use mp4box::display::display_dimensions;
+
+    let clap: Vec<u8> = [1440u32, 1, 1080, 1, 0, 1, 0, 1]
+        .iter()
+        .flat_map(|v| v.to_be_bytes())
+        .collect();
+    let mut avc1 = vec![0u8; 6];
+    avc1.extend_from_slice(&1u16.to_be_bytes());
+    avc1.extend_from_slice(&[0; 16]);
+    avc1.extend_from_slice(&1440u16.to_be_bytes());
+    avc1.extend_from_slice(&1088u16.to_be_bytes());
+    avc1.extend_from_slice(&[0; 50]);
+    avc1.extend_from_slice(&boxed(b"clap", &clap));
+    avc1.extend_from_slice(&boxed(b"pasp", &[0, 0, 0, 4, 0, 0, 0, 3]));
+    let mut p = 1u32.to_be_bytes().to_vec();
+    p.extend_from_slice(&boxed(b"avc1", &avc1));
+    let stbl = container(b"stbl", &[full_box(b"stsd", 0, 0, &p)]);
+    let mdia = container(
+        b"mdia",
+        &[
+            mdhd(90000, 0, "und"),
+            hdlr(b"vide", "Video"),
+            container(b"minf", &[stbl]),
+        ],
+    );
+    let one = 0x0001_0000;
+    let rotate_90 = matrix([0, one, 0, -one, 0, 0, 1080 << 16, 0, 0x4000_0000]);
+    let trak = container(
+        b"trak",
+        &[tkhd_with_matrix(1, 0, 0, 1080, 1920, &rotate_90), mdia],
+    );
+    let boxes = decode(container(b"moov", &[mvhd(90000, 0), trak]));
+
+    let dims = display_dimensions(&boxes);
+    assert_eq!(dims.len(), 1);
+    let d = &dims[0];
+    assert_eq!((d.coded_width, d.coded_height), (1440, 1088));
+    assert_eq!(
+        (d.clean_width, d.clean_height),
+        (Some(1440.0), Some(1080.0))
+    );
+    assert_eq!(d.pixel_aspect, (4, 3));
+    // 1440x1080 clean aperture stretched 4:3 to 1920x1080, then rotated
+    assert_eq!((d.width, d.height), (1080, 1920));
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use common::*;
+use mp4box::walk::{Children, Visitor, walk};
+use mp4box::{Box, get_boxes};
+use std::io::Cursor;
+use std::ops::ControlFlow;
+
+fn tree() -> Vec<Box> {
+    let trak = container(b"trak", &[tkhd(1, 1000, 0, 0, 0)]);
+    let data = [
+        ftyp(b"isom", &[b"isom"]),
+        container(b"moov", &[mvhd(1000, 1000), trak]),
+        boxed(b"mdat", &[0; 8]),
+    ]
+    .concat();
+    get_boxes(&mut Cursor::new(&data), data.len() as u64, false).unwrap()
+}
+
+/// Records pre/post calls with the path of ancestors.
+#[derive(Default)]
+struct Trace {
+    lines: Vec<String>,
+    skip: Option<&'static str>,
+}
+
+impl<'a> Visitor<'a> for Trace {
+    type Break = ();
+
+    fn pre(&mut self, b: &'a Box, ancestors: &[&'a Box]) -> ControlFlow<(), Children> {
+        let path: Vec<&str> = ancestors.iter().map(|a| a.typ.as_str()).collect();
+        self.lines
+            .push(format!("pre {} in [{}]", b.typ, path.join(".")));
+        match self.skip == Some(b.typ.as_str()) {
+            true => ControlFlow::Continue(Children::Skip),
+            false => ControlFlow::Continue(Children::Visit),
+        }
+    }
+
+    fn post(&mut self, b: &'a Box, _ancestors: &[&'a Box]) -> ControlFlow<()> {
+        self.lines.push(format!("post {}", b.typ));
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn pre_and_post_hooks_run_depth_first() {
+    let boxes = tree();
+    let mut trace = Trace::default();
+    assert!(walk(&boxes, &mut trace).is_continue());
+    assert_eq!(
+        trace.lines,
+        [
+            "pre ftyp in []",
+            "post ftyp",
+            "pre moov in []",
+            "pre mvhd in [moov]",
+            "post mvhd",
+            "pre trak in [moov]",
+            "pre tkhd in [moov.trak]",
+            "post tkhd",
+            "post trak",
+            "post moov",
+            "pre mdat in []",
+            "post mdat",
+        ]
+    );
+}
+
+#[test]
+fn skipped_children_still_get_post() {
+    let boxes = tree();
+    let mut trace = Trace {
+        skip: Some("moov"),
+        ..Default::default()
+    };
+    let _ = walk(&boxes, &mut trace);
+    assert_eq!(
+        trace.lines[2..5],
+        ["pre moov in []", "post moov", "pre mdat in []"]
+    );
+}
+
+/// First box of type `typ` and its depth.
+fn find<'a>(boxes: &'a [Box], typ: &str, visited: &mut usize) -> Option<(&'a Box, usize)> {
+    walk(boxes, &mut |b: &'a Box, ancestors: &[&'a Box]| {
+        *visited += 1;
+        match b.typ == typ {
+            true => ControlFlow::Break((b, ancestors.len())),
+            false => ControlFlow::Continue(Children::Visit),
+        }
+    })
+    .break_value()
+}
+
+#[test]
+fn closures_break_with_a_reference_into_the_tree() {
+    let boxes = tree();
+    let mut visited = 0;
+    let (tkhd, depth) = find(&boxes, "tkhd", &mut visited).unwrap();
+    assert_eq!(tkhd.typ, "tkhd");
+    assert_eq!(depth, 2);
+    // ftyp, moov, mvhd, trak, tkhd; mdat is never reached
+    assert_eq!(visited, 5);
+    assert!(find(&boxes, "stsd", &mut visited).is_none());
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::*;
+use mp4box::registry::StructuredData;
+
+#[test]
+fn xml_box_utf8_and_utf16() {
+    let utf16: Vec<u8> = [0xFF, 0xFE]
+        .into_iter()
+        .chain("<tt/>".encode_utf16().flat_map(|u| u.to_le_bytes()))
+        .collect();
+    let boxes = decode(
+        [
+            full_box(b"xml ", 0, 0, b"<MPD type=\"dynamic\"/>\0"),
+            full_box(b"xml ", 0, 0, &utf16),
+        ]
+        .concat(),
+    );
+
+    match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::Xml(x)) => {
+            assert_eq!(x.encoding, "utf-8");
+            assert_eq!(x.text, "<MPD type=\"dynamic\"/>");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    match boxes[1].structured_data.as_ref() {
+        Some(StructuredData::Xml(x)) => {
+            assert_eq!(x.encoding, "utf-16");
+            assert_eq!(x.text, "<tt/>");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn bxml_reports_its_size() {
+    let boxes = decode(full_box(b"bxml", 0, 0, &[0x03, 0x01, 0x6A, 0x00, 0x45]));
+    assert_eq!(boxes[0].full_name, "Binary XML Box");
+    assert_eq!(boxes[0].decoded.as_deref(), Some("5 bytes"));
+}
+
+#[test]
+fn xmp_uuid_box() {
+    let packet = r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about="" xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmp:CreatorTool="Adobe Premiere Pro 2024 (Windows)"
+    xmp:CreateDate="2024-03-01T10:00:00Z">
+   <dc:format>video/mp4</dc:format>
+   <!-- a comment -->
+   <dc:title>
+    <rdf:Alt>
+     <rdf:li xml:lang="x-default">Tom &amp; Jerry</rdf:li>
+    </rdf:Alt>
+   </dc:title>
+   <dc:subject><rdf:Bag><rdf:li>cats</rdf:li><rdf:li>mice</rdf:li></rdf:Bag></dc:subject>
+   <xmpMM:DerivedFrom rdf:resource="urn:uuid:1234"/>
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="w"?>"#;
+
+    let mut payload = mp4box::extract::XMP_UUID.to_vec();
+    payload.extend_from_slice(packet.as_bytes());
+    let boxes = decode(boxed(b"uuid", &payload));
+
+    let xmp = match boxes[0].structured_data.as_ref() {
+        Some(StructuredData::Xmp(x)) => x,
+        other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(xmp.packet, packet);
+
+    let props = xmp.properties();
+    assert_eq!(
+        props["xmp:CreatorTool"],
+        "Adobe Premiere Pro 2024 (Windows)"
+    );
+    assert_eq!(props["xmp:CreateDate"], "2024-03-01T10:00:00Z");
+    assert_eq!(props["dc:format"], "video/mp4");
+    assert_eq!(props["dc:title"], "Tom & Jerry");
+    assert_eq!(props["dc:subject"], "cats; mice");
+    assert_eq!(props["xmpMM:DerivedFrom"], "urn:uuid:1234");
+    assert!(!props.contains_key("rdf:about"));
+    assert!(!props.contains_key("xmlns:xmp"));
+}